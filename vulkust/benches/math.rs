@@ -0,0 +1,113 @@
+#[macro_use]
+extern crate criterion;
+extern crate vulkust;
+
+use criterion::{black_box, Criterion};
+use vulkust::cgmath::{self, Rad, SquareMatrix};
+use vulkust::math::matrix::{Affine3, Mat4};
+
+// The generic scalar Mat4x4 that the SIMD types replaced, specialized for f32.
+// Its multiply loops over the elements and its inverse is the cofactor
+// expansion.
+mod scalar {
+    #[derive(Clone, Copy)]
+    pub struct Mat4x4 {
+        pub data: [[f32; 4]; 4],
+    }
+
+    impl Mat4x4 {
+        pub fn mul(&self, o: &Self) -> Self {
+            let mut data = [[0.0; 4]; 4];
+            for i in 0..4 {
+                for j in 0..4 {
+                    for k in 0..4 {
+                        data[j][i] += o.data[j][k] * self.data[k][i];
+                    }
+                }
+            }
+            return Self { data };
+        }
+
+        fn minor(&self, col: usize, row: usize) -> f32 {
+            let others = |i: usize| match i {
+                0 => [1, 2, 3],
+                1 => [0, 2, 3],
+                2 => [0, 1, 3],
+                _ => [0, 1, 2],
+            };
+            let (cols, rows) = (others(col), others(row));
+            let e = |c: usize, r: usize| self.data[cols[c]][rows[r]];
+            return e(0, 0) * (e(1, 1) * e(2, 2) - e(2, 1) * e(1, 2))
+                - e(1, 0) * (e(0, 1) * e(2, 2) - e(2, 1) * e(0, 2))
+                + e(2, 0) * (e(0, 1) * e(1, 2) - e(1, 1) * e(0, 2));
+        }
+
+        pub fn inv(&self) -> Self {
+            let mut cofactors = [[0.0; 4]; 4];
+            for c in 0..4 {
+                for r in 0..4 {
+                    let sign = if (c + r) % 2 == 0 { 1.0 } else { -1.0 };
+                    cofactors[c][r] = sign * self.minor(c, r);
+                }
+            }
+            let mut det = 0.0;
+            for r in 0..4 {
+                det += self.data[0][r] * cofactors[0][r];
+            }
+            let mut data = [[0.0; 4]; 4];
+            for c in 0..4 {
+                for r in 0..4 {
+                    data[c][r] = cofactors[r][c] / det;
+                }
+            }
+            return Self { data };
+        }
+    }
+}
+
+fn create_affine_matrix() -> cgmath::Matrix4<f32> {
+    return cgmath::Matrix4::from_translation(cgmath::Vector3::new(4.0, -2.0, 9.5))
+        * cgmath::Matrix4::from_angle_y(Rad(0.7))
+        * cgmath::Matrix4::from_nonuniform_scale(2.0, 0.5, 3.0);
+}
+
+fn create_matrix() -> cgmath::Matrix4<f32> {
+    return cgmath::perspective(Rad(1.0f32), 1.5, 0.1, 100.0) * create_affine_matrix();
+}
+
+fn mat4_mul(c: &mut Criterion) {
+    let m = create_matrix();
+    let a = scalar::Mat4x4 { data: m.into() };
+    c.bench_function("mat4 mul scalar", move |b| {
+        b.iter(|| black_box(a).mul(&black_box(a)))
+    });
+    c.bench_function("mat4 mul cgmath", move |b| {
+        b.iter(|| black_box(m) * black_box(m))
+    });
+    let a = Mat4::from(m);
+    c.bench_function("mat4 mul simd", move |b| {
+        b.iter(|| black_box(a) * black_box(a))
+    });
+}
+
+fn mat4_inverse(c: &mut Criterion) {
+    let m = create_matrix();
+    let a = scalar::Mat4x4 { data: m.into() };
+    c.bench_function("mat4 inverse scalar", move |b| {
+        b.iter(|| black_box(a).inv())
+    });
+    c.bench_function("mat4 inverse cgmath", move |b| {
+        b.iter(|| black_box(m).invert())
+    });
+    let a = Mat4::from(m);
+    c.bench_function("mat4 inverse simd", move |b| {
+        b.iter(|| black_box(a).inverse())
+    });
+    let a = Affine3::from(create_affine_matrix());
+    c.bench_function("affine3 inverse simd", move |b| {
+        b.iter(|| black_box(a).inverse())
+    });
+}
+
+criterion_group!(benches, mat4_mul, mat4_inverse);
+criterion_main!(benches);
@@ -40,6 +40,14 @@ fn main() {
     }
     #[cfg(feature = "blank_gapi")]
     println!("cargo:rustc-cfg=blank_gapi");
+    #[cfg(feature = "profiler")]
+    println!("cargo:rustc-cfg=profiler");
+    #[cfg(feature = "headless")]
+    println!("cargo:rustc-cfg=headless");
+    #[cfg(feature = "software")]
+    println!("cargo:rustc-cfg=software_gapi");
+    #[cfg(feature = "wayland")]
+    println!("cargo:rustc-cfg=wayland");
     #[cfg(feature = "directx12")]
     {
         if !in_windows {
@@ -47,7 +55,11 @@ fn main() {
         }
         println!("cargo:rustc-cfg=directx12_api");
     }
-    #[cfg(all(not(feature = "directx12"), not(feature = "blank_gapi")))]
+    #[cfg(all(
+        not(feature = "directx12"),
+        not(feature = "blank_gapi"),
+        not(feature = "software")
+    ))]
     println!("cargo:rustc-cfg=vulkan_api");
     if in_macos || in_ios {
         println!("cargo:rustc-cfg=apple_os");
@@ -58,6 +70,14 @@ fn main() {
     if in_linux || in_ios || in_macos || in_android {
         println!("cargo:rustc-cfg=unix_based_os");
     }
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+    let target_features: Vec<&str> = target_features.split(',').collect();
+    if (target_arch == "x86" || target_arch == "x86_64") && target_features.contains(&"sse") {
+        println!("cargo:rustc-cfg=sse_simd");
+    } else if target_arch == "aarch64" && target_features.contains(&"neon") {
+        println!("cargo:rustc-cfg=neon_simd");
+    }
     #[cfg(debug_assertions)]
     {
         println!("cargo:rustc-cfg=debug_gx3d");
@@ -1,20 +1,73 @@
+use super::super::core::types::Id;
+use super::super::render::image::Layout;
+use super::command::Buffer as CmdBuffer;
+use super::image::Image;
+use super::trace::{Event, Trace};
 use std::mem::{size_of, transmute};
 use std::os::raw::c_void;
 use std::sync::{Arc, RwLock};
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Buffer {}
+pub(crate) struct Buffer {
+    id: Id,
+    size: isize,
+}
+
+impl Buffer {
+    pub(crate) fn new(id: Id, size: isize) -> Self {
+        Self { id, size }
+    }
+
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
 
+    pub(crate) fn get_size(&self) -> isize {
+        return self.size;
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Static {
+    buffer: Arc<RwLock<Buffer>>,
+}
+
+impl Static {
+    pub(crate) fn new(buffer: Arc<RwLock<Buffer>>) -> Self {
+        Self { buffer }
+    }
+
+    pub(crate) fn get_buffer(&self) -> &Arc<RwLock<Buffer>> {
+        return &self.buffer;
+    }
+}
+
+// The buffers of all frames have the id of the dynamic buffer, the content
+// is not recorded so the frames are indistinguishable in the trace.
+#[derive(Clone)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(crate) struct Dynamic {
+    id: Id,
+    buffers: Vec<Arc<RwLock<Buffer>>>,
     actual_size: isize,
 }
 
 impl Dynamic {
-    pub(crate) fn update_with_ptr(&mut self, _data: *const c_void, _frame_number: usize) {
-        vx_unimplemented!();
+    pub(crate) fn new(id: Id, frames_count: usize, actual_size: isize) -> Self {
+        let mut buffers = Vec::with_capacity(frames_count);
+        for _ in 0..frames_count {
+            buffers.push(Arc::new(RwLock::new(Buffer::new(id, actual_size))));
+        }
+        Self {
+            id,
+            buffers,
+            actual_size,
+        }
     }
 
+    pub(crate) fn update_with_ptr(&mut self, _data: *const c_void, _frame_number: usize) {}
+
     pub(crate) fn update<T>(&mut self, data: &T, frame_number: usize)
     where
         T: Sized,
@@ -28,23 +81,83 @@ impl Dynamic {
         self.update_with_ptr(unsafe { transmute(data) }, frame_number);
     }
 
-    pub(crate) fn get_buffer(&self, _frame_number: usize) -> &Arc<RwLock<Buffer>> {
-        vx_unimplemented!();
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
     }
-}
 
-#[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Static {}
+    pub(crate) fn get_buffer(&self, frame_number: usize) -> &Arc<RwLock<Buffer>> {
+        return &self.buffers[frame_number];
+    }
+}
 
+// Like the Vulkan one, it keeps the uploads until the next update, there
+// they are recorded as copy commands.
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Manager {}
+pub(crate) struct Manager {
+    trace: Arc<Trace>,
+    frames_count: usize,
+    copy_buffers: Vec<Id>,
+    copy_to_images: Vec<Arc<RwLock<Image>>>,
+}
 
 impl Manager {
-    pub(crate) fn create_dynamic_buffer(&mut self, _: isize) -> Dynamic {
-        vx_unimplemented!();
+    pub(crate) fn new(trace: Arc<Trace>, frames_count: usize) -> Self {
+        Self {
+            trace,
+            frames_count,
+            copy_buffers: Vec::new(),
+            copy_to_images: Vec::new(),
+        }
+    }
+
+    pub(crate) fn create_static_buffer_with_ptr(
+        &mut self,
+        _data: *const c_void,
+        size: isize,
+    ) -> Static {
+        let id = self.trace.create_id();
+        self.trace.record(Event::CreateBuffer {
+            id,
+            size,
+            is_dynamic: false,
+        });
+        self.copy_buffers.push(id);
+        return Static::new(Arc::new(RwLock::new(Buffer::new(id, size))));
+    }
+
+    pub(crate) fn create_static_buffer_with_vec<T>(&mut self, data: &[T]) -> Static {
+        let size = (size_of::<T>() * data.len()) as isize;
+        return self.create_static_buffer_with_ptr(unsafe { transmute(data.as_ptr()) }, size);
+    }
+
+    pub(crate) fn create_dynamic_buffer(&mut self, actual_size: isize) -> Dynamic {
+        let id = self.trace.create_id();
+        self.trace.record(Event::CreateBuffer {
+            id,
+            size: actual_size,
+            is_dynamic: true,
+        });
+        return Dynamic::new(id, self.frames_count, actual_size);
+    }
+
+    pub(super) fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
     }
 
-    pub(crate) fn create_static_buffer_with_vec<T>(&mut self, _data: &[T]) -> Static {
-        vx_unimplemented!();
+    pub(super) fn add_image_upload(&mut self, image: Arc<RwLock<Image>>) {
+        self.copy_to_images.push(image);
+    }
+
+    pub(crate) fn update(&mut self, cmd: &mut CmdBuffer, _frame_number: usize) {
+        for id in &self.copy_buffers {
+            cmd.copy_buffer(*id);
+        }
+        self.copy_buffers.clear();
+        for image in &self.copy_to_images {
+            let mut image = vx_result!(image.write());
+            cmd.copy_buffer_to_image(image.get_id());
+            image.set_layout(cmd, Layout::ShaderReadOnly);
+        }
+        self.copy_to_images.clear();
     }
 }
@@ -0,0 +1,77 @@
+use super::super::render::capture::Capture;
+use super::super::render::image::{AttachmentType, Format};
+use super::framebuffer::Framebuffer;
+use super::image::{Image, View as ImageView};
+use super::memory::Manager as MemoryManager;
+use super::render_pass::RenderPass;
+use super::trace::Event;
+use std::sync::{Arc, RwLock};
+
+// Offscreen render target with an arbitrary size, same as the one of the
+// Vulkan backend.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Target {
+    color: Arc<ImageView>,
+    depth: Arc<ImageView>,
+    render_pass: Arc<RenderPass>,
+    framebuffer: Arc<Framebuffer>,
+}
+
+impl Target {
+    pub(crate) fn new(width: u32, height: u32, memmgr: &Arc<RwLock<MemoryManager>>) -> Self {
+        let color = Arc::new(ImageView::new_attachment(
+            memmgr,
+            Format::RgbaByte,
+            AttachmentType::ColorOffscreen,
+            width,
+            height,
+        ));
+        let depth = Arc::new(ImageView::new_attachment(
+            memmgr,
+            Format::DepthFloat,
+            AttachmentType::DepthGBuffer,
+            width,
+            height,
+        ));
+        let views = vec![color.clone(), depth.clone()];
+        let render_pass = Arc::new(RenderPass::new(views.clone(), true, true));
+        let framebuffer = Arc::new(Framebuffer::new(views, render_pass.clone()));
+        Self {
+            color,
+            depth,
+            render_pass,
+            framebuffer,
+        }
+    }
+
+    pub(crate) fn get_color(&self) -> &Arc<ImageView> {
+        return &self.color;
+    }
+
+    pub(crate) fn get_depth(&self) -> &Arc<ImageView> {
+        return &self.depth;
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+
+    pub(crate) fn get_framebuffer(&self) -> &Arc<Framebuffer> {
+        return &self.framebuffer;
+    }
+
+    pub(crate) fn capture(&self) -> Capture {
+        return read_image(self.color.get_image());
+    }
+}
+
+// Images of the blank graphic API do not have any texel, so the capture is
+// transparent black, the read is recorded in the trace.
+pub(crate) fn read_image(image: &Arc<RwLock<Image>>) -> Capture {
+    let image = vx_result!(image.read());
+    image.get_trace().record(Event::ReadImage {
+        image: image.get_id(),
+    });
+    let (width, height) = image.get_dimensions();
+    return Capture::new(width, height, vec![0; (width * height * 4) as usize]);
+}
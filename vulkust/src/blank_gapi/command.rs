@@ -1,183 +1,445 @@
+use super::super::core::types::Id;
+use super::super::render::image::Layout;
+use super::super::render::pipeline::PipelineType;
 use super::buffer::{Buffer as BufBuffer, Static as StaticBuffer};
 use super::descriptor::Set as DescriptorSet;
 use super::framebuffer::Framebuffer;
 use super::pipeline::Pipeline;
+use super::render_pass::RenderPass;
+use super::trace::{Event, Trace};
 use std::sync::{Arc, RwLock};
 
+const GBUFF_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const GBUFF_MODEL_DESCRIPTOR_OFFSET: usize = 1;
+const GBUFF_MATERIAL_DESCRIPTOR_OFFSET: usize = 2;
+
+const SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET: usize = 3;
+
+const UNLIT_MODEL_DESCRIPTOR_OFFSET: usize = 0;
+const UNLIT_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
+
+const DEFERRED_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const DEFERRED_DEFERRED_DESCRIPTOR_OFFSET: usize = 1;
+
+const SSAO_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const SSAO_SSAO_DESCRIPTOR_OFFSET: usize = 1;
+
+const SHADOW_MAPPER_LIGHT_DESCRIPTOR_OFFSET: usize = 0;
+const SHADOW_MAPPER_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
+
+const SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET: usize = 2;
+
+const SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_OFFSET: usize = 0;
+
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Pool {}
+pub struct Pool {
+    id: Id,
+    trace: Arc<Trace>,
+}
+
+impl Pool {
+    pub(crate) fn new(trace: Arc<Trace>) -> Self {
+        let id = trace.create_id();
+        trace.record(Event::CreateCommandPool { id });
+        Self { id, trace }
+    }
+}
 
+// The events are kept in the command buffer and they go to the trace when
+// the buffer is submitted, executed secondary buffers are inlined after
+// their execute event.
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Buffer {}
+pub struct Buffer {
+    id: Id,
+    is_secondary: bool,
+    has_render_record: bool,
+    events: Vec<Event>,
+    render_pass: Option<Arc<RenderPass>>,
+    bound_pipeline_type: Option<PipelineType>,
+}
 
 impl Buffer {
-    pub(crate) fn new_primary(_pool: Arc<Pool>) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn new_primary(pool: Arc<Pool>) -> Self {
+        return Self::new(pool, false);
+    }
+
+    pub(crate) fn new_secondary(pool: Arc<Pool>) -> Self {
+        return Self::new(pool, true);
     }
 
-    pub(crate) fn new_secondary(_pool: Arc<Pool>) -> Self {
-        vx_unimplemented!();
+    fn new(pool: Arc<Pool>, is_secondary: bool) -> Self {
+        let id = pool.trace.create_id();
+        pool.trace.record(Event::CreateCommandBuffer {
+            id,
+            pool: pool.id,
+            is_secondary,
+        });
+        Self {
+            id,
+            is_secondary,
+            has_render_record: false,
+            events: Vec::new(),
+            render_pass: None,
+            bound_pipeline_type: None,
+        }
     }
 
-    fn new(_pool: Arc<Pool>, _is_secondary: bool) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+
+    pub(crate) fn get_events(&self) -> &[Event] {
+        return &self.events;
     }
 
     pub(crate) fn get_has_render_record(&self) -> bool {
-        vx_unimplemented!();
+        return self.has_render_record;
     }
 
-    pub(crate) fn exe_cmd(&mut self, _other: &Self) {
-        vx_unimplemented!();
+    pub(crate) fn exe_cmd(&mut self, other: &Self) {
+        self.has_render_record = true;
+        self.events.push(Event::ExecuteCommands {
+            cmd: self.id,
+            secondary: other.id,
+        });
+        self.events.extend_from_slice(&other.events);
     }
 
-    pub(crate) fn exe_cmds(&mut self, _others: &[&Self]) {
-        vx_unimplemented!();
+    pub(crate) fn exe_cmds(&mut self, others: &[&Self]) {
+        for other in others {
+            self.exe_cmd(other);
+        }
     }
 
     pub(crate) fn begin(&mut self) {
-        vx_unimplemented!();
+        #[cfg(debug_mode)]
+        {
+            if self.is_secondary {
+                vx_unexpected!();
+            }
+        }
+        self.reset();
+        self.events.push(Event::Begin { cmd: self.id });
+    }
+
+    pub(crate) fn begin_secondary(&mut self, framebuffer: &Framebuffer) {
+        #[cfg(debug_mode)]
+        {
+            if !self.is_secondary {
+                vx_unexpected!();
+            }
+        }
+        self.reset();
+        self.events.push(Event::BeginSecondary {
+            cmd: self.id,
+            framebuffer: framebuffer.get_id(),
+        });
+    }
+
+    fn reset(&mut self) {
+        self.has_render_record = false;
+        self.events.clear();
+        self.render_pass = None;
+        self.bound_pipeline_type = None;
     }
 
-    pub(crate) fn begin_secondary(&mut self, _framebuffer: &Framebuffer) {
-        vx_unimplemented!();
+    pub(crate) fn begin_render_pass_with_framebuffer(&mut self, framebuffer: &Framebuffer) {
+        let render_pass = framebuffer.get_render_pass().clone();
+        render_pass.begin(self);
+        self.events.push(Event::BeginRenderPass {
+            cmd: self.id,
+            render_pass: render_pass.get_id(),
+            framebuffer: framebuffer.get_id(),
+        });
+        self.render_pass = Some(render_pass);
     }
 
     pub(crate) fn end_render_pass(&mut self) {
-        vx_unimplemented!();
+        self.events.push(Event::EndRenderPass { cmd: self.id });
+        let render_pass = vx_unwrap!(self.render_pass.take());
+        render_pass.end(self);
     }
 
     pub(crate) fn end(&mut self) {
-        vx_unimplemented!();
+        self.events.push(Event::End { cmd: self.id });
     }
 
-    pub(crate) fn bind_pipeline(&mut self, _p: &Pipeline) {
-        vx_unimplemented!();
+    pub(crate) fn pipeline_image_barrier(
+        &mut self,
+        image: Id,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) {
+        self.events.push(Event::Barrier {
+            cmd: self.id,
+            image,
+            old_layout,
+            new_layout,
+        });
     }
 
-    pub(crate) fn bind_vertex_buffer(&mut self, _buffer: &Arc<RwLock<BufBuffer>>) {
-        vx_unimplemented!();
+    pub(crate) fn copy_buffer(&mut self, buffer: Id) {
+        self.events.push(Event::CopyBuffer {
+            cmd: self.id,
+            buffer,
+        });
     }
 
-    pub(crate) fn bind_index_buffer(&mut self, _buffer: &Arc<RwLock<BufBuffer>>) {
-        vx_unimplemented!();
+    pub(crate) fn copy_buffer_to_image(&mut self, image: Id) {
+        self.events.push(Event::CopyBufferToImage {
+            cmd: self.id,
+            image,
+        });
     }
 
-    pub(crate) fn draw_index(&mut self, _indices_count: u32) {
-        vx_unimplemented!();
+    pub(crate) fn bind_pipeline(&mut self, p: &Pipeline) {
+        self.bound_pipeline_type = Some(p.get_pipeline_type());
+        self.events.push(Event::BindPipeline {
+            cmd: self.id,
+            pipeline: p.get_id(),
+            pipeline_type: p.get_pipeline_type(),
+        });
     }
 
-    pub(crate) fn draw(&mut self, _vertices_count: u32) {
-        vx_unimplemented!();
+    pub(crate) fn bind_vertex_buffer(&mut self, buffer: &Arc<RwLock<BufBuffer>>) {
+        self.events.push(Event::BindVertexBuffer {
+            cmd: self.id,
+            buffer: vx_result!(buffer.read()).get_id(),
+        });
+    }
+
+    pub(crate) fn bind_index_buffer(&mut self, buffer: &Arc<RwLock<BufBuffer>>) {
+        self.events.push(Event::BindIndexBuffer {
+            cmd: self.id,
+            buffer: vx_result!(buffer.read()).get_id(),
+        });
+    }
+
+    pub(crate) fn draw_index(&mut self, indices_count: u32) {
+        self.events.push(Event::DrawIndexed {
+            cmd: self.id,
+            pipeline_type: *vx_unwrap!(&self.bound_pipeline_type),
+            indices_count,
+        });
+    }
+
+    pub(crate) fn draw(&mut self, vertices_count: u32) {
+        self.events.push(Event::Draw {
+            cmd: self.id,
+            pipeline_type: *vx_unwrap!(&self.bound_pipeline_type),
+            vertices_count,
+        });
+    }
+
+    fn bind_descriptor(
+        &mut self,
+        offset: usize,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.events.push(Event::BindDescriptor {
+            cmd: self.id,
+            pipeline_type: *vx_unwrap!(&self.bound_pipeline_type),
+            offset,
+            set: descriptor_set.get_id(),
+            buffer: buffer.get_id(),
+        });
     }
 
     pub(crate) fn bind_gbuff_scene_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(GBUFF_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_gbuff_model_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(GBUFF_MODEL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_gbuff_material_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(GBUFF_MATERIAL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn render_gbuff(
         &mut self,
-        _vertex_buffer: &StaticBuffer,
-        _index_buffer: &StaticBuffer,
-        _indices_count: u32,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+        self.has_render_record = true;
+    }
+
+    pub(crate) fn bind_skinned_gbuff_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn render_skinned_gbuff(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.render_gbuff(vertex_buffer, index_buffer, indices_count);
+    }
+
+    pub(crate) fn bind_unlit_model_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(UNLIT_MODEL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_unlit_material_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(UNLIT_MATERIAL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn render_unlit(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
     ) {
-        vx_unimplemented!();
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+        self.has_render_record = true;
     }
 
     pub(crate) fn render_deferred(&mut self) {
-        vx_unimplemented!();
+        self.draw(3);
+        self.has_render_record = true;
     }
 
     pub(crate) fn render_ssao(&mut self) {
-        vx_unimplemented!();
+        self.draw(3);
+        self.has_render_record = true;
     }
 
     pub(crate) fn render_shadow_accumulator_directional(&mut self) {
-        vx_unimplemented!();
+        self.draw(3);
+        self.has_render_record = true;
     }
 
     pub(crate) fn bind_deferred_scene_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(DEFERRED_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_deferred_deferred_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(DEFERRED_DEFERRED_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_ssao_scene_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(SSAO_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_ssao_ssao_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(SSAO_SSAO_DESCRIPTOR_OFFSET, descriptor_set, buffer);
     }
 
     pub(crate) fn bind_shadow_mapper_light_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(
+            SHADOW_MAPPER_LIGHT_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
     }
 
     pub(crate) fn bind_shadow_mapper_material_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(
+            SHADOW_MAPPER_MATERIAL_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
     }
 
     pub(crate) fn bind_shadow_accumulator_directional_descriptor(
         &mut self,
-        _descriptor_set: &DescriptorSet,
-        _buffer: &BufBuffer,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
     ) {
-        vx_unimplemented!();
+        self.bind_descriptor(
+            SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
+    }
+
+    pub(crate) fn bind_skinned_shadow_mapper_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(
+            SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
     }
 
     pub(crate) fn render_shadow_mapper(
         &mut self,
-        _vertex_buffer: &StaticBuffer,
-        _index_buffer: &StaticBuffer,
-        _indices_count: u32,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+        self.has_render_record = true;
+    }
+
+    pub(crate) fn render_skinned_shadow_mapper(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
     ) {
-        vx_unimplemented!();
+        self.render_shadow_mapper(vertex_buffer, index_buffer, indices_count);
     }
 }
@@ -1,47 +1,74 @@
+use super::super::core::types::Id;
 use super::super::render::texture::Texture;
 use super::buffer::Dynamic as DynamicBuffer;
+use super::trace::{Event, Trace};
 use std::sync::{Arc, RwLock};
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Set {}
+pub(crate) struct Set {
+    id: Id,
+}
+
+impl Set {
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+}
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Manager {}
+pub(crate) struct Manager {
+    trace: Arc<Trace>,
+}
 
 impl Manager {
+    pub(crate) fn new(trace: Arc<Trace>) -> Self {
+        Self { trace }
+    }
+
+    fn create_set(&mut self, uniform: &DynamicBuffer, textures_count: usize) -> Arc<Set> {
+        let id = self.trace.create_id();
+        self.trace.record(Event::CreateDescriptorSet {
+            id,
+            uniform: uniform.get_id(),
+            textures_count,
+        });
+        return Arc::new(Set { id });
+    }
+
     pub(crate) fn create_gbuff_set(
         &mut self,
-        _uniform: &DynamicBuffer,
-        _textures: Vec<Arc<RwLock<Texture>>>,
+        uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
     ) -> Arc<Set> {
-        vx_unimplemented!();
+        return self.create_set(uniform, textures.len());
     }
 
-    pub(crate) fn create_buffer_only_set(&mut self, _uniform: &DynamicBuffer) -> Arc<Set> {
-        vx_unimplemented!();
+    pub(crate) fn create_buffer_only_set(&mut self, uniform: &DynamicBuffer) -> Arc<Set> {
+        return self.create_set(uniform, 0);
     }
 
     pub(crate) fn create_deferred_set(
         &mut self,
-        _uniform: &DynamicBuffer,
-        _textures: Vec<Arc<RwLock<Texture>>>,
+        uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
     ) -> Arc<Set> {
-        vx_unimplemented!();
+        return self.create_set(uniform, textures.len());
     }
 
     pub(crate) fn create_ssao_set(
         &mut self,
-        _uniform: &DynamicBuffer,
-        _textures: Vec<Arc<RwLock<Texture>>>,
+        uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
     ) -> Arc<Set> {
-        vx_unimplemented!();
+        return self.create_set(uniform, textures.len());
     }
 
     pub(crate) fn create_shadow_accumulator_directional_set(
         &mut self,
-        _uniform: &DynamicBuffer,
-        _texturess: Vec<Vec<Arc<RwLock<Texture>>>>,
+        uniform: &DynamicBuffer,
+        texturess: Vec<Vec<Arc<RwLock<dyn Texture>>>>,
     ) -> Arc<Set> {
-        vx_unimplemented!();
+        let textures_count = texturess.iter().map(|ts| ts.len()).sum();
+        return self.create_set(uniform, textures_count);
     }
 }
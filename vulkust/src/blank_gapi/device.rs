@@ -1,2 +1,28 @@
+use super::trace::Trace;
+use std::sync::Arc;
+
+// The surface of the blank device is virtual, it only has dimensions.
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Device {}
+pub(crate) struct Device {
+    trace: Arc<Trace>,
+    surface_width: u32,
+    surface_height: u32,
+}
+
+impl Device {
+    pub(crate) fn new(trace: Arc<Trace>, surface_width: u32, surface_height: u32) -> Self {
+        Self {
+            trace,
+            surface_width,
+            surface_height,
+        }
+    }
+
+    pub(crate) fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
+    }
+
+    pub(crate) fn get_surface_dimensions(&self) -> (u32, u32) {
+        return (self.surface_width, self.surface_height);
+    }
+}
@@ -1,6 +1,11 @@
-use super::super::render::config::Configurations;
-use super::super::system::os::application::Application as OsApp;
+use super::super::core::types::Id;
+use super::super::platform::os::application::Application as OsApp;
+use super::super::render::capture::Capture;
+use super::super::render::image::{AttachmentType, Format};
+use super::super::render::sampler::Filter as SamplerFilter;
+use super::super::render::texture::container::Container;
 use super::buffer::Manager as BufferManager;
+use super::capture::{read_image, Target as CaptureTarget};
 use super::command::{Buffer as CmdBuffer, Pool as CmdPool};
 use super::descriptor::Manager as DescriptorManager;
 use super::device::Device;
@@ -11,117 +16,373 @@ use super::pipeline::Manager as PipelineManager;
 use super::render_pass::RenderPass;
 use super::sampler::Sampler;
 use super::sync::Semaphore;
+use super::trace::{Event, Trace};
+use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
 
+const FRAMES_COUNT: usize = 2;
+const SURFACE_WIDTH: u32 = 1000;
+const SURFACE_HEIGHT: u32 = 700;
+
+// Display of a secondary window, it is cleared and presented in each frame
+// after the main one.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Window {
+    clear_framebuffer: Arc<Framebuffer>,
+    framebuffer: Arc<Framebuffer>,
+    semaphore: Arc<Semaphore>,
+}
+
+// Graphic API engine that only records, everything the renderer creates or
+// submits goes to its trace, see the trace module.
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Engine {}
+pub struct Engine {
+    trace: Arc<Trace>,
+    device: Arc<Device>,
+    memory_manager: Arc<RwLock<MemoryManager>>,
+    buffer_manager: Arc<RwLock<BufferManager>>,
+    descriptor_manager: Arc<RwLock<DescriptorManager>>,
+    pipeline_manager: Arc<RwLock<PipelineManager>>,
+    graphic_cmd_pool: Arc<CmdPool>,
+    linear_repeat_sampler: Arc<Sampler>,
+    nearest_repeat_sampler: Arc<Sampler>,
+    starting_semaphore: Arc<Semaphore>,
+    display_view: Arc<ImageView>,
+    clear_render_pass: Arc<RenderPass>,
+    render_pass: Arc<RenderPass>,
+    clear_framebuffer: Arc<Framebuffer>,
+    framebuffer: Arc<Framebuffer>,
+    windows: BTreeMap<Id, Window>,
+    current_frame_number: usize,
+}
 
 impl Engine {
-    pub(crate) fn new(_os_app: &Arc<RwLock<OsApp>>, _conf: &Configurations) -> Self {
-        vx_unimplemented!();
+    pub fn new(_os_app: &mut OsApp) -> Self {
+        return Self::new_with_trace(Arc::new(Trace::new()), SURFACE_WIDTH, SURFACE_HEIGHT);
+    }
+
+    pub fn new_with_trace(trace: Arc<Trace>, width: u32, height: u32) -> Self {
+        let device = Arc::new(Device::new(trace.clone(), width, height));
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new(trace.clone())));
+        let buffer_manager = Arc::new(RwLock::new(BufferManager::new(trace.clone(), FRAMES_COUNT)));
+        let descriptor_manager = Arc::new(RwLock::new(DescriptorManager::new(trace.clone())));
+        let pipeline_manager = Arc::new(RwLock::new(PipelineManager::new(trace.clone())));
+        let graphic_cmd_pool = Arc::new(CmdPool::new(trace.clone()));
+        let linear_repeat_sampler = Arc::new(Sampler::new(&trace));
+        let nearest_repeat_sampler =
+            Arc::new(Sampler::new_with_filter(&trace, SamplerFilter::Nearest));
+        let starting_semaphore = Arc::new(Semaphore::new(&trace));
+        let (display_view, clear_framebuffer, framebuffer) =
+            create_display(&device, &memory_manager);
+        let clear_render_pass = clear_framebuffer.get_render_pass().clone();
+        let render_pass = framebuffer.get_render_pass().clone();
+        Self {
+            trace,
+            device,
+            memory_manager,
+            buffer_manager,
+            descriptor_manager,
+            pipeline_manager,
+            graphic_cmd_pool,
+            linear_repeat_sampler,
+            nearest_repeat_sampler,
+            starting_semaphore,
+            display_view,
+            clear_render_pass,
+            render_pass,
+            clear_framebuffer,
+            framebuffer,
+            windows: BTreeMap::new(),
+            current_frame_number: 0,
+        }
+    }
+
+    pub fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
+    }
+
+    pub fn capture_frame(&mut self) -> Option<Capture> {
+        return Some(read_image(self.display_view.get_image()));
+    }
+
+    pub fn create_offscreen_target(&self, width: u32, height: u32) -> CaptureTarget {
+        return CaptureTarget::new(width, height, &self.memory_manager);
+    }
+
+    pub fn update(&mut self) {
+        self.start_rendering();
+        let semaphore = self.starting_semaphore.clone();
+        self.end_rendering(&semaphore);
+    }
+
+    // Surface is virtual, so the device and the display are recreated with
+    // the new dimensions.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) == self.device.get_surface_dimensions() {
+            return;
+        }
+        self.device = Arc::new(Device::new(self.trace.clone(), width, height));
+        let (display_view, clear_framebuffer, framebuffer) =
+            create_display(&self.device, &self.memory_manager);
+        self.display_view = display_view;
+        self.clear_render_pass = clear_framebuffer.get_render_pass().clone();
+        self.render_pass = framebuffer.get_render_pass().clone();
+        self.clear_framebuffer = clear_framebuffer;
+        self.framebuffer = framebuffer;
+    }
+
+    pub fn add_window(&mut self, _os_app: &OsApp, window_id: Id) {
+        self.add_window_with_size(window_id, SURFACE_WIDTH, SURFACE_HEIGHT);
+    }
+
+    pub fn add_window_with_size(&mut self, window_id: Id, width: u32, height: u32) {
+        let display_view = Arc::new(ImageView::new_attachment(
+            &self.memory_manager,
+            Format::RgbaByte,
+            AttachmentType::ColorDisplay,
+            width,
+            height,
+        ));
+        let views = vec![display_view];
+        let clear_render_pass = Arc::new(RenderPass::new(views.clone(), true, false));
+        let render_pass = Arc::new(RenderPass::new(views.clone(), false, false));
+        let window = Window {
+            clear_framebuffer: Arc::new(Framebuffer::new(views.clone(), clear_render_pass)),
+            framebuffer: Arc::new(Framebuffer::new(views, render_pass)),
+            semaphore: Arc::new(self.create_semaphore()),
+        };
+        self.windows.insert(window_id, window);
+    }
+
+    pub fn remove_window(&mut self, window_id: Id) {
+        if self.windows.remove(&window_id).is_none() {
+            vx_log_e!("Window {} does not have any display.", window_id);
+        }
+    }
+
+    pub(crate) fn get_window_framebuffer(&self, window_id: Id) -> Option<&Arc<Framebuffer>> {
+        return self.windows.get(&window_id).map(|w| &w.framebuffer);
     }
 
     pub(crate) fn get_device(&self) -> &Arc<Device> {
-        vx_unimplemented!();
+        return &self.device;
     }
 
     pub(crate) fn get_linear_repeat_sampler(&self) -> &Arc<Sampler> {
-        vx_unimplemented!();
+        return &self.linear_repeat_sampler;
     }
 
     pub(crate) fn get_nearest_repeat_sampler(&self) -> &Arc<Sampler> {
-        vx_unimplemented!();
+        return &self.nearest_repeat_sampler;
     }
 
     pub(crate) fn get_buffer_manager(&self) -> &Arc<RwLock<BufferManager>> {
-        vx_unimplemented!();
+        return &self.buffer_manager;
     }
 
     pub(crate) fn get_descriptor_manager(&self) -> &Arc<RwLock<DescriptorManager>> {
-        vx_unimplemented!();
+        return &self.descriptor_manager;
     }
 
     pub(crate) fn get_pipeline_manager(&self) -> &Arc<RwLock<PipelineManager>> {
-        vx_unimplemented!();
+        return &self.pipeline_manager;
     }
 
     pub(crate) fn get_memory_manager(&self) -> &Arc<RwLock<MemoryManager>> {
-        vx_unimplemented!();
+        return &self.memory_manager;
     }
 
     pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
-        // it should be removed in future, the corresponding render pass must move to deferred structure
-        vx_unexpected!();
+        return &self.render_pass;
     }
 
     pub(crate) fn create_command_pool(&self) -> Arc<CmdPool> {
-        vx_unimplemented!();
+        return Arc::new(CmdPool::new(self.trace.clone()));
     }
 
-    pub(crate) fn create_secondary_command_buffer(&self, _cmd_pool: Arc<CmdPool>) -> CmdBuffer {
-        vx_unimplemented!();
+    pub(crate) fn create_secondary_command_buffer(&self, cmd_pool: Arc<CmdPool>) -> CmdBuffer {
+        return CmdBuffer::new_secondary(cmd_pool);
     }
 
-    pub(crate) fn create_primary_command_buffer(&self, _cmd_pool: Arc<CmdPool>) -> CmdBuffer {
-        vx_unimplemented!();
+    pub(crate) fn create_primary_command_buffer(&self, cmd_pool: Arc<CmdPool>) -> CmdBuffer {
+        return CmdBuffer::new_primary(cmd_pool);
     }
 
     pub(crate) fn create_primary_command_buffer_from_main_graphic_pool(&self) -> CmdBuffer {
-        vx_unimplemented!();
+        return CmdBuffer::new_primary(self.graphic_cmd_pool.clone());
     }
 
     pub(crate) fn create_secondary_command_buffer_from_main_graphic_pool(&self) -> CmdBuffer {
-        vx_unimplemented!();
+        return CmdBuffer::new_secondary(self.graphic_cmd_pool.clone());
     }
 
     pub(crate) fn create_semaphore(&self) -> Semaphore {
-        vx_unimplemented!();
+        return Semaphore::new(&self.trace);
+    }
+
+    pub(crate) fn get_surface_dimensions(&self) -> (u32, u32) {
+        return self.device.get_surface_dimensions();
     }
 
     pub(crate) fn get_frames_count(&self) -> usize {
-        vx_unimplemented!();
+        return FRAMES_COUNT;
     }
 
     pub(crate) fn get_frame_number(&self) -> usize {
-        vx_unimplemented!();
+        return self.current_frame_number;
     }
 
+    // The clearing command buffer is created for each frame, because nothing
+    // is in flight in the recording engine.
     pub(crate) fn start_rendering(&mut self) {
-        vx_unimplemented!();
+        self.current_frame_number = (self.current_frame_number + 1) % FRAMES_COUNT;
+        self.trace.record(Event::StartFrame {
+            frame_number: self.current_frame_number,
+        });
+        let mut cmd = self.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        vx_result!(self.buffer_manager.write()).update(&mut cmd, self.current_frame_number);
+        self.clear_framebuffer.begin(&mut cmd);
+        cmd.end_render_pass();
+        cmd.end();
+        let semaphore = self.starting_semaphore.clone();
+        self.submit_multiple(&[], &[&cmd], &[&semaphore]);
     }
 
-    pub(crate) fn submit(&self, _wait: &Semaphore, _cmd: &CmdBuffer, _signal: &Semaphore) {
-        vx_unimplemented!();
+    pub(crate) fn submit(&self, wait: &Semaphore, cmd: &CmdBuffer, signal: &Semaphore) {
+        self.submit_multiple(&[wait], &[cmd], &[signal]);
     }
 
     pub(crate) fn submit_multiple(
         &self,
-        _waits: &[&Semaphore],
-        _cmds: &[&CmdBuffer],
-        _signals: &[&Semaphore],
+        waits: &[&Semaphore],
+        cmds: &[&CmdBuffer],
+        signals: &[&Semaphore],
     ) {
-        vx_unimplemented!();
+        self.trace.record(Event::Submit {
+            cmds: cmds.iter().map(|c| c.get_id()).collect(),
+            waits: waits.iter().map(|s| s.get_id()).collect(),
+            signals: signals.iter().map(|s| s.get_id()).collect(),
+        });
+        for cmd in cmds {
+            self.trace.record_all(cmd.get_events());
+        }
     }
 
     pub(crate) fn create_texture_2d_with_pixels(
         &self,
-        _width: u32,
-        _height: u32,
-        _data: &[u8],
+        width: u32,
+        height: u32,
+        data: &[u8],
     ) -> Arc<ImageView> {
-        vx_unimplemented!();
+        return Arc::new(ImageView::new_texture_2d_with_pixels(
+            width,
+            height,
+            data,
+            &self.buffer_manager,
+        ));
+    }
+
+    pub(crate) fn create_texture_with_container(&self, container: &Container) -> Arc<ImageView> {
+        return Arc::new(ImageView::new_texture_with_container(
+            container,
+            &self.buffer_manager,
+        ));
     }
 
     pub(crate) fn get_current_framebuffer(&self) -> &Arc<Framebuffer> {
-        // it should be removed in future, the corresponding framebuffer must move to deferred structure
-        vx_unexpected!();
+        return &self.framebuffer;
+    }
+
+    pub(crate) fn end(&self, wait: &Semaphore) {
+        self.trace.record(Event::Present {
+            frame_number: self.current_frame_number,
+            wait: wait.get_id(),
+        });
     }
 
-    pub(crate) fn end(&self, _wait: &Semaphore) {
-        vx_unimplemented!();
+    // The renderer records the scenes between start_rendering and it.
+    pub(crate) fn end_rendering(&mut self, wait: &Semaphore) {
+        self.end(wait);
+        self.present_windows();
     }
 
     pub(crate) fn get_starting_semaphore(&self) -> &Arc<Semaphore> {
-        vx_unimplemented!();
+        return &self.starting_semaphore;
+    }
+
+    fn present_windows(&self) {
+        for (window_id, window) in &self.windows {
+            let mut cmd = self.create_primary_command_buffer_from_main_graphic_pool();
+            cmd.begin();
+            window.clear_framebuffer.begin(&mut cmd);
+            cmd.end_render_pass();
+            cmd.end();
+            self.submit_multiple(&[], &[&cmd], &[&window.semaphore]);
+            self.trace.record(Event::PresentWindow {
+                window: *window_id,
+                frame_number: self.current_frame_number,
+                wait: window.semaphore.get_id(),
+            });
+        }
+    }
+}
+
+fn create_display(
+    device: &Arc<Device>,
+    memory_manager: &Arc<RwLock<MemoryManager>>,
+) -> (Arc<ImageView>, Arc<Framebuffer>, Arc<Framebuffer>) {
+    let display_view = Arc::new(ImageView::new_surface_attachment(
+        device.clone(),
+        memory_manager,
+        Format::RgbaByte,
+        AttachmentType::ColorDisplay,
+    ));
+    let views = vec![display_view.clone()];
+    let clear_render_pass = Arc::new(RenderPass::new(views.clone(), true, false));
+    let render_pass = Arc::new(RenderPass::new(views.clone(), false, false));
+    let clear_framebuffer = Arc::new(Framebuffer::new(views.clone(), clear_render_pass));
+    let framebuffer = Arc::new(Framebuffer::new(views, render_pass));
+    return (display_view, clear_framebuffer, framebuffer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::trace::Event;
+    use super::*;
+
+    #[test]
+    fn secondary_windows_test() {
+        let trace = Arc::new(Trace::new());
+        let mut engine = Engine::new_with_trace(trace.clone(), 320, 240);
+        engine.add_window_with_size(1, 160, 120);
+        engine.add_window_with_size(2, 160, 120);
+        engine.update();
+        engine.remove_window(1);
+        engine.update();
+        let presents: Vec<Id> = trace
+            .get_events()
+            .iter()
+            .filter_map(|e| match e {
+                Event::PresentWindow { window, .. } => Some(*window),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(presents, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn empty_frame_golden_test() {
+        let trace = Arc::new(Trace::new());
+        let mut engine = Engine::new_with_trace(trace.clone(), 320, 240);
+        engine.update();
+        engine.update();
+        if let Some(diff) = trace.diff(include_str!("goldens/empty_frame.trace")) {
+            panic!(
+                "Trace differs from its golden, {}\n{}",
+                diff,
+                trace.to_text()
+            );
+        }
     }
 }
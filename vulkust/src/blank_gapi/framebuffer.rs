@@ -1,25 +1,53 @@
+use super::super::core::types::Id;
 use super::command::Buffer as CmdBuffer;
 use super::image::View as ImageView;
 use super::render_pass::RenderPass;
+use super::trace::Event;
 use std::sync::Arc;
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Framebuffer {}
+pub(crate) struct Framebuffer {
+    id: Id,
+    render_pass: Arc<RenderPass>,
+    width: u32,
+    height: u32,
+}
 
 impl Framebuffer {
-    pub(crate) fn new(_buffers: Vec<Arc<ImageView>>, _render_pass: Arc<RenderPass>) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn new(buffers: Vec<Arc<ImageView>>, render_pass: Arc<RenderPass>) -> Self {
+        let mut attachments = Vec::with_capacity(buffers.len());
+        for b in &buffers {
+            attachments.push(vx_result!(b.get_image().read()).get_id());
+        }
+        let (width, height) = vx_result!(buffers[0].get_image().read()).get_dimensions();
+        let trace = render_pass.get_trace();
+        let id = trace.create_id();
+        trace.record(Event::CreateFramebuffer {
+            id,
+            render_pass: render_pass.get_id(),
+            attachments,
+        });
+        Self {
+            id,
+            render_pass,
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn begin(&self, cmd_buffer: &mut CmdBuffer) {
+        cmd_buffer.begin_render_pass_with_framebuffer(self);
     }
 
-    pub(crate) fn begin(&self, _cmd_buffer: &mut CmdBuffer) {
-        vx_unimplemented!();
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
     }
 
     pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
-        vx_unimplemented!();
+        return &self.render_pass;
     }
 
     pub(crate) fn get_dimensions(&self) -> (u32, u32) {
-        vx_unimplemented!();
+        return (self.width, self.height);
     }
 }
@@ -1,53 +1,167 @@
-use super::super::render::image::{AttachmentType, Format};
+use super::super::core::types::Id;
+use super::super::render::image::{AttachmentType, Format, Layout};
+use super::super::render::texture::container::Container;
 use super::buffer::Manager as BufferManager;
+use super::command::Buffer as CmdBuffer;
 use super::device::Device;
 use super::memory::Manager as MemoryManager;
+use super::trace::{Event, Trace};
 use std::sync::{Arc, RwLock};
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Image {}
+pub struct Image {
+    id: Id,
+    trace: Arc<Trace>,
+    width: u32,
+    height: u32,
+    is_depth: bool,
+    layout: Layout,
+}
+
+impl Image {
+    pub(crate) fn new(
+        trace: Arc<Trace>,
+        width: u32,
+        height: u32,
+        layers_count: u32,
+        is_depth: bool,
+    ) -> Self {
+        let id = trace.create_id();
+        trace.record(Event::CreateImage {
+            id,
+            width,
+            height,
+            layers_count,
+            is_depth,
+        });
+        Self {
+            id,
+            trace,
+            width,
+            height,
+            is_depth,
+            layout: Layout::Uninitialized,
+        }
+    }
+
+    pub(crate) fn set_layout(&mut self, cmd: &mut CmdBuffer, new_layout: Layout) {
+        if self.layout == new_layout {
+            return;
+        }
+        cmd.pipeline_image_barrier(self.id, self.layout, new_layout);
+        self.layout = new_layout;
+    }
+
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+
+    pub(crate) fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
+    }
+
+    pub(crate) fn get_dimensions(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    pub(crate) fn is_depth(&self) -> bool {
+        return self.is_depth;
+    }
+}
+
+fn is_depth_attachment(attachment_type: &AttachmentType) -> bool {
+    match attachment_type {
+        AttachmentType::DepthGBuffer
+        | AttachmentType::DepthShadowBuffer
+        | AttachmentType::DepthStencilDisplay => return true,
+        _ => return false,
+    }
+}
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct View {}
+pub struct View {
+    image: Arc<RwLock<Image>>,
+}
 
 impl View {
+    fn new_texture_with_pixels(
+        width: u32,
+        height: u32,
+        layers_count: u32,
+        buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let mut buffmgr = vx_result!(buffmgr.write());
+        let trace = buffmgr.get_trace().clone();
+        let image = Arc::new(RwLock::new(Image::new(
+            trace,
+            width,
+            height,
+            layers_count,
+            false,
+        )));
+        buffmgr.add_image_upload(image.clone());
+        return Self::new_with_image(image);
+    }
+
     pub(crate) fn new_texture_2d_with_pixels(
-        _width: u32,
-        _height: u32,
+        width: u32,
+        height: u32,
         _data: &[u8],
-        _buffmgr: &Arc<RwLock<BufferManager>>,
+        buffmgr: &Arc<RwLock<BufferManager>>,
     ) -> Self {
-        vx_unimplemented!();
+        return Self::new_texture_with_pixels(width, height, 1, buffmgr);
     }
 
-    pub(crate) fn new_with_image(_image: Arc<RwLock<Image>>) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn new_texture_cube_with_pixels(
+        width: u32,
+        height: u32,
+        _data: &[&[u8]; 6],
+        buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        return Self::new_texture_with_pixels(width, height, 6, buffmgr);
+    }
+
+    pub(crate) fn new_texture_with_container(
+        container: &Container,
+        buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let (width, height) = container.get_dimensions();
+        let layers_count = container.get_layers_count() * container.get_faces_count();
+        return Self::new_texture_with_pixels(width, height, layers_count, buffmgr);
     }
 
-    pub(crate) fn new_with_image_aspect(_image: Arc<RwLock<Image>>, _aspect_mask: u32) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn new_with_image(image: Arc<RwLock<Image>>) -> Self {
+        Self { image }
+    }
+
+    pub(crate) fn new_with_image_aspect(image: Arc<RwLock<Image>>, _aspect_mask: u32) -> Self {
+        return Self::new_with_image(image);
     }
 
     pub(crate) fn new_surface_attachment(
-        _logical_device: Arc<Device>,
-        _memory_mgr: &Arc<RwLock<MemoryManager>>,
-        _format: Format,
-        _attachment_type: AttachmentType,
+        logical_device: Arc<Device>,
+        memory_mgr: &Arc<RwLock<MemoryManager>>,
+        format: Format,
+        attachment_type: AttachmentType,
     ) -> Self {
-        vx_unimplemented!();
+        let (width, height) = logical_device.get_surface_dimensions();
+        return Self::new_attachment(memory_mgr, format, attachment_type, width, height);
     }
 
     pub(crate) fn new_attachment(
-        _memory_mgr: &Arc<RwLock<MemoryManager>>,
+        memory_mgr: &Arc<RwLock<MemoryManager>>,
         _format: Format,
-        _attachment_type: AttachmentType,
-        _width: u32,
-        _height: u32,
+        attachment_type: AttachmentType,
+        width: u32,
+        height: u32,
     ) -> Self {
-        vx_unimplemented!();
+        let trace = vx_result!(memory_mgr.read()).get_trace().clone();
+        let is_depth = is_depth_attachment(&attachment_type);
+        let image = Image::new(trace, width, height, 1, is_depth);
+        return Self::new_with_image(Arc::new(RwLock::new(image)));
     }
 
     pub(crate) fn get_image(&self) -> &Arc<RwLock<Image>> {
-        vx_unimplemented!();
+        return &self.image;
     }
 }
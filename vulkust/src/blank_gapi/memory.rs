@@ -1,5 +1,20 @@
+use super::trace::Trace;
+use std::sync::Arc;
+
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(crate) struct Memory {}
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Manager {}
+pub(crate) struct Manager {
+    trace: Arc<Trace>,
+}
+
+impl Manager {
+    pub(crate) fn new(trace: Arc<Trace>) -> Self {
+        Self { trace }
+    }
+
+    pub(crate) fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
+    }
+}
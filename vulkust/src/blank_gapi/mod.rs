@@ -1,4 +1,5 @@
 pub mod buffer;
+pub mod capture;
 pub mod command;
 pub mod descriptor;
 pub mod device;
@@ -10,3 +11,4 @@ pub mod pipeline;
 pub mod render_pass;
 pub mod sampler;
 pub mod sync;
+pub mod trace;
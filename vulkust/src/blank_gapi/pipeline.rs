@@ -1,21 +1,70 @@
+use super::super::core::types::Id;
 use super::super::render::config::Configurations;
 use super::super::render::pipeline::PipelineType;
 use super::render_pass::RenderPass;
-use std::sync::Arc;
+use super::trace::{Event, Trace};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Weak};
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Pipeline {}
+pub(crate) struct Pipeline {
+    id: Id,
+    pipeline_type: PipelineType,
+    render_pass: Arc<RenderPass>,
+}
+
+impl Pipeline {
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+
+    pub(crate) fn get_pipeline_type(&self) -> PipelineType {
+        return self.pipeline_type;
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+}
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Manager {}
+pub(crate) struct Manager {
+    trace: Arc<Trace>,
+    pipelines: BTreeMap<(Id, u8), Weak<Pipeline>>,
+}
 
 impl Manager {
+    pub(crate) fn new(trace: Arc<Trace>) -> Self {
+        Self {
+            trace,
+            pipelines: BTreeMap::new(),
+        }
+    }
+
     pub(crate) fn create(
         &mut self,
-        _render_pass: Arc<RenderPass>,
-        _pipeline_type: PipelineType,
+        render_pass: Arc<RenderPass>,
+        pipeline_type: PipelineType,
         _config: &Configurations,
     ) -> Arc<Pipeline> {
-        vx_unimplemented!();
+        let key = (render_pass.get_id(), pipeline_type as u8);
+        if let Some(p) = self.pipelines.get(&key) {
+            if let Some(p) = p.upgrade() {
+                return p;
+            }
+        }
+        let id = self.trace.create_id();
+        self.trace.record(Event::CreatePipeline {
+            id,
+            render_pass: render_pass.get_id(),
+            pipeline_type,
+        });
+        let p = Arc::new(Pipeline {
+            id,
+            pipeline_type,
+            render_pass,
+        });
+        self.pipelines.insert(key, Arc::downgrade(&p));
+        return p;
     }
 }
@@ -1,20 +1,91 @@
+use super::super::core::types::Id;
 use super::super::render::image::Layout;
+use super::command::Buffer as CmdBuffer;
 use super::image::View as ImageView;
+use super::trace::{Event, Trace};
 use std::sync::Arc;
 
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct RenderPass {}
+pub(crate) struct RenderPass {
+    id: Id,
+    trace: Arc<Trace>,
+    views: Vec<Arc<ImageView>>,
+    start_layouts: Vec<Layout>,
+    end_layouts: Vec<Layout>,
+}
 
 impl RenderPass {
-    pub(crate) fn new(_views: Vec<Arc<ImageView>>, _clear: bool, _has_reader: bool) -> Self {
-        vx_unimplemented!();
+    pub(crate) fn new(views: Vec<Arc<ImageView>>, clear: bool, has_reader: bool) -> Self {
+        let mut start_layouts = Vec::with_capacity(views.len());
+        let mut end_layouts = Vec::with_capacity(views.len());
+        for v in &views {
+            start_layouts.push(if clear {
+                Layout::Uninitialized
+            } else {
+                Layout::Display
+            });
+            end_layouts.push(if has_reader {
+                Layout::ShaderReadOnly
+            } else if vx_result!(v.get_image().read()).is_depth() {
+                Layout::DepthStencil
+            } else {
+                Layout::Display
+            });
+        }
+        return Self::new_with_layouts(views, clear, &start_layouts, &end_layouts);
     }
+
     pub(crate) fn new_with_layouts(
-        _views: Vec<Arc<ImageView>>,
-        _clear: bool,
-        _start_layouts: &[Layout],
-        _end_layouts: &[Layout],
+        views: Vec<Arc<ImageView>>,
+        clear: bool,
+        start_layouts: &[Layout],
+        end_layouts: &[Layout],
     ) -> Self {
-        vx_unimplemented!();
+        let mut attachments = Vec::with_capacity(views.len());
+        for v in &views {
+            attachments.push(vx_result!(v.get_image().read()).get_id());
+        }
+        let trace = vx_result!(views[0].get_image().read()).get_trace().clone();
+        let id = trace.create_id();
+        trace.record(Event::CreateRenderPass {
+            id,
+            attachments,
+            clear,
+        });
+        Self {
+            id,
+            trace,
+            views,
+            start_layouts: start_layouts.to_vec(),
+            end_layouts: end_layouts.to_vec(),
+        }
+    }
+
+    // An uninitialized start layout means the previous content is not
+    // needed, so it does not need any transition.
+    pub(super) fn begin(&self, cmd: &mut CmdBuffer) {
+        for (v, l) in self.views.iter().zip(&self.start_layouts) {
+            if *l != Layout::Uninitialized {
+                vx_result!(v.get_image().write()).set_layout(cmd, *l);
+            }
+        }
+    }
+
+    pub(super) fn end(&self, cmd: &mut CmdBuffer) {
+        for (v, l) in self.views.iter().zip(&self.end_layouts) {
+            vx_result!(v.get_image().write()).set_layout(cmd, *l);
+        }
+    }
+
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+
+    pub(crate) fn get_trace(&self) -> &Arc<Trace> {
+        return &self.trace;
+    }
+
+    pub(crate) fn get_color_attachments(&self) -> &[Arc<ImageView>] {
+        return &self.views;
     }
 }
@@ -1,2 +1,28 @@
+use super::super::core::types::Id;
+use super::super::render::sampler::Filter;
+use super::trace::{Event, Trace};
+
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Sampler {}
+pub struct Sampler {
+    id: Id,
+}
+
+impl Sampler {
+    pub(crate) fn new(trace: &Trace) -> Self {
+        return Self::new_with_filter(trace, Filter::Linear);
+    }
+
+    pub(crate) fn new_with_filter(trace: &Trace, f: Filter) -> Self {
+        let id = trace.create_id();
+        let is_nearest = match f {
+            Filter::Nearest => true,
+            _ => false,
+        };
+        trace.record(Event::CreateSampler { id, is_nearest });
+        Self { id }
+    }
+
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+}
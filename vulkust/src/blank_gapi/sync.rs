@@ -1,5 +1,22 @@
+use super::super::core::types::Id;
+use super::trace::{Event, Trace};
+
 #[cfg_attr(debug_mode, derive(Debug))]
-pub struct Semaphore {}
+pub struct Semaphore {
+    id: Id,
+}
+
+impl Semaphore {
+    pub(crate) fn new(trace: &Trace) -> Self {
+        let id = trace.create_id();
+        trace.record(Event::CreateSemaphore { id });
+        Self { id }
+    }
+
+    pub(crate) fn get_id(&self) -> Id {
+        return self.id;
+    }
+}
 
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Fence {}
@@ -0,0 +1,491 @@
+use super::super::core::types::Id;
+use super::super::render::image::Layout;
+use super::super::render::pipeline::PipelineType;
+use std::fmt;
+use std::sync::RwLock;
+
+// The blank graphic API does not talk to any device, it records everything
+// that renderer asks it into a trace, so the renderer logic can be tested by
+// looking at the trace or by comparing its text with a checked-in golden.
+// Commands of a command buffer reach the trace when the buffer is submitted,
+// so the order of the events does not depend on the recording threads.
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Event {
+    CreateBuffer {
+        id: Id,
+        size: isize,
+        is_dynamic: bool,
+    },
+    CreateImage {
+        id: Id,
+        width: u32,
+        height: u32,
+        layers_count: u32,
+        is_depth: bool,
+    },
+    CreateSampler {
+        id: Id,
+        is_nearest: bool,
+    },
+    CreateRenderPass {
+        id: Id,
+        attachments: Vec<Id>,
+        clear: bool,
+    },
+    CreateFramebuffer {
+        id: Id,
+        render_pass: Id,
+        attachments: Vec<Id>,
+    },
+    CreatePipeline {
+        id: Id,
+        render_pass: Id,
+        pipeline_type: PipelineType,
+    },
+    CreateDescriptorSet {
+        id: Id,
+        uniform: Id,
+        textures_count: usize,
+    },
+    CreateCommandPool {
+        id: Id,
+    },
+    CreateCommandBuffer {
+        id: Id,
+        pool: Id,
+        is_secondary: bool,
+    },
+    CreateSemaphore {
+        id: Id,
+    },
+    StartFrame {
+        frame_number: usize,
+    },
+    Begin {
+        cmd: Id,
+    },
+    BeginSecondary {
+        cmd: Id,
+        framebuffer: Id,
+    },
+    End {
+        cmd: Id,
+    },
+    BeginRenderPass {
+        cmd: Id,
+        render_pass: Id,
+        framebuffer: Id,
+    },
+    EndRenderPass {
+        cmd: Id,
+    },
+    Barrier {
+        cmd: Id,
+        image: Id,
+        old_layout: Layout,
+        new_layout: Layout,
+    },
+    CopyBuffer {
+        cmd: Id,
+        buffer: Id,
+    },
+    CopyBufferToImage {
+        cmd: Id,
+        image: Id,
+    },
+    BindPipeline {
+        cmd: Id,
+        pipeline: Id,
+        pipeline_type: PipelineType,
+    },
+    BindVertexBuffer {
+        cmd: Id,
+        buffer: Id,
+    },
+    BindIndexBuffer {
+        cmd: Id,
+        buffer: Id,
+    },
+    BindDescriptor {
+        cmd: Id,
+        pipeline_type: PipelineType,
+        offset: usize,
+        set: Id,
+        buffer: Id,
+    },
+    Draw {
+        cmd: Id,
+        pipeline_type: PipelineType,
+        vertices_count: u32,
+    },
+    DrawIndexed {
+        cmd: Id,
+        pipeline_type: PipelineType,
+        indices_count: u32,
+    },
+    ExecuteCommands {
+        cmd: Id,
+        secondary: Id,
+    },
+    Submit {
+        cmds: Vec<Id>,
+        waits: Vec<Id>,
+        signals: Vec<Id>,
+    },
+    Present {
+        frame_number: usize,
+        wait: Id,
+    },
+    // Window is the id that platform application gave to the window.
+    PresentWindow {
+        window: Id,
+        frame_number: usize,
+        wait: Id,
+    },
+    ReadImage {
+        image: Id,
+    },
+}
+
+fn pipeline_type_name(t: PipelineType) -> &'static str {
+    match t {
+        PipelineType::Deferred => "deferred",
+        PipelineType::GBuffer => "gbuffer",
+        PipelineType::ShadowAccumulatorDirectional => "shadow-accumulator-directional",
+        PipelineType::ShadowMapper => "shadow-mapper",
+        PipelineType::SkinnedGBuffer => "skinned-gbuffer",
+        PipelineType::SkinnedShadowMapper => "skinned-shadow-mapper",
+        PipelineType::SSAO => "ssao",
+        PipelineType::TransparentPBR => "transparent-pbr",
+        PipelineType::Unlit => "unlit",
+    }
+}
+
+fn layout_name(l: Layout) -> &'static str {
+    match l {
+        Layout::Uninitialized => "uninitialized",
+        Layout::DepthStencil => "depth-stencil",
+        Layout::Display => "display",
+        Layout::ShaderReadOnly => "shader-read-only",
+    }
+}
+
+fn ids_text(ids: &[Id]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+    return format!("[{}]", ids.join(" "));
+}
+
+// One line for each event, ids are the creation order of the resources in
+// their trace, so they are stable between the runs.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::CreateBuffer {
+                id,
+                size,
+                is_dynamic,
+            } => write!(
+                f,
+                "create-buffer #{} {} size={}",
+                id,
+                if *is_dynamic { "dynamic" } else { "static" },
+                size
+            ),
+            Event::CreateImage {
+                id,
+                width,
+                height,
+                layers_count,
+                is_depth,
+            } => write!(
+                f,
+                "create-image #{} {} {}x{}x{}",
+                id,
+                if *is_depth { "depth" } else { "color" },
+                width,
+                height,
+                layers_count
+            ),
+            Event::CreateSampler { id, is_nearest } => write!(
+                f,
+                "create-sampler #{} {}",
+                id,
+                if *is_nearest { "nearest" } else { "linear" }
+            ),
+            Event::CreateRenderPass {
+                id,
+                attachments,
+                clear,
+            } => write!(
+                f,
+                "create-render-pass #{} attachments={} clear={}",
+                id,
+                ids_text(attachments),
+                clear
+            ),
+            Event::CreateFramebuffer {
+                id,
+                render_pass,
+                attachments,
+            } => write!(
+                f,
+                "create-framebuffer #{} render-pass=#{} attachments={}",
+                id,
+                render_pass,
+                ids_text(attachments)
+            ),
+            Event::CreatePipeline {
+                id,
+                render_pass,
+                pipeline_type,
+            } => write!(
+                f,
+                "create-pipeline #{} {} render-pass=#{}",
+                id,
+                pipeline_type_name(*pipeline_type),
+                render_pass
+            ),
+            Event::CreateDescriptorSet {
+                id,
+                uniform,
+                textures_count,
+            } => write!(
+                f,
+                "create-descriptor-set #{} uniform=#{} textures={}",
+                id, uniform, textures_count
+            ),
+            Event::CreateCommandPool { id } => write!(f, "create-command-pool #{}", id),
+            Event::CreateCommandBuffer {
+                id,
+                pool,
+                is_secondary,
+            } => write!(
+                f,
+                "create-command-buffer #{} {} pool=#{}",
+                id,
+                if *is_secondary {
+                    "secondary"
+                } else {
+                    "primary"
+                },
+                pool
+            ),
+            Event::CreateSemaphore { id } => write!(f, "create-semaphore #{}", id),
+            Event::StartFrame { frame_number } => write!(f, "start-frame {}", frame_number),
+            Event::Begin { cmd } => write!(f, "#{} begin", cmd),
+            Event::BeginSecondary { cmd, framebuffer } => {
+                write!(f, "#{} begin-secondary framebuffer=#{}", cmd, framebuffer)
+            }
+            Event::End { cmd } => write!(f, "#{} end", cmd),
+            Event::BeginRenderPass {
+                cmd,
+                render_pass,
+                framebuffer,
+            } => write!(
+                f,
+                "#{} begin-render-pass #{} framebuffer=#{}",
+                cmd, render_pass, framebuffer
+            ),
+            Event::EndRenderPass { cmd } => write!(f, "#{} end-render-pass", cmd),
+            Event::Barrier {
+                cmd,
+                image,
+                old_layout,
+                new_layout,
+            } => write!(
+                f,
+                "#{} barrier image=#{} {} -> {}",
+                cmd,
+                image,
+                layout_name(*old_layout),
+                layout_name(*new_layout)
+            ),
+            Event::CopyBuffer { cmd, buffer } => write!(f, "#{} copy-buffer #{}", cmd, buffer),
+            Event::CopyBufferToImage { cmd, image } => {
+                write!(f, "#{} copy-buffer-to-image #{}", cmd, image)
+            }
+            Event::BindPipeline {
+                cmd,
+                pipeline,
+                pipeline_type,
+            } => write!(
+                f,
+                "#{} bind-pipeline #{} {}",
+                cmd,
+                pipeline,
+                pipeline_type_name(*pipeline_type)
+            ),
+            Event::BindVertexBuffer { cmd, buffer } => {
+                write!(f, "#{} bind-vertex-buffer #{}", cmd, buffer)
+            }
+            Event::BindIndexBuffer { cmd, buffer } => {
+                write!(f, "#{} bind-index-buffer #{}", cmd, buffer)
+            }
+            Event::BindDescriptor {
+                cmd,
+                pipeline_type,
+                offset,
+                set,
+                buffer,
+            } => write!(
+                f,
+                "#{} bind-descriptor {}[{}] set=#{} buffer=#{}",
+                cmd,
+                pipeline_type_name(*pipeline_type),
+                offset,
+                set,
+                buffer
+            ),
+            Event::Draw {
+                cmd,
+                pipeline_type,
+                vertices_count,
+            } => write!(
+                f,
+                "#{} draw {} vertices={}",
+                cmd,
+                pipeline_type_name(*pipeline_type),
+                vertices_count
+            ),
+            Event::DrawIndexed {
+                cmd,
+                pipeline_type,
+                indices_count,
+            } => write!(
+                f,
+                "#{} draw-indexed {} indices={}",
+                cmd,
+                pipeline_type_name(*pipeline_type),
+                indices_count
+            ),
+            Event::ExecuteCommands { cmd, secondary } => {
+                write!(f, "#{} execute-commands #{}", cmd, secondary)
+            }
+            Event::Submit {
+                cmds,
+                waits,
+                signals,
+            } => write!(
+                f,
+                "submit cmds={} waits={} signals={}",
+                ids_text(cmds),
+                ids_text(waits),
+                ids_text(signals)
+            ),
+            Event::Present { frame_number, wait } => {
+                write!(f, "present {} wait=#{}", frame_number, wait)
+            }
+            Event::PresentWindow {
+                window,
+                frame_number,
+                wait,
+            } => write!(
+                f,
+                "present-window window={} frame={} wait=#{}",
+                window, frame_number, wait
+            ),
+            Event::ReadImage { image } => write!(f, "read-image #{}", image),
+        }
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Trace {
+    last_id: RwLock<Id>,
+    events: RwLock<Vec<Event>>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self {
+            last_id: RwLock::new(0),
+            events: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn create_id(&self) -> Id {
+        let mut last_id = vx_result!(self.last_id.write());
+        *last_id += 1;
+        return *last_id;
+    }
+
+    pub(super) fn record(&self, event: Event) {
+        vx_result!(self.events.write()).push(event);
+    }
+
+    pub(super) fn record_all(&self, events: &[Event]) {
+        vx_result!(self.events.write()).extend_from_slice(events);
+    }
+
+    pub fn get_events(&self) -> Vec<Event> {
+        return vx_result!(self.events.read()).clone();
+    }
+
+    // It drops the recorded events, ids keep growing so the resources that
+    // are created before it remain distinguishable.
+    pub fn clear(&self) {
+        vx_result!(self.events.write()).clear();
+    }
+
+    pub fn count<F>(&self, f: F) -> usize
+    where
+        F: Fn(&Event) -> bool,
+    {
+        return vx_result!(self.events.read())
+            .iter()
+            .filter(|e| f(e))
+            .count();
+    }
+
+    pub fn get_draws_count(&self, pipeline_type: PipelineType) -> usize {
+        return self.count(|e| match e {
+            Event::Draw {
+                pipeline_type: t, ..
+            }
+            | Event::DrawIndexed {
+                pipeline_type: t, ..
+            } => *t == pipeline_type,
+            _ => false,
+        });
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        for e in vx_result!(self.events.read()).iter() {
+            result.push_str(&e.to_string());
+            result.push('\n');
+        }
+        return result;
+    }
+
+    // It returns the first difference with the golden text, empty lines and
+    // comment lines, the ones that start with "# ", in the golden are ignored.
+    pub fn diff(&self, golden: &str) -> Option<String> {
+        let text = self.to_text();
+        let mut found = text.lines();
+        let mut expected = golden
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with("# "));
+        let mut line = 1;
+        loop {
+            match (expected.next(), found.next()) {
+                (None, None) => return None,
+                (Some(e), Some(f)) => {
+                    if e != f {
+                        return Some(format!("event {}: expected `{}`, found `{}`", line, e, f));
+                    }
+                }
+                (Some(e), None) => {
+                    return Some(format!("event {}: expected `{}`, found nothing", line, e));
+                }
+                (None, Some(f)) => {
+                    return Some(format!("event {}: expected nothing, found `{}`", line, f));
+                }
+            }
+            line += 1;
+        }
+    }
+}
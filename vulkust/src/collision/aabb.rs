@@ -88,7 +88,7 @@ impl Aabb3 {
     //     let r = or + mr;
     //     let d = mc - oc;
     //     let d = Vec3::new(d.x.abs(), d.y.abs(), d.z.abs());
-    //     return d.x < r.x || d.y < r.y || d.z < r.z;
+    //     return d.x < r.x && d.y < r.y && d.z < r.z;
     // }
 
     pub(crate) fn get_intersection_with_aabb(&self, o: &Self) -> Self {
@@ -112,7 +112,7 @@ impl Aabb3 {
         let r = mr + Vec3::new(r, r, r);
         let d = mc - c;
         let d = Vec3::new(d.x.abs(), d.y.abs(), d.z.abs());
-        return d.x < r.x || d.y < r.y || d.z < r.z;
+        return d.x < r.x && d.y < r.y && d.z < r.z;
     }
 
     pub(crate) fn get_min_max_diff(&self) -> Vec3 {
@@ -1,10 +1,14 @@
 use super::gesture::State as GestureState;
 use super::object::{create_id, Object};
 use super::types::{Id, Real};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 pub type FingerIndexType = i64;
 
+// Other windows get their ids from create_id, it never gives zero.
+pub const MAIN_WINDOW_ID: Id = 0;
+
 #[derive(Clone)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum Mouse {
@@ -107,6 +111,21 @@ pub enum Window {
         pre_h: Real,
         pre_ratio: Real,
     },
+    FocusGained,
+    FocusLost,
+    // Rendering must stop until the restore.
+    Minimized,
+    Restored,
+    // User asked for closing the window, Quit follows it unless the
+    // application asked for confirming the close requests, then it must
+    // quit itself if user confirms.
+    CloseRequest,
+    // Scale factor is DPI divided by 96, it changes when window moves to a
+    // screen with different density or when user changes the settings.
+    ScaleFactorChange {
+        scale: Real,
+        pre_scale: Real,
+    },
 }
 
 #[derive(Clone)]
@@ -177,6 +196,35 @@ pub enum Touch {
     },
 }
 
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Selection {
+    Clipboard,
+    // The X11 selection that is pasted with the middle click.
+    Primary,
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum DropData {
+    Files(Vec<PathBuf>),
+    Text(String),
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Drag {
+    Enter,
+    Move {
+        position: (Real, Real),
+    },
+    Leave,
+    Drop {
+        position: (Real, Real),
+        data: DropData,
+    },
+}
+
 #[derive(Clone)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum Type {
@@ -187,6 +235,13 @@ pub enum Type {
     },
     Touch(Touch),
     Window(Window),
+    // Answer of a clipboard text request, text is None when the selection is
+    // empty or it does not have any text.
+    Clipboard {
+        selection: Selection,
+        text: Option<String>,
+    },
+    Drag(Drag),
     Quit,
 }
 
@@ -194,13 +249,19 @@ pub enum Type {
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Event {
     id: Id,
+    pub window_id: Id,
     pub event_type: Type,
 }
 
 impl Event {
     pub fn new(event_type: Type) -> Self {
+        return Self::new_with_window(event_type, MAIN_WINDOW_ID);
+    }
+
+    pub fn new_with_window(event_type: Type, window_id: Id) -> Self {
         Event {
             id: create_id(),
+            window_id,
             event_type,
         }
     }
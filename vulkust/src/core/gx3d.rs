@@ -190,6 +190,10 @@ impl Table {
         self.reader.seek(*off);
     }
 
+    pub fn contains(&self, id: Id) -> bool {
+        return self.id_offset.contains_key(&id);
+    }
+
     pub fn get_mut_reader(&mut self) -> &mut Gx3DReader {
         return &mut self.reader;
     }
@@ -1,15 +1,20 @@
 // pub mod algorithms;
 // pub mod allocate;
 pub mod application;
-// pub mod asset;
+// Its managers record through the buffers and descriptors of the backend, see
+// render/mod.rs.
+#[cfg(not(vulkan_api))]
+pub mod asset;
 // pub mod config;
-// pub mod constants;
-// pub mod debug;
-// pub mod event;
-// pub mod gesture;
+pub mod constants;
+pub mod debug;
+pub mod event;
+pub mod gesture;
 // pub mod gx3d;
-// pub mod object;
+pub mod object;
+#[cfg(profiler)]
+pub mod profiler;
 // pub mod storage;
 pub mod string;
-// pub mod timing;
-// pub mod types;
+pub mod timing;
+pub mod types;
@@ -0,0 +1,368 @@
+// Lightweight CPU profiler.
+// Zones are recorded into a per-thread buffer so the hot path only locks an
+// uncontended mutex. Every thread keeps rolling averages of its zones all the
+// time, but the individual events are only stored when a capture is running.
+// Use vx_profile_zone!, vx_profile_counter! and vx_profile_thread_name!
+// macros, they are compiled out when the `profiler` feature is disabled.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+pub const AVERAGE_WINDOW: usize = 64;
+
+static IS_CAPTURING: AtomicBool = AtomicBool::new(false);
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+static GLOBAL: Mutex<Global> = Mutex::new(Global {
+    threads: Vec::new(),
+    counters: Vec::new(),
+});
+// It is apart from the GLOBAL, so recording a zone does not lock it.
+static START: OnceLock<Instant> = OnceLock::new();
+
+thread_local! {
+    static THREAD: RefCell<Option<ThreadEntry>> = RefCell::new(None);
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Global {
+    threads: Vec<Arc<Mutex<ThreadData>>>,
+    counters: Vec<CounterEvent>,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct ZoneEvent {
+    name: &'static str,
+    start: u64, // micro-seconds from profiler start
+    duration: u64,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct CounterEvent {
+    name: &'static str,
+    time: u64,
+    value: f64,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Average {
+    samples: [f64; AVERAGE_WINDOW],
+    index: usize,
+    count: usize,
+}
+
+impl Average {
+    fn new() -> Self {
+        Self {
+            samples: [0.0; AVERAGE_WINDOW],
+            index: 0,
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, v: f64) {
+        self.samples[self.index] = v;
+        self.index = (self.index + 1) % AVERAGE_WINDOW;
+        if self.count < AVERAGE_WINDOW {
+            self.count += 1;
+        }
+    }
+
+    fn get_samples(&self) -> &[f64] {
+        return &self.samples[..self.count];
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct ThreadData {
+    id: u64,
+    name: String,
+    events: Vec<ZoneEvent>,
+    averages: BTreeMap<&'static str, Average>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct ZoneAverage {
+    pub name: &'static str,
+    pub average_ms: f64,
+    pub max_ms: f64,
+    pub samples_count: usize,
+}
+
+// It is owned by the thread local storage of its thread, so the data of the
+// thread is unregistered when the thread exits and its events and averages
+// are not in the later traces and averages.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct ThreadEntry {
+    data: Arc<Mutex<ThreadData>>,
+}
+
+impl Drop for ThreadEntry {
+    fn drop(&mut self) {
+        // A panicked thread may have poisoned the lock, the entry must be
+        // removed anyway.
+        let mut global = match GLOBAL.lock() {
+            Ok(global) => global,
+            Err(e) => e.into_inner(),
+        };
+        global.threads.retain(|t| !Arc::ptr_eq(t, &self.data));
+    }
+}
+
+fn start_time() -> Instant {
+    return *START.get_or_init(Instant::now);
+}
+
+fn micros_since_start(t: Instant) -> u64 {
+    let start = start_time();
+    if t < start {
+        return 0;
+    }
+    return t.duration_since(start).as_micros() as u64;
+}
+
+fn with_thread_data<F: FnOnce(&mut ThreadData)>(f: F) {
+    THREAD.with(|t| {
+        let mut t = t.borrow_mut();
+        if t.is_none() {
+            let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+            let data = Arc::new(Mutex::new(ThreadData {
+                id,
+                name: format!("thread-{}", id),
+                events: Vec::new(),
+                averages: BTreeMap::new(),
+            }));
+            start_time();
+            vx_result!(GLOBAL.lock()).threads.push(data.clone());
+            *t = Some(ThreadEntry { data });
+        }
+        f(&mut *vx_result!(vx_unwrap!(&*t).data.lock()));
+    });
+}
+
+pub fn set_thread_name(name: &str) {
+    with_thread_data(|t| t.name = name.to_string());
+}
+
+pub fn is_capturing() -> bool {
+    return IS_CAPTURING.load(Ordering::Relaxed);
+}
+
+// Clears the previous capture and starts a new one.
+pub fn start_capture() {
+    start_time();
+    let mut global = vx_result!(GLOBAL.lock());
+    for t in &global.threads {
+        vx_result!(t.lock()).events.clear();
+    }
+    global.counters.clear();
+    IS_CAPTURING.store(true, Ordering::Relaxed);
+}
+
+pub fn stop_capture() {
+    IS_CAPTURING.store(false, Ordering::Relaxed);
+}
+
+pub fn counter(name: &'static str, value: f64) {
+    if !is_capturing() {
+        return;
+    }
+    let time = micros_since_start(Instant::now());
+    vx_result!(GLOBAL.lock())
+        .counters
+        .push(CounterEvent { name, time, value });
+}
+
+fn record(name: &'static str, start: Instant, end: Instant) {
+    let duration = end.duration_since(start);
+    let capturing = is_capturing();
+    let start = if capturing {
+        micros_since_start(start)
+    } else {
+        0
+    };
+    with_thread_data(|t| {
+        t.averages
+            .entry(name)
+            .or_insert_with(Average::new)
+            .add(duration.as_secs_f64() * 1000.0);
+        if capturing {
+            t.events.push(ZoneEvent {
+                name,
+                start,
+                duration: duration.as_micros() as u64,
+            });
+        }
+    });
+}
+
+// Merged over all threads, useful for on-screen overlays.
+pub fn get_averages() -> Vec<ZoneAverage> {
+    let mut sums: BTreeMap<&'static str, (f64, f64, usize)> = BTreeMap::new();
+    let threads = vx_result!(GLOBAL.lock()).threads.clone();
+    for t in &threads {
+        let t = vx_result!(t.lock());
+        for (name, average) in &t.averages {
+            let e = sums.entry(name).or_insert((0.0, 0.0, 0));
+            for s in average.get_samples() {
+                e.0 += *s;
+                if e.1 < *s {
+                    e.1 = *s;
+                }
+            }
+            e.2 += average.get_samples().len();
+        }
+    }
+    let mut result = Vec::with_capacity(sums.len());
+    for (name, (sum, max, count)) in sums {
+        result.push(ZoneAverage {
+            name,
+            average_ms: sum / count as f64,
+            max_ms: max,
+            samples_count: count,
+        });
+    }
+    return result;
+}
+
+fn write_json_string<W: Write>(w: &mut W, s: &str) {
+    vx_result!(w.write_all(b"\""));
+    for c in s.chars() {
+        match c {
+            '"' => vx_result!(w.write_all(b"\\\"")),
+            '\\' => vx_result!(w.write_all(b"\\\\")),
+            '\n' => vx_result!(w.write_all(b"\\n")),
+            c if (c as u32) < 0x20 => vx_result!(write!(w, "\\u{:04x}", c as u32)),
+            c => vx_result!(write!(w, "{}", c)),
+        }
+    }
+    vx_result!(w.write_all(b"\""));
+}
+
+// Chrome trace event format, it can be opened in chrome://tracing or Perfetto.
+pub fn write_chrome_trace<W: Write>(w: &mut W) {
+    let global = vx_result!(GLOBAL.lock());
+    vx_result!(w.write_all(b"{\"traceEvents\":["));
+    let mut is_first = true;
+    let mut separate = |w: &mut W| {
+        if is_first {
+            is_first = false;
+        } else {
+            vx_result!(w.write_all(b",\n"));
+        }
+    };
+    for t in &global.threads {
+        let t = vx_result!(t.lock());
+        separate(w);
+        vx_result!(write!(
+            w,
+            "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":",
+            t.id
+        ));
+        write_json_string(w, &t.name);
+        vx_result!(w.write_all(b"}}"));
+        for e in &t.events {
+            separate(w);
+            vx_result!(w.write_all(b"{\"name\":"));
+            write_json_string(w, e.name);
+            vx_result!(write!(
+                w,
+                ",\"cat\":\"cpu\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{},\"dur\":{}}}",
+                t.id, e.start, e.duration
+            ));
+        }
+    }
+    for c in &global.counters {
+        separate(w);
+        vx_result!(w.write_all(b"{\"name\":"));
+        write_json_string(w, c.name);
+        vx_result!(write!(
+            w,
+            ",\"ph\":\"C\",\"pid\":1,\"ts\":{},\"args\":{{\"value\":{}}}}}",
+            c.time, c.value
+        ));
+    }
+    vx_result!(w.write_all(b"]}\n"));
+}
+
+pub fn save_chrome_trace(file_name: &str) {
+    let mut file = BufWriter::new(vx_result!(File::create(file_name)));
+    write_chrome_trace(&mut file);
+    vx_result!(file.flush());
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Zone {
+    name: &'static str,
+    start: Instant,
+}
+
+impl Zone {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Zone {
+    fn drop(&mut self) {
+        record(self.name, self.start, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_average_window() {
+        let mut a = Average::new();
+        for i in 0..(AVERAGE_WINDOW + 10) {
+            a.add(i as f64);
+        }
+        assert_eq!(a.get_samples().len(), AVERAGE_WINDOW);
+        let sum: f64 = a.get_samples().iter().sum();
+        let expected: f64 = (10..(AVERAGE_WINDOW + 10)).map(|i| i as f64).sum();
+        assert_eq!(sum, expected);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        set_thread_name("test \"main\"");
+        start_capture();
+        {
+            let _zone = Zone::new("outer");
+            counter("count", 2.0);
+        }
+        stop_capture();
+        let mut data = Vec::new();
+        write_chrome_trace(&mut data);
+        let data = String::from_utf8(data).unwrap();
+        assert!(data.starts_with("{\"traceEvents\":["));
+        assert!(data.contains("\"name\":\"outer\""));
+        assert!(data.contains("\"ph\":\"C\""));
+        assert!(data.contains("test \\\"main\\\""));
+        assert!(get_averages().iter().any(|a| a.name == "outer"));
+    }
+
+    #[test]
+    fn test_thread_exit() {
+        let data = vx_result!(std::thread::spawn(|| {
+            {
+                let _zone = Zone::new("exited thread");
+            }
+            return THREAD.with(|t| vx_unwrap!(&*t.borrow()).data.clone());
+        })
+        .join());
+        assert_eq!(vx_result!(data.lock()).averages.len(), 1);
+        let global = vx_result!(GLOBAL.lock());
+        assert!(!global.threads.iter().any(|t| Arc::ptr_eq(t, &data)));
+    }
+}
@@ -36,6 +36,12 @@ impl Dynamic {
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(crate) struct Static {}
 
+impl Static {
+    pub(crate) fn new(_buffer: Arc<RwLock<Buffer>>) -> Self {
+        vx_unimplemented!();
+    }
+}
+
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(crate) struct Manager {}
 
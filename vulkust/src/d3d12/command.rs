@@ -159,6 +159,23 @@ impl Buffer {
         vx_unimplemented!();
     }
 
+    pub(crate) fn bind_skinned_gbuff_skin_descriptor(
+        &mut self,
+        _descriptor_set: &DescriptorSet,
+        _buffer: &BufBuffer,
+    ) {
+        vx_unimplemented!();
+    }
+
+    pub(crate) fn render_skinned_gbuff(
+        &mut self,
+        _vertex_buffer: &StaticBuffer,
+        _index_buffer: &StaticBuffer,
+        _indices_count: u32,
+    ) {
+        vx_unimplemented!();
+    }
+
     pub(crate) fn render_deferred(&mut self) {
         vx_unimplemented!();
     }
@@ -227,6 +244,14 @@ impl Buffer {
         vx_unimplemented!();
     }
 
+    pub(crate) fn bind_skinned_shadow_mapper_skin_descriptor(
+        &mut self,
+        _descriptor_set: &DescriptorSet,
+        _buffer: &BufBuffer,
+    ) {
+        vx_unimplemented!();
+    }
+
     pub(crate) fn render_shadow_mapper(
         &mut self,
         _vertex_buffer: &StaticBuffer,
@@ -235,6 +260,15 @@ impl Buffer {
     ) {
         vx_unimplemented!();
     }
+
+    pub(crate) fn render_skinned_shadow_mapper(
+        &mut self,
+        _vertex_buffer: &StaticBuffer,
+        _index_buffer: &StaticBuffer,
+        _indices_count: u32,
+    ) {
+        vx_unimplemented!();
+    }
 }
 
 #[cfg(debug_mode)]
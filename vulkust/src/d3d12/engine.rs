@@ -1,4 +1,5 @@
 use super::super::render::config::Configurations;
+use super::super::render::texture::container::Container;
 use super::super::system::os::application::Application as OsApp;
 use super::buffer::Manager as BufferManager;
 use super::command::{Buffer as CmdBuffer, Pool as CmdPool};
@@ -127,6 +128,10 @@ impl Engine {
         vx_unimplemented!();
     }
 
+    pub(crate) fn create_texture_with_container(&self, _container: &Container) -> Arc<ImageView> {
+        vx_unimplemented!();
+    }
+
     pub(crate) fn get_current_framebuffer(&self) -> &Arc<Framebuffer> {
         // it should be removed in future, the corresponding framebuffer must move to deferred structure
         vx_unexpected!();
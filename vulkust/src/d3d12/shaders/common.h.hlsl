@@ -1,6 +1,7 @@
 #define MAX_DIRECTIONAL_CASCADES_COUNT 6
 #define MAX_DIRECTIONAL_LIGHTS_COUNT 8
 #define MAX_POINT_LIGHTS_COUNT 32
+#define MAX_SPOT_LIGHTS_COUNT 16
 #define BLUR_KERNEL_LENGTH 5
 #define SSAO_SAMPLES 32
 #define SSAO_SEARCH_STEPS 4
@@ -25,11 +26,19 @@ struct DirectionalLight {
 	float4 direction_strength;
 };
 
+struct SpotLight {
+	float4 color_minradius;
+	float4 position_radius;
+	float4 direction_cone_scale;
+	float4 cone_offset_reserved;
+};
+
 struct Scene {
 	Camera camera;
 	DirectionalLight directional_lights[MAX_DIRECTIONAL_LIGHTS_COUNT];
 	PointLight point_lights[MAX_POINT_LIGHTS_COUNT];
-	uint4 directional_point_lights_count;
+	SpotLight spot_lights[MAX_SPOT_LIGHTS_COUNT];
+	uint4 directional_point_spot_lights_count;
 };
 
 struct Deferred {
@@ -47,6 +56,12 @@ struct Material {
     float normal_scale;
     float occlusion_strength;
     float roughness_factor;
+    float emissive_strength;
+    float rezerved;
+    float4 modes; // unlit, opaque, metallic channel, roughness channel
+    float4 base_color_uv_transform[2];
+    float4 metallic_roughness_uv_transform[2];
+    float4 emissive_uv_transform[2];
 };
 
 struct Light {
@@ -58,4 +73,10 @@ struct Light {
 
 struct ModelShadow {
 	float4x4 model_view_projection;
-};
\ No newline at end of file
+};
+
+// Rows of the uv matrix of KHR_texture_transform
+float2 transform_uv(float4 t[2], float2 uv) {
+	float3 huv = float3(uv, 1.0);
+	return float2(dot(t[0].xyz, huv), dot(t[1].xyz, huv));
+}
\ No newline at end of file
@@ -47,16 +47,22 @@ struct PSOutput {
 
 PSOutput frag_main(PSInput input) {
     PSOutput result;
-    result.alb = mul(base_color.Sample(sam, input.uv), base_color_factor.Sample(sam, input.uv));
+    float2 base_uv = transform_uv(material_ubo.base_color_uv_transform, input.uv);
+    result.alb = mul(base_color.Sample(sam, base_uv), base_color_factor.Sample(sam, base_uv));
     result.alb.w *= material_ubo.alpha;
     if(result.alb.w < material_ubo.alpha_cutoff) {
         discard;
     }
+    if(material_ubo.modes.y > 0.5) {
+        result.alb.w = 1.0;
+    }
     result.pos.xyz = input.pos;
     result.pos.w = 1.0;
+    float3 mapped_nrm = (normal.Sample(sam, base_uv).xyz - 0.5) * 2.0;
+    mapped_nrm.xy *= material_ubo.normal_scale;
     result.nrm.xyz = normalize(mul(
         float3x3(input.tng, input.btg, input.nrm), 
-        (normal.Sample(sam, input.uv).xyz - 0.5) * 2.0));
+        mapped_nrm));
     result.nrm.w = 1.0;
   // todo lots of work must be done in here
   // I must add any needed output for deferred part
@@ -31,13 +31,20 @@ pub mod macros;
 // pub mod audio;
 #[cfg(blank_gapi)]
 pub mod blank_gapi;
+#[cfg(blank_gapi)]
+pub use self::blank_gapi as gapi;
 // pub mod collision;
 pub mod core;
 #[cfg(directx12_api)]
 pub mod d3d12;
+pub mod math;
 // pub mod physics;
 pub mod platform;
 pub mod render;
+#[cfg(software_gapi)]
+pub mod software;
+#[cfg(software_gapi)]
+pub use self::software as gapi;
 #[cfg(vulkan_api)]
 pub mod vulkan;
 #[cfg(vulkan_api)]
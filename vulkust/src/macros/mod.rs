@@ -122,6 +122,48 @@ macro_rules! vx_todo {
     };
 }
 
+#[cfg(profiler)]
+#[macro_export]
+macro_rules! vx_profile_zone {
+    ($name:expr) => {
+        let _vx_profile_zone = $crate::core::profiler::Zone::new($name);
+    };
+}
+
+#[cfg(not(profiler))]
+#[macro_export]
+macro_rules! vx_profile_zone {
+    ($name:expr) => {};
+}
+
+#[cfg(profiler)]
+#[macro_export]
+macro_rules! vx_profile_counter {
+    ($name:expr, $value:expr) => {
+        $crate::core::profiler::counter($name, $value as f64);
+    };
+}
+
+#[cfg(not(profiler))]
+#[macro_export]
+macro_rules! vx_profile_counter {
+    ($name:expr, $value:expr) => {};
+}
+
+#[cfg(profiler)]
+#[macro_export]
+macro_rules! vx_profile_thread_name {
+    ($name:expr) => {
+        $crate::core::profiler::set_thread_name($name);
+    };
+}
+
+#[cfg(not(profiler))]
+#[macro_export]
+macro_rules! vx_profile_thread_name {
+    ($name:expr) => {};
+}
+
 macro_rules! vx_flag_check {
     ($f:expr, $b:expr) => {
         $f & $b == $b
@@ -1,94 +1,167 @@
-extern crate num;
-
-use ::math::num::{
-    min,
-    max,
-};
-
-use ::math::vector::{
-    Vec3,
-    Axis,
-    MathVector,
-    VectorElement,
-};
-
-use ::math::ray::Ray3;
-
-pub trait ExpandableToOther {
-    fn expand(&mut self, o: &Self);
-}
-
-pub trait ExpandableToPoint3<T> where T: VectorElement, Vec3<T>: MathVector<T> {
-    fn expand(&mut self, o: &Vec3<T>);
-}
-
-#[derive(Debug, Clone, Copy)]
-pub struct AABBox3<T> where T: VectorElement, Vec3<T>: MathVector<T> {
-    pub blf: Vec3<T>,
-    pub trr: Vec3<T>
+use super::ray::Ray3;
+use super::vector::Vec3;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Aabb3 {
+    min: Vec3,
+    max: Vec3,
 }
 
-impl<T> ExpandableToOther for AABBox3<T> where T: VectorElement, Vec3<T>: MathVector<T> {
+impl Aabb3 {
+    // It is empty, the first inserted point becomes its min and max.
+    pub fn new() -> Self {
+        return Self {
+            min: Vec3::splat(std::f32::MAX),
+            max: Vec3::splat(std::f32::MIN),
+        };
+    }
 
-    fn expand(&mut self, o : &Self) {
-        if o.blf.x < self.blf.x { self.blf.x = o.blf.x; }
-        if o.blf.y < self.blf.y { self.blf.y = o.blf.y; }
-        if o.blf.z < self.blf.z { self.blf.z = o.blf.z; }
+    pub fn new_with_points(min: Vec3, max: Vec3) -> Self {
+        return Self { min, max };
+    }
 
-        if o.trr.x > self.trr.x { self.trr.x = o.trr.x; }
-        if o.trr.y > self.trr.y { self.trr.y = o.trr.y; }
-        if o.trr.z > self.trr.z { self.trr.z = o.trr.z; }
+    pub fn new_with_center_radius(center: Vec3, radius: f32) -> Self {
+        let r = Vec3::splat(radius);
+        return Self {
+            min: center - r,
+            max: center + r,
+        };
     }
-}
 
-impl<T> ExpandableToPoint3<T> for AABBox3<T> where T: VectorElement, Vec3<T>: MathVector<T> {
+    #[inline]
+    pub fn insert(&mut self, p: Vec3) {
+        self.min = self.min.min(p);
+        self.max = self.max.max(p);
+    }
 
-    fn expand(&mut self, p : &Vec3<T>) {
-        if p.x < self.blf.x { self.blf.x = p.x; }
-        if p.y < self.blf.y { self.blf.y = p.y; }
-        if p.z < self.blf.z { self.blf.z = p.z; }
+    #[inline]
+    pub fn insert_aabb(&mut self, o: &Self) {
+        self.min = self.min.min(o.min);
+        self.max = self.max.max(o.max);
     }
-}
 
-impl<T> AABBox3<T> where T: VectorElement, Vec3<T>: MathVector<T> {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let min = self.min.to_array();
+        let max = self.max.to_array();
+        return min[0] > max[0] || min[1] > max[1] || min[2] > max[2];
+    }
 
-    pub fn new() -> AABBox3<T> {
-        AABBox3 {
-            blf: Vec3::new(num::cast(0).unwrap()),
-            trr: Vec3::new(num::cast(0).unwrap()),
-        }
+    #[inline]
+    pub fn get_min(&self) -> Vec3 {
+        return self.min;
     }
 
-    pub fn get_longest_axis(&self) -> Axis {
-        let diff = self.trr - self.blf; // TODO check for occurance, if it is too much store it in box
-        if diff.x > diff.y && diff.x > diff.z { return Axis::X; }
-        if diff.y > diff.x && diff.y > diff.z { return Axis::Y; }
-        return Axis::Z;
+    #[inline]
+    pub fn get_max(&self) -> Vec3 {
+        return self.max;
     }
 
-    // Check if ray intersects with box. Returns true/false and stores distance in t
-    pub fn intersection(&self, r: &Ray3<T>) -> (bool, T) {
+    #[inline]
+    pub fn get_center(&self) -> Vec3 {
+        return (self.min + self.max) * 0.5;
+    }
 
-        let tx1 = (self.blf.x - r.o.x) * r.invd.x;
-        let tx2 = (self.trr.x - r.o.x) * r.invd.x;
+    #[inline]
+    pub fn get_size(&self) -> Vec3 {
+        return self.max - self.min;
+    }
 
-        let mut tmin = min(tx1, tx2);
-        let mut tmax = max(tx1, tx2);
+    // Empty boxes have zero area, so they do not affect the SAH costs.
+    #[inline]
+    pub fn get_surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let s = self.get_size().to_array();
+        return 2.0 * (s[0] * s[1] + s[1] * s[2] + s[2] * s[0]);
+    }
 
-        let ty1 = (self.blf.y - r.o.y) * r.invd.y;
-        let ty2 = (self.trr.y - r.o.y) * r.invd.y;
+    pub fn get_longest_axis(&self) -> usize {
+        let s = self.get_size().to_array();
+        if s[0] >= s[1] && s[0] >= s[2] {
+            return 0;
+        }
+        if s[1] >= s[2] {
+            return 1;
+        }
+        return 2;
+    }
 
-        tmin = max(tmin, min(ty1, ty2));
-        tmax = min(tmax, max(ty1, ty2));
+    #[inline]
+    pub fn intersects_aabb(&self, o: &Self) -> bool {
+        let a_min = self.min.to_array();
+        let a_max = self.max.to_array();
+        let b_min = o.min.to_array();
+        let b_max = o.max.to_array();
+        for i in 0..3 {
+            if a_max[i] < b_min[i] || b_max[i] < a_min[i] {
+                return false;
+            }
+        }
+        return true;
+    }
 
-        let tz1 = (self.blf.z - r.o.z) * r.invd.z;
-        let tz2 = (self.trr.z - r.o.z) * r.invd.z;
+    #[inline]
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        let closest = center.max(self.min).min(self.max);
+        return (closest - center).length_squared() <= radius * radius;
+    }
 
-        tmin = max(tmin, min(tz1, tz2));
-        tmax = min(tmax, max(tz1, tz2));
+    // It returns the entering distance of the ray, it is zero when the origin
+    // is inside the box. Hits farther than max_distance are missed.
+    #[inline]
+    pub fn intersect_ray(&self, ray: &Ray3, max_distance: f32) -> Option<f32> {
+        let origin = ray.get_origin();
+        let inv = ray.get_inv_direction();
+        let t1 = (self.min - origin) * inv;
+        let t2 = (self.max - origin) * inv;
+        let near = t1.min(t2).to_array();
+        let far = t1.max(t2).to_array();
+        let near = near[0].max(near[1]).max(near[2]).max(0.0);
+        let far = far[0].min(far[1]).min(far[2]).min(max_distance);
+        if near <= far {
+            return Some(near);
+        }
+        return None;
+    }
+}
 
-        let t = tmin;
+impl Default for Aabb3 {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
 
-        return (tmax >= tmin, t);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_and_overlap_test() {
+        let b = Aabb3::new_with_points(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 2.0, 3.0));
+        let ray = Ray3::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(b.intersect_ray(&ray, 100.0), Some(4.0));
+        assert_eq!(b.intersect_ray(&ray, 3.0), None);
+        let inside = Ray3::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(b.intersect_ray(&inside, 100.0), Some(0.0));
+        let behind = Ray3::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(b.intersect_ray(&behind, 100.0), None);
+        let miss = Ray3::new(Vec3::new(-5.0, 2.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(miss.get_direction().y(), 0.0);
+        assert_eq!(b.intersect_ray(&miss, 100.0), None);
+        assert_eq!(
+            b.get_surface_area(),
+            2.0 * (2.0 * 3.0 + 3.0 * 4.0 + 4.0 * 2.0)
+        );
+        assert_eq!(b.get_longest_axis(), 2);
+        assert!(b.intersects_sphere(Vec3::new(2.0, 2.0, 0.0), 1.01));
+        assert!(!b.intersects_sphere(Vec3::new(2.0, 3.0, 0.0), 1.4));
+        let other = Aabb3::new_with_center_radius(Vec3::new(1.5, 0.0, 0.0), 0.5);
+        assert!(b.intersects_aabb(&other));
+        assert!(!b.intersects_aabb(&Aabb3::new_with_center_radius(Vec3::splat(3.0), 0.5)));
+        assert!(Aabb3::new().is_empty());
+        assert_eq!(Aabb3::new().get_surface_area(), 0.0);
     }
 }
@@ -0,0 +1,504 @@
+use super::aabbox::Aabb3;
+use super::ray::Ray3;
+use super::triangle::Triangle;
+use super::vector::Vec3;
+
+const BINS_COUNT: usize = 12;
+// Nodes with less triangles than this stay leaves when the split does not
+// lower the SAH cost.
+const MAX_LEAF_TRIANGLES: usize = 4;
+// Cost of visiting a node relative to a ray-triangle test.
+const TRAVERSAL_COST: f32 = 1.0;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Hit {
+    pub distance: f32,
+    // Barycentric coordinates of the second and the third vertices.
+    pub u: f32,
+    pub v: f32,
+    // Index of the triangle in the mesh, not in the tree.
+    pub triangle_index: usize,
+}
+
+// Inner nodes have zero count and their children are first and first + 1,
+// leaves own the triangles from first to first + count.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Node {
+    aabb: Aabb3,
+    first: usize,
+    count: usize,
+}
+
+impl Node {
+    #[inline]
+    fn is_leaf(&self) -> bool {
+        return self.count > 0;
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    aabb: Aabb3,
+    count: usize,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    // Ordered like the leaves.
+    triangles: Vec<Triangle>,
+    triangle_indices: Vec<usize>,
+}
+
+impl Bvh {
+    // Every three indices are a triangle of positions.
+    pub fn new(positions: &[Vec3], indices: &[u32]) -> Self {
+        let triangles: Vec<Triangle> = indices
+            .chunks_exact(3)
+            .map(|i| {
+                Triangle::new(
+                    positions[i[0] as usize],
+                    positions[i[1] as usize],
+                    positions[i[2] as usize],
+                )
+            })
+            .collect();
+        let aabbs: Vec<Aabb3> = triangles.iter().map(|t| t.get_aabb()).collect();
+        let centroids: Vec<Vec3> = aabbs.iter().map(|a| a.get_center()).collect();
+        let mut triangle_indices: Vec<usize> = (0..triangles.len()).collect();
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            nodes.reserve(triangles.len() * 2 - 1);
+            nodes.push(Node {
+                aabb: Aabb3::new(),
+                first: 0,
+                count: triangles.len(),
+            });
+            let mut stack = vec![0];
+            while let Some(node_index) = stack.pop() {
+                let (first, count) = {
+                    let node = &mut nodes[node_index];
+                    for &i in &triangle_indices[node.first..node.first + node.count] {
+                        node.aabb.insert_aabb(&aabbs[i]);
+                    }
+                    (node.first, node.count)
+                };
+                let range = &mut triangle_indices[first..first + count];
+                let left_count =
+                    match Self::split(&nodes[node_index].aabb, range, &aabbs, &centroids) {
+                        Some(c) => c,
+                        None => continue,
+                    };
+                let left = nodes.len();
+                nodes.push(Node {
+                    aabb: Aabb3::new(),
+                    first,
+                    count: left_count,
+                });
+                nodes.push(Node {
+                    aabb: Aabb3::new(),
+                    first: first + left_count,
+                    count: count - left_count,
+                });
+                let node = &mut nodes[node_index];
+                node.first = left;
+                node.count = 0;
+                stack.push(left + 1);
+                stack.push(left);
+            }
+        }
+        let triangles = triangle_indices.iter().map(|&i| triangles[i]).collect();
+        return Self {
+            nodes,
+            triangles,
+            triangle_indices,
+        };
+    }
+
+    // Binned SAH, it reorders the range and returns the count of the left
+    // side or None when the node must stay a leaf.
+    fn split(
+        aabb: &Aabb3,
+        range: &mut [usize],
+        aabbs: &[Aabb3],
+        centroids: &[Vec3],
+    ) -> Option<usize> {
+        if range.len() < 2 {
+            return None;
+        }
+        let mut centroids_aabb = Aabb3::new();
+        for &i in range.iter() {
+            centroids_aabb.insert(centroids[i]);
+        }
+        let axis = centroids_aabb.get_longest_axis();
+        let min = centroids_aabb.get_min().to_array()[axis];
+        let extent = centroids_aabb.get_max().to_array()[axis] - min;
+        if extent <= 0.0 {
+            return None;
+        }
+        let scale = BINS_COUNT as f32 / extent;
+        let get_bin = |i: usize| {
+            let b = ((centroids[i].to_array()[axis] - min) * scale) as usize;
+            return b.min(BINS_COUNT - 1);
+        };
+        let mut bins = [Bin {
+            aabb: Aabb3::new(),
+            count: 0,
+        }; BINS_COUNT];
+        for &i in range.iter() {
+            let bin = &mut bins[get_bin(i)];
+            bin.aabb.insert_aabb(&aabbs[i]);
+            bin.count += 1;
+        }
+        // Right side costs are accumulated from the last bin, then the left
+        // side from the first one.
+        let mut right_costs = [0.0; BINS_COUNT];
+        let mut right = Bin {
+            aabb: Aabb3::new(),
+            count: 0,
+        };
+        for b in (1..BINS_COUNT).rev() {
+            right.aabb.insert_aabb(&bins[b].aabb);
+            right.count += bins[b].count;
+            right_costs[b] = right.aabb.get_surface_area() * right.count as f32;
+        }
+        let mut left = Bin {
+            aabb: Aabb3::new(),
+            count: 0,
+        };
+        let mut best_cost = std::f32::MAX;
+        let mut best_bin = 0;
+        for b in 1..BINS_COUNT {
+            left.aabb.insert_aabb(&bins[b - 1].aabb);
+            left.count += bins[b - 1].count;
+            let cost = left.aabb.get_surface_area() * left.count as f32 + right_costs[b];
+            if cost < best_cost {
+                best_cost = cost;
+                best_bin = b;
+            }
+        }
+        let area = aabb.get_surface_area();
+        let split_cost = TRAVERSAL_COST + if area > 0.0 { best_cost / area } else { 0.0 };
+        if range.len() <= MAX_LEAF_TRIANGLES && split_cost >= range.len() as f32 {
+            return None;
+        }
+        let mut left_count = 0;
+        for i in 0..range.len() {
+            if get_bin(range[i]) < best_bin {
+                range.swap(i, left_count);
+                left_count += 1;
+            }
+        }
+        if left_count == 0 || left_count == range.len() {
+            return None;
+        }
+        return Some(left_count);
+    }
+
+    pub fn get_aabb(&self) -> Aabb3 {
+        if self.nodes.is_empty() {
+            return Aabb3::new();
+        }
+        return self.nodes[0].aabb;
+    }
+
+    #[inline]
+    pub fn get_triangles_count(&self) -> usize {
+        return self.triangles.len();
+    }
+
+    // The nearer child is visited first, and the nodes that are farther than
+    // the closest hit so far are skipped.
+    pub fn closest_hit(&self, ray: &Ray3, max_distance: f32) -> Option<Hit> {
+        let mut closest = max_distance;
+        let mut result = None;
+        let mut stack = Vec::with_capacity(64);
+        if let Some(d) = self.intersect_node(0, ray, closest) {
+            stack.push((0, d));
+        }
+        while let Some((node_index, distance)) = stack.pop() {
+            if distance > closest {
+                continue;
+            }
+            let node = &self.nodes[node_index];
+            if node.is_leaf() {
+                for i in node.first..node.first + node.count {
+                    if let Some((t, u, v)) = self.triangles[i].intersect_ray(ray, closest) {
+                        closest = t;
+                        result = Some(Hit {
+                            distance: t,
+                            u,
+                            v,
+                            triangle_index: self.triangle_indices[i],
+                        });
+                    }
+                }
+                continue;
+            }
+            let left = node.first;
+            let right = left + 1;
+            match (
+                self.intersect_node(left, ray, closest),
+                self.intersect_node(right, ray, closest),
+            ) {
+                (Some(l), Some(r)) => {
+                    if l <= r {
+                        stack.push((right, r));
+                        stack.push((left, l));
+                    } else {
+                        stack.push((left, l));
+                        stack.push((right, r));
+                    }
+                }
+                (Some(l), None) => stack.push((left, l)),
+                (None, Some(r)) => stack.push((right, r)),
+                (None, None) => {}
+            }
+        }
+        return result;
+    }
+
+    // Cheaper than closest_hit, it stops at the first hit, e.g. for shadow
+    // and line of sight rays.
+    pub fn any_hit(&self, ray: &Ray3, max_distance: f32) -> bool {
+        let mut stack = Vec::with_capacity(64);
+        if self.intersect_node(0, ray, max_distance).is_some() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.is_leaf() {
+                for t in &self.triangles[node.first..node.first + node.count] {
+                    if t.intersect_ray(ray, max_distance).is_some() {
+                        return true;
+                    }
+                }
+                continue;
+            }
+            for child in node.first..node.first + 2 {
+                if self.intersect_node(child, ray, max_distance).is_some() {
+                    stack.push(child);
+                }
+            }
+        }
+        return false;
+    }
+
+    // Indices of the mesh triangles that overlap the box, in no order.
+    pub fn query_aabb(&self, aabb: &Aabb3) -> Vec<usize> {
+        return self.query(|a| a.intersects_aabb(aabb), |t| t.intersects_aabb(aabb));
+    }
+
+    // Indices of the mesh triangles that overlap the sphere, in no order.
+    pub fn query_sphere(&self, center: Vec3, radius: f32) -> Vec<usize> {
+        return self.query(
+            |a| a.intersects_sphere(center, radius),
+            |t| t.intersects_sphere(center, radius),
+        );
+    }
+
+    fn query<A, T>(&self, aabb_overlaps: A, triangle_overlaps: T) -> Vec<usize>
+    where
+        A: Fn(&Aabb3) -> bool,
+        T: Fn(&Triangle) -> bool,
+    {
+        let mut result = Vec::new();
+        if self.nodes.is_empty() {
+            return result;
+        }
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !aabb_overlaps(&node.aabb) {
+                continue;
+            }
+            if !node.is_leaf() {
+                stack.push(node.first + 1);
+                stack.push(node.first);
+                continue;
+            }
+            for i in node.first..node.first + node.count {
+                if triangle_overlaps(&self.triangles[i]) {
+                    result.push(self.triangle_indices[i]);
+                }
+            }
+        }
+        return result;
+    }
+
+    #[inline]
+    fn intersect_node(&self, node_index: usize, ray: &Ray3, max_distance: f32) -> Option<f32> {
+        if node_index >= self.nodes.len() {
+            return None;
+        }
+        return self.nodes[node_index].aabb.intersect_ray(ray, max_distance);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Deterministic, so the failures can be reproduced.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, min: f32, max: f32) -> f32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let r = (self.0 >> 40) as f32 / (1u64 << 24) as f32;
+            return min + (max - min) * r;
+        }
+
+        fn next_vec3(&mut self, min: f32, max: f32) -> Vec3 {
+            return Vec3::new(
+                self.next(min, max),
+                self.next(min, max),
+                self.next(min, max),
+            );
+        }
+    }
+
+    fn create_soup(random: &mut Random, count: usize) -> (Vec<Vec3>, Vec<u32>) {
+        let mut positions = Vec::new();
+        for _ in 0..count {
+            let center = random.next_vec3(-10.0, 10.0);
+            for _ in 0..3 {
+                positions.push(center + random.next_vec3(-1.5, 1.5));
+            }
+        }
+        let indices = (0..positions.len() as u32).collect();
+        return (positions, indices);
+    }
+
+    fn get_triangles(positions: &[Vec3], indices: &[u32]) -> Vec<Triangle> {
+        return indices
+            .chunks_exact(3)
+            .map(|i| {
+                Triangle::new(
+                    positions[i[0] as usize],
+                    positions[i[1] as usize],
+                    positions[i[2] as usize],
+                )
+            })
+            .collect();
+    }
+
+    #[test]
+    fn structure_test() {
+        let mut random = Random(7);
+        let (positions, indices) = create_soup(&mut random, 500);
+        let bvh = Bvh::new(&positions, &indices);
+        assert_eq!(bvh.get_triangles_count(), 500);
+        let mut seen = vec![0; 500];
+        for node in &bvh.nodes {
+            if node.is_leaf() {
+                for i in node.first..node.first + node.count {
+                    seen[bvh.triangle_indices[i]] += 1;
+                    let aabb = bvh.triangles[i].get_aabb();
+                    let mut union = node.aabb;
+                    union.insert_aabb(&aabb);
+                    assert!(union == node.aabb);
+                }
+            } else {
+                for child in &bvh.nodes[node.first..node.first + 2] {
+                    let mut union = node.aabb;
+                    union.insert_aabb(&child.aabb);
+                    assert!(union == node.aabb);
+                }
+            }
+        }
+        assert!(seen.iter().all(|&s| s == 1));
+        // SAH must have split it well below the triangles count.
+        assert!(bvh.nodes.len() > 1);
+        assert!(bvh.nodes.len() < 2 * 500);
+    }
+
+    #[test]
+    fn ray_test() {
+        let mut random = Random(42);
+        let (positions, indices) = create_soup(&mut random, 400);
+        let bvh = Bvh::new(&positions, &indices);
+        let triangles = get_triangles(&positions, &indices);
+        let mut hits_count = 0;
+        for r in 0..500 {
+            let origin = random.next_vec3(-15.0, 15.0);
+            let target = random.next_vec3(-8.0, 8.0);
+            let ray = Ray3::new(origin, (target - origin).normalized());
+            let max_distance = if r % 5 == 0 { 10.0 } else { std::f32::MAX };
+            let expected = triangles
+                .iter()
+                .filter_map(|t| t.intersect_ray(&ray, max_distance).map(|h| h.0))
+                .fold(None, |c: Option<f32>, t| Some(c.map_or(t, |c| c.min(t))));
+            let hit = bvh.closest_hit(&ray, max_distance);
+            assert_eq!(hit.map(|h| h.distance), expected);
+            if let Some(hit) = hit {
+                hits_count += 1;
+                let t = triangles[hit.triangle_index].intersect_ray(&ray, max_distance);
+                assert_eq!(t, Some((hit.distance, hit.u, hit.v)));
+            }
+            assert_eq!(bvh.any_hit(&ray, max_distance), expected.is_some());
+        }
+        // Enough rays hit, so the comparisons are not all misses.
+        assert!(hits_count > 150);
+    }
+
+    #[test]
+    fn overlap_test() {
+        let mut random = Random(1234);
+        let (positions, indices) = create_soup(&mut random, 300);
+        let bvh = Bvh::new(&positions, &indices);
+        let triangles = get_triangles(&positions, &indices);
+        for _ in 0..200 {
+            let center = random.next_vec3(-12.0, 12.0);
+            let radius = random.next(0.1, 4.0);
+            let aabb = Aabb3::new_with_center_radius(center, radius);
+            let mut found = bvh.query_aabb(&aabb);
+            found.sort();
+            let expected: Vec<usize> = (0..triangles.len())
+                .filter(|&i| triangles[i].intersects_aabb(&aabb))
+                .collect();
+            assert_eq!(found, expected);
+            let mut found = bvh.query_sphere(center, radius);
+            found.sort();
+            let expected: Vec<usize> = (0..triangles.len())
+                .filter(|&i| triangles[i].intersects_sphere(center, radius))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn small_meshes_test() {
+        let empty = Bvh::new(&[], &[]);
+        let ray = Ray3::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0));
+        assert!(empty.get_aabb().is_empty());
+        assert!(empty.closest_hit(&ray, std::f32::MAX).is_none());
+        assert!(!empty.any_hit(&ray, std::f32::MAX));
+        assert!(empty.query_sphere(Vec3::zero(), 100.0).is_empty());
+        // Shared vertices, a quad made of two triangles.
+        let positions = [
+            Vec3::new(-1.0, -1.0, 5.0),
+            Vec3::new(1.0, -1.0, 5.0),
+            Vec3::new(1.0, 1.0, 5.0),
+            Vec3::new(-1.0, 1.0, 5.0),
+        ];
+        let quad = Bvh::new(&positions, &[0, 1, 2, 0, 2, 3]);
+        let hit = quad.closest_hit(&ray, std::f32::MAX).unwrap();
+        assert_eq!(hit.distance, 5.0);
+        let left = Ray3::new(Vec3::new(-0.5, 0.5, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(quad.closest_hit(&left, 10.0).unwrap().triangle_index, 1);
+        assert!(!quad.any_hit(&left, 4.0));
+        let single = Bvh::new(&positions, &[0, 1, 2]);
+        assert!(single.closest_hit(&left, 10.0).is_none());
+        assert_eq!(single.query_aabb(&single.get_aabb()), vec![0]);
+        // Equal centroids can not be split, they stay in one leaf.
+        let stacked = Bvh::new(&positions, &[0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2]);
+        assert_eq!(stacked.nodes.len(), 1);
+        assert_eq!(stacked.query_sphere(Vec3::zero(), 5.0).len(), 5);
+    }
+}
@@ -0,0 +1,791 @@
+use super::quaternion::Quat;
+use super::vector::Vec3;
+
+// Every segment is sampled this many times for the arc length tables, the
+// frames and the coarse pass of the closest point search.
+const SAMPLES_PER_SEGMENT: usize = 32;
+// Iterations of the golden section refinement of the closest point search.
+const CLOSEST_POINT_ITERATIONS: usize = 24;
+// Shorter derivatives are treated as cusps, their tangent is approximated by
+// the chord of the neighbouring points.
+const ZERO_DERIVATIVE_EPSILON: f32 = 1e-12;
+const CUSP_PARAMETER_STEP: f32 = 1e-3;
+
+// It maps the whole curve parameter in [0, 1] to a segment and its local
+// parameter in [0, 1].
+fn locate(t: f32, segments_count: usize) -> (usize, f32) {
+    let t = t.max(0.0).min(1.0) * segments_count as f32;
+    let index = (t as usize).min(segments_count - 1);
+    return (index, t - index as f32);
+}
+
+// The parameter of all curves is in [0, 1] across the whole curve, and the
+// derivatives are with respect to it.
+pub trait Curve {
+    fn get_point(&self, t: f32) -> Vec3;
+    fn get_derivative(&self, t: f32) -> Vec3;
+    fn get_segments_count(&self) -> usize;
+
+    // Its end point is the same as its start point.
+    fn is_closed(&self) -> bool {
+        return false;
+    }
+
+    fn get_tangent(&self, t: f32) -> Vec3 {
+        let d = self.get_derivative(t);
+        if d.length_squared() > ZERO_DERIVATIVE_EPSILON {
+            return d.normalized();
+        }
+        let before = self.get_point((t - CUSP_PARAMETER_STEP).max(0.0));
+        let after = self.get_point((t + CUSP_PARAMETER_STEP).min(1.0));
+        return (after - before).normalized();
+    }
+
+    // A coarse sampling finds the bracket of the closest point and a golden
+    // section search refines it, so it is exact for the curves that have no
+    // two closest candidates inside one sampling step.
+    fn get_closest_parameter(&self, p: Vec3) -> f32 {
+        let samples_count = self.get_segments_count() * SAMPLES_PER_SEGMENT;
+        let distance = |t: f32| (self.get_point(t) - p).length_squared();
+        let mut closest = 0;
+        let mut closest_distance = std::f32::MAX;
+        for i in 0..=samples_count {
+            let d = distance(i as f32 / samples_count as f32);
+            if d < closest_distance {
+                closest_distance = d;
+                closest = i;
+            }
+        }
+        let step = 1.0 / samples_count as f32;
+        let mut a = (closest as f32 - 1.0).max(0.0) * step;
+        let mut b = (closest as f32 + 1.0).min(samples_count as f32) * step;
+        let ratio = (5.0f32.sqrt() - 1.0) * 0.5;
+        let mut c = b - (b - a) * ratio;
+        let mut d = a + (b - a) * ratio;
+        let mut dc = distance(c);
+        let mut dd = distance(d);
+        for _ in 0..CLOSEST_POINT_ITERATIONS {
+            if dc < dd {
+                b = d;
+                d = c;
+                dd = dc;
+                c = b - (b - a) * ratio;
+                dc = distance(c);
+            } else {
+                a = c;
+                c = d;
+                dc = dd;
+                d = a + (b - a) * ratio;
+                dd = distance(d);
+            }
+        }
+        return (a + b) * 0.5;
+    }
+
+    fn get_closest_point(&self, p: Vec3) -> Vec3 {
+        return self.get_point(self.get_closest_parameter(p));
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Polyline {
+    points: Vec<Vec3>,
+}
+
+impl Polyline {
+    // At least two points are needed, a closed polyline returns to its first
+    // point.
+    pub fn new(points: &[Vec3], is_closed: bool) -> Self {
+        let mut points = points.to_vec();
+        if is_closed {
+            points.push(points[0]);
+        }
+        return Self { points };
+    }
+
+    #[inline]
+    pub fn get_points(&self) -> &[Vec3] {
+        return &self.points;
+    }
+}
+
+impl Curve for Polyline {
+    fn get_point(&self, t: f32) -> Vec3 {
+        let (i, s) = locate(t, self.get_segments_count());
+        return self.points[i].lerp(self.points[i + 1], s);
+    }
+
+    fn get_derivative(&self, t: f32) -> Vec3 {
+        let n = self.get_segments_count();
+        let (i, _) = locate(t, n);
+        return (self.points[i + 1] - self.points[i]) * n as f32;
+    }
+
+    #[inline]
+    fn get_segments_count(&self) -> usize {
+        return self.points.len() - 1;
+    }
+
+    fn is_closed(&self) -> bool {
+        return self.points.len() > 2 && self.points[0] == self.points[self.points.len() - 1];
+    }
+
+    // Segments are projected exactly.
+    fn get_closest_parameter(&self, p: Vec3) -> f32 {
+        let n = self.get_segments_count();
+        let mut closest = 0.0;
+        let mut closest_distance = std::f32::MAX;
+        for i in 0..n {
+            let a = self.points[i];
+            let ab = self.points[i + 1] - a;
+            let length_squared = ab.length_squared();
+            let s = if length_squared > 0.0 {
+                ((p - a).dot(ab) / length_squared).max(0.0).min(1.0)
+            } else {
+                0.0
+            };
+            let d = (a + ab * s - p).length_squared();
+            if d < closest_distance {
+                closest_distance = d;
+                closest = (i as f32 + s) / n as f32;
+            }
+        }
+        return closest;
+    }
+}
+
+// Piecewise cubic Bezier, consecutive segments share their end control
+// point, so there are 3n+1 control points for n segments.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct CubicBezier {
+    points: Vec<Vec3>,
+}
+
+impl CubicBezier {
+    pub fn new(points: &[Vec3]) -> Self {
+        return Self {
+            points: points.to_vec(),
+        };
+    }
+
+    #[inline]
+    pub fn get_points(&self) -> &[Vec3] {
+        return &self.points;
+    }
+
+    #[inline]
+    fn get_segment(&self, i: usize) -> (Vec3, Vec3, Vec3, Vec3) {
+        let p = &self.points[i * 3..i * 3 + 4];
+        return (p[0], p[1], p[2], p[3]);
+    }
+}
+
+impl Curve for CubicBezier {
+    fn get_point(&self, t: f32) -> Vec3 {
+        let (i, s) = locate(t, self.get_segments_count());
+        let (p0, p1, p2, p3) = self.get_segment(i);
+        let r = 1.0 - s;
+        return p0 * (r * r * r)
+            + p1 * (3.0 * r * r * s)
+            + p2 * (3.0 * r * s * s)
+            + p3 * (s * s * s);
+    }
+
+    fn get_derivative(&self, t: f32) -> Vec3 {
+        let n = self.get_segments_count();
+        let (i, s) = locate(t, n);
+        let (p0, p1, p2, p3) = self.get_segment(i);
+        let r = 1.0 - s;
+        let d = (p1 - p0) * (3.0 * r * r) + (p2 - p1) * (6.0 * r * s) + (p3 - p2) * (3.0 * s * s);
+        return d * n as f32;
+    }
+
+    #[inline]
+    fn get_segments_count(&self) -> usize {
+        return (self.points.len() - 1) / 3;
+    }
+
+    fn is_closed(&self) -> bool {
+        return self.points[0] == self.points[self.get_segments_count() * 3];
+    }
+}
+
+// Cubic Hermite spline, the tangents are the derivatives of the segments
+// with respect to their own local parameter.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Hermite {
+    points: Vec<Vec3>,
+    tangents: Vec<Vec3>,
+}
+
+impl Hermite {
+    // Both of them must have the same number of elements, at least two.
+    pub fn new(points: &[Vec3], tangents: &[Vec3]) -> Self {
+        return Self {
+            points: points.to_vec(),
+            tangents: tangents.to_vec(),
+        };
+    }
+
+    #[inline]
+    pub fn get_points(&self) -> &[Vec3] {
+        return &self.points;
+    }
+
+    #[inline]
+    pub fn get_tangents(&self) -> &[Vec3] {
+        return &self.tangents;
+    }
+}
+
+impl Curve for Hermite {
+    fn get_point(&self, t: f32) -> Vec3 {
+        let (i, s) = locate(t, self.get_segments_count());
+        let s2 = s * s;
+        let s3 = s2 * s;
+        return self.points[i] * (2.0 * s3 - 3.0 * s2 + 1.0)
+            + self.tangents[i] * (s3 - 2.0 * s2 + s)
+            + self.points[i + 1] * (3.0 * s2 - 2.0 * s3)
+            + self.tangents[i + 1] * (s3 - s2);
+    }
+
+    fn get_derivative(&self, t: f32) -> Vec3 {
+        let n = self.get_segments_count();
+        let (i, s) = locate(t, n);
+        let s2 = s * s;
+        let d = (self.points[i + 1] - self.points[i]) * (6.0 * s - 6.0 * s2)
+            + self.tangents[i] * (3.0 * s2 - 4.0 * s + 1.0)
+            + self.tangents[i + 1] * (3.0 * s2 - 2.0 * s);
+        return d * n as f32;
+    }
+
+    #[inline]
+    fn get_segments_count(&self) -> usize {
+        return self.points.len() - 1;
+    }
+
+    fn is_closed(&self) -> bool {
+        let last = self.points.len() - 1;
+        return last > 1
+            && self.points[0] == self.points[last]
+            && self.tangents[0] == self.tangents[last];
+    }
+}
+
+// Uniform Catmull-Rom spline that passes through all of its points, it is a
+// Hermite spline with the tangents taken from the neighbouring points.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct CatmullRom {
+    hermite: Hermite,
+}
+
+impl CatmullRom {
+    // At least two points are needed, the open ends take the tangent of their
+    // only segment.
+    pub fn new(points: &[Vec3], is_closed: bool) -> Self {
+        let n = points.len();
+        let mut tangents = Vec::with_capacity(n + 1);
+        for i in 0..n {
+            let (before, after) = if is_closed {
+                (points[(i + n - 1) % n], points[(i + 1) % n])
+            } else if i == 0 {
+                (points[0] * 2.0 - points[1], points[1])
+            } else if i == n - 1 {
+                (points[i - 1], points[i] * 2.0 - points[i - 1])
+            } else {
+                (points[i - 1], points[i + 1])
+            };
+            tangents.push((after - before) * 0.5);
+        }
+        let mut points = points.to_vec();
+        if is_closed {
+            points.push(points[0]);
+            tangents.push(tangents[0]);
+        }
+        return Self {
+            hermite: Hermite { points, tangents },
+        };
+    }
+
+    #[inline]
+    pub fn get_hermite(&self) -> &Hermite {
+        return &self.hermite;
+    }
+}
+
+impl Curve for CatmullRom {
+    #[inline]
+    fn get_point(&self, t: f32) -> Vec3 {
+        return self.hermite.get_point(t);
+    }
+
+    #[inline]
+    fn get_derivative(&self, t: f32) -> Vec3 {
+        return self.hermite.get_derivative(t);
+    }
+
+    #[inline]
+    fn get_segments_count(&self) -> usize {
+        return self.hermite.get_segments_count();
+    }
+
+    fn is_closed(&self) -> bool {
+        return self.hermite.is_closed();
+    }
+}
+
+// Cumulative lengths of the curve at uniformly spaced parameters, the chords
+// of the samples approximate the arcs.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct ArcLengthTable {
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new(curve: &dyn Curve) -> Self {
+        return Self::new_with_samples(curve, curve.get_segments_count() * SAMPLES_PER_SEGMENT);
+    }
+
+    pub fn new_with_samples(curve: &dyn Curve, samples_count: usize) -> Self {
+        let mut lengths = Vec::with_capacity(samples_count + 1);
+        let mut length = 0.0;
+        let mut previous = curve.get_point(0.0);
+        lengths.push(0.0);
+        for i in 1..=samples_count {
+            let p = curve.get_point(i as f32 / samples_count as f32);
+            length += (p - previous).length();
+            lengths.push(length);
+            previous = p;
+        }
+        return Self { lengths };
+    }
+
+    #[inline]
+    pub fn get_length(&self) -> f32 {
+        return self.lengths[self.lengths.len() - 1];
+    }
+
+    pub fn get_distance(&self, t: f32) -> f32 {
+        let (i, s) = locate(t, self.lengths.len() - 1);
+        return self.lengths[i] + (self.lengths[i + 1] - self.lengths[i]) * s;
+    }
+
+    // The reparameterization, it returns the curve parameter of the point
+    // that is at the distance along the curve.
+    pub fn get_parameter(&self, distance: f32) -> f32 {
+        let n = self.lengths.len() - 1;
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        if distance >= self.get_length() {
+            return 1.0;
+        }
+        let i = match self
+            .lengths
+            .binary_search_by(|l| l.partial_cmp(&distance).unwrap())
+        {
+            Ok(i) => return i as f32 / n as f32,
+            Err(i) => i - 1,
+        };
+        let segment = self.lengths[i + 1] - self.lengths[i];
+        let s = if segment > 0.0 {
+            (distance - self.lengths[i]) / segment
+        } else {
+            0.0
+        };
+        return (i as f32 + s) / n as f32;
+    }
+}
+
+// Tangent points forward, normal is the up and binormal is the right of the
+// frame.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Frame {
+    pub position: Vec3,
+    pub tangent: Vec3,
+    pub normal: Vec3,
+    pub binormal: Vec3,
+}
+
+impl Frame {
+    // The rotation that turns the -z forward and y up objects, like cameras,
+    // along the frame.
+    pub fn get_orientation(&self) -> Quat {
+        return Quat::from_axes(self.binormal, self.normal, -self.tangent);
+    }
+}
+
+// It returns the frames at the uniformly spaced distances along the curve.
+// They are carried along the curve by parallel transport with the double
+// reflection method of Wang et al., so they do not twist around the tangent.
+// The first normal is the up vector made perpendicular to the tangent, and
+// for closed curves the remaining twist at the end is distributed along the
+// whole curve so the frames meet.
+pub fn compute_frames(
+    curve: &dyn Curve,
+    arc_length: &ArcLengthTable,
+    frames_count: usize,
+    up: Vec3,
+) -> Vec<Frame> {
+    let frames_count = frames_count.max(2);
+    let length = arc_length.get_length();
+    let mut frames = Vec::with_capacity(frames_count);
+    let t = 0.0;
+    let position = curve.get_point(t);
+    let tangent = curve.get_tangent(t);
+    let mut normal = up - tangent * tangent.dot(up);
+    if normal.length_squared() < ZERO_DERIVATIVE_EPSILON {
+        let axis = if tangent.x().abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        normal = tangent.cross(axis);
+    }
+    let normal = normal.normalized();
+    frames.push(Frame {
+        position,
+        tangent,
+        normal,
+        binormal: tangent.cross(normal),
+    });
+    for i in 1..frames_count {
+        let previous = frames[i - 1];
+        let distance = length * i as f32 / (frames_count - 1) as f32;
+        let t = arc_length.get_parameter(distance);
+        let position = curve.get_point(t);
+        let tangent = curve.get_tangent(t);
+        let mut normal = previous.normal;
+        let v1 = position - previous.position;
+        let c1 = v1.length_squared();
+        if c1 > 0.0 {
+            let r = normal - v1 * (2.0 * v1.dot(normal) / c1);
+            let tr = previous.tangent - v1 * (2.0 * v1.dot(previous.tangent) / c1);
+            let v2 = tangent - tr;
+            let c2 = v2.length_squared();
+            normal = if c2 > 0.0 {
+                r - v2 * (2.0 * v2.dot(r) / c2)
+            } else {
+                r
+            };
+        }
+        let normal = (normal - tangent * tangent.dot(normal)).normalized();
+        frames.push(Frame {
+            position,
+            tangent,
+            normal,
+            binormal: tangent.cross(normal),
+        });
+    }
+    if curve.is_closed() {
+        let first = frames[0];
+        let last = frames[frames_count - 1];
+        let twist = last
+            .binormal
+            .dot(first.normal)
+            .atan2(last.normal.dot(first.normal));
+        for (i, frame) in frames.iter_mut().enumerate() {
+            let angle = twist * i as f32 / (frames_count - 1) as f32;
+            let (sin, cos) = angle.sin_cos();
+            let normal = frame.normal * cos + frame.binormal * sin;
+            frame.normal = normal;
+            frame.binormal = frame.tangent.cross(normal);
+        }
+    }
+    return frames;
+}
+
+// A curve that is ready for the constant speed motion, it keeps the arc
+// length table and the frames of the curve.
+pub struct Path {
+    curve: Box<dyn Curve + Send + Sync>,
+    arc_length: ArcLengthTable,
+    frames: Vec<Frame>,
+}
+
+impl Path {
+    pub fn new(curve: Box<dyn Curve + Send + Sync>, up: Vec3) -> Self {
+        let arc_length = ArcLengthTable::new(&*curve);
+        let frames_count = curve.get_segments_count() * SAMPLES_PER_SEGMENT + 1;
+        let frames = compute_frames(&*curve, &arc_length, frames_count, up);
+        return Self {
+            curve,
+            arc_length,
+            frames,
+        };
+    }
+
+    #[inline]
+    pub fn get_curve(&self) -> &dyn Curve {
+        return &*self.curve;
+    }
+
+    #[inline]
+    pub fn get_arc_length(&self) -> &ArcLengthTable {
+        return &self.arc_length;
+    }
+
+    #[inline]
+    pub fn get_length(&self) -> f32 {
+        return self.arc_length.get_length();
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        return self.curve.is_closed();
+    }
+
+    #[inline]
+    pub fn get_point(&self, distance: f32) -> Vec3 {
+        return self
+            .curve
+            .get_point(self.arc_length.get_parameter(distance));
+    }
+
+    // The position and the tangent are exact, the normal is interpolated
+    // between the neighbouring precomputed frames.
+    pub fn get_frame(&self, distance: f32) -> Frame {
+        let t = self.arc_length.get_parameter(distance);
+        let position = self.curve.get_point(t);
+        let tangent = self.curve.get_tangent(t);
+        let n = self.frames.len() - 1;
+        let length = self.get_length();
+        let f = if length > 0.0 {
+            (distance / length).max(0.0).min(1.0) * n as f32
+        } else {
+            0.0
+        };
+        let i = (f as usize).min(n - 1);
+        let normal = self.frames[i]
+            .normal
+            .lerp(self.frames[i + 1].normal, f - i as f32);
+        let normal = (normal - tangent * tangent.dot(normal)).normalized();
+        return Frame {
+            position,
+            tangent,
+            normal,
+            binormal: tangent.cross(normal),
+        };
+    }
+
+    // The distance along the path of the point closest to p.
+    pub fn get_closest_distance(&self, p: Vec3) -> f32 {
+        return self
+            .arc_length
+            .get_distance(self.curve.get_closest_parameter(p));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_near(a: Vec3, b: Vec3, epsilon: f32) -> bool {
+        return (a - b).length() <= epsilon;
+    }
+
+    fn create_square() -> Vec<Vec3> {
+        return vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, -4.0),
+            Vec3::new(0.0, 0.0, -4.0),
+        ];
+    }
+
+    // The derivative must agree with the central differences of the points.
+    fn check_derivative(curve: &dyn Curve) {
+        let h = 1e-3;
+        for i in 0..40 {
+            let t = (i as f32 + 0.5) / 40.0;
+            let numeric = (curve.get_point(t + h) - curve.get_point(t - h)) * (0.5 / h);
+            let d = curve.get_derivative(t);
+            assert!(is_near(numeric, d, 0.02 * d.length().max(1.0)));
+        }
+    }
+
+    #[test]
+    fn curves_test() {
+        let square = create_square();
+        let polyline = Polyline::new(&square, true);
+        assert_eq!(polyline.get_segments_count(), 4);
+        assert!(polyline.is_closed());
+        assert!(polyline.get_point(0.375) == Vec3::new(4.0, 0.0, -2.0));
+        assert!(polyline.get_point(1.0) == square[0]);
+        check_derivative(&polyline);
+
+        let line = [
+            Vec3::zero(),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 2.0, 0.0),
+        ];
+        let bezier = CubicBezier::new(&[
+            line[0],
+            line[0].lerp(line[1], 1.0 / 3.0),
+            line[0].lerp(line[1], 2.0 / 3.0),
+            line[1],
+            line[1].lerp(line[2], 1.0 / 3.0),
+            line[1].lerp(line[2], 2.0 / 3.0),
+            line[2],
+        ]);
+        assert_eq!(bezier.get_segments_count(), 2);
+        assert!(!bezier.is_closed());
+        assert!(is_near(
+            bezier.get_point(0.25),
+            Vec3::new(0.5, 0.5, 0.0),
+            1e-5
+        ));
+        check_derivative(&bezier);
+
+        let catmull_rom = CatmullRom::new(&square, false);
+        for (i, p) in square.iter().enumerate() {
+            assert!(is_near(catmull_rom.get_point(i as f32 / 3.0), *p, 1e-5));
+        }
+        assert!(!catmull_rom.is_closed());
+        check_derivative(&catmull_rom);
+        let closed = CatmullRom::new(&square, true);
+        assert!(closed.is_closed());
+        assert!(is_near(
+            closed.get_tangent(0.0),
+            closed.get_tangent(1.0),
+            1e-5
+        ));
+        check_derivative(&closed);
+
+        let hermite = Hermite::new(
+            &[Vec3::zero(), Vec3::new(1.0, 0.0, 0.0)],
+            &[Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0)],
+        );
+        assert!(is_near(
+            hermite.get_tangent(0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1e-6
+        ));
+        assert!(is_near(
+            hermite.get_point(0.5),
+            Vec3::new(0.5, 0.25, 0.0),
+            1e-6
+        ));
+        check_derivative(&hermite);
+
+        // A cusp, the derivative vanishes at its start.
+        let cusp = CubicBezier::new(&[
+            Vec3::zero(),
+            Vec3::zero(),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ]);
+        assert!(is_near(
+            cusp.get_tangent(0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            1e-2
+        ));
+    }
+
+    #[test]
+    fn arc_length_test() {
+        let polyline = Polyline::new(&create_square(), true);
+        let table = ArcLengthTable::new(&polyline);
+        assert!((table.get_length() - 16.0).abs() < 1e-4);
+        assert!((table.get_parameter(6.0) - 0.375).abs() < 1e-5);
+        assert!((table.get_distance(0.375) - 6.0).abs() < 1e-4);
+        assert_eq!(table.get_parameter(-1.0), 0.0);
+        assert_eq!(table.get_parameter(20.0), 1.0);
+
+        // The parameter of a quarter circle is not proportional to its length
+        // but its reparameterization is.
+        let k = 0.552_284_8;
+        let arc = CubicBezier::new(&[
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, k, 0.0),
+            Vec3::new(k, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ]);
+        let table = ArcLengthTable::new(&arc);
+        let quarter = std::f32::consts::FRAC_PI_2;
+        assert!((table.get_length() - quarter).abs() < 1e-3);
+        for i in 0..=10 {
+            let angle = quarter * i as f32 / 10.0;
+            let p = arc.get_point(table.get_parameter(angle));
+            let expected = Vec3::new(angle.cos(), angle.sin(), 0.0);
+            assert!(is_near(p, expected, 2e-3));
+        }
+    }
+
+    #[test]
+    fn closest_point_test() {
+        let square = create_square();
+        let polyline = Polyline::new(&square, false);
+        let p = polyline.get_closest_point(Vec3::new(5.0, 1.0, -1.0));
+        assert!(is_near(p, Vec3::new(4.0, 0.0, -1.0), 1e-5));
+        let catmull_rom = CatmullRom::new(&square, true);
+        let target = Vec3::new(6.0, 2.0, -2.5);
+        let t = catmull_rom.get_closest_parameter(target);
+        let closest = (catmull_rom.get_point(t) - target).length();
+        for i in 0..=1000 {
+            let d = (catmull_rom.get_point(i as f32 / 1000.0) - target).length();
+            assert!(closest <= d + 1e-4);
+        }
+        // The error must be perpendicular to the curve.
+        let error = catmull_rom.get_point(t) - target;
+        assert!(error.normalized().dot(catmull_rom.get_tangent(t)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn frames_test() {
+        // A planar wave, its Frenet frame flips at the inflections but the
+        // parallel transported one must stay on the up vector.
+        let mut points = Vec::new();
+        for i in 0..=24 {
+            let a = i as f32 * 0.5;
+            points.push(Vec3::new(a * 2.0, 0.0, a.sin() * 3.0));
+        }
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let path = Path::new(Box::new(CatmullRom::new(&points, false)), up);
+        let length = path.get_length();
+        for i in 0..=50 {
+            let f = path.get_frame(length * i as f32 / 50.0);
+            assert!((f.tangent.length() - 1.0).abs() < 1e-4);
+            assert!((f.normal.length() - 1.0).abs() < 1e-4);
+            assert!(f.tangent.dot(f.normal).abs() < 1e-4);
+            assert!(f.normal.dot(up) > 0.999);
+            let q = f.get_orientation();
+            assert!(is_near(
+                q.rotate(Vec3::new(0.0, 0.0, -1.0)),
+                f.tangent,
+                1e-4
+            ));
+            assert!(is_near(q.rotate(Vec3::new(0.0, 1.0, 0.0)), f.normal, 1e-4));
+            assert!(is_near(
+                q.rotate(Vec3::new(1.0, 0.0, 0.0)),
+                f.binormal,
+                1e-4
+            ));
+        }
+
+        // A tilted closed loop, the frames must meet at its ends.
+        let mut points = Vec::new();
+        for i in 0..8 {
+            let a = i as f32 * std::f32::consts::PI / 4.0;
+            points.push(Vec3::new(
+                a.cos() * 3.0,
+                a.sin() * 2.0,
+                a.sin() * 3.0 + a.cos(),
+            ));
+        }
+        let path = Path::new(Box::new(CatmullRom::new(&points, true)), up);
+        assert!(path.is_closed());
+        let start = path.get_frame(0.0);
+        let end = path.get_frame(path.get_length());
+        assert!(is_near(start.normal, end.normal, 1e-3));
+        let distance = path.get_length() * 0.3;
+        let p = path.get_point(distance);
+        assert!((path.get_closest_distance(p) - distance).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,347 @@
+use super::aabbox::Aabb3;
+use super::matrix::Mat4;
+use super::vector::{Vec3, Vec4};
+
+// Planes with shorter normals are dropped, e.g. the far plane of an infinite
+// perspective.
+const DEGENERATE_PLANE_EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Intersection {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+// Points with positive distance are on the side the normal points to.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Plane {
+    normal: Vec3,
+    distance: f32,
+}
+
+impl Plane {
+    // Normal does not need to be normalized.
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        let inv = 1.0 / normal.length();
+        return Self {
+            normal: normal * inv,
+            distance: distance * inv,
+        };
+    }
+
+    pub fn new_with_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalized();
+        return Self {
+            normal,
+            distance: -normal.dot(point),
+        };
+    }
+
+    #[inline]
+    pub fn get_normal(&self) -> Vec3 {
+        return self.normal;
+    }
+
+    #[inline]
+    pub fn get_distance(&self) -> f32 {
+        return self.distance;
+    }
+
+    #[inline]
+    pub fn get_signed_distance(&self, p: Vec3) -> f32 {
+        return self.normal.dot(p) + self.distance;
+    }
+}
+
+// The inside of the frustum is on the positive side of all the planes.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Frustum {
+    planes: [Plane; 6],
+    planes_count: usize,
+}
+
+impl Frustum {
+    pub fn new_with_planes(planes: &[Plane]) -> Self {
+        let mut myself = Self {
+            planes: [Plane::new_with_point_normal(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)); 6],
+            planes_count: 0,
+        };
+        for p in planes.iter().take(6) {
+            myself.planes[myself.planes_count] = *p;
+            myself.planes_count += 1;
+        }
+        return myself;
+    }
+
+    // Gribb-Hartmann extraction for a clip space with 0 <= z <= w like Vulkan,
+    // it works for perspective, orthographic, reversed depth and oblique
+    // projections.
+    pub fn new_with_view_projection(m: &Mat4) -> Self {
+        let t = m.transpose();
+        let rows = [t.get_col(0), t.get_col(1), t.get_col(2), t.get_col(3)];
+        let equations: [Vec4; 6] = [
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] + rows[1],
+            rows[3] - rows[1],
+            rows[2],
+            rows[3] - rows[2],
+        ];
+        let mut planes = Vec::with_capacity(6);
+        for e in &equations {
+            let normal = e.truncate();
+            if normal.length() < DEGENERATE_PLANE_EPSILON {
+                continue;
+            }
+            planes.push(Plane::new(normal, e.w()));
+        }
+        return Self::new_with_planes(&planes);
+    }
+
+    #[inline]
+    pub fn get_planes(&self) -> &[Plane] {
+        return &self.planes[..self.planes_count];
+    }
+
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Intersection {
+        let mut result = Intersection::Inside;
+        for p in self.get_planes() {
+            let d = p.get_signed_distance(center);
+            if d < -radius {
+                return Intersection::Outside;
+            }
+            if d < radius {
+                result = Intersection::Intersecting;
+            }
+        }
+        return result;
+    }
+
+    // It is conservative, boxes near the corners of the frustum may be
+    // reported intersecting while they are outside.
+    pub fn intersect_aabb(&self, aabb: &Aabb3) -> Intersection {
+        if aabb.is_empty() {
+            return Intersection::Outside;
+        }
+        let center = aabb.get_center();
+        let extent = (aabb.get_size() * 0.5).to_array();
+        let mut result = Intersection::Inside;
+        for p in self.get_planes() {
+            let n = p.get_normal().to_array();
+            let r = extent[0] * n[0].abs() + extent[1] * n[1].abs() + extent[2] * n[2].abs();
+            let d = p.get_signed_distance(center);
+            if d < -r {
+                return Intersection::Outside;
+            }
+            if d < r {
+                result = Intersection::Intersecting;
+            }
+        }
+        return result;
+    }
+
+    #[inline]
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        return self.intersect_sphere(center, radius) != Intersection::Outside;
+    }
+
+    #[inline]
+    pub fn intersects_aabb(&self, aabb: &Aabb3) -> bool {
+        return self.intersect_aabb(aabb) != Intersection::Outside;
+    }
+
+    // A step of the hierarchical culling, the subtree box must contain the
+    // node box and the boxes of all of its descendants. Parent is the result
+    // of the parent node, and Intersecting for the roots. It returns the
+    // visibility of the node and the value its children must receive, the
+    // descendants of an inside or an outside subtree are not tested.
+    pub fn cull_node(
+        &self,
+        parent: Intersection,
+        aabb: &Aabb3,
+        subtree_aabb: &Aabb3,
+    ) -> (bool, Intersection) {
+        match parent {
+            Intersection::Outside => return (false, Intersection::Outside),
+            Intersection::Inside => return (true, Intersection::Inside),
+            Intersection::Intersecting => {}
+        }
+        return match self.intersect_aabb(subtree_aabb) {
+            Intersection::Outside => (false, Intersection::Outside),
+            Intersection::Inside => (true, Intersection::Inside),
+            Intersection::Intersecting => (self.intersects_aabb(aabb), Intersection::Intersecting),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::{Deg, Matrix4, Point3, Vector3};
+    use std::cell::Cell;
+
+    // Moves the cgmath projections to the Vulkan clip space, y is flipped
+    // and z goes from [-w, w] to [0, w].
+    fn to_vulkan(m: Matrix4<f32>) -> Mat4 {
+        let correction = Matrix4::new(
+            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
+        );
+        return Mat4::from(correction * m);
+    }
+
+    // Looks from (0, 0, 10) toward the origin.
+    fn create_view() -> Matrix4<f32> {
+        return Matrix4::look_at_rh(
+            Point3::new(0.0, 0.0, 10.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vector3::unit_y(),
+        );
+    }
+
+    fn create_perspective() -> Frustum {
+        let p = cgmath::perspective(Deg(90.0), 1.0, 1.0, 100.0);
+        return Frustum::new_with_view_projection(&to_vulkan(p * create_view()));
+    }
+
+    #[test]
+    fn perspective_test() {
+        let f = create_perspective();
+        assert_eq!(f.get_planes().len(), 6);
+        let inside = f.intersect_sphere(Vec3::zero(), 1.0);
+        assert!(inside == Intersection::Inside);
+        // Behind the camera and behind the far plane.
+        assert!(f.intersect_sphere(Vec3::new(0.0, 0.0, 12.0), 1.0) == Intersection::Outside);
+        assert!(f.intersect_sphere(Vec3::new(0.0, 0.0, -90.5), 1.0) == Intersection::Intersecting);
+        assert!(f.intersect_sphere(Vec3::new(0.0, 0.0, -88.0), 1.0) == Intersection::Inside);
+        // The side planes are at 45 degrees, at the origin they are 10 away.
+        assert!(f.intersect_sphere(Vec3::new(10.0, 0.0, 0.0), 0.5) == Intersection::Intersecting);
+        assert!(f.intersect_sphere(Vec3::new(11.0, 0.0, 0.0), 0.5) == Intersection::Outside);
+        assert!(f.intersect_sphere(Vec3::new(0.0, -11.0, 0.0), 0.5) == Intersection::Outside);
+        let b = Aabb3::new_with_center_radius(Vec3::zero(), 1.0);
+        assert!(f.intersect_aabb(&b) == Intersection::Inside);
+        let b = Aabb3::new_with_center_radius(Vec3::new(10.0, 0.0, 0.0), 1.0);
+        assert!(f.intersect_aabb(&b) == Intersection::Intersecting);
+        let b = Aabb3::new_with_center_radius(Vec3::new(13.0, 0.0, 0.0), 1.0);
+        assert!(f.intersect_aabb(&b) == Intersection::Outside);
+        // A box that contains the whole frustum.
+        let b = Aabb3::new_with_center_radius(Vec3::zero(), 1000.0);
+        assert!(f.intersect_aabb(&b) == Intersection::Intersecting);
+        assert!(f.intersect_aabb(&Aabb3::new()) == Intersection::Outside);
+    }
+
+    #[test]
+    fn other_projections_test() {
+        let o = cgmath::ortho(-2.0, 2.0, -1.0, 1.0, 1.0, 20.0);
+        let f = Frustum::new_with_view_projection(&to_vulkan(o * create_view()));
+        assert_eq!(f.get_planes().len(), 6);
+        assert!(f.intersects_sphere(Vec3::new(1.9, 0.0, 0.0), 0.05));
+        assert!(!f.intersects_sphere(Vec3::new(2.1, 0.0, 0.0), 0.05));
+        assert!(!f.intersects_sphere(Vec3::new(0.0, 1.1, 0.0), 0.05));
+        assert!(!f.intersects_sphere(Vec3::new(0.0, 0.0, -10.5), 0.05));
+        // Infinite far plane, its degenerate plane is dropped.
+        let p = Matrix4::new(
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0, -2.0, 0.0,
+        );
+        let f = Frustum::new_with_view_projection(&to_vulkan(p * create_view()));
+        assert_eq!(f.get_planes().len(), 5);
+        assert!(f.intersect_sphere(Vec3::new(0.0, 0.0, -1e6), 1.0) == Intersection::Inside);
+        assert!(f.intersect_sphere(Vec3::new(0.0, 0.0, 12.0), 1.0) == Intersection::Outside);
+        // Reversed depth keeps the same volume.
+        let reverse = Matrix4::new(
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0,
+        );
+        let p = cgmath::perspective(Deg(90.0), 1.0, 1.0, 100.0);
+        let f = Frustum::new_with_view_projection(
+            &(Mat4::from(reverse) * to_vulkan(p * create_view())),
+        );
+        let expected = create_perspective();
+        for &z in &[12.0, 8.5, 0.0, -88.0, -95.0] {
+            let c = Vec3::new(0.0, 0.0, z);
+            assert!(f.intersect_sphere(c, 1.0) == expected.intersect_sphere(c, 1.0));
+        }
+    }
+
+    struct Node {
+        aabb: Aabb3,
+        subtree_aabb: Aabb3,
+        is_visible: bool,
+        children: Vec<Node>,
+    }
+
+    impl Node {
+        fn new(center: Vec3, children: Vec<Node>) -> Self {
+            let aabb = Aabb3::new_with_center_radius(center, 0.5);
+            let mut subtree_aabb = aabb;
+            for c in &children {
+                subtree_aabb.insert_aabb(&c.subtree_aabb);
+            }
+            return Self {
+                aabb,
+                subtree_aabb,
+                is_visible: false,
+                children,
+            };
+        }
+
+        fn cull(&mut self, f: &Frustum, parent: Intersection, tests: &Cell<usize>) {
+            if parent == Intersection::Intersecting {
+                tests.set(tests.get() + 1);
+            }
+            let (is_visible, intersection) = f.cull_node(parent, &self.aabb, &self.subtree_aabb);
+            self.is_visible = is_visible;
+            for c in &mut self.children {
+                c.cull(f, intersection, tests);
+            }
+        }
+
+        fn check(&self, f: &Frustum) {
+            assert_eq!(self.is_visible, f.intersects_aabb(&self.aabb));
+            for c in &self.children {
+                c.check(f);
+            }
+        }
+    }
+
+    #[test]
+    fn hierarchy_test() {
+        let f = create_perspective();
+        let leaf = |x, y, z| Node::new(Vec3::new(x, y, z), Vec::new());
+        let mut root = Node::new(
+            Vec3::zero(),
+            vec![
+                // Fully inside, its children must not be tested.
+                Node::new(
+                    Vec3::new(1.0, 0.0, 0.0),
+                    vec![leaf(2.0, 0.0, 0.0), leaf(0.0, 2.0, 0.0)],
+                ),
+                // Fully outside.
+                Node::new(
+                    Vec3::new(50.0, 0.0, 0.0),
+                    vec![leaf(60.0, 0.0, 0.0), leaf(55.0, 1.0, 0.0)],
+                ),
+                // Outside node with a visible child.
+                Node::new(
+                    Vec3::new(0.0, 0.0, 20.0),
+                    vec![leaf(0.0, 0.0, 0.0), leaf(0.0, 0.0, 30.0)],
+                ),
+            ],
+        );
+        for c in &mut root.children {
+            c.is_visible = true;
+            for c in &mut c.children {
+                c.is_visible = true;
+            }
+        }
+        let tests = Cell::new(0);
+        root.cull(&f, Intersection::Intersecting, &tests);
+        root.check(&f);
+        // Root, the three children and the two children of the last one.
+        assert_eq!(tests.get(), 6);
+        assert!(!root.children[2].is_visible);
+        assert!(root.children[2].children[0].is_visible);
+    }
+}
@@ -1,692 +1,516 @@
-use std::ops::{Mul, MulAssign};
-use std::sync::Arc;
-use super::number::{Float, Number};
-use super::vector::Vec3;
-use super::super::system::file::File;
-use super::super::util::cell::DebugCell;
-
-#[repr(simd)]
-pub struct SMat4x4D(
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-    pub f64,
-);
-
-#[repr(simd)]
-pub struct SMat4x4F(
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-);
-
-// column major matrix
+use super::quaternion::Quat;
+use super::simd::{shuffle_mask, F32x4};
+use super::vector::{Vec3, Vec4};
+use std::ops::Mul;
+
+// Column major, it has the layout of mat4 in std140 and std430 blocks.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
-pub struct Mat4x4<E>
-where
-    E: Number,
-{
-    pub data: [[E; 4]; 4],
+pub struct Mat4 {
+    cols: [F32x4; 4],
 }
 
-impl<E> Mat4x4<E>
-where
-    E: Float,
-{
-    pub fn ident() -> Mat4x4<E> {
-        Mat4x4 {
-            data: [
-                [E::new(1.0), E::new(0.0), E::new(0.0), E::new(0.0)],
-                [E::new(0.0), E::new(1.0), E::new(0.0), E::new(0.0)],
-                [E::new(0.0), E::new(0.0), E::new(1.0), E::new(0.0)],
-                [E::new(0.0), E::new(0.0), E::new(0.0), E::new(1.0)],
+impl Mat4 {
+    #[inline]
+    pub fn from_cols(x: Vec4, y: Vec4, z: Vec4, w: Vec4) -> Self {
+        return Self {
+            cols: [x.0, y.0, z.0, w.0],
+        };
+    }
+
+    pub fn from_cols_array(a: &[f32; 16]) -> Self {
+        return Self {
+            cols: [
+                F32x4::new(a[0], a[1], a[2], a[3]),
+                F32x4::new(a[4], a[5], a[6], a[7]),
+                F32x4::new(a[8], a[9], a[10], a[11]),
+                F32x4::new(a[12], a[13], a[14], a[15]),
             ],
-        }
+        };
     }
 
-    pub fn zero() -> Mat4x4<E> {
-        Mat4x4 {
-            data: [[E::new(0.0); 4]; 4],
+    pub fn to_cols_array(&self) -> [f32; 16] {
+        let mut a = [0.0; 16];
+        for (i, c) in self.cols.iter().enumerate() {
+            a[i * 4..i * 4 + 4].copy_from_slice(&c.to_array());
         }
+        return a;
     }
 
-    pub fn rotation(d: E, v: &Vec3<E>) -> Mat4x4<E> {
-        let sinus: E = E::new(d.to().sin());
-        let cosinus: E = E::new(d.to().cos());
-        let oneminuscos = E::new(1.0) - cosinus;
-        let w = v;
-        let wx2 = w.x * w.x;
-        let wxy = w.x * w.y;
-        let wxz = w.x * w.z;
-        let wy2 = w.y * w.y;
-        let wyz = w.y * w.z;
-        let wz2 = w.z * w.z;
-        let wxyonemincos = wxy * oneminuscos;
-        let wxzonemincos = wxz * oneminuscos;
-        let wyzonemincos = wyz * oneminuscos;
-        let wxsin = w.x * sinus;
-        let wysin = w.y * sinus;
-        let wzsin = w.z * sinus;
-        Mat4x4 {
-            data: [
-                [
-                    cosinus + (wx2 * oneminuscos),
-                    wxyonemincos + wzsin,
-                    wxzonemincos - wysin,
-                    E::new(0.0),
-                ],
-                [
-                    wxyonemincos - wzsin,
-                    cosinus + (wy2 * oneminuscos),
-                    wxsin + wyzonemincos,
-                    E::new(0.0),
-                ],
-                [
-                    wysin + wxzonemincos,
-                    wyzonemincos - wxsin,
-                    cosinus + (wz2 * oneminuscos),
-                    E::new(0.0),
-                ],
-                [E::new(0.0), E::new(0.0), E::new(0.0), E::new(1.0)],
+    pub fn identity() -> Self {
+        return Self {
+            cols: [
+                F32x4::new(1.0, 0.0, 0.0, 0.0),
+                F32x4::new(0.0, 1.0, 0.0, 0.0),
+                F32x4::new(0.0, 0.0, 1.0, 0.0),
+                F32x4::new(0.0, 0.0, 0.0, 1.0),
             ],
-        }
+        };
     }
 
-    pub fn translate(&mut self, v: &Vec3<E>) {
-        self.data[3][0] += v.x;
-        self.data[3][1] += v.y;
-        self.data[3][2] += v.z;
+    pub fn zero() -> Self {
+        return Self {
+            cols: [F32x4::zero(); 4],
+        };
     }
 
-    pub fn translator(v: &Vec3<E>) -> Self {
-        let mut m = Mat4x4::ident();
-        m.data[3][0] = v.x;
-        m.data[3][1] = v.y;
-        m.data[3][2] = v.z;
-        return m;
+    pub fn from_translation(t: Vec3) -> Self {
+        return Affine3::from_translation(t).to_mat4();
     }
 
-    pub fn set_translation(&mut self, v: &Vec3<E>) {
-        self.data[3][0] = v.x;
-        self.data[3][1] = v.y;
-        self.data[3][2] = v.z;
+    pub fn from_scale(s: Vec3) -> Self {
+        return Affine3::from_scale(s).to_mat4();
     }
 
-    pub fn update_rotation(&mut self, m: &Mat3x3<E>) {
-        for i in 0..3 {
-            for j in 0..3 {
-                self.data[i][j] = m.data[i][j];
-            }
-        }
+    pub fn from_quat(r: Quat) -> Self {
+        return Affine3::from_quat(r).to_mat4();
+    }
+
+    pub fn from_scale_rotation_translation(s: Vec3, r: Quat, t: Vec3) -> Self {
+        return Affine3::from_scale_rotation_translation(s, r, t).to_mat4();
+    }
+
+    #[inline]
+    pub fn get_col(&self, index: usize) -> Vec4 {
+        return Vec4(self.cols[index]);
     }
 
-    pub fn get_smat4x4f(&self) -> SMat4x4F {
-        SMat4x4F(
-            self.data[0][0].to_f32(),
-            self.data[0][1].to_f32(),
-            self.data[0][2].to_f32(),
-            self.data[0][3].to_f32(),
-            self.data[1][0].to_f32(),
-            self.data[1][1].to_f32(),
-            self.data[1][2].to_f32(),
-            self.data[1][3].to_f32(),
-            self.data[2][0].to_f32(),
-            self.data[2][1].to_f32(),
-            self.data[2][2].to_f32(),
-            self.data[2][3].to_f32(),
-            self.data[3][0].to_f32(),
-            self.data[3][1].to_f32(),
-            self.data[3][2].to_f32(),
-            self.data[3][3].to_f32(),
-        )
-    }
-
-    pub fn pers(fovy: E, aspect: E, near: E, far: E) -> Self {
-        let fovy = (fovy * E::new(0.5)).tan();
-        let ys = E::new(1.0) / fovy;
-        let xs = ys / aspect;
-        let near_far = near - far;
-        let zs = (far + near) / near_far;
-        let ws = (E::new(2.0) * far * near) / near_far;
-        Mat4x4 {
-            data: [
-                [xs, E::new(0.0), E::new(0.0), E::new(0.0)],
-                [E::new(0.0), ys, E::new(0.0), E::new(0.0)],
-                [E::new(0.0), E::new(0.0), zs, E::new(-1.0)],
-                [E::new(0.0), E::new(0.0), ws, E::new(0.0)],
-                // [E::new(0.0), E::new(0.0), zs,           ws],
-                // [E::new(0.0), E::new(0.0), E::new(-1.0), E::new(0.0)],
+    pub fn transpose(&self) -> Self {
+        let [c0, c1, c2, c3] = self.cols;
+        let t0 = c0.shuffle::<{ shuffle_mask(0, 1, 0, 1) }>(c1);
+        let t1 = c2.shuffle::<{ shuffle_mask(0, 1, 0, 1) }>(c3);
+        let t2 = c0.shuffle::<{ shuffle_mask(2, 3, 2, 3) }>(c1);
+        let t3 = c2.shuffle::<{ shuffle_mask(2, 3, 2, 3) }>(c3);
+        return Self {
+            cols: [
+                t0.shuffle::<{ shuffle_mask(0, 2, 0, 2) }>(t1),
+                t0.shuffle::<{ shuffle_mask(1, 3, 1, 3) }>(t1),
+                t2.shuffle::<{ shuffle_mask(0, 2, 0, 2) }>(t3),
+                t2.shuffle::<{ shuffle_mask(1, 3, 1, 3) }>(t3),
             ],
-        }
+        };
+    }
+
+    pub fn determinant(&self) -> f32 {
+        return self.adjugate().1;
     }
 
-    pub fn ortho(aspect: E, near: E, far: E) -> Self {
-        Mat4x4 {
-            data: [
-                [E::new(2.0) / aspect, E::new(0.0), E::new(0.0), E::new(0.0)],
-                [E::new(0.0), E::new(2.0) * aspect, E::new(0.0), E::new(0.0)],
-                [
-                    E::new(0.0),
-                    E::new(0.0),
-                    E::new(2.0) / (near - far),
-                    E::new(0.0),
-                ],
-                [
-                    E::new(0.0),
-                    E::new(0.0),
-                    (far + near) / (near - far),
-                    E::new(1.0),
-                ],
+    // Singular matrices give infinities and NaNs, like a division by zero.
+    pub fn inverse(&self) -> Self {
+        let (adjugate, det) = self.adjugate();
+        let rcp = F32x4::splat(1.0 / det);
+        return Self {
+            cols: [
+                adjugate[0] * rcp,
+                adjugate[1] * rcp,
+                adjugate[2] * rcp,
+                adjugate[3] * rcp,
             ],
-        }
+        };
     }
 
-    pub fn get_mat3x3(&self) -> Mat3x3<E> {
-        let mut mat = Mat3x3::zero();
-        for i in 0..3 {
-            for j in 0..3 {
-                mat.data[i][j] = self.data[i][j];
-            }
+    // It is None for the singular matrices, like a zero scale.
+    pub fn try_inverse(&self) -> Option<Self> {
+        let (adjugate, det) = self.adjugate();
+        if !det.is_normal() {
+            return None;
         }
-        return mat;
-    }
-
-    pub fn det(&self) -> E {
-        (self.data[0][0] *
-            ((self.data[1][1] *
-                ((self.data[2][2] * self.data[3][3]) - (self.data[2][3] * self.data[3][2]))) -
-                (self.data[1][2] *
-                    ((self.data[2][1] * self.data[3][3]) - (self.data[2][3] * self.data[3][1]))) +
-                (self.data[1][3] *
-                    ((self.data[2][1] * self.data[3][2]) - (self.data[2][2] * self.data[3][1]))))) -
-            (self.data[0][1] *
-                ((self.data[1][0] *
-                    ((self.data[2][2] * self.data[3][3]) - (self.data[2][3] * self.data[3][2]))) -
-                    (self.data[1][2] *
-                        ((self.data[2][0] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][0]))) +
-                    (self.data[1][3] *
-                        ((self.data[2][0] * self.data[3][2]) -
-                            (self.data[2][2] * self.data[3][0]))))) +
-            (self.data[0][2] *
-                ((self.data[1][0] *
-                    ((self.data[2][1] * self.data[3][3]) - (self.data[2][3] * self.data[3][1]))) -
-                    (self.data[1][1] *
-                        ((self.data[2][0] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][0]))) +
-                    (self.data[1][3] *
-                        ((self.data[2][0] * self.data[3][1]) -
-                            (self.data[2][1] * self.data[3][0]))))) -
-            (self.data[0][3] *
-                ((self.data[1][0] *
-                    ((self.data[2][1] * self.data[3][2]) - (self.data[2][2] * self.data[3][1]))) -
-                    (self.data[1][1] *
-                        ((self.data[2][0] * self.data[3][2]) -
-                            (self.data[2][2] * self.data[3][0]))) +
-                    (self.data[1][2] *
-                        ((self.data[2][0] * self.data[3][1]) -
-                            (self.data[2][1] * self.data[3][0])))))
-    }
-
-    pub fn inv(&self) -> Self {
-        let d = self.det();
-        Mat4x4 {
-            data: [
-                [
-                    ((self.data[1][1] *
-                        ((self.data[2][2] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][2]))) -
-                        (self.data[1][2] *
-                            ((self.data[2][1] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][1]))) +
-                        (self.data[1][3] *
-                            ((self.data[2][1] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][1])))) / d,
-                    ((self.data[1][0] *
-                        ((self.data[2][2] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][2]))) -
-                        (self.data[1][2] *
-                            ((self.data[2][0] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][0]))) +
-                        (self.data[1][3] *
-                            ((self.data[2][0] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][0])))) / d,
-                    ((self.data[1][0] *
-                        ((self.data[2][1] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][1]))) -
-                        (self.data[1][1] *
-                            ((self.data[2][0] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][0]))) +
-                        (self.data[1][3] *
-                            ((self.data[2][0] * self.data[3][1]) -
-                                (self.data[2][1] * self.data[3][0])))) / d,
-                    ((self.data[1][0] *
-                        ((self.data[2][1] * self.data[3][2]) -
-                            (self.data[2][2] * self.data[3][1]))) -
-                        (self.data[1][1] *
-                            ((self.data[2][0] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][0]))) +
-                        (self.data[1][2] *
-                            ((self.data[2][0] * self.data[3][1]) -
-                                (self.data[2][1] * self.data[3][0])))) / d,
-                ],
-                [
-                    ((self.data[0][1] *
-                        ((self.data[2][2] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[2][1] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][1]))) +
-                        (self.data[0][3] *
-                            ((self.data[2][1] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][1])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[2][2] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[2][0] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[2][0] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[2][1] * self.data[3][3]) -
-                            (self.data[2][3] * self.data[3][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[2][0] * self.data[3][3]) -
-                                (self.data[2][3] * self.data[3][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[2][0] * self.data[3][1]) -
-                                (self.data[2][1] * self.data[3][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[2][1] * self.data[3][2]) -
-                            (self.data[2][2] * self.data[3][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[2][0] * self.data[3][2]) -
-                                (self.data[2][2] * self.data[3][0]))) +
-                        (self.data[0][2] *
-                            ((self.data[2][0] * self.data[3][1]) -
-                                (self.data[2][1] * self.data[3][0])))) / d,
-                ],
-                [
-                    ((self.data[0][1] *
-                        ((self.data[1][2] * self.data[3][3]) -
-                            (self.data[1][3] * self.data[3][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[1][1] * self.data[3][3]) -
-                                (self.data[1][3] * self.data[3][1]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][1] * self.data[3][2]) -
-                                (self.data[1][2] * self.data[3][1])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][2] * self.data[3][3]) -
-                            (self.data[1][3] * self.data[3][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[1][0] * self.data[3][3]) -
-                                (self.data[1][3] * self.data[3][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][0] * self.data[3][2]) -
-                                (self.data[1][2] * self.data[3][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][1] * self.data[3][3]) -
-                            (self.data[1][3] * self.data[3][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[1][0] * self.data[3][3]) -
-                                (self.data[1][3] * self.data[3][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][0] * self.data[3][1]) -
-                                (self.data[1][1] * self.data[3][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][1] * self.data[3][2]) -
-                            (self.data[1][2] * self.data[3][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[1][0] * self.data[3][2]) -
-                                (self.data[1][2] * self.data[3][0]))) +
-                        (self.data[0][2] *
-                            ((self.data[1][0] * self.data[3][1]) -
-                                (self.data[1][1] * self.data[3][0])))) / d,
-                ],
-                [
-                    ((self.data[0][1] *
-                        ((self.data[1][2] * self.data[2][3]) -
-                            (self.data[1][3] * self.data[2][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[1][1] * self.data[2][3]) -
-                                (self.data[1][3] * self.data[2][1]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][1] * self.data[2][2]) -
-                                (self.data[1][2] * self.data[2][1])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][2] * self.data[2][3]) -
-                            (self.data[1][3] * self.data[2][2]))) -
-                        (self.data[0][2] *
-                            ((self.data[1][0] * self.data[2][3]) -
-                                (self.data[1][3] * self.data[2][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][0] * self.data[2][2]) -
-                                (self.data[1][2] * self.data[2][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][1] * self.data[2][3]) -
-                            (self.data[1][3] * self.data[2][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[1][0] * self.data[2][3]) -
-                                (self.data[1][3] * self.data[2][0]))) +
-                        (self.data[0][3] *
-                            ((self.data[1][0] * self.data[2][1]) -
-                                (self.data[1][1] * self.data[2][0])))) / d,
-                    ((self.data[0][0] *
-                        ((self.data[1][1] * self.data[2][2]) -
-                            (self.data[1][2] * self.data[2][1]))) -
-                        (self.data[0][1] *
-                            ((self.data[1][0] * self.data[2][2]) -
-                                (self.data[1][2] * self.data[2][0]))) +
-                        (self.data[0][2] *
-                            ((self.data[1][0] * self.data[2][1]) -
-                                (self.data[1][1] * self.data[2][0])))) / d,
-                ],
+        let rcp = F32x4::splat(1.0 / det);
+        return Some(Self {
+            cols: [
+                adjugate[0] * rcp,
+                adjugate[1] * rcp,
+                adjugate[2] * rcp,
+                adjugate[3] * rcp,
             ],
-        }
+        });
     }
 
-    pub fn new_from_file(f: &Arc<DebugCell<File>>) -> Self {
-        let mut data = [[E::new(0.0); 4]; 4];
-        let mut file = f.borrow_mut();
-        for i in 0..4 {
-            for j in 0..4 {
-                data[i][j] = file.read_type();
-            }
+    #[inline]
+    pub fn transform_vec4(&self, v: Vec4) -> Vec4 {
+        return Vec4(self.transform(v.0));
+    }
+
+    // It does not divide by w, it is meant for the affine matrices.
+    #[inline]
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        let r = self.transform(p.0 + F32x4::new(0.0, 0.0, 0.0, 1.0));
+        return Vec3(r * F32x4::new(1.0, 1.0, 1.0, 0.0));
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let r = self.transform(v.0);
+        return Vec3(r * F32x4::new(1.0, 1.0, 1.0, 0.0));
+    }
+
+    #[inline]
+    fn transform(&self, v: F32x4) -> F32x4 {
+        let [c0, c1, c2, c3] = self.cols;
+        return c0 * v.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(v)
+            + c1 * v.shuffle::<{ shuffle_mask(1, 1, 1, 1) }>(v)
+            + c2 * v.shuffle::<{ shuffle_mask(2, 2, 2, 2) }>(v)
+            + c3 * v.shuffle::<{ shuffle_mask(3, 3, 3, 3) }>(v);
+    }
+
+    // Columns of the adjugate come from the six 2x2 determinant vectors of
+    // the last two columns, like the GLM inverse. The determinant is the dot
+    // of the first column and the first row of the adjugate.
+    fn adjugate(&self) -> ([F32x4; 4], f32) {
+        let [c0, c1, c2, c3] = self.cols;
+        macro_rules! factor {
+            ($i:expr, $j:expr) => {{
+                let a = c3.shuffle::<{ shuffle_mask($i, $i, $i, $i) }>(c2);
+                let b = c3.shuffle::<{ shuffle_mask($j, $j, $j, $j) }>(c2);
+                let c = c2.shuffle::<{ shuffle_mask($j, $j, $j, $j) }>(c1);
+                let d = c2.shuffle::<{ shuffle_mask($i, $i, $i, $i) }>(c1);
+                c * a.shuffle::<{ shuffle_mask(0, 0, 0, 2) }>(a)
+                    - b.shuffle::<{ shuffle_mask(0, 0, 0, 2) }>(b) * d
+            }};
+        }
+        let f0 = factor!(3, 2);
+        let f1 = factor!(3, 1);
+        let f2 = factor!(2, 1);
+        let f3 = factor!(3, 0);
+        let f4 = factor!(2, 0);
+        let f5 = factor!(1, 0);
+        macro_rules! row {
+            ($i:expr) => {{
+                let t = c1.shuffle::<{ shuffle_mask($i, $i, $i, $i) }>(c0);
+                t.shuffle::<{ shuffle_mask(0, 2, 2, 2) }>(t)
+            }};
         }
-        Mat4x4 { data: data }
+        let v0 = row!(0);
+        let v1 = row!(1);
+        let v2 = row!(2);
+        let v3 = row!(3);
+        let sign_a = F32x4::new(-1.0, 1.0, -1.0, 1.0);
+        let sign_b = F32x4::new(1.0, -1.0, 1.0, -1.0);
+        let i0 = sign_b * (v1 * f0 - v2 * f1 + v3 * f2);
+        let i1 = sign_a * (v0 * f0 - v2 * f3 + v3 * f4);
+        let i2 = sign_b * (v0 * f1 - v1 * f3 + v3 * f5);
+        let i3 = sign_a * (v0 * f2 - v1 * f4 + v2 * f5);
+        let r0 = i0.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(i1);
+        let r1 = i2.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(i3);
+        let r = r0.shuffle::<{ shuffle_mask(0, 2, 0, 2) }>(r1);
+        return ([i0, i1, i2, i3], c0.dot(r));
     }
 }
 
-impl<'a, 'b, E> Mul<&'b Vec3<E>> for &'a Mat4x4<E>
-where
-    E: Number,
-{
-    type Output = Vec3<E>;
-    fn mul(self, o: &'b Vec3<E>) -> Vec3<E> {
-        Vec3 {
-            x: self.data[0][0] * o.x + self.data[1][0] * o.y + self.data[2][0] * o.z +
-                self.data[3][0],
-            y: self.data[0][1] * o.x + self.data[1][1] * o.y + self.data[2][1] * o.z +
-                self.data[3][1],
-            z: self.data[0][2] * o.x + self.data[1][2] * o.y + self.data[2][2] * o.z +
-                self.data[3][2],
-        }
+impl Default for Mat4 {
+    fn default() -> Self {
+        return Self::identity();
     }
 }
 
-impl<'a, 'b, E> Mul<&'b Mat4x4<E>> for &'a Mat4x4<E>
-where
-    E: Float,
-{
-    type Output = Mat4x4<E>;
-    fn mul(self, o: &'b Mat4x4<E>) -> Mat4x4<E> {
-        let mut m = Mat4x4::zero();
-        for i in 0..4 {
-            for j in 0..4 {
-                for k in 0..4 {
-                    m.data[j][i] += o.data[j][k] * self.data[k][i];
-                }
-            }
-        }
-        m
+impl Mul for Mat4 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        return Self {
+            cols: [
+                self.transform(other.cols[0]),
+                self.transform(other.cols[1]),
+                self.transform(other.cols[2]),
+                self.transform(other.cols[3]),
+            ],
+        };
     }
 }
 
-impl<'a, E> MulAssign<&'a Mat4x4<E>> for Mat4x4<E>
-where
-    E: Float,
-{
-    fn mul_assign(&mut self, o: &'a Mat4x4<E>) {
-        let mut data = [[E::new(0.0); 4]; 4];
-        for i in 0..4 {
-            for j in 0..4 {
-                for k in 0..4 {
-                    data[j][i] += o.data[j][k] * self.data[k][i];
-                }
-            }
-        }
-        self.data = data;
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    #[inline]
+    fn mul(self, v: Vec4) -> Vec4 {
+        return self.transform_vec4(v);
     }
 }
 
-impl<E> Default for Mat4x4<E> where
-    E: Float,
-{
-    fn default() -> Self {
-        Mat4x4::ident()
+impl From<cgmath::Matrix4<f32>> for Mat4 {
+    fn from(m: cgmath::Matrix4<f32>) -> Self {
+        return Self::from_cols(m.x.into(), m.y.into(), m.z.into(), m.w.into());
     }
 }
 
-#[repr(simd)]
-pub struct SMat3x3F(
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-    pub f32,
-);
+impl From<Mat4> for cgmath::Matrix4<f32> {
+    fn from(m: Mat4) -> Self {
+        return cgmath::Matrix4::from_cols(
+            m.get_col(0).into(),
+            m.get_col(1).into(),
+            m.get_col(2).into(),
+            m.get_col(3).into(),
+        );
+    }
+}
 
+// Rotation, scale and translation without the projection row, it is smaller
+// and faster than Mat4 for the transforms of the scene. The fourth lane of
+// each column is zero, so it has the layout of mat4x3 in std140.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
-pub struct Mat3x3<E>
-where
-    E: Number,
-{
-    pub data: [[E; 3]; 3],
+pub struct Affine3 {
+    cols: [F32x4; 3],
+    translation: F32x4,
 }
 
-impl<E> Mat3x3<E>
-where
-    E: Float,
-{
-    pub fn zero() -> Self {
-        Mat3x3 {
-            data: [[E::new(0.0); 3]; 3],
-        }
+impl Affine3 {
+    pub fn identity() -> Self {
+        return Self::from_scale(Vec3::splat(1.0));
     }
 
-    pub fn ident() -> Self {
-        let mut data = [[E::new(0.0); 3]; 3];
-        for i in 0..3 {
-            data[i][i] = E::new(0.0);
-        }
-        Mat3x3 { data: data }
-    }
-
-    pub fn det(&self) -> E {
-        (self.data[0][0] *
-            ((self.data[1][1] * self.data[2][2]) - (self.data[1][2] * self.data[2][1]))) -
-            (self.data[0][1] *
-                ((self.data[1][0] * self.data[2][2]) - (self.data[1][2] * self.data[2][0]))) +
-            (self.data[0][2] *
-                ((self.data[1][0] * self.data[2][1]) - (self.data[1][1] * self.data[2][0])))
-    }
-
-    pub fn inv(&self) -> Self {
-        let d = self.det();
-        Mat3x3 {
-            data: [
-                [
-                    ((self.data[1][1] * self.data[2][2]) - (self.data[1][2] * self.data[2][1])) / d,
-                    ((self.data[1][0] * self.data[2][2]) - (self.data[1][2] * self.data[2][0])) /
-                        -d,
-                    ((self.data[1][0] * self.data[2][1]) - (self.data[1][1] * self.data[2][0])) / d,
-                ],
-                [
-                    ((self.data[0][1] * self.data[2][2]) - (self.data[0][2] * self.data[2][1])) /
-                        -d,
-                    ((self.data[0][0] * self.data[2][2]) - (self.data[0][2] * self.data[2][0])) / d,
-                    ((self.data[0][0] * self.data[2][1]) - (self.data[0][1] * self.data[2][0])) /
-                        -d,
-                ],
-                [
-                    ((self.data[0][1] * self.data[1][2]) - (self.data[0][2] * self.data[1][1])) / d,
-                    ((self.data[0][0] * self.data[1][2]) - (self.data[0][2] * self.data[1][0])) /
-                        -d,
-                    ((self.data[0][0] * self.data[1][1]) - (self.data[0][1] * self.data[1][0])) / d,
-                ],
+    pub fn from_cols(x: Vec3, y: Vec3, z: Vec3, translation: Vec3) -> Self {
+        return Self {
+            cols: [x.0, y.0, z.0],
+            translation: translation.0,
+        };
+    }
+
+    pub fn from_translation(t: Vec3) -> Self {
+        let mut m = Self::identity();
+        m.translation = t.0;
+        return m;
+    }
+
+    pub fn from_scale(s: Vec3) -> Self {
+        let s = s.to_array();
+        return Self {
+            cols: [
+                F32x4::new(s[0], 0.0, 0.0, 0.0),
+                F32x4::new(0.0, s[1], 0.0, 0.0),
+                F32x4::new(0.0, 0.0, s[2], 0.0),
             ],
-        }
+            translation: F32x4::zero(),
+        };
     }
 
-    pub fn t(&self) -> Self {
-        Mat3x3 {
-            data: [
-                [self.data[0][0], self.data[1][0], self.data[2][0]],
-                [self.data[0][1], self.data[1][1], self.data[2][1]],
-                [self.data[0][2], self.data[1][2], self.data[2][2]],
+    // Quaternion must be normalized.
+    pub fn from_quat(r: Quat) -> Self {
+        let [x, y, z, w] = r.to_array();
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        return Self {
+            cols: [
+                F32x4::new(1.0 - (yy + zz), xy + wz, xz - wy, 0.0),
+                F32x4::new(xy - wz, 1.0 - (xx + zz), yz + wx, 0.0),
+                F32x4::new(xz + wy, yz - wx, 1.0 - (xx + yy), 0.0),
             ],
-        }
+            translation: F32x4::zero(),
+        };
     }
 
-    pub fn get_smat3x3f(&self) -> SMat3x3F {
-        SMat3x3F(
-            self.data[0][0].to_f32(),
-            self.data[0][1].to_f32(),
-            self.data[0][2].to_f32(),
-            self.data[1][0].to_f32(),
-            self.data[1][1].to_f32(),
-            self.data[1][2].to_f32(),
-            self.data[2][0].to_f32(),
-            self.data[2][1].to_f32(),
-            self.data[2][2].to_f32(),
-        )
-    }
-
-    // v: must be normalized
-    pub fn rotation(d: E, v: &Vec3<E>) -> Self {
-        let sinus: E = E::new(d.to().sin());
-        let cosinus: E = E::new(d.to().cos());
-        let oneminuscos = E::new(1.0) - cosinus;
-        let w = v;
-        let wx2 = w.x * w.x;
-        let wxy = w.x * w.y;
-        let wxz = w.x * w.z;
-        let wy2 = w.y * w.y;
-        let wyz = w.y * w.z;
-        let wz2 = w.z * w.z;
-        let wxyonemincos = wxy * oneminuscos;
-        let wxzonemincos = wxz * oneminuscos;
-        let wyzonemincos = wyz * oneminuscos;
-        let wxsin = w.x * sinus;
-        let wysin = w.y * sinus;
-        let wzsin = w.z * sinus;
-        Mat3x3 {
-            data: [
-                [
-                    cosinus + (wx2 * oneminuscos),
-                    wxyonemincos + wzsin,
-                    wxzonemincos - wysin,
-                ],
-                [
-                    wxyonemincos - wzsin,
-                    cosinus + (wy2 * oneminuscos),
-                    wxsin + wyzonemincos,
-                ],
-                [
-                    wysin + wxzonemincos,
-                    wyzonemincos - wxsin,
-                    cosinus + (wz2 * oneminuscos),
-                ],
+    pub fn from_scale_rotation_translation(s: Vec3, r: Quat, t: Vec3) -> Self {
+        let mut m = Self::from_quat(r);
+        let s = s.0;
+        m.cols[0] = m.cols[0] * s.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(s);
+        m.cols[1] = m.cols[1] * s.shuffle::<{ shuffle_mask(1, 1, 1, 1) }>(s);
+        m.cols[2] = m.cols[2] * s.shuffle::<{ shuffle_mask(2, 2, 2, 2) }>(s);
+        m.translation = t.0;
+        return m;
+    }
+
+    // The last row of the matrix is ignored.
+    pub fn from_mat4(m: &Mat4) -> Self {
+        let mask = F32x4::new(1.0, 1.0, 1.0, 0.0);
+        return Self {
+            cols: [m.cols[0] * mask, m.cols[1] * mask, m.cols[2] * mask],
+            translation: m.cols[3] * mask,
+        };
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        return Mat4 {
+            cols: [
+                self.cols[0],
+                self.cols[1],
+                self.cols[2],
+                self.translation + F32x4::new(0.0, 0.0, 0.0, 1.0),
             ],
-        }
+        };
     }
 
-    pub fn to_mat4x4(&self) -> Mat4x4<E> {
-        let mut m = Mat4x4::zero();
-        for i in 0..3 {
-            for j in 0..3 {
-                m.data[i][j] = self.data[i][j];
-            }
-        }
+    #[inline]
+    pub fn get_translation(&self) -> Vec3 {
+        return Vec3(self.translation);
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let [c0, c1, c2] = self.cols;
+        let v = v.0;
+        return Vec3(
+            c0 * v.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(v)
+                + c1 * v.shuffle::<{ shuffle_mask(1, 1, 1, 1) }>(v)
+                + c2 * v.shuffle::<{ shuffle_mask(2, 2, 2, 2) }>(v),
+        );
+    }
+
+    #[inline]
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        return Vec3(self.transform_vector(p).0 + self.translation);
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let [c0, c1, c2] = self.cols;
+        return Vec3(c0).dot(Vec3(c1).cross(Vec3(c2)));
+    }
+
+    // Rows of the inverse of the linear part are the cross products of its
+    // columns divided by the determinant.
+    pub fn inverse(&self) -> Self {
+        let [c0, c1, c2] = self.cols;
+        let (c0, c1, c2) = (Vec3(c0), Vec3(c1), Vec3(c2));
+        let r0 = c1.cross(c2);
+        let r1 = c2.cross(c0);
+        let r2 = c0.cross(c1);
+        let rcp = F32x4::splat(1.0 / c0.dot(r0));
+        let rows = Mat4 {
+            cols: [r0.0 * rcp, r1.0 * rcp, r2.0 * rcp, F32x4::zero()],
+        };
+        let linear = rows.transpose();
+        let mut m = Self {
+            cols: [linear.cols[0], linear.cols[1], linear.cols[2]],
+            translation: F32x4::zero(),
+        };
+        m.translation = (-m.transform_vector(Vec3(self.translation))).0;
         return m;
     }
+}
+
+impl Default for Affine3 {
+    fn default() -> Self {
+        return Self::identity();
+    }
+}
 
-    pub fn scale(&mut self, e: E) {
-        for i in 0..3 {
-            self.data[i][i] *= e;
-        }
+impl Mul for Affine3 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        return Self {
+            cols: [
+                self.transform_vector(Vec3(other.cols[0])).0,
+                self.transform_vector(Vec3(other.cols[1])).0,
+                self.transform_vector(Vec3(other.cols[2])).0,
+            ],
+            translation: self.transform_point(Vec3(other.translation)).0,
+        };
     }
 }
 
-impl<'a, 'b, E> Mul<&'b Vec3<E>> for &'a Mat3x3<E>
-where
-    E: Number,
-{
-    type Output = Vec3<E>;
-    fn mul(self, o: &'b Vec3<E>) -> Vec3<E> {
-        Vec3 {
-            x: self.data[0][0] * o.x + self.data[1][0] * o.y + self.data[2][0] * o.z,
-            y: self.data[0][1] * o.x + self.data[1][1] * o.y + self.data[2][1] * o.z,
-            z: self.data[0][2] * o.x + self.data[1][2] * o.y + self.data[2][2] * o.z,
-        }
+impl From<Affine3> for Mat4 {
+    fn from(m: Affine3) -> Self {
+        return m.to_mat4();
     }
 }
 
-impl<'a, 'b, E> Mul<&'b Mat3x3<E>> for &'a Mat3x3<E>
-where
-    E: Float,
-{
-    type Output = Mat3x3<E>;
-    fn mul(self, o: &'b Mat3x3<E>) -> Mat3x3<E> {
-        let mut m = Mat3x3::zero();
-        for i in 0..3 {
-            for j in 0..3 {
-                for k in 0..3 {
-                    m.data[j][i] += o.data[j][k] * self.data[k][i];
-                }
-            }
-        }
-        m
+impl From<cgmath::Matrix4<f32>> for Affine3 {
+    fn from(m: cgmath::Matrix4<f32>) -> Self {
+        return Self::from_mat4(&m.into());
+    }
+}
+
+impl From<Affine3> for cgmath::Matrix4<f32> {
+    fn from(m: Affine3) -> Self {
+        return m.to_mat4().into();
     }
 }
 
-impl<'a, E> MulAssign<&'a Mat3x3<E>> for Mat3x3<E>
-where
-    E: Float,
-{
-    fn mul_assign(&mut self, o: &'a Mat3x3<E>) {
-        let mut data = [[E::new(0.0); 3]; 3];
-        for i in 0..3 {
-            for j in 0..3 {
-                for k in 0..3 {
-                    data[j][i] += o.data[j][k] * self.data[k][i];
-                }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::{Rad, Rotation3, SquareMatrix};
+
+    fn is_near(a: &[f32], b: &[f32]) -> bool {
+        return a
+            .iter()
+            .zip(b.iter())
+            .all(|(a, b)| (a - b).abs() < 1e-4 * b.abs().max(1.0));
+    }
+
+    fn as_array(m: &cgmath::Matrix4<f32>) -> [f32; 16] {
+        let m: &[f32; 16] = m.as_ref();
+        return *m;
+    }
+
+    fn create_matrices() -> Vec<cgmath::Matrix4<f32>> {
+        let rotation = cgmath::Quaternion::from_axis_angle(
+            cgmath::InnerSpace::normalize(cgmath::Vector3::new(0.3f32, -1.0, 0.2)),
+            Rad(1.1),
+        );
+        let srt = cgmath::Matrix4::from_translation(cgmath::Vector3::new(4.0, -2.0, 9.5))
+            * cgmath::Matrix4::from(rotation)
+            * cgmath::Matrix4::from_nonuniform_scale(2.0, 0.5, 3.0);
+        let perspective = cgmath::perspective(Rad(1.0f32), 1.5, 0.1, 100.0);
+        let general = cgmath::Matrix4::new(
+            2.0, 1.0, 0.5, 3.0, -1.0, 4.0, 2.0, 0.25, 0.5, -3.0, 5.0, 1.0, 7.0, 2.0, -1.0, 6.0,
+        );
+        return vec![srt, perspective, general, perspective * srt * general];
+    }
+
+    #[test]
+    fn mat4_against_cgmath_test() {
+        let matrices = create_matrices();
+        for a in &matrices {
+            let m = Mat4::from(*a);
+            let expected = as_array(&a.invert().unwrap());
+            assert!(is_near(&m.inverse().to_cols_array(), &expected));
+            assert!(is_near(
+                &vx_unwrap!(m.try_inverse()).to_cols_array(),
+                &expected
+            ));
+            assert!((m.determinant() - a.determinant()).abs() < 1e-3 * a.determinant().abs());
+            assert!(is_near(
+                &m.transpose().to_cols_array(),
+                &as_array(&cgmath::Matrix::transpose(a))
+            ));
+            for b in &matrices {
+                let product = (m * Mat4::from(*b)).to_cols_array();
+                assert!(is_near(&product, &as_array(&(a * b))));
             }
         }
-        self.data = data;
+    }
+
+    #[test]
+    fn affine3_test() {
+        let r = Quat::from_axis_angle(Vec3::new(0.0, 0.6, 0.8), 0.9);
+        let a = Affine3::from_scale_rotation_translation(
+            Vec3::new(2.0, 0.5, 3.0),
+            r,
+            Vec3::new(4.0, -2.0, 9.5),
+        );
+        let b = Affine3::from_scale_rotation_translation(
+            Vec3::new(1.0, 2.0, 1.0),
+            r.conjugate(),
+            Vec3::new(-1.0, 0.0, 2.0),
+        );
+        let (ma, mb) = (a.to_mat4(), b.to_mat4());
+        assert!(is_near(
+            &(a * b).to_mat4().to_cols_array(),
+            &(ma * mb).to_cols_array()
+        ));
+        assert!(is_near(
+            &a.inverse().to_mat4().to_cols_array(),
+            &ma.inverse().to_cols_array()
+        ));
+        assert!((a.determinant() - ma.determinant()).abs() < 1e-4);
+        let p = Vec3::new(0.5, -7.0, 2.0);
+        assert!(is_near(
+            &a.transform_point(p).to_array(),
+            &ma.transform_point(p).to_array()
+        ));
+        assert!(is_near(
+            &(a * a.inverse()).to_mat4().to_cols_array(),
+            &Mat4::identity().to_cols_array()
+        ));
+        assert!(Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0))
+            .try_inverse()
+            .is_none());
     }
 }
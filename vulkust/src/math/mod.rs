@@ -1,7 +1,11 @@
-//pub mod aabbox;
-//pub mod kdtree;
+pub mod aabbox;
+pub mod bvh;
+pub mod curve;
+pub mod frustum;
 pub mod matrix;
 pub mod number;
-//pub mod ray;
-//pub mod triangle;
+pub mod quaternion;
+pub mod ray;
+pub mod simd;
+pub mod triangle;
 pub mod vector;
@@ -0,0 +1,230 @@
+use super::simd::{shuffle_mask, F32x4};
+use super::vector::Vec3;
+use std::ops::Mul;
+
+// Lanes are x, y, z and w, w is the scalar part like in glTF and GLSL.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+#[repr(transparent)]
+pub struct Quat(pub(super) F32x4);
+
+impl Quat {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        return Self(F32x4::new(x, y, z, w));
+    }
+
+    #[inline]
+    pub fn identity() -> Self {
+        return Self::new(0.0, 0.0, 0.0, 1.0);
+    }
+
+    // Axis must be normalized.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (s, c) = (angle * 0.5).sin_cos();
+        let a = axis.to_array();
+        return Self::new(a[0] * s, a[1] * s, a[2] * s, c);
+    }
+
+    // Axes must be orthonormal and right handed, they are the columns of the
+    // rotation matrix. Shepperd's method, the largest diagonal term is used.
+    pub fn from_axes(x: Vec3, y: Vec3, z: Vec3) -> Self {
+        let x = x.to_array();
+        let y = y.to_array();
+        let z = z.to_array();
+        let trace = x[0] + y[1] + z[2];
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            return Self::new(
+                (y[2] - z[1]) / s,
+                (z[0] - x[2]) / s,
+                (x[1] - y[0]) / s,
+                0.25 * s,
+            );
+        }
+        if x[0] > y[1] && x[0] > z[2] {
+            let s = (1.0 + x[0] - y[1] - z[2]).sqrt() * 2.0;
+            return Self::new(
+                0.25 * s,
+                (y[0] + x[1]) / s,
+                (z[0] + x[2]) / s,
+                (y[2] - z[1]) / s,
+            );
+        }
+        if y[1] > z[2] {
+            let s = (1.0 + y[1] - x[0] - z[2]).sqrt() * 2.0;
+            return Self::new(
+                (y[0] + x[1]) / s,
+                0.25 * s,
+                (z[1] + y[2]) / s,
+                (z[0] - x[2]) / s,
+            );
+        }
+        let s = (1.0 + z[2] - x[0] - y[1]).sqrt() * 2.0;
+        return Self::new(
+            (z[0] + x[2]) / s,
+            (z[1] + y[2]) / s,
+            0.25 * s,
+            (x[1] - y[0]) / s,
+        );
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        return self.0.to_array();
+    }
+
+    #[inline]
+    pub fn get_vector(self) -> Vec3 {
+        return Vec3(self.0 * F32x4::new(1.0, 1.0, 1.0, 0.0));
+    }
+
+    #[inline]
+    pub fn get_scalar(self) -> f32 {
+        return self.0.to_array()[3];
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        return self.0.dot(other.0);
+    }
+
+    #[inline]
+    pub fn length(self) -> f32 {
+        return self.dot(self).sqrt();
+    }
+
+    #[inline]
+    pub fn normalized(self) -> Self {
+        return Self(self.0 * F32x4::splat(1.0 / self.length()));
+    }
+
+    #[inline]
+    pub fn conjugate(self) -> Self {
+        return Self(self.0 * F32x4::new(-1.0, -1.0, -1.0, 1.0));
+    }
+
+    #[inline]
+    pub fn inverse(self) -> Self {
+        return Self(self.conjugate().0 * F32x4::splat(1.0 / self.dot(self)));
+    }
+
+    // Quaternion must be normalized.
+    #[inline]
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let q = self.get_vector();
+        let t = q.cross(v) * 2.0;
+        return v + t * self.get_scalar() + q.cross(t);
+    }
+
+    // The shorter arc is taken, and the nearly parallel ones are lerped to
+    // avoid the division by the tiny sine.
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = other.0;
+        if cos < 0.0 {
+            cos = -cos;
+            other = -other;
+        }
+        if cos > 0.9995 {
+            let q = self.0 + (other - self.0) * F32x4::splat(t);
+            return Self(q).normalized();
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        return Self(self.0 * F32x4::splat(a) + other * F32x4::splat(b));
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        return Self::identity();
+    }
+}
+
+// Hamilton product, the rotation of other happens first.
+impl Mul for Quat {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        let a = self.0;
+        let b = other.0;
+        let b_wzyx = b.shuffle::<{ shuffle_mask(3, 2, 1, 0) }>(b);
+        let b_zwxy = b.shuffle::<{ shuffle_mask(2, 3, 0, 1) }>(b);
+        let b_yxwz = b.shuffle::<{ shuffle_mask(1, 0, 3, 2) }>(b);
+        let r = a.shuffle::<{ shuffle_mask(3, 3, 3, 3) }>(a) * b
+            + a.shuffle::<{ shuffle_mask(0, 0, 0, 0) }>(a)
+                * b_wzyx
+                * F32x4::new(1.0, -1.0, 1.0, -1.0)
+            + a.shuffle::<{ shuffle_mask(1, 1, 1, 1) }>(a)
+                * b_zwxy
+                * F32x4::new(1.0, 1.0, -1.0, -1.0)
+            + a.shuffle::<{ shuffle_mask(2, 2, 2, 2) }>(a)
+                * b_yxwz
+                * F32x4::new(-1.0, 1.0, 1.0, -1.0);
+        return Self(r);
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    #[inline]
+    fn mul(self, v: Vec3) -> Vec3 {
+        return self.rotate(v);
+    }
+}
+
+impl From<cgmath::Quaternion<f32>> for Quat {
+    fn from(q: cgmath::Quaternion<f32>) -> Self {
+        return Self::new(q.v.x, q.v.y, q.v.z, q.s);
+    }
+}
+
+impl From<Quat> for cgmath::Quaternion<f32> {
+    fn from(q: Quat) -> Self {
+        let a = q.to_array();
+        return cgmath::Quaternion::new(a[3], a[0], a[1], a[2]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::{Rad, Rotation3};
+
+    fn is_near(a: [f32; 4], b: [f32; 4]) -> bool {
+        return a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-5);
+    }
+
+    #[test]
+    fn product_and_rotation_test() {
+        let axis_a = cgmath::Vector3::new(1.0f32, 2.0, -0.5);
+        let axis_b = cgmath::Vector3::new(-3.0f32, 0.25, 1.0);
+        let ca =
+            cgmath::Quaternion::from_axis_angle(cgmath::InnerSpace::normalize(axis_a), Rad(0.7));
+        let cb =
+            cgmath::Quaternion::from_axis_angle(cgmath::InnerSpace::normalize(axis_b), Rad(-2.1));
+        let a = Quat::from(ca);
+        let b = Quat::from(cb);
+        let product = cgmath::Quaternion::from(a * b);
+        let expected = ca * cb;
+        assert!(is_near(
+            [product.v.x, product.v.y, product.v.z, product.s],
+            [expected.v.x, expected.v.y, expected.v.z, expected.s]
+        ));
+        let v = cgmath::Vector3::new(0.3f32, -1.5, 4.0);
+        let rotated = (a * b).rotate(Vec3::from(v)).extend(0.0).to_array();
+        let expected = (ca * cb) * v;
+        assert!(is_near(rotated, [expected.x, expected.y, expected.z, 0.0]));
+        let half = a.slerp(b, 0.5);
+        let expected = ca.slerp(cb, 0.5);
+        assert!(is_near(
+            half.to_array(),
+            [expected.v.x, expected.v.y, expected.v.z, expected.s]
+        ));
+    }
+}
@@ -1,33 +1,43 @@
-extern crate num;
+use super::vector::Vec3;
 
-use ::math::vector::{
-    Vec3,
-    VectorElement,
-};
-
-macro_rules! ncu8 {
-    ($var:expr) => (
-        num::cast::<u8, E>($var).unwrap()
-    )
+// Inverse of the direction is kept for the slab tests of the boxes.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Ray3 {
+    origin: Vec3,
+    direction: Vec3,
+    inv_direction: Vec3,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Ray3<E> where E: VectorElement {
-    pub o:    Vec3<E>,
-    pub d:    Vec3<E>,
-    pub invd: Vec3<E>,
-}
+impl Ray3 {
+    // Direction does not need to be normalized, distances of the hits are in
+    // the units of its length.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        let d = direction.to_array();
+        return Self {
+            origin,
+            direction,
+            inv_direction: Vec3::new(1.0 / d[0], 1.0 / d[1], 1.0 / d[2]),
+        };
+    }
+
+    #[inline]
+    pub fn get_origin(&self) -> Vec3 {
+        return self.origin;
+    }
+
+    #[inline]
+    pub fn get_direction(&self) -> Vec3 {
+        return self.direction;
+    }
+
+    #[inline]
+    pub fn get_inv_direction(&self) -> Vec3 {
+        return self.inv_direction;
+    }
 
-impl<E> Ray3<E> where E: VectorElement {
-    pub fn new(o: Vec3<E>, d: Vec3<E>) -> Ray3<E> {
-        Ray3 {
-            o: o,
-            d: d,
-            invd: Vec3 {
-                x: ncu8!(1) / d.x,
-                y: ncu8!(1) / d.y,
-                z: ncu8!(1) / d.z,
-            }
-        }
+    #[inline]
+    pub fn get_point(&self, distance: f32) -> Vec3 {
+        return self.origin + self.direction * distance;
     }
 }
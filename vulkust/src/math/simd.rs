@@ -0,0 +1,230 @@
+// Intrinsics are safe when their target feature is enabled only in the newer
+// compilers.
+#![allow(unused_unsafe)]
+
+#[cfg(neon_simd)]
+use std::arch::aarch64::*;
+#[cfg(all(sse_simd, target_arch = "x86"))]
+use std::arch::x86::*;
+#[cfg(all(sse_simd, target_arch = "x86_64"))]
+use std::arch::x86_64::*;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Mask of F32x4::shuffle, the first two lanes come from self and the other
+// two from the other operand, like _mm_shuffle_ps.
+pub(crate) const fn shuffle_mask(i0: i32, i1: i32, i2: i32, i3: i32) -> i32 {
+    return i0 | (i1 << 2) | (i2 << 4) | (i3 << 6);
+}
+
+#[cfg(not(any(sse_simd, neon_simd)))]
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+#[repr(C, align(16))]
+struct Lanes([f32; 4]);
+
+// Four f32 lanes of a SSE or NEON register, other targets get a 16 bytes
+// aligned array, so the layout is the same everywhere.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+#[repr(transparent)]
+pub struct F32x4(
+    #[cfg(sse_simd)] __m128,
+    #[cfg(neon_simd)] float32x4_t,
+    #[cfg(not(any(sse_simd, neon_simd)))] Lanes,
+);
+
+impl F32x4 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        return Self::from_array([x, y, z, w]);
+    }
+
+    #[inline]
+    pub fn splat(v: f32) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_set1_ps(v) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vdupq_n_f32(v) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return Self(Lanes([v; 4]));
+    }
+
+    #[inline]
+    pub fn zero() -> Self {
+        return Self::splat(0.0);
+    }
+
+    #[inline]
+    pub fn from_array(a: [f32; 4]) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_loadu_ps(a.as_ptr()) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vld1q_f32(a.as_ptr()) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return Self(Lanes(a));
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        #[cfg(sse_simd)]
+        {
+            let mut a = [0.0; 4];
+            unsafe { _mm_storeu_ps(a.as_mut_ptr(), self.0) };
+            return a;
+        }
+        #[cfg(neon_simd)]
+        {
+            let mut a = [0.0; 4];
+            unsafe { vst1q_f32(a.as_mut_ptr(), self.0) };
+            return a;
+        }
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return (self.0).0;
+    }
+
+    #[inline]
+    pub fn x(self) -> f32 {
+        #[cfg(sse_simd)]
+        return unsafe { _mm_cvtss_f32(self.0) };
+        #[cfg(neon_simd)]
+        return unsafe { vgetq_lane_f32::<0>(self.0) };
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return (self.0).0[0];
+    }
+
+    // Lanes of the result are self[i0], self[i1], other[i2] and other[i3] of
+    // the shuffle_mask(i0, i1, i2, i3).
+    #[inline]
+    pub fn shuffle<const MASK: i32>(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_shuffle_ps::<MASK>(self.0, other.0) });
+        #[cfg(not(sse_simd))]
+        {
+            let a = self.to_array();
+            let b = other.to_array();
+            return Self::new(
+                a[(MASK & 3) as usize],
+                a[((MASK >> 2) & 3) as usize],
+                b[((MASK >> 4) & 3) as usize],
+                b[((MASK >> 6) & 3) as usize],
+            );
+        }
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_min_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vminq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, f32::min);
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_max_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vmaxq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, f32::max);
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        #[cfg(neon_simd)]
+        return unsafe { vaddvq_f32(vmulq_f32(self.0, other.0)) };
+        #[cfg(not(neon_simd))]
+        {
+            let m = self * other;
+            let m = m + m.shuffle::<{ shuffle_mask(1, 0, 3, 2) }>(m);
+            let m = m + m.shuffle::<{ shuffle_mask(2, 3, 0, 1) }>(m);
+            return m.x();
+        }
+    }
+
+    #[cfg(not(any(sse_simd, neon_simd)))]
+    #[inline]
+    fn zip(self, other: Self, f: fn(f32, f32) -> f32) -> Self {
+        let a = self.to_array();
+        let b = other.to_array();
+        return Self::new(f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3]));
+    }
+}
+
+impl Add for F32x4 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_add_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vaddq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, |a, b| a + b);
+    }
+}
+
+impl Sub for F32x4 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_sub_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vsubq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, |a, b| a - b);
+    }
+}
+
+impl Mul for F32x4 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_mul_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vmulq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, |a, b| a * b);
+    }
+}
+
+impl Div for F32x4 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, other: Self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_div_ps(self.0, other.0) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vdivq_f32(self.0, other.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(other, |a, b| a / b);
+    }
+}
+
+impl Neg for F32x4 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        #[cfg(sse_simd)]
+        return Self(unsafe { _mm_xor_ps(self.0, _mm_set1_ps(-0.0)) });
+        #[cfg(neon_simd)]
+        return Self(unsafe { vnegq_f32(self.0) });
+        #[cfg(not(any(sse_simd, neon_simd)))]
+        return self.zip(self, |a, _| -a);
+    }
+}
+
+impl PartialEq for F32x4 {
+    fn eq(&self, other: &Self) -> bool {
+        return self.to_array() == other.to_array();
+    }
+}
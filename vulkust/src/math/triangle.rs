@@ -1,155 +1,221 @@
-extern crate num;
-
-use ::math::vector::{
-    Vec3,
-    Vec2,
-    MathVector,
-    VectorElement,
-};
-use ::math::num::{
-    min,
-    max,
-};
-use ::math::aabbox::AABBox3;
-use ::math::ray::Ray3;
-use ::render::vertex::{
-    HasPosition,
-    HasNormal,
-    HasUV,
-};
-
-pub trait Triangle<T>: Sized + Clone + Copy where T: VectorElement {
-    fn get_aabb<V>(&self, vertices: &Vec<V>) -> AABBox3<T> where V: HasPosition<T>;
-    fn get_midpoint<V>(&self, vertices: &Vec<V>) -> Vec3<T> where V: HasPosition<T>;
-    fn intersect<V>(&self, r: &Ray3<T>, tmin: &T, vertices: &Vec<V>) -> Option<(T, T, T)> where V: HasPosition<T>;
-    fn barycentric<V>(&self, p: &Vec3<T>, vertices: &Vec<V>) -> Vec3<T> where V: HasPosition<T>;
+use super::aabbox::Aabb3;
+use super::ray::Ray3;
+use super::vector::Vec3;
+
+// Rays that are nearly parallel to the triangle plane miss it.
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Triangle {
+    vertices: [Vec3; 3],
 }
 
-macro_rules! tri {
-    ($stru:ident) => (
-        impl<T> Triangle<T> for $stru<T> where T: VectorElement {
-            fn get_aabb<V>(&self, vertices: &Vec<V>) -> AABBox3<T> where V: HasPosition<T> {
-                AABBox3 {
-                    blf: Vec3 {
-                        x: min((*vertices[self.ind[0]].get_pos()).x, min((*vertices[self.ind[1]].get_pos()).x, (*vertices[self.ind[2]].get_pos()).x)),
-                        y: min((*vertices[self.ind[0]].get_pos()).y, min((*vertices[self.ind[1]].get_pos()).y, (*vertices[self.ind[2]].get_pos()).y)),
-                        z: min((*vertices[self.ind[0]].get_pos()).z, min((*vertices[self.ind[1]].get_pos()).z, (*vertices[self.ind[2]].get_pos()).z)),
-                    },
-                    trr: Vec3 {
-                        x: max((*vertices[self.ind[0]].get_pos()).x, max((*vertices[self.ind[1]].get_pos()).x, (*vertices[self.ind[2]].get_pos()).x)),
-                        y: max((*vertices[self.ind[0]].get_pos()).y, max((*vertices[self.ind[1]].get_pos()).y, (*vertices[self.ind[2]].get_pos()).y)),
-                        z: max((*vertices[self.ind[0]].get_pos()).z, max((*vertices[self.ind[1]].get_pos()).z, (*vertices[self.ind[2]].get_pos()).z)),
-                    },
-                }
-            }
+impl Triangle {
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3) -> Self {
+        return Self {
+            vertices: [v0, v1, v2],
+        };
+    }
 
-            fn get_midpoint<V>(&self, vertices: &Vec<V>) -> Vec3<T> where V: HasPosition<T> {
-                ((*vertices[self.ind[0]].get_pos()) + (*vertices[self.ind[1]].get_pos()) + (*vertices[self.ind[2]].get_pos())) / num::cast::<u8, T>(3).unwrap()
-            }
+    #[inline]
+    pub fn get_vertices(&self) -> &[Vec3; 3] {
+        return &self.vertices;
+    }
 
-            /// return: It will t, u, v,
-            fn intersect<V>(&self, r: &Ray3<T>, tmin: &T, vertices: &Vec<V>) -> Option<(T, T, T)> where V: HasPosition<T> {
+    pub fn get_aabb(&self) -> Aabb3 {
+        let [v0, v1, v2] = self.vertices;
+        return Aabb3::new_with_points(v0.min(v1).min(v2), v0.max(v1).max(v2));
+    }
 
-                let pvec = r.d.cross(&self.edg[1]);
-                let det = self.edg[0].dot(&pvec);
-                if det == num::cast(0).unwrap() {
-                    return None;
-                }
-                let inv_det: T;
-                inv_det = num::cast::<i8, T>(1).unwrap() / det;
-                let tvec = r.o - *(vertices[self.ind[0]].get_pos());
-                let u = tvec.dot(&pvec) * inv_det;
-                if u < num::cast(0).unwrap() || u > num::cast(1).unwrap() {
-                    return None;
-                }
-                let qvec = tvec.cross(&self.edg[0]);
-                let v = r.d.dot(&qvec) * inv_det;
-                if v < num::cast(0).unwrap() || u + v > num::cast(1).unwrap() {
-                    return None;
-                }
-                let t = self.edg[1].dot(&qvec) * inv_det; // Set distance along ray to intersection
-                if t < *tmin {
-                    if t > num::cast(1e-9).unwrap() {
-                        return Some((t, u, v));
-                    }
-                }
-                None
-            }
+    #[inline]
+    pub fn get_centroid(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        return (v0 + v1 + v2) * (1.0 / 3.0);
+    }
 
-            // Returns barycentric coordinates of point p on the triangle
-            fn barycentric<V>(&self, p: &Vec3<T>, vertices: &Vec<V>) -> Vec3<T> where V: HasPosition<T> {
-                let v2_ = *p - (*vertices[self.ind[0]].get_pos());
-                let d00 = self.edg[0].dot(&self.edg[0]);
-                let d01 = self.edg[0].dot(&self.edg[1]);
-                let d11 = self.edg[1].dot(&self.edg[1]);
-                let d20 = v2_.dot(&self.edg[0]);
-                let d21 = v2_.dot(&self.edg[1]);
-                let d = d00*d11 - d01*d01;
-                let v = (d11*d20 - d01*d21) / d;
-                let w = (d00*d21 - d01*d20) / d;
-                let u = num::cast::<i8, T>(1).unwrap() - v - w;
-                return Vec3 {
-                    x: u,
-                    y: v,
-                    z: w,
-                };
-            }
-        }
-    )
-}
+    // Not normalized, its length is twice the area.
+    #[inline]
+    pub fn get_normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        return (v1 - v0).cross(v2 - v0);
+    }
 
-#[derive(Debug, Clone, Copy)]
-pub struct TexturedTriangle<T> where T: VectorElement {
-    edg: [Vec3<T>; 2],
-    ind: [usize; 3],
-    tedg: [Vec2<T>; 2],
-}
+    // Moller-Trumbore, both faces are hit. It returns the distance and the
+    // barycentric coordinates of v1 and v2.
+    pub fn intersect_ray(&self, ray: &Ray3, max_distance: f32) -> Option<(f32, f32, f32)> {
+        let [v0, v1, v2] = self.vertices;
+        let e1 = v1 - v0;
+        let e2 = v2 - v0;
+        let direction = ray.get_direction();
+        let p = direction.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = ray.get_origin() - v0;
+        let u = s.dot(p) * inv_det;
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv_det;
+        if t < 0.0 || t > max_distance {
+            return None;
+        }
+        return Some((t, u, v));
+    }
 
-impl<T> TexturedTriangle<T> where T: VectorElement, Vec3<T>: MathVector<T> {
-    pub fn new<V>(inds: &[usize; 3], vertices: &Vec<V>) -> TexturedTriangle<T> where V: HasPosition<T> + HasNormal<T> + HasUV<T> {
-        TexturedTriangle {
-            edg : [
-                *vertices[inds[1]].get_pos() - *vertices[inds[0]].get_pos(),
-                *vertices[inds[2]].get_pos() - *vertices[inds[0]].get_pos(),
-            ],
-            ind: [
-                inds[0],
-                inds[1],
-                inds[2],
-            ],
-            tedg: [
-                *vertices[inds[1]].get_uv() - *vertices[inds[0]].get_uv(),
-                *vertices[inds[2]].get_uv() - *vertices[inds[0]].get_uv(),
-            ]
+    // From Real-Time Collision Detection by Ericson, it checks the Voronoi
+    // regions of the vertices and the edges before the face.
+    pub fn get_closest_point(&self, p: Vec3) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+        let bp = p - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+        let cp = p - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
         }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+        let denom = 1.0 / (va + vb + vc);
+        return a + ab * (vb * denom) + ac * (vc * denom);
+    }
+
+    #[inline]
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        return (self.get_closest_point(center) - center).length_squared() <= radius * radius;
     }
-    pub fn get_texture_coord(&self, u: T, v: T) -> Vec2<T> {
-        self.tedg[0] * u + self.tedg[1] * v
+
+    // Separating axis test of Akenine-Moller, the axes are the box normals,
+    // the triangle normal and the nine cross products of their edges.
+    pub fn intersects_aabb(&self, aabb: &Aabb3) -> bool {
+        if !self.get_aabb().intersects_aabb(aabb) {
+            return false;
+        }
+        let center = aabb.get_center();
+        let extent = (aabb.get_size() * 0.5).to_array();
+        let v = [
+            self.vertices[0] - center,
+            self.vertices[1] - center,
+            self.vertices[2] - center,
+        ];
+        let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+        let box_axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        let is_separated = |axis: Vec3| {
+            let a = axis.to_array();
+            let r = extent[0] * a[0].abs() + extent[1] * a[1].abs() + extent[2] * a[2].abs();
+            let p0 = v[0].dot(axis);
+            let p1 = v[1].dot(axis);
+            let p2 = v[2].dot(axis);
+            return p0.min(p1).min(p2) > r || p0.max(p1).max(p2) < -r;
+        };
+        for box_axis in &box_axes {
+            for edge in &edges {
+                if is_separated(box_axis.cross(*edge)) {
+                    return false;
+                }
+            }
+        }
+        return !is_separated(edges[0].cross(edges[1]));
     }
 }
 
-tri!(TexturedTriangle);
+#[cfg(test)]
+mod test {
+    use super::*;
 
-#[derive(Debug, Clone, Copy)]
-pub struct SolidTriangle<T> where T: VectorElement {
-    edg: [Vec3<T>; 2],
-    ind: [usize; 3],
-}
+    fn create_triangle() -> Triangle {
+        return Triangle::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+        );
+    }
 
-impl<T> SolidTriangle<T> where T: VectorElement, Vec3<T>: MathVector<T> {
-    pub fn new<V>(inds: &[usize; 3], vertices: &Vec<V>) -> SolidTriangle<T> where V: HasPosition<T> + HasNormal<T> {
-        SolidTriangle {
-            edg: [
-                *vertices[inds[1]].get_pos() - *vertices[inds[0]].get_pos(),
-                *vertices[inds[2]].get_pos() - *vertices[inds[0]].get_pos(),
-            ],
-            ind: [
-                inds[0],
-                inds[1],
-                inds[2],
-            ],
-        }
+    #[test]
+    fn ray_test() {
+        let t = create_triangle();
+        let down = Vec3::new(0.0, 0.0, -1.0);
+        let hit = t.intersect_ray(&Ray3::new(Vec3::new(0.5, 0.5, 3.0), down), 10.0);
+        assert_eq!(hit, Some((3.0, 0.25, 0.25)));
+        // Back face is hit too.
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let hit = t.intersect_ray(&Ray3::new(Vec3::new(0.5, 0.5, -2.0), up), 10.0);
+        assert_eq!(hit.map(|h| h.0), Some(2.0));
+        assert!(t
+            .intersect_ray(&Ray3::new(Vec3::new(1.5, 1.5, 3.0), down), 10.0)
+            .is_none());
+        assert!(t
+            .intersect_ray(&Ray3::new(Vec3::new(0.5, 0.5, 3.0), down), 2.0)
+            .is_none());
+        assert!(t
+            .intersect_ray(&Ray3::new(Vec3::new(0.5, 0.5, 3.0), up), 10.0)
+            .is_none());
+        let parallel = Ray3::new(Vec3::new(-1.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(t.intersect_ray(&parallel, 10.0).is_none());
+    }
+
+    #[test]
+    fn closest_point_and_sphere_test() {
+        let t = create_triangle();
+        let face = t.get_closest_point(Vec3::new(0.5, 0.5, 4.0));
+        assert!(face == Vec3::new(0.5, 0.5, 0.0));
+        let vertex = t.get_closest_point(Vec3::new(-1.0, -1.0, 1.0));
+        assert!(vertex == Vec3::zero());
+        let edge = t.get_closest_point(Vec3::new(2.0, 2.0, 0.0));
+        assert!(edge == Vec3::new(1.0, 1.0, 0.0));
+        assert!(t.intersects_sphere(Vec3::new(2.0, 2.0, 0.0), 1.5));
+        assert!(!t.intersects_sphere(Vec3::new(2.0, 2.0, 0.0), 1.4));
     }
-}
 
-tri!(SolidTriangle);
+    #[test]
+    fn aabb_test() {
+        let t = create_triangle();
+        let inside = Aabb3::new_with_center_radius(Vec3::new(0.5, 0.5, 0.0), 0.1);
+        assert!(t.intersects_aabb(&inside));
+        // It overlaps the bounding box of the triangle but not the triangle.
+        let corner = Aabb3::new_with_center_radius(Vec3::new(1.8, 1.8, 0.0), 0.3);
+        assert!(t.get_aabb().intersects_aabb(&corner));
+        assert!(!t.intersects_aabb(&corner));
+        let above = Aabb3::new_with_center_radius(Vec3::new(0.5, 0.5, 0.9), 1.0);
+        assert!(t.intersects_aabb(&above));
+        let far_above = Aabb3::new_with_center_radius(Vec3::new(0.5, 0.5, 1.0), 0.4);
+        assert!(!t.intersects_aabb(&far_above));
+        let big = Aabb3::new_with_center_radius(Vec3::zero(), 10.0);
+        assert!(t.intersects_aabb(&big));
+    }
+}
@@ -1,386 +1,337 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
-use std::sync::Arc;
-#[cfg(any(target_os = "macos", target_os = "ios"))]
-use super::super::objc;
-use super::super::system::file::File;
-use super::super::util::cell::DebugCell;
-use super::number::Number;
-
-#[repr(simd)]
-pub struct SVec4D(pub f64, pub f64, pub f64, pub f64);
-#[repr(simd)]
-pub struct SVec3D(pub f64, pub f64, pub f64);
-#[repr(simd)]
-pub struct SVec2D(pub f64, pub f64);
-#[repr(simd)]
-pub struct SVec4F(pub f32, pub f32, pub f32, pub f32);
-#[repr(simd)]
-pub struct SVec3F(pub f32, pub f32, pub f32);
-#[repr(simd)]
-pub struct SVec2F(pub f32, pub f32);
-#[repr(simd)]
-pub struct SVec4U32(pub u32, pub u32, pub u32, pub u32);
-#[repr(simd)]
-pub struct SVec3U32(pub u32, pub u32, pub u32);
-#[repr(simd)]
-pub struct SVec2U32(pub u32, pub u32);
-
-#[repr(usize)]
-#[derive(Debug, Clone, Copy)]
-pub enum Axis {
-    X,
-    Y,
-    Z,
-    W,
-}
+use super::simd::{shuffle_mask, F32x4};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// Its fourth lane is always zero, so it has the size and the alignment of a
+// vec3 in std140 and std430 blocks. Packed vertex data must use to_array.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+#[repr(transparent)]
+pub struct Vec3(pub(super) F32x4);
+
+impl Vec3 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        return Self(F32x4::new(x, y, z, 0.0));
+    }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
-pub struct Vec4<T>
-where
-    T: Number,
-{
-    pub x: T,
-    pub y: T,
-    pub z: T,
-    pub w: T,
-}
+    #[inline]
+    pub fn splat(v: f32) -> Self {
+        return Self::new(v, v, v);
+    }
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
-unsafe impl<T> objc::Encode for Vec4<T>
-where
-    T: Number,
-{
-    fn encode() -> objc::Encoding {
-        let encoding = format!(
-            "{{?={}{}{}{}}}",
-            T::objc_encode(),
-            T::objc_encode(),
-            T::objc_encode(),
-            T::objc_encode()
-        );
-        unsafe { objc::Encoding::from_str(&encoding) }
+    #[inline]
+    pub fn zero() -> Self {
+        return Self(F32x4::zero());
     }
-}
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy, Default)]
-pub struct Vec3<T>
-where
-    T: Number,
-{
-    pub x: T,
-    pub y: T,
-    pub z: T,
-}
+    #[inline]
+    pub fn from_array(a: [f32; 3]) -> Self {
+        return Self::new(a[0], a[1], a[2]);
+    }
 
-macro_rules! as_expr { ($e:expr) => {$e} }
-
-macro_rules! op3 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, 'b, T> $tra<&'b Vec3<T>> for &'a Vec3<T> where T: Number {
-            type Output = Vec3<T>;
-            fn $func(self, other: &'b Vec3<T>) -> Vec3<T> {
-                Vec3 {
-                    x: as_expr!(self.x $opt other.x),
-                    y: as_expr!(self.y $opt other.y),
-                    z: as_expr!(self.z $opt other.z),
-                }
-            }
-        }
-    )
-}
+    #[inline]
+    pub fn to_array(self) -> [f32; 3] {
+        let a = self.0.to_array();
+        return [a[0], a[1], a[2]];
+    }
 
-macro_rules! sop3 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, T> $tra<T> for &'a Vec3<T> where T: Number {
-            type Output = Vec3<T>;
-            fn $func(self, other: T) -> Vec3<T> {
-                Vec3 {
-                    x: as_expr!(self.x $opt other),
-                    y: as_expr!(self.y $opt other),
-                    z: as_expr!(self.z $opt other),
-                }
-            }
-        }
-    )
+    #[inline]
+    pub fn x(self) -> f32 {
+        return self.0.x();
+    }
+
+    #[inline]
+    pub fn y(self) -> f32 {
+        return self.0.to_array()[1];
+    }
+
+    #[inline]
+    pub fn z(self) -> f32 {
+        return self.0.to_array()[2];
+    }
+
+    #[inline]
+    pub fn extend(self, w: f32) -> Vec4 {
+        let a = self.0.to_array();
+        return Vec4::new(a[0], a[1], a[2], w);
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        return self.0.dot(other.0);
+    }
+
+    #[inline]
+    pub fn cross(self, other: Self) -> Self {
+        let a = self.0;
+        let b = other.0;
+        let a_yzx = a.shuffle::<{ shuffle_mask(1, 2, 0, 3) }>(a);
+        let a_zxy = a.shuffle::<{ shuffle_mask(2, 0, 1, 3) }>(a);
+        let b_yzx = b.shuffle::<{ shuffle_mask(1, 2, 0, 3) }>(b);
+        let b_zxy = b.shuffle::<{ shuffle_mask(2, 0, 1, 3) }>(b);
+        return Self(a_yzx * b_zxy - a_zxy * b_yzx);
+    }
+
+    #[inline]
+    pub fn length_squared(self) -> f32 {
+        return self.dot(self);
+    }
+
+    #[inline]
+    pub fn length(self) -> f32 {
+        return self.length_squared().sqrt();
+    }
+
+    #[inline]
+    pub fn normalized(self) -> Self {
+        return self * (1.0 / self.length());
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        return Self(self.0.min(other.0));
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        return Self(self.0.max(other.0));
+    }
+
+    #[inline]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        return self + (other - self) * t;
+    }
 }
 
-macro_rules! opasg3 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, T> $tra<&'a Vec3<T>> for Vec3<T> where T: Number {
-            fn $func(&mut self, other: &'a Vec3<T>) {
-                as_expr!(self.x $opt other.x);
-                as_expr!(self.y $opt other.y);
-                as_expr!(self.z $opt other.z);
-            }
-        }
-    )
+impl Default for Vec3 {
+    fn default() -> Self {
+        return Self::zero();
+    }
 }
 
-macro_rules! sopasg3 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<T> $tra<T> for Vec3<T> where T: Number {
-            fn $func(&mut self, other: T) {
-                as_expr!(self.x $opt other);
-                as_expr!(self.y $opt other);
-                as_expr!(self.z $opt other);
-            }
-        }
-    )
+impl From<cgmath::Vector3<f32>> for Vec3 {
+    fn from(v: cgmath::Vector3<f32>) -> Self {
+        return Self::new(v.x, v.y, v.z);
+    }
 }
 
-op3!(add, Add, +);
-op3!(sub, Sub, -);
-op3!(mul, Mul, *);
-op3!(div, Div, /);
-
-sop3!(add, Add, +);
-sop3!(sub, Sub, -);
-sop3!(mul, Mul, *);
-sop3!(div, Div, /);
-
-opasg3!(add_assign, AddAssign, +=);
-opasg3!(sub_assign, SubAssign, -=);
-opasg3!(mul_assign, MulAssign, *=);
-opasg3!(div_assign, DivAssign, /=);
-
-sopasg3!(add_assign, AddAssign, +=);
-sopasg3!(sub_assign, SubAssign, -=);
-sopasg3!(mul_assign, MulAssign, *=);
-sopasg3!(div_assign, DivAssign, /=);
-
-impl<'a, E> Neg for &'a Vec3<E>
-where
-    E: Number + Neg<Output = E>,
-{
-    type Output = Vec3<E>;
-    fn neg(self) -> Vec3<E> {
-        Vec3 {
-            x: -self.x,
-            y: -self.y,
-            z: -self.z,
-        }
+impl From<Vec3> for cgmath::Vector3<f32> {
+    fn from(v: Vec3) -> Self {
+        let a = v.to_array();
+        return cgmath::Vector3::new(a[0], a[1], a[2]);
     }
 }
 
-impl<T> Vec3<T>
-where
-    T: Number,
-{
-    pub fn new(e: T) -> Self {
-        Vec3 { x: e, y: e, z: e }
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+#[repr(transparent)]
+pub struct Vec4(pub(super) F32x4);
+
+impl Vec4 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        return Self(F32x4::new(x, y, z, w));
     }
 
-    pub fn new_from_file(f: &Arc<DebugCell<File>>) -> Self {
-        let mut f = f.borrow_mut();
-        Vec3 {
-            x: f.read_type(),
-            y: f.read_type(),
-            z: f.read_type(),
-        }
+    #[inline]
+    pub fn splat(v: f32) -> Self {
+        return Self(F32x4::splat(v));
     }
 
-    pub fn dot(&self, o: &Vec3<T>) -> T {
-        (self.x * o.x) + (self.y * o.y) + (self.z * o.z)
+    #[inline]
+    pub fn zero() -> Self {
+        return Self(F32x4::zero());
     }
 
-    pub fn cross(&self, o: &Vec3<T>) -> Self {
-        Vec3 {
-            x: self.y * o.z - self.z * o.y,
-            y: self.z * o.x - self.x * o.z,
-            z: self.x * o.y - self.y * o.x,
-        }
+    #[inline]
+    pub fn from_array(a: [f32; 4]) -> Self {
+        return Self(F32x4::from_array(a));
     }
 
-    pub fn length(&self) -> T {
-        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        return self.0.to_array();
     }
 
-    pub fn absolute_length(&self) -> T {
-        self.x.abs() + self.y.abs() + self.z.abs()
+    #[inline]
+    pub fn x(self) -> f32 {
+        return self.0.x();
     }
 
-    pub fn square_length(&self) -> T {
-        (self.x * self.x) + (self.y * self.y) + (self.z * self.z)
+    #[inline]
+    pub fn y(self) -> f32 {
+        return self.0.to_array()[1];
     }
 
-    pub fn normalize(&mut self) {
-        let len = ((self.x * self.x) + (self.y * self.y) + (self.z * self.z)).sqrt();
-        self.x /= len;
-        self.y /= len;
-        self.z /= len;
+    #[inline]
+    pub fn z(self) -> f32 {
+        return self.0.to_array()[2];
     }
 
-    pub fn normalized(&self) -> Self {
-        let len = ((self.x * self.x) + (self.y * self.y) + (self.z * self.z)).sqrt();
-        Vec3 {
-            x: self.x / len,
-            y: self.y / len,
-            z: self.z / len,
-        }
+    #[inline]
+    pub fn w(self) -> f32 {
+        return self.0.to_array()[3];
+    }
+
+    #[inline]
+    pub fn truncate(self) -> Vec3 {
+        return Vec3(self.0 * F32x4::new(1.0, 1.0, 1.0, 0.0));
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        return self.0.dot(other.0);
+    }
+
+    #[inline]
+    pub fn length_squared(self) -> f32 {
+        return self.dot(self);
+    }
+
+    #[inline]
+    pub fn length(self) -> f32 {
+        return self.length_squared().sqrt();
+    }
+
+    #[inline]
+    pub fn normalized(self) -> Self {
+        return self * (1.0 / self.length());
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        return Self(self.0.min(other.0));
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        return Self(self.0.max(other.0));
+    }
+
+    #[inline]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        return self + (other - self) * t;
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
-unsafe impl<T> objc::Encode for Vec3<T>
-where
-    T: Number,
-{
-    fn encode() -> objc::Encoding {
-        let encoding = format!("![3{}]", T::objc_encode());
-        unsafe { objc::Encoding::from_str(&encoding) }
+impl Default for Vec4 {
+    fn default() -> Self {
+        return Self::zero();
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
-pub struct Vec2<T>
-where
-    T: Number,
-{
-    pub x: T,
-    pub y: T,
+impl From<cgmath::Vector4<f32>> for Vec4 {
+    fn from(v: cgmath::Vector4<f32>) -> Self {
+        return Self::new(v.x, v.y, v.z, v.w);
+    }
 }
 
-macro_rules! op2 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, 'b, T> $tra<&'b Vec2<T>> for &'a Vec2<T> where T: Number {
-            type Output = Vec2<T>;
-            fn $func(self, other: &'b Vec2<T>) -> Vec2<T> {
-                Vec2 {
-                    x: as_expr!(self.x $opt other.x),
-                    y: as_expr!(self.y $opt other.y),
-                }
-            }
-        }
-    )
+impl From<Vec4> for cgmath::Vector4<f32> {
+    fn from(v: Vec4) -> Self {
+        let a = v.to_array();
+        return cgmath::Vector4::new(a[0], a[1], a[2], a[3]);
+    }
 }
 
-macro_rules! sop2 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, T> $tra<T> for &'a Vec2<T> where T: Number {
-            type Output = Vec2<T>;
-            fn $func(self, other: T) -> Vec2<T> {
-                Vec2 {
-                    x: as_expr!(self.x $opt other),
-                    y: as_expr!(self.y $opt other),
-                }
+// Vectors can not be divided by vectors, because the zero lane of Vec3 would
+// become NaN.
+macro_rules! vector_ops {
+    ($t:ident) => {
+        impl Add for $t {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, other: Self) -> Self {
+                return Self(self.0 + other.0);
             }
         }
-    )
-}
 
-macro_rules! opasg2 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<'a, T> $tra<&'a Vec2<T>> for Vec2<T> where T: Number {
-            fn $func(&mut self, other: &'a Vec2<T>) {
-                as_expr!(self.x $opt other.x);
-                as_expr!(self.y $opt other.y);
+        impl Sub for $t {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, other: Self) -> Self {
+                return Self(self.0 - other.0);
             }
         }
-    )
-}
 
-macro_rules! sopasg2 {
-    ($func:ident, $tra:ident, $opt:tt) => (
-        impl<T> $tra<T> for Vec2<T> where T: Number {
-            fn $func(&mut self, other: T) {
-                as_expr!(self.x $opt other);
-                as_expr!(self.y $opt other);
+        impl Mul for $t {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, other: Self) -> Self {
+                return Self(self.0 * other.0);
             }
         }
-    )
-}
 
-op2!(add, Add, +);
-op2!(sub, Sub, -);
-op2!(mul, Mul, *);
-op2!(div, Div, /);
-
-sop2!(add, Add, +);
-sop2!(sub, Sub, -);
-sop2!(mul, Mul, *);
-sop2!(div, Div, /);
-
-opasg2!(add_assign, AddAssign, +=);
-opasg2!(sub_assign, SubAssign, -=);
-opasg2!(mul_assign, MulAssign, *=);
-opasg2!(div_assign, DivAssign, /=);
-
-sopasg2!(add_assign, AddAssign, +=);
-sopasg2!(sub_assign, SubAssign, -=);
-sopasg2!(mul_assign, MulAssign, *=);
-sopasg2!(div_assign, DivAssign, /=);
-
-impl<'a, E> Neg for &'a Vec2<E>
-where
-    E: Number + Neg<Output = E>,
-{
-    type Output = Vec2<E>;
-    fn neg(self) -> Vec2<E> {
-        Vec2 {
-            x: -self.x,
-            y: -self.y,
-        }
-    }
-}
+        impl Mul<f32> for $t {
+            type Output = Self;
 
-impl<T> Vec2<T>
-where
-    T: Number,
-{
-    pub fn new(e: T) -> Vec2<T> {
-        Vec2 { x: e, y: e }
-    }
+            #[inline]
+            fn mul(self, other: f32) -> Self {
+                return Self(self.0 * F32x4::splat(other));
+            }
+        }
 
-    pub fn dot(&self, o: &Vec2<T>) -> T {
-        self.x * o.x + self.y * o.y
-    }
+        impl Div<f32> for $t {
+            type Output = Self;
 
-    pub fn cross(&self, o: &Vec2<T>) -> Vec2<T> {
-        println!("{:?}", o);
-        println!("In 2D we can not have a cross product");
-        Vec2 {
-            x: T::new(0.0),
-            y: T::new(0.0),
+            #[inline]
+            fn div(self, other: f32) -> Self {
+                return Self(self.0 / F32x4::splat(other));
+            }
         }
-    }
 
-    pub fn length(&self) -> T {
-        (self.x * self.x + self.y * self.y).sqrt()
-    }
+        impl Neg for $t {
+            type Output = Self;
 
-    pub fn absolute_length(&self) -> T {
-        self.x.abs() + self.y.abs()
-    }
+            #[inline]
+            fn neg(self) -> Self {
+                return Self(-self.0);
+            }
+        }
 
-    pub fn square_length(&self) -> T {
-        self.x * self.x + self.y * self.y
-    }
+        impl AddAssign for $t {
+            #[inline]
+            fn add_assign(&mut self, other: Self) {
+                self.0 = self.0 + other.0;
+            }
+        }
 
-    pub fn normalize(&mut self) {
-        let len = (self.x * self.x + self.y * self.y).sqrt();
-        self.x /= len;
-        self.y /= len;
-    }
+        impl SubAssign for $t {
+            #[inline]
+            fn sub_assign(&mut self, other: Self) {
+                self.0 = self.0 - other.0;
+            }
+        }
 
-    pub fn normalized(&self) -> Vec2<T> {
-        let len = (self.x * self.x + self.y * self.y).sqrt();
-        Vec2 {
-            x: self.x / len,
-            y: self.y / len,
+        impl MulAssign<f32> for $t {
+            #[inline]
+            fn mul_assign(&mut self, other: f32) {
+                self.0 = self.0 * F32x4::splat(other);
+            }
         }
-    }
+    };
 }
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
-unsafe impl<T> objc::Encode for Vec2<T>
-where
-    T: Number,
-{
-    fn encode() -> objc::Encoding {
-        let encoding = format!("{{?={}{}}}", T::objc_encode(), T::objc_encode());
-        unsafe { objc::Encoding::from_str(&encoding) }
+vector_ops!(Vec3);
+vector_ops!(Vec4);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cross_dot_test() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(-4.0, 5.0, 0.5);
+        let c = a.cross(b);
+        let expected =
+            cgmath::Vector3::new(1.0, 2.0, 3.0).cross(cgmath::Vector3::new(-4.0, 5.0, 0.5));
+        assert!(c == Vec3::from(expected));
+        assert_eq!(c.0.to_array()[3], 0.0);
+        assert_eq!(c.dot(a), 0.0);
+        assert_eq!(a.dot(b), 7.5);
+        assert_eq!(Vec4::new(1.0, 2.0, 3.0, 4.0).dot(Vec4::splat(2.0)), 20.0);
     }
 }
@@ -1,5 +1,10 @@
 use super::{
-    super::render::engine::Engine as RenderEngine, config::Config,
+    super::core::event::{Type as EventType, Window},
+    super::render::{
+        capture::{create_screenshot_file_name, is_screenshot_hotkey},
+        engine::Engine as RenderEngine,
+    },
+    config::Config,
     os::application::Application as OsApp,
 };
 
@@ -18,6 +23,27 @@ impl Base {
         }
     }
 
+    // Engine wide hotkeys, platform applications must pass every event to it.
+    pub fn on_event(&mut self, e: &EventType) {
+        let render_engine = match &mut self.render_engine {
+            Some(render_engine) => render_engine,
+            None => return,
+        };
+        if is_screenshot_hotkey(e) {
+            render_engine.request_screenshot(&create_screenshot_file_name());
+        }
+        if let EventType::Window(Window::SizeChange { w, h, .. }) = e {
+            render_engine.resize(*w as u32, *h as u32);
+        }
+    }
+
+    #[cfg(all(headless, not(software_gapi)))]
+    pub fn init(_: &mut OsApp) {
+        // there is no surface, render engine is skipped
+    }
+
+    // software graphic API renders offscreen when there is no window
+    #[cfg(any(not(headless), software_gapi))]
     pub fn init(os_app: &mut OsApp) {
         let render_engine = RenderEngine::new(os_app);
         os_app.base.render_engine.replace(render_engine);
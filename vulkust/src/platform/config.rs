@@ -1,6 +1,8 @@
 pub struct Windowed {
     pub width: u32,
     pub height: u32,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
 }
 
 impl Default for Windowed {
@@ -8,6 +10,8 @@ impl Default for Windowed {
         Self {
             width: 1000,
             height: 700,
+            min_size: None,
+            max_size: None,
         }
     }
 }
@@ -17,9 +21,18 @@ pub enum ScreenState {
     Windowed(Windowed),
 }
 
+// Pixels are in RGBA order, row by row from the top.
+#[derive(Clone)]
+pub struct Icon {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
 pub struct Config {
     pub screen_state: ScreenState,
     pub application_name: String,
+    pub icon: Option<Icon>,
 }
 
 impl Default for Config {
@@ -27,6 +40,7 @@ impl Default for Config {
         Self {
             screen_state: ScreenState::Windowed(Windowed::default()),
             application_name: "Gearoenix App".to_string(),
+            icon: None,
         }
     }
 }
@@ -0,0 +1,211 @@
+use super::super::{
+    super::core::{
+        event::{Event, Type as EventType, Window},
+        timing::Timing,
+        types::Real,
+    },
+    base::Base,
+    config::{Config, ScreenState},
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// Application without any display connection, window or surface.
+// It is meant for dedicated servers, asset cookers and CI tests, events can be
+// injected from any thread through the queue returned by get_event_queue.
+
+pub type EventQueue = Arc<Mutex<VecDeque<EventType>>>;
+
+pub struct Application {
+    pub base: Base,
+    pub timing: Timing,
+    events: EventQueue,
+    event_handler: Option<Box<dyn FnMut(&Event)>>,
+    frames_limit: Option<u64>,
+    frame_number: u64,
+    width: Real,
+    height: Real,
+}
+
+impl Application {
+    pub fn new() -> Self {
+        Self::new_with_config(Config::default())
+    }
+
+    pub fn new_with_config(config: Config) -> Self {
+        let (width, height) = if let ScreenState::Windowed(windowed) = &config.screen_state {
+            (windowed.width as Real, windowed.height as Real)
+        } else {
+            vx_log_i!("Fullscreen is meaningless in headless mode, default size is used.");
+            (1000.0, 700.0)
+        };
+        Self {
+            base: Base::new(config),
+            timing: Timing::new(),
+            events: Arc::new(Mutex::new(VecDeque::new())),
+            event_handler: None,
+            frames_limit: None,
+            frame_number: 0,
+            width,
+            height,
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.base.is_running = true;
+        while self.base.is_running {
+            self.timing.update();
+            self.process_events();
+            if !self.base.is_running {
+                break;
+            }
+            if let Some(render_engine) = &mut self.base.render_engine {
+                render_engine.update();
+            }
+            self.frame_number += 1;
+            if let Some(limit) = self.frames_limit {
+                if self.frame_number >= limit {
+                    self.base.is_running = false;
+                }
+            }
+        }
+    }
+
+    fn process_events(&mut self) {
+        let events: Vec<EventType> = vx_result!(self.events.lock()).drain(..).collect();
+        for e in events {
+            match &e {
+                EventType::Quit => self.base.is_running = false,
+                EventType::Window(Window::SizeChange { w, h, .. }) => {
+                    self.width = *w;
+                    self.height = *h;
+                }
+                _ => (),
+            }
+            self.base.on_event(&e);
+            if let Some(handler) = &mut self.event_handler {
+                handler(&Event::new(e));
+            }
+        }
+    }
+
+    pub fn get_event_queue(&self) -> EventQueue {
+        return self.events.clone();
+    }
+
+    pub fn push_event(&self, e: EventType) {
+        vx_result!(self.events.lock()).push_back(e);
+    }
+
+    pub fn set_event_handler(&mut self, handler: Box<dyn FnMut(&Event)>) {
+        self.event_handler = Some(handler);
+    }
+
+    // After this number of frames run returns, it is useful for tests.
+    pub fn set_frames_limit(&mut self, limit: Option<u64>) {
+        self.frames_limit = limit;
+    }
+
+    pub fn get_frame_number(&self) -> u64 {
+        return self.frame_number;
+    }
+
+    pub fn quit(&mut self) {
+        self.base.is_running = false;
+    }
+
+    pub fn get_window_aspect_ratio(&self) -> Real {
+        return self.width / self.height;
+    }
+
+    pub fn get_window_size(&self) -> (Real, Real) {
+        return (self.width, self.height);
+    }
+
+    pub fn get_mouse_position(&self) -> (Real, Real) {
+        return (0.0, 0.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::config::Windowed;
+    use super::*;
+
+    #[test]
+    fn event_queue_test() {
+        let mut config = Config::default();
+        config.screen_state = ScreenState::Windowed(Windowed {
+            width: 100,
+            height: 100,
+            ..Windowed::default()
+        });
+        let mut app = Application::new_with_config(config);
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let handler_received = received.clone();
+        app.set_event_handler(Box::new(move |e: &Event| {
+            vx_result!(handler_received.lock()).push(e.event_type.clone());
+        }));
+        let queue = app.get_event_queue();
+        vx_result!(std::thread::spawn(move || {
+            let mut queue = vx_result!(queue.lock());
+            queue.push_back(EventType::Window(Window::SizeChange {
+                w: 300.0,
+                h: 150.0,
+                ratio: 2.0,
+                pre_w: 100.0,
+                pre_h: 100.0,
+                pre_ratio: 1.0,
+            }));
+            queue.push_back(EventType::Window(Window::FocusLost));
+        })
+        .join());
+        app.push_event(EventType::Quit);
+        app.run();
+        let received = vx_result!(received.lock());
+        assert_eq!(received.len(), 3);
+        match &received[0] {
+            EventType::Window(Window::SizeChange { w, h, .. }) => {
+                assert_eq!((*w, *h), (300.0, 150.0))
+            }
+            _ => vx_unexpected!(),
+        }
+        match &received[1] {
+            EventType::Window(Window::FocusLost) => {}
+            _ => vx_unexpected!(),
+        }
+        match &received[2] {
+            EventType::Quit => {}
+            _ => vx_unexpected!(),
+        }
+        assert_eq!(app.get_window_size(), (300.0, 150.0));
+        assert_eq!(app.get_window_aspect_ratio(), 2.0);
+        // Quit stops the loop before the frame is counted.
+        assert_eq!(app.get_frame_number(), 0);
+        assert!(vx_result!(app.get_event_queue().lock()).is_empty());
+    }
+
+    #[test]
+    fn frames_limit_test() {
+        let mut app = Application::new();
+        app.set_frames_limit(Some(3));
+        app.run();
+        assert_eq!(app.get_frame_number(), 3);
+        assert!(!app.base.is_running);
+        // Limit is the total count of frames, not the count of each run.
+        app.set_frames_limit(Some(5));
+        app.run();
+        assert_eq!(app.get_frame_number(), 5);
+        // Events pushed between the runs are delivered by the next one.
+        let delivered = Arc::new(Mutex::new(0));
+        let handler_delivered = delivered.clone();
+        app.set_event_handler(Box::new(move |_: &Event| {
+            *vx_result!(handler_delivered.lock()) += 1;
+        }));
+        app.push_event(EventType::Window(Window::FocusGained));
+        app.set_frames_limit(Some(6));
+        app.run();
+        assert_eq!(app.get_frame_number(), 6);
+        assert_eq!(*vx_result!(delivered.lock()), 1);
+    }
+}
@@ -2,9 +2,9 @@
 extern crate libc;
 use std::ffi::CString;
 use std::mem::transmute_copy;
-use std::ptr::null_mut;
 
 #[cfg(any(target_os = "linux", target_os = "ios", target_os = "macos", target_os = "android"))]
+#[cfg_attr(debug_mode, derive(Debug))]
 pub struct Linker {
     link: *mut libc::c_void,
 }
@@ -20,7 +20,7 @@ impl Linker {
 
     #[cfg(any(target_os = "linux", target_os = "ios", target_os = "macos", target_os = "android"))]
     pub fn is_ok(&self) -> bool {
-        self.link != null_mut()
+        !self.link.is_null()
     }
 
     #[cfg(any(target_os = "linux", target_os = "ios", target_os = "macos", target_os = "android"))]
@@ -34,7 +34,7 @@ impl Linker {
         F: Sized,
     {
         let f = self.get_fun_ptr(name);
-        if f == null_mut() {
+        if f.is_null() {
             return None;
         } else {
             unsafe {
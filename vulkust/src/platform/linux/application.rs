@@ -1,20 +1,83 @@
 use super::super::super::core::application::Application as CoreAppTrait;
+#[cfg(wayland)]
 use super::super::super::core::constants::{DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH};
 use super::super::super::core::event;
-use super::super::super::core::event::{Button, Event, Keyboard, Mouse, Type as EventType, Window};
-use super::super::super::core::types::Real;
+use super::super::super::core::event::{
+    Button, Event, Keyboard, Mouse, Selection, Type as EventType, Window, MAIN_WINDOW_ID,
+};
+use super::super::super::core::object::create_id;
+use super::super::super::core::types::{Id, Real};
 use super::super::super::libc;
+use super::super::super::render::capture::{create_screenshot_file_name, is_screenshot_hotkey};
 use super::super::super::render::engine::Engine as RenderEngine;
+use super::super::config::{Config, Icon, ScreenState};
+use super::clipboard::Clipboard;
+use super::dnd::DragAndDrop;
+#[cfg(software_gapi)]
+use super::presenter::Presenter;
+#[cfg(wayland)]
+use super::wayland::client::Display as WaylandDisplay;
+#[cfg(wayland)]
+use super::wayland::window::Window as WaylandWindow;
 use super::xcb;
+use super::xinput;
 use super::xproto;
 // use super::super::super::core::asset::manager::Manager as AssetManager;
 // use super::super::file::File;
 
+use std::collections::BTreeMap;
 use std::ffi::CString;
 use std::mem::transmute;
-use std::os::raw::{c_int, c_uint};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
 use std::ptr::null_mut;
 use std::sync::{Arc, RwLock};
+use std::thread::sleep;
+use std::time::Duration;
+
+// Window manager size hints flags, from ICCCM.
+const SIZE_HINT_P_MIN_SIZE: u32 = 1 << 4;
+const SIZE_HINT_P_MAX_SIZE: u32 = 1 << 5;
+const SIZE_HINTS_LENGTH: usize = 18;
+
+// Actions of _NET_WM_STATE client message, from EWMH.
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+const NET_WM_SOURCE_APPLICATION: u32 = 1;
+
+// Xft.dpi of a screen without any scaling.
+const DEFAULT_DPI: Real = 96.0;
+// Resources of X server are usually a few kilobytes, length is in 4 bytes.
+const RESOURCES_MAX_LENGTH: u32 = 64 * 1024;
+
+// Loop does not render when the window is minimized, it only waits for the
+// events.
+const MINIMIZED_SLEEP: Duration = Duration::from_millis(16);
+
+// Everything that can change after the creation of window.
+#[derive(Default)]
+#[cfg_attr(debug_mode, derive(Debug))]
+struct WindowState {
+    width: Real,
+    height: Real,
+    is_fullscreen: bool,
+    is_cursor_hidden: bool,
+    is_pointer_grabbed: bool,
+    is_relative_mouse: bool,
+    xinput_opcode: Option<u8>,
+    is_focused: bool,
+    is_minimized: bool,
+    scale_factor: Real,
+    needs_close_confirmation: bool,
+    is_quit_requested: bool,
+}
+
+// Windows other than the main one, they have the window, mouse and keyboard
+// events, the rest of the platform features are only on the main window.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct SecondaryWindow {
+    window: xcb::Window,
+    state: RwLock<WindowState>,
+}
 
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Application {
@@ -23,15 +86,73 @@ pub struct Application {
     connection: *mut xcb::Connection,
     screen: *mut xcb::Screen,
     window: xcb::Window,
-    atom_wm_delete_window: *mut xcb::InternAtomReply,
-    window_width: Real,
-    window_height: Real,
-    window_aspect_ratio: Real,
+    atom_wm_protocols: xcb::Atom,
+    atom_wm_delete_window: xcb::Atom,
+    atom_net_wm_state: xcb::Atom,
+    atom_net_wm_state_fullscreen: xcb::Atom,
+    atom_resource_manager: xcb::Atom,
+    invisible_cursor: xcb::Cursor,
+    clipboard: Option<RwLock<Clipboard>>,
+    drag_and_drop: Option<RwLock<DragAndDrop>>,
+    window_state: RwLock<WindowState>,
+    secondary_windows: RwLock<BTreeMap<Id, SecondaryWindow>>,
     current_mouse_position: Arc<RwLock<(Real, Real)>>,
+    // When it is set, X11 members are not initialized.
+    #[cfg(wayland)]
+    wayland: Option<RwLock<WaylandWindow>>,
 }
 
 impl Application {
     pub fn new(core_app: Arc<RwLock<dyn CoreAppTrait>>) -> Self {
+        return Self::new_with_config(core_app, Config::default());
+    }
+
+    pub fn new_with_config(core_app: Arc<RwLock<dyn CoreAppTrait>>, config: Config) -> Self {
+        let is_fullscreen = match &config.screen_state {
+            ScreenState::Fullscreen => true,
+            ScreenState::Windowed(_) => false,
+        };
+        #[cfg(wayland)]
+        {
+            let (width, height) = match &config.screen_state {
+                ScreenState::Windowed(windowed) => (windowed.width, windowed.height),
+                ScreenState::Fullscreen => {
+                    (DEFAULT_WINDOW_WIDTH as u32, DEFAULT_WINDOW_HEIGHT as u32)
+                }
+            };
+            let wayland = WaylandWindow::new(&config.application_name, width, height);
+            if let Some(wayland) = wayland {
+                if is_fullscreen {
+                    wayland.set_fullscreen(true);
+                }
+                let (width, height) = wayland.get_size();
+                return Application {
+                    renderer: None,
+                    core_app: Some(core_app),
+                    connection: null_mut(),
+                    screen: null_mut(),
+                    window: 0,
+                    atom_wm_protocols: 0,
+                    atom_wm_delete_window: 0,
+                    atom_net_wm_state: 0,
+                    atom_net_wm_state_fullscreen: 0,
+                    atom_resource_manager: 0,
+                    invisible_cursor: xcb::NONE,
+                    clipboard: None,
+                    drag_and_drop: None,
+                    window_state: RwLock::new(WindowState {
+                        width,
+                        height,
+                        is_fullscreen,
+                        ..WindowState::default()
+                    }),
+                    secondary_windows: RwLock::new(BTreeMap::new()),
+                    current_mouse_position: Arc::new(RwLock::new(wayland.get_mouse_position())),
+                    wayland: Some(RwLock::new(wayland)),
+                };
+            }
+            vx_log_i!("Wayland window can not be created, X11 is used.");
+        }
         let mut scr = 0 as c_int;
         let connection: *mut xcb::Connection = unsafe { xcb::xcb_connect(null_mut(), &mut scr) };
         if connection == null_mut() {
@@ -45,94 +166,65 @@ impl Application {
             }
         }
         let screen = iter.data;
-        let window: xcb::Window = unsafe { transmute(xcb::xcb_generate_id(connection)) };
-        let mut value_list = vec![0u32; 32];
-        value_list[0] = unsafe { (*screen).black_pixel };
-        value_list[1] = (xcb::EventMask::KEY_RELEASE
-            | xcb::EventMask::KEY_PRESS
-            | xcb::EventMask::EXPOSURE
-            | xcb::EventMask::STRUCTURE_NOTIFY
-            | xcb::EventMask::POINTER_MOTION
-            | xcb::EventMask::BUTTON_PRESS
-            | xcb::EventMask::BUTTON_RELEASE
-            | xcb::EventMask::RESIZE_REDIRECT)
-            .bits();
-        let value_mask = (xcb::CW::BACK_PIXEL | xcb::CW::EVENT_MASK).bits();
-        let window_width = DEFAULT_WINDOW_WIDTH as u16;
-        let window_height = DEFAULT_WINDOW_HEIGHT as u16;
-        unsafe {
-            xcb::xcb_create_window(
-                connection,
-                xcb::COPY_FROM_PARENT as u8,
-                window,
-                (*screen).root,
-                0,
-                0,
-                window_width,
-                window_height,
-                0,
-                xcb::WindowClass::InputOutput as u16,
-                (*screen).root_visual,
-                value_mask,
-                value_list.as_ptr(),
-            );
-        }
-        let window_width = window_width as f32;
-        let window_height = window_height as f32;
-        let window_aspect_ratio = window_width / window_height;
-        /* Magic code that will send notification when window is destroyed */
-        let cs = CString::new("WM_PROTOCOLS".to_string().into_bytes()).unwrap();
-        let cookie = unsafe { xcb::xcb_intern_atom(connection, 1, 12, cs.as_ptr()) };
-        let reply = unsafe { xcb::xcb_intern_atom_reply(connection, cookie, null_mut()) };
-        if reply == null_mut() {
-            vx_log_f!("Reply is null.");
-        }
-        let cs = CString::new("WM_DELETE_WINDOW".to_string().into_bytes()).unwrap();
-        let cookie2 = unsafe { xcb::xcb_intern_atom(connection, 0, 16, cs.as_ptr()) };
-        let atom_wm_delete_window: *mut xcb::InternAtomReply =
-            unsafe { xcb::xcb_intern_atom_reply(connection, cookie2, null_mut()) };
-        unsafe {
-            xcb::xcb_change_property(
-                connection,
-                xproto::PropMode::Replace as u8,
-                window,
-                (*reply).atom,
-                4,
-                32,
-                1,
-                transmute(&((*atom_wm_delete_window).atom)),
-            );
-        }
-        let cs = CString::new("Vulkust Game Engine Demo".to_string().into_bytes()).unwrap();
+        let atom_wm_protocols = intern_atom(connection, "WM_PROTOCOLS");
+        let atom_wm_delete_window = intern_atom(connection, "WM_DELETE_WINDOW");
+        let atom_net_wm_state = intern_atom(connection, "_NET_WM_STATE");
+        let atom_net_wm_state_fullscreen = intern_atom(connection, "_NET_WM_STATE_FULLSCREEN");
+        let window = create_x11_window(
+            connection,
+            screen,
+            &config,
+            atom_wm_protocols,
+            atom_wm_delete_window,
+            atom_net_wm_state,
+            atom_net_wm_state_fullscreen,
+        );
+        let (window_width, window_height) = get_config_size(&config, screen);
+        // Changes of Xft.dpi come as property changes of the root window.
+        let atom_resource_manager = intern_atom(connection, "RESOURCE_MANAGER");
+        let root = unsafe { (*screen).root };
+        let root_event_mask = xcb::EventMask::PROPERTY_CHANGE.bits();
         unsafe {
-            xcb::xcb_change_property(
+            xcb::xcb_change_window_attributes(
                 connection,
-                xproto::PropMode::Replace as u8,
-                window,
-                xcb::AtomEnum::WmName as u32,
-                xcb::AtomEnum::String as u32,
-                8,
-                cs.as_bytes_with_nul().len() as u32,
-                transmute(cs.as_ptr()),
+                root,
+                xcb::CW::EVENT_MASK.bits(),
+                &root_event_mask,
             );
-            libc::free(transmute(reply));
-            xcb::xcb_map_window(connection, window);
-            xcb::xcb_flush(connection);
         }
+        let scale_factor = read_scale_factor(connection, root, atom_resource_manager);
+        let invisible_cursor = create_invisible_cursor(connection, window);
+        let clipboard = Some(RwLock::new(Clipboard::new(connection, window)));
+        let drag_and_drop = Some(RwLock::new(DragAndDrop::new(connection, window, screen)));
         let current_mouse_position =
             Arc::new(RwLock::new(get_mouse_position(connection, window, screen)));
-        Application {
+        let myself = Application {
             renderer: None,
             core_app: Some(core_app),
             connection,
             screen,
             window,
+            atom_wm_protocols,
             atom_wm_delete_window,
-            window_width,
-            window_height,
-            window_aspect_ratio,
+            atom_net_wm_state,
+            atom_net_wm_state_fullscreen,
+            atom_resource_manager,
+            invisible_cursor,
+            clipboard,
+            drag_and_drop,
+            window_state: RwLock::new(WindowState {
+                width: window_width as Real,
+                height: window_height as Real,
+                is_fullscreen,
+                scale_factor,
+                ..WindowState::default()
+            }),
+            secondary_windows: RwLock::new(BTreeMap::new()),
             current_mouse_position,
-        }
+            #[cfg(wayland)]
+            wayland: None,
+        };
+        return myself;
     }
 
     pub fn initialize(_itself: &Arc<RwLock<Application>>) {}
@@ -145,42 +237,104 @@ impl Application {
         'main_loop: loop {
             let events = self.fetch_events();
             for e in events {
-                match e.event_type {
-                    EventType::Quit => {
-                        // todo
-                        // terminate core
-                        // terminate renderer
-                        // terminate audio engine
-                        // terminate physic engine
-                        break 'main_loop;
-                    }
-                    _ => (),
+                let is_quit = match e.event_type {
+                    EventType::Quit => true,
+                    _ => false,
+                };
+                if is_screenshot_hotkey(&e.event_type) {
+                    vx_result!(vx_unwrap!(&self.renderer).write())
+                        .request_screenshot(&create_screenshot_file_name());
                 }
+                if let EventType::Window(Window::SizeChange { w, h, .. }) = &e.event_type {
+                    vx_result!(vx_unwrap!(&self.renderer).write()).resize(*w as u32, *h as u32);
+                }
+                // Core application gets the Quit too, to save and release its
+                // resources, the rest is released by dropping.
                 vx_result!(vx_unwrap!(&self.core_app).read()).on_event(e);
+                if is_quit {
+                    break 'main_loop;
+                }
             }
             vx_result!(vx_unwrap!(&self.core_app).write()).update();
+            if self.is_minimized() {
+                sleep(MINIMIZED_SLEEP);
+                continue;
+            }
             vx_result!(vx_unwrap!(&self.renderer).read()).update();
         }
     }
 
     pub fn get_mouse_position(&self) -> (Real, Real) {
+        #[cfg(wayland)]
+        {
+            if let Some(wayland) = &self.wayland {
+                return vx_result!(wayland.read()).get_mouse_position();
+            }
+        }
         get_mouse_position(self.connection, self.window, self.screen)
     }
 
     pub fn get_window_ratio(&self) -> f64 {
+        #[cfg(wayland)]
+        {
+            if self.wayland.is_some() {
+                return self.get_window_aspect_ratio() as f64;
+            }
+        }
         unsafe { (*self.screen).width_in_pixels as f64 / (*self.screen).height_in_pixels as f64 }
     }
 
     pub fn fetch_events(&self) -> Vec<Event> {
-        let mut events = Vec::new();
+        let events = self.fetch_backend_events();
+        let mut result = Vec::with_capacity(events.len());
+        let needs_close_confirmation =
+            vx_result!(self.window_state.read()).needs_close_confirmation;
+        for (window_id, e) in events {
+            let is_close_request = match &e {
+                EventType::Window(Window::CloseRequest) => true,
+                _ => false,
+            };
+            result.push(Event::new_with_window(e, window_id));
+            if !is_close_request || needs_close_confirmation {
+                continue;
+            }
+            if window_id == MAIN_WINDOW_ID {
+                result.push(Event::new(EventType::Quit));
+            } else {
+                self.destroy_window(window_id);
+            }
+        }
+        let is_quit_requested = {
+            let mut window_state = vx_result!(self.window_state.write());
+            let is_quit_requested = window_state.is_quit_requested;
+            window_state.is_quit_requested = false;
+            is_quit_requested
+        };
+        if is_quit_requested {
+            result.push(Event::new(EventType::Quit));
+        }
+        return result;
+    }
+
+    fn fetch_backend_events(&self) -> Vec<(Id, EventType)> {
+        #[cfg(wayland)]
+        {
+            if let Some(wayland) = &self.wayland {
+                let events = vx_result!(wayland.write()).fetch_events();
+                return events.into_iter().map(|e| (MAIN_WINDOW_ID, e)).collect();
+            }
+        }
+        let events = vx_result!(vx_unwrap!(&self.clipboard).write()).take_events();
+        let mut events: Vec<(Id, EventType)> =
+            events.into_iter().map(|e| (MAIN_WINDOW_ID, e)).collect();
         loop {
             let xcb_event = unsafe { xcb::xcb_poll_for_event(self.connection) };
             if xcb_event == null_mut() {
                 break;
             }
-            let e = self.translate(xcb_event);
+            let e = self.translate_with_window(xcb_event);
             if let Some(e) = e {
-                events.push(Event::new(e));
+                events.push(e);
             }
             unsafe {
                 libc::free(transmute(xcb_event));
@@ -189,26 +343,122 @@ impl Application {
         return events;
     }
 
-    fn translate(&self, e: *mut xcb::GenericEvent) -> Option<EventType> {
-        unsafe {
-            if (xproto::DESTROY_NOTIFY as u8 == ((*e).response_type & 0x7f))
-                || ((xproto::CLIENT_MESSAGE as u8 == ((*e).response_type & 0x7f))
-                    && ((*transmute::<*mut xcb::GenericEvent, *mut xcb::ClientMessageEvent>(e))
-                        .data
-                        .data[0]
-                        == (*self.atom_wm_delete_window).atom))
-            {
-                return Some(EventType::Quit);
+    fn translate_with_window(&self, e: *mut xcb::GenericEvent) -> Option<(Id, EventType)> {
+        if let Some(window) = get_event_window(e) {
+            if window != self.window {
+                let secondary_windows = vx_result!(self.secondary_windows.read());
+                for (id, secondary) in secondary_windows.iter() {
+                    if secondary.window == window {
+                        return self.translate(e, &secondary.state, false).map(|t| (*id, t));
+                    }
+                }
+                // Last events of a destroyed window.
+                return None;
             }
         }
+        return self
+            .translate(e, &self.window_state, true)
+            .map(|t| (MAIN_WINDOW_ID, t));
+    }
+
+    // Root window, selections and raw motions are only for the main window.
+    fn translate(
+        &self,
+        e: *mut xcb::GenericEvent,
+        window_state: &RwLock<WindowState>,
+        is_main: bool,
+    ) -> Option<EventType> {
         match unsafe { (*e).response_type as c_uint & 0x7F } {
             xproto::CLIENT_MESSAGE => {
                 let client_msg: &mut xcb::ClientMessageEvent = unsafe { transmute(e) };
-                if client_msg.data.data[0] == unsafe { (*self.atom_wm_delete_window).atom } {
-                    return Some(EventType::Quit);
+                if client_msg.data.data[0] == self.atom_wm_delete_window {
+                    return Some(EventType::Window(Window::CloseRequest));
+                }
+                return vx_result!(vx_unwrap!(&self.drag_and_drop).write())
+                    .on_client_message(client_msg);
+            }
+            xproto::SELECTION_REQUEST => {
+                let request: &xcb::SelectionRequestEvent = unsafe { transmute(e) };
+                vx_result!(vx_unwrap!(&self.clipboard).write()).on_selection_request(request);
+            }
+            xproto::SELECTION_CLEAR => {
+                let clear: &xcb::SelectionClearEvent = unsafe { transmute(e) };
+                vx_result!(vx_unwrap!(&self.clipboard).write()).on_selection_clear(clear);
+            }
+            xproto::SELECTION_NOTIFY => {
+                let notify: &xcb::SelectionNotifyEvent = unsafe { transmute(e) };
+                let e =
+                    vx_result!(vx_unwrap!(&self.drag_and_drop).write()).on_selection_notify(notify);
+                if e.is_some() {
+                    return e;
                 }
+                return vx_result!(vx_unwrap!(&self.clipboard).write()).on_selection_notify(notify);
+            }
+            a @ xproto::FOCUS_IN | a @ xproto::FOCUS_OUT => {
+                let focus: &xcb::FocusInEvent = unsafe { transmute(e) };
+                // Grabs and the pointer focus do not change the keyboard focus.
+                if focus.mode == xproto::NOTIFY_MODE_GRAB
+                    || focus.mode == xproto::NOTIFY_MODE_UNGRAB
+                    || focus.detail == xproto::NOTIFY_DETAIL_POINTER
+                {
+                    return None;
+                }
+                let is_focused = a == xproto::FOCUS_IN;
+                let mut window_state = vx_result!(window_state.write());
+                if window_state.is_focused == is_focused {
+                    return None;
+                }
+                window_state.is_focused = is_focused;
+                return Some(EventType::Window(if is_focused {
+                    Window::FocusGained
+                } else {
+                    Window::FocusLost
+                }));
+            }
+            xproto::UNMAP_NOTIFY => {
+                let mut window_state = vx_result!(window_state.write());
+                if window_state.is_minimized {
+                    return None;
+                }
+                window_state.is_minimized = true;
+                return Some(EventType::Window(Window::Minimized));
+            }
+            xproto::MAP_NOTIFY => {
+                let mut window_state = vx_result!(window_state.write());
+                if !window_state.is_minimized {
+                    return None;
+                }
+                window_state.is_minimized = false;
+                return Some(EventType::Window(Window::Restored));
+            }
+            xproto::PROPERTY_NOTIFY => {
+                let property: &xcb::PropertyNotifyEvent = unsafe { transmute(e) };
+                let root = unsafe { (*self.screen).root };
+                // Other windows are the requestors of clipboard transfers.
+                if property.window != root {
+                    return vx_result!(vx_unwrap!(&self.clipboard).write())
+                        .on_property_notify(property);
+                }
+                if property.atom != self.atom_resource_manager {
+                    return None;
+                }
+                let scale = read_scale_factor(self.connection, root, self.atom_resource_manager);
+                let mut window_state = vx_result!(self.window_state.write());
+                let pre_scale = window_state.scale_factor;
+                if scale == pre_scale {
+                    return None;
+                }
+                window_state.scale_factor = scale;
+                return Some(EventType::Window(Window::ScaleFactorChange {
+                    scale,
+                    pre_scale,
+                }));
             }
             xproto::MOTION_NOTIFY => {
+                // In the relative mode, movements come from the raw motions.
+                if vx_result!(self.window_state.read()).is_relative_mouse {
+                    return None;
+                }
                 let pos = self.get_mouse_position();
                 let pre = *vx_result!(self.current_mouse_position.read());
                 *vx_result!(self.current_mouse_position.write()) = pos;
@@ -218,6 +468,30 @@ impl Application {
                     delta: (pos.0 - pre.0, pos.1 - pre.1),
                 }));
             }
+            xproto::GE_GENERIC => {
+                let window_state = vx_result!(self.window_state.read());
+                if !window_state.is_relative_mouse {
+                    return None;
+                }
+                let opcode = vx_unwrap!(window_state.xinput_opcode);
+                if !xinput::is_raw_motion(e as *const xcb::GeGenericEvent, opcode) {
+                    return None;
+                }
+                let delta = xinput::get_raw_motion_delta(e as *const xinput::RawMotionEvent);
+                // Deltas have the same unit of mouse positions.
+                let delta = unsafe {
+                    (
+                        delta.0 as Real / (*self.screen).width_in_pixels as Real,
+                        delta.1 as Real / (*self.screen).height_in_pixels as Real,
+                    )
+                };
+                let pos = *vx_result!(self.current_mouse_position.read());
+                return Some(EventType::Move(event::Move::Mouse {
+                    previous: pos,
+                    current: pos,
+                    delta,
+                }));
+            }
             xproto::BUTTON_PRESS => {
                 let press: &mut xcb::ButtonPressEvent = unsafe { transmute(e) };
                 let m: xcb::ButtonIndex = unsafe { transmute(press.detail as u32) };
@@ -261,6 +535,9 @@ impl Application {
                     xproto::KEY_D => Keyboard::D,
                     // xproto::KEY_P => { Keyboard::P },
                     xproto::KEY_F1 => Keyboard::Function(1),
+                    xproto::KEY_F11 => Keyboard::Function(11),
+                    xproto::KEY_F12 => Keyboard::Function(12),
+                    xproto::KEY_PRINT => Keyboard::PrintScreen,
                     k @ _ => {
                         vx_log_i!("Unknown key: {:?} presse", k);
                         Keyboard::W
@@ -279,24 +556,34 @@ impl Application {
                 });
             }
             xproto::DESTROY_NOTIFY => {
-                return Some(EventType::Quit);
+                // Secondary windows are only destroyed by destroy_window.
+                if is_main {
+                    return Some(EventType::Quit);
+                }
             }
             xproto::CONFIGURE_NOTIFY => {
                 let cfg_event: &xcb::ConfigureNotifyEvent = unsafe { transmute(e) };
-                // if cfg_event.width as Real != self.window_aspects.0 ||
-                //     cfg_event.height as Real != self.window_aspects.1
-                // {
-                if cfg_event.width > 0 && cfg_event.height > 0 {
-                    return Some(EventType::Window(Window::SizeChange {
-                        w: cfg_event.width as Real,
-                        h: cfg_event.height as Real,
-                        ratio: (cfg_event.width as Real) / (cfg_event.height as Real),
-                        pre_w: 0.0,
-                        pre_h: 0.0,
-                        pre_ratio: 0.0,
-                    }));
+                if cfg_event.width == 0 || cfg_event.height == 0 {
+                    return None;
                 }
-                // }
+                let w = cfg_event.width as Real;
+                let h = cfg_event.height as Real;
+                // Moving of window sends it too.
+                let mut window_state = vx_result!(window_state.write());
+                let (pre_w, pre_h) = (window_state.width, window_state.height);
+                if w == pre_w && h == pre_h {
+                    return None;
+                }
+                window_state.width = w;
+                window_state.height = h;
+                return Some(EventType::Window(Window::SizeChange {
+                    w,
+                    h,
+                    ratio: w / h,
+                    pre_w,
+                    pre_h,
+                    pre_ratio: pre_w / pre_h,
+                }));
             }
             c @ _ => {
                 vx_log_i!("Uncontrolled event: {:?}", c);
@@ -305,8 +592,316 @@ impl Application {
         return None;
     }
 
+    // When it is enabled, CloseRequest is not followed by Quit and the
+    // application must call quit after the confirmation of user.
+    pub fn set_close_confirmation(&self, enabled: bool) {
+        vx_result!(self.window_state.write()).needs_close_confirmation = enabled;
+    }
+
+    // Quit event comes in the next fetch of events.
+    pub fn quit(&self) {
+        vx_result!(self.window_state.write()).is_quit_requested = true;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        return vx_result!(self.window_state.read()).is_focused;
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        return vx_result!(self.window_state.read()).is_minimized;
+    }
+
+    pub fn get_scale_factor(&self) -> Real {
+        #[cfg(wayland)]
+        {
+            if let Some(wayland) = &self.wayland {
+                return vx_result!(wayland.read()).get_scale();
+            }
+        }
+        return vx_result!(self.window_state.read()).scale_factor;
+    }
+
+    // Window owns the selection until another client takes it.
+    pub fn set_clipboard_text(&self, selection: Selection, text: &str) {
+        if self.is_wayland("Clipboard") {
+            return;
+        }
+        vx_result!(vx_unwrap!(&self.clipboard).write()).set_text(selection, text);
+    }
+
+    // Text comes later in a Clipboard event.
+    pub fn request_clipboard_text(&self, selection: Selection) {
+        if self.is_wayland("Clipboard") {
+            return;
+        }
+        vx_result!(vx_unwrap!(&self.clipboard).write()).request_text(selection);
+    }
+
     pub fn get_window_aspect_ratio(&self) -> f32 {
-        self.window_aspect_ratio
+        let (w, h) = self.get_window_size();
+        return w / h;
+    }
+
+    // Extra windows, like an inspector or a view on the second monitor, their
+    // events have their ids. Render engine gives them a surface in its
+    // add_window.
+    pub fn create_window(&self, config: &Config) -> Option<Id> {
+        if self.is_wayland("Multiple windows") {
+            return None;
+        }
+        let window = create_x11_window(
+            self.connection,
+            self.screen,
+            config,
+            self.atom_wm_protocols,
+            self.atom_wm_delete_window,
+            self.atom_net_wm_state,
+            self.atom_net_wm_state_fullscreen,
+        );
+        let (width, height) = get_config_size(config, self.screen);
+        let is_fullscreen = match &config.screen_state {
+            ScreenState::Fullscreen => true,
+            ScreenState::Windowed(_) => false,
+        };
+        let state = RwLock::new(WindowState {
+            width: width as Real,
+            height: height as Real,
+            is_fullscreen,
+            scale_factor: self.get_scale_factor(),
+            ..WindowState::default()
+        });
+        let id = create_id();
+        vx_result!(self.secondary_windows.write()).insert(id, SecondaryWindow { window, state });
+        return Some(id);
+    }
+
+    // Surface of the window is removed from the render engine before it.
+    pub fn destroy_window(&self, window_id: Id) {
+        let secondary = vx_result!(self.secondary_windows.write()).remove(&window_id);
+        let secondary = match secondary {
+            Some(secondary) => secondary,
+            None => {
+                vx_log_e!("Window {} does not exist.", window_id);
+                return;
+            }
+        };
+        if let Some(renderer) = &self.renderer {
+            vx_result!(renderer.write()).remove_window(window_id);
+        }
+        unsafe {
+            xcb::xcb_destroy_window(self.connection, secondary.window);
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    pub fn get_window_size_of(&self, window_id: Id) -> Option<(Real, Real)> {
+        if window_id == MAIN_WINDOW_ID {
+            return Some(self.get_window_size());
+        }
+        let secondary_windows = vx_result!(self.secondary_windows.read());
+        let state = vx_result!(secondary_windows.get(&window_id)?.state.read());
+        return Some((state.width, state.height));
+    }
+
+    pub(crate) fn get_window_of(&self, window_id: Id) -> Option<xcb::Window> {
+        if window_id == MAIN_WINDOW_ID {
+            return Some(self.window);
+        }
+        return vx_result!(self.secondary_windows.read())
+            .get(&window_id)
+            .map(|s| s.window);
+    }
+
+    // Window manager does the actual change, the new size comes as a
+    // SizeChange event.
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        vx_result!(self.window_state.write()).is_fullscreen = fullscreen;
+        #[cfg(wayland)]
+        {
+            if let Some(wayland) = &self.wayland {
+                vx_result!(wayland.read()).set_fullscreen(fullscreen);
+                return;
+            }
+        }
+        let action = if fullscreen {
+            NET_WM_STATE_ADD
+        } else {
+            NET_WM_STATE_REMOVE
+        };
+        let e = xcb::ClientMessageEvent {
+            response_type: xproto::CLIENT_MESSAGE as u8,
+            format: 32,
+            window: self.window,
+            type_: self.atom_net_wm_state,
+            data: xcb::ClientMessageData {
+                data: [
+                    action,
+                    self.atom_net_wm_state_fullscreen,
+                    0,
+                    NET_WM_SOURCE_APPLICATION,
+                    0,
+                ],
+            },
+            ..xcb::ClientMessageEvent::default()
+        };
+        let mask =
+            (xcb::EventMask::SUBSTRUCTURE_NOTIFY | xcb::EventMask::SUBSTRUCTURE_REDIRECT).bits();
+        unsafe {
+            xcb::xcb_send_event(
+                self.connection,
+                0,
+                (*self.screen).root,
+                mask,
+                &e as *const xcb::ClientMessageEvent as *const c_char,
+            );
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        return vx_result!(self.window_state.read()).is_fullscreen;
+    }
+
+    pub fn set_size_limits(&self, min_size: Option<(u32, u32)>, max_size: Option<(u32, u32)>) {
+        if self.is_wayland("Size limits") {
+            return;
+        }
+        set_window_size_limits(self.connection, self.window, min_size, max_size);
+    }
+
+    pub fn set_icon(&self, icon: &Icon) {
+        if self.is_wayland("Window icon") {
+            return;
+        }
+        set_window_icon(self.connection, self.window, icon);
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        if self.is_wayland("Cursor visibility") {
+            return;
+        }
+        vx_result!(self.window_state.write()).is_cursor_hidden = !visible;
+        let cursor = if visible {
+            xcb::NONE
+        } else {
+            self.invisible_cursor
+        };
+        unsafe {
+            xcb::xcb_change_window_attributes(
+                self.connection,
+                self.window,
+                xcb::CW::CURSOR.bits(),
+                &cursor,
+            );
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        return !vx_result!(self.window_state.read()).is_cursor_hidden;
+    }
+
+    // It confines the pointer to the window, it returns false when another
+    // client already has the grab.
+    pub fn set_pointer_grabbed(&self, grabbed: bool) -> bool {
+        if self.is_wayland("Pointer grab") {
+            return false;
+        }
+        if !grabbed {
+            unsafe {
+                xcb::xcb_ungrab_pointer(self.connection, xcb::CURRENT_TIME);
+                xcb::xcb_flush(self.connection);
+            }
+            vx_result!(self.window_state.write()).is_pointer_grabbed = false;
+            return true;
+        }
+        let mask = (xcb::EventMask::POINTER_MOTION
+            | xcb::EventMask::BUTTON_PRESS
+            | xcb::EventMask::BUTTON_RELEASE)
+            .bits();
+        let reply = unsafe {
+            let cookie = xcb::xcb_grab_pointer(
+                self.connection,
+                1,
+                self.window,
+                mask as u16,
+                xcb::GRAB_MODE_ASYNC,
+                xcb::GRAB_MODE_ASYNC,
+                self.window,
+                xcb::NONE,
+                xcb::CURRENT_TIME,
+            );
+            xcb::xcb_grab_pointer_reply(self.connection, cookie, null_mut())
+        };
+        if reply.is_null() {
+            vx_log_e!("Pointer grab failed.");
+            return false;
+        }
+        let status = unsafe { (*reply).status };
+        unsafe {
+            libc::free(transmute(reply));
+        }
+        if status != xcb::GRAB_STATUS_SUCCESS {
+            vx_log_e!("Pointer grab failed with status {}.", status);
+            return false;
+        }
+        vx_result!(self.window_state.write()).is_pointer_grabbed = true;
+        return true;
+    }
+
+    pub fn is_pointer_grabbed(&self) -> bool {
+        return vx_result!(self.window_state.read()).is_pointer_grabbed;
+    }
+
+    // In the relative mode the cursor is hidden and grabbed and the mouse
+    // move events only carry the raw deltas of XInput2, they are not bound by
+    // the edges of screen, the way that a FPS camera needs.
+    pub fn set_relative_mouse(&self, relative: bool) -> bool {
+        if self.is_wayland("Relative mouse") {
+            return false;
+        }
+        if relative {
+            let has_xinput = {
+                let mut window_state = vx_result!(self.window_state.write());
+                if window_state.xinput_opcode.is_none() {
+                    window_state.xinput_opcode = xinput::initialize(self.connection);
+                }
+                window_state.xinput_opcode.is_some()
+            };
+            if !has_xinput || !self.set_pointer_grabbed(true) {
+                return false;
+            }
+        } else {
+            if !vx_result!(self.window_state.read()).is_relative_mouse {
+                return true;
+            }
+            self.set_pointer_grabbed(false);
+        }
+        self.set_cursor_visible(!relative);
+        xinput::select_raw_motion(self.connection, unsafe { (*self.screen).root }, relative);
+        unsafe {
+            xcb::xcb_flush(self.connection);
+        }
+        vx_result!(self.window_state.write()).is_relative_mouse = relative;
+        return true;
+    }
+
+    pub fn is_relative_mouse(&self) -> bool {
+        return vx_result!(self.window_state.read()).is_relative_mouse;
+    }
+
+    #[cfg(wayland)]
+    fn is_wayland(&self, feature: &str) -> bool {
+        if self.wayland.is_some() {
+            vx_log_e!("{} is not supported on Wayland yet.", feature);
+            return true;
+        }
+        return false;
+    }
+
+    #[cfg(not(wayland))]
+    fn is_wayland(&self, _feature: &str) -> bool {
+        return false;
     }
 
     pub(crate) fn get_window(&self) -> xcb::Window {
@@ -316,6 +911,359 @@ impl Application {
     pub(crate) fn get_connection(&self) -> *mut xcb::Connection {
         return self.connection;
     }
+
+    // Vulkan surface must be created on it when it returns the display and
+    // the surface of Wayland.
+    #[cfg(wayland)]
+    pub(crate) fn get_wayland_surface(&self) -> Option<(*mut WaylandDisplay, *mut c_void)> {
+        if let Some(wayland) = &self.wayland {
+            let wayland = vx_result!(wayland.read());
+            return Some((wayland.get_display(), wayland.get_surface()));
+        }
+        return None;
+    }
+
+    pub fn get_window_size(&self) -> (Real, Real) {
+        #[cfg(wayland)]
+        {
+            if let Some(wayland) = &self.wayland {
+                return vx_result!(wayland.read()).get_size();
+            }
+        }
+        let window_state = vx_result!(self.window_state.read());
+        return (window_state.width, window_state.height);
+    }
+
+    // Presenter works only with X11.
+    #[cfg(software_gapi)]
+    pub(crate) fn create_presenter(&self) -> Option<Presenter> {
+        return self.create_window_presenter(MAIN_WINDOW_ID);
+    }
+
+    #[cfg(software_gapi)]
+    pub(crate) fn create_window_presenter(&self, window_id: Id) -> Option<Presenter> {
+        #[cfg(wayland)]
+        {
+            if self.wayland.is_some() {
+                return None;
+            }
+        }
+        let window = self.get_window_of(window_id)?;
+        let depth = unsafe { (*self.screen).root_depth };
+        return Some(Presenter::new(self.connection, window, depth));
+    }
+}
+
+impl Drop for Application {
+    fn drop(&mut self) {
+        if self.invisible_cursor != xcb::NONE {
+            unsafe {
+                xcb::xcb_free_cursor(self.connection, self.invisible_cursor);
+            }
+        }
+    }
+}
+
+pub(super) fn intern_atom(connection: *mut xcb::Connection, name: &str) -> xcb::Atom {
+    let reply = unsafe {
+        let cookie = xcb::xcb_intern_atom(
+            connection,
+            0,
+            name.len() as u16,
+            name.as_ptr() as *const c_char,
+        );
+        xcb::xcb_intern_atom_reply(connection, cookie, null_mut())
+    };
+    if reply.is_null() {
+        vx_log_f!("Can not intern atom {}.", name);
+    }
+    let atom = unsafe { (*reply).atom };
+    unsafe {
+        libc::free(transmute(reply));
+    }
+    return atom;
+}
+
+// Events that do not have any window, or their window is the root, belong to
+// the main window.
+// Window managers read the title as a C string, so the NUL characters of the
+// application name are dropped instead of cutting the title at them.
+pub(super) fn create_title(name: &str) -> CString {
+    let bytes: Vec<u8> = name.bytes().filter(|b| *b != 0).collect();
+    return vx_result!(CString::new(bytes));
+}
+
+fn get_event_window(e: *mut xcb::GenericEvent) -> Option<xcb::Window> {
+    unsafe {
+        return match (*e).response_type as c_uint & 0x7F {
+            // Motion notify has the layout of key events.
+            xproto::KEY_PRESS | xproto::KEY_RELEASE | xproto::MOTION_NOTIFY => {
+                Some((*(e as *const xcb::KeyPressEvent)).event)
+            }
+            xproto::BUTTON_PRESS | xproto::BUTTON_RELEASE => {
+                Some((*(e as *const xcb::ButtonPressEvent)).event)
+            }
+            xproto::FOCUS_IN | xproto::FOCUS_OUT => Some((*(e as *const xcb::FocusInEvent)).event),
+            // Destroy and unmap notifies have the layout of map notify.
+            xproto::MAP_NOTIFY | xproto::UNMAP_NOTIFY | xproto::DESTROY_NOTIFY => {
+                Some((*(e as *const xcb::MapNotifyEvent)).window)
+            }
+            xproto::CONFIGURE_NOTIFY => Some((*(e as *const xcb::ConfigureNotifyEvent)).window),
+            xproto::CLIENT_MESSAGE => Some((*(e as *const xcb::ClientMessageEvent)).window),
+            _ => None,
+        };
+    }
+}
+
+fn get_config_size(config: &Config, screen: *mut xcb::Screen) -> (u16, u16) {
+    return match &config.screen_state {
+        ScreenState::Windowed(windowed) => (windowed.width as u16, windowed.height as u16),
+        ScreenState::Fullscreen => unsafe {
+            ((*screen).width_in_pixels, (*screen).height_in_pixels)
+        },
+    };
+}
+
+// It creates and maps a window with the size, title, state, size limits and
+// icon of config, the main window and the secondary windows are the same.
+fn create_x11_window(
+    connection: *mut xcb::Connection,
+    screen: *mut xcb::Screen,
+    config: &Config,
+    atom_wm_protocols: xcb::Atom,
+    atom_wm_delete_window: xcb::Atom,
+    atom_net_wm_state: xcb::Atom,
+    atom_net_wm_state_fullscreen: xcb::Atom,
+) -> xcb::Window {
+    let window: xcb::Window = unsafe { xcb::xcb_generate_id(connection) };
+    let mut value_list = vec![0u32; 32];
+    value_list[0] = unsafe { (*screen).black_pixel };
+    value_list[1] = (xcb::EventMask::KEY_RELEASE
+        | xcb::EventMask::KEY_PRESS
+        | xcb::EventMask::EXPOSURE
+        | xcb::EventMask::STRUCTURE_NOTIFY
+        | xcb::EventMask::POINTER_MOTION
+        | xcb::EventMask::BUTTON_PRESS
+        | xcb::EventMask::BUTTON_RELEASE
+        | xcb::EventMask::RESIZE_REDIRECT
+        | xcb::EventMask::FOCUS_CHANGE
+        | xcb::EventMask::PROPERTY_CHANGE)
+        .bits();
+    let value_mask = (xcb::CW::BACK_PIXEL | xcb::CW::EVENT_MASK).bits();
+    let (window_width, window_height) = get_config_size(config, screen);
+    unsafe {
+        xcb::xcb_create_window(
+            connection,
+            xcb::COPY_FROM_PARENT as u8,
+            window,
+            (*screen).root,
+            0,
+            0,
+            window_width,
+            window_height,
+            0,
+            xcb::WindowClass::InputOutput as u16,
+            (*screen).root_visual,
+            value_mask,
+            value_list.as_ptr(),
+        );
+    }
+    // Window manager sends WM_DELETE_WINDOW instead of destroying the window.
+    unsafe {
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            atom_wm_protocols,
+            xcb::AtomEnum::Atom as u32,
+            32,
+            1,
+            &atom_wm_delete_window as *const xcb::Atom as *const c_void,
+        );
+    }
+    let cs = create_title(&config.application_name);
+    let atom_net_wm_name = intern_atom(connection, "_NET_WM_NAME");
+    let atom_utf8_string = intern_atom(connection, "UTF8_STRING");
+    unsafe {
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            xcb::AtomEnum::WmName as u32,
+            xcb::AtomEnum::String as u32,
+            8,
+            cs.as_bytes_with_nul().len() as u32,
+            cs.as_ptr() as *const c_void,
+        );
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            atom_net_wm_name,
+            atom_utf8_string,
+            8,
+            cs.as_bytes().len() as u32,
+            cs.as_ptr() as *const c_void,
+        );
+    }
+    // Before mapping, window manager reads the state from the property.
+    let (min_size, max_size) = match &config.screen_state {
+        ScreenState::Windowed(windowed) => (windowed.min_size, windowed.max_size),
+        ScreenState::Fullscreen => {
+            unsafe {
+                xcb::xcb_change_property(
+                    connection,
+                    xproto::PropMode::Replace as u8,
+                    window,
+                    atom_net_wm_state,
+                    xcb::AtomEnum::Atom as u32,
+                    32,
+                    1,
+                    &atom_net_wm_state_fullscreen as *const xcb::Atom as *const c_void,
+                );
+            }
+            (None, None)
+        }
+    };
+    set_window_size_limits(connection, window, min_size, max_size);
+    if let Some(icon) = &config.icon {
+        set_window_icon(connection, window, icon);
+    }
+    unsafe {
+        xcb::xcb_map_window(connection, window);
+        xcb::xcb_flush(connection);
+    }
+    return window;
+}
+
+fn set_window_size_limits(
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    min_size: Option<(u32, u32)>,
+    max_size: Option<(u32, u32)>,
+) {
+    let mut hints = [0u32; SIZE_HINTS_LENGTH];
+    if let Some((w, h)) = min_size {
+        hints[0] |= SIZE_HINT_P_MIN_SIZE;
+        hints[5] = w;
+        hints[6] = h;
+    }
+    if let Some((w, h)) = max_size {
+        hints[0] |= SIZE_HINT_P_MAX_SIZE;
+        hints[7] = w;
+        hints[8] = h;
+    }
+    unsafe {
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            xcb::AtomEnum::WmNormalHints as u32,
+            xcb::AtomEnum::WmSizeHints as u32,
+            32,
+            SIZE_HINTS_LENGTH as u32,
+            hints.as_ptr() as *const c_void,
+        );
+        xcb::xcb_flush(connection);
+    }
+}
+
+fn set_window_icon(connection: *mut xcb::Connection, window: xcb::Window, icon: &Icon) {
+    if icon.pixels.len() != (icon.width * icon.height * 4) as usize {
+        vx_log_e!("Icon pixels do not match its size.");
+        return;
+    }
+    // _NET_WM_ICON is width, height and then ARGB pixels.
+    let mut data = Vec::with_capacity(icon.pixels.len() / 4 + 2);
+    data.push(icon.width);
+    data.push(icon.height);
+    for p in icon.pixels.chunks(4) {
+        data.push(
+            ((p[3] as u32) << 24) | ((p[0] as u32) << 16) | ((p[1] as u32) << 8) | p[2] as u32,
+        );
+    }
+    let atom_net_wm_icon = intern_atom(connection, "_NET_WM_ICON");
+    unsafe {
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            atom_net_wm_icon,
+            xcb::AtomEnum::Cardinal as u32,
+            32,
+            data.len() as u32,
+            data.as_ptr() as *const c_void,
+        );
+        xcb::xcb_flush(connection);
+    }
+}
+
+fn read_scale_factor(
+    connection: *mut xcb::Connection,
+    root: xcb::Window,
+    atom_resource_manager: xcb::Atom,
+) -> Real {
+    let reply = unsafe {
+        let cookie = xcb::xcb_get_property(
+            connection,
+            0,
+            root,
+            atom_resource_manager,
+            xcb::AtomEnum::String as u32,
+            0,
+            RESOURCES_MAX_LENGTH,
+        );
+        xcb::xcb_get_property_reply(connection, cookie, null_mut())
+    };
+    if reply.is_null() {
+        return 1.0;
+    }
+    let resources = unsafe {
+        let value = xcb::xcb_get_property_value(reply) as *const u8;
+        let length = xcb::xcb_get_property_value_length(reply) as usize;
+        String::from_utf8_lossy(std::slice::from_raw_parts(value, length)).to_string()
+    };
+    unsafe {
+        libc::free(transmute(reply));
+    }
+    return match parse_xft_dpi(&resources) {
+        Some(dpi) => dpi / DEFAULT_DPI,
+        None => 1.0,
+    };
+}
+
+// Resources are lines of `name:\tvalue`.
+fn parse_xft_dpi(resources: &str) -> Option<Real> {
+    for line in resources.lines() {
+        if let Some(value) = line.trim().strip_prefix("Xft.dpi:") {
+            return value.trim().parse::<Real>().ok().filter(|dpi| *dpi > 0.0);
+        }
+    }
+    return None;
+}
+
+// A cursor with an empty mask, it is set on the window to hide the cursor.
+fn create_invisible_cursor(connection: *mut xcb::Connection, window: xcb::Window) -> xcb::Cursor {
+    unsafe {
+        let pixmap = xcb::xcb_generate_id(connection);
+        xcb::xcb_create_pixmap(connection, 1, pixmap, window, 1, 1);
+        // Content of a new pixmap is undefined, it must be cleared.
+        let gc = xcb::xcb_generate_id(connection);
+        let foreground = 0u32;
+        xcb::xcb_create_gc(connection, gc, pixmap, xcb::GC_FOREGROUND, &foreground);
+        let rectangle = xcb::Rectangle {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        xcb::xcb_poly_fill_rectangle(connection, pixmap, gc, 1, &rectangle);
+        xcb::xcb_free_gc(connection, gc);
+        let cursor = xcb::xcb_generate_id(connection);
+        xcb::xcb_create_cursor(connection, cursor, pixmap, pixmap, 0, 0, 0, 0, 0, 0, 0, 0);
+        xcb::xcb_free_pixmap(connection, pixmap);
+        return cursor;
+    }
 }
 
 fn get_mouse_position(
@@ -340,3 +1288,24 @@ fn get_mouse_position(
 unsafe impl Send for Application {}
 
 unsafe impl Sync for Application {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_xft_dpi_test() {
+        let resources = "Xcursor.size:\t24\nXft.antialias:\t1\nXft.dpi:\t144\n";
+        assert_eq!(parse_xft_dpi(resources), Some(144.0));
+        assert_eq!(parse_xft_dpi("Xft.dpi: 120.5"), Some(120.5));
+        assert_eq!(parse_xft_dpi("Xcursor.size:\t24\n"), None);
+        assert_eq!(parse_xft_dpi("Xft.dpi:\tbroken\n"), None);
+    }
+
+    #[test]
+    fn create_title_test() {
+        assert_eq!(create_title("Gearoenix App").as_bytes(), b"Gearoenix App");
+        assert_eq!(create_title("Gear\0oenix\0").as_bytes(), b"Gearoenix");
+        assert_eq!(create_title("\0").as_bytes(), b"");
+    }
+}
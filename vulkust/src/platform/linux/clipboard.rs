@@ -0,0 +1,592 @@
+use super::super::super::core::event::{Selection, Type as EventType};
+use super::super::super::libc;
+use super::application::intern_atom;
+use super::xcb;
+use super::xproto;
+use std::mem::transmute;
+use std::os::raw::{c_char, c_void};
+use std::ptr::null_mut;
+
+// Header of ChangeProperty request, it is subtracted from the maximum length
+// of request to find the biggest text that can be served in one piece.
+const CHANGE_PROPERTY_HEADER_SIZE: usize = 24;
+
+// Text that is bigger than a request is sent by INCR protocol, each deletion
+// of the property by the requestor asks for the next chunk and an empty chunk
+// ends the transfer.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct OutgoingTransfer {
+    requestor: xcb::Window,
+    property: xcb::Atom,
+    data_type: xcb::Atom,
+    data: Vec<u8>,
+    offset: usize,
+    is_ended: bool,
+}
+
+impl OutgoingTransfer {
+    fn new(
+        requestor: xcb::Window,
+        property: xcb::Atom,
+        data_type: xcb::Atom,
+        data: Vec<u8>,
+    ) -> Self {
+        return Self {
+            requestor,
+            property,
+            data_type,
+            data,
+            offset: 0,
+            is_ended: false,
+        };
+    }
+
+    // It returns None after the empty chunk.
+    fn next_chunk(&mut self, max_size: usize) -> Option<&[u8]> {
+        if self.is_ended {
+            return None;
+        }
+        let start = self.offset;
+        self.offset = self.data.len().min(start + max_size);
+        self.is_ended = start == self.offset;
+        return Some(&self.data[start..self.offset]);
+    }
+}
+
+// Owner of a selection keeps the data and sends it to the requestors, so the
+// texts that are set live here until another client takes the selection.
+// Requested texts come back asynchronously in Clipboard events.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Clipboard {
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    atom_clipboard: xcb::Atom,
+    atom_targets: xcb::Atom,
+    atom_utf8_string: xcb::Atom,
+    atom_text: xcb::Atom,
+    atom_incr: xcb::Atom,
+    // Indexed by get_index of selection.
+    owned_texts: [Option<String>; 2],
+    // Big texts come in chunks, they are gathered here.
+    incr_transfers: [Option<(xcb::Atom, Vec<u8>)>; 2],
+    outgoing_transfers: Vec<OutgoingTransfer>,
+    events: Vec<EventType>,
+}
+
+impl Clipboard {
+    pub(super) fn new(connection: *mut xcb::Connection, window: xcb::Window) -> Self {
+        return Self {
+            connection,
+            window,
+            atom_clipboard: intern_atom(connection, "CLIPBOARD"),
+            atom_targets: intern_atom(connection, "TARGETS"),
+            atom_utf8_string: intern_atom(connection, "UTF8_STRING"),
+            atom_text: intern_atom(connection, "TEXT"),
+            atom_incr: intern_atom(connection, "INCR"),
+            owned_texts: [None, None],
+            incr_transfers: [None, None],
+            outgoing_transfers: Vec::new(),
+            events: Vec::new(),
+        };
+    }
+
+    pub(super) fn set_text(&mut self, selection: Selection, text: &str) {
+        let atom = self.get_atom(&selection);
+        self.owned_texts[get_index(&selection)] = Some(text.to_string());
+        unsafe {
+            xcb::xcb_set_selection_owner(self.connection, self.window, atom, xcb::CURRENT_TIME);
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    // Answer comes as a Clipboard event.
+    pub(super) fn request_text(&mut self, selection: Selection) {
+        if let Some(text) = &self.owned_texts[get_index(&selection)] {
+            let text = Some(text.clone());
+            self.events.push(EventType::Clipboard { selection, text });
+            return;
+        }
+        self.convert(self.get_atom(&selection), self.atom_utf8_string);
+    }
+
+    pub(super) fn take_events(&mut self) -> Vec<EventType> {
+        return std::mem::take(&mut self.events);
+    }
+
+    // Another client asks for the text of a selection that this window owns.
+    pub(super) fn on_selection_request(&mut self, e: &xcb::SelectionRequestEvent) {
+        // Obsolete clients do not set the property.
+        let property = if e.property == xcb::NONE {
+            e.target
+        } else {
+            e.property
+        };
+        let text = self
+            .get_selection(e.selection)
+            .and_then(|selection| self.owned_texts[get_index(&selection)].clone());
+        let is_served = match text {
+            Some(text) => self.serve(e.requestor, property, e.target, &text),
+            None => false,
+        };
+        let notify = xcb::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY as u8,
+            time: e.time,
+            requestor: e.requestor,
+            selection: e.selection,
+            target: e.target,
+            property: if is_served { property } else { xcb::NONE },
+            ..xcb::SelectionNotifyEvent::default()
+        };
+        unsafe {
+            xcb::xcb_send_event(
+                self.connection,
+                0,
+                e.requestor,
+                0,
+                &notify as *const xcb::SelectionNotifyEvent as *const c_char,
+            );
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    pub(super) fn on_selection_clear(&mut self, e: &xcb::SelectionClearEvent) {
+        if let Some(selection) = self.get_selection(e.selection) {
+            self.owned_texts[get_index(&selection)] = None;
+        }
+    }
+
+    pub(super) fn on_selection_notify(
+        &mut self,
+        e: &xcb::SelectionNotifyEvent,
+    ) -> Option<EventType> {
+        let selection = self.get_selection(e.selection)?;
+        if e.property == xcb::NONE {
+            // Old clients only know the Latin-1 STRING.
+            if e.target == self.atom_utf8_string {
+                self.convert(e.selection, xcb::AtomEnum::String as u32);
+                return None;
+            }
+            return Some(EventType::Clipboard {
+                selection,
+                text: None,
+            });
+        }
+        let (data_type, data) = match read_property(self.connection, self.window, e.property) {
+            Some(p) => p,
+            None => {
+                return Some(EventType::Clipboard {
+                    selection,
+                    text: None,
+                })
+            }
+        };
+        if data_type == self.atom_incr {
+            // Deleting the property by the read starts the transfer.
+            self.incr_transfers[get_index(&selection)] = Some((xcb::NONE, Vec::new()));
+            return None;
+        }
+        let text = self.decode(data_type, &data);
+        return Some(EventType::Clipboard { selection, text });
+    }
+
+    // Chunks of an INCR transfer are written on the property one by one, an
+    // empty chunk ends the transfer. Events of the other windows belong to
+    // the requestors of the outgoing transfers.
+    pub(super) fn on_property_notify(&mut self, e: &xcb::PropertyNotifyEvent) -> Option<EventType> {
+        if e.window != self.window {
+            if e.state == xproto::PROPERTY_DELETE {
+                self.continue_outgoing(e.window, e.atom);
+            }
+            return None;
+        }
+        if e.state != xproto::PROPERTY_NEW_VALUE {
+            return None;
+        }
+        let selection = self.get_selection(e.atom)?;
+        let index = get_index(&selection);
+        if self.incr_transfers[index].is_none() {
+            return None;
+        }
+        let (data_type, data) = match read_property(self.connection, self.window, e.atom) {
+            Some(p) => p,
+            None => {
+                self.incr_transfers[index] = None;
+                return Some(EventType::Clipboard {
+                    selection,
+                    text: None,
+                });
+            }
+        };
+        if !data.is_empty() {
+            let transfer = vx_unwrap!(&mut self.incr_transfers[index]);
+            transfer.0 = data_type;
+            transfer.1.extend_from_slice(&data);
+            return None;
+        }
+        let (data_type, data) = vx_unwrap!(self.incr_transfers[index].take());
+        let text = self.decode(data_type, &data);
+        return Some(EventType::Clipboard { selection, text });
+    }
+
+    fn continue_outgoing(&mut self, requestor: xcb::Window, property: xcb::Atom) {
+        let index = match self
+            .outgoing_transfers
+            .iter()
+            .position(|t| t.requestor == requestor && t.property == property)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        let max_size = self.get_max_chunk_size();
+        let transfer = &mut self.outgoing_transfers[index];
+        let data_type = transfer.data_type;
+        match transfer.next_chunk(max_size) {
+            Some(chunk) => change_property(
+                self.connection,
+                requestor,
+                property,
+                data_type,
+                8,
+                chunk.len(),
+                chunk.as_ptr() as *const c_void,
+            ),
+            None => {
+                self.outgoing_transfers.remove(index);
+                if !self
+                    .outgoing_transfers
+                    .iter()
+                    .any(|t| t.requestor == requestor)
+                {
+                    set_event_mask(self.connection, requestor, 0);
+                }
+            }
+        }
+        unsafe {
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    fn get_max_chunk_size(&self) -> usize {
+        return unsafe { xcb::xcb_get_maximum_request_length(self.connection) } as usize * 4
+            - CHANGE_PROPERTY_HEADER_SIZE;
+    }
+
+    // Property of the answer has the name of selection, so the requests of
+    // different selections do not overwrite each other.
+    fn convert(&self, selection: xcb::Atom, target: xcb::Atom) {
+        unsafe {
+            xcb::xcb_convert_selection(
+                self.connection,
+                self.window,
+                selection,
+                target,
+                selection,
+                xcb::CURRENT_TIME,
+            );
+            xcb::xcb_flush(self.connection);
+        }
+    }
+
+    fn serve(
+        &mut self,
+        requestor: xcb::Window,
+        property: xcb::Atom,
+        target: xcb::Atom,
+        text: &str,
+    ) -> bool {
+        if target == self.atom_targets {
+            let targets = [
+                self.atom_targets,
+                self.atom_utf8_string,
+                self.atom_text,
+                xcb::AtomEnum::String as u32,
+            ];
+            change_property(
+                self.connection,
+                requestor,
+                property,
+                xcb::AtomEnum::Atom as u32,
+                32,
+                targets.len(),
+                targets.as_ptr() as *const c_void,
+            );
+            return true;
+        }
+        let (data_type, data) = if target == self.atom_utf8_string || target == self.atom_text {
+            (self.atom_utf8_string, text.as_bytes().to_vec())
+        } else if target == xcb::AtomEnum::String as u32 {
+            (target, encode_latin1(text))
+        } else {
+            return false;
+        };
+        if data.len() > self.get_max_chunk_size() {
+            // Requestor deletes the INCR property to start the transfer, the
+            // lower bound of the size is the whole size here.
+            let size = data.len() as u32;
+            set_event_mask(
+                self.connection,
+                requestor,
+                xcb::EventMask::PROPERTY_CHANGE.bits(),
+            );
+            change_property(
+                self.connection,
+                requestor,
+                property,
+                self.atom_incr,
+                32,
+                1,
+                &size as *const u32 as *const c_void,
+            );
+            self.outgoing_transfers
+                .retain(|t| t.requestor != requestor || t.property != property);
+            self.outgoing_transfers
+                .push(OutgoingTransfer::new(requestor, property, data_type, data));
+            return true;
+        }
+        change_property(
+            self.connection,
+            requestor,
+            property,
+            data_type,
+            8,
+            data.len(),
+            data.as_ptr() as *const c_void,
+        );
+        return true;
+    }
+
+    fn decode(&self, data_type: xcb::Atom, data: &[u8]) -> Option<String> {
+        if data_type == self.atom_utf8_string || data_type == self.atom_text {
+            return Some(String::from_utf8_lossy(data).to_string());
+        }
+        if data_type == xcb::AtomEnum::String as u32 {
+            return Some(data.iter().map(|b| *b as char).collect());
+        }
+        vx_log_e!("Unsupported type of clipboard data: {}", data_type);
+        return None;
+    }
+
+    fn get_atom(&self, selection: &Selection) -> xcb::Atom {
+        return match selection {
+            Selection::Clipboard => self.atom_clipboard,
+            Selection::Primary => xcb::AtomEnum::Primary as u32,
+        };
+    }
+
+    fn get_selection(&self, atom: xcb::Atom) -> Option<Selection> {
+        if atom == self.atom_clipboard {
+            return Some(Selection::Clipboard);
+        }
+        if atom == xcb::AtomEnum::Primary as u32 {
+            return Some(Selection::Primary);
+        }
+        return None;
+    }
+}
+
+fn get_index(selection: &Selection) -> usize {
+    return match selection {
+        Selection::Clipboard => 0,
+        Selection::Primary => 1,
+    };
+}
+
+// Characters out of Latin-1 are replaced by question marks.
+fn encode_latin1(text: &str) -> Vec<u8> {
+    return text
+        .chars()
+        .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+        .collect();
+}
+
+// Only the events that this client receives from the window change.
+fn set_event_mask(connection: *mut xcb::Connection, window: xcb::Window, event_mask: u32) {
+    unsafe {
+        xcb::xcb_change_window_attributes(
+            connection,
+            window,
+            xcb::CW::EVENT_MASK.bits(),
+            &event_mask,
+        );
+    }
+}
+
+fn change_property(
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    property: xcb::Atom,
+    data_type: xcb::Atom,
+    format: u8,
+    length: usize,
+    data: *const c_void,
+) {
+    unsafe {
+        xcb::xcb_change_property(
+            connection,
+            xproto::PropMode::Replace as u8,
+            window,
+            property,
+            data_type,
+            format,
+            length as u32,
+            data,
+        );
+    }
+}
+
+// It reads the whole property and deletes it, it returns the type and the
+// bytes of data.
+pub(super) fn read_property(
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    property: xcb::Atom,
+) -> Option<(xcb::Atom, Vec<u8>)> {
+    let reply = unsafe {
+        let cookie = xcb::xcb_get_property(
+            connection,
+            1,
+            window,
+            property,
+            xcb::NONE,
+            0,
+            std::u32::MAX / 4,
+        );
+        xcb::xcb_get_property_reply(connection, cookie, null_mut())
+    };
+    if reply.is_null() {
+        vx_log_e!("Reading of property failed.");
+        return None;
+    }
+    let result = unsafe {
+        let value = xcb::xcb_get_property_value(reply) as *const u8;
+        let length = xcb::xcb_get_property_value_length(reply) as usize;
+        (
+            (*reply).type_,
+            std::slice::from_raw_parts(value, length).to_vec(),
+        )
+    };
+    unsafe {
+        libc::free(transmute(reply));
+    }
+    return Some(result);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_latin1_test() {
+        assert_eq!(encode_latin1("caf\u{e9}"), vec![b'c', b'a', b'f', 0xe9]);
+        assert_eq!(encode_latin1("a\u{20ac}b"), b"a?b".to_vec());
+    }
+
+    #[test]
+    fn outgoing_chunks_test() {
+        let mut transfer = OutgoingTransfer::new(1, 2, 3, b"vulkust".to_vec());
+        assert_eq!(transfer.next_chunk(3), Some(&b"vul"[..]));
+        assert_eq!(transfer.next_chunk(3), Some(&b"kus"[..]));
+        assert_eq!(transfer.next_chunk(3), Some(&b"t"[..]));
+        assert_eq!(transfer.next_chunk(3), Some(&b""[..]));
+        assert_eq!(transfer.next_chunk(3), None);
+    }
+
+    fn create_test_client() -> Clipboard {
+        if std::env::var("DISPLAY").is_err() {
+            panic!("DISPLAY is not set, clipboard tests need an X server.");
+        }
+        let mut screen_index = 0;
+        let connection = unsafe { xcb::xcb_connect(null_mut(), &mut screen_index) };
+        if connection.is_null() {
+            panic!("Can not connect to the X server of DISPLAY.");
+        }
+        let window = unsafe {
+            let screen = xcb::xcb_setup_roots_iterator(xcb::xcb_get_setup(connection)).data;
+            let window = xcb::xcb_generate_id(connection);
+            let event_mask = xcb::EventMask::PROPERTY_CHANGE.bits();
+            xcb::xcb_create_window(
+                connection,
+                xcb::COPY_FROM_PARENT as u8,
+                window,
+                (*screen).root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                xcb::WindowClass::InputOutput as u16,
+                (*screen).root_visual,
+                xcb::CW::EVENT_MASK.bits(),
+                &event_mask,
+            );
+            window
+        };
+        return Clipboard::new(connection, window);
+    }
+
+    fn dispatch(clipboard: &mut Clipboard) -> Vec<EventType> {
+        let mut events = clipboard.take_events();
+        loop {
+            let e = unsafe { xcb::xcb_poll_for_event(clipboard.connection) };
+            if e.is_null() {
+                return events;
+            }
+            let event = match unsafe { (*e).response_type as u32 & 0x7F } {
+                xproto::SELECTION_REQUEST => {
+                    clipboard.on_selection_request(unsafe { transmute(e) });
+                    None
+                }
+                xproto::SELECTION_CLEAR => {
+                    clipboard.on_selection_clear(unsafe { transmute(e) });
+                    None
+                }
+                xproto::SELECTION_NOTIFY => clipboard.on_selection_notify(unsafe { transmute(e) }),
+                xproto::PROPERTY_NOTIFY => clipboard.on_property_notify(unsafe { transmute(e) }),
+                _ => None,
+            };
+            if let Some(event) = event {
+                events.push(event);
+            }
+            unsafe {
+                libc::free(transmute(e));
+            }
+        }
+    }
+
+    // The X server tests run with `cargo test -- --ignored` when DISPLAY
+    // points to a server like Xvfb.
+    #[test]
+    #[ignore = "needs an X server"]
+    fn transfer_between_clients_test() {
+        let mut owner = create_test_client();
+        let mut requestor = create_test_client();
+        transfer("Vulkust clipboard \u{2713}", &mut owner, &mut requestor);
+    }
+
+    // Text is bigger than a request, so it goes by INCR.
+    #[test]
+    #[ignore = "needs an X server"]
+    fn incr_transfer_test() {
+        let mut owner = create_test_client();
+        let mut requestor = create_test_client();
+        let size = owner.get_max_chunk_size() * 2 + 7;
+        let sent: String = (0..size).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+        transfer(&sent, &mut owner, &mut requestor);
+    }
+
+    fn transfer(sent: &str, owner: &mut Clipboard, requestor: &mut Clipboard) {
+        owner.set_text(Selection::Clipboard, sent);
+        requestor.request_text(Selection::Clipboard);
+        for _ in 0..10000 {
+            dispatch(owner);
+            for e in dispatch(requestor) {
+                if let EventType::Clipboard { text, .. } = e {
+                    assert!(text.as_deref() == Some(sent));
+                    return;
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("Clipboard text did not arrive.");
+    }
+}
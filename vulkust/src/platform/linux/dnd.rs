@@ -0,0 +1,346 @@
+use super::super::super::core::event::{Drag, DropData, Type as EventType};
+use super::super::super::core::types::Real;
+use super::super::super::libc;
+use super::application::intern_atom;
+use super::clipboard::read_property;
+use super::xcb;
+use super::xproto;
+use std::ffi::OsString;
+use std::mem::transmute;
+use std::os::raw::{c_char, c_void};
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::ptr::null_mut;
+
+// Version of XDND protocol that is implemented here.
+const XDND_VERSION: u32 = 5;
+// Flag of XdndEnter that says the types are in the XdndTypeList property.
+const XDND_ENTER_HAS_TYPE_LIST: u32 = 1;
+// Flags of XdndStatus.
+const XDND_STATUS_ACCEPT: u32 = 1;
+const XDND_STATUS_SEND_POSITIONS: u32 = 2;
+const XDND_FINISHED_ACCEPTED: u32 = 1;
+
+// The drop target side of XDND, window accepts files and texts and gives them
+// in Drag events. The source sends client messages of the drag and the
+// dropped data is transferred through the XdndSelection.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct DragAndDrop {
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    screen_size: (Real, Real),
+    atom_enter: xcb::Atom,
+    atom_position: xcb::Atom,
+    atom_status: xcb::Atom,
+    atom_leave: xcb::Atom,
+    atom_drop: xcb::Atom,
+    atom_finished: xcb::Atom,
+    atom_selection: xcb::Atom,
+    atom_type_list: xcb::Atom,
+    atom_action_copy: xcb::Atom,
+    atom_uri_list: xcb::Atom,
+    // In the order of preference.
+    accepted_types: [xcb::Atom; 4],
+    // Current drag, source is NONE when nothing is over the window.
+    source: xcb::Window,
+    version: u32,
+    data_type: xcb::Atom,
+    position: (Real, Real),
+}
+
+impl DragAndDrop {
+    pub(super) fn new(
+        connection: *mut xcb::Connection,
+        window: xcb::Window,
+        screen: *mut xcb::Screen,
+    ) -> Self {
+        let atom_uri_list = intern_atom(connection, "text/uri-list");
+        let atom_xdnd_aware = intern_atom(connection, "XdndAware");
+        unsafe {
+            xcb::xcb_change_property(
+                connection,
+                xproto::PropMode::Replace as u8,
+                window,
+                atom_xdnd_aware,
+                xcb::AtomEnum::Atom as u32,
+                32,
+                1,
+                &XDND_VERSION as *const u32 as *const c_void,
+            );
+        }
+        return Self {
+            connection,
+            window,
+            screen_size: unsafe {
+                (
+                    (*screen).width_in_pixels as Real,
+                    (*screen).height_in_pixels as Real,
+                )
+            },
+            atom_enter: intern_atom(connection, "XdndEnter"),
+            atom_position: intern_atom(connection, "XdndPosition"),
+            atom_status: intern_atom(connection, "XdndStatus"),
+            atom_leave: intern_atom(connection, "XdndLeave"),
+            atom_drop: intern_atom(connection, "XdndDrop"),
+            atom_finished: intern_atom(connection, "XdndFinished"),
+            atom_selection: intern_atom(connection, "XdndSelection"),
+            atom_type_list: intern_atom(connection, "XdndTypeList"),
+            atom_action_copy: intern_atom(connection, "XdndActionCopy"),
+            atom_uri_list,
+            accepted_types: [
+                atom_uri_list,
+                intern_atom(connection, "UTF8_STRING"),
+                intern_atom(connection, "text/plain;charset=utf-8"),
+                intern_atom(connection, "text/plain"),
+            ],
+            source: xcb::NONE,
+            version: 0,
+            data_type: xcb::NONE,
+            position: (0.0, 0.0),
+        };
+    }
+
+    pub(super) fn on_client_message(&mut self, e: &xcb::ClientMessageEvent) -> Option<EventType> {
+        let data = &e.data.data;
+        if e.type_ == self.atom_enter {
+            self.source = data[0];
+            self.version = XDND_VERSION.min(data[1] >> 24);
+            let types = if data[1] & XDND_ENTER_HAS_TYPE_LIST != 0 {
+                self.read_type_list()
+            } else {
+                data[2..]
+                    .iter()
+                    .cloned()
+                    .filter(|t| *t != xcb::NONE)
+                    .collect()
+            };
+            self.data_type = self.choose_type(&types);
+            return Some(EventType::Drag(Drag::Enter));
+        }
+        // Messages of a drag that has been left or dropped are ignored.
+        if self.source == xcb::NONE || data[0] != self.source {
+            return None;
+        }
+        if e.type_ == self.atom_position {
+            // Position is in the root coordinates, like the mouse positions.
+            self.position = (
+                (data[2] >> 16) as Real / self.screen_size.0,
+                (data[2] & 0xFFFF) as Real / self.screen_size.1,
+            );
+            self.send_status();
+            return Some(EventType::Drag(Drag::Move {
+                position: self.position,
+            }));
+        }
+        if e.type_ == self.atom_leave {
+            self.source = xcb::NONE;
+            return Some(EventType::Drag(Drag::Leave));
+        }
+        if e.type_ == self.atom_drop {
+            if self.data_type == xcb::NONE {
+                self.send_finished(false);
+                self.source = xcb::NONE;
+                return Some(EventType::Drag(Drag::Leave));
+            }
+            let time = if self.version >= 1 {
+                data[2]
+            } else {
+                xcb::CURRENT_TIME
+            };
+            // Data comes in the SelectionNotify.
+            unsafe {
+                xcb::xcb_convert_selection(
+                    self.connection,
+                    self.window,
+                    self.atom_selection,
+                    self.data_type,
+                    self.atom_selection,
+                    time,
+                );
+                xcb::xcb_flush(self.connection);
+            }
+        }
+        return None;
+    }
+
+    pub(super) fn on_selection_notify(
+        &mut self,
+        e: &xcb::SelectionNotifyEvent,
+    ) -> Option<EventType> {
+        if e.selection != self.atom_selection || self.source == xcb::NONE {
+            return None;
+        }
+        let data = if e.property == xcb::NONE {
+            None
+        } else {
+            read_property(self.connection, self.window, e.property)
+        };
+        self.send_finished(data.is_some());
+        self.source = xcb::NONE;
+        let (data_type, data) = match data {
+            Some(data) => data,
+            None => {
+                vx_log_e!("Source of drag did not give the dropped data.");
+                return Some(EventType::Drag(Drag::Leave));
+            }
+        };
+        let data = if data_type == self.atom_uri_list {
+            DropData::Files(parse_uri_list(&String::from_utf8_lossy(&data)))
+        } else {
+            DropData::Text(String::from_utf8_lossy(&data).to_string())
+        };
+        return Some(EventType::Drag(Drag::Drop {
+            position: self.position,
+            data,
+        }));
+    }
+
+    fn read_type_list(&self) -> Vec<xcb::Atom> {
+        let reply = unsafe {
+            let cookie = xcb::xcb_get_property(
+                self.connection,
+                0,
+                self.source,
+                self.atom_type_list,
+                xcb::AtomEnum::Atom as u32,
+                0,
+                std::u32::MAX / 4,
+            );
+            xcb::xcb_get_property_reply(self.connection, cookie, null_mut())
+        };
+        if reply.is_null() {
+            vx_log_e!("Type list of drag can not be read.");
+            return Vec::new();
+        }
+        let types = unsafe {
+            let value = xcb::xcb_get_property_value(reply) as *const xcb::Atom;
+            let length = xcb::xcb_get_property_value_length(reply) as usize / 4;
+            std::slice::from_raw_parts(value, length).to_vec()
+        };
+        unsafe {
+            libc::free(transmute(reply));
+        }
+        return types;
+    }
+
+    fn choose_type(&self, types: &[xcb::Atom]) -> xcb::Atom {
+        for t in &self.accepted_types {
+            if types.contains(t) {
+                return *t;
+            }
+        }
+        return xcb::NONE;
+    }
+
+    fn send_status(&self) {
+        let is_accepted = self.data_type != xcb::NONE;
+        let flags = if is_accepted {
+            XDND_STATUS_ACCEPT | XDND_STATUS_SEND_POSITIONS
+        } else {
+            XDND_STATUS_SEND_POSITIONS
+        };
+        let action = if is_accepted {
+            self.atom_action_copy
+        } else {
+            xcb::NONE
+        };
+        self.send(self.atom_status, [self.window, flags, 0, 0, action]);
+    }
+
+    fn send_finished(&self, is_accepted: bool) {
+        if self.version < 2 {
+            return;
+        }
+        let (flags, action) = if is_accepted {
+            (XDND_FINISHED_ACCEPTED, self.atom_action_copy)
+        } else {
+            (0, xcb::NONE)
+        };
+        self.send(self.atom_finished, [self.window, flags, action, 0, 0]);
+    }
+
+    fn send(&self, message_type: xcb::Atom, data: [u32; 5]) {
+        let e = xcb::ClientMessageEvent {
+            response_type: xproto::CLIENT_MESSAGE as u8,
+            format: 32,
+            window: self.source,
+            type_: message_type,
+            data: xcb::ClientMessageData { data },
+            ..xcb::ClientMessageEvent::default()
+        };
+        unsafe {
+            xcb::xcb_send_event(
+                self.connection,
+                0,
+                self.source,
+                0,
+                &e as *const xcb::ClientMessageEvent as *const c_char,
+            );
+            xcb::xcb_flush(self.connection);
+        }
+    }
+}
+
+// Lines of a text/uri-list are separated by CRLF and the ones that start with
+// # are comments. Only the local files are taken.
+fn parse_uri_list(list: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for line in list.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let path = match line.strip_prefix("file://") {
+            Some(path) => path,
+            None => {
+                vx_log_i!("Dropped URI {} is not a local file.", line);
+                continue;
+            }
+        };
+        // Host name, if it exists, is before the path.
+        let path = match path.find('/') {
+            Some(index) => &path[index..],
+            None => continue,
+        };
+        paths.push(PathBuf::from(OsString::from_vec(decode_percent(path))));
+    }
+    return paths;
+}
+
+fn decode_percent(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                result.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        result.push(bytes[i]);
+        i += 1;
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_uri_list_test() {
+        let list = "# comment\r\nfile:///home/user/My%20Model.gltf\r\n\
+                    file://localhost/tmp/a%C3%A9.png\r\nhttp://example.com/b.png\r\n";
+        let paths = parse_uri_list(list);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/home/user/My Model.gltf"),
+                PathBuf::from("/tmp/a\u{e9}.png"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,10 @@
 pub mod application;
+pub mod clipboard;
+pub mod dnd;
+#[cfg(software_gapi)]
+pub mod presenter;
+#[cfg(wayland)]
+pub mod wayland;
 pub mod xcb;
+pub mod xinput;
 pub mod xproto;
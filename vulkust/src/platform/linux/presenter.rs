@@ -0,0 +1,79 @@
+use super::xcb;
+use std::ptr::null;
+
+// It puts the CPU rendered frames on the window, it is used by the software
+// graphic API. Pixels are in BGRX order, that is the ZPixmap layout of the
+// 24 and 32 bit depth visuals of little endian X servers.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Presenter {
+    connection: *mut xcb::Connection,
+    window: xcb::Window,
+    gc: xcb::GContext,
+    depth: u8,
+    max_request_bytes: u32,
+}
+
+impl Presenter {
+    pub(super) fn new(connection: *mut xcb::Connection, window: xcb::Window, depth: u8) -> Self {
+        let gc = unsafe { xcb::xcb_generate_id(connection) };
+        unsafe {
+            xcb::xcb_create_gc(connection, gc, window, 0, null());
+        }
+        // Maximum request length is in 4 bytes unit.
+        let max_request_bytes = unsafe { xcb::xcb_get_maximum_request_length(connection) } * 4;
+        Self {
+            connection,
+            window,
+            gc,
+            depth,
+            max_request_bytes,
+        }
+    }
+
+    pub(crate) fn present(&self, width: u32, height: u32, bgrx: &[u8]) {
+        #[cfg(debug_mode)]
+        {
+            if bgrx.len() != (width * height * 4) as usize {
+                vx_log_f!("Frame data must have the size of the window.");
+            }
+        }
+        // Window can be resized, so rows of each request are computed from
+        // the width of the frame, 24 bytes is for header of the put image.
+        let max_rows_count = ((self.max_request_bytes - 24) / (width.max(1) * 4)).max(1);
+        let row_size = width as usize * 4;
+        let mut y = 0;
+        while y < height {
+            let rows_count = max_rows_count.min(height - y);
+            let start = y as usize * row_size;
+            let end = start + rows_count as usize * row_size;
+            unsafe {
+                xcb::xcb_put_image(
+                    self.connection,
+                    xcb::IMAGE_FORMAT_Z_PIXMAP,
+                    self.window,
+                    self.gc,
+                    width as u16,
+                    rows_count as u16,
+                    0,
+                    y as i16,
+                    0,
+                    self.depth,
+                    (end - start) as u32,
+                    bgrx[start..end].as_ptr(),
+                );
+            }
+            y += rows_count;
+        }
+        unsafe {
+            xcb::xcb_flush(self.connection);
+        }
+    }
+}
+
+impl Drop for Presenter {
+    fn drop(&mut self) {
+        unsafe {
+            xcb::xcb_free_gc(self.connection, self.gc);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use super::super::super::linker::Linker;
+use super::protocol;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr::null;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::OnceLock;
+
+// Hand written bindings of libwayland-client, the inline request functions of
+// wayland-client-protocol.h are re-implemented on top of the proxy marshaling.
+
+pub(crate) enum Display {}
+pub(super) enum Proxy {}
+
+pub(super) type Fixed = i32;
+
+pub(super) fn fixed_to_f32(f: Fixed) -> f32 {
+    return f as f32 / 256.0;
+}
+
+#[repr(C)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Message {
+    pub(super) name: *const c_char,
+    pub(super) signature: *const c_char,
+    pub(super) types: *const *const Interface,
+}
+
+unsafe impl Sync for Message {}
+
+#[repr(C)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Interface {
+    pub(super) name: *const c_char,
+    pub(super) version: c_int,
+    pub(super) method_count: c_int,
+    pub(super) methods: *const Message,
+    pub(super) event_count: c_int,
+    pub(super) events: *const Message,
+}
+
+unsafe impl Sync for Interface {}
+
+// Element of the types arrays of messages, it makes the arrays shareable
+// between threads and lets the interfaces of libwayland-client be filled
+// after its loading.
+#[repr(transparent)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct InterfaceRef(AtomicPtr<Interface>);
+
+impl InterfaceRef {
+    pub(super) const fn new(interface: *const Interface) -> Self {
+        Self(AtomicPtr::new(interface as *mut Interface))
+    }
+
+    pub(super) const fn none() -> Self {
+        Self::new(null())
+    }
+
+    pub(super) fn set(&self, interface: *const Interface) {
+        self.0.store(interface as *mut Interface, Ordering::Relaxed);
+    }
+
+    pub(super) fn get(&self) -> *const Interface {
+        return self.0.load(Ordering::Relaxed);
+    }
+}
+
+#[repr(C)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Array {
+    pub(super) size: usize,
+    pub(super) alloc: usize,
+    pub(super) data: *mut c_void,
+}
+
+pub(super) const DISPLAY_GET_REGISTRY: u32 = 1;
+
+pub(super) const REGISTRY_BIND: u32 = 0;
+
+pub(super) const COMPOSITOR_CREATE_SURFACE: u32 = 0;
+
+pub(super) const SURFACE_DESTROY: u32 = 0;
+pub(super) const SURFACE_COMMIT: u32 = 6;
+
+pub(super) const SEAT_GET_POINTER: u32 = 0;
+pub(super) const SEAT_GET_KEYBOARD: u32 = 1;
+pub(super) const SEAT_GET_TOUCH: u32 = 2;
+
+pub(super) const SEAT_CAPABILITY_POINTER: u32 = 1;
+pub(super) const SEAT_CAPABILITY_KEYBOARD: u32 = 2;
+pub(super) const SEAT_CAPABILITY_TOUCH: u32 = 4;
+
+pub(super) const POINTER_BUTTON_STATE_PRESSED: u32 = 1;
+
+pub(super) const KEYBOARD_KEYMAP_FORMAT_XKB_V1: u32 = 1;
+pub(super) const KEYBOARD_KEY_STATE_PRESSED: u32 = 1;
+
+#[repr(C)]
+pub(super) struct RegistryListener {
+    pub(super) global: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *const c_char, u32),
+    pub(super) global_remove: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+#[repr(C)]
+pub(super) struct SeatListener {
+    pub(super) capabilities: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+    pub(super) name: unsafe extern "C" fn(*mut c_void, *mut Proxy, *const c_char),
+}
+
+#[repr(C)]
+pub(super) struct PointerListener {
+    pub(super) enter: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy, Fixed, Fixed),
+    pub(super) leave: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy),
+    pub(super) motion: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, Fixed, Fixed),
+    pub(super) button: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32),
+    pub(super) axis: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, Fixed),
+    pub(super) frame: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+    pub(super) axis_source: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+    pub(super) axis_stop: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32),
+    pub(super) axis_discrete: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32),
+}
+
+#[repr(C)]
+pub(super) struct KeyboardListener {
+    pub(super) keymap: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32, u32),
+    pub(super) enter: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy, *mut Array),
+    pub(super) leave: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy),
+    pub(super) key: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32),
+    pub(super) modifiers: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32, u32),
+    pub(super) repeat_info: unsafe extern "C" fn(*mut c_void, *mut Proxy, i32, i32),
+}
+
+#[repr(C)]
+pub(super) struct TouchListener {
+    pub(super) down:
+        unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, *mut Proxy, i32, Fixed, Fixed),
+    pub(super) up: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, i32),
+    pub(super) motion: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32, Fixed, Fixed),
+    pub(super) frame: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+    pub(super) cancel: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+}
+
+// libwayland-client is loaded at runtime, so the binary still starts on the
+// systems without it and the application falls back to X11.
+pub(super) struct Library {
+    _linker: Linker,
+    pub(super) wl_registry_interface: *const Interface,
+    pub(super) wl_compositor_interface: *const Interface,
+    pub(super) wl_surface_interface: *const Interface,
+    pub(super) wl_seat_interface: *const Interface,
+    pub(super) wl_pointer_interface: *const Interface,
+    pub(super) wl_keyboard_interface: *const Interface,
+    pub(super) wl_touch_interface: *const Interface,
+    pub(super) wl_output_interface: *const Interface,
+
+    pub(super) wl_display_connect: unsafe extern "C" fn(*const c_char) -> *mut Display,
+    pub(super) wl_display_disconnect: unsafe extern "C" fn(*mut Display),
+    pub(super) wl_display_get_fd: unsafe extern "C" fn(*mut Display) -> c_int,
+    pub(super) wl_display_roundtrip: unsafe extern "C" fn(*mut Display) -> c_int,
+    pub(super) wl_display_dispatch_pending: unsafe extern "C" fn(*mut Display) -> c_int,
+    pub(super) wl_display_prepare_read: unsafe extern "C" fn(*mut Display) -> c_int,
+    pub(super) wl_display_read_events: unsafe extern "C" fn(*mut Display) -> c_int,
+    pub(super) wl_display_cancel_read: unsafe extern "C" fn(*mut Display),
+    pub(super) wl_display_flush: unsafe extern "C" fn(*mut Display) -> c_int,
+
+    pub(super) wl_proxy_marshal: unsafe extern "C" fn(*mut Proxy, u32, ...),
+    pub(super) wl_proxy_marshal_constructor:
+        unsafe extern "C" fn(*mut Proxy, u32, *const Interface, ...) -> *mut Proxy,
+    pub(super) wl_proxy_marshal_constructor_versioned:
+        unsafe extern "C" fn(*mut Proxy, u32, *const Interface, u32, ...) -> *mut Proxy,
+    pub(super) wl_proxy_add_listener:
+        unsafe extern "C" fn(*mut Proxy, *const c_void, *mut c_void) -> c_int,
+    pub(super) wl_proxy_destroy: unsafe extern "C" fn(*mut Proxy),
+}
+
+unsafe impl Send for Library {}
+
+unsafe impl Sync for Library {}
+
+const LIBRARY_NAME: &str = "libwayland-client.so.0";
+
+static LIBRARY: OnceLock<Option<Library>> = OnceLock::new();
+
+impl Library {
+    fn new() -> Option<Self> {
+        let linker = Linker::new(LIBRARY_NAME);
+        if !linker.is_ok() {
+            vx_log_i!("{} is not found.", LIBRARY_NAME);
+            return None;
+        }
+        macro_rules! symbol {
+            ($name:ident) => {
+                match linker.get_function(stringify!($name)) {
+                    Some(s) => s,
+                    None => {
+                        vx_log_e!(
+                            "Symbol {} is not found in {}.",
+                            stringify!($name),
+                            LIBRARY_NAME
+                        );
+                        return None;
+                    }
+                }
+            };
+        }
+        Some(Self {
+            wl_registry_interface: symbol!(wl_registry_interface),
+            wl_compositor_interface: symbol!(wl_compositor_interface),
+            wl_surface_interface: symbol!(wl_surface_interface),
+            wl_seat_interface: symbol!(wl_seat_interface),
+            wl_pointer_interface: symbol!(wl_pointer_interface),
+            wl_keyboard_interface: symbol!(wl_keyboard_interface),
+            wl_touch_interface: symbol!(wl_touch_interface),
+            wl_output_interface: symbol!(wl_output_interface),
+            wl_display_connect: symbol!(wl_display_connect),
+            wl_display_disconnect: symbol!(wl_display_disconnect),
+            wl_display_get_fd: symbol!(wl_display_get_fd),
+            wl_display_roundtrip: symbol!(wl_display_roundtrip),
+            wl_display_dispatch_pending: symbol!(wl_display_dispatch_pending),
+            wl_display_prepare_read: symbol!(wl_display_prepare_read),
+            wl_display_read_events: symbol!(wl_display_read_events),
+            wl_display_cancel_read: symbol!(wl_display_cancel_read),
+            wl_display_flush: symbol!(wl_display_flush),
+            wl_proxy_marshal: symbol!(wl_proxy_marshal),
+            wl_proxy_marshal_constructor: symbol!(wl_proxy_marshal_constructor),
+            wl_proxy_marshal_constructor_versioned: symbol!(wl_proxy_marshal_constructor_versioned),
+            wl_proxy_add_listener: symbol!(wl_proxy_add_listener),
+            wl_proxy_destroy: symbol!(wl_proxy_destroy),
+            _linker: linker,
+        })
+    }
+}
+
+// It loads the library once, None means there is no usable libwayland-client.
+pub(super) fn load() -> Option<&'static Library> {
+    return LIBRARY
+        .get_or_init(|| {
+            let library = Library::new()?;
+            protocol::link(&library);
+            Some(library)
+        })
+        .as_ref();
+}
+
+// Listeners and window use it after a successful load.
+pub(super) fn library() -> &'static Library {
+    return vx_unwrap!(vx_unwrap!(LIBRARY.get()).as_ref());
+}
+
+pub(super) unsafe fn add_listener<T>(proxy: *mut Proxy, listener: &'static T, data: *mut c_void) {
+    if (library().wl_proxy_add_listener)(proxy, listener as *const T as *const c_void, data) != 0 {
+        vx_log_f!("Wayland proxy already has a listener.");
+    }
+}
+
+pub(super) unsafe fn get_registry(display: *mut Display) -> *mut Proxy {
+    return (library().wl_proxy_marshal_constructor)(
+        display as *mut Proxy,
+        DISPLAY_GET_REGISTRY,
+        library().wl_registry_interface,
+        null::<c_void>(),
+    );
+}
+
+pub(super) unsafe fn bind(
+    registry: *mut Proxy,
+    name: u32,
+    interface: *const Interface,
+    version: u32,
+) -> *mut Proxy {
+    return (library().wl_proxy_marshal_constructor_versioned)(
+        registry,
+        REGISTRY_BIND,
+        interface,
+        version,
+        name,
+        (*interface).name,
+        version,
+        null::<c_void>(),
+    );
+}
+
+// It sends the destructor request of the object if it has one and then
+// frees the proxy.
+pub(super) unsafe fn destroy(proxy: *mut Proxy, destructor_opcode: Option<u32>) {
+    if proxy.is_null() {
+        return;
+    }
+    if let Some(opcode) = destructor_opcode {
+        (library().wl_proxy_marshal)(proxy, opcode);
+    }
+    (library().wl_proxy_destroy)(proxy);
+}
@@ -0,0 +1,4 @@
+pub mod client;
+pub mod protocol;
+pub mod window;
+pub mod xkb;
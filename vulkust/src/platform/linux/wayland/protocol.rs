@@ -0,0 +1,273 @@
+use super::client::{Interface, InterfaceRef, Library, Message, Proxy};
+use std::os::raw::{c_char, c_void};
+
+// Interfaces of the protocols that are not part of libwayland-client, they
+// are the hand written equivalent of the wayland-scanner private code for:
+// xdg-shell (stable), fractional-scale-v1 (staging) and viewporter (stable).
+
+macro_rules! cstr {
+    ($s:expr) => {
+        concat!($s, "\0").as_ptr() as *const c_char
+    };
+}
+
+macro_rules! message {
+    ($name:expr, $signature:expr, $types:expr) => {
+        Message {
+            name: cstr!($name),
+            signature: cstr!($signature),
+            types: $types as *const InterfaceRef as *const *const Interface,
+        }
+    };
+}
+
+macro_rules! interface {
+    ($name:expr, $version:expr, $methods:expr, $events:expr) => {
+        Interface {
+            name: cstr!($name),
+            version: $version,
+            method_count: $methods.len() as i32,
+            methods: $methods.as_ptr(),
+            event_count: $events.len() as i32,
+            events: $events.as_ptr(),
+        }
+    };
+}
+
+static NULL_TYPES: [InterfaceRef; 4] = [
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+];
+
+// xdg_wm_base -----------------------------------------------------------------
+
+pub(super) const XDG_WM_BASE_DESTROY: u32 = 0;
+pub(super) const XDG_WM_BASE_GET_XDG_SURFACE: u32 = 2;
+pub(super) const XDG_WM_BASE_PONG: u32 = 3;
+
+pub(super) const XDG_WM_BASE_VERSION: u32 = 3;
+
+static XDG_WM_BASE_CREATE_POSITIONER_TYPES: [InterfaceRef; 1] =
+    [InterfaceRef::new(&xdg_positioner_interface)];
+static XDG_WM_BASE_GET_XDG_SURFACE_TYPES: [InterfaceRef; 2] = [
+    InterfaceRef::new(&xdg_surface_interface),
+    InterfaceRef::none(),
+];
+
+static XDG_WM_BASE_REQUESTS: [Message; 4] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!(
+        "create_positioner",
+        "n",
+        &XDG_WM_BASE_CREATE_POSITIONER_TYPES
+    ),
+    message!("get_xdg_surface", "no", &XDG_WM_BASE_GET_XDG_SURFACE_TYPES),
+    message!("pong", "u", &NULL_TYPES),
+];
+
+static XDG_WM_BASE_EVENTS: [Message; 1] = [message!("ping", "u", &NULL_TYPES)];
+
+#[allow(non_upper_case_globals)]
+pub(super) static xdg_wm_base_interface: Interface =
+    interface!("xdg_wm_base", 3, XDG_WM_BASE_REQUESTS, XDG_WM_BASE_EVENTS);
+
+#[repr(C)]
+pub(super) struct XdgWmBaseListener {
+    pub(super) ping: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+// xdg_positioner and xdg_popup are only referenced by the other interfaces,
+// popups are not used, so their messages are not needed.
+
+static NO_MESSAGES: [Message; 0] = [];
+
+#[allow(non_upper_case_globals)]
+static xdg_positioner_interface: Interface =
+    interface!("xdg_positioner", 3, NO_MESSAGES, NO_MESSAGES);
+
+#[allow(non_upper_case_globals)]
+static xdg_popup_interface: Interface = interface!("xdg_popup", 3, NO_MESSAGES, NO_MESSAGES);
+
+// xdg_surface -----------------------------------------------------------------
+
+pub(super) const XDG_SURFACE_DESTROY: u32 = 0;
+pub(super) const XDG_SURFACE_GET_TOPLEVEL: u32 = 1;
+pub(super) const XDG_SURFACE_ACK_CONFIGURE: u32 = 4;
+
+static XDG_SURFACE_GET_TOPLEVEL_TYPES: [InterfaceRef; 1] =
+    [InterfaceRef::new(&xdg_toplevel_interface)];
+static XDG_SURFACE_GET_POPUP_TYPES: [InterfaceRef; 3] = [
+    InterfaceRef::new(&xdg_popup_interface),
+    InterfaceRef::new(&xdg_surface_interface),
+    InterfaceRef::new(&xdg_positioner_interface),
+];
+
+static XDG_SURFACE_REQUESTS: [Message; 5] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!("get_toplevel", "n", &XDG_SURFACE_GET_TOPLEVEL_TYPES),
+    message!("get_popup", "n?oo", &XDG_SURFACE_GET_POPUP_TYPES),
+    message!("set_window_geometry", "iiii", &NULL_TYPES),
+    message!("ack_configure", "u", &NULL_TYPES),
+];
+
+static XDG_SURFACE_EVENTS: [Message; 1] = [message!("configure", "u", &NULL_TYPES)];
+
+#[allow(non_upper_case_globals)]
+pub(super) static xdg_surface_interface: Interface =
+    interface!("xdg_surface", 3, XDG_SURFACE_REQUESTS, XDG_SURFACE_EVENTS);
+
+#[repr(C)]
+pub(super) struct XdgSurfaceListener {
+    pub(super) configure: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+// xdg_toplevel ----------------------------------------------------------------
+
+pub(super) const XDG_TOPLEVEL_DESTROY: u32 = 0;
+pub(super) const XDG_TOPLEVEL_SET_TITLE: u32 = 2;
+pub(super) const XDG_TOPLEVEL_SET_APP_ID: u32 = 3;
+pub(super) const XDG_TOPLEVEL_SET_FULLSCREEN: u32 = 11;
+pub(super) const XDG_TOPLEVEL_UNSET_FULLSCREEN: u32 = 12;
+
+static XDG_TOPLEVEL_SET_PARENT_TYPES: [InterfaceRef; 1] =
+    [InterfaceRef::new(&xdg_toplevel_interface)];
+static XDG_TOPLEVEL_SEAT_TYPES: [InterfaceRef; 4] = [
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+    InterfaceRef::none(),
+];
+static XDG_TOPLEVEL_SET_FULLSCREEN_TYPES: [InterfaceRef; 1] = [InterfaceRef::none()];
+
+static XDG_TOPLEVEL_REQUESTS: [Message; 14] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!("set_parent", "?o", &XDG_TOPLEVEL_SET_PARENT_TYPES),
+    message!("set_title", "s", &NULL_TYPES),
+    message!("set_app_id", "s", &NULL_TYPES),
+    message!("show_window_menu", "ouii", &XDG_TOPLEVEL_SEAT_TYPES),
+    message!("move", "ou", &XDG_TOPLEVEL_SEAT_TYPES),
+    message!("resize", "ouu", &XDG_TOPLEVEL_SEAT_TYPES),
+    message!("set_max_size", "ii", &NULL_TYPES),
+    message!("set_min_size", "ii", &NULL_TYPES),
+    message!("set_maximized", "", &NULL_TYPES),
+    message!("unset_maximized", "", &NULL_TYPES),
+    message!("set_fullscreen", "?o", &XDG_TOPLEVEL_SET_FULLSCREEN_TYPES),
+    message!("unset_fullscreen", "", &NULL_TYPES),
+    message!("set_minimized", "", &NULL_TYPES),
+];
+
+static XDG_TOPLEVEL_EVENTS: [Message; 2] = [
+    message!("configure", "iia", &NULL_TYPES),
+    message!("close", "", &NULL_TYPES),
+];
+
+#[allow(non_upper_case_globals)]
+pub(super) static xdg_toplevel_interface: Interface = interface!(
+    "xdg_toplevel",
+    3,
+    XDG_TOPLEVEL_REQUESTS,
+    XDG_TOPLEVEL_EVENTS
+);
+
+#[repr(C)]
+pub(super) struct XdgToplevelListener {
+    pub(super) configure:
+        unsafe extern "C" fn(*mut c_void, *mut Proxy, i32, i32, *mut super::client::Array),
+    pub(super) close: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+}
+
+// wp_fractional_scale_manager_v1 ----------------------------------------------
+
+pub(super) const FRACTIONAL_SCALE_MANAGER_DESTROY: u32 = 0;
+pub(super) const FRACTIONAL_SCALE_MANAGER_GET_FRACTIONAL_SCALE: u32 = 1;
+
+static FRACTIONAL_SCALE_MANAGER_GET_TYPES: [InterfaceRef; 2] = [
+    InterfaceRef::new(&wp_fractional_scale_v1_interface),
+    InterfaceRef::none(),
+];
+
+static FRACTIONAL_SCALE_MANAGER_REQUESTS: [Message; 2] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!(
+        "get_fractional_scale",
+        "no",
+        &FRACTIONAL_SCALE_MANAGER_GET_TYPES
+    ),
+];
+
+#[allow(non_upper_case_globals)]
+pub(super) static wp_fractional_scale_manager_v1_interface: Interface = interface!(
+    "wp_fractional_scale_manager_v1",
+    1,
+    FRACTIONAL_SCALE_MANAGER_REQUESTS,
+    NO_MESSAGES
+);
+
+// wp_fractional_scale_v1 ------------------------------------------------------
+
+pub(super) const FRACTIONAL_SCALE_DESTROY: u32 = 0;
+
+// Preferred scale is in 120th, so 150% is sent as 180.
+pub(super) const FRACTIONAL_SCALE_DENOMINATOR: f32 = 120.0;
+
+static FRACTIONAL_SCALE_REQUESTS: [Message; 1] = [message!("destroy", "", &NULL_TYPES)];
+
+static FRACTIONAL_SCALE_EVENTS: [Message; 1] = [message!("preferred_scale", "u", &NULL_TYPES)];
+
+#[allow(non_upper_case_globals)]
+pub(super) static wp_fractional_scale_v1_interface: Interface = interface!(
+    "wp_fractional_scale_v1",
+    1,
+    FRACTIONAL_SCALE_REQUESTS,
+    FRACTIONAL_SCALE_EVENTS
+);
+
+#[repr(C)]
+pub(super) struct FractionalScaleListener {
+    pub(super) preferred_scale: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+// wp_viewporter ---------------------------------------------------------------
+
+pub(super) const VIEWPORTER_DESTROY: u32 = 0;
+pub(super) const VIEWPORTER_GET_VIEWPORT: u32 = 1;
+
+static VIEWPORTER_GET_VIEWPORT_TYPES: [InterfaceRef; 2] = [
+    InterfaceRef::new(&wp_viewport_interface),
+    InterfaceRef::none(),
+];
+
+static VIEWPORTER_REQUESTS: [Message; 2] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!("get_viewport", "no", &VIEWPORTER_GET_VIEWPORT_TYPES),
+];
+
+#[allow(non_upper_case_globals)]
+pub(super) static wp_viewporter_interface: Interface =
+    interface!("wp_viewporter", 1, VIEWPORTER_REQUESTS, NO_MESSAGES);
+
+// wp_viewport -----------------------------------------------------------------
+
+pub(super) const VIEWPORT_DESTROY: u32 = 0;
+pub(super) const VIEWPORT_SET_DESTINATION: u32 = 2;
+
+static VIEWPORT_REQUESTS: [Message; 3] = [
+    message!("destroy", "", &NULL_TYPES),
+    message!("set_source", "ffff", &NULL_TYPES),
+    message!("set_destination", "ii", &NULL_TYPES),
+];
+
+#[allow(non_upper_case_globals)]
+pub(super) static wp_viewport_interface: Interface =
+    interface!("wp_viewport", 1, VIEWPORT_REQUESTS, NO_MESSAGES);
+
+// Interfaces of libwayland-client are only known after its loading.
+pub(super) fn link(library: &Library) {
+    XDG_WM_BASE_GET_XDG_SURFACE_TYPES[1].set(library.wl_surface_interface);
+    XDG_TOPLEVEL_SEAT_TYPES[0].set(library.wl_seat_interface);
+    XDG_TOPLEVEL_SET_FULLSCREEN_TYPES[0].set(library.wl_output_interface);
+    FRACTIONAL_SCALE_MANAGER_GET_TYPES[1].set(library.wl_surface_interface);
+    VIEWPORTER_GET_VIEWPORT_TYPES[1].set(library.wl_surface_interface);
+}
@@ -0,0 +1,816 @@
+use super::super::super::super::core::event::{
+    Button, ButtonAction, FingerIndexType, Mouse, Move, Touch, TouchAction, Type as EventType,
+    Window as WindowEvent,
+};
+use super::super::super::super::core::types::Real;
+use super::super::super::super::libc;
+use super::super::application::create_title;
+use super::client::{self, Array, Display, Fixed, Proxy};
+use super::protocol::{self, FRACTIONAL_SCALE_DENOMINATOR};
+use super::xkb;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr::{null, null_mut};
+
+const COMPOSITOR_VERSION: u32 = 4;
+const SEAT_VERSION: u32 = 5;
+
+const POINTER_RELEASE: u32 = 1;
+const KEYBOARD_RELEASE: u32 = 0;
+const TOUCH_RELEASE: u32 = 0;
+const SEAT_RELEASE: u32 = 3;
+
+// Linux input event codes of the mouse buttons.
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+
+// Everything that the listeners change, it is boxed so its address that is
+// given to libwayland remains valid.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct State {
+    compositor: *mut Proxy,
+    wm_base: *mut Proxy,
+    seat: *mut Proxy,
+    pointer: *mut Proxy,
+    keyboard: *mut Proxy,
+    touch: *mut Proxy,
+    fractional_scale_manager: *mut Proxy,
+    viewporter: *mut Proxy,
+    viewport: *mut Proxy,
+    xkb: xkb::State,
+    events: Vec<EventType>,
+    is_configured: bool,
+    is_disconnected: bool,
+    width: i32,
+    height: i32,
+    pending_width: i32,
+    pending_height: i32,
+    scale: Real,
+    mouse_position: (Real, Real),
+    touches: BTreeMap<FingerIndexType, (Real, Real)>,
+}
+
+impl State {
+    fn get_physical_size(&self) -> (Real, Real) {
+        return (
+            (self.width as Real * self.scale).round(),
+            (self.height as Real * self.scale).round(),
+        );
+    }
+
+    fn normalize(&self, x: Fixed, y: Fixed) -> (Real, Real) {
+        return (
+            client::fixed_to_f32(x) / self.width.max(1) as Real,
+            client::fixed_to_f32(y) / self.height.max(1) as Real,
+        );
+    }
+
+    // Surface size is in logical unit, swapchain must be in the scaled size
+    // and the viewport maps it back to the logical size.
+    fn resize(&mut self, width: i32, height: i32, scale: Real) {
+        let previous = self.get_physical_size();
+        let previous_scale = self.scale;
+        self.width = width;
+        self.height = height;
+        self.scale = scale;
+        if previous_scale != scale && self.is_configured {
+            self.events
+                .push(EventType::Window(WindowEvent::ScaleFactorChange {
+                    scale,
+                    pre_scale: previous_scale,
+                }));
+        }
+        if !self.viewport.is_null() {
+            unsafe {
+                (client::library().wl_proxy_marshal)(
+                    self.viewport,
+                    protocol::VIEWPORT_SET_DESTINATION,
+                    width,
+                    height,
+                );
+            }
+        }
+        let current = self.get_physical_size();
+        if current == previous || !self.is_configured {
+            return;
+        }
+        self.events.push(EventType::Window(WindowEvent::SizeChange {
+            w: current.0,
+            h: current.1,
+            ratio: current.0 / current.1,
+            pre_w: previous.0,
+            pre_h: previous.1,
+            pre_ratio: previous.0 / previous.1,
+        }));
+    }
+}
+
+fn get_state<'a>(data: *mut c_void) -> &'a mut State {
+    return unsafe { &mut *(data as *mut State) };
+}
+
+unsafe extern "C" fn registry_global(
+    data: *mut c_void,
+    registry: *mut Proxy,
+    name: u32,
+    interface: *const c_char,
+    version: u32,
+) {
+    let state = get_state(data);
+    let interface = CStr::from_ptr(interface).to_bytes();
+    match interface {
+        b"wl_compositor" => {
+            state.compositor = client::bind(
+                registry,
+                name,
+                client::library().wl_compositor_interface,
+                version.min(COMPOSITOR_VERSION),
+            );
+        }
+        b"xdg_wm_base" => {
+            state.wm_base = client::bind(
+                registry,
+                name,
+                &protocol::xdg_wm_base_interface,
+                version.min(protocol::XDG_WM_BASE_VERSION),
+            );
+            client::add_listener(state.wm_base, &WM_BASE_LISTENER, data);
+        }
+        b"wl_seat" => {
+            if !state.seat.is_null() {
+                vx_log_i!("Only the first Wayland seat is used.");
+                return;
+            }
+            state.seat = client::bind(
+                registry,
+                name,
+                client::library().wl_seat_interface,
+                version.min(SEAT_VERSION),
+            );
+            client::add_listener(state.seat, &SEAT_LISTENER, data);
+        }
+        b"wp_fractional_scale_manager_v1" => {
+            state.fractional_scale_manager = client::bind(
+                registry,
+                name,
+                &protocol::wp_fractional_scale_manager_v1_interface,
+                1,
+            );
+        }
+        b"wp_viewporter" => {
+            state.viewporter = client::bind(registry, name, &protocol::wp_viewporter_interface, 1);
+        }
+        _ => (),
+    }
+}
+
+unsafe extern "C" fn registry_global_remove(_: *mut c_void, _: *mut Proxy, _: u32) {}
+
+static REGISTRY_LISTENER: client::RegistryListener = client::RegistryListener {
+    global: registry_global,
+    global_remove: registry_global_remove,
+};
+
+unsafe extern "C" fn wm_base_ping(_: *mut c_void, wm_base: *mut Proxy, serial: u32) {
+    (client::library().wl_proxy_marshal)(wm_base, protocol::XDG_WM_BASE_PONG, serial);
+}
+
+static WM_BASE_LISTENER: protocol::XdgWmBaseListener =
+    protocol::XdgWmBaseListener { ping: wm_base_ping };
+
+unsafe extern "C" fn xdg_surface_configure(
+    data: *mut c_void,
+    xdg_surface: *mut Proxy,
+    serial: u32,
+) {
+    let state = get_state(data);
+    (client::library().wl_proxy_marshal)(xdg_surface, protocol::XDG_SURFACE_ACK_CONFIGURE, serial);
+    let (width, height, scale) = (state.pending_width, state.pending_height, state.scale);
+    state.resize(width, height, scale);
+    state.is_configured = true;
+}
+
+static XDG_SURFACE_LISTENER: protocol::XdgSurfaceListener = protocol::XdgSurfaceListener {
+    configure: xdg_surface_configure,
+};
+
+// Zero size means the client decides, the current size is kept.
+unsafe extern "C" fn toplevel_configure(
+    data: *mut c_void,
+    _: *mut Proxy,
+    width: i32,
+    height: i32,
+    _states: *mut Array,
+) {
+    let state = get_state(data);
+    if width > 0 && height > 0 {
+        state.pending_width = width;
+        state.pending_height = height;
+    }
+}
+
+unsafe extern "C" fn toplevel_close(data: *mut c_void, _: *mut Proxy) {
+    get_state(data)
+        .events
+        .push(EventType::Window(WindowEvent::CloseRequest));
+}
+
+static TOPLEVEL_LISTENER: protocol::XdgToplevelListener = protocol::XdgToplevelListener {
+    configure: toplevel_configure,
+    close: toplevel_close,
+};
+
+unsafe extern "C" fn fractional_scale_preferred(data: *mut c_void, _: *mut Proxy, scale: u32) {
+    let state = get_state(data);
+    let (width, height) = (state.width, state.height);
+    state.resize(width, height, scale as Real / FRACTIONAL_SCALE_DENOMINATOR);
+}
+
+static FRACTIONAL_SCALE_LISTENER: protocol::FractionalScaleListener =
+    protocol::FractionalScaleListener {
+        preferred_scale: fractional_scale_preferred,
+    };
+
+unsafe fn update_device(
+    device: &mut *mut Proxy,
+    has_capability: bool,
+    seat: *mut Proxy,
+    get_opcode: u32,
+    interface: *const client::Interface,
+    release_opcode: u32,
+    listener: *const c_void,
+    data: *mut c_void,
+) {
+    if has_capability && device.is_null() {
+        *device = (client::library().wl_proxy_marshal_constructor)(
+            seat,
+            get_opcode,
+            interface,
+            null::<c_void>(),
+        );
+        if (client::library().wl_proxy_add_listener)(*device, listener, data) != 0 {
+            vx_log_f!("Wayland input device already has a listener.");
+        }
+    } else if !has_capability && !device.is_null() {
+        client::destroy(*device, Some(release_opcode));
+        *device = null_mut();
+    }
+}
+
+unsafe extern "C" fn seat_capabilities(data: *mut c_void, seat: *mut Proxy, capabilities: u32) {
+    let state = get_state(data);
+    update_device(
+        &mut state.pointer,
+        capabilities & client::SEAT_CAPABILITY_POINTER != 0,
+        seat,
+        client::SEAT_GET_POINTER,
+        client::library().wl_pointer_interface,
+        POINTER_RELEASE,
+        &POINTER_LISTENER as *const client::PointerListener as *const c_void,
+        data,
+    );
+    update_device(
+        &mut state.keyboard,
+        capabilities & client::SEAT_CAPABILITY_KEYBOARD != 0,
+        seat,
+        client::SEAT_GET_KEYBOARD,
+        client::library().wl_keyboard_interface,
+        KEYBOARD_RELEASE,
+        &KEYBOARD_LISTENER as *const client::KeyboardListener as *const c_void,
+        data,
+    );
+    update_device(
+        &mut state.touch,
+        capabilities & client::SEAT_CAPABILITY_TOUCH != 0,
+        seat,
+        client::SEAT_GET_TOUCH,
+        client::library().wl_touch_interface,
+        TOUCH_RELEASE,
+        &TOUCH_LISTENER as *const client::TouchListener as *const c_void,
+        data,
+    );
+}
+
+unsafe extern "C" fn seat_name(_: *mut c_void, _: *mut Proxy, _: *const c_char) {}
+
+static SEAT_LISTENER: client::SeatListener = client::SeatListener {
+    capabilities: seat_capabilities,
+    name: seat_name,
+};
+
+unsafe extern "C" fn pointer_enter(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _serial: u32,
+    _surface: *mut Proxy,
+    x: Fixed,
+    y: Fixed,
+) {
+    let state = get_state(data);
+    state.mouse_position = state.normalize(x, y);
+}
+
+unsafe extern "C" fn pointer_leave(_: *mut c_void, _: *mut Proxy, _: u32, _: *mut Proxy) {}
+
+unsafe extern "C" fn pointer_motion(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _time: u32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let state = get_state(data);
+    let previous = state.mouse_position;
+    let current = state.normalize(x, y);
+    state.mouse_position = current;
+    state.events.push(EventType::Move(Move::Mouse {
+        previous,
+        current,
+        delta: (current.0 - previous.0, current.1 - previous.1),
+    }));
+}
+
+unsafe extern "C" fn pointer_button(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    button: u32,
+    button_state: u32,
+) {
+    let state = get_state(data);
+    let m = match button {
+        BTN_LEFT => Mouse::Left,
+        BTN_RIGHT => Mouse::Right,
+        BTN_MIDDLE => Mouse::Middle,
+        BTN_SIDE => Mouse::Back,
+        BTN_EXTRA => Mouse::Forward,
+        _ => {
+            vx_log_i!("Unknown mouse button: {}", button);
+            return;
+        }
+    };
+    state.events.push(EventType::Button {
+        button: Button::Mouse(m),
+        action: if button_state == client::POINTER_BUTTON_STATE_PRESSED {
+            ButtonAction::Press
+        } else {
+            ButtonAction::Release
+        },
+    });
+}
+
+unsafe extern "C" fn pointer_axis(_: *mut c_void, _: *mut Proxy, _: u32, _: u32, _: Fixed) {}
+
+unsafe extern "C" fn pointer_frame(_: *mut c_void, _: *mut Proxy) {}
+
+unsafe extern "C" fn pointer_axis_source(_: *mut c_void, _: *mut Proxy, _: u32) {}
+
+unsafe extern "C" fn pointer_axis_stop(_: *mut c_void, _: *mut Proxy, _: u32, _: u32) {}
+
+unsafe extern "C" fn pointer_axis_discrete(_: *mut c_void, _: *mut Proxy, _: u32, _: i32) {}
+
+static POINTER_LISTENER: client::PointerListener = client::PointerListener {
+    enter: pointer_enter,
+    leave: pointer_leave,
+    motion: pointer_motion,
+    button: pointer_button,
+    axis: pointer_axis,
+    frame: pointer_frame,
+    axis_source: pointer_axis_source,
+    axis_stop: pointer_axis_stop,
+    axis_discrete: pointer_axis_discrete,
+};
+
+unsafe extern "C" fn keyboard_keymap(
+    data: *mut c_void,
+    _: *mut Proxy,
+    format: u32,
+    fd: i32,
+    size: u32,
+) {
+    if format != client::KEYBOARD_KEYMAP_FORMAT_XKB_V1 {
+        vx_log_e!("Unsupported Wayland keymap format: {}", format);
+        libc::close(fd);
+        return;
+    }
+    get_state(data).xkb.set_keymap(fd, size);
+}
+
+unsafe extern "C" fn keyboard_enter(
+    _: *mut c_void,
+    _: *mut Proxy,
+    _: u32,
+    _: *mut Proxy,
+    _: *mut Array,
+) {
+}
+
+unsafe extern "C" fn keyboard_leave(_: *mut c_void, _: *mut Proxy, _: u32, _: *mut Proxy) {}
+
+unsafe extern "C" fn keyboard_key(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    key: u32,
+    key_state: u32,
+) {
+    let state = get_state(data);
+    let k = state.xkb.translate(key);
+    state.events.push(EventType::Button {
+        button: Button::Keyboard(k),
+        action: if key_state == client::KEYBOARD_KEY_STATE_PRESSED {
+            ButtonAction::Press
+        } else {
+            ButtonAction::Release
+        },
+    });
+}
+
+unsafe extern "C" fn keyboard_modifiers(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _serial: u32,
+    depressed: u32,
+    latched: u32,
+    locked: u32,
+    group: u32,
+) {
+    get_state(data)
+        .xkb
+        .update_modifiers(depressed, latched, locked, group);
+}
+
+unsafe extern "C" fn keyboard_repeat_info(_: *mut c_void, _: *mut Proxy, _: i32, _: i32) {}
+
+static KEYBOARD_LISTENER: client::KeyboardListener = client::KeyboardListener {
+    keymap: keyboard_keymap,
+    enter: keyboard_enter,
+    leave: keyboard_leave,
+    key: keyboard_key,
+    modifiers: keyboard_modifiers,
+    repeat_info: keyboard_repeat_info,
+};
+
+unsafe extern "C" fn touch_down(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    _surface: *mut Proxy,
+    id: i32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let state = get_state(data);
+    let point = state.normalize(x, y);
+    let index = id as FingerIndexType;
+    state.touches.insert(index, point);
+    state.events.push(EventType::Touch(Touch::Raw {
+        index,
+        action: TouchAction::Press,
+        point,
+    }));
+}
+
+unsafe extern "C" fn touch_up(data: *mut c_void, _: *mut Proxy, _serial: u32, _time: u32, id: i32) {
+    let state = get_state(data);
+    let index = id as FingerIndexType;
+    if let Some(point) = state.touches.remove(&index) {
+        state.events.push(EventType::Touch(Touch::Raw {
+            index,
+            action: TouchAction::Release,
+            point,
+        }));
+    }
+}
+
+unsafe extern "C" fn touch_motion(
+    data: *mut c_void,
+    _: *mut Proxy,
+    _time: u32,
+    id: i32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let state = get_state(data);
+    let index = id as FingerIndexType;
+    let current = state.normalize(x, y);
+    let previous = match state.touches.insert(index, current) {
+        Some(p) => p,
+        None => current,
+    };
+    state.events.push(EventType::Move(Move::Touch {
+        index,
+        previous,
+        current,
+        delta: (current.0 - previous.0, current.1 - previous.1),
+    }));
+}
+
+unsafe extern "C" fn touch_frame(_: *mut c_void, _: *mut Proxy) {}
+
+// Compositor took the touches for a gesture of its own.
+unsafe extern "C" fn touch_cancel(data: *mut c_void, _: *mut Proxy) {
+    let state = get_state(data);
+    let touches = std::mem::take(&mut state.touches);
+    for (index, point) in touches {
+        state.events.push(EventType::Touch(Touch::Raw {
+            index,
+            action: TouchAction::Release,
+            point,
+        }));
+    }
+}
+
+static TOUCH_LISTENER: client::TouchListener = client::TouchListener {
+    down: touch_down,
+    up: touch_up,
+    motion: touch_motion,
+    frame: touch_frame,
+    cancel: touch_cancel,
+};
+
+// A xdg-shell toplevel window on a Wayland compositor.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Window {
+    display: *mut Display,
+    registry: *mut Proxy,
+    surface: *mut Proxy,
+    xdg_surface: *mut Proxy,
+    toplevel: *mut Proxy,
+    fractional_scale: *mut Proxy,
+    state: Box<State>,
+}
+
+impl Window {
+    // It returns None when libwayland-client or libxkbcommon is missing, when
+    // there is no Wayland compositor or when it misses the needed globals,
+    // then caller can fallback to X11.
+    pub(crate) fn new(title: &str, width: u32, height: u32) -> Option<Self> {
+        let library = client::load()?;
+        let xkb = xkb::State::new()?;
+        let display = unsafe { (library.wl_display_connect)(null()) };
+        if display.is_null() {
+            vx_log_i!("There is no Wayland compositor.");
+            return None;
+        }
+        let mut state = Box::new(State {
+            compositor: null_mut(),
+            wm_base: null_mut(),
+            seat: null_mut(),
+            pointer: null_mut(),
+            keyboard: null_mut(),
+            touch: null_mut(),
+            fractional_scale_manager: null_mut(),
+            viewporter: null_mut(),
+            viewport: null_mut(),
+            xkb,
+            events: Vec::new(),
+            is_configured: false,
+            is_disconnected: false,
+            width: width as i32,
+            height: height as i32,
+            pending_width: width as i32,
+            pending_height: height as i32,
+            scale: 1.0,
+            mouse_position: (0.0, 0.0),
+            touches: BTreeMap::new(),
+        });
+        let data = &mut *state as *mut State as *mut c_void;
+        let registry = unsafe { client::get_registry(display) };
+        let mut myself = Self {
+            display,
+            registry,
+            surface: null_mut(),
+            xdg_surface: null_mut(),
+            toplevel: null_mut(),
+            fractional_scale: null_mut(),
+            state,
+        };
+        unsafe {
+            client::add_listener(registry, &REGISTRY_LISTENER, data);
+            // first one for globals and second one for seat capabilities
+            (library.wl_display_roundtrip)(display);
+            (library.wl_display_roundtrip)(display);
+        }
+        if myself.state.compositor.is_null() || myself.state.wm_base.is_null() {
+            vx_log_i!("Wayland compositor does not have wl_compositor or xdg_wm_base.");
+            return None;
+        }
+        unsafe {
+            myself.surface = (library.wl_proxy_marshal_constructor)(
+                myself.state.compositor,
+                client::COMPOSITOR_CREATE_SURFACE,
+                library.wl_surface_interface,
+                null::<c_void>(),
+            );
+            myself.xdg_surface = (library.wl_proxy_marshal_constructor)(
+                myself.state.wm_base,
+                protocol::XDG_WM_BASE_GET_XDG_SURFACE,
+                &protocol::xdg_surface_interface,
+                null::<c_void>(),
+                myself.surface,
+            );
+            client::add_listener(myself.xdg_surface, &XDG_SURFACE_LISTENER, data);
+            myself.toplevel = (library.wl_proxy_marshal_constructor)(
+                myself.xdg_surface,
+                protocol::XDG_SURFACE_GET_TOPLEVEL,
+                &protocol::xdg_toplevel_interface,
+                null::<c_void>(),
+            );
+            client::add_listener(myself.toplevel, &TOPLEVEL_LISTENER, data);
+            let title = create_title(title);
+            (library.wl_proxy_marshal)(
+                myself.toplevel,
+                protocol::XDG_TOPLEVEL_SET_TITLE,
+                title.as_ptr(),
+            );
+            let app_id = CString::new("vulkust").unwrap();
+            (library.wl_proxy_marshal)(
+                myself.toplevel,
+                protocol::XDG_TOPLEVEL_SET_APP_ID,
+                app_id.as_ptr(),
+            );
+            if !myself.state.fractional_scale_manager.is_null()
+                && !myself.state.viewporter.is_null()
+            {
+                myself.fractional_scale = (library.wl_proxy_marshal_constructor)(
+                    myself.state.fractional_scale_manager,
+                    protocol::FRACTIONAL_SCALE_MANAGER_GET_FRACTIONAL_SCALE,
+                    &protocol::wp_fractional_scale_v1_interface,
+                    null::<c_void>(),
+                    myself.surface,
+                );
+                client::add_listener(myself.fractional_scale, &FRACTIONAL_SCALE_LISTENER, data);
+                myself.state.viewport = (library.wl_proxy_marshal_constructor)(
+                    myself.state.viewporter,
+                    protocol::VIEWPORTER_GET_VIEWPORT,
+                    &protocol::wp_viewport_interface,
+                    null::<c_void>(),
+                    myself.surface,
+                );
+            }
+            // Surface must be committed without any buffer to get its first
+            // configure event.
+            (library.wl_proxy_marshal)(myself.surface, client::SURFACE_COMMIT);
+            // listeners change the state through data pointer
+            while !get_state(data).is_configured {
+                if (library.wl_display_roundtrip)(display) < 0 {
+                    vx_log_e!("Wayland connection is lost before the configuration of window.");
+                    return None;
+                }
+            }
+        }
+        return Some(myself);
+    }
+
+    // It does not block, it only reads what has already arrived.
+    pub(crate) fn fetch_events(&mut self) -> Vec<EventType> {
+        let library = client::library();
+        unsafe {
+            while (library.wl_display_prepare_read)(self.display) != 0 {
+                (library.wl_display_dispatch_pending)(self.display);
+            }
+            (library.wl_display_flush)(self.display);
+            let mut fds = libc::pollfd {
+                fd: (library.wl_display_get_fd)(self.display),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if libc::poll(&mut fds, 1, 0) > 0 {
+                (library.wl_display_read_events)(self.display);
+            } else {
+                (library.wl_display_cancel_read)(self.display);
+            }
+            if (library.wl_display_dispatch_pending)(self.display) < 0
+                && !self.state.is_disconnected
+            {
+                self.state.is_disconnected = true;
+                self.state.events.push(EventType::Quit);
+            }
+        }
+        return std::mem::take(&mut self.state.events);
+    }
+
+    // Compositor chooses the output, the new size comes with the next
+    // configure event.
+    pub(crate) fn set_fullscreen(&self, fullscreen: bool) {
+        unsafe {
+            if fullscreen {
+                (client::library().wl_proxy_marshal)(
+                    self.toplevel,
+                    protocol::XDG_TOPLEVEL_SET_FULLSCREEN,
+                    null_mut::<Proxy>(),
+                );
+            } else {
+                (client::library().wl_proxy_marshal)(
+                    self.toplevel,
+                    protocol::XDG_TOPLEVEL_UNSET_FULLSCREEN,
+                );
+            }
+            (client::library().wl_display_flush)(self.display);
+        }
+    }
+
+    pub(crate) fn get_mouse_position(&self) -> (Real, Real) {
+        return self.state.mouse_position;
+    }
+
+    pub(crate) fn get_scale(&self) -> Real {
+        return self.state.scale;
+    }
+
+    // It is in pixels, fractional scale is applied.
+    pub(crate) fn get_size(&self) -> (Real, Real) {
+        return self.state.get_physical_size();
+    }
+
+    pub(crate) fn get_display(&self) -> *mut Display {
+        return self.display;
+    }
+
+    pub(crate) fn get_surface(&self) -> *mut c_void {
+        return self.surface as *mut c_void;
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        let state = &mut self.state;
+        unsafe {
+            client::destroy(state.viewport, Some(protocol::VIEWPORT_DESTROY));
+            client::destroy(
+                self.fractional_scale,
+                Some(protocol::FRACTIONAL_SCALE_DESTROY),
+            );
+            client::destroy(self.toplevel, Some(protocol::XDG_TOPLEVEL_DESTROY));
+            client::destroy(self.xdg_surface, Some(protocol::XDG_SURFACE_DESTROY));
+            client::destroy(self.surface, Some(client::SURFACE_DESTROY));
+            client::destroy(state.pointer, Some(POINTER_RELEASE));
+            client::destroy(state.keyboard, Some(KEYBOARD_RELEASE));
+            client::destroy(state.touch, Some(TOUCH_RELEASE));
+            client::destroy(state.seat, Some(SEAT_RELEASE));
+            client::destroy(state.viewporter, Some(protocol::VIEWPORTER_DESTROY));
+            client::destroy(
+                state.fractional_scale_manager,
+                Some(protocol::FRACTIONAL_SCALE_MANAGER_DESTROY),
+            );
+            client::destroy(state.wm_base, Some(protocol::XDG_WM_BASE_DESTROY));
+            client::destroy(state.compositor, None);
+            client::destroy(self.registry, None);
+            (client::library().wl_display_disconnect)(self.display);
+        }
+    }
+}
+
+unsafe impl Send for Window {}
+
+unsafe impl Sync for Window {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // It does not need a compositor, without libwayland-client the window
+    // must fail so the X11 fallback is used and with it, the messages of the
+    // protocols must refer to the loaded interfaces.
+    #[test]
+    fn library_test() {
+        let library = match client::load() {
+            Some(library) => library,
+            None => {
+                assert!(Window::new("Vulkust Wayland Test", 320, 240).is_none());
+                return;
+            }
+        };
+        let name = CString::new("vulkust-missing-compositor").unwrap();
+        assert!(unsafe { (library.wl_display_connect)(name.as_ptr()) }.is_null());
+        let show_window_menu = unsafe { &*protocol::xdg_toplevel_interface.methods.add(4) };
+        let seat = unsafe { *show_window_menu.types };
+        assert_eq!(seat, library.wl_seat_interface);
+        let seat_name = unsafe { CStr::from_ptr((*seat).name) };
+        assert_eq!(seat_name.to_str().unwrap(), "wl_seat");
+    }
+
+    // It needs a running compositor, for example:
+    // weston --backend=headless-backend.so --socket=vulkust-test &
+    // WAYLAND_DISPLAY=vulkust-test cargo test --features wayland
+    #[test]
+    fn headless_compositor_test() {
+        if std::env::var("WAYLAND_DISPLAY").is_err() {
+            return;
+        }
+        let mut window = vx_unwrap!(Window::new("Vulkust Wayland Test", 320, 240));
+        let (w, h) = window.get_size();
+        assert!(w > 0.0 && h > 0.0);
+        window.fetch_events();
+        assert!(!window.get_display().is_null());
+        assert!(!window.get_surface().is_null());
+    }
+}
@@ -0,0 +1,318 @@
+use super::super::super::super::core::event::Keyboard;
+use super::super::super::super::libc;
+use super::super::super::linker::Linker;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr::null_mut;
+use std::sync::OnceLock;
+
+// Hand written bindings of the few libxkbcommon functions that turn the
+// evdev key codes of the Wayland keyboard into key symbols.
+
+enum Context {}
+enum Keymap {}
+enum XkbState {}
+
+const CONTEXT_NO_FLAGS: c_int = 0;
+const KEYMAP_FORMAT_TEXT_V1: c_int = 1;
+const KEYMAP_COMPILE_NO_FLAGS: c_int = 0;
+
+// Wayland key codes are evdev ones, xkb ones are 8 more than them.
+const EVDEV_OFFSET: u32 = 8;
+
+// libxkbcommon is loaded at runtime like libwayland-client.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Library {
+    _linker: Linker,
+    xkb_context_new: unsafe extern "C" fn(c_int) -> *mut Context,
+    xkb_context_unref: unsafe extern "C" fn(*mut Context),
+    xkb_keymap_new_from_string:
+        unsafe extern "C" fn(*mut Context, *const c_char, c_int, c_int) -> *mut Keymap,
+    xkb_keymap_unref: unsafe extern "C" fn(*mut Keymap),
+    xkb_state_new: unsafe extern "C" fn(*mut Keymap) -> *mut XkbState,
+    xkb_state_unref: unsafe extern "C" fn(*mut XkbState),
+    xkb_state_update_mask:
+        unsafe extern "C" fn(*mut XkbState, u32, u32, u32, u32, u32, u32) -> c_int,
+    xkb_state_key_get_one_sym: unsafe extern "C" fn(*mut XkbState, u32) -> u32,
+}
+
+unsafe impl Send for Library {}
+
+unsafe impl Sync for Library {}
+
+const LIBRARY_NAME: &str = "libxkbcommon.so.0";
+
+static LIBRARY: OnceLock<Option<Library>> = OnceLock::new();
+
+impl Library {
+    fn new() -> Option<Self> {
+        let linker = Linker::new(LIBRARY_NAME);
+        if !linker.is_ok() {
+            vx_log_i!("{} is not found.", LIBRARY_NAME);
+            return None;
+        }
+        macro_rules! symbol {
+            ($name:ident) => {
+                match linker.get_function(stringify!($name)) {
+                    Some(s) => s,
+                    None => {
+                        vx_log_e!(
+                            "Symbol {} is not found in {}.",
+                            stringify!($name),
+                            LIBRARY_NAME
+                        );
+                        return None;
+                    }
+                }
+            };
+        }
+        Some(Self {
+            xkb_context_new: symbol!(xkb_context_new),
+            xkb_context_unref: symbol!(xkb_context_unref),
+            xkb_keymap_new_from_string: symbol!(xkb_keymap_new_from_string),
+            xkb_keymap_unref: symbol!(xkb_keymap_unref),
+            xkb_state_new: symbol!(xkb_state_new),
+            xkb_state_unref: symbol!(xkb_state_unref),
+            xkb_state_update_mask: symbol!(xkb_state_update_mask),
+            xkb_state_key_get_one_sym: symbol!(xkb_state_key_get_one_sym),
+            _linker: linker,
+        })
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct State {
+    library: &'static Library,
+    context: *mut Context,
+    keymap: *mut Keymap,
+    state: *mut XkbState,
+}
+
+impl State {
+    // It returns None when libxkbcommon is missing or unusable.
+    pub(super) fn new() -> Option<Self> {
+        let library = LIBRARY.get_or_init(Library::new).as_ref()?;
+        let context = unsafe { (library.xkb_context_new)(CONTEXT_NO_FLAGS) };
+        if context.is_null() {
+            vx_log_e!("Can not create xkb context.");
+            return None;
+        }
+        Some(Self {
+            library,
+            context,
+            keymap: null_mut(),
+            state: null_mut(),
+        })
+    }
+
+    // Keymap is sent by the compositor as a shared memory file.
+    pub(super) fn set_keymap(&mut self, fd: i32, size: u32) {
+        let data = unsafe {
+            libc::mmap(
+                null_mut(),
+                size as usize,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                fd,
+                0,
+            )
+        };
+        unsafe {
+            libc::close(fd);
+        }
+        if data == libc::MAP_FAILED {
+            vx_log_e!("Can not map the keymap of Wayland keyboard.");
+            return;
+        }
+        let keymap = unsafe {
+            (self.library.xkb_keymap_new_from_string)(
+                self.context,
+                data as *const c_char,
+                KEYMAP_FORMAT_TEXT_V1,
+                KEYMAP_COMPILE_NO_FLAGS,
+            )
+        };
+        unsafe {
+            libc::munmap(data as *mut c_void, size as usize);
+        }
+        if keymap.is_null() {
+            vx_log_e!("Can not compile the keymap of Wayland keyboard.");
+            return;
+        }
+        self.release_keymap();
+        self.keymap = keymap;
+        self.state = unsafe { (self.library.xkb_state_new)(keymap) };
+    }
+
+    pub(super) fn update_modifiers(
+        &mut self,
+        depressed: u32,
+        latched: u32,
+        locked: u32,
+        group: u32,
+    ) {
+        if self.state.is_null() {
+            return;
+        }
+        unsafe {
+            (self.library.xkb_state_update_mask)(
+                self.state, depressed, latched, locked, 0, 0, group,
+            );
+        }
+    }
+
+    pub(super) fn translate(&self, key: u32) -> Keyboard {
+        if self.state.is_null() {
+            return Keyboard::Unknown;
+        }
+        let sym =
+            unsafe { (self.library.xkb_state_key_get_one_sym)(self.state, key + EVDEV_OFFSET) };
+        return keysym_to_keyboard(sym);
+    }
+
+    fn release_keymap(&mut self) {
+        unsafe {
+            if !self.state.is_null() {
+                (self.library.xkb_state_unref)(self.state);
+            }
+            if !self.keymap.is_null() {
+                (self.library.xkb_keymap_unref)(self.keymap);
+            }
+        }
+        self.state = null_mut();
+        self.keymap = null_mut();
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        self.release_keymap();
+        unsafe {
+            (self.library.xkb_context_unref)(self.context);
+        }
+    }
+}
+
+// Keys that exist twice, left and right or main and keypad, are indexed by
+// 0 for the first one and 1 for the second one.
+fn keysym_to_keyboard(sym: u32) -> Keyboard {
+    const LETTERS: [Keyboard; 26] = [
+        Keyboard::A,
+        Keyboard::B,
+        Keyboard::C,
+        Keyboard::D,
+        Keyboard::E,
+        Keyboard::F,
+        Keyboard::G,
+        Keyboard::H,
+        Keyboard::I,
+        Keyboard::J,
+        Keyboard::K,
+        Keyboard::L,
+        Keyboard::M,
+        Keyboard::N,
+        Keyboard::O,
+        Keyboard::P,
+        Keyboard::Q,
+        Keyboard::R,
+        Keyboard::S,
+        Keyboard::T,
+        Keyboard::U,
+        Keyboard::V,
+        Keyboard::W,
+        Keyboard::X,
+        Keyboard::Y,
+        Keyboard::Z,
+    ];
+    match sym {
+        0x61..=0x7a => return LETTERS[(sym - 0x61) as usize].clone(),
+        0x41..=0x5a => return LETTERS[(sym - 0x41) as usize].clone(),
+        0x30..=0x39 => {
+            return Keyboard::Number {
+                number: (sym - 0x30) as u8,
+                padd: false,
+            }
+        }
+        0xffb0..=0xffb9 => {
+            return Keyboard::Number {
+                number: (sym - 0xffb0) as u8,
+                padd: true,
+            }
+        }
+        0xffbe..=0xffc9 => return Keyboard::Function((sym - 0xffbe + 1) as u8),
+        0x20 => return Keyboard::Space(0),
+        0x2c => return Keyboard::Comma,
+        0x2d => return Keyboard::Minus(0),
+        0x2e => return Keyboard::Period(0),
+        0x2f => return Keyboard::Slash(0),
+        0x3b => return Keyboard::SemiColon,
+        0x3d => return Keyboard::Equal,
+        0x27 => return Keyboard::Quotem,
+        0x5b => return Keyboard::SquareBracketLeft,
+        0x5c => return Keyboard::BackSlash(0),
+        0x5d => return Keyboard::SquareBracketRight,
+        0x60 => return Keyboard::BackQuote,
+        0xff08 => return Keyboard::Backspace,
+        0xff09 => return Keyboard::Tab,
+        0xff0d => return Keyboard::Enter(0),
+        0xff13 => return Keyboard::PauseBreak,
+        0xff14 => return Keyboard::ScrollLock,
+        0xff1b => return Keyboard::Escape(0),
+        0xff50 => return Keyboard::Home,
+        0xff51 => return Keyboard::ArrowLeft,
+        0xff52 => return Keyboard::ArrowUp,
+        0xff53 => return Keyboard::ArrowRight,
+        0xff54 => return Keyboard::ArrowDown,
+        0xff55 => return Keyboard::PageUp,
+        0xff56 => return Keyboard::PageDown,
+        0xff57 => return Keyboard::End,
+        0xff61 => return Keyboard::PrintScreen,
+        0xff63 => return Keyboard::Insert,
+        0xff7f => return Keyboard::NumLock,
+        0xff8d => return Keyboard::Enter(1),
+        0xffaa => return Keyboard::Star,
+        0xffab => return Keyboard::Plus(1),
+        0xffad => return Keyboard::Minus(1),
+        0xffae => return Keyboard::Period(1),
+        0xffaf => return Keyboard::Slash(1),
+        0xffe1 => return Keyboard::Shift(0),
+        0xffe2 => return Keyboard::Shift(1),
+        0xffe3 => return Keyboard::Control(0),
+        0xffe4 => return Keyboard::Control(1),
+        0xffe5 => return Keyboard::CapseLock,
+        0xffe9 => return Keyboard::Alt(0),
+        0xffea => return Keyboard::Alt(1),
+        0xffeb => return Keyboard::Super(0),
+        0xffec => return Keyboard::Super(1),
+        0xffff => return Keyboard::Delete,
+        _ => return Keyboard::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keysym_test() {
+        assert!(matches!(keysym_to_keyboard(0x61), Keyboard::A));
+        assert!(matches!(keysym_to_keyboard(0x5a), Keyboard::Z));
+        assert!(matches!(
+            keysym_to_keyboard(0xffb3),
+            Keyboard::Number {
+                number: 3,
+                padd: true
+            }
+        ));
+        assert!(matches!(keysym_to_keyboard(0xffc9), Keyboard::Function(12)));
+        assert!(matches!(keysym_to_keyboard(0xffe2), Keyboard::Shift(1)));
+        assert!(matches!(keysym_to_keyboard(0x1234), Keyboard::Unknown));
+    }
+
+    // Without a keymap from the compositor every key is unknown.
+    #[test]
+    fn no_keymap_test() {
+        if let Some(state) = State::new() {
+            assert!(matches!(state.translate(30), Keyboard::Unknown));
+        }
+    }
+}
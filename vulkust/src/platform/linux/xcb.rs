@@ -66,12 +66,12 @@ pub(super) enum WindowClass {
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(super) enum AtomEnum {
     _None = 0,
-    _Primary = 1,
+    Primary = 1,
     _Secondary = 2,
     _Arc = 3,
-    _Atom = 4,
+    Atom = 4,
     _Bitmap = 5,
-    _Cardinal = 6,
+    Cardinal = 6,
     _Colormap = 7,
     _Cursor = 8,
     _CutBuffer0 = 9,
@@ -105,8 +105,8 @@ pub(super) enum AtomEnum {
     _WmIconName = 37,
     _WmIconSize = 38,
     WmName = 39,
-    _WmNormalHints = 40,
-    _WmSizeHints = 41,
+    WmNormalHints = 40,
+    WmSizeHints = 41,
     _WmZoomHints = 42,
     _MinSpace = 43,
     _NormSpace = 44,
@@ -159,6 +159,10 @@ pub(super) type Atom = u32;
 pub(super) type KeyCode = u8;
 pub(super) type Button = u8;
 pub(super) type TimeStamp = u32;
+pub(super) type GContext = u32;
+pub(super) type Drawable = u32;
+pub(super) type Pixmap = u32;
+pub(super) type Cursor = u32;
 pub(super) type ButtonReleaseEvent = ButtonPressEvent;
 pub(super) type KeyReleaseEvent = KeyPressEvent;
 
@@ -463,7 +467,248 @@ impl Default for QueryPointerReply {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct GrabPointerCookie {
+    pub(super) sequence: c_uint,
+}
+
+impl Default for GrabPointerCookie {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct GrabPointerReply {
+    pub(super) response_type: u8,
+    pub(super) status: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+}
+
+impl Default for GrabPointerReply {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct QueryExtensionReply {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+    pub(super) present: u8,
+    pub(super) major_opcode: u8,
+    pub(super) first_event: u8,
+    pub(super) first_error: u8,
+}
+
+impl Default for QueryExtensionReply {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct GeGenericEvent {
+    pub(super) response_type: u8,
+    pub(super) extension: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+    pub(super) event_type: u16,
+    pub(super) pad0: [u8; 22usize],
+    pub(super) full_sequence: u32,
+}
+
+impl Default for GeGenericEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Rectangle {
+    pub(super) x: i16,
+    pub(super) y: i16,
+    pub(super) width: u16,
+    pub(super) height: u16,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct FocusInEvent {
+    pub(super) response_type: u8,
+    pub(super) detail: u8,
+    pub(super) sequence: u16,
+    pub(super) event: Window,
+    pub(super) mode: u8,
+    pub(super) pad0: [u8; 3usize],
+}
+
+impl Default for FocusInEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct MapNotifyEvent {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) event: Window,
+    pub(super) window: Window,
+    pub(super) override_redirect: u8,
+    pub(super) pad1: [u8; 3usize],
+}
+
+impl Default for MapNotifyEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct PropertyNotifyEvent {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) window: Window,
+    pub(super) atom: Atom,
+    pub(super) time: TimeStamp,
+    pub(super) state: u8,
+    pub(super) pad1: [u8; 3usize],
+}
+
+impl Default for PropertyNotifyEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct GetPropertyCookie {
+    pub(super) sequence: c_uint,
+}
+
+impl Default for GetPropertyCookie {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct GetPropertyReply {
+    pub(super) response_type: u8,
+    pub(super) format: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+    pub(super) type_: Atom,
+    pub(super) bytes_after: u32,
+    pub(super) value_len: u32,
+    pub(super) pad0: [u8; 12usize],
+}
+
+impl Default for GetPropertyReply {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct SelectionClearEvent {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) time: TimeStamp,
+    pub(super) owner: Window,
+    pub(super) selection: Atom,
+}
+
+impl Default for SelectionClearEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct SelectionRequestEvent {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) time: TimeStamp,
+    pub(super) owner: Window,
+    pub(super) requestor: Window,
+    pub(super) selection: Atom,
+    pub(super) target: Atom,
+    pub(super) property: Atom,
+}
+
+impl Default for SelectionRequestEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct SelectionNotifyEvent {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) time: TimeStamp,
+    pub(super) requestor: Window,
+    pub(super) selection: Atom,
+    pub(super) target: Atom,
+    pub(super) property: Atom,
+}
+
+impl Default for SelectionNotifyEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+// Extension identifier, like xcb_input_id of libxcb-xinput.
+#[repr(C)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Extension {
+    pub(super) name: *const c_char,
+    pub(super) global_id: c_int,
+}
+
+pub(super) const NONE: u32 = 0;
+pub(super) const CURRENT_TIME: TimeStamp = 0;
+pub(super) const GRAB_MODE_ASYNC: u8 = 1;
+pub(super) const GRAB_STATUS_SUCCESS: u8 = 0;
+pub(super) const GC_FOREGROUND: u32 = 4;
 pub(super) const COPY_FROM_PARENT: u64 = 0;
+pub(super) const IMAGE_FORMAT_Z_PIXMAP: u8 = 2;
 
 #[link(name = "xcb", kind = "dylib")]
 extern "C" {
@@ -509,6 +754,7 @@ extern "C" {
         data: *const c_void,
     ) -> VoidCookie;
     pub(super) fn xcb_map_window(c: *mut Connection, window: Window) -> VoidCookie;
+    pub(super) fn xcb_destroy_window(c: *mut Connection, window: Window) -> VoidCookie;
     pub(super) fn xcb_flush(c: *mut Connection) -> c_int;
     pub(super) fn xcb_poll_for_event(c: *mut Connection) -> *mut GenericEvent;
     pub(super) fn xcb_query_pointer(c: *mut Connection, window: Window) -> QueryPointerCookie;
@@ -517,4 +763,122 @@ extern "C" {
         cookie: QueryPointerCookie,
         e: *mut *mut GenericError,
     ) -> *mut QueryPointerReply;
+    pub(super) fn xcb_create_gc(
+        c: *mut Connection,
+        cid: GContext,
+        drawable: Drawable,
+        value_mask: u32,
+        value_list: *const u32,
+    ) -> VoidCookie;
+    pub(super) fn xcb_free_gc(c: *mut Connection, gc: GContext) -> VoidCookie;
+    pub(super) fn xcb_put_image(
+        c: *mut Connection,
+        format: u8,
+        drawable: Drawable,
+        gc: GContext,
+        width: u16,
+        height: u16,
+        dst_x: i16,
+        dst_y: i16,
+        left_pad: u8,
+        depth: u8,
+        data_len: u32,
+        data: *const u8,
+    ) -> VoidCookie;
+    pub(super) fn xcb_get_maximum_request_length(c: *mut Connection) -> u32;
+    pub(super) fn xcb_send_event(
+        c: *mut Connection,
+        propagate: u8,
+        destination: Window,
+        event_mask: u32,
+        event: *const c_char,
+    ) -> VoidCookie;
+    pub(super) fn xcb_change_window_attributes(
+        c: *mut Connection,
+        window: Window,
+        value_mask: u32,
+        value_list: *const u32,
+    ) -> VoidCookie;
+    pub(super) fn xcb_create_pixmap(
+        c: *mut Connection,
+        depth: u8,
+        pid: Pixmap,
+        drawable: Drawable,
+        width: u16,
+        height: u16,
+    ) -> VoidCookie;
+    pub(super) fn xcb_free_pixmap(c: *mut Connection, pixmap: Pixmap) -> VoidCookie;
+    pub(super) fn xcb_poly_fill_rectangle(
+        c: *mut Connection,
+        drawable: Drawable,
+        gc: GContext,
+        rectangles_len: u32,
+        rectangles: *const Rectangle,
+    ) -> VoidCookie;
+    pub(super) fn xcb_create_cursor(
+        c: *mut Connection,
+        cid: Cursor,
+        source: Pixmap,
+        mask: Pixmap,
+        fore_red: u16,
+        fore_green: u16,
+        fore_blue: u16,
+        back_red: u16,
+        back_green: u16,
+        back_blue: u16,
+        x: u16,
+        y: u16,
+    ) -> VoidCookie;
+    pub(super) fn xcb_free_cursor(c: *mut Connection, cursor: Cursor) -> VoidCookie;
+    pub(super) fn xcb_grab_pointer(
+        c: *mut Connection,
+        owner_events: u8,
+        grab_window: Window,
+        event_mask: u16,
+        pointer_mode: u8,
+        keyboard_mode: u8,
+        confine_to: Window,
+        cursor: Cursor,
+        time: TimeStamp,
+    ) -> GrabPointerCookie;
+    pub(super) fn xcb_grab_pointer_reply(
+        c: *mut Connection,
+        cookie: GrabPointerCookie,
+        e: *mut *mut GenericError,
+    ) -> *mut GrabPointerReply;
+    pub(super) fn xcb_ungrab_pointer(c: *mut Connection, time: TimeStamp) -> VoidCookie;
+    pub(super) fn xcb_get_property(
+        c: *mut Connection,
+        delete: u8,
+        window: Window,
+        property: Atom,
+        type_: Atom,
+        long_offset: u32,
+        long_length: u32,
+    ) -> GetPropertyCookie;
+    pub(super) fn xcb_get_property_reply(
+        c: *mut Connection,
+        cookie: GetPropertyCookie,
+        e: *mut *mut GenericError,
+    ) -> *mut GetPropertyReply;
+    pub(super) fn xcb_get_property_value(r: *const GetPropertyReply) -> *mut c_void;
+    pub(super) fn xcb_get_property_value_length(r: *const GetPropertyReply) -> c_int;
+    pub(super) fn xcb_set_selection_owner(
+        c: *mut Connection,
+        owner: Window,
+        selection: Atom,
+        time: TimeStamp,
+    ) -> VoidCookie;
+    pub(super) fn xcb_convert_selection(
+        c: *mut Connection,
+        requestor: Window,
+        selection: Atom,
+        target: Atom,
+        property: Atom,
+        time: TimeStamp,
+    ) -> VoidCookie;
+    pub(super) fn xcb_get_extension_data(
+        c: *mut Connection,
+        ext: *const Extension,
+    ) -> *const QueryExtensionReply;
 }
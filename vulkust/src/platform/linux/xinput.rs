@@ -0,0 +1,164 @@
+use super::super::super::libc;
+use super::xcb::{
+    xcb_get_extension_data, Connection, Extension, GeGenericEvent, GenericError, VoidCookie, Window,
+};
+use std::mem::zeroed;
+use std::os::raw::c_uint;
+use std::ptr::null_mut;
+
+// Hand written bindings of the few XInput2 requests of libxcb-xinput that
+// are needed for the raw (unaccelerated and unbounded) mouse motion.
+
+pub(super) const ALL_MASTER_DEVICES: u16 = 1;
+pub(super) const RAW_MOTION: u16 = 17;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct XiQueryVersionCookie {
+    pub(super) sequence: c_uint,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct XiQueryVersionReply {
+    pub(super) response_type: u8,
+    pub(super) pad0: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+    pub(super) major_version: u16,
+    pub(super) minor_version: u16,
+    pub(super) pad1: [u8; 20usize],
+}
+
+// It is the xcb_input_event_mask_t header followed by its only mask word.
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct EventMask {
+    pub(super) deviceid: u16,
+    pub(super) mask_len: u16,
+    pub(super) mask: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct RawMotionEvent {
+    pub(super) response_type: u8,
+    pub(super) extension: u8,
+    pub(super) sequence: u16,
+    pub(super) length: u32,
+    pub(super) event_type: u16,
+    pub(super) deviceid: u16,
+    pub(super) time: u32,
+    pub(super) detail: u32,
+    pub(super) sourceid: u16,
+    pub(super) valuators_len: u16,
+    pub(super) flags: u32,
+    pub(super) pad0: [u8; 4usize],
+    pub(super) full_sequence: u32,
+}
+
+impl Default for RawMotionEvent {
+    fn default() -> Self {
+        unsafe { zeroed() }
+    }
+}
+
+#[link(name = "xcb-xinput", kind = "dylib")]
+extern "C" {
+    static xcb_input_id: Extension;
+
+    fn xcb_input_xi_query_version(
+        c: *mut Connection,
+        major_version: u16,
+        minor_version: u16,
+    ) -> XiQueryVersionCookie;
+    fn xcb_input_xi_query_version_reply(
+        c: *mut Connection,
+        cookie: XiQueryVersionCookie,
+        e: *mut *mut GenericError,
+    ) -> *mut XiQueryVersionReply;
+    fn xcb_input_xi_select_events(
+        c: *mut Connection,
+        window: Window,
+        num_mask: u16,
+        masks: *const EventMask,
+    ) -> VoidCookie;
+}
+
+// It returns the major opcode of the extension, the generic events that have
+// it are XInput events. XInput2 must be negotiated before any selection.
+pub(super) fn initialize(connection: *mut Connection) -> Option<u8> {
+    let extension = unsafe { xcb_get_extension_data(connection, &xcb_input_id) };
+    if extension.is_null() || unsafe { (*extension).present } == 0 {
+        vx_log_e!("X server does not have XInput extension.");
+        return None;
+    }
+    let opcode = unsafe { (*extension).major_opcode };
+    let reply = unsafe {
+        let cookie = xcb_input_xi_query_version(connection, 2, 0);
+        xcb_input_xi_query_version_reply(connection, cookie, null_mut())
+    };
+    if reply.is_null() {
+        vx_log_e!("XInput version query failed.");
+        return None;
+    }
+    let major_version = unsafe { (*reply).major_version };
+    unsafe {
+        libc::free(reply as *mut libc::c_void);
+    }
+    if major_version < 2 {
+        vx_log_e!("X server does not have XInput2.");
+        return None;
+    }
+    return Some(opcode);
+}
+
+// Raw events are only delivered to the root window.
+pub(super) fn select_raw_motion(connection: *mut Connection, root: Window, enabled: bool) {
+    let mask = EventMask {
+        deviceid: ALL_MASTER_DEVICES,
+        mask_len: 1,
+        mask: if enabled { 1 << RAW_MOTION } else { 0 },
+    };
+    unsafe {
+        xcb_input_xi_select_events(connection, root, 1, &mask);
+    }
+}
+
+pub(super) fn is_raw_motion(e: *const GeGenericEvent, opcode: u8) -> bool {
+    return unsafe { (*e).extension == opcode && (*e).event_type == RAW_MOTION };
+}
+
+// It returns the raw delta of the first two axes, that are x and y of the
+// mouse. After the valuator masks there are the accelerated values and then
+// the raw ones, both are 32.32 fixed point for each set bit of the masks.
+pub(super) fn get_raw_motion_delta(e: *const RawMotionEvent) -> (f64, f64) {
+    let mut delta = [0f64; 2];
+    unsafe {
+        let masks_count = (*e).valuators_len as usize;
+        let masks = (e as *const u8).add(std::mem::size_of::<RawMotionEvent>()) as *const u32;
+        let masks = std::slice::from_raw_parts(masks, masks_count);
+        let values_count: usize = masks.iter().map(|m| m.count_ones() as usize).sum();
+        let raw_values = (masks.as_ptr().add(masks_count) as *const i32).add(values_count * 2);
+        let mut value_index = 0;
+        for (mask_index, mask) in masks.iter().enumerate() {
+            for bit in 0..32 {
+                if mask & (1 << bit) == 0 {
+                    continue;
+                }
+                let axis = mask_index * 32 + bit;
+                if axis < 2 {
+                    let integral = *raw_values.add(value_index * 2);
+                    let fraction = *(raw_values.add(value_index * 2 + 1) as *const u32);
+                    delta[axis] = integral as f64 + fraction as f64 / 4294967296.0;
+                }
+                value_index += 1;
+            }
+        }
+    }
+    return (delta[0], delta[1]);
+}
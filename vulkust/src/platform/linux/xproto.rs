@@ -4,9 +4,23 @@ pub(super) const KEY_RELEASE: c_uint = 3;
 pub(super) const BUTTON_PRESS: c_uint = 4;
 pub(super) const BUTTON_RELEASE: c_uint = 5;
 pub(super) const MOTION_NOTIFY: c_uint = 6;
+pub(super) const FOCUS_IN: c_uint = 9;
+pub(super) const FOCUS_OUT: c_uint = 10;
 pub(super) const DESTROY_NOTIFY: c_uint = 17;
+pub(super) const UNMAP_NOTIFY: c_uint = 18;
+pub(super) const MAP_NOTIFY: c_uint = 19;
 pub(super) const CONFIGURE_NOTIFY: c_uint = 22;
+pub(super) const PROPERTY_NOTIFY: c_uint = 28;
+pub(super) const SELECTION_CLEAR: c_uint = 29;
+pub(super) const SELECTION_REQUEST: c_uint = 30;
+pub(super) const SELECTION_NOTIFY: c_uint = 31;
 pub(super) const CLIENT_MESSAGE: c_uint = 33;
+pub(super) const GE_GENERIC: c_uint = 35;
+pub(super) const NOTIFY_MODE_GRAB: u8 = 1;
+pub(super) const NOTIFY_MODE_UNGRAB: u8 = 2;
+pub(super) const NOTIFY_DETAIL_POINTER: u8 = 5;
+pub(super) const PROPERTY_NEW_VALUE: u8 = 0;
+pub(super) const PROPERTY_DELETE: u8 = 1;
 pub(super) const KEY_A: u8 = 38;
 // pub(super) const KEY_B: u8 = 48;
 // pub(super) const KEY_C: u8 = 46;
@@ -43,8 +57,9 @@ pub(super) const KEY_F1: u8 = 67;
 // pub(super) const KEY_F8: u8 = 66;
 // pub(super) const KEY_F9: u8 = 67;
 // pub(super) const KEY_F10: u8 = 68;
-// pub(super) const KEY_F11: u8 = 87;
-// pub(super) const KEY_F12: u8 = 88;
+pub(super) const KEY_F11: u8 = 95;
+pub(super) const KEY_F12: u8 = 96;
+pub(super) const KEY_PRINT: u8 = 107;
 
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
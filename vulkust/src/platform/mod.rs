@@ -1,26 +1,31 @@
 #[cfg(target_os = "android")]
 pub mod android;
-#[cfg(target_os = "android")]
+#[cfg(all(target_os = "android", not(headless)))]
 pub use self::android as os;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub mod apple;
+#[cfg(headless)]
+pub mod headless;
+#[cfg(headless)]
+pub use self::headless as os;
 #[cfg(target_os = "ios")]
 pub mod ios;
-#[cfg(target_os = "ios")]
+#[cfg(all(target_os = "ios", not(headless)))]
 pub use self::ios as os;
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(headless)))]
 pub mod linux;
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(headless)))]
 pub use self::linux as os;
 #[cfg(target_os = "macos")]
 pub mod macos;
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", not(headless)))]
 pub use self::macos as os;
 #[cfg(target_os = "windows")]
 pub mod windows;
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(headless)))]
 pub use self::windows as os;
-//pub mod linker;
 //pub mod file;
 pub mod base;
 pub mod config;
+#[cfg(any(target_os = "linux", target_os = "ios", target_os = "macos", target_os = "android"))]
+pub mod linker;
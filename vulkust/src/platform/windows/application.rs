@@ -1,5 +1,8 @@
 use super::super::{
-    super::core::string::string_to_cwstring,
+    super::core::{
+        event::{Button, ButtonAction, Keyboard, Type as EventType},
+        string::string_to_cwstring,
+    },
     base::Base,
     config::{Config, ScreenState},
 };
@@ -273,6 +276,25 @@ impl Application {
                 winapi::um::winuser::ValidateRect(hwnd, null());
             },
             we @ winapi::um::winuser::WM_KEYDOWN | we @ winapi::um::winuser::WM_KEYUP => {
+                let key = w_param as i32;
+                // Windows only sends key-up for PrintScreen.
+                let hotkey = if we == winapi::um::winuser::WM_KEYDOWN
+                    && key == winapi::um::winuser::VK_F12
+                {
+                    Some(Keyboard::Function(12))
+                } else if we == winapi::um::winuser::WM_KEYUP
+                    && key == winapi::um::winuser::VK_SNAPSHOT
+                {
+                    Some(Keyboard::PrintScreen)
+                } else {
+                    None
+                };
+                if let Some(k) = hotkey {
+                    self.base.on_event(&EventType::Button {
+                        button: Button::Keyboard(k),
+                        action: ButtonAction::Press,
+                    });
+                }
                 // let button = match w_param {
                 //     0x41 => event::Button::Keyboard(event::Keyboard::A),
                 //     0x42 => event::Button::Keyboard(event::Keyboard::B),
@@ -0,0 +1,1066 @@
+use super::super::core::types::Real;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+use super::skeleton::Joint;
+use gltf;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Interpolation {
+    Step,
+    Linear,
+    /// Every keyframe has an in-tangent, a value and an out-tangent.
+    CubicSpline,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Property {
+    Translation,
+    /// Quaternions in x, y, z, w order like glTF.
+    Rotation,
+    Scale,
+    /// Morph target weights.
+    Weights,
+}
+
+impl Property {
+    fn get_bit(&self) -> u8 {
+        match self {
+            Property::Translation => return 1,
+            Property::Rotation => return 2,
+            Property::Scale => return 4,
+            Property::Weights => return 8,
+        }
+    }
+}
+
+/// Keyframes of one property of one target.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Channel {
+    target: usize,
+    property: Property,
+    interpolation: Interpolation,
+    components_count: usize,
+    times: Vec<Real>,
+    values: Vec<Real>,
+}
+
+impl Channel {
+    /// Times must be ascending, values have the components of the property
+    /// for every time and cubic splines have three of them per time.
+    pub fn new(
+        target: usize,
+        property: Property,
+        interpolation: Interpolation,
+        times: Vec<Real>,
+        values: Vec<Real>,
+    ) -> Self {
+        let keyframe_values_count = if interpolation == Interpolation::CubicSpline {
+            3
+        } else {
+            1
+        };
+        let components_count = match property {
+            Property::Translation | Property::Scale => 3,
+            Property::Rotation => 4,
+            Property::Weights if times.is_empty() => 0,
+            Property::Weights => values.len() / (times.len() * keyframe_values_count),
+        };
+        if components_count == 0
+            || times.len() * keyframe_values_count * components_count != values.len()
+        {
+            vx_log_f!("Number of the keyframe values does not match the times.");
+        }
+        Self {
+            target,
+            property,
+            interpolation,
+            components_count,
+            times,
+            values,
+        }
+    }
+
+    #[inline]
+    pub fn get_target(&self) -> usize {
+        return self.target;
+    }
+
+    #[inline]
+    pub fn get_property(&self) -> Property {
+        return self.property;
+    }
+
+    #[inline]
+    pub fn get_interpolation(&self) -> Interpolation {
+        return self.interpolation;
+    }
+
+    #[inline]
+    pub fn get_components_count(&self) -> usize {
+        return self.components_count;
+    }
+
+    pub fn get_duration(&self) -> Real {
+        return self.times[self.times.len() - 1];
+    }
+
+    // Part is 0 for the in-tangent, 1 for the value and 2 for the
+    // out-tangent of the cubic splines.
+    fn get_values(&self, keyframe: usize, part: usize) -> &[Real] {
+        let n = self.components_count;
+        let start = if self.interpolation == Interpolation::CubicSpline {
+            (keyframe * 3 + part) * n
+        } else {
+            keyframe * n
+        };
+        return &self.values[start..start + n];
+    }
+
+    /// Result must have the components of the property, the value is clamped
+    /// to the first and the last keyframes outside of them.
+    pub fn sample(&self, time: Real, result: &mut [Real]) {
+        let last = self.times.len() - 1;
+        if time <= self.times[0] {
+            result.copy_from_slice(self.get_values(0, 1));
+            return;
+        }
+        if time >= self.times[last] {
+            result.copy_from_slice(self.get_values(last, 1));
+            return;
+        }
+        // The first keyframe after the time.
+        let next = self.times.partition_point(|t| *t <= time);
+        let previous = next - 1;
+        let t0 = self.times[previous];
+        let delta = self.times[next] - t0;
+        let factor = (time - t0) / delta;
+        match self.interpolation {
+            Interpolation::Step => {
+                result.copy_from_slice(self.get_values(previous, 1));
+            }
+            Interpolation::Linear if self.property == Property::Rotation => {
+                let a = self.get_values(previous, 1);
+                let b = self.get_values(next, 1);
+                let a = Quat::new(a[0], a[1], a[2], a[3]);
+                let b = Quat::new(b[0], b[1], b[2], b[3]);
+                result.copy_from_slice(&a.slerp(b, factor).to_array());
+            }
+            Interpolation::Linear => {
+                let a = self.get_values(previous, 1);
+                let b = self.get_values(next, 1);
+                for i in 0..self.components_count {
+                    result[i] = a[i] + (b[i] - a[i]) * factor;
+                }
+            }
+            Interpolation::CubicSpline => {
+                // Hermite spline of the glTF specification, its tangents are
+                // scaled by the duration of the keyframe.
+                let f2 = factor * factor;
+                let f3 = f2 * factor;
+                let cv0 = 2.0 * f3 - 3.0 * f2 + 1.0;
+                let ct0 = (f3 - 2.0 * f2 + factor) * delta;
+                let cv1 = -2.0 * f3 + 3.0 * f2;
+                let ct1 = (f3 - f2) * delta;
+                let v0 = self.get_values(previous, 1);
+                let b0 = self.get_values(previous, 2);
+                let v1 = self.get_values(next, 1);
+                let a1 = self.get_values(next, 0);
+                for i in 0..self.components_count {
+                    result[i] = cv0 * v0[i] + ct0 * b0[i] + cv1 * v1[i] + ct1 * a1[i];
+                }
+                if self.property == Property::Rotation {
+                    let length = result.iter().map(|c| c * c).sum::<Real>().sqrt();
+                    if length > 0.0 {
+                        for c in result.iter_mut() {
+                            *c /= length;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Local transformation and morph target weights of an animated target.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct TargetPose {
+    pub location: Vec3,
+    pub orientation: Quat,
+    pub scale: Vec3,
+    pub weights: Vec<Real>,
+}
+
+impl TargetPose {
+    pub fn new(location: Vec3, orientation: Quat, scale: Vec3, weights: Vec<Real>) -> Self {
+        Self {
+            location,
+            orientation,
+            scale,
+            weights,
+        }
+    }
+
+    fn copy(&mut self, other: &Self) {
+        self.location = other.location;
+        self.orientation = other.orientation;
+        self.scale = other.scale;
+        self.weights.clear();
+        self.weights.extend_from_slice(&other.weights);
+    }
+}
+
+/// Poses of all of the targets of an animated object. It remembers which
+/// properties have been animated, blending only touches them, so the layers
+/// can animate a part of the targets.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Pose {
+    targets: Vec<TargetPose>,
+    animated: Vec<u8>,
+}
+
+impl Pose {
+    pub fn new(targets: Vec<TargetPose>) -> Self {
+        let animated = vec![0; targets.len()];
+        Self { targets, animated }
+    }
+
+    #[inline]
+    pub fn get_targets(&self) -> &[TargetPose] {
+        return &self.targets;
+    }
+
+    pub fn is_animated(&self, target: usize, property: Property) -> bool {
+        return self.animated[target] & property.get_bit() != 0;
+    }
+
+    /// Brings back the rest pose without reallocation.
+    pub fn reset(&mut self, rest: &Pose) {
+        for (t, r) in self.targets.iter_mut().zip(&rest.targets) {
+            t.copy(r);
+        }
+        for a in &mut self.animated {
+            *a = 0;
+        }
+    }
+
+    pub fn set(&mut self, target: usize, property: Property, values: &[Real]) {
+        let t = &mut self.targets[target];
+        match property {
+            Property::Translation => {
+                t.location = Vec3::new(values[0], values[1], values[2]);
+            }
+            Property::Rotation => {
+                t.orientation = Quat::new(values[0], values[1], values[2], values[3]);
+            }
+            Property::Scale => t.scale = Vec3::new(values[0], values[1], values[2]),
+            Property::Weights => {
+                t.weights.clear();
+                t.weights.extend_from_slice(values);
+            }
+        }
+        self.animated[target] |= property.get_bit();
+    }
+
+    /// Moves the animated properties of the other pose toward it by factor.
+    pub fn blend(&mut self, other: &Pose, factor: Real) {
+        for (i, (t, o)) in self.targets.iter_mut().zip(&other.targets).enumerate() {
+            let bits = other.animated[i];
+            if bits & Property::Translation.get_bit() != 0 {
+                t.location = t.location.lerp(o.location, factor);
+            }
+            if bits & Property::Rotation.get_bit() != 0 {
+                t.orientation = t.orientation.slerp(o.orientation, factor);
+            }
+            if bits & Property::Scale.get_bit() != 0 {
+                t.scale = t.scale.lerp(o.scale, factor);
+            }
+            if bits & Property::Weights.get_bit() != 0 {
+                t.weights.resize(o.weights.len(), 0.0);
+                for (w, ow) in t.weights.iter_mut().zip(&o.weights) {
+                    *w += (ow - *w) * factor;
+                }
+            }
+            self.animated[i] |= bits;
+        }
+    }
+
+    /// Adds the difference of the other pose from the reference pose, scaled
+    /// by factor. Rotations are added in the local space of the targets and
+    /// scales are multiplied.
+    pub fn add(&mut self, other: &Pose, reference: &Pose, factor: Real) {
+        let identity = Quat::identity();
+        for (i, (t, o)) in self.targets.iter_mut().zip(&other.targets).enumerate() {
+            let r = &reference.targets[i];
+            let bits = other.animated[i];
+            if bits & Property::Translation.get_bit() != 0 {
+                t.location += (o.location - r.location) * factor;
+            }
+            if bits & Property::Rotation.get_bit() != 0 {
+                let delta = r.orientation.conjugate() * o.orientation;
+                t.orientation = (t.orientation * identity.slerp(delta, factor)).normalized();
+            }
+            if bits & Property::Scale.get_bit() != 0 {
+                let mut scale = t.scale.to_array();
+                let os = o.scale.to_array();
+                let rs = r.scale.to_array();
+                for c in 0..3 {
+                    if rs[c] != 0.0 {
+                        scale[c] *= 1.0 + (os[c] / rs[c] - 1.0) * factor;
+                    }
+                }
+                t.scale = Vec3::from_array(scale);
+            }
+            if bits & Property::Weights.get_bit() != 0 {
+                if t.weights.len() < o.weights.len() {
+                    t.weights.resize(o.weights.len(), 0.0);
+                }
+                for (j, ow) in o.weights.iter().enumerate() {
+                    let rw = r.weights.get(j).cloned().unwrap_or(0.0);
+                    t.weights[j] += (ow - rw) * factor;
+                }
+            }
+            self.animated[i] |= bits;
+        }
+    }
+}
+
+/// A named set of channels that are played together.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Clip {
+    name: Option<String>,
+    channels: Vec<Channel>,
+    duration: Real,
+}
+
+impl Clip {
+    pub fn new(name: Option<String>, channels: Vec<Channel>) -> Self {
+        let mut duration = 0.0;
+        for c in &channels {
+            duration = c.get_duration().max(duration);
+        }
+        Self {
+            name,
+            channels,
+            duration,
+        }
+    }
+
+    /// Targets maps the glTF node indices to the targets of the channels,
+    /// channels of the other nodes are dropped.
+    pub fn new_with_gltf(
+        animation: &gltf::Animation,
+        targets: &BTreeMap<usize, usize>,
+        data: &[u8],
+    ) -> Self {
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let target = match targets.get(&channel.target().node().index()) {
+                Some(t) => *t,
+                None => continue,
+            };
+            let reader = channel.reader(|_| Some(data));
+            let times: Vec<Real> = vx_unwrap!(reader.read_inputs()).collect();
+            let (property, values): (Property, Vec<Real>) = match vx_unwrap!(reader.read_outputs())
+            {
+                gltf::animation::util::ReadOutputs::Translations(t) => {
+                    (Property::Translation, t.flat_map(|v| v.to_vec()).collect())
+                }
+                gltf::animation::util::ReadOutputs::Rotations(r) => (
+                    Property::Rotation,
+                    r.into_f32().flat_map(|v| v.to_vec()).collect(),
+                ),
+                gltf::animation::util::ReadOutputs::Scales(s) => {
+                    (Property::Scale, s.flat_map(|v| v.to_vec()).collect())
+                }
+                gltf::animation::util::ReadOutputs::MorphTargetWeights(w) => {
+                    (Property::Weights, w.into_f32().collect())
+                }
+            };
+            let interpolation = match channel.sampler().interpolation() {
+                gltf::animation::Interpolation::Step => Interpolation::Step,
+                gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+            };
+            channels.push(Channel::new(target, property, interpolation, times, values));
+        }
+        return Self::new(animation.name().map(|n| n.to_string()), channels);
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> Option<&str> {
+        return self.name.as_ref().map(|n| n.as_str());
+    }
+
+    #[inline]
+    pub fn get_duration(&self) -> Real {
+        return self.duration;
+    }
+
+    #[inline]
+    pub fn get_channels(&self) -> &[Channel] {
+        return &self.channels;
+    }
+
+    /// Sets the animated properties of the pose.
+    pub fn sample(&self, time: Real, pose: &mut Pose) {
+        let mut values = Vec::new();
+        for c in &self.channels {
+            values.resize(c.get_components_count(), 0.0);
+            c.sample(time, &mut values);
+            pose.set(c.get_target(), c.get_property(), &values);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Wrap {
+    /// It stops at the end, or at the start when it plays backward.
+    Once,
+    Loop,
+    /// It goes back and forth.
+    PingPong,
+}
+
+/// Playing state of a clip.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Playback {
+    clip: Arc<Clip>,
+    wrap: Wrap,
+    speed: Real,
+    time: Real,
+}
+
+impl Playback {
+    /// Negative speeds play the clip backward.
+    pub fn new(clip: Arc<Clip>, wrap: Wrap, speed: Real) -> Self {
+        Self {
+            clip,
+            wrap,
+            speed,
+            time: 0.0,
+        }
+    }
+
+    #[inline]
+    pub fn get_clip(&self) -> &Arc<Clip> {
+        return &self.clip;
+    }
+
+    #[inline]
+    pub fn get_wrap(&self) -> Wrap {
+        return self.wrap;
+    }
+
+    pub fn set_speed(&mut self, speed: Real) {
+        self.speed = speed;
+    }
+
+    #[inline]
+    pub fn get_speed(&self) -> Real {
+        return self.speed;
+    }
+
+    pub fn set_time(&mut self, time: Real) {
+        self.time = time;
+        self.clamp_time();
+    }
+
+    /// Time in the clip after wrapping.
+    pub fn get_time(&self) -> Real {
+        let duration = self.clip.get_duration();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.wrap {
+            Wrap::Once => return self.time,
+            Wrap::Loop => return self.time.rem_euclid(duration),
+            Wrap::PingPong => {
+                let t = self.time.rem_euclid(duration * 2.0);
+                return if t > duration { duration * 2.0 - t } else { t };
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        if self.wrap != Wrap::Once {
+            return false;
+        }
+        if self.speed < 0.0 {
+            return self.time <= 0.0;
+        }
+        return self.time >= self.clip.get_duration();
+    }
+
+    pub fn advance(&mut self, delta_time: Real) {
+        self.time += delta_time * self.speed;
+        self.clamp_time();
+    }
+
+    fn clamp_time(&mut self) {
+        if self.wrap == Wrap::Once {
+            self.time = self.time.max(0.0).min(self.clip.get_duration());
+        }
+    }
+
+    pub fn sample(&self, pose: &mut Pose) {
+        self.clip.sample(self.get_time(), pose);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Blending {
+    /// The layer replaces the animated properties of the layers below it.
+    Override,
+    /// The layer adds the difference of its clip from the first keyframes of
+    /// the clip.
+    Additive,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Layer {
+    blending: Blending,
+    weight: Real,
+    current: Option<(Playback, Pose)>,
+    // The fading out playback and its reference pose.
+    previous: Option<(Playback, Pose)>,
+    fade_time: Real,
+    fade_duration: Real,
+}
+
+impl Layer {
+    fn new(blending: Blending, weight: Real) -> Self {
+        Self {
+            blending,
+            weight,
+            current: None,
+            previous: None,
+            fade_time: 0.0,
+            fade_duration: 0.0,
+        }
+    }
+
+    fn get_fade_factor(&self) -> Real {
+        if self.previous.is_none() || self.fade_duration <= 0.0 {
+            return 1.0;
+        }
+        return (self.fade_time / self.fade_duration).min(1.0);
+    }
+}
+
+/// Plays the clips of an object in layers. The first layer overrides the rest
+/// pose and the next ones are applied in their order.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Animator {
+    clips: Vec<Arc<Clip>>,
+    rest: Pose,
+    layers: Vec<Layer>,
+    pose: Pose,
+    layer_pose: Pose,
+    fading_pose: Pose,
+    is_changed: bool,
+    // glTF node index to the target of the pose.
+    gltf_targets: BTreeMap<usize, usize>,
+}
+
+impl Animator {
+    pub fn new(rest: Pose) -> Self {
+        Self {
+            clips: Vec::new(),
+            pose: rest.clone(),
+            layer_pose: rest.clone(),
+            fading_pose: rest.clone(),
+            rest,
+            layers: vec![Layer::new(Blending::Override, 1.0)],
+            is_changed: false,
+            gltf_targets: BTreeMap::new(),
+        }
+    }
+
+    /// The first target is the node and the next ones are the joints of its
+    /// skin, joint nodes maps the glTF node indices to the joints.
+    pub fn new_with_gltf(
+        node: &gltf::Node,
+        joint_nodes: &BTreeMap<usize, usize>,
+        joints: &[Joint],
+    ) -> Self {
+        let (location, orientation, scale) = node.transform().decomposed();
+        let weights = match node.weights() {
+            Some(w) => w.to_vec(),
+            None => node
+                .mesh()
+                .and_then(|m| m.weights().map(|w| w.to_vec()))
+                .unwrap_or_default(),
+        };
+        let mut targets = vec![TargetPose::new(
+            Vec3::from_array(location),
+            Quat::new(
+                orientation[0],
+                orientation[1],
+                orientation[2],
+                orientation[3],
+            ),
+            Vec3::from_array(scale),
+            weights,
+        )];
+        for j in joints {
+            targets.push(TargetPose::new(
+                j.location,
+                j.orientation,
+                j.scale,
+                Vec::new(),
+            ));
+        }
+        let mut gltf_targets = BTreeMap::new();
+        gltf_targets.insert(node.index(), 0);
+        for (node_index, joint) in joint_nodes {
+            gltf_targets.insert(*node_index, joint + 1);
+        }
+        let mut myself = Self::new(Pose::new(targets));
+        myself.gltf_targets = gltf_targets;
+        return myself;
+    }
+
+    /// Adds the animations of the document that move the targets, clips of
+    /// the other nodes are dropped.
+    pub fn load_gltf_clips(&mut self, document: &gltf::Document, data: &[u8]) {
+        for animation in document.animations() {
+            let clip = Clip::new_with_gltf(&animation, &self.gltf_targets, data);
+            if clip.get_channels().is_empty() {
+                continue;
+            }
+            self.add_clip(Arc::new(clip));
+        }
+    }
+
+    pub fn add_clip(&mut self, clip: Arc<Clip>) {
+        self.clips.push(clip);
+    }
+
+    #[inline]
+    pub fn get_clips(&self) -> &[Arc<Clip>] {
+        return &self.clips;
+    }
+
+    pub fn find_clip(&self, name: &str) -> Option<&Arc<Clip>> {
+        return self.clips.iter().find(|c| c.get_name() == Some(name));
+    }
+
+    #[inline]
+    pub fn get_rest_pose(&self) -> &Pose {
+        return &self.rest;
+    }
+
+    /// The pose of the last evaluation.
+    #[inline]
+    pub fn get_pose(&self) -> &Pose {
+        return &self.pose;
+    }
+
+    pub fn add_layer(&mut self, blending: Blending, weight: Real) -> usize {
+        self.layers.push(Layer::new(blending, weight));
+        return self.layers.len() - 1;
+    }
+
+    pub fn set_layer_weight(&mut self, layer: usize, weight: Real) {
+        self.layers[layer].weight = weight;
+        self.is_changed = true;
+    }
+
+    #[inline]
+    pub fn get_layer_weight(&self, layer: usize) -> Real {
+        return self.layers[layer].weight;
+    }
+
+    // Additive layers need the first keyframes of their clips.
+    fn make_reference(&self, playback: &Playback) -> Pose {
+        let mut reference = self.rest.clone();
+        playback.get_clip().sample(0.0, &mut reference);
+        return reference;
+    }
+
+    /// Replaces the playback of the layer at once.
+    pub fn play(&mut self, layer: usize, playback: Playback) {
+        let reference = self.make_reference(&playback);
+        let layer = &mut self.layers[layer];
+        layer.current = Some((playback, reference));
+        layer.previous = None;
+        self.is_changed = true;
+    }
+
+    /// The current playback of the layer fades out while the new one fades
+    /// in during the duration.
+    pub fn cross_fade(&mut self, layer: usize, playback: Playback, duration: Real) {
+        let reference = self.make_reference(&playback);
+        let layer = &mut self.layers[layer];
+        layer.previous = layer.current.take();
+        layer.current = Some((playback, reference));
+        layer.fade_time = 0.0;
+        layer.fade_duration = duration;
+        self.is_changed = true;
+    }
+
+    /// The layer stops affecting the pose.
+    pub fn stop(&mut self, layer: usize) {
+        let layer = &mut self.layers[layer];
+        layer.current = None;
+        layer.previous = None;
+        self.is_changed = true;
+    }
+
+    pub fn get_playback(&self, layer: usize) -> Option<&Playback> {
+        return self.layers[layer].current.as_ref().map(|c| &c.0);
+    }
+
+    pub fn get_mut_playback(&mut self, layer: usize) -> Option<&mut Playback> {
+        self.is_changed = true;
+        return self.layers[layer].current.as_mut().map(|c| &mut c.0);
+    }
+
+    /// True when the pose must be evaluated again.
+    #[inline]
+    pub fn is_changed(&self) -> bool {
+        return self.is_changed;
+    }
+
+    /// True when any of the layers is changing the pose.
+    pub fn is_playing(&self) -> bool {
+        for l in &self.layers {
+            if l.previous.is_some() {
+                return true;
+            }
+            if let Some((p, _)) = &l.current {
+                if !p.is_finished() {
+                    return true;
+                }
+            }
+        }
+        return false;
+    }
+
+    pub fn advance(&mut self, delta_time: Real) {
+        for l in &mut self.layers {
+            if let Some((p, _)) = &mut l.current {
+                self.is_changed |= !p.is_finished() || l.previous.is_some();
+                p.advance(delta_time);
+            }
+            if let Some((p, _)) = &mut l.previous {
+                p.advance(delta_time);
+                l.fade_time += delta_time;
+                if l.fade_time >= l.fade_duration {
+                    l.previous = None;
+                }
+            }
+        }
+    }
+
+    pub fn evaluate(&mut self) -> &Pose {
+        self.is_changed = false;
+        self.pose.reset(&self.rest);
+        for l in &self.layers {
+            let (current, reference) = match &l.current {
+                Some(c) => c,
+                None => continue,
+            };
+            if l.weight <= 0.0 {
+                continue;
+            }
+            let fade = l.get_fade_factor();
+            match l.blending {
+                Blending::Override => {
+                    self.layer_pose.reset(&self.rest);
+                    if let Some((previous, _)) = &l.previous {
+                        previous.sample(&mut self.layer_pose);
+                        self.fading_pose.reset(&self.rest);
+                        current.sample(&mut self.fading_pose);
+                        self.layer_pose.blend(&self.fading_pose, fade);
+                    } else {
+                        current.sample(&mut self.layer_pose);
+                    }
+                    self.pose.blend(&self.layer_pose, l.weight);
+                }
+                Blending::Additive => {
+                    if let Some((previous, previous_reference)) = &l.previous {
+                        self.layer_pose.reset(&self.rest);
+                        previous.sample(&mut self.layer_pose);
+                        self.pose.add(
+                            &self.layer_pose,
+                            previous_reference,
+                            l.weight * (1.0 - fade),
+                        );
+                    }
+                    self.layer_pose.reset(&self.rest);
+                    current.sample(&mut self.layer_pose);
+                    self.pose.add(&self.layer_pose, reference, l.weight * fade);
+                }
+            }
+        }
+        return &self.pose;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::math::matrix::Mat4;
+    use super::*;
+
+    fn sample(c: &Channel, time: Real) -> Vec<Real> {
+        let mut result = vec![0.0; c.get_components_count()];
+        c.sample(time, &mut result);
+        return result;
+    }
+
+    fn new_rest() -> Pose {
+        return Pose::new(vec![TargetPose::new(
+            Vec3::zero(),
+            Quat::identity(),
+            Vec3::splat(1.0),
+            vec![0.0],
+        )]);
+    }
+
+    // It moves along x from zero to one in one second.
+    fn new_move_clip(name: &str, y: Real) -> Arc<Clip> {
+        return Arc::new(Clip::new(
+            Some(name.to_string()),
+            vec![Channel::new(
+                0,
+                Property::Translation,
+                Interpolation::Linear,
+                vec![0.0, 1.0],
+                vec![0.0, y, 0.0, 1.0, y, 0.0],
+            )],
+        ));
+    }
+
+    #[test]
+    fn channel_test() {
+        let c = Channel::new(
+            0,
+            Property::Translation,
+            Interpolation::Linear,
+            vec![1.0, 2.0, 4.0],
+            vec![0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0],
+        );
+        assert_eq!(c.get_duration(), 4.0);
+        assert_eq!(sample(&c, 0.0), vec![0.0, 0.0, 0.0]);
+        assert_eq!(sample(&c, 1.5), vec![0.5, 1.0, 1.5]);
+        assert_eq!(sample(&c, 2.0), vec![1.0, 2.0, 3.0]);
+        assert_eq!(sample(&c, 3.0), vec![2.0, 2.0, 2.0]);
+        assert_eq!(sample(&c, 5.0), vec![3.0, 2.0, 1.0]);
+        let c = Channel::new(
+            0,
+            Property::Scale,
+            Interpolation::Step,
+            vec![0.0, 1.0],
+            vec![1.0, 1.0, 1.0, 2.0, 2.0, 2.0],
+        );
+        assert_eq!(sample(&c, 0.99), vec![1.0, 1.0, 1.0]);
+        assert_eq!(sample(&c, 1.0), vec![2.0, 2.0, 2.0]);
+        // A quarter turn around z in two keyframes, its negated end must not
+        // make it take the long way.
+        let h = (0.5 as Real).sqrt();
+        let c = Channel::new(
+            0,
+            Property::Rotation,
+            Interpolation::Linear,
+            vec![0.0, 1.0],
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -h, -h],
+        );
+        let v = sample(&c, 0.5);
+        let expected = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_4);
+        let q = Quat::new(v[0], v[1], v[2], v[3]);
+        assert!(q.dot(expected).abs() > 0.99999);
+        // Two weights from zero to one, the first keyframe leaves with the
+        // slope of one and the second one arrives flat.
+        let c = Channel::new(
+            0,
+            Property::Weights,
+            Interpolation::CubicSpline,
+            vec![0.0, 2.0],
+            vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0],
+        );
+        assert_eq!(c.get_components_count(), 2);
+        assert_eq!(sample(&c, 0.0), vec![0.0, 0.0]);
+        assert_eq!(sample(&c, 1.0), vec![0.75, 0.75]);
+        assert_eq!(sample(&c, 2.0), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn playback_test() {
+        let clip = new_move_clip("move", 0.0);
+        let mut p = Playback::new(clip.clone(), Wrap::Once, 2.0);
+        p.advance(0.25);
+        assert_eq!(p.get_time(), 0.5);
+        p.advance(1.0);
+        assert_eq!(p.get_time(), 1.0);
+        assert!(p.is_finished());
+        let mut p = Playback::new(clip.clone(), Wrap::Loop, 1.0);
+        p.advance(1.25);
+        assert_eq!(p.get_time(), 0.25);
+        p.set_speed(-1.0);
+        p.advance(0.5);
+        assert_eq!(p.get_time(), 0.75);
+        assert!(!p.is_finished());
+        let mut p = Playback::new(clip, Wrap::PingPong, 1.0);
+        p.advance(1.25);
+        assert_eq!(p.get_time(), 0.75);
+        p.advance(1.0);
+        assert_eq!(p.get_time(), 0.25);
+    }
+
+    #[test]
+    fn animator_test() {
+        let walk = new_move_clip("walk", 0.0);
+        let run = new_move_clip("run", 2.0);
+        let mut animator = Animator::new(new_rest());
+        animator.add_clip(walk.clone());
+        animator.add_clip(run.clone());
+        assert!(Arc::ptr_eq(vx_unwrap!(animator.find_clip("run")), &run));
+        animator.play(0, Playback::new(walk, Wrap::Once, 1.0));
+        animator.advance(0.5);
+        assert!(animator.is_playing());
+        assert!(animator.is_changed());
+        let l = animator.evaluate().get_targets()[0].location;
+        assert!(!animator.is_changed());
+        assert!(l == Vec3::new(0.5, 0.0, 0.0));
+        // Half way of the cross-fade is half way between the clips.
+        animator.cross_fade(0, Playback::new(run, Wrap::Once, 1.0), 1.0);
+        animator.advance(0.5);
+        let l = animator.evaluate().get_targets()[0].location;
+        assert!((l - Vec3::new(0.75, 1.0, 0.0)).length() < 1e-5);
+        animator.advance(0.5);
+        let l = animator.evaluate().get_targets()[0].location;
+        assert!((l - Vec3::new(1.0, 2.0, 0.0)).length() < 1e-5);
+        // The additive layer only adds its move from its first keyframe.
+        let nod = Arc::new(Clip::new(
+            None,
+            vec![Channel::new(
+                0,
+                Property::Rotation,
+                Interpolation::Linear,
+                vec![0.0, 1.0],
+                vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+            )],
+        ));
+        let layer = animator.add_layer(Blending::Additive, 0.5);
+        animator.play(layer, Playback::new(nod, Wrap::Once, 1.0));
+        animator.advance(1.0);
+        let pose = animator.evaluate();
+        assert!(pose.is_animated(0, Property::Rotation));
+        assert!(!pose.is_animated(0, Property::Scale));
+        let t = &pose.get_targets()[0];
+        assert!((t.location - Vec3::new(1.0, 2.0, 0.0)).length() < 1e-5);
+        let expected = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2);
+        assert!(t.orientation.dot(expected).abs() > 0.99999);
+        assert!(!animator.is_playing());
+        animator.advance(1.0);
+        assert!(!animator.is_changed());
+        animator.stop(0);
+        animator.stop(layer);
+        assert!(animator.is_changed());
+    }
+
+    #[test]
+    fn gltf_test() {
+        // The node moves along x and the second joint of its skin, node 2,
+        // turns around z, node 3 is not a target of the animator.
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [
+                {"translation": [0.0, 0.0, 3.0]},
+                {"children": [2]},
+                {"translation": [1.0, 0.0, 0.0]},
+                {}
+            ],
+            "animations": [
+                {
+                    "name": "walk",
+                    "channels": [
+                        {"sampler": 0, "target": {"node": 0, "path": "translation"}},
+                        {"sampler": 1, "target": {"node": 2, "path": "rotation"}},
+                        {"sampler": 0, "target": {"node": 3, "path": "translation"}}
+                    ],
+                    "samplers": [
+                        {"input": 0, "output": 1},
+                        {"input": 0, "output": 2, "interpolation": "STEP"}
+                    ]
+                },
+                {
+                    "name": "other",
+                    "channels": [
+                        {"sampler": 0, "target": {"node": 3, "path": "translation"}}
+                    ],
+                    "samplers": [{"input": 0, "output": 1}]
+                }
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0.0], "max": [1.0]},
+                {"bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3"},
+                {"bufferView": 2, "componentType": 5126, "count": 2, "type": "VEC4"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 8, "byteLength": 24},
+                {"buffer": 0, "byteOffset": 32, "byteLength": 32}
+            ],
+            "buffers": [{"byteLength": 64}]
+        }"#;
+        let half_turn = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f32::consts::PI);
+        let mut values = vec![0.0, 1.0, 0.0, 0.0, 3.0, 2.0, 0.0, 3.0];
+        values.extend_from_slice(&Quat::identity().to_array());
+        values.extend_from_slice(&half_turn.to_array());
+        let mut data = Vec::new();
+        for v in &values {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let node = vx_unwrap!(document.nodes().next());
+        let joint = |location: Vec3| Joint {
+            name: None,
+            parent: None,
+            location,
+            orientation: Quat::identity(),
+            scale: Vec3::splat(1.0),
+            inverse_bind: Mat4::identity(),
+        };
+        let joints = [joint(Vec3::new(1.0, 0.0, 0.0)), joint(Vec3::zero())];
+        let mut joint_nodes = BTreeMap::new();
+        joint_nodes.insert(2, 0);
+        joint_nodes.insert(1, 1);
+        let mut animator = Animator::new_with_gltf(&node, &joint_nodes, &joints);
+        let rest = animator.get_rest_pose().get_targets();
+        assert_eq!(rest.len(), 3);
+        assert!(rest[0].location == Vec3::new(0.0, 0.0, 3.0));
+        assert!(rest[1].location == Vec3::new(1.0, 0.0, 0.0));
+        animator.load_gltf_clips(&document, &data);
+        assert_eq!(animator.get_clips().len(), 1);
+        let clip = Arc::clone(vx_unwrap!(animator.find_clip("walk")));
+        assert_eq!(clip.get_channels().len(), 2);
+        assert_eq!(clip.get_channels()[0].get_target(), 0);
+        assert_eq!(clip.get_channels()[1].get_target(), 1);
+        assert!(clip.get_channels()[1].get_interpolation() == Interpolation::Step);
+        animator.play(0, Playback::new(clip, Wrap::Once, 1.0));
+        animator.advance(0.5);
+        let pose = animator.evaluate();
+        assert!((pose.get_targets()[0].location - Vec3::new(1.0, 0.0, 3.0)).length() < 1e-5);
+        assert!(pose.get_targets()[1].orientation.dot(Quat::identity()) > 0.9999);
+        // The joint without any channel keeps its rest pose.
+        assert!(!pose.is_animated(2, Property::Rotation));
+        animator.advance(0.5);
+        let pose = animator.evaluate();
+        assert!((pose.get_targets()[0].location - Vec3::new(2.0, 0.0, 3.0)).length() < 1e-5);
+        assert!(pose.get_targets()[1].orientation.dot(half_turn).abs() > 0.9999);
+    }
+}
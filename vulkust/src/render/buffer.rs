@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::buffer::*;
 pub(crate) use super::super::d3d12::buffer::*;
 #[cfg(metal_api)]
 pub(crate) use super::super::metal::buffer::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::buffer::*;
 #[cfg(vulkan_api)]
 pub(crate) use super::super::vulkan::buffer::*;
@@ -1,59 +1,78 @@
-use super::super::collision::aabb::Aabb3;
-use super::super::collision::frustum::Frustum;
-use super::super::collision::plane::Plane;
-use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
+// use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
 use super::super::core::object::Object as CoreObject;
 use super::super::core::types::{Id, Real};
+use super::super::math::frustum::Frustum;
+use super::super::math::matrix::Mat4;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::{Vec3, Vec4};
 use super::engine::Engine;
+use super::gltf_node::{
+    get_fov_x, read_transform as read_gltf_transform, Camera as GltfCamera,
+    Projection as GltfProjection,
+};
+use super::lod::get_screen_size as get_lod_screen_size;
 use super::object::{Base as ObjectBase, Loadable, Object, Transferable};
-use cgmath;
-use cgmath::prelude::InnerSpace;
 use gltf;
 use std::collections::BTreeMap;
-use std::convert::From;
 use std::sync::{Arc, RwLock, Weak};
 
+// It maps the clip space of the projections to the texture space.
+const UNIFORM_CORRECTION: [Real; 16] = [
+    0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
+];
+
+// Projections of the right handed view space to the clip space of Vulkan, y is
+// down and 0 <= z <= w. Near and far are positive distances.
+fn new_perspective(fovy: Real, aspect_ratio: Real, near: Real, far: Real) -> Mat4 {
+    let f = 1.0 / (fovy * 0.5).tan();
+    let d = 1.0 / (near - far);
+    return Mat4::from_cols(
+        Vec4::new(f / aspect_ratio, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, -f, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, far * d, -1.0),
+        Vec4::new(0.0, 0.0, near * far * d, 0.0),
+    );
+}
+
+fn new_orthographic(width: Real, height: Real, near: Real, far: Real) -> Mat4 {
+    let d = 1.0 / (far - near);
+    return Mat4::from_cols(
+        Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, -2.0 / height, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, -d, 0.0),
+        Vec4::new(0.0, 0.0, -near * d, 1.0),
+    );
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Uniform {
-    x: cgmath::Vector4<Real>,
-    y: cgmath::Vector4<Real>,
-    z: cgmath::Vector4<Real>,
-    position_far: cgmath::Vector4<Real>, // far is negative
-    near_aspect_ratio_reserved: cgmath::Vector4<Real>, // near is negative
-    inversed_rotation: cgmath::Matrix4<Real>,
-    view: cgmath::Matrix4<Real>,
-    projection: cgmath::Matrix4<Real>,
-    uniform_projection: cgmath::Matrix4<Real>,
-    view_projection: cgmath::Matrix4<Real>,
-    uniform_view_projection: cgmath::Matrix4<Real>,
+    x: Vec4,
+    y: Vec4,
+    z: Vec4,
+    position_far: Vec4,               // far is negative
+    near_aspect_ratio_reserved: Vec4, // near is negative
+    inversed_rotation: Mat4,
+    view: Mat4,
+    projection: Mat4,
+    uniform_projection: Mat4,
+    view_projection: Mat4,
+    uniform_view_projection: Mat4,
 }
 
 impl Uniform {
     pub fn new() -> Self {
-        let x = cgmath::Vector4::new(1.0, 0.0, 0.0, 0.0);
-        let y = cgmath::Vector4::new(0.0, 1.0, 0.0, 0.0);
-        let z = cgmath::Vector4::new(0.0, 0.0, 1.0, 0.0);
-        let position_far = cgmath::Vector4::new(0.0, 0.0, 0.0, -100.0);
-        let near_aspect_ratio_reserved = cgmath::Vector4::new(-1.0, 0.0, 0.0, -100.0);
-        let view = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-        );
-        let projection = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
-        );
-        let uniform_projection = cgmath::Matrix4::new(
-            0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
-        ) * projection;
+        let projection = new_orthographic(2.0, 2.0, 1.0, 100.0);
+        let uniform_projection = Mat4::from_cols_array(&UNIFORM_CORRECTION) * projection;
         Self {
-            x,
-            y,
-            z,
-            position_far,
-            near_aspect_ratio_reserved,
-            inversed_rotation: view,
-            view,
+            x: Vec4::new(1.0, 0.0, 0.0, 0.0),
+            y: Vec4::new(0.0, 1.0, 0.0, 0.0),
+            z: Vec4::new(0.0, 0.0, 1.0, 0.0),
+            position_far: Vec4::new(0.0, 0.0, 0.0, -100.0),
+            near_aspect_ratio_reserved: Vec4::new(-1.0, 1.0, 0.0, 0.0),
+            inversed_rotation: Mat4::identity(),
+            view: Mat4::identity(),
             projection,
             uniform_projection,
             view_projection: projection,
@@ -61,29 +80,36 @@ impl Uniform {
         }
     }
 
-    pub(super) fn get_inversed_rotation(&self) -> &cgmath::Matrix4<Real> {
+    pub(super) fn get_inversed_rotation(&self) -> &Mat4 {
         return &self.inversed_rotation;
     }
 
-    pub(super) fn get_projection(&self) -> &cgmath::Matrix4<Real> {
+    pub(super) fn get_projection(&self) -> &Mat4 {
         return &self.projection;
     }
 
     pub(super) fn get_far(&self) -> Real {
-        return self.position_far.w;
+        return self.position_far.w();
     }
 
     pub(super) fn get_near(&self) -> Real {
-        return self.near_aspect_ratio_reserved.x;
+        return self.near_aspect_ratio_reserved.x();
+    }
+
+    pub(crate) fn get_orientation(&self) -> Quat {
+        return Quat::from_axes(self.x.truncate(), self.y.truncate(), self.z.truncate());
     }
 }
 
 pub trait Camera: Object + Transferable {
-    fn get_view_projection(&self) -> &cgmath::Matrix4<Real>;
-    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[cgmath::Vector3<Real>; 4]>;
-    fn is_in_frustum(&self, radius: Real, location: &cgmath::Vector3<Real>) -> bool;
+    fn get_view_projection(&self) -> &Mat4;
+    fn get_frustum(&self) -> &Frustum;
+    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[Vec3; 4]>;
+    fn is_in_frustum(&self, radius: Real, location: &Vec3) -> bool;
     fn update_uniform(&self, uniform: &mut Uniform);
-    fn get_distance(&self, location: &cgmath::Vector3<Real>) -> Real;
+    fn get_distance(&self, location: &Vec3) -> Real;
+    /// Ratio of the projected diameter of the sphere to the screen height.
+    fn get_screen_size(&self, radius: Real, location: &Vec3) -> Real;
     fn get_uniform(&self) -> &Uniform;
 }
 
@@ -103,7 +129,8 @@ pub struct Manager {
     engine: Option<Weak<RwLock<Engine>>>,
     cameras: BTreeMap<Id, Weak<RwLock<dyn Camera>>>,
     name_to_id: BTreeMap<String, Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // Gx3D reader is not ported yet.
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -114,7 +141,6 @@ impl Manager {
             engine: None,
             cameras,
             name_to_id,
-            gx3d_table: None,
         }
     }
 
@@ -122,11 +148,12 @@ impl Manager {
         self.engine = Some(e);
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub fn load_gltf(&mut self, n: &gltf::Node, eng: &Engine) -> Arc<RwLock<dyn Camera>> {
+        vx_profile_zone!("camera::load_gltf");
         let c = vx_unwrap!(n.camera());
         let data: Vec<u8> = Vec::new();
         let camera = match c.projection() {
@@ -152,26 +179,28 @@ impl Manager {
         camera
     }
 
-    pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Camera>> {
-        if let Some(camera) = self.cameras.get(&id) {
-            if let Some(camera) = camera.upgrade() {
-                return camera;
-            }
-        }
-        let table = vx_unwrap!(&mut self.gx3d_table);
-        table.goto(id);
-        let reader: &mut Gx3DReader = table.get_mut_reader();
-        let type_id = reader.read_type_id();
-        let camera: Arc<RwLock<dyn Camera>> = if type_id == TypeId::Perspective as u8 {
-            Arc::new(RwLock::new(Perspective::new_with_gx3d(engine, reader, id)))
-        } else if type_id == TypeId::Orthographic as u8 {
-            Arc::new(RwLock::new(Orthographic::new_with_gx3d(engine, reader, id)))
-        } else {
-            vx_unexpected!();
-        };
-        self.cameras.insert(id, Arc::downgrade(&camera));
-        camera
-    }
+    // Gx3D reader is not ported yet.
+    // pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Camera>> {
+    //     vx_profile_zone!("camera::load_gx3d");
+    //     if let Some(camera) = self.cameras.get(&id) {
+    //         if let Some(camera) = camera.upgrade() {
+    //             return camera;
+    //         }
+    //     }
+    //     let table = vx_unwrap!(&mut self.gx3d_table);
+    //     table.goto(id);
+    //     let reader: &mut Gx3DReader = table.get_mut_reader();
+    //     let type_id = reader.read_type_id();
+    //     let camera: Arc<RwLock<dyn Camera>> = if type_id == TypeId::Perspective as u8 {
+    //         Arc::new(RwLock::new(Perspective::new_with_gx3d(engine, reader, id)))
+    //     } else if type_id == TypeId::Orthographic as u8 {
+    //         Arc::new(RwLock::new(Orthographic::new_with_gx3d(engine, reader, id)))
+    //     } else {
+    //         vx_unexpected!();
+    //     };
+    //     self.cameras.insert(id, Arc::downgrade(&camera));
+    //     camera
+    // }
 
     pub fn create<C>(&mut self) -> Arc<RwLock<C>>
     where
@@ -192,10 +221,17 @@ impl Manager {
     }
 }
 
+/// Near and far are positive distances, the camera looks toward its -z.
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Base {
     obj_base: ObjectBase,
+    location: Vec3,
+    orientation: Quat,
+    near: Real,
+    far: Real,
+    aspect_ratio: Real,
     uniform: Uniform,
+    frustum: Frustum,
     cascades_count: usize,
 }
 
@@ -208,30 +244,67 @@ impl Base {
         Self::new_with_obj_base(eng, ObjectBase::new_with_id(id))
     }
 
+    // Scenes are drawn on the main window, so its framebuffer has the aspect
+    // ratio of camera.
     pub fn new_with_obj_base(eng: &Engine, obj_base: ObjectBase) -> Self {
-        let os_app = vx_unwrap!(eng.get_os_app().upgrade());
-        let os_app = vx_result!(os_app.read());
-        let mut uniform = Uniform::new();
-        uniform.near_aspect_ratio_reserved.y = os_app.get_window_aspect_ratio();
-        Self {
+        let (width, height) = eng.gapi_engine.get_current_framebuffer().get_dimensions();
+        let uniform = Uniform::new();
+        let mut myself = Self {
             obj_base,
+            location: Vec3::zero(),
+            orientation: Quat::identity(),
+            near: 1.0,
+            far: 100.0,
+            aspect_ratio: width as Real / height as Real,
+            frustum: Frustum::new_with_view_projection(&uniform.view_projection),
             uniform,
-            cascades_count: eng.get_config().get_cascaded_shadows_count() as usize,
-        }
+            cascades_count: eng.get_config().cascaded_shadows_count as usize,
+        };
+        myself.update_location();
+        return myself;
+    }
+
+    pub fn set_projection(&mut self, projection: Mat4) {
+        self.uniform.projection = projection;
+        self.uniform.uniform_projection = Mat4::from_cols_array(&UNIFORM_CORRECTION) * projection;
+        self.update_view_projection();
     }
 
     pub fn update_view_projection(&mut self) {
-        self.uniform.view_projection = self.uniform.projection * self.uniform.view;
-        self.uniform.uniform_view_projection = cgmath::Matrix4::new(
-            0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
-        ) * self.uniform.view_projection;
+        let u = &mut self.uniform;
+        u.view_projection = u.projection * u.view;
+        u.uniform_view_projection = Mat4::from_cols_array(&UNIFORM_CORRECTION) * u.view_projection;
+        self.frustum = Frustum::new_with_view_projection(&u.view_projection);
     }
 
     pub fn update_location(&mut self) {
-        let translate = cgmath::Matrix4::from_translation(-self.uniform.position_far.truncate());
-        self.uniform.view = self.uniform.inversed_rotation * translate;
+        let rotation = Mat4::from_quat(self.orientation);
+        let u = &mut self.uniform;
+        u.x = rotation.get_col(0);
+        u.y = rotation.get_col(1);
+        u.z = rotation.get_col(2);
+        u.position_far = self.location.extend(-self.far);
+        u.near_aspect_ratio_reserved = Vec4::new(-self.near, self.aspect_ratio, 0.0, 0.0);
+        u.inversed_rotation = Mat4::from_quat(self.orientation.conjugate());
+        u.view = u.inversed_rotation * Mat4::from_translation(-self.location);
         self.update_view_projection();
     }
+
+    pub fn get_near(&self) -> Real {
+        return self.near;
+    }
+
+    pub fn get_far(&self) -> Real {
+        return self.far;
+    }
+
+    pub fn get_aspect_ratio(&self) -> Real {
+        return self.aspect_ratio;
+    }
+
+    fn get_axis(&self, v: Vec3) -> Vec3 {
+        return self.orientation.rotate(v);
+    }
 }
 
 impl CoreObject for Base {
@@ -264,130 +337,110 @@ impl Object for Base {
 }
 
 impl Transferable for Base {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
-        let rotation = cgmath::Matrix4::from(*q);
-        self.uniform.x = (rotation * cgmath::Vector4::new(1.0, 0.0, 0.0, 0.0))
-            .truncate()
-            .extend(self.uniform.x.w);
-        self.uniform.y = (rotation * cgmath::Vector4::new(0.0, 1.0, 0.0, 0.0))
-            .truncate()
-            .extend(self.uniform.y.w);
-        self.uniform.z = (rotation * cgmath::Vector4::new(0.0, 0.0, 1.0, 0.0))
-            .truncate()
-            .extend(self.uniform.z.w);
-        let mut q = -*q;
-        q.s = -q.s;
-        self.uniform.inversed_rotation = cgmath::Matrix4::from(q);
+    fn set_orientation(&mut self, q: &Quat) {
+        self.orientation = q.normalized();
         self.update_location();
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
-        self.uniform.position_far = l.extend(self.uniform.position_far.w);
+    fn set_location(&mut self, l: &Vec3) {
+        self.location = *l;
         self.update_location();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
-        return self.uniform.position_far.truncate();
+    fn get_location(&self) -> Vec3 {
+        return self.location;
     }
 
     fn move_local_z(&mut self, v: Real) {
-        let t = self.uniform.z.truncate() * v;
-        self.uniform.position_far =
-            (self.uniform.position_far.truncate() + t).extend(self.uniform.position_far.w);
+        self.location += self.get_axis(Vec3::new(0.0, 0.0, 1.0)) * v;
         self.update_location();
     }
 
     fn move_local_x(&mut self, v: Real) {
-        let t = self.uniform.x.truncate() * v;
-        self.uniform.position_far =
-            (self.uniform.position_far.truncate() + t).extend(self.uniform.position_far.w);
+        self.location += self.get_axis(Vec3::new(1.0, 0.0, 0.0)) * v;
         self.update_location();
     }
 
     fn rotate_local_x(&mut self, v: Real) {
-        let rot = cgmath::Matrix4::from_axis_angle(self.uniform.x.truncate(), cgmath::Rad(-v));
-        let irot = cgmath::Matrix4::from_axis_angle(self.uniform.x.truncate(), cgmath::Rad(v));
-        self.uniform.y = (irot * self.uniform.y.truncate().extend(0.0))
-            .truncate()
-            .extend(self.uniform.y.w);
-        self.uniform.z = (irot * self.uniform.z.truncate().extend(0.0))
-            .truncate()
-            .extend(self.uniform.z.w);
-        self.uniform.inversed_rotation = self.uniform.inversed_rotation * rot;
-        self.update_location();
+        let q = self.orientation * Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), v);
+        self.set_orientation(&q);
     }
 
     fn rotate_global_z(&mut self, v: Real) {
-        let ax = cgmath::Vector3::new(0.0, 0.0, 1.0);
-        let rot = cgmath::Matrix4::from_axis_angle(ax, cgmath::Rad(-v));
-        let irot = cgmath::Matrix4::from_axis_angle(ax, cgmath::Rad(v));
-        self.uniform.x = (irot * self.uniform.x.truncate().extend(0.0))
-            .truncate()
-            .extend(self.uniform.x.w);
-        self.uniform.y = (irot * self.uniform.y.truncate().extend(0.0))
-            .truncate()
-            .extend(self.uniform.y.w);
-        self.uniform.z = (irot * self.uniform.z.truncate().extend(0.0))
-            .truncate()
-            .extend(self.uniform.z.w);
-        self.uniform.inversed_rotation = self.uniform.inversed_rotation * rot;
-        self.update_location();
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), v) * self.orientation;
+        self.set_orientation(&q);
     }
 }
 
 impl Loadable for Base {
-    fn new_with_gltf(node: &gltf::Node, eng: &Engine, _: &[u8]) -> Self {
-        let (near, far) = match vx_unwrap!(node.camera()).projection() {
-            gltf::camera::Projection::Perspective(p) => (p.znear(), vx_unwrap!(p.zfar())),
-            gltf::camera::Projection::Orthographic(p) => (p.znear(), p.zfar()),
-        };
-        let mut myself = Base::new(eng);
-        myself.uniform.near_aspect_ratio_reserved.x = near;
-        let (l, r, _) = node.transform().decomposed();
-        myself.uniform.position_far = cgmath::Vector4::new(l[0], l[1], l[2], -far);
-        let rotation = cgmath::Quaternion::new(r[3], r[0], r[1], r[2]);
-        myself.set_orientation(&rotation);
+    fn new_with_gltf(node: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
+        let mut myself = Base::new_with_obj_base(eng, ObjectBase::new_with_gltf(node, eng, data));
+        let camera = GltfCamera::new_with_gltf(&vx_unwrap!(node.camera()));
+        myself.near = camera.near;
+        if let Some(far) = camera.far {
+            myself.far = far;
+        }
+        // glTF aspect ratios override the aspect ratio of window.
+        if let Some(aspect_ratio) = camera.aspect_ratio {
+            myself.aspect_ratio = aspect_ratio;
+        }
+        let (l, r, _) = read_gltf_transform(node);
+        myself.location = l;
+        myself.set_orientation(&r);
         return myself;
     }
 
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let mut myself = Base::new_with_id(engine, my_id);
-        myself.uniform.position_far.x = reader.read();
-        myself.uniform.position_far.y = reader.read();
-        myself.uniform.position_far.z = reader.read();
-        let r: [Real; 4] = [reader.read(), reader.read(), reader.read(), reader.read()];
-        myself.uniform.near_aspect_ratio_reserved.x = -reader.read::<Real>();
-        myself.uniform.position_far.w = -reader.read::<Real>();
-        myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Camera position is: {:?}", &myself.uniform.position_far);
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Camera quaternion is: {:?}", &r);
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Camera view matrix is: {:?}", &myself.uniform.view);
-        return myself;
-    }
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let mut myself = Base::new_with_id(engine, my_id);
+    //     myself.uniform.position_far.x = reader.read();
+    //     myself.uniform.position_far.y = reader.read();
+    //     myself.uniform.position_far.z = reader.read();
+    //     let r: [Real; 4] = [reader.read(), reader.read(), reader.read(), reader.read()];
+    //     myself.uniform.near_aspect_ratio_reserved.x = -reader.read::<Real>();
+    //     myself.uniform.position_far.w = -reader.read::<Real>();
+    //     myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Camera position is: {:?}", &myself.uniform.position_far);
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Camera quaternion is: {:?}", &r);
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Camera view matrix is: {:?}", &myself.uniform.view);
+    //     return myself;
+    // }
 }
 
 impl Camera for Base {
-    fn get_view_projection(&self) -> &cgmath::Matrix4<Real> {
+    fn get_view_projection(&self) -> &Mat4 {
         &self.uniform.view_projection
     }
 
-    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[cgmath::Vector3<Real>; 4]> {
+    fn get_frustum(&self) -> &Frustum {
+        return &self.frustum;
+    }
+
+    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[Vec3; 4]> {
         vx_log_f!("Base camera does not implement cascading.");
     }
 
-    fn is_in_frustum(&self, _: Real, _: &cgmath::Vector3<Real>) -> bool {
-        vx_unexpected!();
+    fn is_in_frustum(&self, radius: Real, location: &Vec3) -> bool {
+        return self.frustum.intersects_sphere(*location, radius);
     }
 
     fn update_uniform(&self, uniform: &mut Uniform) {
         *uniform = self.uniform;
     }
 
-    fn get_distance(&self, _: &cgmath::Vector3<Real>) -> Real {
-        vx_unexpected!();
+    fn get_distance(&self, location: &Vec3) -> Real {
+        return (self.location - *location).length();
+    }
+
+    fn get_screen_size(&self, radius: Real, location: &Vec3) -> Real {
+        return get_lod_screen_size(
+            &self.uniform.projection,
+            &self.uniform.view_projection,
+            radius,
+            *location,
+        );
     }
 
     fn get_uniform(&self) -> &Uniform {
@@ -402,7 +455,6 @@ pub struct Perspective {
     fovy: Real,
     tanx: Real,
     tany: Real,
-    frustum: Frustum,
     lambda: Real, // (sin(fovy/2) + sin(fovx/2)) / 2 -no proof for it just my gut, let see what I get
 }
 
@@ -411,9 +463,7 @@ const DEFAULT_FOVX: Real = 1.396263402; // 80 degree
 impl Perspective {
     pub fn new(eng: &Engine) -> Self {
         let base = Base::new_with_obj_base(eng, ObjectBase::new());
-        let mut myself = Self::new_with_base(base);
-        myself.set_fov_vertical(DEFAULT_FOVX);
-        return myself;
+        return Self::new_with_base(base);
     }
 
     fn new_with_base(base: Base) -> Self {
@@ -423,7 +473,6 @@ impl Perspective {
             fovx: 0.0,
             tanx: 0.0,
             tany: 0.0,
-            frustum: Frustum::default(),
             lambda: 0.0,
         };
         s.set_fov_vertical(DEFAULT_FOVX);
@@ -433,55 +482,27 @@ impl Perspective {
     pub fn set_fov_vertical(&mut self, fovx: Real) {
         self.fovx = fovx;
         self.tanx = (fovx * 0.5).tan();
-        self.tany = self.tanx / self.base.uniform.near_aspect_ratio_reserved.y;
+        self.tany = self.tanx / self.base.aspect_ratio;
         self.fovy = self.tany.atan() * 2.0;
-        self.base.uniform.projection = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
-        ) * cgmath::perspective(
-            cgmath::Rad(self.fovy),
-            self.base.uniform.near_aspect_ratio_reserved.y,
-            -self.base.uniform.near_aspect_ratio_reserved.x,
-            -self.base.uniform.position_far.w,
-        );
-        self.base.uniform.uniform_projection = cgmath::Matrix4::new(
-            0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
-        ) * self.base.uniform.projection;
+        self.base.set_projection(new_perspective(
+            self.fovy,
+            self.base.aspect_ratio,
+            self.base.near,
+            self.base.far,
+        ));
         self.lambda = ((self.fovx * 0.5).sin() + (self.fovy * 0.5).sin()) * 0.5;
-        self.base.update_view_projection();
-        self.update_frustum();
-    }
-
-    fn update_frustum(&mut self) {
-        let zn = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.near_aspect_ratio_reserved.x);
-        let zf = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.position_far.w);
-        let xn = self.base.uniform.x.truncate()
-            * (-self.base.uniform.near_aspect_ratio_reserved.x * self.tanx);
-        let yn = self.base.uniform.y.truncate()
-            * (-self.base.uniform.near_aspect_ratio_reserved.x * self.tany);
-        let xf = self.base.uniform.x.truncate() * (-self.base.uniform.position_far.w * self.tanx);
-        let yf = self.base.uniform.y.truncate() * (-self.base.uniform.position_far.w * self.tany);
-        let znpxn = zn + xn;
-        let znmxn = zn - xn;
-        let np1 = znpxn + yn;
-        let np2 = znpxn - yn;
-        let np3 = znmxn + yn;
-        let np4 = znmxn - yn;
-        let zfpxf = zf + xf;
-        let zfmxf = zf - xf;
-        let fp1 = zfpxf + yf;
-        let fp2 = zfpxf - yf;
-        let fp3 = zfmxf + yf;
-        let fp4 = zfmxf - yf;
-        self.frustum = Frustum::new([
-            Plane::new(np1, np3, np2),
-            Plane::new(np1, fp1, np3),
-            Plane::new(np1, np2, fp1),
-            Plane::new(fp4, fp3, fp2),
-            Plane::new(fp4, fp2, np4),
-            Plane::new(fp4, np4, fp3),
-        ]);
+    }
+
+    pub fn set_fov_y(&mut self, fovy: Real) {
+        self.set_fov_vertical(get_fov_x(fovy, self.base.aspect_ratio));
+    }
+
+    // Corners of the section of the frustum at the distance from the camera.
+    fn get_section(&self, distance: Real) -> [Vec3; 4] {
+        let x = self.base.get_axis(Vec3::new(1.0, 0.0, 0.0)) * (self.tanx * distance);
+        let y = self.base.get_axis(Vec3::new(0.0, 1.0, 0.0)) * (self.tany * distance);
+        let z = self.base.location - self.base.get_axis(Vec3::new(0.0, 0.0, 1.0)) * distance;
+        return [(z - x) - y, z + x - y, z + x + y, (z - x) + y];
     }
 }
 
@@ -516,71 +537,66 @@ impl Object for Perspective {
 
 impl Loadable for Perspective {
     fn new_with_gltf(n: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
-        let c = vx_unwrap!(n.camera());
-        let p = match c.projection() {
-            gltf::camera::Projection::Perspective(p) => p,
-            gltf::camera::Projection::Orthographic(_) => {
-                vx_log_f!("Type of camera isn't perspective.")
-            }
+        let fov_y = match GltfCamera::new_with_gltf(&vx_unwrap!(n.camera())).projection {
+            GltfProjection::Perspective { fov_y } => fov_y,
+            GltfProjection::Orthographic { .. } => vx_log_f!("Type of camera isn't perspective."),
         };
         let base = Base::new_with_gltf(n, eng, data);
         let mut myself = Self::new_with_base(base);
-        myself.set_fov_vertical(p.yfov());
+        myself.set_fov_y(fov_y);
         return myself;
     }
 
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let base = Base::new_with_gx3d(engine, reader, my_id);
-        let mut myself = Self::new_with_base(base);
-        myself.set_fov_vertical(reader.read());
-        #[cfg(debug_gx3d)]
-        vx_log_i!("{:?}", &myself.base.uniform);
-        return myself;
-    }
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let base = Base::new_with_gx3d(engine, reader, my_id);
+    //     let mut myself = Self::new_with_base(base);
+    //     myself.set_fov_vertical(reader.read());
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("{:?}", &myself.base.uniform);
+    //     return myself;
+    // }
 }
 
 impl Transferable for Perspective {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
+    fn set_orientation(&mut self, q: &Quat) {
         self.base.set_orientation(q);
-        self.update_frustum();
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, l: &Vec3) {
         self.base.set_location(l);
-        self.update_frustum();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         return self.base.get_location();
     }
 
     fn move_local_z(&mut self, v: Real) {
         self.base.move_local_z(v);
-        self.update_frustum();
     }
 
     fn move_local_x(&mut self, v: Real) {
         self.base.move_local_x(v);
-        self.update_frustum();
     }
 
     fn rotate_local_x(&mut self, v: Real) {
         self.base.rotate_local_x(v);
-        self.update_frustum();
     }
 
     fn rotate_global_z(&mut self, v: Real) {
         self.base.rotate_global_z(v);
-        self.update_frustum();
     }
 }
 
 impl Camera for Perspective {
-    fn get_view_projection(&self) -> &cgmath::Matrix4<Real> {
+    fn get_view_projection(&self) -> &Mat4 {
         self.base.get_view_projection()
     }
 
-    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[cgmath::Vector3<Real>; 4]> {
+    fn get_frustum(&self) -> &Frustum {
+        return self.base.get_frustum();
+    }
+
+    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[Vec3; 4]> {
         let sections_count = self.base.cascades_count;
         #[cfg(debug_mode)]
         {
@@ -588,91 +604,48 @@ impl Camera for Perspective {
                 vx_log_f!("sections_count must be greater than zero.");
             }
         }
-
-        let mut result = vec![[cgmath::Vector3::new(0.0, 0.0, 0.0); 4]; sections_count + 1];
-
-        let xtanx = self.base.uniform.x.truncate() * self.tanx;
-        let ytany = self.base.uniform.y.truncate() * self.tany;
-
-        let x = xtanx * -self.base.uniform.near_aspect_ratio_reserved.x;
-        let y = ytany * -self.base.uniform.near_aspect_ratio_reserved.x;
-        let z = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.near_aspect_ratio_reserved.x);
-
-        result[0][0] = (z - x) - y;
-        result[0][1] = z + x - y;
-        result[0][2] = z + x + y;
-        result[0][3] = (z - x) + y;
-
-        let x = xtanx * -self.base.uniform.position_far.w;
-        let y = ytany * -self.base.uniform.position_far.w;
-        let z = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.position_far.w);
-
-        result[sections_count][0] = (z - x) - y;
-        result[sections_count][1] = z + x - y;
-        result[sections_count][2] = z + x + y;
-        result[sections_count][3] = (z - x) + y;
-
+        let near = self.base.near;
+        let far = self.base.far;
+        let mut result = vec![[Vec3::zero(); 4]; sections_count + 1];
+        result[0] = self.get_section(near);
+        result[sections_count] = self.get_section(far);
         if sections_count < 2 {
             return result;
         }
         // Zi = yn(f/n)^(i/N) + (1-y)(n+(i/N)(f-n))
         // Zi = yn((f/n)^(1/N))^i + (1-y)n + (1-y)((f-n)/N)i
         let oneminlambda = 1.0 - self.lambda;
-        let lambda = self.lambda;
         let onedivcn = 1.0 / sections_count as Real;
         // uniform increament
-        let unisecinc = oneminlambda
-            * onedivcn
-            * (self.base.uniform.position_far.w - self.base.uniform.near_aspect_ratio_reserved.x);
-        let fdivn =
-            self.base.uniform.position_far.w / self.base.uniform.near_aspect_ratio_reserved.x;
+        let unisecinc = oneminlambda * onedivcn * (far - near);
         // logarithmic multiplication
-        let logsecmul = fdivn.powf(onedivcn);
+        let logsecmul = (far / near).powf(onedivcn);
         // uniform sector
-        let mut unisec = oneminlambda * self.base.uniform.near_aspect_ratio_reserved.x + unisecinc;
+        let mut unisec = oneminlambda * near;
         // logarithmic sector
-        let mut logsec = lambda * self.base.uniform.near_aspect_ratio_reserved.x * logsecmul;
-
-        let l = logsec + unisec;
-        let x = xtanx * l;
-        let y = ytany * l;
-        let z = self.base.uniform.position_far.truncate() + (self.base.uniform.z.truncate() * l);
-
-        result[1][0] = (z - x) - y;
-        result[1][1] = z + x - y;
-        result[1][2] = z + x + y;
-        result[1][3] = (z - x) + y;
-
-        for i in 2..sections_count {
+        let mut logsec = self.lambda * near;
+        for section in result.iter_mut().take(sections_count).skip(1) {
             logsec *= logsecmul;
             unisec += unisecinc;
-
-            let l = logsec + unisec;
-            let x = xtanx * l;
-            let y = ytany * l;
-            let z =
-                self.base.uniform.position_far.truncate() + (self.base.uniform.z.truncate() * l);
-
-            result[i][0] = (z - x) - y;
-            result[i][1] = z + x - y;
-            result[i][2] = z + x + y;
-            result[i][3] = (z - x) + y;
+            *section = self.get_section(logsec + unisec);
         }
         return result;
     }
 
-    fn is_in_frustum(&self, radius: Real, location: &cgmath::Vector3<Real>) -> bool {
-        return self.frustum.intersects_center_radius(location, radius);
+    fn is_in_frustum(&self, radius: Real, location: &Vec3) -> bool {
+        return self.base.is_in_frustum(radius, location);
     }
 
     fn update_uniform(&self, uniform: &mut Uniform) {
         self.base.update_uniform(uniform);
     }
 
-    fn get_distance(&self, p: &cgmath::Vector3<Real>) -> Real {
-        return (self.base.uniform.position_far.truncate() - p).magnitude();
+    fn get_distance(&self, p: &Vec3) -> Real {
+        return self.base.get_distance(p);
+    }
+
+    fn get_screen_size(&self, radius: Real, location: &Vec3) -> Real {
+        return self.base.get_screen_size(radius, location);
     }
 
     fn get_uniform(&self) -> &Uniform {
@@ -686,11 +659,11 @@ impl DefaultCamera for Perspective {
     }
 }
 
+/// Size is the height of the view volume.
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Orthographic {
     base: Base,
     size: Real,
-    aabb: Aabb3,
 }
 
 impl Orthographic {
@@ -698,73 +671,21 @@ impl Orthographic {
         Self::new_with_base(Base::new(eng), size)
     }
 
-    fn update_frustum(&mut self) {
-        self.aabb = Aabb3::new();
-        let zn = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.near_aspect_ratio_reserved.x);
-        let zf = self.base.uniform.position_far.truncate()
-            + (self.base.uniform.z.truncate() * self.base.uniform.position_far.w);
-        let x = self.base.uniform.x.truncate()
-            * (self.base.uniform.near_aspect_ratio_reserved.y * self.size);
-        let y = self.base.uniform.y.truncate() * self.size;
-        let xpy = x + y;
-        let np = zn + xpy;
-        let fp = zf - xpy;
-        self.aabb.insert(&np);
-        self.aabb.insert(&fp);
-    }
-
     pub fn new_with_base(mut base: Base, size: Real) -> Self {
-        let size = size * 0.5;
-        let w = base.uniform.near_aspect_ratio_reserved.y * size;
-        base.uniform.projection = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
-        ) * cgmath::ortho(
-            -w,
-            w,
-            -size,
+        base.set_projection(new_orthographic(
+            base.aspect_ratio * size,
             size,
-            -base.uniform.near_aspect_ratio_reserved.x,
-            -base.uniform.position_far.w,
-        );
-        base.uniform.uniform_projection = cgmath::Matrix4::new(
-            0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
-        ) * base.uniform.projection;
-        base.update_view_projection();
-        let mut s = Orthographic {
+            base.near,
+            base.far,
+        ));
+        return Orthographic {
             base,
-            size,
-            aabb: Aabb3::new(),
+            size: size * 0.5,
         };
-        s.update_frustum();
-        return s;
     }
 
     pub fn new_with_id(eng: &Engine, id: Id) -> Self {
-        let mut base = Base::new_with_id(eng, id);
-        let size = 0.5;
-        let w = base.uniform.near_aspect_ratio_reserved.y * size;
-        base.uniform.projection = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
-        ) * cgmath::ortho(
-            -w,
-            w,
-            -size,
-            size,
-            -base.uniform.near_aspect_ratio_reserved.x,
-            -base.uniform.position_far.w,
-        );
-        base.uniform.uniform_projection = cgmath::Matrix4::new(
-            0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.5, 0.5, 0.0, 1.0,
-        ) * base.uniform.projection;
-        base.update_view_projection();
-        let mut s = Orthographic {
-            base,
-            size,
-            aabb: Aabb3::new(),
-        };
-        s.update_frustum();
-        return s;
+        return Self::new_with_base(Base::new_with_id(eng, id), 1.0);
     }
 }
 
@@ -798,65 +719,60 @@ impl Object for Orthographic {
 
 impl Loadable for Orthographic {
     fn new_with_gltf(n: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
-        let c = vx_unwrap!(n.camera());
-        let o = match c.projection() {
-            gltf::camera::Projection::Perspective(_) => {
-                vx_log_f!("Type of camera isn't perspective.")
-            }
-            gltf::camera::Projection::Orthographic(o) => o,
+        let height = match GltfCamera::new_with_gltf(&vx_unwrap!(n.camera())).projection {
+            GltfProjection::Perspective { .. } => vx_log_f!("Type of camera isn't orthographic."),
+            GltfProjection::Orthographic { height } => height,
         };
         let base = Base::new_with_gltf(n, eng, data);
-        Self::new_with_base(base, o.ymag())
+        Self::new_with_base(base, height)
     }
 
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let base = Base::new_with_gx3d(engine, reader, my_id);
-        Self::new_with_base(base, reader.read())
-    }
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let base = Base::new_with_gx3d(engine, reader, my_id);
+    //     Self::new_with_base(base, reader.read())
+    // }
 }
 
 impl Transferable for Orthographic {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
+    fn set_orientation(&mut self, q: &Quat) {
         self.base.set_orientation(q);
-        self.update_frustum();
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, l: &Vec3) {
         self.base.set_location(l);
-        self.update_frustum();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         return self.base.get_location();
     }
 
     fn move_local_z(&mut self, v: Real) {
         self.base.move_local_z(v);
-        self.update_frustum();
     }
 
     fn move_local_x(&mut self, v: Real) {
         self.base.move_local_x(v);
-        self.update_frustum();
     }
 
     fn rotate_local_x(&mut self, v: Real) {
         self.base.rotate_local_x(v);
-        self.update_frustum();
     }
 
     fn rotate_global_z(&mut self, v: Real) {
         self.base.rotate_global_z(v);
-        self.update_frustum();
     }
 }
 
 impl Camera for Orthographic {
-    fn get_view_projection(&self) -> &cgmath::Matrix4<Real> {
+    fn get_view_projection(&self) -> &Mat4 {
         &self.base.uniform.view_projection
     }
 
-    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[cgmath::Vector3<Real>; 4]> {
+    fn get_frustum(&self) -> &Frustum {
+        return self.base.get_frustum();
+    }
+
+    fn get_cascaded_shadow_frustum_partitions(&self) -> Vec<[Vec3; 4]> {
         let sections_count = self.base.cascades_count;
         #[cfg(debug_mode)]
         {
@@ -864,56 +780,37 @@ impl Camera for Orthographic {
                 vx_log_f!("sections_count must be greater than zero.");
             }
         }
-        let mut result = vec![[cgmath::Vector3::new(0.0, 0.0, 0.0); 4]; sections_count + 1];
-
-        let w = self.size * self.base.uniform.near_aspect_ratio_reserved.y;
-
-        let l = self.base.uniform.near_aspect_ratio_reserved.x;
-        let x = self.base.uniform.x.truncate() * w;
-        let y = self.base.uniform.y.truncate() * self.size;
-        let mut z =
-            self.base.uniform.position_far.truncate() + (self.base.uniform.z.truncate() * l);
-
+        let x = self.base.get_axis(Vec3::new(1.0, 0.0, 0.0)) * (self.size * self.base.aspect_ratio);
+        let y = self.base.get_axis(Vec3::new(0.0, 1.0, 0.0)) * self.size;
+        let forward = -self.base.get_axis(Vec3::new(0.0, 0.0, 1.0));
         let xny = x - y;
         let xpy = x + y;
-
-        result[0][0] = z - xny;
-        result[0][1] = z + xny;
-        result[0][2] = z + xpy;
-        result[0][3] = z - xpy;
-
-        let unisecinc = (self.base.uniform.position_far.w
-            - self.base.uniform.near_aspect_ratio_reserved.x)
-            / (sections_count as Real);
-        let unisecinc = self.base.uniform.z.truncate() * unisecinc;
-
-        let sections_count = sections_count + 1;
-
-        for i in 1..sections_count {
+        let unisecinc = forward * ((self.base.far - self.base.near) / sections_count as Real);
+        let mut z = self.base.location + forward * self.base.near;
+        let mut result = Vec::with_capacity(sections_count + 1);
+        for _ in 0..=sections_count {
+            result.push([z - xny, z + xny, z + xpy, z - xpy]);
             z += unisecinc;
-
-            result[i][0] = z - xny;
-            result[i][1] = z + xny;
-            result[i][2] = z + xpy;
-            result[i][3] = z - xpy;
         }
         return result;
     }
 
-    fn is_in_frustum(&self, radius: Real, location: &cgmath::Vector3<Real>) -> bool {
-        let p = (self.base.uniform.view * location.extend(1.0)).truncate();
-        return self.aabb.intersects_center_radius(&p, radius);
+    fn is_in_frustum(&self, radius: Real, location: &Vec3) -> bool {
+        return self.base.is_in_frustum(radius, location);
     }
 
     fn update_uniform(&self, uniform: &mut Uniform) {
         self.base.update_uniform(uniform);
     }
 
-    fn get_distance(&self, p: &cgmath::Vector3<Real>) -> Real {
-        return -cgmath::dot(
-            self.base.uniform.position_far.truncate() - p,
-            self.base.uniform.z.truncate(),
-        );
+    // Depth of the point, because all the points of a plane that faces the
+    // camera have the same size on the screen.
+    fn get_distance(&self, p: &Vec3) -> Real {
+        return (self.base.location - *p).dot(self.base.get_axis(Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    fn get_screen_size(&self, radius: Real, location: &Vec3) -> Real {
+        return self.base.get_screen_size(radius, location);
     }
 
     fn get_uniform(&self) -> &Uniform {
@@ -926,3 +823,107 @@ impl DefaultCamera for Orthographic {
         Orthographic::new(eng, 1.0)
     }
 }
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn new_engine() -> Engine {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 4);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            4,
+        );
+        return Engine::new_with_gapi_engine(gapi_engine);
+    }
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    #[test]
+    fn gltf_perspective_test() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.5707964, "znear": 0.5, "zfar": 50.0}}],
+            "nodes": [{"name": "eye", "camera": 0, "translation": [0.0, 0.0, 10.0]}]
+        }"#;
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let engine = new_engine();
+        let mut manager = Manager::new();
+        let camera = manager.load_gltf(&vx_unwrap!(document.nodes().next()), &engine);
+        assert_eq!(manager.name_to_id.len(), 1);
+        let camera = vx_result!(camera.read());
+        assert_eq!(camera.get_frustum().get_planes().len(), 6);
+        assert!(is_near(camera.get_location(), Vec3::new(0.0, 0.0, 10.0)));
+        assert_eq!(camera.get_distance(&Vec3::zero()), 10.0);
+        // Aspect ratio of the framebuffer is two, at the origin the view is
+        // 40 wide and 20 high.
+        assert!(camera.is_in_frustum(0.5, &Vec3::zero()));
+        assert!(camera.is_in_frustum(0.5, &Vec3::new(19.0, 0.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(22.0, 0.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(0.0, 11.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(0.0, 0.0, 11.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(0.0, 0.0, -41.0)));
+        let clip = camera
+            .get_view_projection()
+            .transform_vec4(Vec4::new(0.0, 5.0, 0.0, 1.0));
+        assert!(clip.y() < 0.0 && 0.0 < clip.z() && clip.z() < clip.w());
+    }
+
+    #[test]
+    fn transfer_test() {
+        let engine = new_engine();
+        let mut camera = Perspective::new(&engine);
+        camera.set_location(&Vec3::new(1.0, 0.0, 0.0));
+        // It looks toward +y after the rotation, and toward -x after the next.
+        camera.rotate_local_x(PI * 0.5);
+        assert!(camera.is_in_frustum(0.5, &Vec3::new(1.0, 10.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(1.0, 0.0, -10.0)));
+        camera.move_local_z(-2.0);
+        assert!(is_near(camera.get_location(), Vec3::new(1.0, 2.0, 0.0)));
+        camera.rotate_global_z(PI * 0.5);
+        camera.move_local_x(1.0);
+        assert!(is_near(camera.get_location(), Vec3::new(1.0, 3.0, 0.0)));
+        assert!(camera.is_in_frustum(0.5, &Vec3::new(-10.0, 3.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.5, &Vec3::new(1.0, 13.0, 0.0)));
+        let partitions = camera.get_cascaded_shadow_frustum_partitions();
+        assert_eq!(
+            partitions.len(),
+            engine.get_config().cascaded_shadows_count as usize + 1
+        );
+        let depth = |p: &[Vec3; 4]| 1.0 - p[0].x();
+        assert!((depth(&partitions[0]) - 1.0).abs() < 1e-4);
+        assert!((depth(&partitions[partitions.len() - 1]) - 100.0).abs() < 1e-3);
+        for p in partitions.windows(2) {
+            assert!(depth(&p[0]) < depth(&p[1]));
+        }
+        // Cameras take the default translation of Transferable.
+        camera.translate(&Vec3::new(0.0, 0.0, 1.0));
+        assert!(is_near(camera.get_location(), Vec3::new(1.0, 3.0, 1.0)));
+    }
+
+    #[test]
+    fn orthographic_test() {
+        let engine = new_engine();
+        let mut camera = Orthographic::new(&engine, 4.0);
+        camera.set_location(&Vec3::new(0.0, 0.0, 10.0));
+        // View volume is 8 wide and 4 high.
+        assert!(camera.is_in_frustum(0.05, &Vec3::new(3.9, 0.0, 0.0)));
+        assert!(!camera.is_in_frustum(0.05, &Vec3::new(4.1, 0.0, 0.0)));
+        assert!(camera.is_in_frustum(0.05, &Vec3::new(0.0, -1.9, -80.0)));
+        assert!(!camera.is_in_frustum(0.05, &Vec3::new(0.0, -2.1, -80.0)));
+        assert_eq!(camera.get_distance(&Vec3::new(3.0, 1.0, 0.0)), 10.0);
+        let partitions = camera.get_cascaded_shadow_frustum_partitions();
+        assert!(is_near(partitions[0][0], Vec3::new(-4.0, 2.0, 9.0)));
+        assert!(is_near(
+            partitions[partitions.len() - 1][2],
+            Vec3::new(4.0, 2.0, -90.0)
+        ));
+    }
+}
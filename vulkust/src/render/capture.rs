@@ -0,0 +1,145 @@
+#[cfg(blank_gapi)]
+pub use super::super::blank_gapi::capture::Target;
+#[cfg(software_gapi)]
+pub use super::super::software::capture::Target;
+// #[cfg(vulkan_api)]
+// pub use super::super::vulkan::capture::Target;
+
+use super::super::core::event::{Button, ButtonAction, Keyboard, Type as EventType};
+use image::{ImageBuffer, ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Host side copy of a rendered color image.
+// Pixels are tightly packed 8 bit RGBA rows from top to bottom.
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Capture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Capture {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        #[cfg(debug_mode)]
+        {
+            if pixels.len() != (width * height * 4) as usize {
+                vx_log_f!("Capture pixels size does not match its dimensions.");
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Swapchain images are usually BGRA.
+    pub fn new_with_bgra(width: u32, height: u32, mut pixels: Vec<u8>) -> Self {
+        for p in pixels.chunks_mut(4) {
+            p.swap(0, 2);
+        }
+        Self::new(width, height, pixels)
+    }
+
+    pub fn get_width(&self) -> u32 {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> u32 {
+        return self.height;
+    }
+
+    pub fn get_pixels(&self) -> &[u8] {
+        return &self.pixels;
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        return vx_unwrap!(ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(
+            self.width,
+            self.height,
+            self.pixels.clone(),
+        ));
+    }
+
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        vx_result!(self
+            .to_image()
+            .write_to(&mut Cursor::new(&mut result), ImageFormat::Png));
+        return result;
+    }
+
+    pub fn save_png(&self, file_name: &str) {
+        vx_result!(self
+            .to_image()
+            .save_with_format(file_name, ImageFormat::Png));
+    }
+}
+
+// F12 and PrintScreen take a screenshot in every platform application.
+pub fn is_screenshot_hotkey(e: &EventType) -> bool {
+    match e {
+        EventType::Button {
+            button: Button::Keyboard(Keyboard::Function(12)),
+            action: ButtonAction::Press,
+        }
+        | EventType::Button {
+            button: Button::Keyboard(Keyboard::PrintScreen),
+            action: ButtonAction::Press,
+        } => return true,
+        _ => return false,
+    }
+}
+
+pub fn create_screenshot_file_name() -> String {
+    let now = vx_result!(SystemTime::now().duration_since(UNIX_EPOCH));
+    return format!("screenshot-{}.png", now.as_millis());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2x2 with distinct channels and alphas, so any swap or flip is caught.
+    const PIXELS: [u8; 16] = [255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 64, 10, 20, 30, 0];
+
+    #[test]
+    fn png_round_trip_test() {
+        let capture = Capture::new(2, 2, PIXELS.to_vec());
+        let png = capture.encode_png();
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+        let decoded =
+            vx_result!(image::load_from_memory_with_format(&png, ImageFormat::Png)).to_rgba8();
+        assert_eq!(decoded.dimensions(), (2, 2));
+        assert_eq!(decoded.into_raw(), PIXELS.to_vec());
+    }
+
+    #[test]
+    fn bgra_test() {
+        let mut bgra = PIXELS.to_vec();
+        for p in bgra.chunks_mut(4) {
+            p.swap(0, 2);
+        }
+        let capture = Capture::new_with_bgra(2, 2, bgra);
+        assert_eq!(capture.get_pixels(), &PIXELS[..]);
+    }
+
+    #[test]
+    fn screenshot_hotkey_test() {
+        let press = |button| EventType::Button {
+            button,
+            action: ButtonAction::Press,
+        };
+        assert!(is_screenshot_hotkey(&press(Button::Keyboard(
+            Keyboard::Function(12)
+        ))));
+        assert!(is_screenshot_hotkey(&press(Button::Keyboard(
+            Keyboard::PrintScreen
+        ))));
+        assert!(!is_screenshot_hotkey(&press(Button::Keyboard(
+            Keyboard::Function(11)
+        ))));
+    }
+}
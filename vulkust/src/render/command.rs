@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::command::*;
 pub use super::super::d3d12::command::*;
 #[cfg(metal_api)]
 pub use super::super::metal::command::*;
+#[cfg(software_gapi)]
+pub use super::super::software::command::*;
 #[cfg(vulkan_api)]
 pub use super::super::vulkan::command::*;
@@ -13,6 +13,7 @@ pub struct Configurations {
     pub max_shadow_maker_kernel_render_data_count: u64,
     pub content_width: u32,
     pub content_height: u32,
+    pub mesh_lods_count: u8,
 }
 
 impl Default for Configurations {
@@ -32,6 +33,7 @@ impl Default for Configurations {
             max_shadow_maker_kernel_render_data_count: 600,
             content_width: 1000,
             content_height: 700,
+            mesh_lods_count: 4,
         }
     }
 }
@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::descriptor::*;
 pub(crate) use super::super::d3d12::descriptor::*;
 #[cfg(metal)]
 pub(crate) use super::super::metal::descriptor::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::descriptor::*;
 #[cfg(vulkan_api)]
 pub(crate) use super::super::vulkan::descriptor::*;
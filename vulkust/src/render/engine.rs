@@ -1,16 +1,436 @@
-use crate::{gapi, platform::os::application::Application as OsApp};
+#[cfg(not(vulkan_api))]
+use crate::core::asset::Manager as AssetManager;
+#[cfg(any(blank_gapi, software_gapi))]
+use crate::render::capture::Target as CaptureTarget;
+#[cfg(not(vulkan_api))]
+use crate::render::{g_buffer_filler::GBufferFiller, scene::Scene, sync::Semaphore};
+use crate::{
+    core::{timing::Timing, types::Id},
+    gapi,
+    platform::os::application::Application as OsApp,
+    render::{capture::Capture, config::Configurations},
+};
+#[cfg(not(vulkan_api))]
+use std::sync::{Arc, RwLock, Weak};
 
 pub struct Engine {
     pub gapi_engine: gapi::engine::Engine,
+    #[cfg(not(vulkan_api))]
+    asset_manager: AssetManager,
+    // It is created with the first renderable scene, so the frames without
+    // any scene only clear the display.
+    #[cfg(not(vulkan_api))]
+    g_buffer_filler: Option<GBufferFiller>,
+    config: Configurations,
+    timing: Timing,
+    screenshot_requests: Vec<String>,
 }
 
 impl Engine {
     pub fn new(os_app: &mut OsApp) -> Self {
-        let gapi_engine = gapi::engine::Engine::new(os_app);
-        Self { gapi_engine }
+        return Self::new_with_gapi_engine(gapi::engine::Engine::new(os_app));
     }
 
+    // For the engines that do not need any platform application, like the
+    // offscreen software one and the recording blank one.
+    pub fn new_with_gapi_engine(gapi_engine: gapi::engine::Engine) -> Self {
+        Self {
+            gapi_engine,
+            #[cfg(not(vulkan_api))]
+            asset_manager: AssetManager::new(),
+            #[cfg(not(vulkan_api))]
+            g_buffer_filler: None,
+            config: Configurations::default(),
+            timing: Timing::new(),
+            screenshot_requests: Vec::new(),
+        }
+    }
+
+    pub fn get_config(&self) -> &Configurations {
+        return &self.config;
+    }
+
+    // Managers create their assets through the engine, so the owner of the
+    // engine must give it a reference to itself after its wrapping.
+    #[cfg(not(vulkan_api))]
+    pub fn set_myself(&self, myself: Weak<RwLock<Engine>>) {
+        self.asset_manager.set_engine(&myself);
+    }
+
+    #[cfg(not(vulkan_api))]
+    pub fn get_asset_manager(&self) -> &AssetManager {
+        return &self.asset_manager;
+    }
+
+    pub fn get_timing(&self) -> &Timing {
+        return &self.timing;
+    }
+
+    #[cfg(vulkan_api)]
     pub fn update(&mut self) {
+        vx_profile_zone!("frame");
+        self.timing.update();
         self.gapi_engine.update();
+        self.save_requested_screenshots();
+    }
+
+    #[cfg(not(vulkan_api))]
+    pub fn update(&mut self) {
+        vx_profile_zone!("frame");
+        self.timing.update();
+        {
+            vx_profile_zone!("start_rendering");
+            self.gapi_engine.start_rendering();
+        }
+        let scenes = self.update_scenes();
+        let semaphore = self.record_scenes(&scenes);
+        {
+            vx_profile_zone!("end_rendering");
+            self.gapi_engine.end_rendering(&semaphore);
+        }
+        self.save_requested_screenshots();
+    }
+
+    // Scenes that are dropped by their owners are forgotten here, it returns
+    // the renderable ones.
+    #[cfg(not(vulkan_api))]
+    fn update_scenes(&self) -> Vec<Arc<RwLock<dyn Scene>>> {
+        vx_profile_zone!("update_scenes");
+        let frame_number = self.gapi_engine.get_frame_number();
+        let delta_time = self.timing.length_of_previous_frame.as_secs_f32();
+        let scene_manager = self.asset_manager.get_scene_manager();
+        let mut ids = Vec::new();
+        let mut scenes = Vec::new();
+        for (id, scene) in vx_result!(scene_manager.read()).get_scenes() {
+            if let Some(scene) = scene.upgrade() {
+                {
+                    let mut scene = vx_result!(scene.write());
+                    if !scene.is_renderable() {
+                        continue;
+                    }
+                    scene.update(frame_number, delta_time);
+                    scene.clean();
+                }
+                scenes.push(scene);
+            } else {
+                ids.push(*id);
+            }
+        }
+        let mut scene_manager = vx_result!(scene_manager.write());
+        for id in ids {
+            scene_manager.remove_with_id(&id);
+        }
+        return scenes;
+    }
+
+    // It returns the semaphore that the presentation must wait for.
+    #[cfg(not(vulkan_api))]
+    fn record_scenes(&mut self, scenes: &[Arc<RwLock<dyn Scene>>]) -> Arc<Semaphore> {
+        vx_profile_zone!("record_scenes");
+        let mut semaphore = self.gapi_engine.get_starting_semaphore().clone();
+        if scenes.is_empty() {
+            return semaphore;
+        }
+        if self.g_buffer_filler.is_none() {
+            self.g_buffer_filler = Some(GBufferFiller::new(&self.gapi_engine, &self.config));
+        }
+        let g_buffer_filler = vx_unwrap!(&self.g_buffer_filler);
+        for scene in scenes {
+            semaphore = vx_result!(scene.write()).submit_gbuffer(
+                &self.gapi_engine,
+                &semaphore,
+                g_buffer_filler,
+            );
+        }
+        return semaphore;
+    }
+
+    fn save_requested_screenshots(&mut self) {
+        if !self.screenshot_requests.is_empty() {
+            vx_profile_zone!("screenshots");
+            let requests = std::mem::replace(&mut self.screenshot_requests, Vec::new());
+            for file_name in requests {
+                self.save_screenshot(&file_name);
+            }
+        }
+    }
+
+    // Platform applications call it on the size change of the main window.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.gapi_engine.resize(width, height);
+        // G-buffer has the size of the display.
+        #[cfg(not(vulkan_api))]
+        {
+            self.g_buffer_filler = None;
+        }
+    }
+
+    // Window must be created by the platform application, its surface is
+    // cleared and presented in each frame until remove_window. Scenes are
+    // only drawn on the main window yet.
+    pub fn add_window(&mut self, os_app: &OsApp, window_id: Id) {
+        self.gapi_engine.add_window(os_app, window_id);
+    }
+
+    pub fn remove_window(&mut self, window_id: Id) {
+        self.gapi_engine.remove_window(window_id);
+    }
+
+    // It copies the last rendered color image to host memory, it stalls the
+    // gpu so it is meant for screenshots and tests not for every frame.
+    pub fn capture_frame(&mut self) -> Option<Capture> {
+        return self.gapi_engine.capture_frame();
+    }
+
+    // Offscreen targets of the Vulkan backend come after its porting, see
+    // vulkan::capture.
+    #[cfg(any(blank_gapi, software_gapi))]
+    pub fn create_offscreen_target(&self, width: u32, height: u32) -> CaptureTarget {
+        return self.gapi_engine.create_offscreen_target(width, height);
+    }
+
+    #[cfg(any(blank_gapi, software_gapi))]
+    pub fn capture_target(&self, target: &CaptureTarget) -> Capture {
+        return target.capture();
+    }
+
+    // The screenshot is taken after the rendering of the current frame.
+    pub fn request_screenshot(&mut self, file_name: &str) {
+        self.screenshot_requests.push(file_name.to_string());
+    }
+
+    pub fn save_screenshot(&mut self, file_name: &str) {
+        if let Some(capture) = self.capture_frame() {
+            capture.save_png(file_name);
+            vx_log_i!("Screenshot saved in {}", file_name);
+        }
+    }
+}
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::*;
+
+    #[cfg(software_gapi)]
+    fn new_offscreen(width: u32, height: u32) -> Engine {
+        return Engine::new_with_gapi_engine(gapi::engine::Engine::new_offscreen(width, height));
+    }
+
+    #[cfg(software_gapi)]
+    #[test]
+    fn screenshot_test() {
+        let mut engine = new_offscreen(8, 4);
+        let file_name = std::env::temp_dir().join(format!(
+            "vulkust-screenshot-test-{}.png",
+            std::process::id()
+        ));
+        let file_name = vx_unwrap!(file_name.to_str()).to_string();
+        engine.request_screenshot(&file_name);
+        engine.update();
+        let screenshot = vx_result!(image::open(&file_name)).to_rgba8();
+        vx_result!(std::fs::remove_file(&file_name));
+        assert_eq!(screenshot.dimensions(), (8, 4));
+        assert!(screenshot.into_raw().iter().all(|p| *p == 0));
+    }
+
+    #[cfg(software_gapi)]
+    #[test]
+    fn resize_test() {
+        let mut engine = new_offscreen(8, 4);
+        engine.resize(6, 10);
+        engine.update();
+        let capture = vx_unwrap!(engine.capture_frame());
+        assert_eq!((capture.get_width(), capture.get_height()), (6, 10));
+    }
+
+    #[cfg(software_gapi)]
+    #[test]
+    fn offscreen_target_test() {
+        let engine = new_offscreen(8, 4);
+        let target = engine.create_offscreen_target(5, 3);
+        let capture = engine.capture_target(&target);
+        assert_eq!((capture.get_width(), capture.get_height()), (5, 3));
+        assert_eq!(capture.get_pixels().len(), 5 * 3 * 4);
+    }
+
+    // Every stage of the frame must be timed by the profiler.
+    #[cfg(profiler)]
+    #[test]
+    fn frame_stages_profile_test() {
+        #[cfg(software_gapi)]
+        let mut engine = new_offscreen(8, 4);
+        #[cfg(blank_gapi)]
+        let mut engine = Engine::new_with_gapi_engine(gapi::engine::Engine::new_with_trace(
+            std::sync::Arc::new(crate::blank_gapi::trace::Trace::new()),
+            8,
+            4,
+        ));
+        engine.update();
+        engine.update();
+        let averages = crate::core::profiler::get_averages();
+        for stage in &["frame", "start_rendering", "update_scenes", "end_rendering"] {
+            assert!(
+                averages
+                    .iter()
+                    .any(|a| a.name == *stage && a.samples_count >= 2),
+                "Stage {} is not profiled.",
+                stage
+            );
+        }
+    }
+
+    // Two models are in front of the camera and the third one is behind it,
+    // the scene is loaded and then drawn through the frame of the renderer.
+    fn render_scene_frame(
+        gapi_engine: gapi::engine::Engine,
+        models_count: usize,
+    ) -> Arc<RwLock<Engine>> {
+        use crate::render::scene::Game;
+        let engine = Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+        vx_result!(engine.read()).set_myself(Arc::downgrade(&engine));
+        let mut nodes = vec![
+            r#"{"camera": 0, "translation": [0.0, 0.0, 5.0]}"#.to_string(),
+            r#"{"mesh": 0, "translation": [0.0, 0.0, 20.0]}"#.to_string(),
+        ];
+        for i in 0..models_count {
+            nodes.push(format!(
+                r#"{{"mesh": 0, "translation": [{}.0, 0.0, 0.0]}}"#,
+                i
+            ));
+        }
+        let json = format!(
+            r#"{{
+            "asset": {{"version": "2.0"}},
+            "scene": 0,
+            "scenes": [{{"name": "main", "nodes": [{}]}}],
+            "nodes": [{}],
+            "cameras": [{{"type": "perspective",
+                "perspective": {{"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}}}],
+            "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1}}]}}],
+            "accessors": [
+                {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]}},
+                {{"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"}}
+            ],
+            "bufferViews": [
+                {{"buffer": 0, "byteLength": 36}},
+                {{"buffer": 0, "byteOffset": 36, "byteLength": 12}}
+            ],
+            "buffers": [{{"byteLength": 48}}]
+        }}"#,
+            (0..nodes.len())
+                .map(|i| i.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            nodes.join(", ")
+        );
+        let mut data = Vec::new();
+        for f in &[-1.0f32, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let scene_manager = vx_result!(engine.read())
+            .get_asset_manager()
+            .get_scene_manager()
+            .clone();
+        let scene =
+            vx_result!(scene_manager.write()).load_gltf_document::<Game>(&document, &data, "main");
+        vx_result!(engine.write()).update();
+        // Scenes are owned by the application, the engine only keeps a weak
+        // reference to them.
+        std::mem::drop(scene);
+        return engine;
+    }
+
+    // Deferred pass is not ported yet, so the display only has its clear
+    // color after the G-buffer pass of the scene.
+    #[cfg(software_gapi)]
+    #[test]
+    fn scene_frame_test() {
+        let engine = render_scene_frame(gapi::engine::Engine::new_offscreen(8, 4), 2);
+        let mut engine = vx_result!(engine.write());
+        let capture = vx_unwrap!(engine.capture_frame());
+        assert_eq!((capture.get_width(), capture.get_height()), (8, 4));
+        assert!(capture.get_pixels().iter().all(|p| *p == 0));
+    }
+
+    #[cfg(blank_gapi)]
+    #[test]
+    fn scene_gbuffer_draws_count_test() {
+        use crate::render::pipeline::PipelineType;
+        for models_count in 0..4 {
+            let trace = Arc::new(crate::blank_gapi::trace::Trace::new());
+            render_scene_frame(
+                gapi::engine::Engine::new_with_trace(trace.clone(), 320, 240),
+                models_count,
+            );
+            assert_eq!(trace.get_draws_count(PipelineType::GBuffer), models_count);
+            assert_eq!(trace.get_draws_count(PipelineType::Deferred), 0);
+        }
+    }
+
+    #[cfg(blank_gapi)]
+    #[test]
+    fn scene_gbuffer_golden_test() {
+        let trace = Arc::new(crate::blank_gapi::trace::Trace::new());
+        let engine = render_scene_frame(
+            gapi::engine::Engine::new_with_trace(trace.clone(), 320, 240),
+            2,
+        );
+        if let Some(diff) = trace.diff(include_str!("../blank_gapi/goldens/gbuffer.trace")) {
+            panic!(
+                "Trace differs from its golden, {}\n{}",
+                diff,
+                trace.to_text()
+            );
+        }
+        // The dropped scene is forgotten and nothing is recorded for it.
+        trace.clear();
+        vx_result!(engine.write()).update();
+        assert_eq!(
+            trace.get_draws_count(crate::render::pipeline::PipelineType::GBuffer),
+            0
+        );
+    }
+
+    // Same frames of the blank engine golden, but through the renderer.
+    #[cfg(blank_gapi)]
+    #[test]
+    fn blank_frame_golden_test() {
+        use crate::blank_gapi::trace::{Event, Trace};
+        let trace = Arc::new(Trace::new());
+        let mut engine = Engine::new_with_gapi_engine(gapi::engine::Engine::new_with_trace(
+            trace.clone(),
+            320,
+            240,
+        ));
+        engine.update();
+        engine.update();
+        if let Some(diff) = trace.diff(include_str!("../blank_gapi/goldens/empty_frame.trace")) {
+            panic!(
+                "Trace differs from its golden, {}\n{}",
+                diff,
+                trace.to_text()
+            );
+        }
+        trace.clear();
+        let file_name = std::env::temp_dir().join(format!(
+            "vulkust-blank-screenshot-test-{}.png",
+            std::process::id()
+        ));
+        let file_name = vx_unwrap!(file_name.to_str()).to_string();
+        engine.request_screenshot(&file_name);
+        engine.update();
+        vx_result!(std::fs::remove_file(&file_name));
+        match trace.get_events().last() {
+            Some(Event::ReadImage { image }) => assert_eq!(*image, 5),
+            _ => panic!(
+                "Screenshot is not read after the frame.\n{}",
+                trace.to_text()
+            ),
+        }
     }
 }
@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::framebuffer::*;
 pub(crate) use super::super::d3d12::framebuffer::*;
 #[cfg(metal_api)]
 pub(crate) use super::super::metal::framebuffer::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::framebuffer::*;
 #[cfg(vulkan_api)]
 pub(crate) use super::super::vulkan::framebuffer::*;
@@ -5,68 +5,77 @@ use super::gapi::GraphicApiEngine;
 use super::image::{AttachmentType, Format, View as ImageView};
 use super::pipeline::{Pipeline, PipelineType};
 use super::render_pass::RenderPass;
-use super::texture::{Manager as TextureManager, Texture};
-use std::sync::{Arc, RwLock};
+// use super::texture::{Manager as TextureManager, Texture};
+use std::sync::Arc;
+// use std::sync::RwLock;
 
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct GBufferFiller {
-    textures: Vec<Arc<RwLock<dyn Texture>>>,
-    render_pass: Arc<RenderPass>,
+    // Textures of the buffers are read by the deferred and the SSAO passes,
+    // that are not ported yet.
+    // textures: Vec<Arc<RwLock<dyn Texture>>>,
     framebuffer: Arc<Framebuffer>,
     pipeline: Arc<Pipeline>,
+    skinned_pipeline: Arc<Pipeline>,
 }
 
 impl GBufferFiller {
     pub(super) fn new(
         eng: &GraphicApiEngine,
-        texmgr: &mut TextureManager,
+        // texmgr: &mut TextureManager,
         config: &Configurations,
     ) -> Self {
-        let dev = eng.get_device();
         let memmgr = eng.get_memory_manager();
+        let (width, height) = eng.get_surface_dimensions();
         let buffers = vec![
-            Arc::new(ImageView::new_surface_attachment(
-                dev.clone(),
+            Arc::new(ImageView::new_attachment(
                 memmgr,
                 Format::RgbaFloat,
                 AttachmentType::ColorGBuffer,
+                width,
+                height,
             )),
-            Arc::new(ImageView::new_surface_attachment(
-                dev.clone(),
+            Arc::new(ImageView::new_attachment(
                 memmgr,
                 Format::RgbaFloat,
                 AttachmentType::ColorGBuffer,
+                width,
+                height,
             )),
-            Arc::new(ImageView::new_surface_attachment(
-                dev.clone(),
+            Arc::new(ImageView::new_attachment(
                 memmgr,
                 Format::RgbaFloat,
                 AttachmentType::ColorGBuffer,
+                width,
+                height,
             )),
-            Arc::new(ImageView::new_surface_attachment(
-                dev.clone(),
+            Arc::new(ImageView::new_attachment(
                 memmgr,
                 Format::DepthFloat,
                 AttachmentType::DepthGBuffer,
+                width,
+                height,
             )),
         ];
-        let sampler = eng.get_nearest_repeat_sampler();
-        let mut textures = Vec::with_capacity(buffers.len());
-        for b in &buffers {
-            textures.push(texmgr.create_2d_with_view_sampler(b.clone(), sampler.clone()));
-        }
+        // let sampler = eng.get_nearest_repeat_sampler();
+        // let mut textures = Vec::with_capacity(buffers.len());
+        // for b in &buffers {
+        //     textures.push(texmgr.create_2d_with_view_sampler(b.clone(), sampler.clone()));
+        // }
         let render_pass = Arc::new(RenderPass::new(buffers.clone(), true, true));
         let framebuffer = Arc::new(Framebuffer::new(buffers, render_pass.clone()));
-        let pipeline = vx_result!(eng.get_pipeline_manager().write()).create(
-            render_pass.clone(),
-            PipelineType::GBuffer,
-            config,
-        );
+        let (pipeline, skinned_pipeline) = {
+            let mut pipmgr = vx_result!(eng.get_pipeline_manager().write());
+            (
+                pipmgr.create(render_pass.clone(), PipelineType::GBuffer, config),
+                pipmgr.create(render_pass.clone(), PipelineType::SkinnedGBuffer, config),
+            )
+        };
+        // Framebuffer keeps the render pass.
         Self {
-            textures,
-            render_pass,
             framebuffer,
             pipeline,
+            skinned_pipeline,
         }
     }
 
@@ -75,29 +84,34 @@ impl GBufferFiller {
         cmd.bind_pipeline(&self.pipeline);
     }
 
-    pub(super) fn begin_primary(&self, cmd: &mut CmdBuffer) {
-        self.framebuffer.begin(cmd);
-    }
-
-    pub(super) fn get_textures(&self) -> &Vec<Arc<RwLock<dyn Texture>>> {
-        return &self.textures;
-    }
-
-    pub(super) fn get_normal_texture(&self) -> &Arc<RwLock<dyn Texture>> {
-        return &self.textures[1];
-    }
-
-    pub(super) fn get_position_texture(&self) -> &Arc<RwLock<dyn Texture>> {
-        return &self.textures[0];
+    pub(super) fn bind_skinned_pipeline(&self, cmd: &mut CmdBuffer) {
+        cmd.bind_pipeline(&self.skinned_pipeline);
     }
 
-    pub(super) fn get_depth_texture(&self) -> &Arc<RwLock<dyn Texture>> {
-        return &self.textures[3];
+    pub(super) fn begin_primary(&self, cmd: &mut CmdBuffer) {
+        self.framebuffer.begin(cmd);
     }
 
-    pub(super) fn get_framebuffer(&self) -> &Framebuffer {
-        return &self.framebuffer;
-    }
+    // Deferred and SSAO passes read them, they are not ported yet.
+    // pub(super) fn get_textures(&self) -> &Vec<Arc<RwLock<dyn Texture>>> {
+    //     return &self.textures;
+    // }
+    //
+    // pub(super) fn get_normal_texture(&self) -> &Arc<RwLock<dyn Texture>> {
+    //     return &self.textures[1];
+    // }
+    //
+    // pub(super) fn get_position_texture(&self) -> &Arc<RwLock<dyn Texture>> {
+    //     return &self.textures[0];
+    // }
+    //
+    // pub(super) fn get_depth_texture(&self) -> &Arc<RwLock<dyn Texture>> {
+    //     return &self.textures[3];
+    // }
+    //
+    // pub(super) fn get_framebuffer(&self) -> &Framebuffer {
+    //     return &self.framebuffer;
+    // }
 }
 
 unsafe impl Send for GBufferFiller {}
@@ -1,5 +1,7 @@
 #[cfg(directx12_api)]
 pub use super::super::d3d12::engine::Engine as GraphicApiEngine;
+#[cfg(software_gapi)]
+pub use super::super::software::engine::Engine as GraphicApiEngine;
 #[cfg(vulkan_api)]
 pub use super::super::vulkan::engine::Engine as GraphicApiEngine;
 // maybe a day I forced to implement with other API
@@ -0,0 +1,239 @@
+use super::super::core::types::Real;
+use super::super::math::vector::Vec4;
+use gltf;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum AlphaMode {
+    Opaque,
+    Mask { cutoff: Real },
+    Blend,
+}
+
+/// The parameters of a glTF metallic roughness material, textures are left
+/// to the caller to load.
+/// Normal and occlusion textures use the uv transform of base color,
+/// because glTF does not give theirs.
+pub struct Material<'a> {
+    pub alpha_mode: AlphaMode,
+    pub is_unlit: bool,
+    pub is_double_sided: bool,
+    pub base_color: [u8; 4],
+    pub base_color_texture: Option<gltf::Texture<'a>>,
+    pub base_color_uv_transform: [Vec4; 2],
+    pub metallic_factor: Real,
+    pub roughness_factor: Real,
+    pub metallic_roughness_texture: Option<gltf::Texture<'a>>,
+    pub metallic_roughness_uv_transform: [Vec4; 2],
+    pub normal_scale: Real,
+    pub normal_texture: Option<gltf::Texture<'a>>,
+    pub occlusion_strength: Real,
+    pub occlusion_texture: Option<gltf::Texture<'a>>,
+    pub emissive: [u8; 4],
+    pub emissive_strength: Real,
+    pub emissive_texture: Option<gltf::Texture<'a>>,
+    pub emissive_uv_transform: [Vec4; 2],
+}
+
+impl<'a> Material<'a> {
+    pub fn new_with_gltf(mat: &gltf::Material<'a>) -> Self {
+        let alpha_mode = match mat.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask {
+                cutoff: mat.alpha_cutoff().unwrap_or(0.5),
+            },
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        };
+        let identity = get_uv_transform([0.0, 0.0], 0.0, [1.0, 1.0]);
+        let pbr = mat.pbr_metallic_roughness();
+        let (base_color_texture, base_color_uv_transform) = match pbr.base_color_texture() {
+            Some(info) => (Some(info.texture()), get_gltf_uv_transform(&info)),
+            None => (None, identity),
+        };
+        let (metallic_roughness_texture, metallic_roughness_uv_transform) =
+            match pbr.metallic_roughness_texture() {
+                Some(info) => (Some(info.texture()), get_gltf_uv_transform(&info)),
+                None => (None, identity),
+            };
+        let (normal_texture, normal_scale) = match mat.normal_texture() {
+            Some(n) => (Some(n.texture()), n.scale()),
+            None => (None, 1.0),
+        };
+        let (occlusion_texture, occlusion_strength) = match mat.occlusion_texture() {
+            Some(o) => (Some(o.texture()), o.strength()),
+            None => (None, 1.0),
+        };
+        let (emissive_texture, emissive_uv_transform) = match mat.emissive_texture() {
+            Some(info) => (Some(info.texture()), get_gltf_uv_transform(&info)),
+            None => (None, identity),
+        };
+        let e = mat.emissive_factor();
+        return Self {
+            alpha_mode,
+            is_unlit: mat.unlit(),
+            is_double_sided: mat.double_sided(),
+            base_color: convert_color(pbr.base_color_factor()),
+            base_color_texture,
+            base_color_uv_transform,
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            metallic_roughness_texture,
+            metallic_roughness_uv_transform,
+            normal_scale,
+            normal_texture,
+            occlusion_strength,
+            occlusion_texture,
+            emissive: convert_color([e[0], e[1], e[2], 1.0]),
+            emissive_strength: mat.emissive_strength().unwrap_or(1.0),
+            emissive_texture,
+            emissive_uv_transform,
+        };
+    }
+}
+
+/// Rows of the uv matrix of KHR_texture_transform, that is translation *
+/// rotation * scale.
+pub fn get_uv_transform(offset: [Real; 2], rotation: Real, scale: [Real; 2]) -> [Vec4; 2] {
+    let (sin, cos) = rotation.sin_cos();
+    return [
+        Vec4::new(cos * scale[0], sin * scale[1], offset[0], 0.0),
+        Vec4::new(-sin * scale[0], cos * scale[1], offset[1], 0.0),
+    ];
+}
+
+fn get_gltf_uv_transform(info: &gltf::texture::Info) -> [Vec4; 2] {
+    let mut tex_coord = info.tex_coord();
+    let transform = match info.texture_transform() {
+        Some(t) => {
+            if let Some(t) = t.tex_coord() {
+                tex_coord = t;
+            }
+            get_uv_transform(t.offset(), t.rotation(), t.scale())
+        }
+        None => get_uv_transform([0.0, 0.0], 0.0, [1.0, 1.0]),
+    };
+    if tex_coord != 0 {
+        vx_log_e!("Only the first uv set of meshes is supported.");
+    }
+    return transform;
+}
+
+/// Linear color factors to the pixels of one pixel textures.
+pub fn convert_color(c: [Real; 4]) -> [u8; 4] {
+    let convert = |c: Real| (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    return [convert(c[0]), convert(c[1]), convert(c[2]), convert(c[3])];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn transform(t: &[Vec4; 2], u: Real, v: Real) -> (Real, Real) {
+        let uv = Vec4::new(u, v, 1.0, 0.0);
+        return (t[0].dot(uv), t[1].dot(uv));
+    }
+
+    #[test]
+    fn uv_transform_test() {
+        let identity = get_uv_transform([0.0, 0.0], 0.0, [1.0, 1.0]);
+        assert_eq!(transform(&identity, 0.25, 0.75), (0.25, 0.75));
+        let t = get_uv_transform([0.5, 0.25], 0.0, [2.0, 4.0]);
+        assert_eq!(transform(&t, 1.0, 1.0), (2.5, 4.25));
+        // A quarter of counter-clockwise turn maps u to -v, since v goes
+        // down in glTF.
+        let t = get_uv_transform([0.0, 0.0], std::f32::consts::FRAC_PI_2, [1.0, 1.0]);
+        let (u, v) = transform(&t, 1.0, 0.0);
+        assert!(u.abs() < 1e-6 && (v + 1.0).abs() < 1e-6);
+        assert!(convert_color([0.0, 0.5, 1.0, 2.0]) == [0, 128, 255, 255]);
+    }
+
+    #[test]
+    fn gltf_material_test() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_materials_emissive_strength",
+                "KHR_materials_unlit", "KHR_texture_transform"],
+            "images": [{"uri": "a.png"}, {"uri": "b.png"}],
+            "textures": [{"source": 0}, {"source": 1}],
+            "materials": [
+                {},
+                {"alphaMode": "MASK", "doubleSided": true,
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": [1.0, 0.5, 0.0, 1.0],
+                        "baseColorTexture": {"index": 0, "extensions": {
+                            "KHR_texture_transform": {"offset": [0.5, 0.25],
+                                "scale": [2.0, 4.0]}}},
+                        "metallicFactor": 0.25,
+                        "roughnessFactor": 0.75,
+                        "metallicRoughnessTexture": {"index": 1}},
+                    "normalTexture": {"index": 1, "scale": 0.5},
+                    "occlusionTexture": {"index": 1, "strength": 0.25},
+                    "emissiveFactor": [1.0, 1.0, 0.0],
+                    "emissiveTexture": {"index": 0}},
+                {"alphaMode": "MASK", "alphaCutoff": 0.25,
+                    "extensions": {"KHR_materials_emissive_strength":
+                        {"emissiveStrength": 5.0}}},
+                {"alphaMode": "BLEND", "extensions": {"KHR_materials_unlit": {}}}
+            ]
+        }"#;
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let materials: Vec<Material> = document
+            .materials()
+            .map(|m| Material::new_with_gltf(&m))
+            .collect();
+        // The defaults of glTF.
+        let m = &materials[0];
+        match m.alpha_mode {
+            AlphaMode::Opaque => {}
+            _ => vx_unexpected!(),
+        }
+        assert!(!m.is_unlit && !m.is_double_sided);
+        assert!(m.base_color == [255, 255, 255, 255]);
+        assert!(m.emissive == [0, 0, 0, 255]);
+        assert_eq!((m.metallic_factor, m.roughness_factor), (1.0, 1.0));
+        assert_eq!((m.normal_scale, m.occlusion_strength), (1.0, 1.0));
+        assert_eq!(m.emissive_strength, 1.0);
+        assert!(m.base_color_texture.is_none());
+        assert!(m.metallic_roughness_texture.is_none());
+        assert!(m.normal_texture.is_none());
+        assert!(m.occlusion_texture.is_none());
+        assert!(m.emissive_texture.is_none());
+        assert_eq!(
+            transform(&m.base_color_uv_transform, 0.25, 0.75),
+            (0.25, 0.75)
+        );
+
+        let m = &materials[1];
+        match m.alpha_mode {
+            AlphaMode::Mask { cutoff } => assert_eq!(cutoff, 0.5),
+            _ => vx_unexpected!(),
+        }
+        assert!(m.is_double_sided);
+        assert!(m.base_color == [255, 128, 0, 255]);
+        assert!(m.emissive == [255, 255, 0, 255]);
+        assert_eq!((m.metallic_factor, m.roughness_factor), (0.25, 0.75));
+        assert_eq!((m.normal_scale, m.occlusion_strength), (0.5, 0.25));
+        let index = |t: &Option<gltf::Texture>| vx_unwrap!(t.as_ref()).source().index();
+        assert_eq!(index(&m.base_color_texture), 0);
+        assert_eq!(index(&m.metallic_roughness_texture), 1);
+        assert_eq!(index(&m.normal_texture), 1);
+        assert_eq!(index(&m.occlusion_texture), 1);
+        assert_eq!(index(&m.emissive_texture), 0);
+        assert_eq!(transform(&m.base_color_uv_transform, 1.0, 1.0), (2.5, 4.25));
+        assert_eq!(transform(&m.emissive_uv_transform, 1.0, 1.0), (1.0, 1.0));
+
+        let m = &materials[2];
+        match m.alpha_mode {
+            AlphaMode::Mask { cutoff } => assert_eq!(cutoff, 0.25),
+            _ => vx_unexpected!(),
+        }
+        assert_eq!(m.emissive_strength, 5.0);
+
+        let m = &materials[3];
+        match m.alpha_mode {
+            AlphaMode::Blend => {}
+            _ => vx_unexpected!(),
+        }
+        assert!(m.is_unlit);
+    }
+}
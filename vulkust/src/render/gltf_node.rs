@@ -0,0 +1,207 @@
+use super::super::core::types::Real;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+use gltf;
+use std::f32::consts::PI;
+
+/// Luminous efficacy that turns the photometric intensities of glTF into the
+/// radiometric strengths of the engine (the same watts as gx3d).
+const LUMENS_PER_WATT: Real = 683.0;
+
+/// Location, orientation and scale of the node relative to its parent.
+pub fn read_transform(node: &gltf::Node) -> (Vec3, Quat, Vec3) {
+    let (l, r, s) = node.transform().decomposed();
+    return (
+        Vec3::from_array(l),
+        Quat::new(r[0], r[1], r[2], r[3]),
+        Vec3::from_array(s),
+    );
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Projection {
+    Perspective {
+        fov_y: Real,
+    },
+    /// Height is the full height of the view volume.
+    Orthographic {
+        height: Real,
+    },
+}
+
+/// Projection of a glTF camera, the missing values are left to the engine.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Camera {
+    pub projection: Projection,
+    pub near: Real,
+    /// Infinite perspective cameras do not have it.
+    pub far: Option<Real>,
+    /// Overrides the aspect ratio of the window.
+    pub aspect_ratio: Option<Real>,
+}
+
+impl Camera {
+    pub fn new_with_gltf(camera: &gltf::Camera) -> Self {
+        return match camera.projection() {
+            gltf::camera::Projection::Perspective(p) => Self {
+                projection: Projection::Perspective { fov_y: p.yfov() },
+                near: p.znear(),
+                far: p.zfar(),
+                aspect_ratio: p.aspect_ratio(),
+            },
+            // Magnifications are the half of the sizes.
+            gltf::camera::Projection::Orthographic(p) => Self {
+                projection: Projection::Orthographic {
+                    height: p.ymag() * 2.0,
+                },
+                near: p.znear(),
+                far: Some(p.zfar()),
+                aspect_ratio: Some(p.xmag() / p.ymag()),
+            },
+        };
+    }
+}
+
+/// Horizontal field of view of a vertical one.
+pub fn get_fov_x(fov_y: Real, aspect_ratio: Real) -> Real {
+    return ((fov_y * 0.5).tan() * aspect_ratio).atan() * 2.0;
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum LightKind {
+    Directional,
+    Point,
+    /// Angles are from the direction of the light.
+    Spot {
+        inner_cone_angle: Real,
+        outer_cone_angle: Real,
+    },
+}
+
+/// A KHR_lights_punctual light in the units of the engine.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Vec3,
+    pub strength: Real,
+    pub range: Option<Real>,
+}
+
+impl Light {
+    /// glTF directional lights are in lux and the others in candela.
+    pub fn new_with_gltf(light: &gltf::khr_lights_punctual::Light) -> Self {
+        let (kind, strength) = match light.kind() {
+            gltf::khr_lights_punctual::Kind::Directional => {
+                (LightKind::Directional, light.intensity() / LUMENS_PER_WATT)
+            }
+            gltf::khr_lights_punctual::Kind::Point => (
+                LightKind::Point,
+                light.intensity() * 4.0 * PI / LUMENS_PER_WATT,
+            ),
+            gltf::khr_lights_punctual::Kind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => (
+                LightKind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                },
+                light.intensity() * 4.0 * PI / LUMENS_PER_WATT,
+            ),
+        };
+        return Self {
+            kind,
+            color: Vec3::from_array(light.color()),
+            strength,
+            range: light.range(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gltf_node_test() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {"KHR_lights_punctual": {"lights": [
+                {"type": "directional", "intensity": 683.0},
+                {"type": "point", "color": [1.0, 0.5, 0.25], "intensity": 683.0,
+                    "range": 10.0},
+                {"type": "spot", "spot": {"innerConeAngle": 0.25, "outerConeAngle": 0.5}}
+            ]}},
+            "cameras": [
+                {"type": "perspective", "perspective": {"yfov": 1.0, "znear": 0.1}},
+                {"type": "orthographic", "orthographic":
+                    {"xmag": 4.0, "ymag": 2.0, "znear": 0.5, "zfar": 50.0}}
+            ],
+            "nodes": [
+                {"camera": 0, "translation": [1.0, 2.0, 3.0],
+                    "rotation": [0.0, 0.7071068, 0.0, 0.7071068]},
+                {"camera": 1, "scale": [2.0, 2.0, 2.0]},
+                {"extensions": {"KHR_lights_punctual": {"light": 0}}},
+                {"extensions": {"KHR_lights_punctual": {"light": 1}}},
+                {"extensions": {"KHR_lights_punctual": {"light": 2}}}
+            ]
+        }"#;
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let nodes: Vec<gltf::Node> = document.nodes().collect();
+        let (l, r, s) = read_transform(&nodes[0]);
+        assert!(l == Vec3::new(1.0, 2.0, 3.0));
+        // The quaternion of glTF is x, y, z, w.
+        let forward = r.rotate(Vec3::new(0.0, 0.0, -1.0));
+        assert!((forward - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-5);
+        assert!(s == Vec3::splat(1.0));
+        assert!(read_transform(&nodes[1]).2 == Vec3::splat(2.0));
+
+        let c = Camera::new_with_gltf(&vx_unwrap!(nodes[0].camera()));
+        match c.projection {
+            Projection::Perspective { fov_y } => assert_eq!(fov_y, 1.0),
+            _ => vx_unexpected!(),
+        }
+        assert_eq!(c.near, 0.1);
+        assert!(c.far.is_none());
+        assert!(c.aspect_ratio.is_none());
+        let c = Camera::new_with_gltf(&vx_unwrap!(nodes[1].camera()));
+        match c.projection {
+            Projection::Orthographic { height } => assert_eq!(height, 4.0),
+            _ => vx_unexpected!(),
+        }
+        assert_eq!(c.far, Some(50.0));
+        assert_eq!(c.aspect_ratio, Some(2.0));
+        let fov_x = get_fov_x(PI * 0.5, 2.0);
+        assert!(((fov_x * 0.5).tan() - 2.0).abs() < 1e-5);
+
+        let lights: Vec<Light> = nodes[2..]
+            .iter()
+            .map(|n| Light::new_with_gltf(&vx_unwrap!(n.light())))
+            .collect();
+        match lights[0].kind {
+            LightKind::Directional => {}
+            _ => vx_unexpected!(),
+        }
+        // One lux of sun light and one candela in every direction of a lamp.
+        assert!((lights[0].strength - 1.0).abs() < 1e-5);
+        assert!(lights[0].color == Vec3::splat(1.0));
+        assert!(lights[0].range.is_none());
+        assert!((lights[1].strength - 4.0 * PI).abs() < 1e-4);
+        assert!(lights[1].color == Vec3::new(1.0, 0.5, 0.25));
+        assert_eq!(lights[1].range, Some(10.0));
+        match lights[2].kind {
+            LightKind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => assert_eq!((inner_cone_angle, outer_cone_angle), (0.25, 0.5)),
+            _ => vx_unexpected!(),
+        }
+        // The default intensity is one candela.
+        assert!((lights[2].strength * LUMENS_PER_WATT - 4.0 * PI).abs() < 1e-3);
+    }
+}
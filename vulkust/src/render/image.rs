@@ -4,8 +4,10 @@ pub(crate) use super::super::blank_gapi::image::*;
 pub use super::super::d3d12::image::*;
 #[cfg(metal_api)]
 pub use super::super::metal::image::*;
-#[cfg(vulkan_api)]
-pub use super::super::vulkan::image::*;
+#[cfg(software_gapi)]
+pub use super::super::software::image::*;
+// #[cfg(vulkan_api)]
+// pub use super::super::vulkan::image::*;
 
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum AttachmentType {
@@ -15,6 +17,7 @@ pub enum AttachmentType {
     ColorDisplay,
     DepthStencilDisplay,
     ShadowAccumulator,
+    ColorOffscreen,
 }
 
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -28,6 +31,7 @@ pub enum Format {
     FlagBits64,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum Layout {
     Uninitialized,
@@ -42,9 +46,70 @@ pub enum Usage {
     DepthStencil,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum ImageType {
     Cube,
+    CubeArray,
     D2,
+    D2Array,
     D3,
 }
+
+/// Formats of the textures that come from the containers, the sRGB variants
+/// are loaded as their UNORM ones like the other 8-bit textures.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum TextureFormat {
+    RgbaByte,
+    Bc1Rgb,
+    Bc1Rgba,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc4Signed,
+    Bc5,
+    Bc5Signed,
+    Bc6h,
+    Bc6hSigned,
+    Bc7,
+    Etc2Rgb,
+    Etc2RgbA1,
+    Etc2Rgba,
+    /// Block width and height
+    Astc(u8, u8),
+}
+
+impl TextureFormat {
+    pub fn is_compressed(&self) -> bool {
+        return *self != TextureFormat::RgbaByte;
+    }
+
+    pub fn get_block_dimensions(&self) -> (u32, u32) {
+        match self {
+            TextureFormat::RgbaByte => return (1, 1),
+            TextureFormat::Astc(w, h) => return (*w as u32, *h as u32),
+            _ => return (4, 4),
+        }
+    }
+
+    pub fn get_block_size(&self) -> usize {
+        match self {
+            TextureFormat::RgbaByte => return 4,
+            TextureFormat::Bc1Rgb
+            | TextureFormat::Bc1Rgba
+            | TextureFormat::Bc4
+            | TextureFormat::Bc4Signed
+            | TextureFormat::Etc2Rgb
+            | TextureFormat::Etc2RgbA1 => return 8,
+            _ => return 16,
+        }
+    }
+
+    /// Size of an image of this format in bytes.
+    pub fn get_image_size(&self, width: u32, height: u32) -> usize {
+        let (bw, bh) = self.get_block_dimensions();
+        let blocks = ((width + bw - 1) / bw) * ((height + bh - 1) / bh);
+        return blocks as usize * self.get_block_size();
+    }
+}
@@ -1,18 +1,25 @@
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::Object as CoreObject;
 use super::super::super::core::types::{Id, Real};
-use super::super::config::MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT;
+// use super::super::super::math::matrix::Mat4;
+use super::super::super::math::quaternion::Quat;
+use super::super::super::math::vector::{Vec3, Vec4};
+// use super::super::config::MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT;
 use super::super::engine::Engine;
+use super::super::gltf_node::{
+    read_transform as read_gltf_transform, Light as GltfLight, LightKind as GltfLightKind,
+};
 use super::super::object::{Base as ObjectBase, Loadable, Object, Transferable};
-use super::{DefaultLighting, Light, Point, ShadowMaker, Sun};
-
-use cgmath;
+// use super::Sun;
+use super::{DefaultLighting, Light, Point, ShadowMaker, Spot};
 
 pub trait Directional: Light {
-    fn to_sun(&self) -> Option<&Sun>;
-    fn to_mut_sun(&mut self) -> Option<&mut Sun>;
+    // Sun waits for the shadower, that is not ported yet.
+    // fn to_sun(&self) -> Option<&Sun>;
+    // fn to_mut_sun(&mut self) -> Option<&mut Sun>;
     fn to_base(&self) -> Option<&Base>;
     fn to_mut_base(&mut self) -> Option<&mut Base>;
+    fn get_orientation(&self) -> &Quat;
     fn update_uniform(&self, uniform: &mut DirectionalUniform);
 }
 
@@ -20,48 +27,49 @@ pub trait Directional: Light {
 #[derive(Clone, Copy)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct DirectionalUniform {
-    pub(super) color: cgmath::Vector4<Real>,
-    pub(super) direction: cgmath::Vector4<Real>,
+    pub(super) color: Vec4,
+    pub(super) direction: Vec4,
 }
 
 impl DirectionalUniform {
     pub(crate) fn new() -> Self {
         Self {
-            color: cgmath::Vector4::new(1.0, 1.0, 1.0, 1.0),
-            direction: cgmath::Vector4::new(0.0, 0.0, -1.0, 1.0),
+            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            direction: Vec4::new(0.0, 0.0, -1.0, 1.0),
         }
     }
 }
 
-#[repr(C)]
-#[derive(Clone, Copy)]
-#[cfg_attr(debug_mode, derive(Debug))]
-pub struct ShadowAccumulatorDirectionalUniform {
-    pub(super) view_projection_biases:
-        [cgmath::Matrix4<Real>; MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT as usize],
-    pub(super) direction_strength: cgmath::Vector4<Real>,
-    pub(super) cascades_count: u32,
-    pub(super) light_index: u32,
-}
-
-impl ShadowAccumulatorDirectionalUniform {
-    pub(super) fn new() -> Self {
-        Self {
-            view_projection_biases: [cgmath::Matrix4::new(
-                1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
-            ); MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT as usize],
-            direction_strength: cgmath::Vector4::new(0.0, 0.0, -1.0, 1.0),
-            cascades_count: 0,
-            light_index: 0,
-        }
-    }
-}
+// Shadow accumulator waits for the shadower, that is not ported yet.
+// #[repr(C)]
+// #[derive(Clone, Copy)]
+// #[cfg_attr(debug_mode, derive(Debug))]
+// pub struct ShadowAccumulatorDirectionalUniform {
+//     pub(super) view_projection_biases: [Mat4; MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT as usize],
+//     pub(super) direction_strength: Vec4,
+//     pub(super) cascades_count: u32,
+//     pub(super) light_index: u32,
+// }
+//
+// impl ShadowAccumulatorDirectionalUniform {
+//     pub(super) fn new() -> Self {
+//         Self {
+//             view_projection_biases: [Mat4::from_cols_array(&[
+//                 1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 1.0,
+//             ]); MAX_DIRECTIONAL_CASCADES_MATRIX_COUNT as usize],
+//             direction_strength: Vec4::new(0.0, 0.0, -1.0, 1.0),
+//             cascades_count: 0,
+//             light_index: 0,
+//         }
+//     }
+// }
 
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Base {
     obj_base: ObjectBase,
-    direction: cgmath::Vector3<Real>,
-    color: cgmath::Vector3<Real>,
+    orientation: Quat,
+    direction: Vec3,
+    color: Vec3,
     strength: Real,
 }
 
@@ -73,8 +81,9 @@ impl Base {
     fn new_with_obj_base(obj_base: ObjectBase) -> Self {
         Self {
             obj_base,
-            direction: cgmath::Vector3::new(0.0, 0.0, -1.0),
-            color: cgmath::Vector3::new(1.0, 1.0, 1.0),
+            orientation: Quat::identity(),
+            direction: Vec3::new(0.0, 0.0, -1.0),
+            color: Vec3::new(1.0, 1.0, 1.0),
             strength: 1.0,
         }
     }
@@ -93,7 +102,6 @@ impl Object for Base {
 
     fn set_name(&mut self, name: &str) {
         self.obj_base.set_name(name);
-        vx_unimplemented!(); //it must update corresponding manager
     }
 
     fn disable_rendering(&mut self) {
@@ -110,16 +118,16 @@ impl Object for Base {
 }
 
 impl Transferable for Base {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
-        let rotation = cgmath::Matrix3::from(*q);
-        self.direction = rotation * cgmath::Vector3::new(0.0, 0.0, -1.0);
+    fn set_orientation(&mut self, q: &Quat) {
+        self.orientation = q.normalized();
+        self.direction = self.orientation.rotate(Vec3::new(0.0, 0.0, -1.0));
     }
 
-    fn set_location(&mut self, _: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, _: &Vec3) {
         vx_unexpected!();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         vx_unexpected!();
     }
 
@@ -131,15 +139,17 @@ impl Transferable for Base {
         vx_unexpected!();
     }
 
-    fn rotate_local_x(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn rotate_local_x(&mut self, v: Real) {
+        let q = self.orientation * Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), v);
+        self.set_orientation(&q);
     }
 
-    fn rotate_global_z(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn rotate_global_z(&mut self, v: Real) {
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), v) * self.orientation;
+        self.set_orientation(&q);
     }
 
-    fn translate(&mut self, _: &cgmath::Vector3<Real>) {
+    fn translate(&mut self, _: &Vec3) {
         vx_unexpected!();
     }
 
@@ -165,6 +175,14 @@ impl Light for Base {
         return None;
     }
 
+    fn to_spot(&self) -> Option<&dyn Spot> {
+        return None;
+    }
+
+    fn to_mut_spot(&mut self) -> Option<&mut dyn Spot> {
+        return None;
+    }
+
     fn to_shadow_maker(&self) -> Option<&dyn ShadowMaker> {
         return None;
     }
@@ -173,47 +191,73 @@ impl Light for Base {
         return None;
     }
 
+    fn get_color(&self) -> &Vec3 {
+        return &self.color;
+    }
+
+    fn set_color(&mut self, color: &Vec3) {
+        self.color = *color;
+    }
+
+    fn get_strength(&self) -> Real {
+        return self.strength;
+    }
+
+    fn set_strength(&mut self, strength: Real) {
+        self.strength = strength;
+    }
+
     fn update(&mut self) {}
 }
 
 impl Loadable for Base {
-    fn new_with_gltf(_node: &gltf::Node, _eng: &Engine, _: &[u8]) -> Self {
-        vx_unimplemented!();
-    }
-
-    fn new_with_gx3d(_: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
-        let mut myself = Self::new_with_obj_base(ObjectBase::new_with_id(id));
-        let r = [
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-        ];
-        myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
-        myself.color = cgmath::Vector3::new(
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-        );
-        myself.strength = reader.read::<Real>();
-        #[cfg(debug_gx3d)]
-        {
-            vx_log_i!("Direction {:?}", &myself.direction);
-            vx_log_i!("Color {:?}", &myself.color);
-            vx_log_i!("Strength {:?}", &myself.strength);
+    fn new_with_gltf(node: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
+        let light = GltfLight::new_with_gltf(&vx_unwrap!(node.light()));
+        match light.kind {
+            GltfLightKind::Directional => (),
+            _ => vx_log_f!("Type of light isn't directional."),
         }
+        let mut myself = Self::new_with_obj_base(ObjectBase::new_with_gltf(node, eng, data));
+        let (_, r, _) = read_gltf_transform(node);
+        myself.set_orientation(&r);
+        myself.color = light.color;
+        myself.strength = light.strength;
         return myself;
     }
+
+    // fn new_with_gx3d(_: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
+    //     let mut myself = Self::new_with_obj_base(ObjectBase::new_with_id(id));
+    //     let r = [
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //     ];
+    //     myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
+    //     myself.color = cgmath::Vector3::new(
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //     );
+    //     myself.strength = reader.read::<Real>();
+    //     #[cfg(debug_gx3d)]
+    //     {
+    //         vx_log_i!("Direction {:?}", &myself.direction);
+    //         vx_log_i!("Color {:?}", &myself.color);
+    //         vx_log_i!("Strength {:?}", &myself.strength);
+    //     }
+    //     return myself;
+    // }
 }
 
 impl Directional for Base {
-    fn to_sun(&self) -> Option<&Sun> {
-        return None;
-    }
+    // fn to_sun(&self) -> Option<&Sun> {
+    //     return None;
+    // }
 
-    fn to_mut_sun(&mut self) -> Option<&mut Sun> {
-        return None;
-    }
+    // fn to_mut_sun(&mut self) -> Option<&mut Sun> {
+    //     return None;
+    // }
 
     fn to_base(&self) -> Option<&Base> {
         return Some(self);
@@ -223,6 +267,10 @@ impl Directional for Base {
         return Some(self);
     }
 
+    fn get_orientation(&self) -> &Quat {
+        return &self.orientation;
+    }
+
     fn update_uniform(&self, u: &mut DirectionalUniform) {
         u.color = (self.color * self.strength).extend(1.0);
         u.direction = self.direction.extend(self.strength);
@@ -1,8 +1,9 @@
-use super::super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
+// use super::super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
 use super::super::super::core::types::Id;
 use super::super::engine::Engine;
 use super::super::object::Loadable;
-use super::{DefaultLighting, DirectionalBase, Light, PointBase, Sun, TypeId};
+// use super::{Sun, TypeId};
+use super::{DefaultLighting, DirectionalBase, Light, PointBase, SpotBase};
 use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock, Weak};
 
@@ -11,7 +12,8 @@ pub struct Manager {
     engine: Option<Weak<RwLock<Engine>>>,
     lights: BTreeMap<Id, Weak<RwLock<dyn Light>>>,
     name_to_id: BTreeMap<String, Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // Gx3D reader is not ported yet.
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -20,13 +22,12 @@ impl Manager {
             engine: None,
             lights: BTreeMap::new(),
             name_to_id: BTreeMap::new(),
-            gx3d_table: None,
         }
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub fn create<L>(&mut self) -> Arc<RwLock<L>>
     where
@@ -42,36 +43,130 @@ impl Manager {
         return result;
     }
 
-    pub fn load_gx3d(&mut self, eng: &Engine, id: Id) -> Arc<RwLock<dyn Light>> {
-        if let Some(light) = self.lights.get(&id) {
-            if let Some(light) = light.upgrade() {
-                return light;
+    /// glTF directional lights become directional bases until the suns, that
+    /// cast shadows, are ported.
+    pub fn load_gltf(
+        &mut self,
+        n: &gltf::Node,
+        eng: &Engine,
+        data: &[u8],
+    ) -> Arc<RwLock<dyn Light>> {
+        vx_profile_zone!("light::load_gltf");
+        let light = vx_unwrap!(n.light());
+        let result: Arc<RwLock<dyn Light>> = match light.kind() {
+            gltf::khr_lights_punctual::Kind::Directional => {
+                Arc::new(RwLock::new(DirectionalBase::new_with_gltf(n, eng, data)))
             }
-        }
-        let table = vx_unwrap!(&mut self.gx3d_table);
-        table.goto(id);
-        let reader: &mut Gx3DReader = table.get_mut_reader();
-        let type_id = reader.read_type_id();
-        let result: Arc<RwLock<dyn Light>> = if type_id == TypeId::Sun as u8 {
-            if reader.read_bool() {
-                Arc::new(RwLock::new(Sun::new_with_gx3d(eng, reader, id)))
-            } else {
-                Arc::new(RwLock::new(DirectionalBase::new_with_gx3d(eng, reader, id)))
+            gltf::khr_lights_punctual::Kind::Point => {
+                Arc::new(RwLock::new(PointBase::new_with_gltf(n, eng, data)))
             }
-        } else if type_id == TypeId::Lamp as u8 {
-            if reader.read_bool() {
-                vx_unimplemented!();
-            } else {
-                Arc::new(RwLock::new(PointBase::new_with_gx3d(eng, reader, id)))
+            gltf::khr_lights_punctual::Kind::Spot { .. } => {
+                Arc::new(RwLock::new(SpotBase::new_with_gltf(n, eng, data)))
             }
-        } else {
-            vx_unexpected!();
         };
+        let id = vx_result!(result.read()).get_id();
         self.lights.insert(id, Arc::downgrade(&result));
+        if let Some(name) = n.name() {
+            self.name_to_id.insert(name.to_string(), id);
+        }
         return result;
     }
 
+    // Gx3D reader is not ported yet.
+    // pub fn load_gx3d(&mut self, eng: &Engine, id: Id) -> Arc<RwLock<dyn Light>> {
+    //     vx_profile_zone!("light::load_gx3d");
+    //     if let Some(light) = self.lights.get(&id) {
+    //         if let Some(light) = light.upgrade() {
+    //             return light;
+    //         }
+    //     }
+    //     let table = vx_unwrap!(&mut self.gx3d_table);
+    //     table.goto(id);
+    //     let reader: &mut Gx3DReader = table.get_mut_reader();
+    //     let type_id = reader.read_type_id();
+    //     let result: Arc<RwLock<dyn Light>> = if type_id == TypeId::Sun as u8 {
+    //         if reader.read_bool() {
+    //             Arc::new(RwLock::new(Sun::new_with_gx3d(eng, reader, id)))
+    //         } else {
+    //             Arc::new(RwLock::new(DirectionalBase::new_with_gx3d(eng, reader, id)))
+    //         }
+    //     } else if type_id == TypeId::Lamp as u8 {
+    //         if reader.read_bool() {
+    //             vx_unimplemented!();
+    //         } else {
+    //             Arc::new(RwLock::new(PointBase::new_with_gx3d(eng, reader, id)))
+    //         }
+    //     } else {
+    //         vx_unexpected!();
+    //     };
+    //     self.lights.insert(id, Arc::downgrade(&result));
+    //     return result;
+    // }
+
     pub(crate) fn set_engine(&mut self, e: Weak<RwLock<Engine>>) {
         self.engine = Some(e);
     }
 }
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::super::super::super::math::vector::{Vec3, Vec4};
+    use super::super::DirectionalUniform;
+    use super::*;
+
+    #[test]
+    fn gltf_test() {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        let engine = Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+        let engine = vx_result!(engine.read());
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {"KHR_lights_punctual": {"lights": [
+                {"type": "directional", "intensity": 683.0},
+                {"type": "point", "intensity": 683.0},
+                {"type": "spot", "spot": {"innerConeAngle": 0.25, "outerConeAngle": 0.5}}
+            ]}},
+            "nodes": [
+                {"name": "sun", "rotation": [0.0, 0.7071068, 0.0, 0.7071068],
+                    "extensions": {"KHR_lights_punctual": {"light": 0}}},
+                {"name": "lamp", "translation": [1.0, 2.0, 3.0],
+                    "extensions": {"KHR_lights_punctual": {"light": 1}}},
+                {"name": "spot", "translation": [0.0, 0.0, 5.0],
+                    "extensions": {"KHR_lights_punctual": {"light": 2}}}
+            ]
+        }"#;
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let mut manager = vx_result!(engine.get_asset_manager().get_light_manager().write());
+        let lights: Vec<Arc<RwLock<dyn Light>>> = document
+            .nodes()
+            .map(|n| manager.load_gltf(&n, &*engine, &[]))
+            .collect();
+        assert_eq!(manager.lights.len(), 3);
+        assert_eq!(manager.name_to_id.len(), 3);
+        {
+            let light = vx_result!(lights[0].read());
+            assert_eq!(light.get_name(), Some("sun".to_string()));
+            let directional = vx_unwrap!(light.to_directional());
+            assert!(directional.to_base().is_some());
+            let mut u = DirectionalUniform::new();
+            directional.update_uniform(&mut u);
+            let d = u.direction.truncate();
+            assert!((d - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-5);
+            assert!(u.color == Vec4::new(1.0, 1.0, 1.0, 1.0));
+        }
+        let light = vx_result!(lights[1].read());
+        assert!(light.to_point().is_some());
+        assert!(light.get_location() == Vec3::new(1.0, 2.0, 3.0));
+        let light = vx_result!(lights[2].read());
+        assert!(vx_unwrap!(light.to_spot()).to_shadow_maker().is_none());
+        assert!(light.get_location() == Vec3::new(0.0, 0.0, 5.0));
+    }
+}
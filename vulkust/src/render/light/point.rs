@@ -1,12 +1,19 @@
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::Object as CoreObject;
 use super::super::super::core::types::{Id, Real};
+use super::super::super::math::quaternion::Quat;
+use super::super::super::math::vector::{Vec3, Vec4};
 use super::super::engine::Engine;
+use super::super::gltf_node::{
+    read_transform as read_gltf_transform, Light as GltfLight, LightKind as GltfLightKind,
+};
 use super::super::object::{Base as ObjectBase, Loadable, Object, Transferable};
-use super::{DefaultLighting, Directional, Light, ShadowMaker};
+use super::{DefaultLighting, Directional, Light, ShadowMaker, Spot};
 use std::f32::consts::PI;
 
 pub trait Point: Light {
+    fn get_radius(&self) -> Real;
+    fn set_radius(&mut self, radius: Real);
     fn update_uniform(&self, uniform: &mut PointUniform);
 }
 
@@ -14,15 +21,15 @@ pub trait Point: Light {
 #[derive(Clone, Copy)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct PointUniform {
-    color_minradius: cgmath::Vector4<Real>,
-    position_radius: cgmath::Vector4<Real>,
+    color_minradius: Vec4,
+    position_radius: Vec4,
 }
 
 impl PointUniform {
     pub fn new() -> Self {
         PointUniform {
-            color_minradius: cgmath::Vector4::new(0.0, 0.0, 0.0, 0.0),
-            position_radius: cgmath::Vector4::new(0.0, 0.0, 0.0, 0.0),
+            color_minradius: Vec4::zero(),
+            position_radius: Vec4::zero(),
         }
     }
 }
@@ -30,8 +37,8 @@ impl PointUniform {
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Base {
     obj_base: ObjectBase,
-    location: cgmath::Vector3<Real>,
-    color: cgmath::Vector3<Real>,
+    location: Vec3,
+    color: Vec3,
     strength: Real,
     radius: Real, // by default I calculate the effective radius by `0.001 < (strength / (4 * VX_PI * radius * radius))`
     min_radius: Real,
@@ -45,18 +52,13 @@ impl Base {
     fn new_with_obj_base(obj_base: ObjectBase) -> Self {
         Self {
             obj_base,
-            location: cgmath::Vector3::new(0.0, 0.0, 0.0),
-            color: cgmath::Vector3::new(1.0, 1.0, 1.0),
+            location: Vec3::zero(),
+            color: Vec3::new(1.0, 1.0, 1.0),
             strength: 1.0,
             radius: 80.0,
             min_radius: 0.1,
         }
     }
-
-    fn set_strength(&mut self, strength: Real) {
-        self.strength = strength;
-        self.radius = (strength / (0.004 * PI)).sqrt();
-    }
 }
 
 impl CoreObject for Base {
@@ -72,7 +74,6 @@ impl Object for Base {
 
     fn set_name(&mut self, name: &str) {
         self.obj_base.set_name(name);
-        vx_unimplemented!(); //it must update corresponding manager
     }
 
     fn disable_rendering(&mut self) {
@@ -89,15 +90,15 @@ impl Object for Base {
 }
 
 impl Transferable for Base {
-    fn set_orientation(&mut self, _: &cgmath::Quaternion<Real>) {
+    fn set_orientation(&mut self, _: &Quat) {
         vx_unexpected!();
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, l: &Vec3) {
         self.location = *l;
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         return self.location;
     }
 
@@ -117,7 +118,7 @@ impl Transferable for Base {
         vx_unexpected!();
     }
 
-    fn translate(&mut self, t: &cgmath::Vector3<Real>) {
+    fn translate(&mut self, t: &Vec3) {
         self.location += *t;
     }
 
@@ -143,6 +144,14 @@ impl Light for Base {
         return Some(self);
     }
 
+    fn to_spot(&self) -> Option<&dyn Spot> {
+        return None;
+    }
+
+    fn to_mut_spot(&mut self) -> Option<&mut dyn Spot> {
+        return None;
+    }
+
     fn to_shadow_maker(&self) -> Option<&dyn ShadowMaker> {
         return None;
     }
@@ -151,34 +160,72 @@ impl Light for Base {
         return None;
     }
 
+    fn get_color(&self) -> &Vec3 {
+        return &self.color;
+    }
+
+    fn set_color(&mut self, color: &Vec3) {
+        self.color = *color;
+    }
+
+    fn get_strength(&self) -> Real {
+        return self.strength;
+    }
+
+    fn set_strength(&mut self, strength: Real) {
+        self.strength = strength;
+        self.radius = (strength / (0.004 * PI)).sqrt();
+    }
+
     fn update(&mut self) {}
 }
 
 impl Loadable for Base {
-    fn new_with_gltf(_node: &gltf::Node, _eng: &Engine, _: &[u8]) -> Self {
-        vx_unimplemented!();
-    }
-
-    fn new_with_gx3d(_: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
-        let mut myself = Self::new_with_obj_base(ObjectBase::new_with_id(id));
-        myself.location.x = reader.read();
-        myself.location.y = reader.read();
-        myself.location.z = reader.read();
-        myself.color.x = reader.read();
-        myself.color.y = reader.read();
-        myself.color.z = reader.read();
-        myself.set_strength(reader.read());
-        #[cfg(debug_gx3d_light)]
-        {
-            vx_log_i!("Direction {:?}", &myself.location);
-            vx_log_i!("Color {:?}", &myself.color);
-            vx_log_i!("Strength {:?}", &myself.strength);
+    fn new_with_gltf(node: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
+        let light = GltfLight::new_with_gltf(&vx_unwrap!(node.light()));
+        match light.kind {
+            GltfLightKind::Point => (),
+            _ => vx_log_f!("Type of light isn't point."),
+        }
+        let mut myself = Self::new_with_obj_base(ObjectBase::new_with_gltf(node, eng, data));
+        let (l, _, _) = read_gltf_transform(node);
+        myself.location = l;
+        myself.color = light.color;
+        myself.set_strength(light.strength);
+        if let Some(range) = light.range {
+            myself.radius = range;
         }
         return myself;
     }
+
+    // fn new_with_gx3d(_: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
+    //     let mut myself = Self::new_with_obj_base(ObjectBase::new_with_id(id));
+    //     myself.location.x = reader.read();
+    //     myself.location.y = reader.read();
+    //     myself.location.z = reader.read();
+    //     myself.color.x = reader.read();
+    //     myself.color.y = reader.read();
+    //     myself.color.z = reader.read();
+    //     myself.set_strength(reader.read());
+    //     #[cfg(debug_gx3d_light)]
+    //     {
+    //         vx_log_i!("Direction {:?}", &myself.location);
+    //         vx_log_i!("Color {:?}", &myself.color);
+    //         vx_log_i!("Strength {:?}", &myself.strength);
+    //     }
+    //     return myself;
+    // }
 }
 
 impl Point for Base {
+    fn get_radius(&self) -> Real {
+        return self.radius;
+    }
+
+    fn set_radius(&mut self, radius: Real) {
+        self.radius = radius;
+    }
+
     fn update_uniform(&self, u: &mut PointUniform) {
         u.color_minradius = (self.color * self.strength).extend(self.min_radius);
         u.position_radius = self.location.extend(self.radius);
@@ -0,0 +1,327 @@
+// use super::super::super::core::gx3d::Gx3DReader;
+use super::super::super::core::object::Object as CoreObject;
+use super::super::super::core::types::{Id, Real};
+use super::super::super::math::quaternion::Quat;
+use super::super::super::math::vector::{Vec3, Vec4};
+use super::super::engine::Engine;
+use super::super::gltf_node::{
+    read_transform as read_gltf_transform, Light as GltfLight, LightKind as GltfLightKind,
+};
+use super::super::object::{Base as ObjectBase, Loadable, Object, Transferable};
+use super::{DefaultLighting, Directional, Light, Point, ShadowMaker};
+use std::f32::consts::PI;
+
+pub trait Spot: Light {
+    fn get_orientation(&self) -> &Quat;
+    fn get_radius(&self) -> Real;
+    fn set_radius(&mut self, radius: Real);
+    // Inner and outer angles of the cone, in radian.
+    fn get_cone_angles(&self) -> (Real, Real);
+    fn set_cone_angles(&mut self, inner: Real, outer: Real);
+    fn update_uniform(&self, uniform: &mut SpotUniform);
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct SpotUniform {
+    color_minradius: Vec4,
+    position_radius: Vec4,
+    direction_cone_scale: Vec4,
+    cone_offset_reserved: Vec4,
+}
+
+impl SpotUniform {
+    pub fn new() -> Self {
+        SpotUniform {
+            color_minradius: Vec4::zero(),
+            position_radius: Vec4::zero(),
+            direction_cone_scale: Vec4::new(0.0, 0.0, -1.0, 0.0),
+            cone_offset_reserved: Vec4::zero(),
+        }
+    }
+}
+
+/// Its light fades from the inner cone to the outer one, the angles are
+/// between the direction and the cone in radians.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Base {
+    obj_base: ObjectBase,
+    location: Vec3,
+    orientation: Quat,
+    direction: Vec3,
+    color: Vec3,
+    strength: Real,
+    radius: Real,
+    min_radius: Real,
+    inner_cone_angle: Real,
+    outer_cone_angle: Real,
+}
+
+impl Base {
+    fn new() -> Self {
+        Self::new_with_obj_base(ObjectBase::new())
+    }
+
+    fn new_with_obj_base(obj_base: ObjectBase) -> Self {
+        Self {
+            obj_base,
+            location: Vec3::zero(),
+            orientation: Quat::identity(),
+            direction: Vec3::new(0.0, 0.0, -1.0),
+            color: Vec3::new(1.0, 1.0, 1.0),
+            strength: 1.0,
+            radius: 80.0,
+            min_radius: 0.1,
+            inner_cone_angle: 0.0,
+            outer_cone_angle: PI * 0.25,
+        }
+    }
+}
+
+impl CoreObject for Base {
+    fn get_id(&self) -> Id {
+        self.obj_base.get_id()
+    }
+}
+
+impl Object for Base {
+    fn get_name(&self) -> Option<String> {
+        self.obj_base.get_name()
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.obj_base.set_name(name);
+    }
+
+    fn disable_rendering(&mut self) {
+        self.obj_base.disable_rendering()
+    }
+
+    fn enable_rendering(&mut self) {
+        self.obj_base.enable_rendering()
+    }
+
+    fn is_renderable(&self) -> bool {
+        return self.obj_base.is_renderable();
+    }
+}
+
+// Like the cameras it looks toward its local -z.
+impl Transferable for Base {
+    fn set_orientation(&mut self, q: &Quat) {
+        self.orientation = q.normalized();
+        self.direction = self.orientation.rotate(Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    fn set_location(&mut self, l: &Vec3) {
+        self.location = *l;
+    }
+
+    fn get_location(&self) -> Vec3 {
+        return self.location;
+    }
+
+    fn move_local_z(&mut self, v: Real) {
+        self.location += self.direction * -v;
+    }
+
+    fn move_local_x(&mut self, v: Real) {
+        self.location += self.orientation.rotate(Vec3::new(1.0, 0.0, 0.0)) * v;
+    }
+
+    fn rotate_local_x(&mut self, v: Real) {
+        let q = self.orientation * Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), v);
+        self.set_orientation(&q);
+    }
+
+    fn rotate_global_z(&mut self, v: Real) {
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), v) * self.orientation;
+        self.set_orientation(&q);
+    }
+
+    fn translate(&mut self, t: &Vec3) {
+        self.location += *t;
+    }
+}
+
+impl Light for Base {
+    fn to_directional(&self) -> Option<&dyn Directional> {
+        return None;
+    }
+
+    fn to_mut_directional(&mut self) -> Option<&mut dyn Directional> {
+        return None;
+    }
+
+    fn to_point(&self) -> Option<&dyn Point> {
+        return None;
+    }
+
+    fn to_mut_point(&mut self) -> Option<&mut dyn Point> {
+        return None;
+    }
+
+    fn to_spot(&self) -> Option<&dyn Spot> {
+        return Some(self);
+    }
+
+    fn to_mut_spot(&mut self) -> Option<&mut dyn Spot> {
+        return Some(self);
+    }
+
+    fn to_shadow_maker(&self) -> Option<&dyn ShadowMaker> {
+        return None;
+    }
+
+    fn to_mut_shadow_maker(&mut self) -> Option<&mut dyn ShadowMaker> {
+        return None;
+    }
+
+    fn get_color(&self) -> &Vec3 {
+        return &self.color;
+    }
+
+    fn set_color(&mut self, color: &Vec3) {
+        self.color = *color;
+    }
+
+    fn get_strength(&self) -> Real {
+        return self.strength;
+    }
+
+    // Same effective radius as the point lights.
+    fn set_strength(&mut self, strength: Real) {
+        self.strength = strength;
+        self.radius = (strength / (0.004 * PI)).sqrt();
+    }
+
+    fn update(&mut self) {}
+}
+
+impl Loadable for Base {
+    fn new_with_gltf(node: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
+        let light = GltfLight::new_with_gltf(&vx_unwrap!(node.light()));
+        let (inner, outer) = match light.kind {
+            GltfLightKind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => (inner_cone_angle, outer_cone_angle),
+            _ => vx_log_f!("Type of light isn't spot."),
+        };
+        let mut myself = Self::new_with_obj_base(ObjectBase::new_with_gltf(node, eng, data));
+        let (l, r, _) = read_gltf_transform(node);
+        myself.location = l;
+        myself.set_orientation(&r);
+        myself.color = light.color;
+        myself.set_strength(light.strength);
+        if let Some(range) = light.range {
+            myself.radius = range;
+        }
+        myself.set_cone_angles(inner, outer);
+        return myself;
+    }
+
+    // fn new_with_gx3d(_: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
+    //     let mut myself = Self::new_with_obj_base(ObjectBase::new_with_id(id));
+    //     let l = [reader.read(), reader.read(), reader.read()];
+    //     myself.location = Vec3::from_array(l);
+    //     let r = [reader.read(), reader.read(), reader.read(), reader.read()];
+    //     myself.set_orientation(&Quat::new(r[0], r[1], r[2], r[3]));
+    //     let c = [reader.read(), reader.read(), reader.read()];
+    //     myself.color = Vec3::from_array(c);
+    //     myself.set_strength(reader.read());
+    //     let inner = reader.read();
+    //     myself.set_cone_angles(inner, reader.read());
+    //     #[cfg(debug_gx3d_light)]
+    //     {
+    //         vx_log_i!("Location {:?}", &myself.location);
+    //         vx_log_i!("Direction {:?}", &myself.direction);
+    //         vx_log_i!("Color {:?}", &myself.color);
+    //         vx_log_i!("Strength {:?}", &myself.strength);
+    //     }
+    //     return myself;
+    // }
+}
+
+impl Spot for Base {
+    fn get_orientation(&self) -> &Quat {
+        return &self.orientation;
+    }
+
+    fn get_radius(&self) -> Real {
+        return self.radius;
+    }
+
+    fn set_radius(&mut self, radius: Real) {
+        self.radius = radius;
+    }
+
+    fn get_cone_angles(&self) -> (Real, Real) {
+        return (self.inner_cone_angle, self.outer_cone_angle);
+    }
+
+    fn set_cone_angles(&mut self, inner: Real, outer: Real) {
+        self.inner_cone_angle = inner;
+        self.outer_cone_angle = outer;
+    }
+
+    // The cone factor is saturate(cos(angle) * scale + offset), it is one in
+    // the inner cone and zero out of the outer one.
+    fn update_uniform(&self, u: &mut SpotUniform) {
+        let cos_outer = self.outer_cone_angle.cos();
+        let scale = 1.0 / (self.inner_cone_angle.cos() - cos_outer).max(0.001);
+        u.color_minradius = (self.color * self.strength).extend(self.min_radius);
+        u.position_radius = self.location.extend(self.radius);
+        u.direction_cone_scale = self.direction.normalized().extend(scale);
+        u.cone_offset_reserved = Vec4::new(-cos_outer * scale, 0.0, 0.0, 0.0);
+    }
+}
+
+impl DefaultLighting for Base {
+    fn default(_: &Engine) -> Self {
+        return Self::new();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    #[test]
+    fn transfer_test() {
+        let mut light = Base::new();
+        light.set_name("spot");
+        assert_eq!(light.get_name(), Some("spot".to_string()));
+        light.set_location(&Vec3::new(1.0, 0.0, 0.0));
+        // It points toward +y after the rotation, and toward -x after the next.
+        light.rotate_local_x(PI * 0.5);
+        assert!(is_near(light.direction, Vec3::new(0.0, 1.0, 0.0)));
+        light.move_local_z(-2.0);
+        assert!(is_near(light.get_location(), Vec3::new(1.0, 2.0, 0.0)));
+        light.rotate_global_z(PI * 0.5);
+        assert!(is_near(light.direction, Vec3::new(-1.0, 0.0, 0.0)));
+        light.move_local_x(1.0);
+        assert!(is_near(light.get_location(), Vec3::new(1.0, 3.0, 0.0)));
+        light.translate(&Vec3::new(0.0, 0.0, 1.0));
+        light.scale(2.0);
+        let mut u = SpotUniform::new();
+        light.update_uniform(&mut u);
+        assert!(is_near(
+            u.position_radius.truncate(),
+            Vec3::new(1.0, 3.0, 1.0)
+        ));
+        assert!(is_near(
+            u.direction_cone_scale.truncate(),
+            Vec3::new(-1.0, 0.0, 0.0)
+        ));
+        // Cone factor is one on the direction and zero on the outer cone.
+        let factor = |cos: Real| cos * u.direction_cone_scale.w() + u.cone_offset_reserved.x();
+        assert!((factor(1.0) - 1.0).abs() < 1e-4);
+        assert!(factor((PI * 0.25).cos()).abs() < 1e-4);
+    }
+}
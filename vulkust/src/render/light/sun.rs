@@ -1,11 +1,16 @@
 use super::super::super::collision::aabb::Aabb3;
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::Object as CoreObject;
 use super::super::super::core::types::{Id, Real};
+use super::super::super::math::quaternion::Quat;
+use super::super::super::math::vector::Vec3;
 use super::super::buffer::{Dynamic as DynamicBuffer, Manager as BufferManager};
 use super::super::command::{Buffer as CmdBuffer, Pool as CmdPool};
 use super::super::engine::Engine;
 use super::super::gapi::GraphicApiEngine;
+use super::super::gltf_node::{
+    read_transform as read_gltf_transform, Light as GltfLight, LightKind as GltfLightKind,
+};
 use super::super::model::Model;
 use super::super::object::{Base as ObjectBase, Loadable, Object, Transferable};
 use super::super::shadower::Shadower;
@@ -13,7 +18,7 @@ use super::super::sync::Semaphore;
 use super::directional::Base;
 use super::{
     DefaultLighting, Directional, DirectionalUniform, Light, Point,
-    ShadowAccumulatorDirectionalUniform, ShadowMaker,
+    ShadowAccumulatorDirectionalUniform, ShadowMaker, Spot,
 };
 use std::mem::size_of;
 use std::sync::{Arc, Mutex, RwLock, Weak};
@@ -122,7 +127,8 @@ impl SunShadowMakerKernelData {
 
     fn shadow(&mut self, m: &mut dyn Model, model: &Arc<RwLock<dyn Model>>) {
         let rd = m.get_occlusion_culling_radius();
-        let v = (self.zero_located_view * m.get_location().extend(1.0)).truncate();
+        let v = (self.zero_located_view * m.get_transform().get_world_location().extend(1.0))
+            .truncate();
         let b = Aabb3::new_with_center_radius(&v, rd);
         let ccc = self.cascade_cameras.len();
         for ci in 0..ccc {
@@ -157,6 +163,7 @@ impl SunShadowMakerKernelData {
     }
 
     fn render_shadow_mapper(&mut self, shadower: &Shadower, frame_number: usize) {
+        let mut has_skinned = false;
         for i in 0..self.last_render_data_index {
             let rd = &mut self.render_data[i];
             let ci = rd.cascade_index;
@@ -175,6 +182,28 @@ impl SunShadowMakerKernelData {
                 &*vx_result!(rd.uniform_buffer.get_buffer(frame_number).read()),
             );
             model.render_shadow(cmd, frame_number);
+            has_skinned |= model.get_skin().is_some();
+        }
+        // Skinned meshes come after the others to switch the pipeline once.
+        if has_skinned {
+            shadower.bind_skinned_shadow_mappers(&mut self.frames_data[frame_number].cascades_cmds);
+            for i in 0..self.last_render_data_index {
+                let rd = &self.render_data[i];
+                let model = match vx_unwrap!(&rd.model).upgrade() {
+                    Some(m) => m,
+                    None => continue,
+                };
+                let model = vx_result!(model.read());
+                if model.get_skin().is_none() {
+                    continue;
+                }
+                let cmd = &mut self.frames_data[frame_number].cascades_cmds[rd.cascade_index];
+                cmd.bind_shadow_mapper_light_descriptor(
+                    shadower.get_shadow_map_descriptor_set(),
+                    &*vx_result!(rd.uniform_buffer.get_buffer(frame_number).read()),
+                );
+                model.render_skinned_shadow(cmd, frame_number);
+            }
         }
         for cmd in &mut self.frames_data[frame_number].cascades_cmds {
             cmd.end();
@@ -324,7 +353,6 @@ impl Object for Sun {
 
     fn set_name(&mut self, name: &str) {
         self.obj_base.set_name(name);
-        vx_unimplemented!(); //it must update corresponding manager
     }
 
     fn disable_rendering(&mut self) {
@@ -357,6 +385,14 @@ impl Light for Sun {
         return None;
     }
 
+    fn to_spot(&self) -> Option<&dyn Spot> {
+        return None;
+    }
+
+    fn to_mut_spot(&mut self) -> Option<&mut dyn Spot> {
+        return None;
+    }
+
     fn to_shadow_maker(&self) -> Option<&dyn ShadowMaker> {
         return Some(self);
     }
@@ -515,20 +551,21 @@ impl DefaultLighting for Sun {
 }
 
 impl Transferable for Sun {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
-        let rotation = cgmath::Matrix3::from(*q);
+    fn set_orientation(&mut self, q: &Quat) {
+        let q = cgmath::Quaternion::from(*q);
+        let rotation = cgmath::Matrix3::from(q);
         self.direction = rotation * cgmath::Vector3::new(0.0, 0.0, -1.0);
-        let mut q = -*q;
+        let mut q = -q;
         q.s = -q.s;
         self.zero_located_view = cgmath::Matrix4::from(q);
     }
 
-    fn set_location(&mut self, _: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, _: &Vec3) {
         // camera does not have location
         vx_unexpected!();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         vx_unexpected!();
     }
 
@@ -548,7 +585,7 @@ impl Transferable for Sun {
         vx_unimplemented!();
     }
 
-    fn translate(&mut self, _: &cgmath::Vector3<Real>) {
+    fn translate(&mut self, _: &Vec3) {
         vx_unexpected!();
     }
 
@@ -558,32 +595,42 @@ impl Transferable for Sun {
 }
 
 impl Loadable for Sun {
-    fn new_with_gltf(_node: &gltf::Node, _eng: &Engine, _: &[u8]) -> Self {
-        vx_unimplemented!();
-    }
-
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
-        let mut myself = Self::new_with_obj_base(engine, ObjectBase::new_with_id(id));
-        let r = [
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-        ];
-        myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
-        myself.color = cgmath::Vector3::new(
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-            reader.read::<Real>(),
-        );
-        myself.strength = reader.read::<Real>();
-        #[cfg(debug_gx3d)]
-        {
-            vx_log_i!("Matrix {:?}", &myself.zero_located_view);
-            vx_log_i!("Quaternion {:?}", &r);
-            vx_log_i!("Color {:?}", &myself.color);
-            vx_log_i!("Strength {:?}", &myself.strength);
+    fn new_with_gltf(node: &gltf::Node, eng: &Engine, data: &[u8]) -> Self {
+        let light = GltfLight::new_with_gltf(&vx_unwrap!(node.light()));
+        match light.kind {
+            GltfLightKind::Directional => (),
+            _ => vx_log_f!("Type of light isn't directional."),
         }
+        let mut myself = Self::new_with_obj_base(eng, ObjectBase::new_with_gltf(node, eng, data));
+        let (_, r, _) = read_gltf_transform(node);
+        myself.set_orientation(&r);
+        myself.color = cgmath::Vector3::from(light.color);
+        myself.strength = light.strength;
         return myself;
     }
+
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
+    //     let mut myself = Self::new_with_obj_base(engine, ObjectBase::new_with_id(id));
+    //     let r = [
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //     ];
+    //     myself.set_orientation(&cgmath::Quaternion::new(r[0], r[1], r[2], r[3]));
+    //     myself.color = cgmath::Vector3::new(
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //         reader.read::<Real>(),
+    //     );
+    //     myself.strength = reader.read::<Real>();
+    //     #[cfg(debug_gx3d)]
+    //     {
+    //         vx_log_i!("Matrix {:?}", &myself.zero_located_view);
+    //         vx_log_i!("Quaternion {:?}", &r);
+    //         vx_log_i!("Color {:?}", &myself.color);
+    //         vx_log_i!("Strength {:?}", &myself.strength);
+    //     }
+    //     return myself;
+    // }
 }
@@ -0,0 +1,160 @@
+use super::super::core::types::Real;
+use super::super::math::matrix::Mat4;
+use super::super::math::vector::Vec3;
+
+/// Screen sizes where the models switch to their next lods.
+pub const DEFAULT_THRESHOLDS: [Real; 3] = [0.4, 0.2, 0.1];
+pub const DEFAULT_HYSTERESIS: Real = 0.1;
+
+/// Picks the level of detail of an object from its screen size, that is the
+/// ratio of its projected diameter to the height of the screen.
+///
+/// Level i + 1 is taken when the size goes below thresholds[i], thresholds
+/// are descending. Hysteresis is the relative band around every threshold
+/// that the size must cross before the level changes, so the objects that
+/// stay around a threshold do not flicker between two levels.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Selector {
+    thresholds: Vec<Real>,
+    hysteresis: Real,
+    level: usize,
+}
+
+impl Selector {
+    pub fn new() -> Self {
+        return Self::new_with_thresholds(&DEFAULT_THRESHOLDS, DEFAULT_HYSTERESIS);
+    }
+
+    pub fn new_with_thresholds(thresholds: &[Real], hysteresis: Real) -> Self {
+        return Self {
+            thresholds: thresholds.to_vec(),
+            hysteresis,
+            level: 0,
+        };
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: &[Real]) {
+        self.thresholds = thresholds.to_vec();
+        if self.level > self.thresholds.len() {
+            self.level = self.thresholds.len();
+        }
+    }
+
+    #[inline]
+    pub fn get_thresholds(&self) -> &[Real] {
+        return &self.thresholds;
+    }
+
+    pub fn set_hysteresis(&mut self, hysteresis: Real) {
+        self.hysteresis = hysteresis;
+    }
+
+    #[inline]
+    pub fn get_hysteresis(&self) -> Real {
+        return self.hysteresis;
+    }
+
+    #[inline]
+    pub fn get_level(&self) -> usize {
+        return self.level;
+    }
+
+    pub fn update(&mut self, screen_size: Real) -> usize {
+        loop {
+            let level = self.level;
+            if level < self.thresholds.len()
+                && screen_size < self.thresholds[level] * (1.0 - self.hysteresis)
+            {
+                self.level += 1;
+            } else if level > 0
+                && screen_size >= self.thresholds[level - 1] * (1.0 + self.hysteresis)
+            {
+                self.level -= 1;
+            } else {
+                return self.level;
+            }
+        }
+    }
+}
+
+impl Default for Selector {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Ratio of the projected diameter of the sphere to the screen height.
+///
+/// The w of the clip space is the depth for the perspective and one for the
+/// orthographic projection, spheres behind the camera are infinitely big.
+pub fn get_screen_size(
+    projection: &Mat4,
+    view_projection: &Mat4,
+    radius: Real,
+    location: Vec3,
+) -> Real {
+    let w = view_projection.transform_vec4(location.extend(1.0)).w();
+    if w <= 0.0 {
+        return std::f32::MAX;
+    }
+    return radius * projection.get_col(1).y().abs() / w;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selector_test() {
+        let mut selector = Selector::new_with_thresholds(&[0.5, 0.25, 0.1], 0.1);
+        assert_eq!(selector.update(1.0), 0);
+        // It jumps over several levels at once.
+        assert_eq!(selector.update(0.2), 2);
+        // Inside the band of 0.25 nothing changes in either direction.
+        assert_eq!(selector.update(0.26), 2);
+        assert_eq!(selector.update(0.27), 2);
+        assert_eq!(selector.update(0.28), 1);
+        assert_eq!(selector.update(0.24), 1);
+        assert_eq!(selector.update(0.23), 1);
+        assert_eq!(selector.update(0.01), 3);
+        assert_eq!(selector.update(0.6), 0);
+        selector.set_hysteresis(0.0);
+        assert_eq!(selector.update(0.25), 1);
+        assert_eq!(selector.update(0.2499), 2);
+        selector.set_thresholds(&[0.5]);
+        assert_eq!(selector.get_level(), 1);
+        assert_eq!(selector.update(0.5), 0);
+    }
+
+    #[test]
+    fn screen_size_test() {
+        // Right handed perspective with 90 degrees vertical field of view,
+        // near at 1 and far at 100.
+        let (n, f) = (1.0, 100.0);
+        #[rustfmt::skip]
+        let projection = Mat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, f / (n - f), -1.0,
+            0.0, 0.0, n * f / (n - f), 0.0,
+        ]);
+        let view = Mat4::from_translation(Vec3::new(0.0, 0.0, -5.0));
+        let view_projection = projection * view;
+        let size = get_screen_size(
+            &projection,
+            &view_projection,
+            1.0,
+            Vec3::new(0.0, 0.0, -5.0),
+        );
+        assert!((size - 0.1).abs() < 1e-6);
+        let size = get_screen_size(&projection, &view_projection, 1.0, Vec3::new(3.0, 0.0, 5.0));
+        assert_eq!(size, std::f32::MAX);
+        // Orthographic size does not change with the distance.
+        let projection = Mat4::from_scale(Vec3::new(0.25, 0.5, -0.01));
+        for z in &[-2.0, -50.0] {
+            let size = get_screen_size(&projection, &projection, 1.0, Vec3::new(0.0, 0.0, *z));
+            assert!((size - 0.5).abs() < 1e-6);
+        }
+    }
+}
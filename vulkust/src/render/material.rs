@@ -1,15 +1,19 @@
-use super::super::core::gx3d::Gx3DReader;
-use super::super::core::types::{Id, Real, TypeId};
+// use super::super::core::gx3d::Gx3DReader;
+use super::super::core::types::Real;
+use super::super::math::vector::Vec4;
 use super::buffer::Dynamic as DynamicBuffer;
 use super::command::Buffer as CmdBuffer;
 use super::descriptor::Set as DescriptorSet;
 use super::engine::Engine;
-use super::texture::{Manager as TextureManager, Texture};
+use super::gltf_material::{
+    get_uv_transform, AlphaMode as GltfAlphaMode, Material as GltfMaterial,
+};
+use super::texture::{Texture, Texture2D};
 use std::default::Default;
 use std::mem::size_of;
 use std::sync::{Arc, RwLock};
 
-// use gltf;
+use gltf;
 
 #[repr(u8)]
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -29,10 +33,17 @@ pub struct Uniform {
     normal_scale: Real,
     occlusion_strength: Real,
     roughness_factor: Real,
+    emissive_strength: Real,
+    rezerved: Real,
+    modes: Vec4, // unlit, opaque, metallic channel, roughness channel
+    base_color_uv_transform: [Vec4; 2],
+    metallic_roughness_uv_transform: [Vec4; 2],
+    emissive_uv_transform: [Vec4; 2],
 }
 
 impl Uniform {
     pub fn new() -> Self {
+        let identity = get_uv_transform([0.0, 0.0], 0.0, [1.0, 1.0]);
         Uniform {
             alpha: 1.0,
             alpha_cutoff: 0.001,
@@ -40,6 +51,12 @@ impl Uniform {
             normal_scale: 1.0,
             occlusion_strength: 1.0,
             roughness_factor: 1.0,
+            emissive_strength: 1.0,
+            rezerved: 0.0,
+            modes: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            base_color_uv_transform: identity,
+            metallic_roughness_uv_transform: identity,
+            emissive_uv_transform: identity,
         }
     }
 }
@@ -75,321 +92,47 @@ pub struct Material {
 }
 
 impl Material {
-    // pub(crate) fn new_with_gltf(_engine: &Engine, _mat: &gltf::Material) -> Self {
-    //     vx_unimplemented!();
-    // }
-
-    // pub(crate) fn get_uniform_buffer(&self) -> &DynamicBuffer {
-    //     return &self.uniform_buffer;
-    // }
-
-    // pub(crate) fn get_descriptor_set(&self) -> &Arc<DescriptorSet> {
-    //     return &self.descriptor_set;
-    // }
-
-    pub(crate) fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader) -> Self {
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+    pub(crate) fn new_with_gltf(eng: &Engine, mat: &gltf::Material, data: &[u8]) -> Self {
+        let gapi_engine = &eng.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
         let mut texture_manager = vx_result!(eng.get_asset_manager().get_texture_manager().write());
+        let mat = GltfMaterial::new_with_gltf(mat);
         let mut uniform = Uniform::new();
-        let mut translucency = TranslucencyMode::Opaque;
-        let read_color = |r: &mut Gx3DReader| {
-            let read = |r: &mut Gx3DReader| {
-                let f = r.read::<Real>() * 255.0;
-                #[cfg(debug_gx3d)]
-                {
-                    if f > 255.5 || f < 0.0 {
-                        vx_unexpected!();
-                    }
-                }
-                f as u8
-            };
-            [read(r), read(r), read(r), read(r)]
-        };
-        let read_tex =
-            |engine: &Engine, reader: &mut Gx3DReader, texture_manager: &mut TextureManager| {
-                let t = reader.read_type_id();
-                if t == Field::Texture as TypeId {
-                    let id: Id = reader.read();
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Texture Id: {:?}", id);
-                    texture_manager.load_gx3d(engine, id)
-                } else if t == Field::Vector as TypeId {
-                    let color = read_color(reader);
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Color: {:?}", &color);
-                    texture_manager.create_2d_with_color(&gapi_engine, color)
-                } else {
-                    vx_unexpected!()
-                }
-            };
-        let read_value = |reader: &mut Gx3DReader| {
-            let t = reader.read_type_id();
-            if t != Field::Float as TypeId {
-                vx_unexpected!();
+        let (translucency, opaque) = match mat.alpha_mode {
+            GltfAlphaMode::Opaque => {
+                uniform.alpha_cutoff = 0.0;
+                (TranslucencyMode::Opaque, 1.0)
             }
-            let v = reader.read::<Real>();
-            #[cfg(debug_gx3d)]
-            vx_log_i!("Value: {:?}", v);
-            v
-        };
-        let mut alpha = None;
-        let mut alpha_cutoff = None;
-        let mut alpha_mode = None;
-        let mut base_color = None;
-        let mut base_color_factor = None;
-        let mut double_sided = None;
-        let mut emissive = None;
-        let mut emissive_factor = None;
-        let mut metallic_factor = None;
-        let mut metallic_roughness = None;
-        let mut normal = None;
-        let mut normal_scale = None;
-        let mut occlusion = None;
-        let mut occlusion_strength = None;
-        let mut roughness_factor = None;
-        for _ in 0..15 {
-            let it = reader.read_type_id();
-            match it {
-                1 => {
-                    // Alpha
-                    #[cfg(debug_gx3d)]
-                    {
-                        if alpha.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    let t = reader.read_type_id();
-                    if t == Field::Float as TypeId {
-                        alpha = Some(reader.read());
-                    } else if t == Field::Texture as TypeId {
-                        let _: Id = reader.read();
-                        alpha = Some(1.0);
-                        translucency = TranslucencyMode::Tansparent;
-                    } else {
-                        vx_unexpected!();
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Alpha is: {:?}", uniform.alpha);
-                }
-                2 => {
-                    // AlphaCutoff
-                    #[cfg(debug_gx3d)]
-                    {
-                        if alpha_cutoff.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    let t = reader.read_type_id();
-                    if t == Field::Float as TypeId {
-                        alpha_cutoff = Some(reader.read());
-                        if vx_unwrap!(alpha_cutoff) > 0.003 {
-                            translucency = TranslucencyMode::Cutoff;
-                        }
-                    } else {
-                        vx_unexpected!();
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Alpha cutoff is: {:?}", uniform.alpha_cutoff);
-                }
-                3 => {
-                    // AlphaMode
-                    #[cfg(debug_gx3d)]
-                    {
-                        if alpha_mode.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    let t = reader.read_type_id();
-                    if t == Field::Float as TypeId {
-                        alpha_mode = Some(reader.read());
-                        let alpha_mode = vx_unwrap!(alpha_mode);
-                        if alpha_mode < 1.0 && 0.0 < alpha_mode {
-                            translucency = TranslucencyMode::Cutoff;
-                        }
-                    } else {
-                        vx_unexpected!();
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Translucency is: {:?}", translucency);
-                }
-                4 => {
-                    // BaseColor
-                    #[cfg(debug_gx3d)]
-                    {
-                        if base_color.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    let t = reader.read_type_id();
-                    base_color = Some(if t == Field::Texture as TypeId {
-                        let id: Id = reader.read();
-                        #[cfg(debug_gx3d)]
-                        vx_log_i!("Base color is: texture<{:?}>", id);
-                        texture_manager.load_gx3d(&*eng, id)
-                    } else if t == Field::Vector as TypeId {
-                        let color = read_color(reader);
-                        if color[3] < 254 {
-                            translucency = TranslucencyMode::Tansparent;
-                        }
-                        #[cfg(debug_gx3d)]
-                        vx_log_i!("Base color is: {:?}", &color);
-                        texture_manager.create_2d_with_color(&*gapi_engine, color)
-                    } else {
-                        vx_unexpected!()
-                    });
-                }
-                5 => {
-                    // BaseColorFactor
-                    #[cfg(debug_gx3d)]
-                    {
-                        if base_color_factor.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Base color factor");
-                    base_color_factor = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                6 => {
-                    // DoubleSided maybe // in future I gonna think about it
-                    #[cfg(debug_gx3d)]
-                    {
-                        if double_sided.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Double sided");
-                    double_sided = Some(read_value(reader));
-                }
-                7 => {
-                    // Emissive
-                    #[cfg(debug_gx3d)]
-                    {
-                        if emissive.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Emissive");
-                    emissive = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                8 => {
-                    // EmissiveFactor
-                    #[cfg(debug_gx3d)]
-                    {
-                        if emissive_factor.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Emissive factor");
-                    emissive_factor = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                9 => {
-                    // MetallicFactor
-                    #[cfg(debug_gx3d)]
-                    {
-                        if metallic_factor.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Metallic Factor");
-                    metallic_factor = Some(read_value(reader));
-                }
-                10 => {
-                    // MetallicRoughness
-                    #[cfg(debug_gx3d)]
-                    {
-                        if metallic_roughness.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("MetallicRoughness");
-                    metallic_roughness = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                11 => {
-                    // Normal
-                    #[cfg(debug_gx3d)]
-                    {
-                        if normal.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Normal");
-                    normal = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                12 => {
-                    // NormalScale
-                    #[cfg(debug_gx3d)]
-                    {
-                        if normal_scale.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("NormalScale");
-                    normal_scale = Some(read_value(reader));
-                }
-                13 => {
-                    // Occlusion
-                    #[cfg(debug_gx3d)]
-                    {
-                        if occlusion.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("Occlusion");
-                    occlusion = Some(read_tex(&*eng, reader, &mut *texture_manager));
-                }
-                14 => {
-                    // OcclusionStrength
-                    #[cfg(debug_gx3d)]
-                    {
-                        if occlusion_strength.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("OcclusionStrength");
-                    occlusion_strength = Some(read_value(reader));
-                }
-                15 => {
-                    // RoughnessFactor
-                    #[cfg(debug_gx3d)]
-                    {
-                        if roughness_factor.is_some() {
-                            vx_unexpected!();
-                        }
-                    }
-                    #[cfg(debug_gx3d)]
-                    vx_log_i!("RoughnessFactor");
-                    roughness_factor = Some(read_value(reader));
-                }
-                c @ _ => vx_log_f!("Unexpected input key {}", c),
+            GltfAlphaMode::Mask { cutoff } => {
+                uniform.alpha_cutoff = cutoff;
+                (TranslucencyMode::Cutoff, 1.0)
             }
-        }
-        uniform.alpha = vx_unwrap!(alpha);
-        uniform.alpha_cutoff = vx_unwrap!(alpha_cutoff);
-        uniform.metallic_factor = vx_unwrap!(metallic_factor);
-        uniform.normal_scale = vx_unwrap!(normal_scale);
-        uniform.occlusion_strength = vx_unwrap!(occlusion_strength);
-        uniform.roughness_factor = vx_unwrap!(roughness_factor);
-        let _: Real = vx_unwrap!(alpha_mode);
-        let base_color = vx_unwrap!(base_color);
-        let base_color_factor = vx_unwrap!(base_color_factor);
-        let _: Real = vx_unwrap!(double_sided);
-        let emissive = vx_unwrap!(emissive);
-        let emissive_factor = vx_unwrap!(emissive_factor);
-        let metallic_roughness = vx_unwrap!(metallic_roughness);
-        let normal = vx_unwrap!(normal);
-        let occlusion = vx_unwrap!(occlusion);
+            GltfAlphaMode::Blend => (TranslucencyMode::Tansparent, 0.0),
+        };
+        let unlit = if mat.is_unlit { 1.0 } else { 0.0 };
+        // glTF has metallic in blue and roughness in green.
+        uniform.modes = Vec4::new(unlit, opaque, 2.0, 1.0);
+        uniform.base_color_uv_transform = mat.base_color_uv_transform;
+        uniform.metallic_roughness_uv_transform = mat.metallic_roughness_uv_transform;
+        uniform.emissive_uv_transform = mat.emissive_uv_transform;
+        uniform.metallic_factor = mat.metallic_factor;
+        uniform.roughness_factor = mat.roughness_factor;
+        uniform.normal_scale = mat.normal_scale;
+        uniform.occlusion_strength = mat.occlusion_strength;
+        uniform.emissive_strength = mat.emissive_strength;
+        let white = [255, 255, 255, 255];
+        let mut load_texture = |texture: Option<gltf::Texture>, color: [u8; 4]| match texture {
+            Some(texture) => texture_manager.load_gltf::<Texture2D>(&texture, eng, data),
+            None => texture_manager.create_2d_with_color(gapi_engine, color),
+        };
+        let base_color = load_texture(mat.base_color_texture, white);
+        let base_color_factor = load_texture(None, mat.base_color);
+        let metallic_roughness = load_texture(mat.metallic_roughness_texture, white);
+        let normal = load_texture(mat.normal_texture, [127, 127, 255, 255]);
+        let occlusion = load_texture(mat.occlusion_texture, white);
+        let emissive = load_texture(mat.emissive_texture, white);
+        let emissive_factor = load_texture(None, mat.emissive);
         let textures = vec![
             base_color.clone(),
             base_color_factor.clone(),
@@ -399,7 +142,6 @@ impl Material {
             emissive.clone(),
             emissive_factor.clone(),
         ];
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
         let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
         let descriptor_set = descriptor_manager.create_gbuff_set(&uniform_buffer, textures);
         Self {
@@ -417,22 +159,361 @@ impl Material {
         }
     }
 
+    // pub(crate) fn get_uniform_buffer(&self) -> &DynamicBuffer {
+    //     return &self.uniform_buffer;
+    // }
+
+    // pub(crate) fn get_descriptor_set(&self) -> &Arc<DescriptorSet> {
+    //     return &self.descriptor_set;
+    // }
+
+    // Gx3D reader is not ported yet.
+    // pub(crate) fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader) -> Self {
+    //     let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+    //     let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
+    //         .create_dynamic_buffer(size_of::<Uniform>() as isize);
+    //     let mut texture_manager = vx_result!(eng.get_asset_manager().get_texture_manager().write());
+    //     let mut uniform = Uniform::new();
+    //     let mut translucency = TranslucencyMode::Opaque;
+    //     let read_color = |r: &mut Gx3DReader| {
+    //         let read = |r: &mut Gx3DReader| {
+    //             let f = r.read::<Real>() * 255.0;
+    //             #[cfg(debug_gx3d)]
+    //             {
+    //                 if f > 255.5 || f < 0.0 {
+    //                     vx_unexpected!();
+    //                 }
+    //             }
+    //             f as u8
+    //         };
+    //         [read(r), read(r), read(r), read(r)]
+    //     };
+    //     let read_tex =
+    //         |engine: &Engine, reader: &mut Gx3DReader, texture_manager: &mut TextureManager| {
+    //             let t = reader.read_type_id();
+    //             if t == Field::Texture as TypeId {
+    //                 let id: Id = reader.read();
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Texture Id: {:?}", id);
+    //                 texture_manager.load_gx3d(engine, id)
+    //             } else if t == Field::Vector as TypeId {
+    //                 let color = read_color(reader);
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Color: {:?}", &color);
+    //                 texture_manager.create_2d_with_color(&gapi_engine, color)
+    //             } else {
+    //                 vx_unexpected!()
+    //             }
+    //         };
+    //     let read_value = |reader: &mut Gx3DReader| {
+    //         let t = reader.read_type_id();
+    //         if t != Field::Float as TypeId {
+    //             vx_unexpected!();
+    //         }
+    //         let v = reader.read::<Real>();
+    //         #[cfg(debug_gx3d)]
+    //         vx_log_i!("Value: {:?}", v);
+    //         v
+    //     };
+    //     let mut alpha = None;
+    //     let mut alpha_cutoff = None;
+    //     let mut alpha_mode = None;
+    //     let mut base_color = None;
+    //     let mut base_color_factor = None;
+    //     let mut double_sided = None;
+    //     let mut emissive = None;
+    //     let mut emissive_factor = None;
+    //     let mut metallic_factor = None;
+    //     let mut metallic_roughness = None;
+    //     let mut normal = None;
+    //     let mut normal_scale = None;
+    //     let mut occlusion = None;
+    //     let mut occlusion_strength = None;
+    //     let mut roughness_factor = None;
+    //     for _ in 0..15 {
+    //         let it = reader.read_type_id();
+    //         match it {
+    //             1 => {
+    //                 // Alpha
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if alpha.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 let t = reader.read_type_id();
+    //                 if t == Field::Float as TypeId {
+    //                     alpha = Some(reader.read());
+    //                 } else if t == Field::Texture as TypeId {
+    //                     let _: Id = reader.read();
+    //                     alpha = Some(1.0);
+    //                     translucency = TranslucencyMode::Tansparent;
+    //                 } else {
+    //                     vx_unexpected!();
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Alpha is: {:?}", uniform.alpha);
+    //             }
+    //             2 => {
+    //                 // AlphaCutoff
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if alpha_cutoff.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 let t = reader.read_type_id();
+    //                 if t == Field::Float as TypeId {
+    //                     alpha_cutoff = Some(reader.read());
+    //                     if vx_unwrap!(alpha_cutoff) > 0.003 {
+    //                         translucency = TranslucencyMode::Cutoff;
+    //                     }
+    //                 } else {
+    //                     vx_unexpected!();
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Alpha cutoff is: {:?}", uniform.alpha_cutoff);
+    //             }
+    //             3 => {
+    //                 // AlphaMode
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if alpha_mode.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 let t = reader.read_type_id();
+    //                 if t == Field::Float as TypeId {
+    //                     alpha_mode = Some(reader.read());
+    //                     let alpha_mode = vx_unwrap!(alpha_mode);
+    //                     if alpha_mode < 1.0 && 0.0 < alpha_mode {
+    //                         translucency = TranslucencyMode::Cutoff;
+    //                     }
+    //                 } else {
+    //                     vx_unexpected!();
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Translucency is: {:?}", translucency);
+    //             }
+    //             4 => {
+    //                 // BaseColor
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if base_color.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 let t = reader.read_type_id();
+    //                 base_color = Some(if t == Field::Texture as TypeId {
+    //                     let id: Id = reader.read();
+    //                     #[cfg(debug_gx3d)]
+    //                     vx_log_i!("Base color is: texture<{:?}>", id);
+    //                     texture_manager.load_gx3d(&*eng, id)
+    //                 } else if t == Field::Vector as TypeId {
+    //                     let color = read_color(reader);
+    //                     if color[3] < 254 {
+    //                         translucency = TranslucencyMode::Tansparent;
+    //                     }
+    //                     #[cfg(debug_gx3d)]
+    //                     vx_log_i!("Base color is: {:?}", &color);
+    //                     texture_manager.create_2d_with_color(&*gapi_engine, color)
+    //                 } else {
+    //                     vx_unexpected!()
+    //                 });
+    //             }
+    //             5 => {
+    //                 // BaseColorFactor
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if base_color_factor.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Base color factor");
+    //                 base_color_factor = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             6 => {
+    //                 // DoubleSided maybe // in future I gonna think about it
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if double_sided.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Double sided");
+    //                 double_sided = Some(read_value(reader));
+    //             }
+    //             7 => {
+    //                 // Emissive
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if emissive.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Emissive");
+    //                 emissive = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             8 => {
+    //                 // EmissiveFactor
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if emissive_factor.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Emissive factor");
+    //                 emissive_factor = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             9 => {
+    //                 // MetallicFactor
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if metallic_factor.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Metallic Factor");
+    //                 metallic_factor = Some(read_value(reader));
+    //             }
+    //             10 => {
+    //                 // MetallicRoughness
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if metallic_roughness.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("MetallicRoughness");
+    //                 metallic_roughness = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             11 => {
+    //                 // Normal
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if normal.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Normal");
+    //                 normal = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             12 => {
+    //                 // NormalScale
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if normal_scale.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("NormalScale");
+    //                 normal_scale = Some(read_value(reader));
+    //             }
+    //             13 => {
+    //                 // Occlusion
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if occlusion.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("Occlusion");
+    //                 occlusion = Some(read_tex(&*eng, reader, &mut *texture_manager));
+    //             }
+    //             14 => {
+    //                 // OcclusionStrength
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if occlusion_strength.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("OcclusionStrength");
+    //                 occlusion_strength = Some(read_value(reader));
+    //             }
+    //             15 => {
+    //                 // RoughnessFactor
+    //                 #[cfg(debug_gx3d)]
+    //                 {
+    //                     if roughness_factor.is_some() {
+    //                         vx_unexpected!();
+    //                     }
+    //                 }
+    //                 #[cfg(debug_gx3d)]
+    //                 vx_log_i!("RoughnessFactor");
+    //                 roughness_factor = Some(read_value(reader));
+    //             }
+    //             c @ _ => vx_log_f!("Unexpected input key {}", c),
+    //         }
+    //     }
+    //     uniform.alpha = vx_unwrap!(alpha);
+    //     uniform.alpha_cutoff = vx_unwrap!(alpha_cutoff);
+    //     uniform.metallic_factor = vx_unwrap!(metallic_factor);
+    //     uniform.normal_scale = vx_unwrap!(normal_scale);
+    //     uniform.occlusion_strength = vx_unwrap!(occlusion_strength);
+    //     uniform.roughness_factor = vx_unwrap!(roughness_factor);
+    //     let _: Real = vx_unwrap!(alpha_mode);
+    //     let base_color = vx_unwrap!(base_color);
+    //     let base_color_factor = vx_unwrap!(base_color_factor);
+    //     let _: Real = vx_unwrap!(double_sided);
+    //     let emissive = vx_unwrap!(emissive);
+    //     let emissive_factor = vx_unwrap!(emissive_factor);
+    //     let metallic_roughness = vx_unwrap!(metallic_roughness);
+    //     let normal = vx_unwrap!(normal);
+    //     let occlusion = vx_unwrap!(occlusion);
+    //     let textures = vec![
+    //         base_color.clone(),
+    //         base_color_factor.clone(),
+    //         metallic_roughness.clone(),
+    //         normal.clone(),
+    //         occlusion.clone(),
+    //         emissive.clone(),
+    //         emissive_factor.clone(),
+    //     ];
+    //     let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+    //     let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
+    //     let descriptor_set = descriptor_manager.create_gbuff_set(&uniform_buffer, textures);
+    //     Self {
+    //         base_color,
+    //         base_color_factor,
+    //         metallic_roughness,
+    //         normal,
+    //         occlusion,
+    //         emissive,
+    //         emissive_factor,
+    //         translucency,
+    //         uniform,
+    //         uniform_buffer,
+    //         descriptor_set,
+    //     }
+    // }
+
     pub fn default(eng: &Engine) -> Self {
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+        let gapi_engine = &eng.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
         let mut texture_manager = vx_result!(eng.get_asset_manager().get_texture_manager().write());
         let uniform = Uniform::new();
         let translucency = TranslucencyMode::default();
-        let base_color = texture_manager.create_2d_with_color(&*gapi_engine, [255, 255, 255, 255]);
+        let base_color = texture_manager.create_2d_with_color(gapi_engine, [255, 255, 255, 255]);
         let base_color_factor =
-            texture_manager.create_2d_with_color(&*gapi_engine, [255, 255, 255, 255]);
-        let emissive = texture_manager.create_2d_with_color(&*gapi_engine, [255, 255, 255, 255]);
-        let emissive_factor = texture_manager.create_2d_with_color(&*gapi_engine, [0, 0, 0, 0]);
+            texture_manager.create_2d_with_color(gapi_engine, [255, 255, 255, 255]);
+        let emissive = texture_manager.create_2d_with_color(gapi_engine, [255, 255, 255, 255]);
+        let emissive_factor = texture_manager.create_2d_with_color(gapi_engine, [0, 0, 0, 0]);
         let metallic_roughness =
-            texture_manager.create_2d_with_color(&*gapi_engine, [255, 255, 255, 255]);
-        let normal = texture_manager.create_2d_with_color(&*gapi_engine, [127, 127, 255, 255]);
-        let occlusion = texture_manager.create_2d_with_color(&*gapi_engine, [255, 255, 255, 255]);
+            texture_manager.create_2d_with_color(gapi_engine, [255, 255, 255, 255]);
+        let normal = texture_manager.create_2d_with_color(gapi_engine, [127, 127, 255, 255]);
+        let occlusion = texture_manager.create_2d_with_color(gapi_engine, [255, 255, 255, 255]);
         let textures = vec![
             base_color.clone(),
             base_color_factor.clone(),
@@ -469,8 +550,7 @@ impl Material {
             self.emissive.clone(),
             self.emissive_factor.clone(),
         ];
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
-        let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
+        let mut descriptor_manager = vx_result!(eng.gapi_engine.get_descriptor_manager().write());
         self.descriptor_set = descriptor_manager.create_gbuff_set(&self.uniform_buffer, textures);
     }
 
@@ -480,29 +560,32 @@ impl Material {
 
     pub(crate) fn bind_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         let buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
-        cmd.bind_gbuff_material_descriptor(&*self.descriptor_set, &*buffer);
+        cmd.bind_gbuff_material_descriptor(&self.descriptor_set, &buffer);
     }
 
     pub(crate) fn bind_unlit(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         let buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
-        cmd.bind_unlit_material_descriptor(&*self.descriptor_set, &*buffer);
+        cmd.bind_unlit_material_descriptor(&self.descriptor_set, &buffer);
     }
 
     pub(crate) fn bind_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         let buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
-        cmd.bind_shadow_mapper_material_descriptor(&*self.descriptor_set, &*buffer);
+        cmd.bind_shadow_mapper_material_descriptor(&self.descriptor_set, &buffer);
     }
 
     pub fn set_base_color(&mut self, eng: &Engine, r: u8, g: u8, b: u8, a: u8) {
         let mut texmgr = vx_result!(eng.get_asset_manager().get_texture_manager().write());
-        self.base_color =
-            texmgr.create_2d_with_color(&*vx_result!(eng.get_gapi_engine().read()), [r, g, b, a]);
+        self.base_color = texmgr.create_2d_with_color(&eng.gapi_engine, [r, g, b, a]);
     }
 
     pub fn set_base_color_texture(&mut self, base_color: Arc<RwLock<dyn Texture>>) {
         self.base_color = base_color;
     }
 
+    pub fn get_translucency(&self) -> &TranslucencyMode {
+        return &self.translucency;
+    }
+
     pub fn set_metallic_factor(&mut self, v: Real) {
         self.uniform.metallic_factor = v;
     }
@@ -511,3 +594,70 @@ impl Material {
         self.uniform.roughness_factor = v;
     }
 }
+
+#[cfg(all(test, software_gapi))]
+mod test {
+    use super::super::super::gapi::engine::Engine as GraphicApiEngine;
+    use super::super::super::math::matrix::Mat4;
+    use super::super::config::Configurations;
+    use super::super::mesh::{Base as MeshBase, Mesh};
+    use super::super::pipeline::PipelineType;
+    use super::*;
+
+    // Model and its projection are identity, so the triangle is in clip space.
+    fn render_unlit(engine: &mut Engine, material: &mut Material) {
+        let mut vertices = [0.0; 36];
+        for (i, (x, y)) in [(-0.5, -0.5), (-0.5, 0.5), (0.5, -0.5)].iter().enumerate() {
+            vertices[i * 12..i * 12 + 3].copy_from_slice(&[*x, *y, 0.5]);
+        }
+        let mesh = MeshBase::new(&vertices, &[0, 1, 2], 1.0, engine);
+        let geng = &mut engine.gapi_engine;
+        let pipeline = vx_result!(geng.get_pipeline_manager().write()).create(
+            geng.get_render_pass().clone(),
+            PipelineType::Unlit,
+            &Configurations::default(),
+        );
+        let model_data = [Mat4::identity(), Mat4::identity()];
+        let mut model_uniform = vx_result!(geng.get_buffer_manager().write())
+            .create_dynamic_buffer(size_of::<[Mat4; 2]>() as isize);
+        let model_set = vx_result!(geng.get_descriptor_manager().write())
+            .create_buffer_only_set(&model_uniform);
+        geng.start_rendering();
+        let frame_number = geng.get_frame_number();
+        model_uniform.update(&model_data, frame_number);
+        material.update_uniform_buffer(frame_number);
+        let mut cmd = geng.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        geng.get_current_framebuffer().begin(&mut cmd);
+        cmd.bind_pipeline(&pipeline);
+        let buffer = vx_result!(model_uniform.get_buffer(frame_number).read());
+        cmd.bind_unlit_model_descriptor(&model_set, &buffer);
+        material.bind_unlit(&mut cmd, frame_number);
+        mesh.render_unlit(&mut cmd, frame_number, 0);
+        cmd.end_render_pass();
+        cmd.end();
+        let signal = geng.create_semaphore();
+        geng.submit(geng.get_starting_semaphore(), &cmd, &signal);
+        geng.end(&signal);
+    }
+
+    #[test]
+    fn gltf_base_color_test() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1.0, 0.5, 0.0, 1.0]}}]
+        }"#;
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let mut engine = Engine::new_with_gapi_engine(GraphicApiEngine::new_offscreen(16, 16));
+        let mut material =
+            Material::new_with_gltf(&engine, &vx_unwrap!(document.materials().next()), &[]);
+        render_unlit(&mut engine, &mut material);
+        let capture = vx_unwrap!(engine.capture_frame());
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * capture.get_width() + x) * 4) as usize;
+            capture.get_pixels()[i..i + 4].to_vec()
+        };
+        assert_eq!(pixel(5, 5), [255, 128, 0, 255]);
+        assert_eq!(pixel(12, 12), [0, 0, 0, 0]);
+    }
+}
@@ -1,18 +1,26 @@
-use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
+// use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
 use super::super::core::object::Object as CoreObject;
 use super::super::core::types::{Id, Real};
+use super::super::math::vector::Vec3;
 use super::buffer::Static as StaticBuffer;
 use super::command::Buffer as CmdBuffer;
 use super::engine::Engine;
+use super::mesh_builder::{get_vector3_bits, MeshBuilder, VERTEX_FLOATS_COUNT};
+use super::mesh_simplifier::generate_lods;
+use super::morph::{Target as MorphTarget, Targets as MorphTargets};
 use super::object::{Base as ObjectBase, Object};
+use super::skeleton::{interleave_skins, VertexSkin, SKIN_FLOATS_COUNT};
 use std::collections::BTreeMap;
 use std::mem::size_of;
 use std::sync::{Arc, RwLock, Weak};
 
-use cgmath;
-use cgmath::prelude::*;
 use gltf;
 
+// Every lod aims for half of the triangles of its previous one, and the
+// first one may move the surface by one percent of the mesh radius.
+const LOD_REDUCTION: Real = 0.5;
+const LOD_MAX_RELATIVE_ERROR: Real = 0.01;
+
 #[repr(u8)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum TypeId {
@@ -23,10 +31,40 @@ pub trait Mesh: Object {
     fn is_shadow_caster(&self) -> bool;
     fn is_transparent(&self) -> bool;
     fn get_occlusion_culling_radius(&self) -> Real;
+    fn get_lods_count(&self) -> usize;
+    /// Skinned meshes have the joints and the weights in their vertices and
+    /// only the skinned pipelines can render them.
+    fn is_skinned(&self) -> bool;
+    /// Models morph their own copies of the vertices of the meshes that have
+    /// morph targets.
+    fn get_morph_targets(&self) -> Option<&MorphTargets>;
     fn update(&mut self, frame_number: usize);
-    fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize);
-    fn render_unlit(&self, cmd: &mut CmdBuffer, frame_number: usize);
-    fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize);
+    /// Lods after the last one of the mesh render its last one.
+    fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize);
+    fn render_unlit(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize);
+    fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize);
+    /// Vertices replace the ones of the mesh and must have their layout.
+    fn render_gbuffer_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        frame_number: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    );
+    fn render_unlit_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        frame_number: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    );
+    fn render_shadow_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        frame_number: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    );
 }
 
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -34,7 +72,7 @@ pub struct Manager {
     engine: Option<Weak<RwLock<Engine>>>,
     meshes: BTreeMap<Id, Weak<RwLock<dyn Mesh>>>,
     name_to_id: BTreeMap<String, Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -43,13 +81,14 @@ impl Manager {
             engine: None,
             meshes: BTreeMap::new(),
             name_to_id: BTreeMap::new(),
-            gx3d_table: None,
+            // gx3d_table: None,
         }
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // Gx3D reader is not ported yet.
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub fn load_gltf(
         &mut self,
@@ -57,6 +96,7 @@ impl Manager {
         engine: &Engine,
         data: &[u8],
     ) -> Arc<RwLock<dyn Mesh>> {
+        vx_profile_zone!("mesh::load_gltf");
         let mesh = Base::new_with_gltf_primitive(primitive, engine, data);
         let id = mesh.get_id();
         let name = mesh.get_name();
@@ -68,24 +108,25 @@ impl Manager {
         return mesh;
     }
 
-    pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Mesh>> {
-        if let Some(mesh) = self.meshes.get(&id) {
-            if let Some(mesh) = mesh.upgrade() {
-                return mesh;
-            }
-        }
-        let gx3d_table = vx_unwrap!(self.gx3d_table.as_mut());
-        gx3d_table.goto(id);
-        let reader = gx3d_table.get_mut_reader();
-        let t = reader.read_type_id();
-        let mesh: Arc<RwLock<dyn Mesh>> = if t == TypeId::Base as u8 {
-            Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
-        } else {
-            vx_unimplemented!();
-        };
-        self.meshes.insert(id, Arc::downgrade(&mesh));
-        return mesh;
-    }
+    // pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Mesh>> {
+    //     vx_profile_zone!("mesh::load_gx3d");
+    //     if let Some(mesh) = self.meshes.get(&id) {
+    //         if let Some(mesh) = mesh.upgrade() {
+    //             return mesh;
+    //         }
+    //     }
+    //     let gx3d_table = vx_unwrap!(self.gx3d_table.as_mut());
+    //     gx3d_table.goto(id);
+    //     let reader = gx3d_table.get_mut_reader();
+    //     let t = reader.read_type_id();
+    //     let mesh: Arc<RwLock<dyn Mesh>> = if t == TypeId::Base as u8 {
+    //         Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
+    //     } else {
+    //         vx_unimplemented!();
+    //     };
+    //     self.meshes.insert(id, Arc::downgrade(&mesh));
+    //     return mesh;
+    // }
 
     pub fn add(&mut self, mesh: &Arc<RwLock<dyn Mesh>>) {
         let id = vx_result!(mesh.read()).get_id();
@@ -106,65 +147,115 @@ impl Manager {
         return mesh;
     }
 
-    pub fn create_cube(&mut self, aspect: Real) -> Arc<RwLock<dyn Mesh>> {
+    pub fn create_with_builder(&mut self, builder: &MeshBuilder) -> Arc<RwLock<dyn Mesh>> {
         let eng = vx_unwrap!(vx_unwrap!(&self.engine).upgrade());
         let eng = vx_result!(eng.read());
-        let vertices = [
-            -aspect, -aspect, aspect, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, -aspect, aspect, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, aspect, aspect, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, aspect, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            //----------------------------------------------------------------------------------
-            -aspect, -aspect, -aspect, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, -aspect, -aspect, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, aspect, -aspect, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, -aspect, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
-            //----------------------------------------------------------------------------------
-            aspect, -aspect, -aspect, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, -aspect, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, -aspect, aspect, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, aspect, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            //----------------------------------------------------------------------------------
-            -aspect, -aspect, -aspect, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, aspect, -aspect, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, -aspect, aspect, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, aspect, aspect, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            //----------------------------------------------------------------------------------
-            -aspect, aspect, -aspect, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, -aspect, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, aspect, aspect, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, aspect, aspect, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            //----------------------------------------------------------------------------------
-            -aspect, -aspect, -aspect, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, -aspect, -aspect, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            -aspect, -aspect, aspect, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0,
-            // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
-            aspect, -aspect, aspect, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0,
-            0.0,
-            //----------------------------------------------------------------------------------
-        ];
-        let indices = [
-            0, 1, 2, 1, 3, 2, 4, 6, 5, 5, 6, 7, 8, 9, 10, 9, 11, 10, 12, 14, 13, 13, 14, 15, 16,
-            18, 17, 17, 18, 19, 20, 21, 22, 21, 23, 22,
-        ];
-        self.create(&vertices, &indices, aspect.abs() * 1.732050809, &*eng)
+        return self.create(
+            &builder.get_vertex_buffer_data(),
+            builder.get_indices(),
+            builder.get_occlusion_culling_radius(),
+            &*eng,
+        );
+    }
+
+    /// The lods are simplified from the builder, up to lods_count of them.
+    pub fn create_with_lods(
+        &mut self,
+        builder: &MeshBuilder,
+        lods_count: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        let eng = vx_unwrap!(vx_unwrap!(&self.engine).upgrade());
+        let eng = vx_result!(eng.read());
+        let mesh = Base::new_with_builder(
+            builder,
+            lods_count,
+            builder.get_occlusion_culling_radius(),
+            &*eng,
+        );
+        let mesh_id = mesh.get_id();
+        let mesh: Arc<RwLock<dyn Mesh>> = Arc::new(RwLock::new(mesh));
+        self.meshes.insert(mesh_id, Arc::downgrade(&mesh));
+        return mesh;
+    }
+
+    pub fn create_cube(&mut self, aspect: Real) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_cube(aspect));
+    }
+
+    pub fn create_plane(
+        &mut self,
+        width: Real,
+        depth: Real,
+        width_subdivisions: usize,
+        depth_subdivisions: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_plane(
+            width,
+            depth,
+            width_subdivisions,
+            depth_subdivisions,
+        ));
+    }
+
+    pub fn create_screen_quad(&mut self) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_screen_quad());
+    }
+
+    pub fn create_uv_sphere(
+        &mut self,
+        radius: Real,
+        segments: usize,
+        rings: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_uv_sphere(radius, segments, rings));
+    }
+
+    pub fn create_icosphere(&mut self, radius: Real, subdivisions: usize) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_icosphere(radius, subdivisions));
+    }
+
+    pub fn create_cylinder(
+        &mut self,
+        radius: Real,
+        height: Real,
+        segments: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_cylinder(radius, height, segments));
+    }
+
+    pub fn create_cone(
+        &mut self,
+        radius: Real,
+        height: Real,
+        segments: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_cone(radius, height, segments));
+    }
+
+    pub fn create_capsule(
+        &mut self,
+        radius: Real,
+        height: Real,
+        segments: usize,
+        rings: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self
+            .create_with_builder(&MeshBuilder::new_capsule(radius, height, segments, rings));
+    }
+
+    pub fn create_torus(
+        &mut self,
+        major_radius: Real,
+        minor_radius: Real,
+        major_segments: usize,
+        minor_segments: usize,
+    ) -> Arc<RwLock<dyn Mesh>> {
+        return self.create_with_builder(&MeshBuilder::new_torus(
+            major_radius,
+            minor_radius,
+            major_segments,
+            minor_segments,
+        ));
     }
 
     pub fn set_engine(&mut self, e: Weak<RwLock<Engine>>) {
@@ -172,13 +263,113 @@ impl Manager {
     }
 }
 
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Lod {
+    index_buffer: StaticBuffer,
+    indices_count: u32,
+}
+
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Base {
     obj_base: ObjectBase,
     vertex_buffer: StaticBuffer,
-    index_buffer: StaticBuffer,
-    indices_count: u32,
+    lods: Vec<Lod>,
     occlusion_culling_radius: Real,
+    is_skinned: bool,
+    morph_targets: Option<MorphTargets>,
+}
+
+impl Base {
+    // Meshes may have fewer lods than what their models ask for.
+    fn get_lod(&self, lod: usize) -> &Lod {
+        return &self.lods[lod.min(self.lods.len() - 1)];
+    }
+
+    fn generate_builder_lods(
+        builder: &MeshBuilder,
+        lods_count: usize,
+        occlusion_culling_radius: Real,
+    ) -> Vec<Vec<u32>> {
+        return generate_lods(
+            builder.get_vertices(),
+            builder.get_indices(),
+            lods_count,
+            LOD_REDUCTION,
+            occlusion_culling_radius * LOD_MAX_RELATIVE_ERROR,
+        );
+    }
+
+    // The builder may split the vertices for the normals and the tangents,
+    // the first vertex that it makes from a glTF vertex keeps its index and
+    // the others are appended, they find their glTF vertex by their position.
+    // Collapses of the lods keep the existing vertices, so the lods keep the
+    // glTF vertices too.
+    fn get_gltf_vertex_indices(vertices: &[Real], builder: &MeshBuilder) -> Vec<usize> {
+        let count = vertices.len() / VERTEX_FLOATS_COUNT;
+        let mut positions = BTreeMap::new();
+        for (i, v) in vertices.chunks_exact(VERTEX_FLOATS_COUNT).enumerate() {
+            positions
+                .entry(get_vector3_bits(Vec3::new(v[0], v[1], v[2])))
+                .or_insert(i);
+        }
+        return builder
+            .get_vertices()
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                if i < count {
+                    i
+                } else {
+                    *vx_unwrap!(positions.get(&get_vector3_bits(v.position)))
+                }
+            })
+            .collect();
+    }
+
+    fn read_gltf_skins(
+        primitive: &gltf::Primitive,
+        data: &[u8],
+        gltf_indices: &[usize],
+    ) -> Option<Vec<VertexSkin>> {
+        let reader = primitive.reader(|_| Some(data));
+        let joints = reader.read_joints(0)?.into_u16();
+        let weights = vx_unwrap!(reader.read_weights(0)).into_f32();
+        let skins: Vec<VertexSkin> = joints
+            .zip(weights)
+            .map(|(j, w)| VertexSkin::new(j, w))
+            .collect();
+        return Some(gltf_indices.iter().map(|i| skins[*i]).collect());
+    }
+
+    fn read_gltf_deltas<I>(deltas: Option<I>, gltf_indices: &[usize]) -> Vec<Vec3>
+    where
+        I: Iterator<Item = [Real; 3]>,
+    {
+        let deltas: Vec<[Real; 3]> = match deltas {
+            Some(d) => d.collect(),
+            None => return Vec::new(),
+        };
+        return gltf_indices
+            .iter()
+            .map(|i| Vec3::from_array(deltas[*i]))
+            .collect();
+    }
+
+    fn read_gltf_morph_targets(
+        primitive: &gltf::Primitive,
+        data: &[u8],
+        gltf_indices: &[usize],
+    ) -> Vec<MorphTarget> {
+        let reader = primitive.reader(|_| Some(data));
+        return reader
+            .read_morph_targets()
+            .map(|(positions, normals, tangents)| MorphTarget {
+                positions: Self::read_gltf_deltas(positions, gltf_indices),
+                normals: Self::read_gltf_deltas(normals, gltf_indices),
+                tangents: Self::read_gltf_deltas(tangents, gltf_indices),
+            })
+            .collect();
+    }
 }
 
 impl Base {
@@ -189,17 +380,21 @@ impl Base {
     ) -> Self {
         let count = vx_unwrap!(primitive.get(&gltf::Semantic::Positions)).count();
         let mut vertex_buffer = vec![0u8; count * size_of::<Real>() * 12];
+        // Models put the culling sphere at their origin.
         let occlusion_culling_radius = {
-            let mut center = cgmath::Vector3::new(0.0, 0.0, 0.0);
-            let p1 = &primitive.bounding_box().max;
-            let p1 = cgmath::Vector3::new(p1[0], p1[1], p1[2]);
-            center += p1;
-            let p2 = &primitive.bounding_box().max;
-            center += cgmath::Vector3::new(p2[0], p2[1], p2[2]);
-            center *= 0.5;
-            p1.distance(center)
+            let bounding_box = primitive.bounding_box();
+            let min = Vec3::from_array(bounding_box.min).length();
+            let max = Vec3::from_array(bounding_box.max).length();
+            min.max(max)
         };
+        let mut has_normals = false;
+        let mut has_tangents = false;
         for (sem, acc) in primitive.attributes() {
+            match sem {
+                // They are read with their own types after the other ones.
+                gltf::Semantic::Joints(_) | gltf::Semantic::Weights(_) => continue,
+                _ => {}
+            }
             let view = vx_unwrap!(acc.view());
             match acc.data_type() {
                 gltf::accessor::DataType::F32 => {}
@@ -229,6 +424,7 @@ impl Base {
                     }
                 }
                 gltf::Semantic::Normals => {
+                    has_normals = true;
                     let mut buffer_index = 12; // previous ending index
                     let mut data_index = offset;
                     for _ in 0..count {
@@ -241,6 +437,7 @@ impl Base {
                     }
                 }
                 gltf::Semantic::Tangents => {
+                    has_tangents = true;
                     let mut buffer_index = 24; // previous ending index
                     let mut data_index = offset;
                     for _ in 0..count {
@@ -276,24 +473,57 @@ impl Base {
             _ => vx_log_f!("Only u32 data type is acceptable for indices."),
         }
         let view = vx_unwrap!(indices.view());
-        let indices_count = indices.count();
         let offset = view.offset();
         let end = view.length() + offset;
-        let index_buffer = &data[offset..end];
-        let indices_count = indices_count as u32;
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
-        let vertex_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
-            .create_static_buffer_with_vec(&vertex_buffer);
-        let index_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
-            .create_static_buffer_with_vec(&index_buffer);
-        let obj_base = ObjectBase::new();
-        Self {
-            obj_base,
-            vertex_buffer,
-            index_buffer,
-            indices_count,
-            occlusion_culling_radius,
+        let vertices: Vec<Real> = vertex_buffer
+            .chunks_exact(size_of::<Real>())
+            .map(|b| Real::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let indices: Vec<u32> = data[offset..end]
+            .chunks_exact(size_of::<u32>())
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let mut builder = MeshBuilder::new_with_vertex_buffer_data(&vertices, &indices);
+        // glTF asks for flat normals and MikkTSpace tangents when they are
+        // missing, the calculated tangents only follow the conventions of
+        // MikkTSpace.
+        if !has_normals {
+            builder.calculate_flat_normals();
+        }
+        if !has_tangents {
+            builder.calculate_tangents();
+        }
+        // Culling stays on, the double sided surfaces get their back faces.
+        if primitive.material().double_sided() {
+            builder.add_back_faces();
+        }
+        let lods_count = engine.get_config().mesh_lods_count as usize;
+        let lods = Self::generate_builder_lods(&builder, lods_count, occlusion_culling_radius);
+        let gltf_indices = Self::get_gltf_vertex_indices(&vertices, &builder);
+        let skins = Self::read_gltf_skins(primitive, data, &gltf_indices);
+        let morph_targets = Self::read_gltf_morph_targets(primitive, data, &gltf_indices);
+        let mut vertices = builder.get_vertex_buffer_data();
+        let mut vertex_floats_count = VERTEX_FLOATS_COUNT;
+        if let Some(skins) = &skins {
+            vertices = interleave_skins(&vertices, VERTEX_FLOATS_COUNT, skins);
+            vertex_floats_count += SKIN_FLOATS_COUNT;
+        }
+        // Targets with full weights are the farthest that the vertices go.
+        let mut occlusion_culling_radius = occlusion_culling_radius;
+        for t in &morph_targets {
+            occlusion_culling_radius +=
+                t.positions.iter().fold(0.0, |r: Real, d| r.max(d.length()));
         }
+        let mut mesh = Self::new_with_lods(&vertices, &lods, occlusion_culling_radius, engine);
+        mesh.is_skinned = skins.is_some();
+        if morph_targets.len() != 0 {
+            mesh.morph_targets = Some(MorphTargets::new(
+                vertices,
+                vertex_floats_count,
+                morph_targets,
+            ));
+        }
+        return mesh;
     }
 
     pub fn new(
@@ -302,56 +532,98 @@ impl Base {
         occlusion_culling_radius: Real,
         engine: &Engine,
     ) -> Self {
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
-        let mut buffer_manager = vx_result!(gapi_engine.get_buffer_manager().write());
+        return Self::new_with_lods(
+            vertices,
+            &[indices.to_vec()],
+            occlusion_culling_radius,
+            engine,
+        );
+    }
+
+    /// Lods share the vertices and the first one must be the full mesh.
+    pub fn new_with_lods(
+        vertices: &[Real],
+        lods: &[Vec<u32>],
+        occlusion_culling_radius: Real,
+        engine: &Engine,
+    ) -> Self {
+        let mut buffer_manager = vx_result!(engine.gapi_engine.get_buffer_manager().write());
         let vertex_buffer = buffer_manager.create_static_buffer_with_vec(vertices);
-        let index_buffer = buffer_manager.create_static_buffer_with_vec(indices);
+        let lods = lods
+            .iter()
+            .map(|indices| Lod {
+                index_buffer: buffer_manager.create_static_buffer_with_vec(indices),
+                indices_count: indices.len() as u32,
+            })
+            .collect();
         let obj_base = ObjectBase::new();
         Self {
             obj_base,
             vertex_buffer,
-            index_buffer,
-            indices_count: indices.len() as u32,
+            lods,
             occlusion_culling_radius,
+            is_skinned: false,
+            morph_targets: None,
         }
     }
 
-    pub fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let number_of_vertex_attribute = reader.read_u8() as usize;
-        #[cfg(debug_mode)]
-        {
-            if number_of_vertex_attribute != 12 {
-                vx_unexpected!();
-            }
-        }
-        let vertex_count = reader.read::<u64>() as usize;
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Number of vertices is: {}", vertex_count);
-        let number_of_floats = vertex_count * number_of_vertex_attribute;
-        let mut vertices = vec![0.0; number_of_floats];
-        for i in 0..number_of_floats {
-            vertices[i] = reader.read();
-        }
-        let indices = reader.read_array::<u32>();
-        let occlusion_culling_radius = reader.read();
-        let obj_base = ObjectBase::new_with_id(my_id);
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
-        let mut buffer_manager = vx_result!(gapi_engine.get_buffer_manager().write());
-        let vertex_buffer = buffer_manager.create_static_buffer_with_vec(&vertices);
-        let index_buffer = buffer_manager.create_static_buffer_with_vec(&indices);
-        let indices_count = indices.len() as u32;
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Number of indices is: {}", indices_count);
-        #[cfg(debug_gx3d)]
-        vx_log_i!("Occlusion culling radius is: {}", occlusion_culling_radius);
-        Self {
-            obj_base,
-            vertex_buffer,
-            index_buffer,
-            indices_count,
+    /// The lods are simplified from the builder, up to lods_count of them.
+    pub fn new_with_builder(
+        builder: &MeshBuilder,
+        lods_count: usize,
+        occlusion_culling_radius: Real,
+        engine: &Engine,
+    ) -> Self {
+        let lods = Self::generate_builder_lods(builder, lods_count, occlusion_culling_radius);
+        return Self::new_with_lods(
+            &builder.get_vertex_buffer_data(),
+            &lods,
             occlusion_culling_radius,
-        }
+            engine,
+        );
     }
+
+    // Gx3D reader is not ported yet.
+    // pub fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let number_of_vertex_attribute = reader.read_u8() as usize;
+    //     #[cfg(debug_mode)]
+    //     {
+    //         if number_of_vertex_attribute != 12 {
+    //             vx_unexpected!();
+    //         }
+    //     }
+    //     let vertex_count = reader.read::<u64>() as usize;
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Number of vertices is: {}", vertex_count);
+    //     let number_of_floats = vertex_count * number_of_vertex_attribute;
+    //     let mut vertices = vec![0.0; number_of_floats];
+    //     for i in 0..number_of_floats {
+    //         vertices[i] = reader.read();
+    //     }
+    //     let indices = reader.read_array::<u32>();
+    //     let occlusion_culling_radius = reader.read();
+    //     let obj_base = ObjectBase::new_with_id(my_id);
+    //     let gapi_engine = vx_result!(engine.get_gapi_engine().read());
+    //     let mut buffer_manager = vx_result!(gapi_engine.get_buffer_manager().write());
+    //     let vertex_buffer = buffer_manager.create_static_buffer_with_vec(&vertices);
+    //     let index_buffer = buffer_manager.create_static_buffer_with_vec(&indices);
+    //     let indices_count = indices.len() as u32;
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Number of indices is: {}", indices_count);
+    //     #[cfg(debug_gx3d)]
+    //     vx_log_i!("Occlusion culling radius is: {}", occlusion_culling_radius);
+    //     Self {
+    //         obj_base,
+    //         vertex_buffer,
+    //         lods: vec![Lod {
+    //             index_buffer,
+    //             indices_count,
+    //         }],
+    //         occlusion_culling_radius,
+    //         is_skinned: false,
+    //         morph_targets: None,
+    //     }
+    // }
 }
 
 impl CoreObject for Base {
@@ -398,17 +670,295 @@ impl Mesh for Base {
         return self.occlusion_culling_radius;
     }
 
+    fn get_lods_count(&self) -> usize {
+        return self.lods.len();
+    }
+
+    fn is_skinned(&self) -> bool {
+        return self.is_skinned;
+    }
+
+    fn get_morph_targets(&self) -> Option<&MorphTargets> {
+        return self.morph_targets.as_ref();
+    }
+
     fn update(&mut self, _: usize) {}
 
-    fn render_gbuffer(&self, cmd: &mut CmdBuffer, _: usize) {
-        cmd.render_gbuff(&self.vertex_buffer, &self.index_buffer, self.indices_count);
+    fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize) {
+        self.render_gbuffer_with_vertices(cmd, frame_number, lod, &self.vertex_buffer);
     }
 
-    fn render_unlit(&self, cmd: &mut CmdBuffer, _: usize) {
-        cmd.render_unlit(&self.vertex_buffer, &self.index_buffer, self.indices_count);
+    fn render_unlit(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize) {
+        self.render_unlit_with_vertices(cmd, frame_number, lod, &self.vertex_buffer);
     }
 
-    fn render_shadow(&self, cmd: &mut CmdBuffer, _: usize) {
-        cmd.render_shadow_mapper(&self.vertex_buffer, &self.index_buffer, self.indices_count);
+    fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize, lod: usize) {
+        self.render_shadow_with_vertices(cmd, frame_number, lod, &self.vertex_buffer);
+    }
+
+    fn render_gbuffer_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        _: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    ) {
+        let lod = self.get_lod(lod);
+        if self.is_skinned {
+            cmd.render_skinned_gbuff(vertices, &lod.index_buffer, lod.indices_count);
+        } else {
+            cmd.render_gbuff(vertices, &lod.index_buffer, lod.indices_count);
+        }
+    }
+
+    fn render_unlit_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        _: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    ) {
+        #[cfg(debug_mode)]
+        {
+            if self.is_skinned {
+                vx_log_f!("Unlit pipeline does not support skinned meshes.");
+            }
+        }
+        let lod = self.get_lod(lod);
+        cmd.render_unlit(vertices, &lod.index_buffer, lod.indices_count);
+    }
+
+    fn render_shadow_with_vertices(
+        &self,
+        cmd: &mut CmdBuffer,
+        _: usize,
+        lod: usize,
+        vertices: &StaticBuffer,
+    ) {
+        let lod = self.get_lod(lod);
+        if self.is_skinned {
+            cmd.render_skinned_shadow_mapper(vertices, &lod.index_buffer, lod.indices_count);
+        } else {
+            cmd.render_shadow_mapper(vertices, &lod.index_buffer, lod.indices_count);
+        }
+    }
+}
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::super::super::math::matrix::Mat4;
+    use super::super::lod::{get_screen_size, Selector as LodSelector, DEFAULT_THRESHOLDS};
+    use super::*;
+
+    fn new_engine() -> Arc<RwLock<Engine>> {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        return Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+    }
+
+    fn get_size(buffer: &StaticBuffer) -> isize {
+        return vx_result!(buffer.get_buffer().read()).get_size();
+    }
+
+    #[test]
+    fn generators_test() {
+        let engine = new_engine();
+        let mut manager = Manager::new();
+        manager.set_engine(Arc::downgrade(&engine));
+        let meshes = [
+            manager.create_cube(1.0),
+            manager.create_plane(2.0, 2.0, 4, 4),
+            manager.create_screen_quad(),
+            manager.create_uv_sphere(1.0, 16, 8),
+            manager.create_icosphere(1.0, 2),
+            manager.create_cylinder(1.0, 2.0, 12),
+            manager.create_cone(1.0, 2.0, 12),
+            manager.create_capsule(0.5, 1.0, 12, 4),
+            manager.create_torus(1.0, 0.25, 16, 8),
+        ];
+        assert_eq!(manager.meshes.len(), meshes.len());
+        for mesh in &meshes {
+            let id = vx_result!(mesh.read()).get_id();
+            let registered = vx_unwrap!(vx_unwrap!(manager.meshes.get(&id)).upgrade());
+            assert!(Arc::ptr_eq(&registered, mesh));
+            assert_eq!(vx_result!(mesh.read()).get_lods_count(), 1);
+        }
+        let radius = vx_result!(meshes[3].read()).get_occlusion_culling_radius();
+        assert!((radius - 1.0).abs() < 1e-5);
+        let id = vx_result!(meshes[0].read()).get_id();
+        drop(meshes);
+        assert!(vx_unwrap!(manager.meshes.get(&id)).upgrade().is_none());
+    }
+
+    #[test]
+    fn buffers_test() {
+        let engine = new_engine();
+        let engine = vx_result!(engine.read());
+        // A flat grid simplifies without any error.
+        let builder = MeshBuilder::new_plane(2.0, 2.0, 16, 16);
+        let radius = builder.get_occlusion_culling_radius();
+        let mesh = Base::new_with_builder(&builder, 3, radius, &*engine);
+        let vertices_size = builder.get_vertex_buffer_data().len() * size_of::<Real>();
+        assert_eq!(get_size(&mesh.vertex_buffer), vertices_size as isize);
+        assert_eq!(mesh.get_lods_count(), 3);
+        assert_eq!(
+            mesh.get_lod(0).indices_count as usize,
+            builder.get_indices().len()
+        );
+        for lod in &mesh.lods {
+            let size = lod.indices_count as usize * size_of::<u32>();
+            assert_eq!(get_size(&lod.index_buffer), size as isize);
+        }
+        // Lods past the last one fall back to it.
+        let last = mesh.get_lod(mesh.get_lods_count() - 1).indices_count;
+        assert_eq!(mesh.get_lod(10).indices_count, last);
+    }
+
+    #[test]
+    fn lod_selection_test() {
+        let engine = new_engine();
+        let engine = vx_result!(engine.read());
+        let builder = MeshBuilder::new_plane(2.0, 2.0, 16, 16);
+        let radius = builder.get_occlusion_culling_radius();
+        let mesh = Base::new_with_builder(&builder, 3, radius, &*engine);
+        // Perspective with 90 degrees vertical field of view at the origin.
+        let (n, f) = (0.1, 100.0);
+        #[rustfmt::skip]
+        let projection = Mat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, f / (n - f), -1.0,
+            0.0, 0.0, n * f / (n - f), 0.0,
+        ]);
+        let mut selector = LodSelector::new();
+        let mut select = |distance: Real| {
+            let location = Vec3::new(0.0, 0.0, -distance);
+            let size = get_screen_size(&projection, &projection, radius, location);
+            return mesh.get_lod(selector.update(size)).indices_count;
+        };
+        // Going away the lods only get coarser, down to the last one.
+        let mut indices_counts = Vec::new();
+        for i in 1..40 {
+            indices_counts.push(select(i as Real));
+        }
+        assert_eq!(indices_counts[0] as usize, builder.get_indices().len());
+        for c in indices_counts.windows(2) {
+            assert!(c[1] <= c[0]);
+        }
+        let last = mesh.get_lod(mesh.get_lods_count() - 1).indices_count;
+        assert_eq!(*vx_unwrap!(indices_counts.last()), last);
+        // Around the first threshold the lod sticks to its side of the band.
+        assert!(mesh.get_lod(1).indices_count < indices_counts[0]);
+        let distance = radius / DEFAULT_THRESHOLDS[0];
+        assert_eq!(select(distance * 1.05), mesh.get_lod(1).indices_count);
+        assert_eq!(select(distance * 0.95), mesh.get_lod(1).indices_count);
+        assert_eq!(select(distance * 0.85), indices_counts[0]);
+        assert_eq!(select(distance * 1.05), indices_counts[0]);
+        assert_eq!(select(distance * 1.15), mesh.get_lod(1).indices_count);
+    }
+
+    #[test]
+    fn gltf_test() {
+        let engine = new_engine();
+        let engine = vx_result!(engine.read());
+        // A skinned triangle, every vertex is bound to the joint of its own
+        // index, and a morph target that lifts its first vertex along z.
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "meshes": [{
+                "primitives": [{
+                    "attributes": {
+                        "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2,
+                        "JOINTS_0": 5, "WEIGHTS_0": 6
+                    },
+                    "indices": 3,
+                    "targets": [{"POSITION": 4}]
+                }],
+                "weights": [0.0]
+            }],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3"},
+                {"bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2"},
+                {"bufferView": 3, "componentType": 5125, "count": 3, "type": "SCALAR"},
+                {"bufferView": 4, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0.0, 0.0, 0.0], "max": [0.0, 0.0, 1.0]},
+                {"bufferView": 5, "componentType": 5123, "count": 3, "type": "VEC4"},
+                {"bufferView": 6, "componentType": 5126, "count": 3, "type": "VEC4"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 72, "byteLength": 24},
+                {"buffer": 0, "byteOffset": 96, "byteLength": 12},
+                {"buffer": 0, "byteOffset": 108, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 144, "byteLength": 24},
+                {"buffer": 0, "byteOffset": 168, "byteLength": 48}
+            ],
+            "buffers": [{"byteLength": 216}]
+        }"#;
+        let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let mut floats: Vec<Real> = Vec::new();
+        for p in &positions {
+            floats.extend_from_slice(p);
+        }
+        for _ in 0..3 {
+            floats.extend_from_slice(&[0.0, 0.0, 1.0]);
+        }
+        floats.extend_from_slice(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        let mut data = Vec::new();
+        for f in &floats {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        for f in &[0.0 as Real, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in 0..3u16 {
+            for j in &[i, 0, 0, 0] {
+                data.extend_from_slice(&j.to_le_bytes());
+            }
+        }
+        for _ in 0..3 {
+            for f in &[1.0 as Real, 0.0, 0.0, 0.0] {
+                data.extend_from_slice(&f.to_le_bytes());
+            }
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let primitive = vx_unwrap!(vx_unwrap!(document.meshes().next()).primitives().next());
+        let mesh = Base::new_with_gltf_primitive(&primitive, &*engine, &data);
+        assert!(mesh.is_skinned());
+        // Targets with full weights are inside the culling sphere.
+        assert!(mesh.get_occlusion_culling_radius() >= 1.0);
+        let targets = vx_unwrap!(mesh.get_morph_targets());
+        assert_eq!(targets.get_targets().len(), 1);
+        let floats_count = targets.get_vertex_floats_count();
+        assert_eq!(floats_count, VERTEX_FLOATS_COUNT + SKIN_FLOATS_COUNT);
+        let vertices = targets.get_vertices();
+        assert_eq!(
+            get_size(&mesh.vertex_buffer) as usize,
+            vertices.len() * size_of::<Real>()
+        );
+        let mut morphed = Vec::new();
+        targets.apply(&[1.0], &mut morphed);
+        let split = vertices.chunks_exact(floats_count);
+        assert!(split.len() >= positions.len());
+        for (v, m) in split.zip(morphed.chunks_exact(floats_count)) {
+            let index = vx_unwrap!(positions.iter().position(|p| p[..] == v[..3]));
+            // Vertices that the builder splits keep their glTF skin.
+            assert_eq!(v[VERTEX_FLOATS_COUNT], index as Real);
+            assert_eq!(v[VERTEX_FLOATS_COUNT + 4], 1.0);
+            let lift = if index == 0 { 1.0 } else { 0.0 };
+            assert_eq!(m[..3], [v[0], v[1], v[2] + lift]);
+        }
     }
 }
@@ -0,0 +1,1013 @@
+use super::super::core::types::Real;
+use super::super::math::vector::{Vec3, Vec4};
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+
+/// Number of floats of a vertex in the vertex buffer, position, normal,
+/// tangent and uv.
+pub const VERTEX_FLOATS_COUNT: usize = 12;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Vertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    /// The w is the handedness, the bitangent is cross(normal, tangent) * w
+    /// like in glTF.
+    pub tangent: Vec4,
+    pub uv: [Real; 2],
+}
+
+impl Vertex {
+    pub fn new(position: Vec3, normal: Vec3, tangent: Vec4, uv: [Real; 2]) -> Self {
+        Self {
+            position,
+            normal,
+            tangent,
+            uv,
+        }
+    }
+}
+
+/// Assembles the vertices and the indices of a mesh on the CPU.
+///
+/// Front faces are counter-clockwise and uvs start from the top left like in
+/// glTF. The generators map u to the right and v to the bottom of a surface
+/// when it is seen from its front, so their tangents have positive handedness.
+#[derive(Clone, Default)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct MeshBuilder {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Reads the vertices from the layout of the vertex buffer of the meshes.
+    pub fn new_with_vertex_buffer_data(data: &[Real], indices: &[u32]) -> Self {
+        let vertices = data
+            .chunks_exact(VERTEX_FLOATS_COUNT)
+            .map(|v| {
+                Vertex::new(
+                    Vec3::new(v[0], v[1], v[2]),
+                    Vec3::new(v[3], v[4], v[5]),
+                    Vec4::new(v[6], v[7], v[8], v[9]),
+                    [v[10], v[11]],
+                )
+            })
+            .collect();
+        Self {
+            vertices,
+            indices: indices.to_vec(),
+        }
+    }
+
+    /// A cube centered at origin with the half size of aspect.
+    pub fn new_cube(aspect: Real) -> Self {
+        let faces = [
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ];
+        let mut builder = Self::new();
+        for &(normal, right, down) in &faces {
+            let normal = Vec3::from_array(normal);
+            let right = Vec3::from_array(right);
+            let down = Vec3::from_array(down);
+            builder.add_grid(1, 1, |i, j| {
+                let (u, v) = (j as Real, i as Real);
+                let position = (normal + right * (2.0 * u - 1.0) + down * (2.0 * v - 1.0)) * aspect;
+                Vertex::new(position, normal, right.extend(1.0), [u, v])
+            });
+        }
+        return builder;
+    }
+
+    /// A plane on the xz, facing +y, centered at origin.
+    pub fn new_plane(
+        width: Real,
+        depth: Real,
+        width_subdivisions: usize,
+        depth_subdivisions: usize,
+    ) -> Self {
+        let columns = width_subdivisions.max(1);
+        let rows = depth_subdivisions.max(1);
+        let mut builder = Self::new();
+        builder.add_grid(rows, columns, |i, j| {
+            let u = j as Real / columns as Real;
+            let v = i as Real / rows as Real;
+            Vertex::new(
+                Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec4::new(1.0, 0.0, 0.0, 1.0),
+                [u, v],
+            )
+        });
+        return builder;
+    }
+
+    /// A quad that covers the screen in the normalized device coordinates of
+    /// Vulkan, y is downward and the uv of the top left is zero. It faces -z,
+    /// toward the viewer.
+    pub fn new_screen_quad() -> Self {
+        let mut builder = Self::new();
+        for &(x, y) in &[(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            builder.add_vertex(Vertex::new(
+                Vec3::new(x, y, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                Vec4::new(1.0, 0.0, 0.0, 1.0),
+                [(x + 1.0) * 0.5, (y + 1.0) * 0.5],
+            ));
+        }
+        builder.add_triangle(0, 2, 3);
+        builder.add_triangle(0, 3, 1);
+        return builder;
+    }
+
+    /// The seam is at +z, rings go from +y to -y.
+    pub fn new_uv_sphere(radius: Real, segments: usize, rings: usize) -> Self {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
+        let mut builder = Self::new();
+        builder.add_grid(rings, segments, |i, j| {
+            let u = j as Real / segments as Real;
+            let v = i as Real / rings as Real;
+            Self::get_sphere_vertex(radius, u * 2.0 * PI, v * PI, u, v)
+        });
+        return builder;
+    }
+
+    /// A subdivided icosahedron, it has no poles with dense triangles. Its uvs
+    /// are mapped like the uv sphere and the vertices on the seam are doubled.
+    pub fn new_icosphere(radius: Real, subdivisions: usize) -> Self {
+        let t = (1.0 + (5.0 as Real).sqrt()) * 0.5;
+        let mut positions: Vec<Vec3> = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .iter()
+        .map(|p| Vec3::from_array(*p).normalized())
+        .collect();
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+        for _ in 0..subdivisions {
+            let mut middles = BTreeMap::new();
+            let mut get_middle = |a: u32, b: u32| {
+                let key = (a.min(b), a.max(b));
+                if let Some(m) = middles.get(&key) {
+                    return *m;
+                }
+                let m = positions.len() as u32;
+                positions.push((positions[a as usize] + positions[b as usize]).normalized());
+                middles.insert(key, m);
+                return m;
+            };
+            let mut subdivided = Vec::with_capacity(triangles.len() * 4);
+            for &[a, b, c] in &triangles {
+                let ab = get_middle(a, b);
+                let bc = get_middle(b, c);
+                let ca = get_middle(c, a);
+                subdivided.push([a, ab, ca]);
+                subdivided.push([b, bc, ab]);
+                subdivided.push([c, ca, bc]);
+                subdivided.push([ab, bc, ca]);
+            }
+            triangles = subdivided;
+        }
+        let get_u = |p: &Vec3| {
+            let u = p.x().atan2(p.z()) / (2.0 * PI);
+            return if u < 0.0 { u + 1.0 } else { u };
+        };
+        let is_pole = |p: &Vec3| p.x().abs() < 1e-6 && p.z().abs() < 1e-6;
+        let mut builder = Self::new();
+        // Same position with the same u is shared.
+        let mut vertices = BTreeMap::new();
+        for triangle in &triangles {
+            let ps = [
+                positions[triangle[0] as usize],
+                positions[triangle[1] as usize],
+                positions[triangle[2] as usize],
+            ];
+            let mut us = [get_u(&ps[0]), get_u(&ps[1]), get_u(&ps[2])];
+            let others: Vec<usize> = (0..3).filter(|&k| !is_pole(&ps[k])).collect();
+            let min_u = others.iter().map(|&k| us[k]).fold(1.0, Real::min);
+            let max_u = others.iter().map(|&k| us[k]).fold(0.0, Real::max);
+            if max_u - min_u > 0.5 {
+                for &k in &others {
+                    if us[k] < 0.5 {
+                        us[k] += 1.0;
+                    }
+                }
+            }
+            for k in 0..3 {
+                if is_pole(&ps[k]) {
+                    us[k] = others.iter().map(|&o| us[o]).sum::<Real>() / others.len() as Real;
+                }
+            }
+            let mut indices = [0; 3];
+            for k in 0..3 {
+                let key = (triangle[k], us[k].to_bits());
+                indices[k] = match vertices.get(&key) {
+                    Some(index) => *index,
+                    None => {
+                        let theta = ps[k].y().max(-1.0).min(1.0).acos();
+                        let v = theta / PI;
+                        let vertex =
+                            Self::get_sphere_vertex(radius, us[k] * 2.0 * PI, theta, us[k], v);
+                        let index = builder.add_vertex(vertex);
+                        vertices.insert(key, index);
+                        index
+                    }
+                };
+            }
+            builder.add_triangle(indices[0], indices[1], indices[2]);
+        }
+        return builder;
+    }
+
+    /// A capped cylinder along y centered at origin.
+    pub fn new_cylinder(radius: Real, height: Real, segments: usize) -> Self {
+        let segments = segments.max(3);
+        let half = height * 0.5;
+        let mut builder = Self::new();
+        builder.add_grid(1, segments, |i, j| {
+            let u = j as Real / segments as Real;
+            let (sin, cos) = (u * 2.0 * PI).sin_cos();
+            let normal = Vec3::new(sin, 0.0, cos);
+            Vertex::new(
+                Vec3::new(radius * sin, half - height * i as Real, radius * cos),
+                normal,
+                Vec4::new(cos, 0.0, -sin, 1.0),
+                [u, i as Real],
+            )
+        });
+        builder.add_disc(half, radius, segments, true);
+        builder.add_disc(-half, radius, segments, false);
+        return builder;
+    }
+
+    /// A capped cone along y centered at origin, the apex is at +y.
+    pub fn new_cone(radius: Real, height: Real, segments: usize) -> Self {
+        let segments = segments.max(3);
+        let half = height * 0.5;
+        let slant = (radius * radius + height * height).sqrt();
+        let mut builder = Self::new();
+        builder.add_grid(1, segments, |i, j| {
+            let u = j as Real / segments as Real;
+            let (sin, cos) = (u * 2.0 * PI).sin_cos();
+            let ring_radius = radius * i as Real;
+            Vertex::new(
+                Vec3::new(
+                    ring_radius * sin,
+                    half - height * i as Real,
+                    ring_radius * cos,
+                ),
+                Vec3::new(height * sin, radius, height * cos) / slant,
+                Vec4::new(cos, 0.0, -sin, 1.0),
+                [u, i as Real],
+            )
+        });
+        builder.add_disc(-half, radius, segments, false);
+        return builder;
+    }
+
+    /// A cylinder along y with hemispheres on its ends, height does not
+    /// include the hemispheres. Its v is proportional to the arc length.
+    pub fn new_capsule(radius: Real, height: Real, segments: usize, rings: usize) -> Self {
+        let segments = segments.max(3);
+        let rings = rings.max(1);
+        let half = height * 0.5;
+        let quarter = 0.5 * PI * radius;
+        let length = 2.0 * quarter + height;
+        let mut builder = Self::new();
+        builder.add_grid(2 * rings + 1, segments, |i, j| {
+            let u = j as Real / segments as Real;
+            let (theta, y, v) = if i <= rings {
+                let r = i as Real / rings as Real;
+                (r * 0.5 * PI, half, r * quarter / length)
+            } else {
+                let r = (i - rings - 1) as Real / rings as Real;
+                (
+                    (1.0 + r) * 0.5 * PI,
+                    -half,
+                    (quarter + height + r * quarter) / length,
+                )
+            };
+            let mut vertex = Self::get_sphere_vertex(radius, u * 2.0 * PI, theta, u, v);
+            vertex.position += Vec3::new(0.0, y, 0.0);
+            vertex
+        });
+        return builder;
+    }
+
+    /// A torus around y, the tube goes from the outer equator downward.
+    pub fn new_torus(
+        major_radius: Real,
+        minor_radius: Real,
+        major_segments: usize,
+        minor_segments: usize,
+    ) -> Self {
+        let major_segments = major_segments.max(3);
+        let minor_segments = minor_segments.max(3);
+        let mut builder = Self::new();
+        builder.add_grid(minor_segments, major_segments, |i, j| {
+            let u = j as Real / major_segments as Real;
+            let v = i as Real / minor_segments as Real;
+            let (sin_phi, cos_phi) = (u * 2.0 * PI).sin_cos();
+            let (sin_psi, cos_psi) = (v * 2.0 * PI).sin_cos();
+            let outward = Vec3::new(sin_phi, 0.0, cos_phi);
+            let normal = outward * cos_psi - Vec3::new(0.0, 1.0, 0.0) * sin_psi;
+            Vertex::new(
+                outward * major_radius + normal * minor_radius,
+                normal,
+                Vec4::new(cos_phi, 0.0, -sin_phi, 1.0),
+                [u, v],
+            )
+        });
+        return builder;
+    }
+
+    pub fn add_vertex(&mut self, vertex: Vertex) -> u32 {
+        self.vertices.push(vertex);
+        return (self.vertices.len() - 1) as u32;
+    }
+
+    pub fn add_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.push(a);
+        self.indices.push(b);
+        self.indices.push(c);
+    }
+
+    /// Appends the other mesh, its indices are offset to its new vertices.
+    pub fn append(&mut self, other: &Self) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices
+            .extend(other.indices.iter().map(|i| i + offset));
+    }
+
+    /// Adds a copy of every triangle that faces the other side, for the
+    /// surfaces that are seen from both of their sides. The copied vertices
+    /// have flipped normals and handednesses, so their bitangents do not
+    /// change.
+    pub fn add_back_faces(&mut self) {
+        let offset = self.vertices.len() as u32;
+        let back_vertices: Vec<Vertex> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let t = v.tangent;
+                Vertex::new(
+                    v.position,
+                    -v.normal,
+                    Vec4::new(t.x(), t.y(), t.z(), -t.w()),
+                    v.uv,
+                )
+            })
+            .collect();
+        self.vertices.extend(back_vertices);
+        let back_indices: Vec<u32> = self
+            .indices
+            .chunks_exact(3)
+            .flat_map(|t| vec![t[0] + offset, t[2] + offset, t[1] + offset])
+            .collect();
+        self.indices.extend(back_indices);
+    }
+
+    /// Adds a (rows + 1) * (columns + 1) grid of vertices, rows go along v and
+    /// columns along u. The triangles that are collapsed, like the ones on
+    /// the poles of spheres, are skipped.
+    pub fn add_grid<F>(&mut self, rows: usize, columns: usize, mut f: F)
+    where
+        F: FnMut(usize, usize) -> Vertex,
+    {
+        let first = self.vertices.len() as u32;
+        for i in 0..=rows {
+            for j in 0..=columns {
+                let vertex = f(i, j);
+                self.add_vertex(vertex);
+            }
+        }
+        let stride = columns as u32 + 1;
+        for i in 0..rows as u32 {
+            for j in 0..columns as u32 {
+                let a = first + i * stride + j;
+                let b = a + stride;
+                let c = b + 1;
+                let d = a + 1;
+                for &(x, y, z) in &[(a, b, c), (a, c, d)] {
+                    if !self.is_collapsed(x, y, z) {
+                        self.add_triangle(x, y, z);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_disc(&mut self, y: Real, radius: Real, segments: usize, is_upward: bool) {
+        let normal = if is_upward {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            -Vec3::new(0.0, 1.0, 0.0)
+        };
+        let tangent = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        // Seen from the front, v goes toward +z on the top and -z on the bottom.
+        let v_sign = if is_upward { 0.5 } else { -0.5 };
+        let center = self.add_vertex(Vertex::new(
+            Vec3::new(0.0, y, 0.0),
+            normal,
+            tangent,
+            [0.5, 0.5],
+        ));
+        for j in 0..segments {
+            let (sin, cos) = (j as Real * 2.0 * PI / segments as Real).sin_cos();
+            self.add_vertex(Vertex::new(
+                Vec3::new(radius * sin, y, radius * cos),
+                normal,
+                tangent,
+                [0.5 + 0.5 * sin, 0.5 + v_sign * cos],
+            ));
+        }
+        for j in 0..segments as u32 {
+            let a = center + 1 + j;
+            let b = center + 1 + (j + 1) % segments as u32;
+            if is_upward {
+                self.add_triangle(center, a, b);
+            } else {
+                self.add_triangle(center, b, a);
+            }
+        }
+    }
+
+    fn get_sphere_vertex(radius: Real, phi: Real, theta: Real, u: Real, v: Real) -> Vertex {
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let normal = Vec3::new(sin_theta * sin_phi, cos_theta, sin_theta * cos_phi);
+        return Vertex::new(
+            normal * radius,
+            normal,
+            Vec4::new(cos_phi, 0.0, -sin_phi, 1.0),
+            [u, v],
+        );
+    }
+
+    fn is_collapsed(&self, a: u32, b: u32, c: u32) -> bool {
+        let p = self.vertices[a as usize].position;
+        let e1 = self.vertices[b as usize].position - p;
+        let e2 = self.vertices[c as usize].position - p;
+        let longest = e1.length_squared().max(e2.length_squared());
+        return e1.cross(e2).length_squared() <= 1e-10 * longest * longest;
+    }
+
+    /// Smooth area weighted normals, vertices that are not shared between
+    /// triangles keep their faces normal.
+    pub fn calculate_normals(&mut self) {
+        let mut normals = vec![Vec3::zero(); self.vertices.len()];
+        for t in self.indices.chunks_exact(3) {
+            let p0 = self.vertices[t[0] as usize].position;
+            let n = (self.vertices[t[1] as usize].position - p0)
+                .cross(self.vertices[t[2] as usize].position - p0);
+            for &i in t {
+                normals[i as usize] += n;
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            if normal.length_squared() > 0.0 {
+                vertex.normal = normal.normalized();
+            }
+        }
+    }
+
+    /// Angle weighted normals that are smoothed across the faces that share
+    /// a position when the angle between their normals is not more than
+    /// max_angle. Zero gives flat normals and PI smooths everything. Vertices
+    /// that end up with several normals are split.
+    pub fn calculate_normals_with_angle(&mut self, max_angle: Real) {
+        let zero = Vec3::zero();
+        let corners_count = self.indices.len() / 3 * 3;
+        let mut face_normals = Vec::with_capacity(corners_count / 3);
+        let mut weights = Vec::with_capacity(corners_count);
+        let mut shared_corners = BTreeMap::new();
+        for t in self.indices[..corners_count].chunks_exact(3) {
+            let p = [
+                self.vertices[t[0] as usize].position,
+                self.vertices[t[1] as usize].position,
+                self.vertices[t[2] as usize].position,
+            ];
+            let n = (p[1] - p[0]).cross(p[2] - p[0]);
+            face_normals.push(if n.length_squared() > 0.0 {
+                n.normalized()
+            } else {
+                zero
+            });
+            for c in 0..3 {
+                weights.push(get_corner_angle(p[c], p[(c + 1) % 3], p[(c + 2) % 3], zero));
+                shared_corners
+                    .entry(get_vector3_bits(p[c]))
+                    .or_insert_with(Vec::new)
+                    .push(weights.len() - 1);
+            }
+        }
+        let min_cos = max_angle.cos();
+        let mut normals = Vec::with_capacity(corners_count);
+        for (corner, &index) in self.indices[..corners_count].iter().enumerate() {
+            let face = corner / 3;
+            let n = face_normals[face];
+            let mut sum = zero;
+            let position = self.vertices[index as usize].position;
+            for &other in &shared_corners[&get_vector3_bits(position)] {
+                let other_face = other / 3;
+                if other_face == face || n.dot(face_normals[other_face]) >= min_cos {
+                    sum += face_normals[other_face] * weights[other];
+                }
+            }
+            normals.push(if sum.length_squared() > 0.0 {
+                Some(sum.normalized())
+            } else {
+                None
+            });
+        }
+        self.rebuild_corners(|corner, vertex| {
+            if let Some(normal) = normals[corner] {
+                vertex.normal = normal;
+            }
+        });
+    }
+
+    /// Every triangle gets its own normal, like glTF asks for the meshes that
+    /// do not have normals.
+    pub fn calculate_flat_normals(&mut self) {
+        self.calculate_normals_with_angle(0.0);
+    }
+
+    /// Tangents in the conventions of MikkTSpace, it is not a port of its
+    /// reference implementation and its results are not bit exact with
+    /// MikkTSpace, so the normal maps that are baked for MikkTSpace may show
+    /// small seams on the smoothed vertices. Corners of the triangles are
+    /// welded by their position, normal and uv, then the tangents of each
+    /// welded vertex are accumulated separately for the two handednesses and
+    /// weighted by the angles of the corners, so the vertices that are shared
+    /// by mirrored uvs are split. Bitangents point to the decreasing v, like
+    /// MikkTSpace does for glTF with its flipped uvs.
+    pub fn calculate_tangents(&mut self) {
+        let zero = Vec3::zero();
+        let corners_count = self.indices.len() / 3 * 3;
+        let mut welds = BTreeMap::new();
+        let mut corner_welds = Vec::with_capacity(corners_count);
+        for &index in &self.indices[..corners_count] {
+            let v = &self.vertices[index as usize];
+            let p = get_vector3_bits(v.position);
+            let n = get_vector3_bits(v.normal);
+            let key = [
+                p[0],
+                p[1],
+                p[2],
+                n[0],
+                n[1],
+                n[2],
+                (v.uv[0] + 0.0).to_bits(),
+                (v.uv[1] + 0.0).to_bits(),
+            ];
+            let next = welds.len();
+            corner_welds.push(*welds.entry(key).or_insert(next));
+        }
+        // The first sum is for the positive handedness and the second one for
+        // the negative one.
+        let mut sums = vec![[zero; 2]; welds.len()];
+        let mut corner_handednesses = vec![None; corners_count];
+        for (face, t) in self.indices[..corners_count].chunks_exact(3).enumerate() {
+            let v = [
+                &self.vertices[t[0] as usize],
+                &self.vertices[t[1] as usize],
+                &self.vertices[t[2] as usize],
+            ];
+            let e1 = v[1].position - v[0].position;
+            let e2 = v[2].position - v[0].position;
+            let d1 = [v[1].uv[0] - v[0].uv[0], v[1].uv[1] - v[0].uv[1]];
+            let d2 = [v[2].uv[0] - v[0].uv[0], v[2].uv[1] - v[0].uv[1]];
+            let det = d1[0] * d2[1] - d2[0] * d1[1];
+            // Triangles without uv area do not contribute.
+            if det.abs() <= 1e-6 * d1[0].hypot(d1[1]) * d2[0].hypot(d2[1]) {
+                continue;
+            }
+            let tangent = (e1 * d2[1] - e2 * d1[1]) / det;
+            // The v of glTF goes down, so the positive uv area has negative
+            // handedness.
+            let handedness = if det < 0.0 { 0 } else { 1 };
+            for c in 0..3 {
+                let corner = face * 3 + c;
+                let n = v[c].normal;
+                let t = tangent - n * n.dot(tangent);
+                corner_handednesses[corner] = Some(handedness);
+                if t.length_squared() <= 0.0 {
+                    continue;
+                }
+                let weight = get_corner_angle(
+                    v[c].position,
+                    v[(c + 1) % 3].position,
+                    v[(c + 2) % 3].position,
+                    n,
+                );
+                sums[corner_welds[corner]][handedness] += t.normalized() * weight;
+            }
+        }
+        self.rebuild_corners(|corner, vertex| {
+            let sums = &sums[corner_welds[corner]];
+            // The triangles without uv area follow the other triangles of
+            // their vertices.
+            let handedness = match corner_handednesses[corner] {
+                Some(h) => h,
+                None if sums[0].length_squared() >= sums[1].length_squared() => 0,
+                None => 1,
+            };
+            let n = vertex.normal;
+            let t = sums[handedness] - n * n.dot(sums[handedness]);
+            let t = if t.length_squared() > 0.0 {
+                t.normalized()
+            } else {
+                get_perpendicular(n)
+            };
+            vertex.tangent = t.extend(if handedness == 0 { 1.0 } else { -1.0 });
+        });
+    }
+
+    // Every corner of the triangles gets the vertex that f makes from its
+    // original vertex. Equal results of a vertex are shared, the first one
+    // replaces it and the others are appended.
+    fn rebuild_corners<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut Vertex),
+    {
+        let originals = self.vertices.clone();
+        let mut is_replaced = vec![false; originals.len()];
+        let mut made = BTreeMap::new();
+        for corner in 0..self.indices.len() / 3 * 3 {
+            let index = self.indices[corner];
+            let mut vertex = originals[index as usize];
+            f(corner, &mut vertex);
+            let key = (index, get_vertex_bits(&vertex));
+            if let Some(&i) = made.get(&key) {
+                self.indices[corner] = i;
+                continue;
+            }
+            let i = if is_replaced[index as usize] {
+                self.add_vertex(vertex)
+            } else {
+                is_replaced[index as usize] = true;
+                self.vertices[index as usize] = vertex;
+                index
+            };
+            made.insert(key, i);
+            self.indices[corner] = i;
+        }
+    }
+
+    pub fn get_vertices(&self) -> &[Vertex] {
+        return &self.vertices;
+    }
+
+    pub fn get_indices(&self) -> &[u32] {
+        return &self.indices;
+    }
+
+    /// Radius of the sphere around origin that contains the mesh.
+    pub fn get_occlusion_culling_radius(&self) -> Real {
+        return self
+            .vertices
+            .iter()
+            .map(|v| v.position.length_squared())
+            .fold(0.0, Real::max)
+            .sqrt();
+    }
+
+    /// Vertices in the layout of the vertex buffer of the meshes.
+    pub fn get_vertex_buffer_data(&self) -> Vec<Real> {
+        let mut data = Vec::with_capacity(self.vertices.len() * VERTEX_FLOATS_COUNT);
+        for v in &self.vertices {
+            data.extend_from_slice(&v.position.to_array());
+            data.extend_from_slice(&v.normal.to_array());
+            data.extend_from_slice(&v.tangent.to_array());
+            data.extend_from_slice(&v.uv);
+        }
+        return data;
+    }
+}
+
+// Negative zeros are made positive, so they are equal to zeros.
+pub(super) fn get_vector3_bits(v: Vec3) -> [u32; 3] {
+    return [
+        (v.x() + 0.0).to_bits(),
+        (v.y() + 0.0).to_bits(),
+        (v.z() + 0.0).to_bits(),
+    ];
+}
+
+fn get_vertex_bits(v: &Vertex) -> [u32; VERTEX_FLOATS_COUNT] {
+    let mut bits = [0; VERTEX_FLOATS_COUNT];
+    let values = [
+        v.position.x(),
+        v.position.y(),
+        v.position.z(),
+        v.normal.x(),
+        v.normal.y(),
+        v.normal.z(),
+        v.tangent.x(),
+        v.tangent.y(),
+        v.tangent.z(),
+        v.tangent.w(),
+        v.uv[0],
+        v.uv[1],
+    ];
+    for (b, v) in bits.iter_mut().zip(values.iter()) {
+        *b = (v + 0.0).to_bits();
+    }
+    return bits;
+}
+
+// The angle between the edges of a corner after they are projected on the
+// plane of the normal, like the weights of the corners in MikkTSpace.
+fn get_corner_angle(p: Vec3, next: Vec3, previous: Vec3, normal: Vec3) -> Real {
+    let e1 = next - p;
+    let e1 = e1 - normal * normal.dot(e1);
+    let e2 = previous - p;
+    let e2 = e2 - normal * normal.dot(e2);
+    if e1.length_squared() <= 0.0 || e2.length_squared() <= 0.0 {
+        return 0.0;
+    }
+    return e1
+        .normalized()
+        .dot(e2.normalized())
+        .max(-1.0)
+        .min(1.0)
+        .acos();
+}
+
+fn get_perpendicular(n: Vec3) -> Vec3 {
+    let axis = if n.x().abs() < 0.9 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    return (axis - n * n.dot(axis)).normalized();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(builder: &MeshBuilder, max_compared_normal_y: Real) {
+        let vertices = builder.get_vertices();
+        let indices = builder.get_indices();
+        assert!(!indices.is_empty());
+        assert_eq!(indices.len() % 3, 0);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        for v in vertices {
+            assert!((v.normal.length() - 1.0).abs() < 1e-4);
+            assert!((v.tangent.truncate().length() - 1.0).abs() < 1e-4);
+            assert!(v.normal.dot(v.tangent.truncate()).abs() < 1e-4);
+            assert_eq!(v.tangent.w(), 1.0);
+            // The doubled seam vertices of the icosphere may go a bit over one.
+            assert!(v.uv[0] >= 0.0 && v.uv[0] < 1.5 && v.uv[1] >= 0.0 && v.uv[1] <= 1.0);
+        }
+        // Front faces are counter-clockwise.
+        for t in indices.chunks_exact(3) {
+            let [v0, v1, v2] = [
+                &vertices[t[0] as usize],
+                &vertices[t[1] as usize],
+                &vertices[t[2] as usize],
+            ];
+            let face = (v1.position - v0.position).cross(v2.position - v0.position);
+            assert!(face.dot(v0.normal + v1.normal + v2.normal) > 0.0);
+        }
+        // The analytic tangents must agree with the ones from the uvs, except
+        // near the poles of the spherical mappings that distort the uvs.
+        let mut calculated = builder.clone();
+        calculated.calculate_tangents();
+        for (v, c) in vertices.iter().zip(calculated.get_vertices()) {
+            if v.normal.y().abs() > max_compared_normal_y {
+                continue;
+            }
+            assert!(v.tangent.truncate().dot(c.tangent.truncate()) > 0.9);
+            assert_eq!(v.tangent.w(), c.tangent.w());
+        }
+        assert_eq!(
+            builder.get_vertex_buffer_data().len(),
+            vertices.len() * VERTEX_FLOATS_COUNT
+        );
+    }
+
+    #[test]
+    fn generators_test() {
+        let cube = MeshBuilder::new_cube(2.0);
+        check(&cube, 1.0);
+        assert_eq!(cube.get_vertices().len(), 24);
+        assert_eq!(cube.get_indices().len(), 36);
+        assert!((cube.get_occlusion_culling_radius() - 2.0 * (3.0 as Real).sqrt()).abs() < 1e-5);
+        let plane = MeshBuilder::new_plane(4.0, 2.0, 4, 2);
+        check(&plane, 1.0);
+        assert_eq!(plane.get_vertices().len(), 15);
+        assert_eq!(plane.get_indices().len(), 4 * 2 * 6);
+        check(&MeshBuilder::new_screen_quad(), 1.0);
+        let sphere = MeshBuilder::new_uv_sphere(1.5, 16, 8);
+        check(&sphere, 1.0);
+        // Each pole ring has one triangle per segment.
+        assert_eq!(sphere.get_indices().len(), (16 * 8 * 2 - 2 * 16) * 3);
+        assert!((sphere.get_occlusion_culling_radius() - 1.5).abs() < 1e-5);
+        for subdivisions in 0..4 {
+            let icosphere = MeshBuilder::new_icosphere(2.0, subdivisions);
+            check(&icosphere, 0.8);
+            assert_eq!(
+                icosphere.get_indices().len(),
+                20 * 4usize.pow(subdivisions as u32) * 3
+            );
+            for v in icosphere.get_vertices() {
+                assert!((v.position.length() - 2.0).abs() < 1e-5);
+            }
+        }
+        check(&MeshBuilder::new_cylinder(1.0, 3.0, 12), 1.0);
+        check(&MeshBuilder::new_cone(1.0, 2.0, 12), 1.0);
+        let capsule = MeshBuilder::new_capsule(0.5, 2.0, 12, 4);
+        check(&capsule, 1.0);
+        assert!((capsule.get_occlusion_culling_radius() - 1.5).abs() < 1e-5);
+        check(&MeshBuilder::new_torus(2.0, 0.5, 24, 12), 1.0);
+    }
+
+    #[test]
+    fn builder_test() {
+        let mut builder = MeshBuilder::new();
+        let uvs = [(0.0, 1.0), (1.0, 1.0), (0.0, 0.0), (1.0, 0.0)];
+        for &(u, v) in &uvs {
+            builder.add_vertex(Vertex::new(
+                Vec3::new(u, 0.0, v),
+                Vec3::zero(),
+                Vec4::new(0.0, 0.0, 0.0, 0.0),
+                [u, v],
+            ));
+        }
+        // Seen from +y, v goes toward +z, so the normal is +y.
+        builder.add_triangle(0, 1, 2);
+        builder.add_triangle(2, 1, 3);
+        builder.calculate_normals();
+        builder.calculate_tangents();
+        let plane = MeshBuilder::new_plane(1.0, 1.0, 1, 1);
+        for v in builder.get_vertices() {
+            let expected = plane.get_vertices()[0];
+            assert!(v.normal == expected.normal);
+            assert!(v.tangent == expected.tangent);
+        }
+        let mut merged = builder.clone();
+        merged.append(&plane);
+        assert_eq!(merged.get_vertices().len(), 8);
+        assert_eq!(&merged.get_indices()[6..], &[4, 6, 7, 4, 7, 5][..]);
+        let mut double_sided = builder.clone();
+        double_sided.add_back_faces();
+        assert_eq!(double_sided.get_vertices().len(), 8);
+        assert_eq!(&double_sided.get_indices()[6..], &[4, 6, 5, 6, 7, 5][..]);
+        let mut calculated = double_sided.clone();
+        calculated.calculate_normals();
+        calculated.calculate_tangents();
+        for (v, c) in double_sided.get_vertices()[4..]
+            .iter()
+            .zip(&calculated.get_vertices()[4..])
+        {
+            assert!(v.normal == -Vec3::new(0.0, 1.0, 0.0));
+            assert!(v.normal == c.normal);
+            assert!(v.tangent == c.tangent);
+        }
+    }
+
+    #[test]
+    fn normals_and_tangents_test() {
+        // A cube that shares its corners between its faces and has no
+        // normals, tangents or uvs.
+        let mut cube = MeshBuilder::new();
+        for i in 0..8 {
+            let p = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            cube.add_vertex(Vertex::new(
+                Vec3::new(p(1), p(2), p(4)),
+                Vec3::zero(),
+                Vec4::new(0.0, 0.0, 0.0, 0.0),
+                [0.0, 0.0],
+            ));
+        }
+        for &(axis, other1, other2) in &[(1, 2, 4), (2, 4, 1), (4, 1, 2)] {
+            for &side in &[0, axis] {
+                let q = [side, side | other1, side | other1 | other2, side | other2];
+                let v = |i: usize| cube.get_vertices()[q[i]].position;
+                let face = (v(1) - v(0)).cross(v(2) - v(0));
+                let q: Vec<u32> = if face.dot(v(0)) > 0.0 {
+                    q.iter().map(|&i| i as u32).collect()
+                } else {
+                    q.iter().rev().map(|&i| i as u32).collect()
+                };
+                cube.add_triangle(q[0], q[1], q[2]);
+                cube.add_triangle(q[0], q[2], q[3]);
+            }
+        }
+        let mut smooth = cube.clone();
+        smooth.calculate_normals_with_angle(PI);
+        assert_eq!(smooth.get_vertices().len(), 8);
+        for v in smooth.get_vertices() {
+            assert!((v.normal - v.position.normalized()).length() < 1e-5);
+        }
+        let mut flat = cube.clone();
+        flat.calculate_normals_with_angle(PI / 3.0);
+        assert_eq!(flat.get_vertices().len(), 24);
+        assert_eq!(flat.get_indices().len(), 36);
+        for t in flat.get_indices().chunks_exact(3) {
+            let v = |i: usize| flat.get_vertices()[t[i] as usize];
+            let face = (v(1).position - v(0).position)
+                .cross(v(2).position - v(0).position)
+                .normalized();
+            for i in 0..3 {
+                assert!((v(i).normal - face).length() < 1e-6);
+            }
+        }
+        let mut flat_again = cube.clone();
+        flat_again.calculate_flat_normals();
+        assert_eq!(flat_again.get_vertices().len(), 24);
+        // Without uvs, the tangents are still perpendicular to the normals.
+        flat.calculate_tangents();
+        assert_eq!(flat.get_vertices().len(), 24);
+        for v in flat.get_vertices() {
+            assert!((v.tangent.truncate().length() - 1.0).abs() < 1e-5);
+            assert!(v.tangent.truncate().dot(v.normal).abs() < 1e-5);
+            assert_eq!(v.tangent.w(), 1.0);
+        }
+
+        // Two quads that mirror their uvs around their shared edge.
+        let mut mirrored = MeshBuilder::new();
+        for &(x, u) in &[(-1.0, 0.0), (0.0, 1.0), (1.0, 0.0)] {
+            for &(z, v) in &[(-1.0, 0.0), (1.0, 1.0)] {
+                mirrored.add_vertex(Vertex::new(
+                    Vec3::new(x, 0.0, z),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    Vec4::new(0.0, 0.0, 0.0, 0.0),
+                    [u, v],
+                ));
+            }
+        }
+        mirrored.add_triangle(0, 1, 3);
+        mirrored.add_triangle(0, 3, 2);
+        mirrored.add_triangle(2, 3, 5);
+        mirrored.add_triangle(2, 5, 4);
+        mirrored.calculate_tangents();
+        // The shared edge is split for the two handednesses.
+        assert_eq!(mirrored.get_vertices().len(), 8);
+        for (i, t) in mirrored.get_indices().chunks_exact(3).enumerate() {
+            for &index in t {
+                let tangent = mirrored.get_vertices()[index as usize].tangent;
+                if i < 2 {
+                    assert!(tangent == Vec4::new(1.0, 0.0, 0.0, 1.0));
+                } else {
+                    assert!(tangent == Vec4::new(-1.0, 0.0, 0.0, -1.0));
+                }
+            }
+        }
+        // The generated meshes do not change.
+        let sphere = MeshBuilder::new_uv_sphere(1.0, 8, 4);
+        let mut calculated = sphere.clone();
+        calculated.calculate_tangents();
+        assert_eq!(calculated.get_vertices().len(), sphere.get_vertices().len());
+        assert!(calculated.get_indices() == sphere.get_indices());
+    }
+}
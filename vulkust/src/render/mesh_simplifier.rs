@@ -0,0 +1,488 @@
+use super::super::core::types::Real;
+use super::mesh_builder::{get_vector3_bits, Vertex};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+/// Border edges are kept much stronger than the faces, so the holes and the
+/// outlines of the meshes do not shrink.
+const BORDER_WEIGHT: f64 = 10.0;
+
+/// Positions are in double precision, since the quadrics sum the squares of
+/// their coordinates.
+type Position = [f64; 3];
+
+/// Symmetric 4x4 matrix of the sum of the squared distances to some planes,
+/// weighted by the areas of their triangles.
+#[derive(Clone, Copy, Default)]
+struct Quadric {
+    a: [f64; 10],
+    weight: f64,
+}
+
+impl Quadric {
+    fn new(n: Position, d: f64, weight: f64) -> Self {
+        let a = [
+            n[0] * n[0],
+            n[0] * n[1],
+            n[0] * n[2],
+            n[0] * d,
+            n[1] * n[1],
+            n[1] * n[2],
+            n[1] * d,
+            n[2] * n[2],
+            n[2] * d,
+            d * d,
+        ];
+        let mut q = Self { a, weight };
+        for v in &mut q.a {
+            *v *= weight;
+        }
+        return q;
+    }
+
+    fn add(&mut self, o: &Self) {
+        for (a, b) in self.a.iter_mut().zip(o.a.iter()) {
+            *a += b;
+        }
+        self.weight += o.weight;
+    }
+
+    /// Weighted mean of the squared distances of p to the planes.
+    fn get_error(&self, p: Position) -> f64 {
+        if self.weight <= 0.0 {
+            return 0.0;
+        }
+        let a = &self.a;
+        let e = a[0] * p[0] * p[0]
+            + 2.0 * a[1] * p[0] * p[1]
+            + 2.0 * a[2] * p[0] * p[2]
+            + 2.0 * a[3] * p[0]
+            + a[4] * p[1] * p[1]
+            + 2.0 * a[5] * p[1] * p[2]
+            + 2.0 * a[6] * p[1]
+            + a[7] * p[2] * p[2]
+            + 2.0 * a[8] * p[2]
+            + a[9];
+        return (e / self.weight).max(0.0);
+    }
+}
+
+struct Simplifier {
+    positions: Vec<Position>,
+    vertex_positions: Vec<usize>,
+    triangles: Vec<[u32; 3]>,
+    is_triangle_removed: Vec<bool>,
+    position_triangles: Vec<Vec<usize>>,
+    is_position_removed: Vec<bool>,
+    quadrics: Vec<Quadric>,
+    triangles_count: usize,
+    // Costs are bits of non negative floats, so they sort like the floats.
+    heap: BinaryHeap<Reverse<(u64, usize, usize)>>,
+}
+
+impl Simplifier {
+    fn new(vertices: &[Vertex], indices: &[u32]) -> Self {
+        // Vertices that only differ in their normals or uvs share a position.
+        let mut welds = BTreeMap::new();
+        let mut positions = Vec::new();
+        let mut vertex_positions = Vec::with_capacity(vertices.len());
+        for v in vertices {
+            let next = positions.len();
+            let p = *welds.entry(get_vector3_bits(v.position)).or_insert(next);
+            if p == next {
+                let [x, y, z] = v.position.to_array();
+                positions.push([x as f64, y as f64, z as f64]);
+            }
+            vertex_positions.push(p);
+        }
+        let mut triangles = Vec::with_capacity(indices.len() / 3);
+        let mut position_triangles = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut edges = BTreeMap::new();
+        for t in indices.chunks_exact(3) {
+            let p = [
+                vertex_positions[t[0] as usize],
+                vertex_positions[t[1] as usize],
+                vertex_positions[t[2] as usize],
+            ];
+            if p[0] == p[1] || p[1] == p[2] || p[2] == p[0] {
+                continue;
+            }
+            for i in 0..3 {
+                position_triangles[p[i]].push(triangles.len());
+                let edge = (p[i].min(p[(i + 1) % 3]), p[i].max(p[(i + 1) % 3]));
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+            triangles.push([t[0], t[1], t[2]]);
+            let n = get_normal(positions[p[0]], positions[p[1]], positions[p[2]]);
+            let length = dot(n, n).sqrt();
+            if length <= 0.0 {
+                continue;
+            }
+            let n = scale(n, 1.0 / length);
+            let q = Quadric::new(n, -dot(n, positions[p[0]]), length * 0.5);
+            for &i in &p {
+                quadrics[i].add(&q);
+            }
+        }
+        // Planes that are perpendicular to the faces of the border edges.
+        for triangle in &triangles {
+            let p: Vec<usize> = triangle
+                .iter()
+                .map(|&v| vertex_positions[v as usize])
+                .collect();
+            let n = get_normal(positions[p[0]], positions[p[1]], positions[p[2]]);
+            for i in 0..3 {
+                let (a, b) = (p[i], p[(i + 1) % 3]);
+                if edges[&(a.min(b), a.max(b))] != 1 {
+                    continue;
+                }
+                let e = sub(positions[b], positions[a]);
+                let border_normal = cross(e, n);
+                let length = dot(border_normal, border_normal).sqrt();
+                if length <= 0.0 {
+                    continue;
+                }
+                let border_normal = scale(border_normal, 1.0 / length);
+                let q = Quadric::new(
+                    border_normal,
+                    -dot(border_normal, positions[a]),
+                    dot(e, e) * BORDER_WEIGHT,
+                );
+                quadrics[a].add(&q);
+                quadrics[b].add(&q);
+            }
+        }
+        let triangles_count = triangles.len();
+        return Self {
+            is_position_removed: vec![false; positions.len()],
+            is_triangle_removed: vec![false; triangles_count],
+            positions,
+            vertex_positions,
+            triangles,
+            position_triangles,
+            quadrics,
+            triangles_count,
+            heap: BinaryHeap::new(),
+        };
+    }
+
+    #[inline]
+    fn get_position(&self, v: u32) -> usize {
+        return self.vertex_positions[v as usize];
+    }
+
+    fn get_cost(&self, from: usize, to: usize) -> f64 {
+        let mut q = self.quadrics[from];
+        q.add(&self.quadrics[to]);
+        return q.get_error(self.positions[to]);
+    }
+
+    fn get_neighbours(&self, p: usize) -> Vec<usize> {
+        let mut neighbours = Vec::new();
+        for &t in &self.position_triangles[p] {
+            for &v in &self.triangles[t] {
+                let o = self.get_position(v);
+                if o != p && !neighbours.contains(&o) {
+                    neighbours.push(o);
+                }
+            }
+        }
+        return neighbours;
+    }
+
+    fn push_edges(&mut self, p: usize) {
+        for o in self.get_neighbours(p) {
+            let cost = self.get_cost(p, o);
+            self.heap.push(Reverse((cost.to_bits(), p, o)));
+            let cost = self.get_cost(o, p);
+            self.heap.push(Reverse((cost.to_bits(), o, p)));
+        }
+    }
+
+    // Every vertex of from moves to the vertex of to that it shares a
+    // triangle with, so the seams of the uvs and the normals stay sharp. When
+    // a vertex has none or several of them the collapse is not possible.
+    fn get_partners(&self, from: usize, to: usize) -> Option<BTreeMap<u32, u32>> {
+        let mut partners = BTreeMap::new();
+        for &t in &self.position_triangles[from] {
+            let triangle = &self.triangles[t];
+            let v = *triangle.iter().find(|&&v| self.get_position(v) == from)?;
+            let partner = triangle.iter().find(|&&v| self.get_position(v) == to);
+            let entry = partners.entry(v).or_insert(None);
+            if let Some(&partner) = partner {
+                match *entry {
+                    Some(p) if p != partner => return None,
+                    _ => *entry = Some(partner),
+                }
+            }
+        }
+        let mut result = BTreeMap::new();
+        for (v, partner) in partners {
+            result.insert(v, partner?);
+        }
+        return Some(result);
+    }
+
+    // The link condition keeps the topology, the two ends must not share any
+    // neighbour other than the ones of their shared triangles. And the
+    // remaining triangles must not flip.
+    fn is_collapsible(&self, from: usize, to: usize) -> bool {
+        let shared_triangles = self.position_triangles[from]
+            .iter()
+            .filter(|&&t| {
+                self.triangles[t]
+                    .iter()
+                    .any(|&v| self.get_position(v) == to)
+            })
+            .count();
+        let to_neighbours = self.get_neighbours(to);
+        let shared_neighbours = self
+            .get_neighbours(from)
+            .iter()
+            .filter(|n| to_neighbours.contains(n))
+            .count();
+        if shared_neighbours != shared_triangles {
+            return false;
+        }
+        for &t in &self.position_triangles[from] {
+            let p: Vec<usize> = self.triangles[t]
+                .iter()
+                .map(|&v| self.get_position(v))
+                .collect();
+            if p.contains(&to) {
+                continue;
+            }
+            let moved: Vec<Position> = p
+                .iter()
+                .map(|&i| self.positions[if i == from { to } else { i }])
+                .collect();
+            let old = get_normal(
+                self.positions[p[0]],
+                self.positions[p[1]],
+                self.positions[p[2]],
+            );
+            let new = get_normal(moved[0], moved[1], moved[2]);
+            if dot(new, old) <= 1e-6 * dot(old, old) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn collapse(&mut self, from: usize, to: usize, partners: &BTreeMap<u32, u32>) {
+        let triangles = std::mem::replace(&mut self.position_triangles[from], Vec::new());
+        let mut touched = Vec::new();
+        for t in triangles {
+            let has_to = self.triangles[t]
+                .iter()
+                .any(|&v| self.vertex_positions[v as usize] == to);
+            if has_to {
+                self.is_triangle_removed[t] = true;
+                self.triangles_count -= 1;
+                for &v in &self.triangles[t] {
+                    touched.push(self.vertex_positions[v as usize]);
+                }
+                continue;
+            }
+            for v in &mut self.triangles[t] {
+                if let Some(&partner) = partners.get(v) {
+                    *v = partner;
+                }
+            }
+            self.position_triangles[to].push(t);
+        }
+        let is_triangle_removed = &self.is_triangle_removed;
+        for p in touched {
+            self.position_triangles[p].retain(|&t| !is_triangle_removed[t]);
+        }
+        let q = self.quadrics[from];
+        self.quadrics[to].add(&q);
+        self.is_position_removed[from] = true;
+    }
+
+    fn run(&mut self, target_triangles_count: usize, max_error: Real) {
+        let max_error = max_error as f64 * max_error as f64;
+        for p in 0..self.positions.len() {
+            for o in self.get_neighbours(p) {
+                let cost = self.get_cost(p, o);
+                self.heap.push(Reverse((cost.to_bits(), p, o)));
+            }
+        }
+        // Quadrics only grow, so the costs in the heap can only be less than
+        // the real ones and the updated edges are pushed back.
+        while self.triangles_count > target_triangles_count {
+            let Reverse((cost, from, to)) = match self.heap.pop() {
+                Some(e) => e,
+                None => break,
+            };
+            if self.is_position_removed[from] || self.is_position_removed[to] {
+                continue;
+            }
+            let current = self.get_cost(from, to);
+            if current.to_bits() != cost {
+                self.heap.push(Reverse((current.to_bits(), from, to)));
+                continue;
+            }
+            if current > max_error {
+                break;
+            }
+            if !self.get_neighbours(from).contains(&to) || !self.is_collapsible(from, to) {
+                continue;
+            }
+            let partners = match self.get_partners(from, to) {
+                Some(p) => p,
+                None => continue,
+            };
+            self.collapse(from, to, &partners);
+            self.push_edges(to);
+        }
+    }
+
+    fn get_indices(&self) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(self.triangles_count * 3);
+        for (t, triangle) in self.triangles.iter().enumerate() {
+            if !self.is_triangle_removed[t] {
+                indices.extend_from_slice(triangle);
+            }
+        }
+        return indices;
+    }
+}
+
+fn sub(a: Position, b: Position) -> Position {
+    return [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+}
+
+fn scale(a: Position, s: f64) -> Position {
+    return [a[0] * s, a[1] * s, a[2] * s];
+}
+
+fn dot(a: Position, b: Position) -> f64 {
+    return a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+}
+
+fn cross(a: Position, b: Position) -> Position {
+    return [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ];
+}
+
+// Its length is twice the area of the triangle.
+fn get_normal(p0: Position, p1: Position, p2: Position) -> Position {
+    return cross(sub(p1, p0), sub(p2, p0));
+}
+
+/// Quadric error metric simplification of Garland and Heckbert. Edges are
+/// collapsed into one of their ends, so the result indexes a subset of the
+/// same vertices and the lods of a mesh can share its vertex buffer. It
+/// stops at the target count of triangles or when the next collapse moves
+/// the surface more than max_error.
+pub fn simplify(
+    vertices: &[Vertex],
+    indices: &[u32],
+    target_triangles_count: usize,
+    max_error: Real,
+) -> Vec<u32> {
+    let mut simplifier = Simplifier::new(vertices, indices);
+    simplifier.run(target_triangles_count, max_error);
+    return simplifier.get_indices();
+}
+
+/// The indices of a chain of lods, the first one is the mesh itself and
+/// every next one aims for reduction times the triangles of its previous
+/// one. The allowed error is doubled for every next level, since it is seen
+/// about half of the size. The chain ends early when a level can not get
+/// half of the way to its target.
+pub fn generate_lods(
+    vertices: &[Vertex],
+    indices: &[u32],
+    lods_count: usize,
+    reduction: Real,
+    max_error: Real,
+) -> Vec<Vec<u32>> {
+    let mut lods = vec![indices.to_vec()];
+    let mut max_error = max_error;
+    while lods.len() < lods_count {
+        let previous = lods[lods.len() - 1].len() / 3;
+        let target = (previous as Real * reduction) as usize;
+        let lod = simplify(vertices, &lods[lods.len() - 1], target, max_error);
+        if lod.is_empty() || (lod.len() / 3) as Real > (previous + target) as Real * 0.5 {
+            break;
+        }
+        lods.push(lod);
+        max_error *= 2.0;
+    }
+    return lods;
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::mesh_builder::MeshBuilder;
+    use super::*;
+
+    fn check_indices(vertices: &[Vertex], indices: &[u32]) {
+        assert_eq!(indices.len() % 3, 0);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+    }
+
+    #[test]
+    fn simplify_test() {
+        // A flat grid collapses to very few triangles without any error and
+        // keeps its outline.
+        let plane = MeshBuilder::new_plane(4.0, 4.0, 16, 16);
+        let vertices = plane.get_vertices();
+        let indices = simplify(vertices, plane.get_indices(), 2, 1e-4);
+        check_indices(vertices, &indices);
+        assert!(indices.len() / 3 <= 8);
+        let mut area = 0.0;
+        for t in indices.chunks_exact(3) {
+            let p = |i: usize| vertices[t[i] as usize].position;
+            let n = (p(1) - p(0)).cross(p(2) - p(0));
+            assert!(n.y() > 0.0);
+            area += n.length() * 0.5;
+        }
+        assert!((area - 16.0).abs() < 1e-3);
+
+        // A sphere can not lose much without its error going up.
+        let sphere = MeshBuilder::new_uv_sphere(1.0, 32, 16);
+        let vertices = sphere.get_vertices();
+        let full = sphere.get_indices().len() / 3;
+        let strict = simplify(vertices, sphere.get_indices(), full / 4, 1e-4);
+        assert!(strict.len() / 3 > full / 2);
+        let loose = simplify(vertices, sphere.get_indices(), full / 4, 0.1);
+        check_indices(vertices, &loose);
+        assert!(loose.len() / 3 <= full / 4);
+        // The remaining vertices are still on the sphere and the faces still
+        // look outward.
+        for t in loose.chunks_exact(3) {
+            let p = |i: usize| vertices[t[i] as usize].position;
+            let n = (p(1) - p(0)).cross(p(2) - p(0));
+            assert!(n.dot(p(0) + p(1) + p(2)) > 0.0);
+        }
+
+        // The cube has nothing to collapse, its corners are seams.
+        let cube = MeshBuilder::new_cube(1.0);
+        let indices = simplify(cube.get_vertices(), cube.get_indices(), 0, 10.0);
+        assert!(indices.len() >= 12 * 3);
+    }
+
+    #[test]
+    fn lods_test() {
+        let torus = MeshBuilder::new_torus(2.0, 0.5, 48, 24);
+        let vertices = torus.get_vertices();
+        let lods = generate_lods(vertices, torus.get_indices(), 4, 0.5, 0.01);
+        assert_eq!(lods.len(), 4);
+        assert!(lods[0] == torus.get_indices());
+        for i in 1..lods.len() {
+            check_indices(vertices, &lods[i]);
+            assert!(lods[i].len() <= lods[i - 1].len() * 3 / 4);
+        }
+        // A quad can not lose its corners without a visible error.
+        let quad = MeshBuilder::new_screen_quad();
+        let lods = generate_lods(quad.get_vertices(), quad.get_indices(), 4, 0.5, 1e-3);
+        assert_eq!(lods.len(), 1);
+    }
+}
@@ -1,30 +1,62 @@
-// pub mod buffer;
-// pub mod camera;
-// pub mod command;
+pub mod animation;
+// Vulkan backend only ports its instance and engine yet, see vulkan/mod.rs,
+// it does not have any buffer, command or descriptor to re-export. So these
+// modules, and the ones that record or allocate through them, are compiled
+// for the software and the blank backends until that porting.
+#[cfg(not(vulkan_api))]
+pub mod buffer;
+#[cfg(not(vulkan_api))]
+pub mod camera;
+pub mod capture;
+#[cfg(not(vulkan_api))]
+pub mod command;
 pub mod config;
 // pub mod deferred;
-// pub mod descriptor;
+#[cfg(not(vulkan_api))]
+pub mod descriptor;
 // pub mod device;
 pub mod engine;
 // pub mod font;
-// pub mod framebuffer;
-// pub mod g_buffer_filler;
-// pub mod gapi;
-// pub mod image;
-// pub mod light;
-// pub mod material;
-// pub mod mesh;
-// pub mod model;
+#[cfg(not(vulkan_api))]
+pub mod framebuffer;
+#[cfg(not(vulkan_api))]
+pub mod g_buffer_filler;
+#[cfg(not(vulkan_api))]
+pub mod gapi;
+pub mod gltf_material;
+pub mod gltf_node;
+pub mod image;
+#[cfg(not(vulkan_api))]
+pub mod light;
+pub mod lod;
+#[cfg(not(vulkan_api))]
+pub mod material;
+#[cfg(not(vulkan_api))]
+pub mod mesh;
+pub mod mesh_builder;
+pub mod mesh_simplifier;
+pub mod morph;
+#[cfg(not(vulkan_api))]
+pub mod model;
 // pub mod multithreaded;
-// pub mod object;
+pub mod object;
 // pub mod pass;
-// pub mod pipeline;
-// pub mod render_pass;
-// pub mod sampler;
-// pub mod scene;
+pub mod path_follower;
+pub mod pipeline;
+#[cfg(not(vulkan_api))]
+pub mod render_pass;
+pub mod sampler;
+#[cfg(not(vulkan_api))]
+pub mod scene;
+pub mod scene_state;
 // pub mod shadower;
+pub mod skeleton;
+#[cfg(not(vulkan_api))]
+pub mod skin;
 // pub mod skybox;
 // pub mod ssao;
-// pub mod sync;
-// pub mod texture;
+#[cfg(not(vulkan_api))]
+pub mod sync;
+pub mod texture;
+pub mod transform;
 // pub mod widget;
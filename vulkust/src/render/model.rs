@@ -1,36 +1,88 @@
-use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
+// use super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
 use super::super::core::object::Object as CoreObject;
 use super::super::core::types::{Id, Real};
-use super::super::physics::collider::{read as read_collider, Collider, Ghost as GhostCollider};
-use super::buffer::Dynamic as DynamicBuffer;
+use super::super::math::aabbox::Aabb3;
+use super::super::math::frustum::{Frustum, Intersection};
+use super::super::math::matrix::Mat4;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+// Physics is not ported yet.
+// use super::super::physics::collider::{read as read_collider, Collider, Ghost as GhostCollider};
+use super::animation::{Animator, Property};
+use super::buffer::{Dynamic as DynamicBuffer, Static as StaticBuffer};
 use super::camera::Camera;
 use super::command::Buffer as CmdBuffer;
 use super::descriptor::Set as DescriptorSet;
 use super::engine::Engine;
-use super::material::Material;
+use super::lod::Selector as LodSelector;
+use super::material::{Material, TranslucencyMode};
 use super::mesh::Mesh;
+use super::morph::Targets as MorphTargets;
 use super::object::{Base as ObjectBase, Loadable, Object, Transferable};
-use super::scene::Scene;
+use super::skin::Skin;
+use super::transform::Transform;
 use std::collections::BTreeMap;
 use std::mem::size_of;
+use std::os::raw::c_void;
 use std::sync::{Arc, RwLock, Weak};
 
-use cgmath;
 use gltf;
 
 pub trait Model: Object + Transferable {
-    fn update(&mut self, scene: &dyn Scene, camera: &dyn Camera, frame_number: usize);
+    /// Advances the animator and applies its pose. The pose moves the model,
+    /// so it comes before the culling.
+    fn animate(&mut self, delta_time: Real);
+    /// Invisible models only upload their pose, the visibility comes from the
+    /// culling of the tree that the model is in.
+    fn update(&mut self, camera: &dyn Camera, frame_number: usize);
+    /// Updates the world boxes of the model and of its children, it returns
+    /// the box of the whole subtree.
+    fn update_bounds(&mut self) -> Aabb3;
+    /// Culls the model and its children, the boxes must be updated before.
+    /// Parent is the result of the parent model and Intersecting for the
+    /// roots, see math::frustum::Frustum::cull_node.
+    fn cull(&mut self, frustum: &Frustum, parent: Intersection);
+    fn is_visible(&self) -> bool;
     fn add_mesh(&mut self, mesh: Arc<RwLock<dyn Mesh>>, material: Material);
     fn clear_meshes(&mut self);
     fn get_meshes(&self) -> &BTreeMap<Id, (Arc<RwLock<dyn Mesh>>, Material)>;
     fn bring_all_child_models(&self) -> Vec<(Id, Arc<RwLock<dyn Model>>)>;
+    fn add_child(&mut self, child: Arc<RwLock<dyn Model>>);
+    fn remove_child(&mut self, id: Id) -> Option<Arc<RwLock<dyn Model>>>;
+    fn get_parent(&self) -> Option<Id>;
+    fn set_parent(&mut self, parent: Option<Id>);
+    fn set_parent_world_matrix(&mut self, m: &Mat4);
+    fn get_transform(&self) -> &Transform;
+    fn set_transform(&mut self, t: &Transform);
     fn has_shadow(&self) -> bool;
     fn has_transparent(&self) -> bool;
     fn get_occlusion_culling_radius(&self) -> Real;
     fn get_distance_from_camera(&self, camera: &dyn Camera) -> Real;
+    /// Level of detail of the meshes, it is picked in update from the screen
+    /// size of the model.
+    fn get_lod(&self) -> usize;
+    fn get_lod_selector(&self) -> &LodSelector;
+    fn get_mut_lod_selector(&mut self) -> &mut LodSelector;
     fn get_uniform(&self) -> &Uniform;
+    fn get_skin(&self) -> Option<&Skin>;
+    fn get_mut_skin(&mut self) -> Option<&mut Skin>;
+    /// Its first target is the model and the next ones are the joints of its
+    /// skin, the pose is applied in animate.
+    fn get_animator(&self) -> Option<&Animator>;
+    fn get_mut_animator(&mut self) -> Option<&mut Animator>;
+    /// Weights of the morph targets of the meshes, the animator overrides
+    /// them while it plays their channel.
+    fn get_morph_weights(&self) -> &[Real];
+    fn set_morph_weights(&mut self, weights: &[Real]);
+    /// Adds the animations of the document that move the model or the joints
+    /// of its skin.
+    fn load_gltf_animations(&mut self, document: &gltf::Document, data: &[u8]);
     fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize);
     fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize);
+    /// Skinned meshes are skipped by the other render functions, they need
+    /// the skinned pipelines to be bound.
+    fn render_skinned_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize);
+    fn render_skinned_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize);
     fn render_unlit(&mut self, cmd: &mut CmdBuffer, camera: &dyn Camera, frame_number: usize);
 }
 
@@ -38,6 +90,12 @@ pub trait DefaultModel: Model + Sized {
     fn default(engine: &Engine) -> Self;
 }
 
+/// Subtrees without any mesh, like the joints of the skeletons, do not
+/// become models.
+pub(super) fn has_mesh(node: &gltf::Node) -> bool {
+    return node.mesh().is_some() || node.children().any(|c| has_mesh(&c));
+}
+
 #[repr(u8)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub enum TypeId {
@@ -51,7 +109,8 @@ pub struct Manager {
     engine: Option<Weak<RwLock<Engine>>>,
     models: BTreeMap<Id, Weak<RwLock<dyn Model>>>,
     name_to_id: BTreeMap<String, Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // Gx3D reader is not ported yet.
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -60,33 +119,34 @@ impl Manager {
             engine: None,
             models: BTreeMap::new(),
             name_to_id: BTreeMap::new(),
-            gx3d_table: None,
         }
     }
 
-    pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Model>> {
-        if let Some(model) = self.models.get(&id) {
-            if let Some(model) = model.upgrade() {
-                return model;
-            }
-        }
-        let gx3d_table = vx_unwrap!(self.gx3d_table.as_mut());
-        gx3d_table.goto(id);
-        let reader = gx3d_table.get_mut_reader();
-        let t = reader.read_type_id();
-        let model: Arc<RwLock<dyn Model>> = if t == TypeId::Static as u8 {
-            // maybe in future I will implement it defferently for static
-            Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
-        } else if t == TypeId::Dynamic as u8 {
-            Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
-        } else if t == TypeId::Widget as u8 {
-            vx_unimplemented!()
-        } else {
-            vx_unexpected!()
-        };
-        self.models.insert(id, Arc::downgrade(&model));
-        return model;
-    }
+    // Gx3D reader is not ported yet.
+    // pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Model>> {
+    //     vx_profile_zone!("model::load_gx3d");
+    //     if let Some(model) = self.models.get(&id) {
+    //         if let Some(model) = model.upgrade() {
+    //             return model;
+    //         }
+    //     }
+    //     let gx3d_table = vx_unwrap!(self.gx3d_table.as_mut());
+    //     gx3d_table.goto(id);
+    //     let reader = gx3d_table.get_mut_reader();
+    //     let t = reader.read_type_id();
+    //     let model: Arc<RwLock<dyn Model>> = if t == TypeId::Static as u8 {
+    //         // maybe in future I will implement it defferently for static
+    //         Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
+    //     } else if t == TypeId::Dynamic as u8 {
+    //         Arc::new(RwLock::new(Base::new_with_gx3d(engine, reader, id)))
+    //     } else if t == TypeId::Widget as u8 {
+    //         vx_unimplemented!()
+    //     } else {
+    //         vx_unexpected!()
+    //     };
+    //     self.models.insert(id, Arc::downgrade(&model));
+    //     return model;
+    // }
 
     pub fn create<M>(&mut self) -> Arc<RwLock<M>>
     where
@@ -102,9 +162,9 @@ impl Manager {
         return m1;
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub(crate) fn set_engine(&mut self, e: Weak<RwLock<Engine>>) {
         self.engine = Some(e);
@@ -114,60 +174,46 @@ impl Manager {
 #[repr(C)]
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Uniform {
-    model: cgmath::Matrix4<Real>,
-    model_view_projection: cgmath::Matrix4<Real>,
+    model: Mat4,
+    model_view_projection: Mat4,
 }
 
 impl Uniform {
-    fn new_with_gltf(node: &gltf::Node) -> Self {
-        let m = node.transform().matrix();
-        let model = cgmath::Matrix4::new(
-            m[0][0], m[0][1], m[0][2], m[0][3], m[1][0], m[1][1], m[1][2], m[1][3], m[2][0],
-            m[2][1], m[2][2], m[2][3], m[3][0], m[3][1], m[3][2], m[3][3],
-        );
-        Self {
-            model,
-            model_view_projection: model,
-        }
-    }
-
-    fn new_with_gx3d(reader: &mut Gx3DReader) -> Self {
-        let model = cgmath::Matrix4::new(
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-            reader.read(),
-        );
-        Self {
-            model,
-            model_view_projection: model,
-        }
-    }
+    // fn new_with_gx3d(reader: &mut Gx3DReader) -> Self {
+    //     let model = cgmath::Matrix4::new(
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //         reader.read(),
+    //     );
+    //     Self {
+    //         model,
+    //         model_view_projection: model,
+    //     }
+    // }
 
-    pub(crate) fn get_model(&self) -> &cgmath::Matrix4<Real> {
+    pub(crate) fn get_model(&self) -> &Mat4 {
         return &self.model;
     }
 
-    pub(crate) fn set_model_view_projection(&mut self, mvp: cgmath::Matrix4<Real>) {
+    pub(crate) fn set_model_view_projection(&mut self, mvp: Mat4) {
         return self.model_view_projection = mvp;
     }
 
     pub(super) fn default() -> Self {
-        let m = cgmath::Matrix4::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-        );
+        let m = Mat4::identity();
         Self {
             model: m,
             model_view_projection: m,
@@ -175,23 +221,130 @@ impl Uniform {
     }
 }
 
+// Copy of the vertices of a mesh with morph targets, they are morphed on CPU
+// because the pipelines can not read the targets.
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Morph {
+    vertices: Vec<Real>,
+    vertex_buffer: DynamicBuffer,
+}
+
+impl Morph {
+    fn new(targets: &MorphTargets, weights: &[Real], engine: &Engine) -> Self {
+        let mut vertices = Vec::new();
+        targets.apply(weights, &mut vertices);
+        let gapi_engine = &engine.gapi_engine;
+        let vertex_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
+            .create_dynamic_buffer((vertices.len() * size_of::<Real>()) as isize);
+        Self {
+            vertices,
+            vertex_buffer,
+        }
+    }
+
+    fn get_vertex_buffer(&self, frame_number: usize) -> StaticBuffer {
+        return StaticBuffer::new(self.vertex_buffer.get_buffer(frame_number).clone());
+    }
+}
+
 #[cfg_attr(debug_mode, derive(Debug))]
 pub struct Base {
     obj_base: ObjectBase,
     has_shadow_caster: bool,
     has_transparent_mesh: bool,
     occlusion_culling_radius: Real,
+    aabb: Aabb3,
+    subtree_aabb: Aabb3,
     is_visible: bool,
-    collider: Arc<RwLock<dyn Collider>>,
+    lod_selector: LodSelector,
+    // collider: Arc<RwLock<dyn Collider>>,
     uniform: Uniform,
     uniform_buffer: DynamicBuffer,
     descriptor_set: Arc<DescriptorSet>,
     meshes: BTreeMap<Id, (Arc<RwLock<dyn Mesh>>, Material)>,
+    skin: Option<Skin>,
+    animator: Option<Animator>,
+    morph_weights: Vec<Real>,
+    is_morph_changed: bool,
+    morphs: BTreeMap<Id, Morph>,
     children: BTreeMap<Id, Arc<RwLock<dyn Model>>>,
-    scales: cgmath::Vector3<Real>,
+    parent: Option<Id>,
+    transform: Transform,
 }
 
-impl Base {}
+impl Base {
+    // It must be called after any change in transform, it feeds the uniform
+    // and propagates the new world matrix to the children.
+    fn update_transform(&mut self) {
+        if !self.transform.update() {
+            return;
+        }
+        self.uniform.model = *self.transform.get_world_matrix();
+        for (_, c) in &self.children {
+            vx_result!(c.write()).set_parent_world_matrix(&self.uniform.model);
+        }
+    }
+
+    // The pose is applied only when the animator has changed it, so the
+    // transform and the joints can be changed by hand otherwise.
+    fn apply_pose(&mut self) {
+        {
+            let animator = match &mut self.animator {
+                Some(a) if a.is_changed() => a,
+                _ => return,
+            };
+            let pose = animator.evaluate();
+            let target = &pose.get_targets()[0];
+            if pose.is_animated(0, Property::Translation) {
+                self.transform.set_location(&target.location);
+            }
+            if pose.is_animated(0, Property::Rotation) {
+                self.transform.set_orientation(&target.orientation);
+            }
+            if pose.is_animated(0, Property::Scale) {
+                self.transform.set_scale(&target.scale);
+            }
+            if pose.is_animated(0, Property::Weights) {
+                self.morph_weights.clear();
+                self.morph_weights.extend_from_slice(&target.weights);
+                self.is_morph_changed = true;
+            }
+            if let Some(skin) = &mut self.skin {
+                let skeleton = skin.get_mut_skeleton();
+                for (i, t) in pose.get_targets()[1..].iter().enumerate() {
+                    skeleton.set_joint_location(i, &t.location);
+                    skeleton.set_joint_orientation(i, &t.orientation);
+                    skeleton.set_joint_scale(i, &t.scale);
+                }
+            }
+        }
+        self.update_transform();
+    }
+
+    // Invisible models may still cast shadows, so their vertices are
+    // uploaded too.
+    fn morph(&mut self, frame_number: usize) {
+        for (id, morph) in &mut self.morphs {
+            if self.is_morph_changed {
+                let mesh = vx_result!(vx_unwrap!(self.meshes.get(id)).0.read());
+                if let Some(targets) = mesh.get_morph_targets() {
+                    targets.apply(&self.morph_weights, &mut morph.vertices);
+                }
+            }
+            morph
+                .vertex_buffer
+                .update_with_ptr(morph.vertices.as_ptr() as *const c_void, frame_number);
+        }
+        self.is_morph_changed = false;
+    }
+
+    fn get_morphed_vertices(&self, mesh_id: &Id, frame_number: usize) -> Option<StaticBuffer> {
+        return self
+            .morphs
+            .get(mesh_id)
+            .map(|m| m.get_vertex_buffer(frame_number));
+    }
+}
 
 impl CoreObject for Base {
     fn get_id(&self) -> Id {
@@ -204,9 +357,8 @@ impl Object for Base {
         self.obj_base.get_name()
     }
 
-    fn set_name(&mut self, _name: &str) {
-        self.obj_base.get_name();
-        vx_unimplemented!();
+    fn set_name(&mut self, name: &str) {
+        self.obj_base.set_name(name);
     }
 
     fn disable_rendering(&mut self) {
@@ -224,148 +376,220 @@ impl Object for Base {
 
 impl Loadable for Base {
     fn new_with_gltf(node: &gltf::Node, engine: &Engine, data: &[u8]) -> Self {
-        let obj_base = ObjectBase::new();
-        let mut mesh_manager = vx_result!(engine.get_asset_manager().get_mesh_manager().write());
-        let model = vx_unwrap!(node.mesh());
-        let primitives = model.primitives();
+        let obj_base = ObjectBase::new_with_gltf(node, engine, data);
         let mut meshes = BTreeMap::new();
         let mut has_shadow_caster = false;
         let mut has_transparent_mesh = false;
         let mut occlusion_culling_radius = 0.0001;
-        for primitive in primitives {
-            let mesh = mesh_manager.load_gltf(&primitive, &engine, data);
-            let id = {
-                let mesh = vx_result!(mesh.read());
-                has_shadow_caster |= mesh.is_shadow_caster(); // todo remove
-                has_transparent_mesh |= mesh.is_transparent(); // todo remove
-                let occ = mesh.get_occlusion_culling_radius();
-                if occ > occlusion_culling_radius {
-                    occlusion_culling_radius = occ;
+        if let Some(model) = node.mesh() {
+            let mut mesh_manager =
+                vx_result!(engine.get_asset_manager().get_mesh_manager().write());
+            for primitive in model.primitives() {
+                let mesh = mesh_manager.load_gltf(&primitive, &engine, data);
+                let material = Material::new_with_gltf(engine, &primitive.material(), data);
+                if let TranslucencyMode::Tansparent = material.get_translucency() {
+                    has_transparent_mesh = true;
                 }
-                mesh.get_id()
-            };
-            meshes.insert(id, (mesh, Material::default(engine)));
-        }
-        if node.children().count() > 0 {
-            vx_unimplemented!(); // todo support children
+                let id = {
+                    let mesh = vx_result!(mesh.read());
+                    has_shadow_caster |= mesh.is_shadow_caster(); // todo remove
+                    has_transparent_mesh |= mesh.is_transparent(); // todo remove
+                    let occ = mesh.get_occlusion_culling_radius();
+                    if occ > occlusion_culling_radius {
+                        occlusion_culling_radius = occ;
+                    }
+                    mesh.get_id()
+                };
+                meshes.insert(id, (mesh, material));
+            }
         }
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
-        let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
-            .create_dynamic_buffer(size_of::<Uniform>() as isize);
-        let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
-        let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
-        let uniform = Uniform::new_with_gltf(node);
-        vx_todo!(); // not tested
-        Base {
-            obj_base,
-            has_shadow_caster,
-            has_transparent_mesh,
-            occlusion_culling_radius,
-            is_visible: false,
-            collider: Arc::new(RwLock::new(GhostCollider::new())),
-            uniform,
-            uniform_buffer,
-            descriptor_set,
-            meshes,
-            children: BTreeMap::new(),
-            scales: cgmath::Vector3::new(1.0, 1.0, 1.0),
+        let skin = node
+            .skin()
+            .map(|s| Skin::new_with_gltf(node, &s, engine, data));
+        let animator = match &skin {
+            Some(s) => {
+                Animator::new_with_gltf(node, s.get_joint_nodes(), s.get_skeleton().get_joints())
+            }
+            None => Animator::new_with_gltf(node, &BTreeMap::new(), &[]),
+        };
+        let weights = animator.get_rest_pose().get_targets()[0].weights.clone();
+        let mut morphs = BTreeMap::new();
+        for (id, (mesh, _)) in &meshes {
+            let mesh = vx_result!(mesh.read());
+            if let Some(targets) = mesh.get_morph_targets() {
+                morphs.insert(*id, Morph::new(targets, &weights, engine));
+            }
         }
-    }
-
-    fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let obj_base = ObjectBase::new_with_id(my_id);
-        let uniform = Uniform::new_with_gx3d(reader);
-        let occlusion_culling_radius = reader.read();
-        let collider = read_collider(reader);
-        let meshes_ids = reader.read_array();
-        let mut mesh_manager = vx_result!(eng.get_asset_manager().get_mesh_manager().write());
-        let mut meshes = BTreeMap::new();
-        let mut has_shadow_caster = false;
-        let mut has_transparent_mesh = false;
-        for mesh_id in meshes_ids {
-            let mat = Material::new_with_gx3d(eng, reader);
-            let mesh = mesh_manager.load_gx3d(eng, mesh_id);
-            {
-                let mesh = vx_result!(mesh.read());
-                has_shadow_caster |= mesh.is_shadow_caster();
-                has_transparent_mesh |= mesh.is_transparent();
+        let morph_weights = weights;
+        let mut children: Vec<Arc<RwLock<dyn Model>>> = Vec::new();
+        for child in node.children() {
+            if child.camera().is_some() {
+                continue; // todo cameras as children
             }
-            meshes.insert(mesh_id, (mesh, mat));
+            if !has_mesh(&child) {
+                continue;
+            }
+            children.push(Arc::new(RwLock::new(Self::new_with_gltf(
+                &child, engine, data,
+            ))));
         }
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+        let gapi_engine = &engine.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
         let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
         let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
-        Base {
+        let transform = Transform::new_with_gltf(node);
+        let mut uniform = Uniform::default();
+        uniform.model = *transform.get_world_matrix();
+        let mut myself = Base {
             obj_base,
             has_shadow_caster,
             has_transparent_mesh,
             occlusion_culling_radius,
+            aabb: Aabb3::new(),
+            subtree_aabb: Aabb3::new(),
             is_visible: false,
-            collider,
+            lod_selector: LodSelector::new(),
             uniform,
             uniform_buffer,
             descriptor_set,
             meshes,
+            skin,
+            animator: Some(animator),
+            morph_weights,
+            is_morph_changed: false,
+            morphs,
             children: BTreeMap::new(),
-            scales: cgmath::Vector3::new(1.0, 1.0, 1.0),
+            parent: None,
+            transform,
+        };
+        for child in children {
+            myself.add_child(child);
         }
+        return myself;
     }
+
+    // fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let obj_base = ObjectBase::new_with_id(my_id);
+    //     let uniform = Uniform::new_with_gx3d(reader);
+    //     let transform = Transform::new_with_matrix(uniform.get_model());
+    //     let occlusion_culling_radius = reader.read();
+    //     let collider = read_collider(reader);
+    //     let meshes_ids = reader.read_array();
+    //     let mut mesh_manager = vx_result!(eng.get_asset_manager().get_mesh_manager().write());
+    //     let mut meshes = BTreeMap::new();
+    //     let mut has_shadow_caster = false;
+    //     let mut has_transparent_mesh = false;
+    //     for mesh_id in meshes_ids {
+    //         let mat = Material::new_with_gx3d(eng, reader);
+    //         let mesh = mesh_manager.load_gx3d(eng, mesh_id);
+    //         {
+    //             let mesh = vx_result!(mesh.read());
+    //             has_shadow_caster |= mesh.is_shadow_caster();
+    //             has_transparent_mesh |= mesh.is_transparent();
+    //         }
+    //         meshes.insert(mesh_id, (mesh, mat));
+    //     }
+    //     let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+    //     let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
+    //         .create_dynamic_buffer(size_of::<Uniform>() as isize);
+    //     let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
+    //     let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
+    //     Base {
+    //         obj_base,
+    //         has_shadow_caster,
+    //         has_transparent_mesh,
+    //         occlusion_culling_radius,
+    //         is_visible: false,
+    //         lod_selector: LodSelector::new(),
+    //         collider,
+    //         uniform,
+    //         uniform_buffer,
+    //         descriptor_set,
+    //         meshes,
+    //         skin: None,
+    //         animator: None,
+    //         morph_weights: Vec::new(),
+    //         is_morph_changed: false,
+    //         morphs: BTreeMap::new(),
+    //         children: BTreeMap::new(),
+    //         parent: None,
+    //         transform,
+    //     }
+    // }
 }
 
+// All of the transformations are in the parent space,
+// children follow their parent through update_transform.
 impl Transferable for Base {
-    fn set_orientation(&mut self, _: &cgmath::Quaternion<Real>) {
-        vx_unimplemented!();
+    fn set_orientation(&mut self, q: &Quat) {
+        self.transform.set_orientation(q);
+        self.update_transform();
     }
 
-    fn set_location(&mut self, _: &cgmath::Vector3<Real>) {
-        vx_unimplemented!();
+    fn set_location(&mut self, l: &Vec3) {
+        self.transform.set_location(l);
+        self.update_transform();
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
-        return self.uniform.model.w.truncate();
+    fn get_location(&self) -> Vec3 {
+        return *self.transform.get_location();
     }
 
-    fn move_local_z(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn move_local_z(&mut self, v: Real) {
+        self.transform.move_local_z(v);
+        self.update_transform();
     }
 
-    fn move_local_x(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn move_local_x(&mut self, v: Real) {
+        self.transform.move_local_x(v);
+        self.update_transform();
     }
 
-    fn rotate_local_x(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn rotate_local_x(&mut self, v: Real) {
+        self.transform.rotate_local_x(v);
+        self.update_transform();
     }
 
-    fn rotate_global_z(&mut self, _: Real) {
-        vx_unimplemented!();
+    fn rotate_global_z(&mut self, v: Real) {
+        self.transform.rotate_global_z(v);
+        self.update_transform();
     }
 
-    fn translate(&mut self, t: &cgmath::Vector3<Real>) {
-        self.uniform.model.w += t.extend(0.0);
+    fn translate(&mut self, t: &Vec3) {
         // todo take care of collider
-        for (_, c) in &self.children {
-            vx_result!(c.write()).translate(t);
-        }
+        self.transform.translate(t);
+        self.update_transform();
     }
 
     fn scale(&mut self, s: Real) {
-        self.scales *= s;
-        self.occlusion_culling_radius *= s;
-        let s = cgmath::Matrix4::from_scale(s);
-        self.uniform.model = self.uniform.model * s;
+        self.transform.scale(s);
+        self.update_transform();
     }
 }
 
 impl Model for Base {
-    fn update(&mut self, _: &dyn Scene, camera: &dyn Camera, frame_number: usize) {
-        let location = self.uniform.model.w.truncate();
-        self.is_visible = camera.is_in_frustum(self.occlusion_culling_radius, &location);
+    fn animate(&mut self, delta_time: Real) {
+        if let Some(animator) = &mut self.animator {
+            animator.advance(delta_time);
+        }
+        self.apply_pose();
+    }
+
+    fn update(&mut self, camera: &dyn Camera, frame_number: usize) {
+        self.morph(frame_number);
+        // Invisible models may still cast shadows with their pose.
+        if let Some(skin) = &mut self.skin {
+            skin.update(frame_number);
+        }
         if !self.is_visible {
             return;
         }
+        let screen_size = camera.get_screen_size(
+            self.get_occlusion_culling_radius(),
+            &self.transform.get_world_location(),
+        );
+        self.lod_selector.update(screen_size);
         self.uniform_buffer.update(&self.uniform, frame_number);
         for (_, m) in &mut self.meshes {
             vx_result!(m.0.write()).update(frame_number);
@@ -373,8 +597,38 @@ impl Model for Base {
         }
     }
 
+    fn update_bounds(&mut self) -> Aabb3 {
+        self.aabb = if self.meshes.is_empty() {
+            Aabb3::new()
+        } else {
+            Aabb3::new_with_center_radius(
+                self.transform.get_world_location(),
+                self.get_occlusion_culling_radius(),
+            )
+        };
+        self.subtree_aabb = self.aabb;
+        for c in self.children.values() {
+            let aabb = vx_result!(c.write()).update_bounds();
+            self.subtree_aabb.insert_aabb(&aabb);
+        }
+        return self.subtree_aabb;
+    }
+
+    fn cull(&mut self, frustum: &Frustum, parent: Intersection) {
+        let (is_visible, intersection) = frustum.cull_node(parent, &self.aabb, &self.subtree_aabb);
+        self.is_visible = is_visible;
+        for c in self.children.values() {
+            vx_result!(c.write()).cull(frustum, intersection);
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        return self.is_visible;
+    }
+
     fn clear_meshes(&mut self) {
         self.meshes.clear();
+        self.morphs.clear();
         self.has_shadow_caster = false;
         self.has_transparent_mesh = false;
         self.occlusion_culling_radius = 0.0;
@@ -385,6 +639,38 @@ impl Model for Base {
         return &self.uniform;
     }
 
+    fn get_skin(&self) -> Option<&Skin> {
+        return self.skin.as_ref();
+    }
+
+    fn get_mut_skin(&mut self) -> Option<&mut Skin> {
+        return self.skin.as_mut();
+    }
+
+    fn get_animator(&self) -> Option<&Animator> {
+        return self.animator.as_ref();
+    }
+
+    fn get_mut_animator(&mut self) -> Option<&mut Animator> {
+        return self.animator.as_mut();
+    }
+
+    fn get_morph_weights(&self) -> &[Real] {
+        return &self.morph_weights;
+    }
+
+    fn set_morph_weights(&mut self, weights: &[Real]) {
+        self.morph_weights.clear();
+        self.morph_weights.extend_from_slice(weights);
+        self.is_morph_changed = true;
+    }
+
+    fn load_gltf_animations(&mut self, document: &gltf::Document, data: &[u8]) {
+        if let Some(animator) = &mut self.animator {
+            animator.load_gltf_clips(document, data);
+        }
+    }
+
     fn get_meshes(&self) -> &BTreeMap<Id, (Arc<RwLock<dyn Mesh>>, Material)> {
         return &self.meshes;
     }
@@ -411,6 +697,50 @@ impl Model for Base {
         return result;
     }
 
+    fn add_child(&mut self, child: Arc<RwLock<dyn Model>>) {
+        let id = {
+            let mut c = vx_result!(child.write());
+            c.set_parent(Some(self.get_id()));
+            c.set_parent_world_matrix(self.transform.get_world_matrix());
+            c.get_id()
+        };
+        self.children.insert(id, child);
+    }
+
+    fn remove_child(&mut self, id: Id) -> Option<Arc<RwLock<dyn Model>>> {
+        let child = self.children.remove(&id);
+        if let Some(c) = &child {
+            let mut c = vx_result!(c.write());
+            c.set_parent(None);
+            c.set_parent_world_matrix(&Mat4::identity());
+        }
+        return child;
+    }
+
+    fn get_parent(&self) -> Option<Id> {
+        return self.parent;
+    }
+
+    fn set_parent(&mut self, parent: Option<Id>) {
+        self.parent = parent;
+    }
+
+    fn set_parent_world_matrix(&mut self, m: &Mat4) {
+        self.transform.set_parent_world_matrix(m);
+        self.update_transform();
+    }
+
+    fn get_transform(&self) -> &Transform {
+        return &self.transform;
+    }
+
+    fn set_transform(&mut self, t: &Transform) {
+        let parent_world = *self.transform.get_parent_world_matrix();
+        self.transform = t.clone();
+        self.transform.set_parent_world_matrix(&parent_world);
+        self.update_transform();
+    }
+
     fn has_shadow(&self) -> bool {
         return self.has_shadow_caster;
     }
@@ -420,11 +750,23 @@ impl Model for Base {
     }
 
     fn get_occlusion_culling_radius(&self) -> Real {
-        return self.occlusion_culling_radius;
+        return self.occlusion_culling_radius * self.transform.get_world_max_scale();
     }
 
     fn get_distance_from_camera(&self, c: &dyn Camera) -> Real {
-        return c.get_distance(&self.uniform.model.w.truncate());
+        return c.get_distance(&self.transform.get_world_location());
+    }
+
+    fn get_lod(&self) -> usize {
+        return self.lod_selector.get_level();
+    }
+
+    fn get_lod_selector(&self) -> &LodSelector {
+        return &self.lod_selector;
+    }
+
+    fn get_mut_lod_selector(&mut self) -> &mut LodSelector {
+        return &mut self.lod_selector;
     }
 
     fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
@@ -433,16 +775,77 @@ impl Model for Base {
         }
         let buffer = self.uniform_buffer.get_buffer(frame_number);
         cmd.bind_gbuff_model_descriptor(&*self.descriptor_set, &*vx_result!(buffer.read()));
-        for (_, mesh) in &self.meshes {
+        let lod = self.get_lod();
+        for (id, mesh) in &self.meshes {
+            let m = vx_result!(mesh.0.read());
+            if m.is_skinned() {
+                continue;
+            }
             mesh.1.bind_gbuffer(cmd, frame_number);
-            vx_result!(mesh.0.read()).render_gbuffer(cmd, frame_number);
+            match self.get_morphed_vertices(id, frame_number) {
+                Some(v) => m.render_gbuffer_with_vertices(cmd, frame_number, lod, &v),
+                None => m.render_gbuffer(cmd, frame_number, lod),
+            }
         }
     }
 
     fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
-        for (_, mesh) in &self.meshes {
+        let lod = self.get_lod();
+        for (id, mesh) in &self.meshes {
+            let m = vx_result!(mesh.0.read());
+            if m.is_skinned() {
+                continue;
+            }
             mesh.1.bind_shadow(cmd, frame_number);
-            vx_result!(mesh.0.read()).render_shadow(cmd, frame_number);
+            match self.get_morphed_vertices(id, frame_number) {
+                Some(v) => m.render_shadow_with_vertices(cmd, frame_number, lod, &v),
+                None => m.render_shadow(cmd, frame_number, lod),
+            }
+        }
+    }
+
+    fn render_skinned_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        if !self.is_visible {
+            return;
+        }
+        let skin = match &self.skin {
+            Some(s) => s,
+            None => return,
+        };
+        let buffer = self.uniform_buffer.get_buffer(frame_number);
+        cmd.bind_gbuff_model_descriptor(&*self.descriptor_set, &*vx_result!(buffer.read()));
+        skin.bind_gbuffer(cmd, frame_number);
+        let lod = self.get_lod();
+        for (id, mesh) in &self.meshes {
+            let m = vx_result!(mesh.0.read());
+            if !m.is_skinned() {
+                continue;
+            }
+            mesh.1.bind_gbuffer(cmd, frame_number);
+            match self.get_morphed_vertices(id, frame_number) {
+                Some(v) => m.render_gbuffer_with_vertices(cmd, frame_number, lod, &v),
+                None => m.render_gbuffer(cmd, frame_number, lod),
+            }
+        }
+    }
+
+    fn render_skinned_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        let skin = match &self.skin {
+            Some(s) => s,
+            None => return,
+        };
+        skin.bind_shadow(cmd, frame_number);
+        let lod = self.get_lod();
+        for (id, mesh) in &self.meshes {
+            let m = vx_result!(mesh.0.read());
+            if !m.is_skinned() {
+                continue;
+            }
+            mesh.1.bind_shadow(cmd, frame_number);
+            match self.get_morphed_vertices(id, frame_number) {
+                Some(v) => m.render_shadow_with_vertices(cmd, frame_number, lod, &v),
+                None => m.render_shadow(cmd, frame_number, lod),
+            }
         }
     }
 
@@ -450,20 +853,29 @@ impl Model for Base {
         if !self.is_visible {
             return;
         }
-        self.uniform.model_view_projection = camera.get_view_projection() * self.uniform.model;
+        self.uniform.model_view_projection = *camera.get_view_projection() * self.uniform.model;
         self.uniform_buffer.update(&self.uniform, frame_number);
         let buffer = self.uniform_buffer.get_buffer(frame_number);
         cmd.bind_unlit_model_descriptor(&*self.descriptor_set, &*vx_result!(buffer.read()));
-        for (_, mesh) in &self.meshes {
+        let lod = self.get_lod();
+        for (id, mesh) in &self.meshes {
+            let m = vx_result!(mesh.0.read());
+            // Unlit pipeline does not support skinning.
+            if m.is_skinned() {
+                continue;
+            }
             mesh.1.bind_unlit(cmd, frame_number);
-            vx_result!(mesh.0.read()).render_unlit(cmd, frame_number);
+            match self.get_morphed_vertices(id, frame_number) {
+                Some(v) => m.render_unlit_with_vertices(cmd, frame_number, lod, &v),
+                None => m.render_unlit(cmd, frame_number, lod),
+            }
         }
     }
 }
 
 impl DefaultModel for Base {
     fn default(eng: &Engine) -> Self {
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+        let gapi_engine = &eng.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
         let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
@@ -473,14 +885,221 @@ impl DefaultModel for Base {
             has_shadow_caster: true,
             has_transparent_mesh: false,
             occlusion_culling_radius: 0.0,
+            aabb: Aabb3::new(),
+            subtree_aabb: Aabb3::new(),
             is_visible: false,
-            collider: Arc::new(RwLock::new(GhostCollider::new())),
+            lod_selector: LodSelector::new(),
             uniform: Uniform::default(),
             uniform_buffer,
             descriptor_set,
             meshes: BTreeMap::new(),
+            skin: None,
+            animator: None,
+            morph_weights: Vec::new(),
+            is_morph_changed: false,
+            morphs: BTreeMap::new(),
             children: BTreeMap::new(),
-            scales: cgmath::Vector3::new(1.0, 1.0, 1.0),
+            parent: None,
+            transform: Transform::new(),
         }
     }
 }
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::super::camera::Perspective;
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn new_engine() -> Arc<RwLock<Engine>> {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        let engine = Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+        vx_result!(engine.read()).set_myself(Arc::downgrade(&engine));
+        return engine;
+    }
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    fn get_world_location(model: &Arc<RwLock<Base>>) -> Vec3 {
+        return vx_result!(model.read())
+            .get_transform()
+            .get_world_location();
+    }
+
+    #[test]
+    fn transform_propagation_test() {
+        let engine = new_engine();
+        let model_manager = vx_result!(engine.read())
+            .get_asset_manager()
+            .get_model_manager()
+            .clone();
+        let mut model_manager = vx_result!(model_manager.write());
+        let parent = model_manager.create::<Base>();
+        let child = model_manager.create::<Base>();
+        let grandchild = model_manager.create::<Base>();
+        vx_result!(child.write()).set_location(&Vec3::new(1.0, 0.0, 0.0));
+        vx_result!(grandchild.write()).set_location(&Vec3::new(0.0, 0.0, 1.0));
+        vx_result!(child.write()).add_child(grandchild.clone());
+        {
+            let mut parent = vx_result!(parent.write());
+            parent.add_child(child.clone());
+            assert_eq!(parent.bring_all_child_models().len(), 2);
+            parent.set_location(&Vec3::new(1.0, 0.0, 0.0));
+            parent.rotate_global_z(PI * 0.5);
+        }
+        let parent_id = vx_result!(parent.read()).get_id();
+        assert_eq!(vx_result!(child.read()).get_parent(), Some(parent_id));
+        assert!(is_near(
+            get_world_location(&child),
+            Vec3::new(1.0, 1.0, 0.0)
+        ));
+        assert!(is_near(
+            get_world_location(&grandchild),
+            Vec3::new(1.0, 1.0, 1.0)
+        ));
+        {
+            let mut parent = vx_result!(parent.write());
+            parent.translate(&Vec3::new(0.0, 0.0, 2.0));
+            parent.scale(2.0);
+        }
+        assert!(is_near(
+            get_world_location(&child),
+            Vec3::new(1.0, 2.0, 2.0)
+        ));
+        assert!(is_near(
+            get_world_location(&grandchild),
+            Vec3::new(1.0, 2.0, 4.0)
+        ));
+        let child_id = vx_result!(child.read()).get_id();
+        let removed = vx_unwrap!(vx_result!(parent.write()).remove_child(child_id));
+        assert_eq!(vx_result!(removed.read()).get_parent(), None);
+        assert!(is_near(
+            get_world_location(&child),
+            Vec3::new(1.0, 0.0, 0.0)
+        ));
+        assert!(is_near(
+            get_world_location(&grandchild),
+            Vec3::new(1.0, 0.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn gltf_hierarchy_test() {
+        let engine = new_engine();
+        let engine = vx_result!(engine.read());
+        // Two nodes that share a triangle, the child is twice bigger.
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [
+                {"mesh": 0, "translation": [1.0, 0.0, 0.0], "children": [1]},
+                {"mesh": 0, "translation": [0.0, 1.0, 0.0], "scale": [2.0, 2.0, 2.0]}
+            ],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12}
+            ],
+            "buffers": [{"byteLength": 48}]
+        }"#;
+        let mut data = Vec::new();
+        for f in &[-1.0 as Real, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let node = vx_unwrap!(document.nodes().next());
+        let mut parent = Base::new_with_gltf(&node, &*engine, &data);
+        let children = parent.bring_all_child_models();
+        assert_eq!(children.len(), 1);
+        let child = &children[0].1;
+        let child_location = || {
+            vx_result!(child.read())
+                .get_transform()
+                .get_world_location()
+        };
+        assert!(is_near(child_location(), Vec3::new(1.0, 1.0, 0.0)));
+        let radius = parent.get_occlusion_culling_radius();
+        let child_radius = vx_result!(child.read()).get_occlusion_culling_radius();
+        assert!((child_radius - radius * 2.0).abs() < 1e-4);
+        parent.rotate_global_z(PI * 0.5);
+        assert!(is_near(child_location(), Vec3::new(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn gltf_culling_test() {
+        let engine = new_engine();
+        let engine = vx_result!(engine.read());
+        // Far leaves the view but its child comes back next to the root, the
+        // subtree of behind is behind the camera.
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [
+                {"name": "root", "mesh": 0, "translation": [0.0, 0.0, -5.0], "children": [1, 3]},
+                {"name": "far", "mesh": 0, "translation": [100.0, 0.0, 0.0], "children": [2]},
+                {"name": "back", "mesh": 0, "translation": [-100.0, 0.0, 0.0]},
+                {"name": "behind", "mesh": 0, "translation": [0.0, 0.0, 20.0], "children": [4]},
+                {"name": "behind_child", "mesh": 0, "translation": [0.0, 0.0, 1.0]}
+            ],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12}
+            ],
+            "buffers": [{"byteLength": 48}]
+        }"#;
+        let mut data = Vec::new();
+        for f in &[-1.0 as Real, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let node = vx_unwrap!(document.nodes().next());
+        let mut root = Base::new_with_gltf(&node, &*engine, &data);
+        let mut camera = Perspective::new(&*engine);
+        camera.set_location(&Vec3::zero());
+        let cull = |root: &mut Base| {
+            root.update_bounds();
+            root.cull(camera.get_frustum(), Intersection::Intersecting);
+            let mut visibilities = BTreeMap::new();
+            visibilities.insert(vx_unwrap!(root.get_name()), root.is_visible());
+            for (_, c) in root.bring_all_child_models() {
+                let c = vx_result!(c.read());
+                visibilities.insert(vx_unwrap!(c.get_name()), c.is_visible());
+            }
+            return visibilities;
+        };
+        let visibilities = cull(&mut root);
+        assert_eq!(visibilities.len(), 5);
+        assert!(visibilities["root"]);
+        assert!(!visibilities["far"]);
+        assert!(visibilities["back"]);
+        assert!(!visibilities["behind"]);
+        assert!(!visibilities["behind_child"]);
+        // Past the far plane the whole tree is culled.
+        root.set_location(&Vec3::new(0.0, 0.0, -500.0));
+        assert!(cull(&mut root).values().all(|v| !v));
+    }
+}
@@ -0,0 +1,151 @@
+use super::super::core::types::Real;
+use super::super::math::vector::Vec3;
+
+// Offsets of the attributes in a vertex, the skinned vertices have the same
+// ones.
+const POSITION_OFFSET: usize = 0;
+const NORMAL_OFFSET: usize = 3;
+const TANGENT_OFFSET: usize = 6;
+
+/// Displacements of the vertices in a morph target, the missing attributes
+/// are empty.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Target {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    /// The handedness of a tangent does not change.
+    pub tangents: Vec<Vec3>,
+}
+
+/// Morph targets of a mesh and its rest vertices.
+///
+/// The morphed vertex is the rest vertex plus the weighted sum of its
+/// displacements in the targets, the normals and the tangents are normalized
+/// again after that.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Targets {
+    vertices: Vec<Real>,
+    vertex_floats_count: usize,
+    targets: Vec<Target>,
+}
+
+impl Targets {
+    pub fn new(vertices: Vec<Real>, vertex_floats_count: usize, targets: Vec<Target>) -> Self {
+        #[cfg(debug_mode)]
+        {
+            let count = vertices.len() / vertex_floats_count;
+            for t in &targets {
+                for d in &[&t.positions, &t.normals, &t.tangents] {
+                    if d.len() != 0 && d.len() != count {
+                        vx_log_f!("Morph target must have a displacement for every vertex.");
+                    }
+                }
+            }
+        }
+        Self {
+            vertices,
+            vertex_floats_count,
+            targets,
+        }
+    }
+
+    #[inline]
+    pub fn get_vertices(&self) -> &[Real] {
+        return &self.vertices;
+    }
+
+    #[inline]
+    pub fn get_vertex_floats_count(&self) -> usize {
+        return self.vertex_floats_count;
+    }
+
+    #[inline]
+    pub fn get_targets(&self) -> &[Target] {
+        return &self.targets;
+    }
+
+    /// Missing weights are zero.
+    pub fn apply(&self, weights: &[Real], result: &mut Vec<Real>) {
+        result.clear();
+        result.extend_from_slice(&self.vertices);
+        let mut has_normals = false;
+        let mut has_tangents = false;
+        for (t, &w) in self.targets.iter().zip(weights) {
+            if w == 0.0 {
+                continue;
+            }
+            has_normals |= t.normals.len() != 0;
+            has_tangents |= t.tangents.len() != 0;
+            let deltas = [
+                (POSITION_OFFSET, &t.positions),
+                (NORMAL_OFFSET, &t.normals),
+                (TANGENT_OFFSET, &t.tangents),
+            ];
+            for &(offset, deltas) in &deltas {
+                for (v, d) in result
+                    .chunks_exact_mut(self.vertex_floats_count)
+                    .zip(deltas)
+                {
+                    v[offset] += d.x() * w;
+                    v[offset + 1] += d.y() * w;
+                    v[offset + 2] += d.z() * w;
+                }
+            }
+        }
+        for v in result.chunks_exact_mut(self.vertex_floats_count) {
+            if has_normals {
+                normalize(&mut v[NORMAL_OFFSET..NORMAL_OFFSET + 3]);
+            }
+            if has_tangents {
+                normalize(&mut v[TANGENT_OFFSET..TANGENT_OFFSET + 3]);
+            }
+        }
+    }
+}
+
+fn normalize(v: &mut [Real]) {
+    let n = Vec3::new(v[0], v[1], v[2]);
+    let l = n.length();
+    if l <= 0.0 {
+        return;
+    }
+    v.copy_from_slice(&(n / l).to_array());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn targets_test() {
+        // Two vertices with four floats after the position, normal and
+        // tangent.
+        let vertices = vec![
+            0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, -1.0, 0.5, 0.5, //
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0,
+        ];
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+        let lift = Target {
+            positions: vec![Vec3::new(0.0, 1.0, 0.0), zero],
+            normals: Vec::new(),
+            tangents: Vec::new(),
+        };
+        let tilt = Target {
+            positions: Vec::new(),
+            normals: vec![Vec3::new(0.0, 1.0, -1.0), zero],
+            tangents: vec![Vec3::new(-1.0, 1.0, 0.0), zero],
+        };
+        let targets = Targets::new(vertices.clone(), 12, vec![lift, tilt]);
+        let mut result = Vec::new();
+        targets.apply(&[], &mut result);
+        assert_eq!(result, vertices);
+        targets.apply(&[0.5], &mut result);
+        assert_eq!(result[..3], [0.0, 0.5, 0.0]);
+        assert_eq!(result[12..], vertices[12..]);
+        targets.apply(&[1.0, 1.0], &mut result);
+        assert_eq!(result[..6], [0.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(result[6..12], [0.0, 1.0, 0.0, -1.0, 0.5, 0.5]);
+        assert_eq!(result[12..], vertices[12..]);
+    }
+}
@@ -36,6 +36,7 @@ impl Kernel {
         let (shadow_signal, shadow_receiver) = channel();
         let (shadow_ready, shadow_wait) = channel();
         let handle = spawn(move || {
+            vx_profile_thread_name!(&format!("render-kernel-{}", index));
             let mut renderer =
                 Renderer::new(index, engine, scene_manager, g_buffer_filler, shadower);
             while vx_result!(render_receiver.recv()) {
@@ -112,6 +113,7 @@ impl Renderer {
     }
 
     pub fn render(&mut self) {
+        vx_profile_zone!("kernel::render");
         let geng = vx_result!(self.g_engine.read());
         let scnmgr = vx_result!(self.scene_manager.read());
         let scenes = scnmgr.get_scenes();
@@ -131,6 +133,7 @@ impl Renderer {
     }
 
     pub fn shadow(&mut self) {
+        vx_profile_zone!("kernel::shadow");
         let frame_number = vx_result!(self.g_engine.read()).get_frame_number();
         let shadower = vx_result!(self.shadower.read());
         let scnmgr = vx_result!(self.scene_manager.read());
@@ -224,27 +227,35 @@ impl Engine {
     }
 
     pub(crate) fn render(&self) {
+        vx_profile_zone!("render");
         vx_result!(self.engine.write()).start_rendering();
         let engine = vx_result!(self.engine.read());
         let frame_number = engine.get_frame_number();
         self.update_scenes(frame_number);
-        for k in &self.kernels {
-            k.start_rendering();
-        }
-        for k in &self.kernels {
-            k.wait_rendering();
+        {
+            vx_profile_zone!("wait_kernels_render");
+            for k in &self.kernels {
+                k.start_rendering();
+            }
+            for k in &self.kernels {
+                k.wait_rendering();
+            }
         }
         self.update_shadow_makers();
-        for k in &self.kernels {
-            k.start_shadowing();
-        }
-        for k in &self.kernels {
-            k.wait_shadowing();
+        {
+            vx_profile_zone!("wait_kernels_shadow");
+            for k in &self.kernels {
+                k.start_shadowing();
+            }
+            for k in &self.kernels {
+                k.wait_shadowing();
+            }
         }
         self.submit(&*engine);
     }
 
     fn update_scenes(&self, frame_number: usize) {
+        vx_profile_zone!("update_scenes");
         let mut ids = Vec::new();
         for (id, scene) in vx_result!(self.scene_manager.read()).get_scenes() {
             if let Some(scene) = scene.upgrade() {
@@ -265,6 +276,7 @@ impl Engine {
     }
 
     fn update_shadow_makers(&self) {
+        vx_profile_zone!("update_shadow_makers");
         let scnmgr = vx_result!(self.scene_manager.read());
         let scenes = scnmgr.get_scenes();
         for (_, scene) in &*scenes {
@@ -279,6 +291,7 @@ impl Engine {
     }
 
     fn submit(&self, engine: &GraphicApiEngine) {
+        vx_profile_zone!("submit");
         let mut last_semaphore = engine.get_starting_semaphore().clone();
         let frame_number = engine.get_frame_number();
         vx_result!(self.deferred.write()).update(frame_number);
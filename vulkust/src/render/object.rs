@@ -1,8 +1,9 @@
-use super::super::core::gx3d::Gx3DReader;
+// use super::super::core::gx3d::Gx3DReader;
 use super::super::core::object::{Base as CoreBase, Object as CoreObject};
 use super::super::core::types::{Id, Real};
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
 use super::engine::Engine;
-use cgmath;
 use gltf;
 
 pub trait Object: CoreObject {
@@ -15,25 +16,25 @@ pub trait Object: CoreObject {
 
 pub trait Loadable: Sized {
     fn new_with_gltf(node: &gltf::Node, engine: &Engine, data: &[u8]) -> Self;
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self;
+    // Gx3D reader is not ported yet.
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self;
 }
 
 pub trait Transferable {
-    fn set_orientation(&mut self, quad: &cgmath::Quaternion<Real>);
-    fn set_location(&mut self, loc: &cgmath::Vector3<Real>);
-    fn get_location(&self) -> cgmath::Vector3<Real>;
+    fn set_orientation(&mut self, quad: &Quat);
+    fn set_location(&mut self, loc: &Vec3);
+    fn get_location(&self) -> Vec3;
     fn move_local_z(&mut self, degree: Real);
     fn move_local_x(&mut self, degree: Real);
     fn rotate_local_x(&mut self, degree: Real);
     fn rotate_global_z(&mut self, degree: Real);
-    fn translate(&mut self, _: &cgmath::Vector3<Real>) {
-        // todo temporary
-        vx_unimplemented!();
-    }
-    fn scale(&mut self, _: Real) {
-        // todo temporary
-        vx_unimplemented!();
+    fn translate(&mut self, t: &Vec3) {
+        let location = self.get_location() + *t;
+        self.set_location(&location);
     }
+    /// Objects without a scale, like the cameras and the lights, keep their
+    /// size.
+    fn scale(&mut self, _: Real) {}
 }
 
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -110,7 +111,7 @@ impl Loadable for Base {
         }
     }
 
-    fn new_with_gx3d(_: &Engine, _: &mut Gx3DReader, my_id: Id) -> Self {
-        Self::new_with_id(my_id)
-    }
+    // fn new_with_gx3d(_: &Engine, _: &mut Gx3DReader, my_id: Id) -> Self {
+    //     Self::new_with_id(my_id)
+    // }
 }
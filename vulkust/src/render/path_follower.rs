@@ -0,0 +1,201 @@
+use super::super::core::types::Real;
+use super::super::math::curve::Path;
+use super::object::Transferable;
+use std::sync::Arc;
+
+// It moves a transferable object, e.g. a camera, along a path with a constant
+// speed. The oriented objects look along the path with their -z and keep
+// their y on the parallel transported normal of the path.
+pub struct PathFollower {
+    path: Arc<Path>,
+    speed: Real,
+    distance: Real,
+    is_looping: bool,
+    is_orienting: bool,
+}
+
+impl PathFollower {
+    pub fn new(path: Arc<Path>, speed: Real) -> Self {
+        let is_looping = path.is_closed();
+        return Self {
+            path,
+            speed,
+            distance: 0.0,
+            is_looping,
+            is_orienting: true,
+        };
+    }
+
+    #[inline]
+    pub fn get_path(&self) -> &Arc<Path> {
+        return &self.path;
+    }
+
+    // Negative speeds move the object backward.
+    pub fn set_speed(&mut self, speed: Real) {
+        self.speed = speed;
+    }
+
+    #[inline]
+    pub fn get_speed(&self) -> Real {
+        return self.speed;
+    }
+
+    pub fn set_distance(&mut self, distance: Real) {
+        self.distance = distance;
+        self.wrap_distance();
+    }
+
+    #[inline]
+    pub fn get_distance(&self) -> Real {
+        return self.distance;
+    }
+
+    // Open paths can be looped too, the object jumps back to the start.
+    pub fn set_looping(&mut self, is_looping: bool) {
+        self.is_looping = is_looping;
+    }
+
+    pub fn set_orienting(&mut self, is_orienting: bool) {
+        self.is_orienting = is_orienting;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        if self.is_looping {
+            return false;
+        }
+        if self.speed < 0.0 {
+            return self.distance <= 0.0;
+        }
+        return self.distance >= self.path.get_length();
+    }
+
+    pub fn update(&mut self, delta_time: Real, object: &mut dyn Transferable) {
+        self.distance += self.speed * delta_time;
+        self.wrap_distance();
+        self.apply(object);
+    }
+
+    // It places the object at the current distance without advancing it.
+    pub fn apply(&self, object: &mut dyn Transferable) {
+        if !self.is_orienting {
+            object.set_location(&self.path.get_point(self.distance));
+            return;
+        }
+        let frame = self.path.get_frame(self.distance);
+        object.set_orientation(&frame.get_orientation());
+        object.set_location(&frame.position);
+    }
+
+    fn wrap_distance(&mut self) {
+        let length = self.path.get_length();
+        if self.is_looping && length > 0.0 {
+            self.distance = self.distance.rem_euclid(length);
+        } else {
+            self.distance = self.distance.max(0.0).min(length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::math::curve::Polyline;
+    use super::super::super::math::quaternion::Quat;
+    use super::super::super::math::vector::Vec3;
+    use super::*;
+
+    struct Mock {
+        location: Vec3,
+        orientation: Quat,
+    }
+
+    impl Transferable for Mock {
+        fn set_orientation(&mut self, q: &Quat) {
+            self.orientation = *q;
+        }
+
+        fn set_location(&mut self, l: &Vec3) {
+            self.location = *l;
+        }
+
+        fn get_location(&self) -> Vec3 {
+            return self.location;
+        }
+
+        fn move_local_z(&mut self, _: Real) {
+            vx_unexpected!();
+        }
+
+        fn move_local_x(&mut self, _: Real) {
+            vx_unexpected!();
+        }
+
+        fn rotate_local_x(&mut self, _: Real) {
+            vx_unexpected!();
+        }
+
+        fn rotate_global_z(&mut self, _: Real) {
+            vx_unexpected!();
+        }
+    }
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-3;
+    }
+
+    #[test]
+    fn path_follower_test() {
+        let mut object = Mock {
+            location: Vec3::zero(),
+            orientation: Quat::identity(),
+        };
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        // An L shaped path, 10 along x and then 5 along -z.
+        let points = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(10.0, 0.0, -5.0),
+        ];
+        let path = Arc::new(Path::new(Box::new(Polyline::new(&points, false)), up));
+        assert!((path.get_length() - 15.0).abs() < 1e-3);
+        let mut follower = PathFollower::new(path, 2.0);
+        follower.update(1.5, &mut object);
+        assert!((follower.get_distance() - 3.0).abs() < 1e-5);
+        assert!(is_near(object.location, Vec3::new(3.0, 0.0, 0.0)));
+        let forward = object.orientation.rotate(Vec3::new(0.0, 0.0, -1.0));
+        assert!(is_near(forward, Vec3::new(1.0, 0.0, 0.0)));
+        assert!(is_near(object.orientation.rotate(up), up));
+        follower.update(5.0, &mut object);
+        assert!(is_near(object.location, Vec3::new(10.0, 0.0, -3.0)));
+        let forward = object.orientation.rotate(Vec3::new(0.0, 0.0, -1.0));
+        assert!(is_near(forward, Vec3::new(0.0, 0.0, -1.0)));
+        assert!(!follower.is_finished());
+        // Open paths stop at their end.
+        follower.update(10.0, &mut object);
+        assert!(follower.is_finished());
+        assert!(is_near(object.location, Vec3::new(10.0, 0.0, -5.0)));
+        follower.set_speed(-4.0);
+        follower.update(1.0, &mut object);
+        assert!(!follower.is_finished());
+        assert!(is_near(object.location, Vec3::new(10.0, 0.0, -1.0)));
+        // Looped ones wrap around in both directions.
+        follower.set_looping(true);
+        follower.update(1.0, &mut object);
+        assert!((follower.get_distance() - 7.0).abs() < 1e-3);
+        assert!(is_near(object.location, Vec3::new(7.0, 0.0, 0.0)));
+        follower.set_speed(-10.0);
+        follower.update(1.0, &mut object);
+        assert!((follower.get_distance() - 12.0).abs() < 1e-3);
+        assert!(is_near(object.location, Vec3::new(10.0, 0.0, -2.0)));
+        follower.set_speed(10.0);
+        follower.update(1.0, &mut object);
+        assert!((follower.get_distance() - 7.0).abs() < 1e-3);
+        // Without orienting only the location is changed.
+        let orientation = object.orientation;
+        follower.set_orienting(false);
+        follower.set_distance(12.0);
+        follower.apply(&mut object);
+        assert!(is_near(object.location, Vec3::new(10.0, 0.0, -2.0)));
+        assert!(orientation.dot(object.orientation) > 0.9999);
+    }
+}
@@ -4,8 +4,10 @@ pub(crate) use super::super::blank_gapi::pipeline::*;
 pub(crate) use super::super::d3d12::pipeline::*;
 #[cfg(metal_api)]
 pub(crate) use super::super::metal::pileline::*;
-#[cfg(vulkan_api)]
-pub(crate) use super::super::vulkan::pipeline::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::pipeline::*;
+// #[cfg(vulkan_api)]
+// pub(crate) use super::super::vulkan::pipeline::*;
 
 #[repr(u8)]
 #[derive(Clone, Copy, PartialOrd, PartialEq, Eq, Ord)]
@@ -15,6 +17,8 @@ pub enum PipelineType {
     GBuffer,
     ShadowAccumulatorDirectional,
     ShadowMapper,
+    SkinnedGBuffer,
+    SkinnedShadowMapper,
     SSAO,
     TransparentPBR,
     Unlit,
@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::render_pass::*;
 pub(crate) use super::super::d3d12::render_pass::*;
 #[cfg(metal_api)]
 pub(crate) use super::super::metal::render_pass::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::render_pass::*;
 #[cfg(vulkan_api)]
 pub(crate) use super::super::vulkan::render_pass::*;
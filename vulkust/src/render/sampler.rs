@@ -4,8 +4,10 @@ pub(crate) use super::super::blank_gapi::sampler::*;
 pub(crate) use super::super::d3d12::sampler::*;
 #[cfg(metal_api)]
 pub(crate) use super::super::metal::sampler::*;
-#[cfg(vulkan_api)]
-pub(crate) use super::super::vulkan::sampler::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::sampler::*;
+// #[cfg(vulkan_api)]
+// pub(crate) use super::super::vulkan::sampler::*;
 
 #[derive(Clone, Copy)]
 #[cfg_attr(debug_mode, derive(Debug))]
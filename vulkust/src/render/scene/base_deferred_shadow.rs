@@ -1,30 +1,35 @@
-use super::super::super::core::algorithms::merge_all_sorted;
-use super::super::super::core::constants::{MAX_DIRECTIONAL_LIGHTS_COUNT, MAX_POINT_LIGHTS_COUNT};
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::algorithms::merge_all_sorted;
+use super::super::super::core::constants::{
+    MAX_DIRECTIONAL_LIGHTS_COUNT, MAX_POINT_LIGHTS_COUNT, MAX_SPOT_LIGHTS_COUNT,
+};
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::Object as CoreObject;
 use super::super::super::core::types::{Id, Real};
+use super::super::super::math::frustum::Intersection;
+use super::super::super::math::vector::Vec4;
 use super::super::buffer::Dynamic as DynamicBuffer;
 use super::super::camera::{Camera, Uniform as CameraUniform};
-use super::super::command::{Buffer as CmdBuffer, Pool as CmdPool};
-use super::super::deferred::Deferred;
+use super::super::command::Buffer as CmdBuffer;
+// use super::super::command::Pool as CmdPool;
+// use super::super::deferred::Deferred;
 use super::super::descriptor::Set as DescriptorSet;
 use super::super::engine::Engine;
-use super::super::framebuffer::Framebuffer;
+// use super::super::framebuffer::Framebuffer;
 use super::super::g_buffer_filler::GBufferFiller;
 use super::super::gapi::GraphicApiEngine;
-use super::super::light::{DirectionalUniform, Light, PointUniform};
-use super::super::model::{Base as ModelBase, Model};
+use super::super::light::{DirectionalUniform, Light, PointUniform, SpotUniform};
+use super::super::model::{has_mesh, Base as ModelBase, Model};
 use super::super::object::{Base as ObjectBase, Loadable as ObjectLoadable, Object};
-use super::super::pipeline::{Pipeline, PipelineType};
-use super::super::render_pass::RenderPass;
-use super::super::shadower::Shadower;
-use super::super::skybox::Skybox;
-use super::super::ssao::SSAO;
+// use super::super::pipeline::{Pipeline, PipelineType};
+// use super::super::render_pass::RenderPass;
+// use super::super::shadower::Shadower;
+// use super::super::skybox::Skybox;
+// use super::super::ssao::SSAO;
 use super::super::sync::Semaphore;
 use super::{DefaultScene, Scene};
 use std::collections::BTreeMap;
 use std::mem::size_of;
-use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::sync::{Arc, RwLock, Weak};
 
 #[repr(C)]
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -32,8 +37,9 @@ struct Uniform {
     camera: CameraUniform,
     directional_lights: [DirectionalUniform; MAX_DIRECTIONAL_LIGHTS_COUNT],
     point_lights: [PointUniform; MAX_POINT_LIGHTS_COUNT],
-    lights_count: cgmath::Vector4<u32>, // directional, point, rezerved
-    ssao_config: cgmath::Vector4<Real>, // samples-count, radius, z-tolerance, rezerved
+    spot_lights: [SpotUniform; MAX_SPOT_LIGHTS_COUNT],
+    lights_count: [u32; 4], // directional, point, spot, rezerved
+    ssao_config: Vec4,      // samples-count, radius, z-tolerance, rezerved
 }
 
 impl Uniform {
@@ -43,67 +49,90 @@ impl Uniform {
             camera,
             directional_lights: [DirectionalUniform::new(); MAX_DIRECTIONAL_LIGHTS_COUNT],
             point_lights: [PointUniform::new(); MAX_POINT_LIGHTS_COUNT],
-            lights_count: cgmath::Vector4::new(0, 0, 0, 0),
-            ssao_config: cgmath::Vector4::new(64.1, 0.2, 0.6, 0.0),
+            spot_lights: [SpotUniform::new(); MAX_SPOT_LIGHTS_COUNT],
+            lights_count: [0; 4],
+            ssao_config: Vec4::new(64.1, 0.2, 0.6, 0.0),
         }
     }
 }
 
+// Single threaded G-buffer recording of a frame, it is a part of the frames
+// data below.
 #[cfg_attr(debug_mode, derive(Debug))]
-struct BaseKernelFramedata {
-    gbuff: CmdBuffer,
+struct GBufferFramedata {
+    secondary: CmdBuffer,
+    primary: CmdBuffer,
+    semaphore: Arc<Semaphore>,
 }
 
-#[cfg_attr(debug_mode, derive(Debug))]
-struct BaseKernelData {
-    frames_data: Vec<BaseKernelFramedata>,
-    distance_transparent_models: Vec<(Real, Weak<RwLock<dyn Model>>)>,
-}
-
-#[cfg_attr(debug_mode, derive(Debug))]
-struct BaseFramedata {
-    gbuffer: CmdBuffer,
-    gbuffer_semaphore: Arc<Semaphore>,
-    gbuffer_semaphore_ssao: Arc<Semaphore>,
-    deferred: CmdBuffer,
-    deferred_secondary: CmdBuffer,
-    deferred_semaphore: Arc<Semaphore>,
-    ssao: CmdBuffer,
-    ssao_secondary: CmdBuffer,
-    ssao_semaphore: Arc<Semaphore>,
-    preparation_cmd: CmdBuffer,
-    preparation_semaphore: Arc<Semaphore>,
-}
-
-impl BaseFramedata {
-    fn new(engine: &GraphicApiEngine, cmd_pool: &Arc<CmdPool>) -> Self {
-        let gbuffer = engine.create_primary_command_buffer(cmd_pool.clone());
-        let gbuffer_semaphore = Arc::new(engine.create_semaphore());
-        let gbuffer_semaphore_ssao = Arc::new(engine.create_semaphore());
-        let deferred = engine.create_primary_command_buffer(cmd_pool.clone());
-        let deferred_secondary = engine.create_secondary_command_buffer(cmd_pool.clone());
-        let deferred_semaphore = Arc::new(engine.create_semaphore());
-        let ssao = engine.create_primary_command_buffer(cmd_pool.clone());
-        let ssao_secondary = engine.create_secondary_command_buffer(cmd_pool.clone());
-        let ssao_semaphore = Arc::new(engine.create_semaphore());
-        let preparation_cmd = engine.create_primary_command_buffer(cmd_pool.clone());
-        let preparation_semaphore = Arc::new(engine.create_semaphore());
+impl GBufferFramedata {
+    fn new(engine: &GraphicApiEngine) -> Self {
         Self {
-            gbuffer,
-            gbuffer_semaphore,
-            gbuffer_semaphore_ssao,
-            deferred,
-            deferred_secondary,
-            deferred_semaphore,
-            ssao,
-            ssao_secondary,
-            ssao_semaphore,
-            preparation_cmd,
-            preparation_semaphore,
+            secondary: engine.create_secondary_command_buffer_from_main_graphic_pool(),
+            primary: engine.create_primary_command_buffer_from_main_graphic_pool(),
+            semaphore: Arc::new(engine.create_semaphore()),
         }
     }
 }
 
+// Kernels and frames data hold the command buffers of the multithreaded
+// recording, that waits for the shadower, the deferred and the SSAO passes.
+// #[cfg_attr(debug_mode, derive(Debug))]
+// struct BaseKernelFramedata {
+//     gbuff: CmdBuffer,
+// }
+//
+// #[cfg_attr(debug_mode, derive(Debug))]
+// struct BaseKernelData {
+//     frames_data: Vec<BaseKernelFramedata>,
+//     distance_transparent_models: Vec<(Real, Weak<RwLock<dyn Model>>)>,
+//     skinned_models: Vec<Arc<RwLock<dyn Model>>>,
+// }
+//
+// #[cfg_attr(debug_mode, derive(Debug))]
+// struct BaseFramedata {
+//     gbuffer: CmdBuffer,
+//     gbuffer_semaphore: Arc<Semaphore>,
+//     gbuffer_semaphore_ssao: Arc<Semaphore>,
+//     deferred: CmdBuffer,
+//     deferred_secondary: CmdBuffer,
+//     deferred_semaphore: Arc<Semaphore>,
+//     ssao: CmdBuffer,
+//     ssao_secondary: CmdBuffer,
+//     ssao_semaphore: Arc<Semaphore>,
+//     preparation_cmd: CmdBuffer,
+//     preparation_semaphore: Arc<Semaphore>,
+// }
+//
+// impl BaseFramedata {
+//     fn new(engine: &GraphicApiEngine, cmd_pool: &Arc<CmdPool>) -> Self {
+//         let gbuffer = engine.create_primary_command_buffer(cmd_pool.clone());
+//         let gbuffer_semaphore = Arc::new(engine.create_semaphore());
+//         let gbuffer_semaphore_ssao = Arc::new(engine.create_semaphore());
+//         let deferred = engine.create_primary_command_buffer(cmd_pool.clone());
+//         let deferred_secondary = engine.create_secondary_command_buffer(cmd_pool.clone());
+//         let deferred_semaphore = Arc::new(engine.create_semaphore());
+//         let ssao = engine.create_primary_command_buffer(cmd_pool.clone());
+//         let ssao_secondary = engine.create_secondary_command_buffer(cmd_pool.clone());
+//         let ssao_semaphore = Arc::new(engine.create_semaphore());
+//         let preparation_cmd = engine.create_primary_command_buffer(cmd_pool.clone());
+//         let preparation_semaphore = Arc::new(engine.create_semaphore());
+//         Self {
+//             gbuffer,
+//             gbuffer_semaphore,
+//             gbuffer_semaphore_ssao,
+//             deferred,
+//             deferred_secondary,
+//             deferred_semaphore,
+//             ssao,
+//             ssao_secondary,
+//             ssao_semaphore,
+//             preparation_cmd,
+//             preparation_semaphore,
+//         }
+//     }
+// }
+
 #[cfg_attr(debug_mode, derive(Debug))]
 pub(super) struct Base {
     obj_base: ObjectBase,
@@ -116,26 +145,29 @@ pub(super) struct Base {
     models: BTreeMap<Id, Arc<RwLock<dyn Model>>>,
     all_models: BTreeMap<Id, Weak<RwLock<dyn Model>>>,
     descriptor_set: Arc<DescriptorSet>,
-    kernels_data: Vec<Arc<Mutex<BaseKernelData>>>,
-    distance_transparent_models: Vec<(Real, Weak<RwLock<dyn Model>>)>,
-    frames_data: Vec<BaseFramedata>,
-    skybox: Option<Arc<RwLock<dyn Skybox>>>,
-    render_pass: Arc<RenderPass>,
-    framebuffers: Vec<Arc<Framebuffer>>,
-    unlit_pipeline: Arc<Pipeline>,
+    gbuffer_frames_data: Vec<GBufferFramedata>,
+    // kernels_data: Vec<Arc<Mutex<BaseKernelData>>>,
+    // distance_transparent_models: Vec<(Real, Weak<RwLock<dyn Model>>)>,
+    // frames_data: Vec<BaseFramedata>,
+    // skybox: Option<Arc<RwLock<dyn Skybox>>>,
+    // render_pass: Arc<RenderPass>,
+    // framebuffers: Vec<Arc<Framebuffer>>,
+    // unlit_pipeline: Arc<Pipeline>,
     // constraints: BTreeMap<Id, Arc<RwLock<Constraint>>>, // todo
 }
 
 impl Base {
     pub fn new_with_gltf(engine: &Engine, scene: &gltf::Scene, data: &[u8]) -> Self {
         let camera_manager = engine.get_asset_manager().get_camera_manager();
+        let light_manager = engine.get_asset_manager().get_light_manager();
         let obj_base = ObjectBase::new();
         let uniform = Uniform::new();
         let mut cameras = BTreeMap::new();
         let mut active_camera = None;
         let mut models = BTreeMap::new();
         let mut all_models = BTreeMap::new();
-        let lights = BTreeMap::new();
+        let mut lights = BTreeMap::new();
+        let mut shadow_maker_lights = BTreeMap::new();
         for node in scene.nodes() {
             if node.camera().is_some() {
                 let camera = vx_result!(camera_manager.write()).load_gltf(&node, engine);
@@ -143,7 +175,7 @@ impl Base {
                 let w = Arc::downgrade(&camera);
                 cameras.insert(id, camera);
                 active_camera = Some(w);
-            } else if let Some(_) = node.mesh() {
+            } else if has_mesh(&node) {
                 let model = ModelBase::new_with_gltf(&node, engine, data);
                 let child_models = model.bring_all_child_models();
                 for (id, model) in child_models {
@@ -153,107 +185,12 @@ impl Base {
                 let model: Arc<RwLock<dyn Model>> = Arc::new(RwLock::new(model));
                 all_models.insert(id, Arc::downgrade(&model));
                 models.insert(id, model);
-            } // todo read lights
-        }
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
-        let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
-            .create_dynamic_buffer(size_of::<Uniform>() as isize);
-        let render_pass = gapi_engine.get_render_pass().clone();
-        let framebuffers = gapi_engine.get_framebuffers().clone();
-        let unlit_pipeline = vx_result!(gapi_engine.get_pipeline_manager().write()).create(
-            render_pass.clone(),
-            PipelineType::Unlit,
-            engine.get_config(),
-        );
-        let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
-        let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
-        let frames_count = gapi_engine.get_frames_count();
-        let kernels_count = num_cpus::get();
-        let mut kernels_data = Vec::with_capacity(kernels_count);
-        for _ in 0..kernels_count {
-            kernels_data.push(Arc::new(Mutex::new(BaseKernelData {
-                frames_data: Vec::with_capacity(frames_count),
-                distance_transparent_models: Vec::new(),
-            })));
-        }
-        Self {
-            obj_base,
-            uniform,
-            uniform_buffer,
-            descriptor_set,
-            cameras,
-            active_camera,
-            shadow_maker_lights: BTreeMap::new(),
-            lights,
-            models,
-            all_models,
-            kernels_data,
-            distance_transparent_models: Vec::new(),
-            frames_data: Vec::new(),
-            skybox: None,
-            unlit_pipeline,
-            framebuffers,
-            render_pass,
-        }
-    }
-
-    pub fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let asset_manager = eng.get_asset_manager();
-        let cameras_ids = reader.read_array::<Id>();
-        let _audios_ids = reader.read_array::<Id>(); // todo
-        let lights_ids = reader.read_array::<Id>();
-        let models_ids = reader.read_array::<Id>();
-        let skybox = if reader.read_bool() {
-            let skybox_id: Id = reader.read();
-            let skyboxmgr = asset_manager.get_skybox_manager();
-            let mut skyboxmgr = vx_result!(skyboxmgr.write());
-            Some(skyboxmgr.load_gx3d(eng, skybox_id))
-        } else {
-            None
-        };
-        let _constraits_ids = reader.read_array::<Id>(); // todo
-        if reader.read_bool() {
-            vx_unimplemented!(); // todo
-        }
-        let camera_manager = asset_manager.get_camera_manager();
-        let light_manager = asset_manager.get_light_manager();
-        let model_manager = asset_manager.get_model_manager();
-        let mut cameras = BTreeMap::new();
-        let active_camera = {
-            let mut mgr = vx_result!(camera_manager.write());
-            for id in &cameras_ids {
-                cameras.insert(*id, mgr.load_gx3d(eng, *id));
-            }
-            if cameras_ids.len() > 0 {
-                Some(Arc::downgrade(&mgr.load_gx3d(eng, cameras_ids[0])))
-            } else {
-                None
-            }
-        };
-        let mut models = BTreeMap::new();
-        let mut all_models = BTreeMap::new();
-        {
-            let mut mgr = vx_result!(model_manager.write());
-            for id in models_ids {
-                let model = mgr.load_gx3d(eng, id);
-                {
-                    let model = vx_result!(model.read());
-                    let child_models = model.bring_all_child_models();
-                    for (id, model) in child_models {
-                        all_models.insert(id, Arc::downgrade(&model));
-                    }
-                }
-                all_models.insert(id, Arc::downgrade(&model));
-                models.insert(id, model);
-            }
-        }
-        let mut lights = BTreeMap::new();
-        let mut shadow_maker_lights = BTreeMap::new();
-        {
-            let mut mgr = vx_result!(light_manager.write());
-            for id in lights_ids {
-                let light = mgr.load_gx3d(eng, id);
-                let is_shadow_maker = vx_result!(light.read()).to_shadow_maker().is_some();
+            } else if node.light().is_some() {
+                let light = vx_result!(light_manager.write()).load_gltf(&node, engine, data);
+                let (id, is_shadow_maker) = {
+                    let light = vx_result!(light.read());
+                    (light.get_id(), light.to_shadow_maker().is_some())
+                };
                 if is_shadow_maker {
                     shadow_maker_lights.insert(id, light);
                 } else {
@@ -261,71 +198,157 @@ impl Base {
                 }
             }
         }
-        let uniform = Uniform::new();
-        let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+        let gapi_engine = &engine.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
-        let render_pass = gapi_engine.get_render_pass().clone();
-        let framebuffers = gapi_engine.get_framebuffers().clone();
-        let unlit_pipeline = vx_result!(gapi_engine.get_pipeline_manager().write()).create(
-            render_pass.clone(),
-            PipelineType::Unlit,
-            eng.get_config(),
-        );
         let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
         let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
-        let frames_count = gapi_engine.get_frames_count();
-        let kernels_count = num_cpus::get();
-        let mut kernels_data = Vec::with_capacity(kernels_count);
-        for _ in 0..kernels_count {
-            kernels_data.push(Arc::new(Mutex::new(BaseKernelData {
-                frames_data: Vec::with_capacity(frames_count),
-                distance_transparent_models: Vec::new(),
-            })));
-        }
         Self {
-            obj_base: ObjectBase::new_with_id(my_id),
+            obj_base,
             uniform,
             uniform_buffer,
             descriptor_set,
+            gbuffer_frames_data: Vec::new(),
             cameras,
             active_camera,
-            models,
-            all_models,
             shadow_maker_lights,
             lights,
-            kernels_data,
-            distance_transparent_models: Vec::new(),
-            frames_data: Vec::new(),
-            skybox,
-            render_pass,
-            framebuffers,
-            unlit_pipeline,
+            models,
+            all_models,
         }
     }
 
-    fn gather_all_transparent_models_sorted(&self) -> Vec<Weak<RwLock<dyn Model>>> {
-        let kernels_count = self.kernels_data.len();
-        let mut kernels_data = Vec::with_capacity(kernels_count);
-        for kd in &self.kernels_data {
-            kernels_data.push(vx_result!(kd.lock()));
-        }
-        let mut ds: Vec<&[(Real, Weak<RwLock<dyn Model>>)]> = Vec::with_capacity(kernels_count);
-        for kd in &kernels_data {
-            ds.push(&kd.distance_transparent_models);
-        }
-        let sorted = merge_all_sorted(
-            &ds,
-            &mut |b: &(Real, Weak<RwLock<dyn Model>>), a: &(Real, Weak<RwLock<dyn Model>>)| {
-                a.0.partial_cmp(&b.0).unwrap()
-            },
-        );
-        let mut result = Vec::with_capacity(sorted.len());
-        for e in sorted {
-            result.push(e.1);
-        }
-        return result;
-    }
+    // Gx3D reader is not ported yet.
+    // pub fn new_with_gx3d(eng: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let asset_manager = eng.get_asset_manager();
+    //     let cameras_ids = reader.read_array::<Id>();
+    //     let _audios_ids = reader.read_array::<Id>(); // todo
+    //     let lights_ids = reader.read_array::<Id>();
+    //     let models_ids = reader.read_array::<Id>();
+    //     let skybox = if reader.read_bool() {
+    //         let skybox_id: Id = reader.read();
+    //         let skyboxmgr = asset_manager.get_skybox_manager();
+    //         let mut skyboxmgr = vx_result!(skyboxmgr.write());
+    //         Some(skyboxmgr.load_gx3d(eng, skybox_id))
+    //     } else {
+    //         None
+    //     };
+    //     let _constraits_ids = reader.read_array::<Id>(); // todo
+    //     if reader.read_bool() {
+    //         vx_unimplemented!(); // todo
+    //     }
+    //     let camera_manager = asset_manager.get_camera_manager();
+    //     let light_manager = asset_manager.get_light_manager();
+    //     let model_manager = asset_manager.get_model_manager();
+    //     let mut cameras = BTreeMap::new();
+    //     let active_camera = {
+    //         let mut mgr = vx_result!(camera_manager.write());
+    //         for id in &cameras_ids {
+    //             cameras.insert(*id, mgr.load_gx3d(eng, *id));
+    //         }
+    //         if cameras_ids.len() > 0 {
+    //             Some(Arc::downgrade(&mgr.load_gx3d(eng, cameras_ids[0])))
+    //         } else {
+    //             None
+    //         }
+    //     };
+    //     let mut models = BTreeMap::new();
+    //     let mut all_models = BTreeMap::new();
+    //     {
+    //         let mut mgr = vx_result!(model_manager.write());
+    //         for id in models_ids {
+    //             let model = mgr.load_gx3d(eng, id);
+    //             {
+    //                 let model = vx_result!(model.read());
+    //                 let child_models = model.bring_all_child_models();
+    //                 for (id, model) in child_models {
+    //                     all_models.insert(id, Arc::downgrade(&model));
+    //                 }
+    //             }
+    //             all_models.insert(id, Arc::downgrade(&model));
+    //             models.insert(id, model);
+    //         }
+    //     }
+    //     let mut lights = BTreeMap::new();
+    //     let mut shadow_maker_lights = BTreeMap::new();
+    //     {
+    //         let mut mgr = vx_result!(light_manager.write());
+    //         for id in lights_ids {
+    //             let light = mgr.load_gx3d(eng, id);
+    //             let is_shadow_maker = vx_result!(light.read()).to_shadow_maker().is_some();
+    //             if is_shadow_maker {
+    //                 shadow_maker_lights.insert(id, light);
+    //             } else {
+    //                 lights.insert(id, light);
+    //             }
+    //         }
+    //     }
+    //     let uniform = Uniform::new();
+    //     let gapi_engine = vx_result!(eng.get_gapi_engine().read());
+    //     let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
+    //         .create_dynamic_buffer(size_of::<Uniform>() as isize);
+    //     let render_pass = gapi_engine.get_render_pass().clone();
+    //     let framebuffers = gapi_engine.get_framebuffers().clone();
+    //     let unlit_pipeline = vx_result!(gapi_engine.get_pipeline_manager().write()).create(
+    //         render_pass.clone(),
+    //         PipelineType::Unlit,
+    //         eng.get_config(),
+    //     );
+    //     let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
+    //     let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
+    //     let frames_count = gapi_engine.get_frames_count();
+    //     let kernels_count = num_cpus::get();
+    //     let mut kernels_data = Vec::with_capacity(kernels_count);
+    //     for _ in 0..kernels_count {
+    //         kernels_data.push(Arc::new(Mutex::new(BaseKernelData {
+    //             frames_data: Vec::with_capacity(frames_count),
+    //             distance_transparent_models: Vec::new(),
+    //             skinned_models: Vec::new(),
+    //         })));
+    //     }
+    //     Self {
+    //         obj_base: ObjectBase::new_with_id(my_id),
+    //         uniform,
+    //         uniform_buffer,
+    //         descriptor_set,
+    //         cameras,
+    //         active_camera,
+    //         models,
+    //         all_models,
+    //         shadow_maker_lights,
+    //         lights,
+    //         kernels_data,
+    //         distance_transparent_models: Vec::new(),
+    //         frames_data: Vec::new(),
+    //         skybox,
+    //         render_pass,
+    //         framebuffers,
+    //         unlit_pipeline,
+    //     }
+    // }
+
+    // fn gather_all_transparent_models_sorted(&self) -> Vec<Weak<RwLock<dyn Model>>> {
+    //     let kernels_count = self.kernels_data.len();
+    //     let mut kernels_data = Vec::with_capacity(kernels_count);
+    //     for kd in &self.kernels_data {
+    //         kernels_data.push(vx_result!(kd.lock()));
+    //     }
+    //     let mut ds: Vec<&[(Real, Weak<RwLock<dyn Model>>)]> = Vec::with_capacity(kernels_count);
+    //     for kd in &kernels_data {
+    //         ds.push(&kd.distance_transparent_models);
+    //     }
+    //     let sorted = merge_all_sorted(
+    //         &ds,
+    //         &mut |b: &(Real, Weak<RwLock<dyn Model>>), a: &(Real, Weak<RwLock<dyn Model>>)| {
+    //             a.0.partial_cmp(&b.0).unwrap()
+    //         },
+    //     );
+    //     let mut result = Vec::with_capacity(sorted.len());
+    //     for e in sorted {
+    //         result.push(e.1);
+    //     }
+    //     return result;
+    // }
 
     // fn render_transparent_models(&self) {
     //     let models = self.gather_all_transparent_models_sorted();
@@ -410,7 +433,28 @@ impl Scene for Base {
         return &self.active_camera;
     }
 
-    fn update(&mut self, frame_number: usize) {
+    fn set_active_camera(&mut self, id: Id) {
+        let camera = vx_unwrap!(self.cameras.get(&id));
+        self.active_camera = Some(Arc::downgrade(camera));
+    }
+
+    fn get_cameras(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Camera>>> {
+        return &self.cameras;
+    }
+
+    fn bring_all_lights(&self) -> Vec<(Id, Arc<RwLock<dyn Light>>)> {
+        let mut result = Vec::with_capacity(self.lights.len() + self.shadow_maker_lights.len());
+        for (id, light) in &self.lights {
+            result.push((*id, light.clone()));
+        }
+        for (id, light) in &self.shadow_maker_lights {
+            result.push((*id, light.clone()));
+        }
+        return result;
+    }
+
+    fn update(&mut self, frame_number: usize, delta_time: Real) {
+        vx_profile_zone!("Scene::update");
         if !self.is_renderable() {
             return;
         }
@@ -420,7 +464,9 @@ impl Scene for Base {
         camera.update_uniform(&mut self.uniform.camera);
         let mut last_directional_light_index = 0;
         let mut last_point_light_index = 0;
-        let csmws = camera.get_cascaded_shadow_frustum_partitions();
+        let mut last_spot_light_index = 0;
+        // Sun waits for the shadower, that is not ported yet.
+        // let csmws = camera.get_cascaded_shadow_frustum_partitions();
         for (_, shm) in &self.shadow_maker_lights {
             let mut shm = vx_result!(shm.write());
             if !shm.is_renderable() {
@@ -428,12 +474,12 @@ impl Scene for Base {
             }
             {
                 if let Some(shm) = shm.to_mut_directional() {
-                    if let Some(sun) = shm.to_mut_sun() {
-                        sun.update_cascaded_shadow_map_cameras(
-                            &csmws,
-                            last_directional_light_index,
-                        );
-                    }
+                    // if let Some(sun) = shm.to_mut_sun() {
+                    //     sun.update_cascaded_shadow_map_cameras(
+                    //         &csmws,
+                    //         last_directional_light_index,
+                    //     );
+                    // }
                     shm.update_uniform(
                         &mut self.uniform.directional_lights[last_directional_light_index],
                     );
@@ -462,121 +508,169 @@ impl Scene for Base {
             } else if let Some(l) = l.to_point() {
                 l.update_uniform(&mut self.uniform.point_lights[last_point_light_index]);
                 last_point_light_index += 1;
+            } else if let Some(l) = l.to_spot() {
+                l.update_uniform(&mut self.uniform.spot_lights[last_spot_light_index]);
+                last_spot_light_index += 1;
             }
         }
-        self.uniform.lights_count.x = last_directional_light_index as u32;
-        self.uniform.lights_count.y = last_point_light_index as u32;
+        self.uniform.lights_count[0] = last_directional_light_index as u32;
+        self.uniform.lights_count[1] = last_point_light_index as u32;
+        self.uniform.lights_count[2] = last_spot_light_index as u32;
+        vx_profile_counter!("directional_lights", last_directional_light_index);
+        vx_profile_counter!("point_lights", last_point_light_index);
+        vx_profile_counter!("spot_lights", last_spot_light_index);
+        vx_profile_counter!("models", self.all_models.len());
         self.uniform_buffer.update(&self.uniform, frame_number);
-        if let Some(skybox) = &self.skybox {
-            vx_result!(skybox.write()).update(&*camera, frame_number);
-        }
-    }
-
-    fn update_shadow_makers(&self) {
-        for (_, shm) in &self.shadow_maker_lights {
-            let mut shm = vx_result!(shm.write());
-            if !shm.is_renderable() {
-                continue;
-            }
-            shm.update();
-        }
-    }
-
-    fn render_gbuffer_shadow_maps(
-        &self,
-        geng: &GraphicApiEngine,
-        cmd_pool: &Arc<CmdPool>,
-        g_buffer_filler: &GBufferFiller,
-        shadower: &Shadower,
-        kernel_index: usize,
-    ) {
-        if !self.is_renderable() {
-            return;
-        }
-        let frame_number = geng.get_frame_number();
-        for (_, shm) in &self.shadow_maker_lights {
-            vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).begin_secondary_commands(
-                geng,
-                cmd_pool,
-                shadower,
-                kernel_index,
-                frame_number,
-            );
-        }
-        let kernels_count = self.kernels_data.len();
-        let mut kernel_data = vx_result!(self.kernels_data[kernel_index].lock());
-        if kernel_data.frames_data.len() < 1 {
-            let frames_count = geng.get_frames_count();
-            for _ in 0..frames_count {
-                kernel_data.frames_data.push(BaseKernelFramedata {
-                    gbuff: geng.create_secondary_command_buffer(cmd_pool.clone()),
-                });
+        // Skybox is not ported yet.
+        // if let Some(skybox) = &self.skybox {
+        //     vx_result!(skybox.write()).update(&*camera, frame_number);
+        // }
+        for model in self.all_models.values() {
+            if let Some(model) = model.upgrade() {
+                vx_result!(model.write()).animate(delta_time);
             }
         }
-        kernel_data.distance_transparent_models.clear();
-        {
-            let cmd = &mut kernel_data.frames_data[frame_number].gbuff;
-            g_buffer_filler.begin_secondary(cmd);
-            let buffer = self.uniform_buffer.get_buffer(frame_number);
-            let buffer = vx_result!(buffer.read());
-            cmd.bind_gbuff_scene_descriptor(&*self.descriptor_set, &*buffer);
+        // Roots cull their subtrees, so the bounds of the children are
+        // gathered by them.
+        let frustum = camera.get_frustum();
+        for model in self.models.values() {
+            let mut model = vx_result!(model.write());
+            model.update_bounds();
+            model.cull(frustum, Intersection::Intersecting);
         }
-        let camera = vx_unwrap!(&self.active_camera).upgrade();
-        let camera = vx_unwrap!(camera);
-        let camera = vx_result!(camera.read());
-        let mut task_index = 0;
-        for (_, mw) in &self.all_models {
-            task_index += 1;
-            task_index %= kernels_count;
-            if task_index != kernel_index {
-                continue;
-            }
-            let model = mw.upgrade();
-            let m = if let Some(model) = model {
+        for model in self.all_models.values() {
+            let model = if let Some(model) = model.upgrade() {
                 model
             } else {
                 continue;
             };
-            let mut model = vx_result!(m.write());
+            let mut model = vx_result!(model.write());
             if !model.is_renderable() {
                 continue;
             }
-            model.update(self, &*camera, frame_number);
-            model.render_gbuffer(
-                &mut kernel_data.frames_data[frame_number].gbuff,
-                frame_number,
-            );
-            if model.has_shadow() {
-                for (_, shm) in &self.shadow_maker_lights {
-                    vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).shadow(
-                        &mut *model,
-                        &m,
-                        kernel_index,
-                    );
-                }
-            }
-            if model.has_transparent() {
-                let d = model.get_distance_from_camera(&*camera);
-                kernel_data
-                    .distance_transparent_models
-                    .push((d, mw.clone()));
-            }
+            model.update(&*camera, frame_number);
         }
-        kernel_data.frames_data[frame_number].gbuff.end();
-        kernel_data
-            .distance_transparent_models
-            .sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
     }
 
-    fn render_shadow_maps(&self, shadower: &Shadower, kernel_index: usize, frame_number: usize) {
-        for (_, shm) in &self.shadow_maker_lights {
-            vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).render_shadow_mapper(
-                shadower,
-                kernel_index,
-                frame_number,
-            );
-        }
-    }
+    // Multithreaded recording waits for the shadower, the deferred and the
+    // SSAO passes, that are not ported yet.
+    // fn update_shadow_makers(&self) {
+    //     for (_, shm) in &self.shadow_maker_lights {
+    //         let mut shm = vx_result!(shm.write());
+    //         if !shm.is_renderable() {
+    //             continue;
+    //         }
+    //         shm.update();
+    //     }
+    // }
+    //
+    // fn render_gbuffer_shadow_maps(
+    //     &self,
+    //     geng: &GraphicApiEngine,
+    //     cmd_pool: &Arc<CmdPool>,
+    //     g_buffer_filler: &GBufferFiller,
+    //     shadower: &Shadower,
+    //     kernel_index: usize,
+    // ) {
+    //     if !self.is_renderable() {
+    //         return;
+    //     }
+    //     let frame_number = geng.get_frame_number();
+    //     for (_, shm) in &self.shadow_maker_lights {
+    //         vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).begin_secondary_commands(
+    //             geng,
+    //             cmd_pool,
+    //             shadower,
+    //             kernel_index,
+    //             frame_number,
+    //         );
+    //     }
+    //     let kernels_count = self.kernels_data.len();
+    //     let mut kernel_data = vx_result!(self.kernels_data[kernel_index].lock());
+    //     if kernel_data.frames_data.len() < 1 {
+    //         let frames_count = geng.get_frames_count();
+    //         for _ in 0..frames_count {
+    //             kernel_data.frames_data.push(BaseKernelFramedata {
+    //                 gbuff: geng.create_secondary_command_buffer(cmd_pool.clone()),
+    //             });
+    //         }
+    //     }
+    //     kernel_data.distance_transparent_models.clear();
+    //     {
+    //         let cmd = &mut kernel_data.frames_data[frame_number].gbuff;
+    //         g_buffer_filler.begin_secondary(cmd);
+    //         let buffer = self.uniform_buffer.get_buffer(frame_number);
+    //         let buffer = vx_result!(buffer.read());
+    //         cmd.bind_gbuff_scene_descriptor(&*self.descriptor_set, &*buffer);
+    //     }
+    //     let camera = vx_unwrap!(&self.active_camera).upgrade();
+    //     let camera = vx_unwrap!(camera);
+    //     let camera = vx_result!(camera.read());
+    //     let mut task_index = 0;
+    //     for (_, mw) in &self.all_models {
+    //         task_index += 1;
+    //         task_index %= kernels_count;
+    //         if task_index != kernel_index {
+    //             continue;
+    //         }
+    //         let model = mw.upgrade();
+    //         let m = if let Some(model) = model {
+    //             model
+    //         } else {
+    //             continue;
+    //         };
+    //         let mut model = vx_result!(m.write());
+    //         if !model.is_renderable() {
+    //             continue;
+    //         }
+    //         model.update(self, &*camera, frame_number);
+    //         model.render_gbuffer(
+    //             &mut kernel_data.frames_data[frame_number].gbuff,
+    //             frame_number,
+    //         );
+    //         if model.has_shadow() {
+    //             for (_, shm) in &self.shadow_maker_lights {
+    //                 vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).shadow(
+    //                     &mut *model,
+    //                     &m,
+    //                     kernel_index,
+    //                 );
+    //             }
+    //         }
+    //         if model.has_transparent() {
+    //             let d = model.get_distance_from_camera(&*camera);
+    //             kernel_data
+    //                 .distance_transparent_models
+    //                 .push((d, mw.clone()));
+    //         }
+    //         if model.get_skin().is_some() {
+    //             kernel_data.skinned_models.push(m.clone());
+    //         }
+    //     }
+    //     // Skinned meshes come after the others to switch the pipeline once.
+    //     if !kernel_data.skinned_models.is_empty() {
+    //         let kernel_data = &mut *kernel_data;
+    //         let cmd = &mut kernel_data.frames_data[frame_number].gbuff;
+    //         g_buffer_filler.bind_skinned_pipeline(cmd);
+    //         for m in kernel_data.skinned_models.drain(..) {
+    //             vx_result!(m.read()).render_skinned_gbuffer(cmd, frame_number);
+    //         }
+    //     }
+    //     kernel_data.frames_data[frame_number].gbuff.end();
+    //     kernel_data
+    //         .distance_transparent_models
+    //         .sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    // }
+    //
+    // fn render_shadow_maps(&self, shadower: &Shadower, kernel_index: usize, frame_number: usize) {
+    //     for (_, shm) in &self.shadow_maker_lights {
+    //         vx_unwrap!(vx_result!(shm.read()).to_shadow_maker()).render_shadow_mapper(
+    //             shadower,
+    //             kernel_index,
+    //             frame_number,
+    //         );
+    //     }
+    // }
 
     fn get_models(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Model>>> {
         return &self.models;
@@ -586,166 +680,474 @@ impl Scene for Base {
         return &self.all_models;
     }
 
-    fn clean(&mut self) {
-        let mut ids = Vec::<Id>::new();
-        for (id, model) in &self.all_models {
-            if model.upgrade().is_none() {
-                ids.push(*id);
-            }
-        }
-        for id in ids {
-            self.all_models.remove(&id);
-        }
-    }
-
-    fn submit(
+    fn submit_gbuffer(
         &mut self,
         geng: &GraphicApiEngine,
         sem: &Arc<Semaphore>,
-        cmd_pool: &Arc<CmdPool>,
         g_buffer_filler: &GBufferFiller,
-        shadower: &mut Shadower,
-        deferred: &Deferred,
-        ssao: Option<&SSAO>,
     ) -> Arc<Semaphore> {
+        vx_profile_zone!("Scene::submit_gbuffer");
         if !self.is_renderable() {
             return sem.clone();
         }
         let frame_number = geng.get_frame_number();
-        let frames_count = geng.get_frames_count();
-        let frames_data_len = self.frames_data.len();
-        for _ in frames_data_len..frames_count {
-            self.frames_data.push(BaseFramedata::new(geng, cmd_pool));
+        for _ in self.gbuffer_frames_data.len()..geng.get_frames_count() {
+            self.gbuffer_frames_data.push(GBufferFramedata::new(geng));
         }
-        let frame_data = &mut self.frames_data[frame_number];
-        // g-buffer
+        let frame_data = &mut self.gbuffer_frames_data[frame_number];
+        let cmd = &mut frame_data.secondary;
+        g_buffer_filler.begin_secondary(cmd);
         {
-            let cmd = &mut frame_data.gbuffer;
-            cmd.begin();
-            g_buffer_filler.begin_primary(cmd);
-            for k in &self.kernels_data {
-                cmd.exe_cmd(&vx_result!(k.lock()).frames_data[frame_number].gbuff);
-            }
-            cmd.end_render_pass();
-            cmd.end();
+            let buffer = self.uniform_buffer.get_buffer(frame_number);
+            let buffer = vx_result!(buffer.read());
+            cmd.bind_gbuff_scene_descriptor(&self.descriptor_set, &buffer);
         }
-        if ssao.is_some() {
-            geng.submit_multiple(
-                &[&sem],
-                &[&frame_data.gbuffer],
-                &[
-                    &frame_data.gbuffer_semaphore,
-                    &frame_data.gbuffer_semaphore_ssao,
-                ],
-            );
-        } else {
-            geng.submit(&sem, &frame_data.gbuffer, &frame_data.gbuffer_semaphore);
+        let mut skinned_models = Vec::new();
+        for model in self.all_models.values() {
+            let model = if let Some(model) = model.upgrade() {
+                model
+            } else {
+                continue;
+            };
+            {
+                let model = vx_result!(model.read());
+                if !model.is_renderable() || !model.is_visible() {
+                    continue;
+                }
+                model.render_gbuffer(cmd, frame_number);
+                if model.get_skin().is_none() {
+                    continue;
+                }
+            }
+            skinned_models.push(model);
         }
-        // shadow
-        shadower.clear_shadow_accumulator(&mut frame_data.preparation_cmd);
-        geng.submit(
-            &frame_data.gbuffer_semaphore,
-            &frame_data.preparation_cmd,
-            &frame_data.preparation_semaphore,
-        );
-        let mut last_sem = frame_data.preparation_semaphore.clone();
-        for (_, sml) in &self.shadow_maker_lights {
-            let mut sml = vx_result!(sml.write());
-            let sml = vx_unwrap!(sml.to_mut_shadow_maker());
-            last_sem = sml.submit_shadow_mapper(&last_sem, geng, shadower, frame_number);
+        // Skinned meshes come after the others to switch the pipeline once.
+        if !skinned_models.is_empty() {
+            g_buffer_filler.bind_skinned_pipeline(cmd);
+            for m in &skinned_models {
+                vx_result!(m.read()).render_skinned_gbuffer(cmd, frame_number);
+            }
         }
-        let uniform_buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
-        // SSAO
-        if let Some(ssao) = &ssao {
-            ssao.begin_secondary(&mut frame_data.ssao_secondary);
-            frame_data
-                .ssao_secondary
-                .bind_ssao_scene_descriptor(&*self.descriptor_set, &*uniform_buffer);
-            ssao.end_secondary(&mut frame_data.ssao_secondary, frame_number);
-            ssao.record_primary(&mut frame_data.ssao, &frame_data.ssao_secondary);
-            geng.submit(
-                &frame_data.gbuffer_semaphore_ssao,
-                &frame_data.ssao,
-                &frame_data.ssao_semaphore,
-            );
+        cmd.end();
+        let primary = &mut frame_data.primary;
+        primary.begin();
+        g_buffer_filler.begin_primary(primary);
+        primary.exe_cmd(&frame_data.secondary);
+        primary.end_render_pass();
+        primary.end();
+        geng.submit(sem, primary, &frame_data.semaphore);
+        return frame_data.semaphore.clone();
+    }
+
+    fn clean(&mut self) {
+        let mut ids = Vec::<Id>::new();
+        for (id, model) in &self.all_models {
+            if model.upgrade().is_none() {
+                ids.push(*id);
+            }
         }
-        // deferred
-        frame_data
-            .deferred_secondary
-            .begin_secondary(geng.get_current_framebuffer());
-        // deferred -> skybox
-
-        // deferred -> final
-        deferred.render(&mut frame_data.deferred_secondary, frame_number);
-        frame_data
-            .deferred_secondary
-            .bind_deferred_scene_descriptor(&*self.descriptor_set, &*uniform_buffer);
-        frame_data.deferred_secondary.render_deferred();
-        frame_data.deferred_secondary.end();
-        frame_data.deferred.begin();
-        geng.get_current_framebuffer()
-            .begin(&mut frame_data.deferred);
-        frame_data.deferred.exe_cmd(&frame_data.deferred_secondary);
-        frame_data.deferred.end_render_pass();
-        frame_data.deferred.end();
-        if ssao.is_some() {
-            geng.submit_multiple(
-                &[&last_sem, &frame_data.ssao_semaphore],
-                &[&frame_data.deferred],
-                &[&frame_data.deferred_semaphore],
-            );
-        } else {
-            geng.submit(
-                &last_sem,
-                &frame_data.deferred,
-                &frame_data.deferred_semaphore,
-            );
+        for id in ids {
+            self.all_models.remove(&id);
         }
-        return frame_data.deferred_semaphore.clone();
     }
+
+    // fn submit(
+    //     &mut self,
+    //     geng: &GraphicApiEngine,
+    //     sem: &Arc<Semaphore>,
+    //     cmd_pool: &Arc<CmdPool>,
+    //     g_buffer_filler: &GBufferFiller,
+    //     shadower: &mut Shadower,
+    //     deferred: &Deferred,
+    //     ssao: Option<&SSAO>,
+    // ) -> Arc<Semaphore> {
+    //     if !self.is_renderable() {
+    //         return sem.clone();
+    //     }
+    //     let frame_number = geng.get_frame_number();
+    //     let frames_count = geng.get_frames_count();
+    //     let frames_data_len = self.frames_data.len();
+    //     for _ in frames_data_len..frames_count {
+    //         self.frames_data.push(BaseFramedata::new(geng, cmd_pool));
+    //     }
+    //     let frame_data = &mut self.frames_data[frame_number];
+    //     // g-buffer
+    //     {
+    //         let cmd = &mut frame_data.gbuffer;
+    //         cmd.begin();
+    //         g_buffer_filler.begin_primary(cmd);
+    //         for k in &self.kernels_data {
+    //             cmd.exe_cmd(&vx_result!(k.lock()).frames_data[frame_number].gbuff);
+    //         }
+    //         cmd.end_render_pass();
+    //         cmd.end();
+    //     }
+    //     if ssao.is_some() {
+    //         geng.submit_multiple(
+    //             &[&sem],
+    //             &[&frame_data.gbuffer],
+    //             &[
+    //                 &frame_data.gbuffer_semaphore,
+    //                 &frame_data.gbuffer_semaphore_ssao,
+    //             ],
+    //         );
+    //     } else {
+    //         geng.submit(&sem, &frame_data.gbuffer, &frame_data.gbuffer_semaphore);
+    //     }
+    //     // shadow
+    //     shadower.clear_shadow_accumulator(&mut frame_data.preparation_cmd);
+    //     geng.submit(
+    //         &frame_data.gbuffer_semaphore,
+    //         &frame_data.preparation_cmd,
+    //         &frame_data.preparation_semaphore,
+    //     );
+    //     let mut last_sem = frame_data.preparation_semaphore.clone();
+    //     for (_, sml) in &self.shadow_maker_lights {
+    //         let mut sml = vx_result!(sml.write());
+    //         let sml = vx_unwrap!(sml.to_mut_shadow_maker());
+    //         last_sem = sml.submit_shadow_mapper(&last_sem, geng, shadower, frame_number);
+    //     }
+    //     let uniform_buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
+    //     // SSAO
+    //     if let Some(ssao) = &ssao {
+    //         ssao.begin_secondary(&mut frame_data.ssao_secondary);
+    //         frame_data
+    //             .ssao_secondary
+    //             .bind_ssao_scene_descriptor(&*self.descriptor_set, &*uniform_buffer);
+    //         ssao.end_secondary(&mut frame_data.ssao_secondary, frame_number);
+    //         ssao.record_primary(&mut frame_data.ssao, &frame_data.ssao_secondary);
+    //         geng.submit(
+    //             &frame_data.gbuffer_semaphore_ssao,
+    //             &frame_data.ssao,
+    //             &frame_data.ssao_semaphore,
+    //         );
+    //     }
+    //     // deferred
+    //     frame_data
+    //         .deferred_secondary
+    //         .begin_secondary(geng.get_current_framebuffer());
+    //     // deferred -> skybox
+    //
+    //     // deferred -> final
+    //     deferred.render(&mut frame_data.deferred_secondary, frame_number);
+    //     frame_data
+    //         .deferred_secondary
+    //         .bind_deferred_scene_descriptor(&*self.descriptor_set, &*uniform_buffer);
+    //     frame_data.deferred_secondary.render_deferred();
+    //     frame_data.deferred_secondary.end();
+    //     frame_data.deferred.begin();
+    //     geng.get_current_framebuffer()
+    //         .begin(&mut frame_data.deferred);
+    //     frame_data.deferred.exe_cmd(&frame_data.deferred_secondary);
+    //     frame_data.deferred.end_render_pass();
+    //     frame_data.deferred.end();
+    //     if ssao.is_some() {
+    //         geng.submit_multiple(
+    //             &[&last_sem, &frame_data.ssao_semaphore],
+    //             &[&frame_data.deferred],
+    //             &[&frame_data.deferred_semaphore],
+    //         );
+    //     } else {
+    //         geng.submit(
+    //             &last_sem,
+    //             &frame_data.deferred,
+    //             &frame_data.deferred_semaphore,
+    //         );
+    //     }
+    //     return frame_data.deferred_semaphore.clone();
+    // }
 }
 
 impl DefaultScene for Base {
     fn default(engine: &Engine) -> Self {
-        let gapi_engine = vx_result!(engine.get_gapi_engine().read());
+        let gapi_engine = &engine.gapi_engine;
         let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
             .create_dynamic_buffer(size_of::<Uniform>() as isize);
-        let render_pass = gapi_engine.get_render_pass().clone();
-        let framebuffers = gapi_engine.get_framebuffers().clone();
-        let unlit_pipeline = vx_result!(gapi_engine.get_pipeline_manager().write()).create(
-            render_pass.clone(),
-            PipelineType::Unlit,
-            engine.get_config(),
-        );
         let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
         let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
-        let frames_count = gapi_engine.get_frames_count();
-        let kernels_count = num_cpus::get();
-        let mut kernels_data = Vec::with_capacity(kernels_count);
-        for _ in 0..kernels_count {
-            kernels_data.push(Arc::new(Mutex::new(BaseKernelData {
-                frames_data: Vec::with_capacity(frames_count),
-                distance_transparent_models: Vec::new(),
-            })));
-        }
         Self {
             obj_base: ObjectBase::new(),
             uniform: Uniform::new(),
             uniform_buffer,
             descriptor_set,
+            gbuffer_frames_data: Vec::new(),
             cameras: BTreeMap::new(),
             active_camera: None,
             models: BTreeMap::new(),
             all_models: BTreeMap::new(),
             lights: BTreeMap::new(),
             shadow_maker_lights: BTreeMap::new(),
-            kernels_data,
-            distance_transparent_models: Vec::new(),
-            frames_data: Vec::new(),
-            skybox: None,
-            render_pass,
-            framebuffers,
-            unlit_pipeline,
         }
     }
 }
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::super::super::super::math::vector::Vec3;
+    use super::super::super::animation::{Playback, Wrap};
+    use super::super::super::lod::DEFAULT_THRESHOLDS;
+    use super::super::Game;
+    use super::*;
+
+    fn new_engine() -> Arc<RwLock<Engine>> {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        let engine = Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+        vx_result!(engine.read()).set_myself(Arc::downgrade(&engine));
+        return engine;
+    }
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    // Data starts with the triangle of the meshes and continues with the
+    // given floats.
+    fn load_scene(engine: &Arc<RwLock<Engine>>, json: &str, tail: &[Real]) -> Arc<RwLock<Game>> {
+        let mut data = Vec::new();
+        for f in &[-1.0 as Real, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        for f in tail {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let scene_manager = vx_result!(engine.read())
+            .get_asset_manager()
+            .get_scene_manager()
+            .clone();
+        let mut scene_manager = vx_result!(scene_manager.write());
+        return scene_manager.load_gltf_document::<Game>(&document, &data, "main");
+    }
+
+    fn find_model(scene: &dyn Scene, name: &str) -> Arc<RwLock<dyn Model>> {
+        for (_, m) in scene.get_all_models() {
+            let m = vx_unwrap!(m.upgrade());
+            if vx_result!(m.read()).get_name() == Some(name.to_string()) {
+                return m;
+            }
+        }
+        vx_unexpected!();
+    }
+
+    fn play(model: &Arc<RwLock<dyn Model>>, clip_name: &str) {
+        let mut model = vx_result!(model.write());
+        let animator = vx_unwrap!(model.get_mut_animator());
+        let clip = vx_unwrap!(animator.find_clip(clip_name)).clone();
+        animator.play(0, Playback::new(clip, Wrap::Once, 1.0));
+    }
+
+    #[test]
+    fn animation_update_test() {
+        let engine = new_engine();
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"name": "main", "nodes": [0, 1]}],
+            "nodes": [
+                {"name": "camera", "camera": 0, "translation": [0.0, 0.0, 5.0]},
+                {"name": "model", "mesh": 0}
+            ],
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "animations": [{"name": "move",
+                "channels": [{"sampler": 0, "target": {"node": 1, "path": "translation"}}],
+                "samplers": [{"input": 2, "output": 3}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"},
+                {"bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0.0], "max": [1.0]},
+                {"bufferView": 3, "componentType": 5126, "count": 2, "type": "VEC3"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12},
+                {"buffer": 0, "byteOffset": 48, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 56, "byteLength": 24}
+            ],
+            "buffers": [{"byteLength": 80}]
+        }"#;
+        let tail = [0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0];
+        let scene = load_scene(&engine, json, &tail);
+        let mut scene = vx_result!(scene.write());
+        let model = find_model(&*scene, "model");
+        let location = || *vx_result!(model.read()).get_transform().get_location();
+        // Without any playing clip the model keeps its place.
+        scene.update(0, 0.5);
+        assert!(is_near(location(), Vec3::zero()));
+        play(&model, "move");
+        scene.update(1, 0.5);
+        assert!(is_near(location(), Vec3::new(0.0, 1.0, 0.0)));
+        scene.update(0, 0.5);
+        assert!(is_near(location(), Vec3::new(0.0, 2.0, 0.0)));
+        // Clip is over and the last pose stays.
+        scene.update(1, 0.5);
+        assert!(is_near(location(), Vec3::new(0.0, 2.0, 0.0)));
+        assert!(!vx_unwrap!(vx_result!(model.read()).get_animator()).is_playing());
+    }
+    #[test]
+    fn morph_update_test() {
+        let engine = new_engine();
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"name": "main", "nodes": [0, 1]}],
+            "nodes": [
+                {"name": "camera", "camera": 0, "translation": [0.0, 0.0, 5.0]},
+                {"name": "model", "mesh": 0}
+            ],
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}],
+            "meshes": [{"weights": [0.0], "primitives": [{
+                "attributes": {"POSITION": 0}, "indices": 1, "targets": [{"POSITION": 4}]}]}],
+            "animations": [{"name": "bulge",
+                "channels": [{"sampler": 0, "target": {"node": 1, "path": "weights"}}],
+                "samplers": [{"input": 2, "output": 3}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"},
+                {"bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0.0], "max": [1.0]},
+                {"bufferView": 3, "componentType": 5126, "count": 2, "type": "SCALAR"},
+                {"bufferView": 4, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0.0, 0.0, 1.0], "max": [0.0, 0.0, 1.0]}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12},
+                {"buffer": 0, "byteOffset": 48, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 56, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 64, "byteLength": 36}
+            ],
+            "buffers": [{"byteLength": 100}]
+        }"#;
+        let tail = [
+            0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+        ];
+        let scene = load_scene(&engine, json, &tail);
+        let mut scene = vx_result!(scene.write());
+        let model = find_model(&*scene, "model");
+        let weights = || vx_result!(model.read()).get_morph_weights().to_vec();
+        assert_eq!(weights(), vec![0.0]);
+        play(&model, "bulge");
+        scene.update(0, 0.25);
+        assert_eq!(weights(), vec![0.25]);
+        scene.update(1, 0.5);
+        assert_eq!(weights(), vec![0.75]);
+        scene.update(0, 0.5);
+        assert_eq!(weights(), vec![1.0]);
+        // Clip is over, so the weights that are set by hand last.
+        vx_result!(model.write()).set_morph_weights(&[0.1]);
+        scene.update(1, 0.5);
+        assert_eq!(weights(), vec![0.1]);
+    }
+    #[test]
+    fn skin_update_test() {
+        let engine = new_engine();
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"name": "main", "nodes": [0, 1, 2]}],
+            "nodes": [
+                {"name": "camera", "camera": 0, "translation": [0.0, 0.0, 5.0]},
+                {"name": "model", "mesh": 0, "skin": 0},
+                {"name": "joint", "translation": [1.0, 0.0, 0.0]}
+            ],
+            "skins": [{"joints": [2]}],
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "animations": [{"name": "lift",
+                "channels": [{"sampler": 0, "target": {"node": 2, "path": "translation"}}],
+                "samplers": [{"input": 2, "output": 3}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"},
+                {"bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0.0], "max": [1.0]},
+                {"bufferView": 3, "componentType": 5126, "count": 2, "type": "VEC3"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12},
+                {"buffer": 0, "byteOffset": 48, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 56, "byteLength": 24}
+            ],
+            "buffers": [{"byteLength": 80}]
+        }"#;
+        let tail = [0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 2.0, 0.0];
+        let scene = load_scene(&engine, json, &tail);
+        let mut scene = vx_result!(scene.write());
+        // Joints do not become models.
+        assert_eq!(scene.get_all_models().len(), 1);
+        let model = find_model(&*scene, "model");
+        let joint_location = || {
+            let model = vx_result!(model.read());
+            let skeleton = vx_unwrap!(model.get_skin()).get_skeleton();
+            return skeleton.get_palette()[0].transform_point(Vec3::zero());
+        };
+        scene.update(0, 0.5);
+        assert!(is_near(joint_location(), Vec3::new(1.0, 0.0, 0.0)));
+        play(&model, "lift");
+        scene.update(1, 0.5);
+        assert!(is_near(joint_location(), Vec3::new(1.0, 1.0, 0.0)));
+        scene.update(0, 0.5);
+        assert!(is_near(joint_location(), Vec3::new(1.0, 2.0, 0.0)));
+    }
+    #[test]
+    fn lod_update_test() {
+        let engine = new_engine();
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"name": "main", "nodes": [0, 1, 2]}],
+            "nodes": [
+                {"name": "camera", "camera": 0, "translation": [0.0, 0.0, 5.0]},
+                {"name": "near", "mesh": 0},
+                {"name": "far", "mesh": 0, "translation": [0.0, 0.0, -80.0]}
+            ],
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12}
+            ],
+            "buffers": [{"byteLength": 48}]
+        }"#;
+        let scene = load_scene(&engine, json, &[]);
+        let mut scene = vx_result!(scene.write());
+        let near = find_model(&*scene, "near");
+        let far = find_model(&*scene, "far");
+        let lod = |m: &Arc<RwLock<dyn Model>>| vx_result!(m.read()).get_lod();
+        scene.update(0, 0.0);
+        assert_eq!(lod(&near), 0);
+        assert_eq!(lod(&far), DEFAULT_THRESHOLDS.len());
+        vx_result!(far.write()).set_location(&Vec3::new(0.0, 0.0, 2.0));
+        scene.update(1, 0.0);
+        assert_eq!(lod(&far), 0);
+        // Culled models keep their lods.
+        vx_result!(near.write()).set_location(&Vec3::new(0.0, 0.0, 50.0));
+        scene.update(0, 0.0);
+        assert_eq!(lod(&near), 0);
+    }
+}
@@ -9,7 +9,7 @@ use super::super::framebuffer::Framebuffer;
 use super::super::g_buffer_filler::GBufferFiller;
 use super::super::gapi::GraphicApiEngine;
 use super::super::light::Light;
-use super::super::model::{Base as ModelBase, Model};
+use super::super::model::{has_mesh, Base as ModelBase, Model};
 use super::super::object::{Base as ObjectBase, Loadable as ObjectLoadable, Object};
 use super::super::pipeline::{Pipeline, PipelineType};
 use super::super::render_pass::RenderPass;
@@ -76,7 +76,7 @@ impl Base {
                 let w = Arc::downgrade(&camera);
                 cameras.insert(id, camera);
                 active_camera = Some(w);
-            } else if let Some(_) = node.mesh() {
+            } else if has_mesh(&node) {
                 let model = ModelBase::new_with_gltf(&node, engine, data);
                 let child_models = model.bring_all_child_models();
                 for (id, model) in child_models {
@@ -275,6 +275,19 @@ impl Scene for Base {
         return &self.active_camera;
     }
 
+    fn set_active_camera(&mut self, id: Id) {
+        let camera = vx_unwrap!(self.cameras.get(&id));
+        self.active_camera = Some(Arc::downgrade(camera));
+    }
+
+    fn get_cameras(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Camera>>> {
+        return &self.cameras;
+    }
+
+    fn bring_all_lights(&self) -> Vec<(Id, Arc<RwLock<dyn Light>>)> {
+        return Vec::new();
+    }
+
     fn update(&mut self, _: usize) {}
 
     fn update_shadow_makers(&self) {}
@@ -287,6 +300,7 @@ impl Scene for Base {
         _: &Shadower,
         kernel_index: usize,
     ) {
+        vx_profile_zone!("Scene::update");
         if !self.is_renderable() {
             return;
         }
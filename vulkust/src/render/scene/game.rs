@@ -1,17 +1,17 @@
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::Object as CoreObject;
-use super::super::super::core::types::Id;
+use super::super::super::core::types::{Id, Real};
 use super::super::camera::Camera;
-use super::super::command::Pool as CmdPool;
-use super::super::deferred::Deferred;
+// use super::super::command::Pool as CmdPool;
+// use super::super::deferred::Deferred;
 use super::super::engine::Engine;
 use super::super::g_buffer_filler::GBufferFiller;
 use super::super::gapi::GraphicApiEngine;
 use super::super::light::Light;
 use super::super::model::Model;
 use super::super::object::Object;
-use super::super::shadower::Shadower;
-use super::super::ssao::SSAO;
+// use super::super::shadower::Shadower;
+// use super::super::ssao::SSAO;
 use super::super::sync::Semaphore;
 use super::base_deferred_shadow::Base;
 use super::{DefaultScene, Loadable, Scene};
@@ -71,36 +71,48 @@ impl Scene for Game {
         return self.base.get_active_camera();
     }
 
-    fn update(&mut self, frame_number: usize) {
-        self.base.update(frame_number);
+    fn set_active_camera(&mut self, id: Id) {
+        self.base.set_active_camera(id);
     }
 
-    fn render_gbuffer_shadow_maps(
-        &self,
-        geng: &GraphicApiEngine,
-        cmd_pool: &Arc<CmdPool>,
-        g_buffer_filler: &GBufferFiller,
-        shadower: &Shadower,
-        kernel_index: usize,
-    ) {
-        self.base.render_gbuffer_shadow_maps(
-            geng,
-            cmd_pool,
-            g_buffer_filler,
-            shadower,
-            kernel_index,
-        );
+    fn get_cameras(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Camera>>> {
+        return self.base.get_cameras();
     }
 
-    fn update_shadow_makers(&self) {
-        self.base.update_shadow_makers();
+    fn bring_all_lights(&self) -> Vec<(Id, Arc<RwLock<dyn Light>>)> {
+        return self.base.bring_all_lights();
     }
 
-    fn render_shadow_maps(&self, shadower: &Shadower, kernel_index: usize, frame_number: usize) {
-        self.base
-            .render_shadow_maps(shadower, kernel_index, frame_number);
+    fn update(&mut self, frame_number: usize, delta_time: Real) {
+        self.base.update(frame_number, delta_time);
     }
 
+    // fn render_gbuffer_shadow_maps(
+    //     &self,
+    //     geng: &GraphicApiEngine,
+    //     cmd_pool: &Arc<CmdPool>,
+    //     g_buffer_filler: &GBufferFiller,
+    //     shadower: &Shadower,
+    //     kernel_index: usize,
+    // ) {
+    //     self.base.render_gbuffer_shadow_maps(
+    //         geng,
+    //         cmd_pool,
+    //         g_buffer_filler,
+    //         shadower,
+    //         kernel_index,
+    //     );
+    // }
+
+    // fn update_shadow_makers(&self) {
+    //     self.base.update_shadow_makers();
+    // }
+
+    // fn render_shadow_maps(&self, shadower: &Shadower, kernel_index: usize, frame_number: usize) {
+    //     self.base
+    //         .render_shadow_maps(shadower, kernel_index, frame_number);
+    // }
+
     fn get_models(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Model>>> {
         return self.base.get_models();
     }
@@ -113,26 +125,35 @@ impl Scene for Game {
         self.base.clean();
     }
 
-    fn submit(
+    fn submit_gbuffer(
         &mut self,
-        geng: &GraphicApiEngine,
-        sem: &Arc<Semaphore>,
-        cmd_pool: &Arc<CmdPool>,
-        g_buffer_filler: &GBufferFiller,
-        shadower: &mut Shadower,
-        deferred: &Deferred,
-        ssao: Option<&SSAO>,
+        gapi_engine: &GraphicApiEngine,
+        semaphore: &Arc<Semaphore>,
+        filler: &GBufferFiller,
     ) -> Arc<Semaphore> {
-        return self.base.submit(
-            geng,
-            sem,
-            cmd_pool,
-            g_buffer_filler,
-            shadower,
-            deferred,
-            ssao,
-        );
-    }
+        return self.base.submit_gbuffer(gapi_engine, semaphore, filler);
+    }
+
+    // fn submit(
+    //     &mut self,
+    //     geng: &GraphicApiEngine,
+    //     sem: &Arc<Semaphore>,
+    //     cmd_pool: &Arc<CmdPool>,
+    //     g_buffer_filler: &GBufferFiller,
+    //     shadower: &mut Shadower,
+    //     deferred: &Deferred,
+    //     ssao: Option<&SSAO>,
+    // ) -> Arc<Semaphore> {
+    //     return self.base.submit(
+    //         geng,
+    //         sem,
+    //         cmd_pool,
+    //         g_buffer_filler,
+    //         shadower,
+    //         deferred,
+    //         ssao,
+    //     );
+    // }
 }
 
 impl Loadable for Game {
@@ -141,10 +162,10 @@ impl Loadable for Game {
         Game { base }
     }
 
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
-        let base = Base::new_with_gx3d(engine, reader, my_id);
-        Game { base }
-    }
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, my_id: Id) -> Self {
+    //     let base = Base::new_with_gx3d(engine, reader, my_id);
+    //     Game { base }
+    // }
 }
 
 impl DefaultScene for Game {
@@ -1,11 +1,14 @@
-use super::super::super::core::gx3d::Table as Gx3dTable;
-use super::super::super::core::types::{Id, TypeId as CoreTypeId};
-use super::super::super::system::file::File;
+// use super::super::super::core::gx3d::Table as Gx3dTable;
+use super::super::super::core::types::Id;
+// use super::super::super::core::types::TypeId as CoreTypeId;
 use super::super::engine::Engine;
-use super::game::Game;
-use super::ui::Ui;
-use super::{DefaultScene, Loadable, Scene, TypeId};
+// use super::game::Game;
+use super::save::{Error as StateError, State};
+// use super::ui::Ui;
+// use super::TypeId;
+use super::{DefaultScene, Loadable, Scene};
 use std::collections::BTreeMap;
+use std::fs::File;
 use std::io::BufReader;
 use std::sync::{Arc, RwLock, Weak};
 
@@ -14,7 +17,7 @@ pub struct Manager {
     engine: Option<Weak<RwLock<Engine>>>,
     scenes: BTreeMap<Id, Weak<RwLock<dyn Scene>>>,
     name_to_id: BTreeMap<String, Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -25,13 +28,14 @@ impl Manager {
             engine: None,
             scenes,
             name_to_id,
-            gx3d_table: None,
+            // gx3d_table: None,
         }
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // Gx3D reader is not ported yet.
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub(crate) fn set_engine(&mut self, engine: Weak<RwLock<Engine>>) {
         self.engine = Some(engine);
@@ -41,45 +45,100 @@ impl Manager {
     where
         S: 'static + Loadable,
     {
+        vx_profile_zone!("scene::load_gltf");
         let file = Self::load_gltf_struct(file_name);
-        let scene = Self::fetch_gltf_scene(&file, scene_name);
+        return self.load_gltf_document(&file, vx_unwrap!(&file.blob), scene_name);
+    }
+
+    // For the documents that are not read from a glb file, like the embedded
+    // or the generated ones, data is their only buffer.
+    pub fn load_gltf_document<S>(
+        &mut self,
+        document: &gltf::Document,
+        data: &[u8],
+        scene_name: &str,
+    ) -> Arc<RwLock<S>>
+    where
+        S: 'static + Loadable,
+    {
+        let scene = Self::fetch_gltf_scene(document, scene_name);
         let scene = {
             let engine = vx_unwrap!(&self.engine);
             let engine = vx_unwrap!(engine.upgrade());
             let engine = vx_result!(engine.read());
-            Arc::new(RwLock::new(S::new_with_gltf(
-                &*engine,
-                &scene,
-                vx_unwrap!(&file.blob),
-            )))
+            Arc::new(RwLock::new(S::new_with_gltf(&*engine, &scene, data)))
         };
+        // Models take the animations of the file that move them.
+        for (_, m) in vx_result!(scene.read()).get_all_models() {
+            if let Some(m) = m.upgrade() {
+                vx_result!(m.write()).load_gltf_animations(document, data);
+            }
+        }
         let s: Arc<RwLock<dyn Scene>> = scene.clone();
         self.add_scene(&s);
         return scene;
     }
 
-    pub fn load_gx3d(&mut self, id: Id) -> Arc<RwLock<dyn Scene>> {
-        let scene: Arc<RwLock<dyn Scene>> = {
-            let table = vx_unwrap!(&mut self.gx3d_table);
-            table.goto(id);
-            let reader = table.get_mut_reader();
-            let type_id = reader.read_type_id();
-            if type_id == TypeId::GAME as CoreTypeId {
-                let engine = vx_unwrap!(&self.engine);
-                let engine = vx_unwrap!(engine.upgrade());
-                let engine = vx_result!(engine.read());
-                Arc::new(RwLock::new(Game::new_with_gx3d(&engine, reader, id)))
-            } else if type_id == TypeId::UI as CoreTypeId {
-                let engine = vx_unwrap!(&self.engine);
-                let engine = vx_unwrap!(engine.upgrade());
-                let engine = vx_result!(engine.read());
-                Arc::new(RwLock::new(Ui::new_with_gx3d(&engine, reader, id)))
-            } else {
-                vx_unexpected!();
-            }
+    // Gx3D reader is not ported yet.
+    // pub fn load_gx3d(&mut self, id: Id) -> Arc<RwLock<dyn Scene>> {
+    //     vx_profile_zone!("scene::load_gx3d");
+    //     let scene: Arc<RwLock<dyn Scene>> = {
+    //         let table = vx_unwrap!(&mut self.gx3d_table);
+    //         table.goto(id);
+    //         let reader = table.get_mut_reader();
+    //         let type_id = reader.read_type_id();
+    //         if type_id == TypeId::GAME as CoreTypeId {
+    //             let engine = vx_unwrap!(&self.engine);
+    //             let engine = vx_unwrap!(engine.upgrade());
+    //             let engine = vx_result!(engine.read());
+    //             Arc::new(RwLock::new(Game::new_with_gx3d(&engine, reader, id)))
+    //         } else if type_id == TypeId::UI as CoreTypeId {
+    //             let engine = vx_unwrap!(&self.engine);
+    //             let engine = vx_unwrap!(engine.upgrade());
+    //             let engine = vx_result!(engine.read());
+    //             Arc::new(RwLock::new(Ui::new_with_gx3d(&engine, reader, id)))
+    //         } else {
+    //             vx_unexpected!();
+    //         }
+    //     };
+    //     self.add_scene(&scene);
+    //     return scene;
+    // }
+
+    // The saved state is applied on the scene if it is still alive, otherwise
+    // the scene is reloaded from gx3d pack. Runtime created scenes can not be
+    // reloaded, so they must be alive. Gx3D reader is not ported yet, so all
+    // of the scenes must be alive for now.
+    pub fn load_state(
+        &mut self,
+        file_name: &str,
+    ) -> Result<(Arc<RwLock<dyn Scene>>, State), StateError> {
+        let state = State::load(file_name)?;
+        let alive = self.scenes.get(&state.scene_id).and_then(|s| s.upgrade());
+        // let in_gx3d = match &self.gx3d_table {
+        //     Some(table) => table.contains(state.scene_id),
+        //     None => false,
+        // };
+        let scene = if let Some(scene) = alive {
+            scene
+        // } else if in_gx3d {
+        //     self.load_gx3d(state.scene_id)
+        } else {
+            return Err(StateError::SceneNotFound(state.scene_id));
         };
-        self.add_scene(&scene);
-        return scene;
+        state.apply(&mut *vx_result!(scene.write()));
+        return Ok((scene, state));
+    }
+
+    pub fn save_state(
+        &self,
+        scene: &dyn Scene,
+        custom_data: &[u8],
+        file_name: &str,
+    ) -> Result<(), StateError> {
+        let mut state = State::new_with_scene(scene);
+        state.custom_data = custom_data.to_vec();
+        return state.save(file_name);
     }
 
     pub fn create<S>(&mut self) -> Arc<RwLock<S>>
@@ -97,8 +156,8 @@ impl Manager {
         scene
     }
 
-    pub fn fetch_gltf_scene<'a>(file: &'a gltf::Gltf, scene_name: &str) -> gltf::Scene<'a> {
-        let scenes = file.scenes();
+    pub fn fetch_gltf_scene<'a>(document: &'a gltf::Document, scene_name: &str) -> gltf::Scene<'a> {
+        let scenes = document.scenes();
         for scene in scenes {
             if vx_unwrap!(scene.name()) == scene_name {
                 return scene;
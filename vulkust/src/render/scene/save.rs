@@ -0,0 +1,337 @@
+use super::super::object::Object;
+use super::super::transform::Transform;
+use super::Scene;
+
+pub use super::super::scene_state::{
+    CameraState, Error, LightParameters, LightState, ModelState, State, VERSION,
+};
+
+// The format of the state is in scene_state module, here it is only captured
+// from and applied on a scene.
+
+impl State {
+    pub fn new_with_scene(scene: &dyn Scene) -> Self {
+        let active_camera = if let Some(c) = scene.get_active_camera() {
+            if let Some(c) = c.upgrade() {
+                Some(vx_result!(c.read()).get_id())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        let mut cameras = Vec::new();
+        for (id, c) in scene.get_cameras() {
+            let c = vx_result!(c.read());
+            cameras.push(CameraState {
+                id: *id,
+                location: c.get_location(),
+                orientation: c.get_uniform().get_orientation(),
+                is_renderable: c.is_renderable(),
+            });
+        }
+        let mut lights = Vec::new();
+        for (id, l) in scene.bring_all_lights() {
+            let l = vx_result!(l.read());
+            let (location, orientation, radius, cone_angles) = if let Some(d) = l.to_directional() {
+                (None, Some(*d.get_orientation()), None, None)
+            } else if let Some(p) = l.to_point() {
+                (Some(l.get_location()), None, Some(p.get_radius()), None)
+            } else if let Some(s) = l.to_spot() {
+                (
+                    Some(l.get_location()),
+                    Some(*s.get_orientation()),
+                    Some(s.get_radius()),
+                    Some(s.get_cone_angles()),
+                )
+            } else {
+                (None, None, None, None)
+            };
+            lights.push(LightState {
+                id,
+                location,
+                orientation,
+                parameters: Some(LightParameters {
+                    color: *l.get_color(),
+                    strength: l.get_strength(),
+                    radius,
+                    cone_angles,
+                }),
+                is_renderable: l.is_renderable(),
+            });
+        }
+        let mut models = Vec::new();
+        for (id, m) in scene.get_all_models() {
+            let m = if let Some(m) = m.upgrade() {
+                m
+            } else {
+                continue;
+            };
+            let m = vx_result!(m.read());
+            let t = m.get_transform();
+            models.push(ModelState {
+                id: *id,
+                location: *t.get_location(),
+                orientation: *t.get_orientation(),
+                scale: *t.get_scale(),
+                is_renderable: m.is_renderable(),
+            });
+        }
+        let mut state = Self::new(scene.get_id());
+        state.active_camera = active_camera;
+        state.cameras = cameras;
+        state.lights = lights;
+        state.models = models;
+        return state;
+    }
+
+    pub fn apply(&self, scene: &mut dyn Scene) {
+        for c in &self.cameras {
+            let camera = if let Some(camera) = scene.get_cameras().get(&c.id) {
+                camera.clone()
+            } else {
+                vx_log_e!("Camera with id {} not found in the scene.", c.id);
+                continue;
+            };
+            let mut camera = vx_result!(camera.write());
+            camera.set_orientation(&c.orientation);
+            camera.set_location(&c.location);
+            set_renderable(&mut *camera, c.is_renderable);
+        }
+        if let Some(id) = self.active_camera {
+            if scene.get_cameras().contains_key(&id) {
+                scene.set_active_camera(id);
+            }
+        }
+        let lights = scene.bring_all_lights();
+        for l in &self.lights {
+            let light = lights.iter().find(|(id, _)| *id == l.id);
+            let light = if let Some((_, light)) = light {
+                light
+            } else {
+                vx_log_e!("Light with id {} not found in the scene.", l.id);
+                continue;
+            };
+            let mut light = vx_result!(light.write());
+            if let Some(location) = &l.location {
+                light.set_location(location);
+            }
+            if let Some(orientation) = &l.orientation {
+                light.set_orientation(orientation);
+            }
+            if let Some(p) = &l.parameters {
+                light.set_color(&p.color);
+                // It resets the radius, so it comes first.
+                light.set_strength(p.strength);
+                if let Some(radius) = p.radius {
+                    if let Some(point) = light.to_mut_point() {
+                        point.set_radius(radius);
+                    } else if let Some(spot) = light.to_mut_spot() {
+                        spot.set_radius(radius);
+                    }
+                }
+                if let Some((inner, outer)) = p.cone_angles {
+                    if let Some(spot) = light.to_mut_spot() {
+                        spot.set_cone_angles(inner, outer);
+                    }
+                }
+            }
+            set_renderable(&mut *light, l.is_renderable);
+        }
+        for m in &self.models {
+            let model = scene.get_all_models().get(&m.id).and_then(|m| m.upgrade());
+            let model = if let Some(model) = model {
+                model
+            } else {
+                vx_log_e!("Model with id {} not found in the scene.", m.id);
+                continue;
+            };
+            let mut model = vx_result!(model.write());
+            model.set_transform(&Transform::new_with_trs(m.location, m.orientation, m.scale));
+            set_renderable(&mut *model, m.is_renderable);
+        }
+    }
+}
+
+fn set_renderable<O: Object + ?Sized>(o: &mut O, b: bool) {
+    if b {
+        o.enable_rendering();
+    } else {
+        o.disable_rendering();
+    }
+}
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::super::super::super::core::types::Real;
+    use super::super::super::super::math::quaternion::Quat;
+    use super::super::super::super::math::vector::Vec3;
+    use super::super::super::engine::Engine;
+    use super::super::super::light::Light;
+    use super::super::Game;
+    use super::*;
+    use std::sync::{Arc, RwLock};
+
+    fn new_engine() -> Arc<RwLock<Engine>> {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        let engine = Arc::new(RwLock::new(Engine::new_with_gapi_engine(gapi_engine)));
+        vx_result!(engine.read()).set_myself(Arc::downgrade(&engine));
+        return engine;
+    }
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    fn find_light(scene: &dyn Scene, name: &str) -> Arc<RwLock<dyn Light>> {
+        for (_, l) in scene.bring_all_lights() {
+            if vx_result!(l.read()).get_name() == Some(name.to_string()) {
+                return l;
+            }
+        }
+        vx_unexpected!();
+    }
+
+    // It moves everything of the scene, so the state that is saved before
+    // the moves can be recognized after its loading.
+    fn change_all(scene: &dyn Scene, v: Real) {
+        for (_, c) in scene.get_cameras() {
+            let mut c = vx_result!(c.write());
+            c.set_location(&Vec3::new(v, 0.0, 0.0));
+            c.rotate_global_z(v);
+        }
+        for (_, m) in scene.get_all_models() {
+            let m = vx_unwrap!(m.upgrade());
+            vx_result!(m.write()).set_location(&Vec3::new(0.0, v, 0.0));
+        }
+        for (_, l) in scene.bring_all_lights() {
+            let mut l = vx_result!(l.write());
+            l.set_color(&Vec3::new(v, v, v));
+            l.set_strength(v);
+            if let Some(p) = l.to_mut_point() {
+                p.set_radius(v * 2.0);
+            }
+            if let Some(s) = l.to_mut_spot() {
+                s.set_radius(v * 2.0);
+                s.set_cone_angles(v * 0.1, v * 0.2);
+            }
+            if l.to_directional().is_none() {
+                l.set_location(&Vec3::new(0.0, 0.0, v));
+            }
+            if l.to_point().is_none() {
+                l.set_orientation(&Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), v));
+            }
+        }
+    }
+
+    #[test]
+    fn scene_round_trip_test() {
+        let engine = new_engine();
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {"KHR_lights_punctual": {"lights": [
+                {"type": "directional"},
+                {"type": "point"},
+                {"type": "spot", "spot": {"innerConeAngle": 0.25, "outerConeAngle": 0.5}}
+            ]}},
+            "scene": 0,
+            "scenes": [{"name": "main", "nodes": [0, 1, 2, 3, 4]}],
+            "nodes": [
+                {"name": "camera", "camera": 0, "translation": [0.0, 0.0, 5.0]},
+                {"name": "model", "mesh": 0},
+                {"name": "sun", "extensions": {"KHR_lights_punctual": {"light": 0}}},
+                {"name": "lamp", "extensions": {"KHR_lights_punctual": {"light": 1}}},
+                {"name": "spot", "extensions": {"KHR_lights_punctual": {"light": 2}}}
+            ],
+            "cameras": [{"type": "perspective",
+                "perspective": {"yfov": 1.0, "znear": 0.1, "zfar": 100.0, "aspectRatio": 1.0}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0]},
+                {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12}
+            ],
+            "buffers": [{"byteLength": 48}]
+        }"#;
+        let mut data = Vec::new();
+        for f in &[-1.0 as Real, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f.to_le_bytes());
+        }
+        for i in &[0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let scene_manager = vx_result!(engine.read())
+            .get_asset_manager()
+            .get_scene_manager()
+            .clone();
+        let mut scene_manager = vx_result!(scene_manager.write());
+        let scene = scene_manager.load_gltf_document::<Game>(&document, &data, "main");
+        let file_name = std::env::temp_dir().join(format!(
+            "vulkust-scene-state-test-{}.vxss",
+            std::process::id()
+        ));
+        let file_name = vx_unwrap!(file_name.to_str()).to_string();
+        {
+            let scene = vx_result!(scene.read());
+            assert_eq!(scene.get_cameras().len(), 1);
+            assert_eq!(scene.get_all_models().len(), 1);
+            assert_eq!(scene.bring_all_lights().len(), 3);
+            change_all(&*scene, 0.5);
+            vx_result!(find_light(&*scene, "lamp").write()).disable_rendering();
+            vx_result!(scene_manager.save_state(&*scene, &[7, 8], &file_name));
+            change_all(&*scene, 2.0);
+            vx_result!(find_light(&*scene, "lamp").write()).enable_rendering();
+        }
+        let (loaded, state) = vx_result!(scene_manager.load_state(&file_name));
+        vx_result!(std::fs::remove_file(&file_name));
+        assert_eq!(state.custom_data, vec![7, 8]);
+        assert_eq!(state.lights.len(), 3);
+        let scene = vx_result!(loaded.read());
+        for (_, c) in scene.get_cameras() {
+            let c = vx_result!(c.read());
+            assert!(is_near(c.get_location(), Vec3::new(0.5, 0.0, 0.0)));
+            let x = c
+                .get_uniform()
+                .get_orientation()
+                .rotate(Vec3::new(1.0, 0.0, 0.0));
+            assert!(is_near(x, Vec3::new(0.5f32.cos(), 0.5f32.sin(), 0.0)));
+        }
+        for (_, m) in scene.get_all_models() {
+            let m = vx_unwrap!(m.upgrade());
+            let location = *vx_result!(m.read()).get_transform().get_location();
+            assert!(is_near(location, Vec3::new(0.0, 0.5, 0.0)));
+        }
+        let orientation = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), 0.5);
+        let sun = find_light(&*scene, "sun");
+        let sun = vx_result!(sun.read());
+        assert!(sun.is_renderable());
+        assert!(*sun.get_color() == Vec3::new(0.5, 0.5, 0.5));
+        assert_eq!(sun.get_strength(), 0.5);
+        assert!(*vx_unwrap!(sun.to_directional()).get_orientation() == orientation);
+        let lamp = find_light(&*scene, "lamp");
+        let lamp = vx_result!(lamp.read());
+        assert!(!lamp.is_renderable());
+        assert!(is_near(lamp.get_location(), Vec3::new(0.0, 0.0, 0.5)));
+        assert_eq!(vx_unwrap!(lamp.to_point()).get_radius(), 1.0);
+        let spot = find_light(&*scene, "spot");
+        let spot = vx_result!(spot.read());
+        assert!(is_near(spot.get_location(), Vec3::new(0.0, 0.0, 0.5)));
+        let s = vx_unwrap!(spot.to_spot());
+        assert_eq!(s.get_radius(), 1.0);
+        assert_eq!(s.get_cone_angles(), (0.05, 0.1));
+        assert!(*s.get_orientation() == orientation);
+    }
+}
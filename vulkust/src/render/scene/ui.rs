@@ -71,6 +71,18 @@ impl Scene for Ui {
         return self.base.get_active_camera();
     }
 
+    fn set_active_camera(&mut self, id: Id) {
+        self.base.set_active_camera(id);
+    }
+
+    fn get_cameras(&self) -> &BTreeMap<Id, Arc<RwLock<dyn Camera>>> {
+        return self.base.get_cameras();
+    }
+
+    fn bring_all_lights(&self) -> Vec<(Id, Arc<RwLock<dyn Light>>)> {
+        return self.base.bring_all_lights();
+    }
+
     fn update(&mut self, frame_number: usize) {
         self.base.update(frame_number);
     }
@@ -0,0 +1,529 @@
+use super::super::core::types::{Id, Real};
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+
+// Saved state only refers to the assets with their ids, the scene itself is
+// reloaded or reused and then the runtime overrides are applied on it, see
+// the scene::save module. Objects that are not in the scene are skipped
+// during applying.
+
+const SIGNATURE: [u8; 4] = *b"VXSS";
+// Version 2 adds the orientation and the parameters of the lights, the first
+// version is still read without them.
+pub const VERSION: u32 = 2;
+
+// Smallest encoded size of each entry in all versions, the counts of a file
+// are bounded by its remaining size with them.
+const CAMERA_SIZE: usize = 8 + 12 + 16 + 1;
+const LIGHT_MIN_SIZE: usize = 8 + 1 + 1;
+const MODEL_SIZE: usize = 8 + 12 + 16 + 12 + 1;
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Error {
+    Io(std::io::Error),
+    Signature,
+    Version(u32),
+    Truncated,
+    SceneNotFound(Id),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Error::Io(e) => write!(f, "scene state I/O error: {}", e),
+            Error::Signature => write!(f, "unknown scene state signature"),
+            Error::Version(v) => write!(f, "unsupported scene state version {}", v),
+            Error::Truncated => write!(f, "scene state is truncated"),
+            Error::SceneNotFound(id) => write!(f, "scene with id {} not found", id),
+        };
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        return Error::Io(e);
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct ModelState {
+    pub id: Id,
+    pub location: Vec3,
+    pub orientation: Quat,
+    pub scale: Vec3,
+    pub is_renderable: bool,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct CameraState {
+    pub id: Id,
+    pub location: Vec3,
+    pub orientation: Quat,
+    pub is_renderable: bool,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct LightParameters {
+    pub color: Vec3,
+    pub strength: Real,
+    // Directional lights do not have radius.
+    pub radius: Option<Real>,
+    // Inner and outer angles of the spot lights.
+    pub cone_angles: Option<(Real, Real)>,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct LightState {
+    pub id: Id,
+    // Directional lights do not have location.
+    pub location: Option<Vec3>,
+    // Point lights do not have orientation.
+    pub orientation: Option<Quat>,
+    // States of the first version do not have the parameters.
+    pub parameters: Option<LightParameters>,
+    pub is_renderable: bool,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct State {
+    pub scene_id: Id,
+    pub active_camera: Option<Id>,
+    pub cameras: Vec<CameraState>,
+    pub lights: Vec<LightState>,
+    pub models: Vec<ModelState>,
+    pub custom_data: Vec<u8>,
+}
+
+impl State {
+    pub fn new(scene_id: Id) -> Self {
+        Self {
+            scene_id,
+            active_camera: None,
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            models: Vec::new(),
+            custom_data: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, file_name: &str) -> Result<(), Error> {
+        let mut file = BufWriter::new(File::create(file_name)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        return Ok(());
+    }
+
+    pub fn load(file_name: &str) -> Result<Self, Error> {
+        let mut file = File::open(file_name)?;
+        return Self::read(&mut file);
+    }
+
+    // All numbers are little endian.
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), Error> {
+        w.write_all(&SIGNATURE)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&self.scene_id.to_le_bytes())?;
+        write_bool(w, self.active_camera.is_some())?;
+        w.write_all(&self.active_camera.unwrap_or(0).to_le_bytes())?;
+        w.write_all(&(self.cameras.len() as u64).to_le_bytes())?;
+        for c in &self.cameras {
+            w.write_all(&c.id.to_le_bytes())?;
+            write_vec3(w, &c.location)?;
+            write_quat(w, &c.orientation)?;
+            write_bool(w, c.is_renderable)?;
+        }
+        w.write_all(&(self.lights.len() as u64).to_le_bytes())?;
+        for l in &self.lights {
+            w.write_all(&l.id.to_le_bytes())?;
+            write_bool(w, l.location.is_some())?;
+            if let Some(location) = &l.location {
+                write_vec3(w, location)?;
+            }
+            write_bool(w, l.orientation.is_some())?;
+            if let Some(orientation) = &l.orientation {
+                write_quat(w, orientation)?;
+            }
+            write_bool(w, l.parameters.is_some())?;
+            if let Some(p) = &l.parameters {
+                write_vec3(w, &p.color)?;
+                w.write_all(&p.strength.to_le_bytes())?;
+                write_bool(w, p.radius.is_some())?;
+                if let Some(radius) = p.radius {
+                    w.write_all(&radius.to_le_bytes())?;
+                }
+                write_bool(w, p.cone_angles.is_some())?;
+                if let Some((inner, outer)) = p.cone_angles {
+                    w.write_all(&inner.to_le_bytes())?;
+                    w.write_all(&outer.to_le_bytes())?;
+                }
+            }
+            write_bool(w, l.is_renderable)?;
+        }
+        w.write_all(&(self.models.len() as u64).to_le_bytes())?;
+        for m in &self.models {
+            w.write_all(&m.id.to_le_bytes())?;
+            write_vec3(w, &m.location)?;
+            write_quat(w, &m.orientation)?;
+            write_vec3(w, &m.scale)?;
+            write_bool(w, m.is_renderable)?;
+        }
+        w.write_all(&(self.custom_data.len() as u64).to_le_bytes())?;
+        w.write_all(&self.custom_data)?;
+        return Ok(());
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> Result<Self, Error> {
+        let mut data = Vec::new();
+        r.read_to_end(&mut data)?;
+        return Self::read_bytes(&data);
+    }
+
+    pub fn read_bytes(data: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader { data };
+        if r.take(4)? != SIGNATURE {
+            return Err(Error::Signature);
+        }
+        let version = r.read_u32()?;
+        if version == 0 || version > VERSION {
+            return Err(Error::Version(version));
+        }
+        let scene_id = r.read_u64()?;
+        let has_active_camera = r.read_bool()?;
+        let active_camera = r.read_u64()?;
+        let active_camera = if has_active_camera {
+            Some(active_camera)
+        } else {
+            None
+        };
+        let count = r.read_count(CAMERA_SIZE)?;
+        let mut cameras = Vec::with_capacity(count);
+        for _ in 0..count {
+            cameras.push(CameraState {
+                id: r.read_u64()?,
+                location: r.read_vec3()?,
+                orientation: r.read_quat()?,
+                is_renderable: r.read_bool()?,
+            });
+        }
+        let count = r.read_count(LIGHT_MIN_SIZE)?;
+        let mut lights = Vec::with_capacity(count);
+        for _ in 0..count {
+            let id = r.read_u64()?;
+            let location = if r.read_bool()? {
+                Some(r.read_vec3()?)
+            } else {
+                None
+            };
+            let (orientation, parameters) = if version > 1 {
+                (r.read_light_orientation()?, r.read_light_parameters()?)
+            } else {
+                (None, None)
+            };
+            lights.push(LightState {
+                id,
+                location,
+                orientation,
+                parameters,
+                is_renderable: r.read_bool()?,
+            });
+        }
+        let count = r.read_count(MODEL_SIZE)?;
+        let mut models = Vec::with_capacity(count);
+        for _ in 0..count {
+            models.push(ModelState {
+                id: r.read_u64()?,
+                location: r.read_vec3()?,
+                orientation: r.read_quat()?,
+                scale: r.read_vec3()?,
+                is_renderable: r.read_bool()?,
+            });
+        }
+        let count = r.read_count(1)?;
+        let custom_data = r.take(count)?.to_vec();
+        return Ok(Self {
+            scene_id,
+            active_camera,
+            cameras,
+            lights,
+            models,
+            custom_data,
+        });
+    }
+}
+
+fn write_bool<W: Write>(w: &mut W, b: bool) -> Result<(), Error> {
+    w.write_all(&[b as u8])?;
+    return Ok(());
+}
+
+fn write_vec3<W: Write>(w: &mut W, v: &Vec3) -> Result<(), Error> {
+    for c in &v.to_array() {
+        w.write_all(&c.to_le_bytes())?;
+    }
+    return Ok(());
+}
+
+// The scalar part is written first.
+fn write_quat<W: Write>(w: &mut W, q: &Quat) -> Result<(), Error> {
+    w.write_all(&q.get_scalar().to_le_bytes())?;
+    return write_vec3(w, &q.get_vector());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if count > self.data.len() {
+            return Err(Error::Truncated);
+        }
+        let (result, rest) = self.data.split_at(count);
+        self.data = rest;
+        return Ok(result);
+    }
+
+    fn read_count(&mut self, item_size: usize) -> Result<usize, Error> {
+        let count = self.read_u64()?;
+        if count > (self.data.len() / item_size) as u64 {
+            return Err(Error::Truncated);
+        }
+        return Ok(count as usize);
+    }
+
+    fn read_bool(&mut self) -> Result<bool, Error> {
+        return Ok(self.take(1)?[0] != 0);
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.take(4)?);
+        return Ok(u32::from_le_bytes(b));
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(self.take(8)?);
+        return Ok(u64::from_le_bytes(b));
+    }
+
+    fn read_real(&mut self) -> Result<Real, Error> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.take(4)?);
+        return Ok(Real::from_le_bytes(b));
+    }
+
+    fn read_vec3(&mut self) -> Result<Vec3, Error> {
+        let x = self.read_real()?;
+        let y = self.read_real()?;
+        let z = self.read_real()?;
+        return Ok(Vec3::new(x, y, z));
+    }
+
+    fn read_quat(&mut self) -> Result<Quat, Error> {
+        let s = self.read_real()?;
+        let v = self.read_vec3()?;
+        return Ok(Quat::new(v.x(), v.y(), v.z(), s));
+    }
+
+    fn read_light_orientation(&mut self) -> Result<Option<Quat>, Error> {
+        if !self.read_bool()? {
+            return Ok(None);
+        }
+        return Ok(Some(self.read_quat()?));
+    }
+
+    fn read_light_parameters(&mut self) -> Result<Option<LightParameters>, Error> {
+        if !self.read_bool()? {
+            return Ok(None);
+        }
+        let color = self.read_vec3()?;
+        let strength = self.read_real()?;
+        let radius = if self.read_bool()? {
+            Some(self.read_real()?)
+        } else {
+            None
+        };
+        let cone_angles = if self.read_bool()? {
+            let inner = self.read_real()?;
+            Some((inner, self.read_real()?))
+        } else {
+            None
+        };
+        return Ok(Some(LightParameters {
+            color,
+            strength,
+            radius,
+            cone_angles,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> State {
+        let mut state = State::new(7);
+        state.active_camera = Some(3);
+        state.cameras.push(CameraState {
+            id: 3,
+            location: Vec3::new(1.0, 2.0, 3.0),
+            orientation: Quat::new(0.5, 0.5, 0.5, 0.5),
+            is_renderable: true,
+        });
+        state.lights.push(LightState {
+            id: 4,
+            location: None,
+            orientation: Some(Quat::new(0.0, 1.0, 0.0, 0.0)),
+            parameters: Some(LightParameters {
+                color: Vec3::new(1.0, 0.9, 0.8),
+                strength: 3.0,
+                radius: None,
+                cone_angles: None,
+            }),
+            is_renderable: true,
+        });
+        state.lights.push(LightState {
+            id: 5,
+            location: Some(Vec3::new(-1.0, 0.0, 4.0)),
+            orientation: None,
+            parameters: Some(LightParameters {
+                color: Vec3::new(0.0, 0.0, 1.0),
+                strength: 10.0,
+                radius: Some(20.0),
+                cone_angles: None,
+            }),
+            is_renderable: false,
+        });
+        state.lights.push(LightState {
+            id: 8,
+            location: Some(Vec3::new(0.0, 5.0, 0.0)),
+            orientation: Some(Quat::new(0.5, 0.5, 0.5, 0.5)),
+            parameters: Some(LightParameters {
+                color: Vec3::new(1.0, 1.0, 1.0),
+                strength: 2.0,
+                radius: Some(6.0),
+                cone_angles: Some((0.1, 0.5)),
+            }),
+            is_renderable: true,
+        });
+        state.lights.push(LightState {
+            id: 9,
+            location: None,
+            orientation: None,
+            parameters: None,
+            is_renderable: true,
+        });
+        state.models.push(ModelState {
+            id: 6,
+            location: Vec3::new(0.0, 1.0, 0.0),
+            orientation: Quat::identity(),
+            scale: Vec3::new(2.0, 2.0, 2.0),
+            is_renderable: false,
+        });
+        state.custom_data = vec![1, 2, 3, 4, 5];
+        return state;
+    }
+
+    fn encode(state: &State) -> Vec<u8> {
+        let mut data = Vec::new();
+        if state.write(&mut data).is_err() {
+            panic!("Writing into memory must not fail.");
+        }
+        return data;
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let state = sample();
+        let data = encode(&state);
+        match State::read(&mut &data[..]) {
+            Ok(s) => assert!(s == state),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn truncated_test() {
+        let data = encode(&sample());
+        for size in 0..data.len() {
+            match State::read_bytes(&data[..size]) {
+                Err(Error::Truncated) => (),
+                Err(e) => panic!("Unexpected error {} for size {}", e, size),
+                Ok(_) => panic!("Truncated state with size {} is read.", size),
+            }
+        }
+    }
+
+    #[test]
+    fn huge_count_test() {
+        let mut data = encode(&State::new(1));
+        // cameras count is after signature, version, scene id and active camera
+        let offset = 4 + 4 + 8 + 1 + 8;
+        data[offset..offset + 8].copy_from_slice(&u64::max_value().to_le_bytes());
+        match State::read_bytes(&data) {
+            Err(Error::Truncated) => (),
+            _ => panic!("Huge count must be rejected."),
+        }
+    }
+
+    #[test]
+    fn first_version_test() {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"VXSS");
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        // no camera, one light and no model
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.push(1);
+        for c in &[-1.0 as Real, 0.0, 4.0] {
+            data.extend_from_slice(&c.to_le_bytes());
+        }
+        data.push(0);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        let state = match State::read_bytes(&data) {
+            Ok(s) => s,
+            Err(e) => panic!("{}", e),
+        };
+        assert_eq!(state.scene_id, 7);
+        assert!(
+            state.lights
+                == vec![LightState {
+                    id: 5,
+                    location: Some(Vec3::new(-1.0, 0.0, 4.0)),
+                    orientation: None,
+                    parameters: None,
+                    is_renderable: false,
+                }]
+        );
+    }
+
+    #[test]
+    fn bad_version_test() {
+        let mut data = encode(&sample());
+        data[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        match State::read_bytes(&data) {
+            Err(Error::Version(v)) => assert_eq!(v, VERSION + 1),
+            _ => panic!("Unsupported version must be rejected."),
+        }
+        data[0] = b'X';
+        match State::read_bytes(&data) {
+            Err(Error::Signature) => (),
+            _ => panic!("Bad signature must be rejected."),
+        }
+    }
+}
@@ -27,6 +27,7 @@ pub struct Shadower {
     shadow_map_render_pass: Arc<RenderPass>,
     shadow_map_framebuffers: Vec<Arc<Framebuffer>>,
     shadow_map_pipeline: Arc<Pipeline>,
+    skinned_shadow_map_pipeline: Arc<Pipeline>,
     shadow_map_descriptor_set: Arc<DescriptorSet>,
     //---------------------------------------
     shadow_accumulator_flagbits_buffer: Arc<ImageView>,
@@ -131,7 +132,11 @@ impl Shadower {
                 ),
             )
         };
-        let (shadow_map_pipeline, shadow_accumulator_directional_pipeline) = {
+        let (
+            shadow_map_pipeline,
+            skinned_shadow_map_pipeline,
+            shadow_accumulator_directional_pipeline,
+        ) = {
             let mut pipmgr = vx_result!(geng.get_pipeline_manager().write());
             (
                 pipmgr.create(
@@ -139,6 +144,11 @@ impl Shadower {
                     PipelineType::ShadowMapper,
                     conf,
                 ),
+                pipmgr.create(
+                    shadow_map_render_pass.clone(),
+                    PipelineType::SkinnedShadowMapper,
+                    conf,
+                ),
                 pipmgr.create(
                     shadow_accumulator_render_pass.clone(),
                     PipelineType::ShadowAccumulatorDirectional,
@@ -151,6 +161,7 @@ impl Shadower {
             shadow_map_render_pass,
             shadow_map_framebuffers,
             shadow_map_pipeline,
+            skinned_shadow_map_pipeline,
             shadow_map_descriptor_set,
             //---------------------------------------
             shadow_accumulator_flagbits_buffer,
@@ -172,6 +183,12 @@ impl Shadower {
         }
     }
 
+    pub(super) fn bind_skinned_shadow_mappers(&self, cmds: &mut [CmdBuffer]) {
+        for cmd in cmds {
+            cmd.bind_pipeline(&self.skinned_shadow_map_pipeline);
+        }
+    }
+
     pub(super) fn begin_shadow_map_primary(&self, cmd: &mut CmdBuffer, map_index: usize) {
         self.shadow_map_framebuffers[map_index].begin(cmd);
     }
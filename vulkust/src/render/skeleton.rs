@@ -0,0 +1,304 @@
+use super::super::core::types::Real;
+use super::super::math::matrix::Mat4;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+
+/// It must be equal to MAX_JOINTS_COUNT of the shaders.
+pub const MAX_JOINTS_COUNT: usize = 128;
+
+/// Number of floats of the joints and the weights of a vertex, they come
+/// after the other attributes in the vertex buffer of the skinned meshes.
+pub const SKIN_FLOATS_COUNT: usize = 8;
+
+/// The four most influential joints of a vertex.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct VertexSkin {
+    pub joints: [u16; 4],
+    pub weights: [Real; 4],
+}
+
+impl VertexSkin {
+    /// Weights are normalized, exporters do not always do it.
+    pub fn new(joints: [u16; 4], weights: [Real; 4]) -> Self {
+        let sum: Real = weights.iter().sum();
+        let weights = if sum > 0.0 {
+            [
+                weights[0] / sum,
+                weights[1] / sum,
+                weights[2] / sum,
+                weights[3] / sum,
+            ]
+        } else {
+            [1.0, 0.0, 0.0, 0.0]
+        };
+        Self { joints, weights }
+    }
+}
+
+/// Appends the skins to the vertices of the vertex buffer data, joints are
+/// stored as floats like the other attributes.
+pub fn interleave_skins(
+    data: &[Real],
+    vertex_floats_count: usize,
+    skins: &[VertexSkin],
+) -> Vec<Real> {
+    let mut result = Vec::with_capacity(skins.len() * (vertex_floats_count + SKIN_FLOATS_COUNT));
+    for (v, s) in data.chunks_exact(vertex_floats_count).zip(skins) {
+        result.extend_from_slice(v);
+        for j in &s.joints {
+            result.push(*j as Real);
+        }
+        result.extend_from_slice(&s.weights);
+    }
+    return result;
+}
+
+/// Rest state of a joint, its transformation is relative to its parent.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Joint {
+    pub name: Option<String>,
+    pub parent: Option<usize>,
+    pub location: Vec3,
+    pub orientation: Quat,
+    pub scale: Vec3,
+    /// Brings the mesh from its space to the space of the joint in bind pose.
+    pub inverse_bind: Mat4,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Pose {
+    location: Vec3,
+    orientation: Quat,
+    scale: Vec3,
+}
+
+impl Pose {
+    fn new(joint: &Joint) -> Self {
+        Self {
+            location: joint.location,
+            orientation: joint.orientation,
+            scale: joint.scale,
+        }
+    }
+
+    fn get_matrix(&self) -> Mat4 {
+        return Mat4::from_scale_rotation_translation(self.scale, self.orientation, self.location);
+    }
+}
+
+/// Joint hierarchy of a skin and its current pose.
+///
+/// The palette is the matrices that move the vertices from the bind pose to
+/// the current pose in the space of the mesh, the skinned vertex is the
+/// weighted sum of the vertex transformed by the palette of its joints. The
+/// roots are relative to the bind shape space, that is the parent space of
+/// the joint roots seen from the mesh.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    // Parents come before their children.
+    order: Vec<usize>,
+    bind_shape: Mat4,
+    poses: Vec<Pose>,
+    world_matrices: Vec<Mat4>,
+    palette: Vec<Mat4>,
+    is_dirty: bool,
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>, bind_shape: Mat4) -> Self {
+        if joints.len() > MAX_JOINTS_COUNT {
+            vx_log_f!(
+                "Skeleton has {} joints, at most {} are supported.",
+                joints.len(),
+                MAX_JOINTS_COUNT
+            );
+        }
+        let mut order = Vec::with_capacity(joints.len());
+        let mut is_ordered = vec![false; joints.len()];
+        while order.len() < joints.len() {
+            let count = order.len();
+            for (i, j) in joints.iter().enumerate() {
+                if is_ordered[i] {
+                    continue;
+                }
+                if let Some(p) = j.parent {
+                    if !is_ordered[p] {
+                        continue;
+                    }
+                }
+                is_ordered[i] = true;
+                order.push(i);
+            }
+            if count == order.len() {
+                vx_log_f!("Joints of the skeleton have a cycle.");
+            }
+        }
+        let poses = joints.iter().map(Pose::new).collect();
+        let identity = Mat4::identity();
+        let joints_count = joints.len();
+        let mut myself = Self {
+            joints,
+            order,
+            bind_shape,
+            poses,
+            world_matrices: vec![identity; joints_count],
+            palette: vec![identity; joints_count],
+            is_dirty: true,
+        };
+        myself.update();
+        return myself;
+    }
+
+    #[inline]
+    pub fn get_joints(&self) -> &[Joint] {
+        return &self.joints;
+    }
+
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        return self
+            .joints
+            .iter()
+            .position(|j| j.name.as_ref().map(|n| n.as_str()) == Some(name));
+    }
+
+    pub fn set_joint_location(&mut self, joint: usize, location: &Vec3) {
+        self.poses[joint].location = *location;
+        self.is_dirty = true;
+    }
+
+    pub fn set_joint_orientation(&mut self, joint: usize, orientation: &Quat) {
+        self.poses[joint].orientation = *orientation;
+        self.is_dirty = true;
+    }
+
+    pub fn set_joint_scale(&mut self, joint: usize, scale: &Vec3) {
+        self.poses[joint].scale = *scale;
+        self.is_dirty = true;
+    }
+
+    #[inline]
+    pub fn get_joint_location(&self, joint: usize) -> &Vec3 {
+        return &self.poses[joint].location;
+    }
+
+    #[inline]
+    pub fn get_joint_orientation(&self, joint: usize) -> &Quat {
+        return &self.poses[joint].orientation;
+    }
+
+    #[inline]
+    pub fn get_joint_scale(&self, joint: usize) -> &Vec3 {
+        return &self.poses[joint].scale;
+    }
+
+    /// Brings back the rest pose.
+    pub fn reset_pose(&mut self) {
+        for (p, j) in self.poses.iter_mut().zip(&self.joints) {
+            *p = Pose::new(j);
+        }
+        self.is_dirty = true;
+    }
+
+    /// Returns true if the palette has been changed.
+    pub fn update(&mut self) -> bool {
+        if !self.is_dirty {
+            return false;
+        }
+        for &i in &self.order {
+            let local = self.poses[i].get_matrix();
+            self.world_matrices[i] = match self.joints[i].parent {
+                Some(p) => self.world_matrices[p] * local,
+                None => self.bind_shape * local,
+            };
+            self.palette[i] = self.world_matrices[i] * self.joints[i].inverse_bind;
+        }
+        self.is_dirty = false;
+        return true;
+    }
+
+    /// Transformations of the joints in the space of the mesh.
+    pub fn get_world_matrices(&self) -> &[Mat4] {
+        #[cfg(debug_mode)]
+        {
+            if self.is_dirty {
+                vx_log_f!("Skeleton must be updated before reading its matrices.");
+            }
+        }
+        return &self.world_matrices;
+    }
+
+    pub fn get_palette(&self) -> &[Mat4] {
+        #[cfg(debug_mode)]
+        {
+            if self.is_dirty {
+                vx_log_f!("Skeleton must be updated before reading its matrices.");
+            }
+        }
+        return &self.palette;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::math::vector::Vec4;
+    use super::*;
+
+    fn is_near(a: &Vec4, b: &Vec4) -> bool {
+        return (*a - *b).length() < 1e-5;
+    }
+
+    #[test]
+    fn skeleton_test() {
+        // An arm along x with its elbow at one.
+        let shoulder = Joint {
+            name: Some("shoulder".to_string()),
+            parent: None,
+            location: Vec3::new(0.0, 0.0, 0.0),
+            orientation: Quat::identity(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            inverse_bind: Mat4::identity(),
+        };
+        let elbow = Joint {
+            name: Some("elbow".to_string()),
+            parent: Some(1),
+            location: Vec3::new(1.0, 0.0, 0.0),
+            orientation: Quat::identity(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            inverse_bind: Mat4::from_translation(Vec3::new(-1.0, 0.0, 0.0)),
+        };
+        // Children may come before their parents.
+        let mut skeleton = Skeleton::new(vec![elbow, shoulder], Mat4::identity());
+        assert_eq!(skeleton.find_joint("shoulder"), Some(1));
+        assert_eq!(skeleton.find_joint("wrist"), None);
+        // The bind pose does not move the vertices.
+        let hand = Vec4::new(2.0, 0.0, 0.0, 1.0);
+        for m in skeleton.get_palette() {
+            assert!(is_near(&(*m * hand), &hand));
+        }
+        assert!(!skeleton.update());
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2);
+        skeleton.set_joint_orientation(0, &q);
+        assert!(skeleton.update());
+        let expected = Vec4::new(1.0, 1.0, 0.0, 1.0);
+        assert!(is_near(&(skeleton.get_palette()[0] * hand), &expected));
+        // Children follow their parents.
+        skeleton.set_joint_orientation(1, &q);
+        skeleton.update();
+        let expected = Vec4::new(-1.0, 1.0, 0.0, 1.0);
+        assert!(is_near(&(skeleton.get_palette()[0] * hand), &expected));
+        let elbow_location = skeleton.get_world_matrices()[0] * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        assert!(is_near(&elbow_location, &Vec4::new(0.0, 1.0, 0.0, 1.0)));
+        skeleton.reset_pose();
+        skeleton.update();
+        assert!(is_near(&(skeleton.get_palette()[0] * hand), &hand));
+        let skin = VertexSkin::new([0, 1, 0, 0], [2.0, 2.0, 0.0, 0.0]);
+        assert_eq!(skin.weights, [0.5, 0.5, 0.0, 0.0]);
+        let data = interleave_skins(&[7.0, 8.0], 2, &[skin]);
+        assert_eq!(data, vec![7.0, 8.0, 0.0, 1.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0]);
+    }
+}
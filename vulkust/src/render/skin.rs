@@ -0,0 +1,221 @@
+use super::super::core::types::Real;
+use super::super::math::matrix::Mat4;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::{Vec3, Vec4};
+use super::buffer::Dynamic as DynamicBuffer;
+use super::command::Buffer as CmdBuffer;
+use super::descriptor::Set as DescriptorSet;
+use super::engine::Engine;
+use super::skeleton::{Joint, Skeleton, MAX_JOINTS_COUNT};
+use std::collections::BTreeMap;
+use std::mem::size_of;
+use std::sync::Arc;
+
+use gltf;
+
+#[repr(C)]
+#[cfg_attr(debug_mode, derive(Debug))]
+struct Uniform {
+    joints: [Mat4; MAX_JOINTS_COUNT],
+}
+
+/// Skeleton of a skinned model and the joint palette that the skinned
+/// pipelines read.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Skin {
+    skeleton: Skeleton,
+    // glTF node index to joint index.
+    joint_nodes: BTreeMap<usize, usize>,
+    uniform: Box<Uniform>,
+    uniform_buffer: DynamicBuffer,
+    descriptor_set: Arc<DescriptorSet>,
+}
+
+impl Skin {
+    /// Node is the skinned mesh node. Its transformation is ignored like
+    /// glTF asks, the joint roots must be in the space of its parent.
+    pub fn new_with_gltf(
+        node: &gltf::Node,
+        skin: &gltf::Skin,
+        engine: &Engine,
+        data: &[u8],
+    ) -> Self {
+        let reader = skin.reader(|_| Some(data));
+        let inverse_binds: Vec<[[Real; 4]; 4]> = match reader.read_inverse_bind_matrices() {
+            Some(m) => m.collect(),
+            None => Vec::new(),
+        };
+        let mut joint_nodes = BTreeMap::new();
+        for (i, j) in skin.joints().enumerate() {
+            joint_nodes.insert(j.index(), i);
+        }
+        let mut parents = vec![None; joint_nodes.len()];
+        for (i, j) in skin.joints().enumerate() {
+            for c in j.children() {
+                if let Some(c) = joint_nodes.get(&c.index()) {
+                    parents[*c] = Some(i);
+                }
+            }
+        }
+        let joints = skin
+            .joints()
+            .enumerate()
+            .map(|(i, j)| {
+                let (location, orientation, scale) = j.transform().decomposed();
+                Joint {
+                    name: j.name().map(|n| n.to_string()),
+                    parent: parents[i],
+                    location: Vec3::from_array(location),
+                    orientation: Quat::new(
+                        orientation[0],
+                        orientation[1],
+                        orientation[2],
+                        orientation[3],
+                    ),
+                    scale: Vec3::from_array(scale),
+                    inverse_bind: match inverse_binds.get(i) {
+                        Some(m) => get_matrix(m),
+                        None => Mat4::identity(),
+                    },
+                }
+            })
+            .collect();
+        let bind_shape = vx_unwrap!(get_matrix(&node.transform().matrix()).try_inverse());
+        let skeleton = Skeleton::new(joints, bind_shape);
+        let mut uniform = Box::new(Uniform {
+            joints: [Mat4::identity(); MAX_JOINTS_COUNT],
+        });
+        let palette = skeleton.get_palette();
+        uniform.joints[..palette.len()].copy_from_slice(palette);
+        let gapi_engine = &engine.gapi_engine;
+        let uniform_buffer = vx_result!(gapi_engine.get_buffer_manager().write())
+            .create_dynamic_buffer(size_of::<Uniform>() as isize);
+        let mut descriptor_manager = vx_result!(gapi_engine.get_descriptor_manager().write());
+        let descriptor_set = descriptor_manager.create_buffer_only_set(&uniform_buffer);
+        Self {
+            skeleton,
+            joint_nodes,
+            uniform,
+            uniform_buffer,
+            descriptor_set,
+        }
+    }
+
+    #[inline]
+    pub fn get_joint_nodes(&self) -> &BTreeMap<usize, usize> {
+        return &self.joint_nodes;
+    }
+
+    #[inline]
+    pub fn get_skeleton(&self) -> &Skeleton {
+        return &self.skeleton;
+    }
+
+    /// Joints can be posed by hand when the animator of the model is not
+    /// playing.
+    #[inline]
+    pub fn get_mut_skeleton(&mut self) -> &mut Skeleton {
+        return &mut self.skeleton;
+    }
+
+    pub(super) fn update(&mut self, frame_number: usize) {
+        if self.skeleton.update() {
+            let palette = self.skeleton.get_palette();
+            self.uniform.joints[..palette.len()].copy_from_slice(palette);
+        }
+        self.uniform_buffer.update(&*self.uniform, frame_number);
+    }
+
+    pub(super) fn bind_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        let buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
+        cmd.bind_skinned_gbuff_skin_descriptor(&*self.descriptor_set, &*buffer);
+    }
+
+    pub(super) fn bind_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        let buffer = vx_result!(self.uniform_buffer.get_buffer(frame_number).read());
+        cmd.bind_skinned_shadow_mapper_skin_descriptor(&*self.descriptor_set, &*buffer);
+    }
+}
+
+// glTF matrices are column major.
+fn get_matrix(m: &[[Real; 4]; 4]) -> Mat4 {
+    return Mat4::from_cols(
+        Vec4::from_array(m[0]),
+        Vec4::from_array(m[1]),
+        Vec4::from_array(m[2]),
+        Vec4::from_array(m[3]),
+    );
+}
+
+#[cfg(all(test, any(blank_gapi, software_gapi)))]
+mod test {
+    use super::*;
+
+    fn is_near(a: Vec4, b: Vec4) -> bool {
+        return (a - b).length() < 1e-5;
+    }
+
+    #[test]
+    fn palette_test() {
+        #[cfg(software_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_offscreen(8, 8);
+        #[cfg(blank_gapi)]
+        let gapi_engine = crate::gapi::engine::Engine::new_with_trace(
+            Arc::new(crate::gapi::trace::Trace::new()),
+            8,
+            8,
+        );
+        let engine = Engine::new_with_gapi_engine(gapi_engine);
+        // An arm along x with its elbow at one, the joints are listed before
+        // their parents and the skinned node is moved along z.
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "nodes": [
+                {"skin": 0, "translation": [0.0, 0.0, 3.0]},
+                {"name": "shoulder", "children": [2]},
+                {"name": "elbow", "translation": [1.0, 0.0, 0.0]}
+            ],
+            "skins": [{"joints": [2, 1], "inverseBindMatrices": 0}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 2, "type": "MAT4"}
+            ],
+            "bufferViews": [{"buffer": 0, "byteLength": 128}],
+            "buffers": [{"byteLength": 128}]
+        }"#;
+        let mut data = Vec::new();
+        let inverse_binds = [
+            Mat4::from_translation(Vec3::new(-1.0, 0.0, 0.0)),
+            Mat4::identity(),
+        ];
+        for m in &inverse_binds {
+            for f in &m.to_cols_array() {
+                data.extend_from_slice(&f.to_le_bytes());
+            }
+        }
+        let document = vx_result!(gltf::Gltf::from_slice(json.as_bytes())).document;
+        let node = vx_unwrap!(document.nodes().next());
+        let mut skin = Skin::new_with_gltf(&node, &vx_unwrap!(node.skin()), &engine, &data);
+        assert_eq!(skin.get_joint_nodes().get(&2), Some(&0));
+        assert_eq!(skin.get_joint_nodes().get(&1), Some(&1));
+        assert_eq!(skin.get_skeleton().get_joints()[0].parent, Some(1));
+        // The bind pose only brings the vertices to the bind shape space.
+        let hand = Vec4::new(2.0, 0.0, 0.0, 1.0);
+        let expected = Vec4::new(2.0, 0.0, -3.0, 1.0);
+        assert!(is_near(skin.uniform.joints[0] * hand, expected));
+        assert!(is_near(skin.uniform.joints[1] * hand, expected));
+        // Unused joints of the palette do not move the vertices.
+        assert!(skin.uniform.joints[2] == Mat4::identity());
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2);
+        skin.get_mut_skeleton().set_joint_orientation(1, &q);
+        skin.update(0);
+        assert!(is_near(
+            skin.uniform.joints[0] * hand,
+            Vec4::new(0.0, 2.0, -3.0, 1.0)
+        ));
+        assert!(is_near(
+            skin.uniform.joints[1] * hand,
+            Vec4::new(0.0, 2.0, -3.0, 1.0)
+        ));
+        assert!(skin.uniform.joints[..2] == *skin.get_skeleton().get_palette());
+    }
+}
@@ -52,6 +52,7 @@ impl Manager {
     }
 
     pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Skybox>> {
+        vx_profile_zone!("skybox::load_gx3d");
         if let Some(skybox) = self.skyboxes.get(&id) {
             if let Some(skybox) = skybox.upgrade() {
                 return skybox;
@@ -185,7 +186,7 @@ impl Skybox for Base {
         let buffer = self.uniform_buffer.get_buffer(frame_number);
         cmd.bind_unlit_model_descriptor(&*self.descriptor_set, &*vx_result!(buffer.read()));
         self.material.bind_unlit(cmd, frame_number);
-        vx_result!(self.mesh.read()).render_unlit(cmd, frame_number);
+        vx_result!(self.mesh.read()).render_unlit(cmd, frame_number, 0);
     }
 }
 
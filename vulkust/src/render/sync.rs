@@ -4,5 +4,7 @@ pub(crate) use super::super::blank_gapi::sync::*;
 pub use super::super::d3d12::sync::*;
 #[cfg(metal_api)]
 pub use super::super::metal::sync::*;
+#[cfg(software_gapi)]
+pub(crate) use super::super::software::sync::*;
 #[cfg(vulkan_api)]
 pub(crate) use super::super::vulkan::sync::*;
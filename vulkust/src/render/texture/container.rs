@@ -0,0 +1,540 @@
+use super::super::image::{ImageType, TextureFormat};
+use super::decoder;
+use std::fmt;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_SIZE: usize = 24;
+
+const DDS_MAGIC: [u8; 4] = [b'D', b'D', b'S', b' '];
+const DDS_HEADER_SIZE: usize = 128;
+const DDS_DX10_HEADER_SIZE: usize = 20;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_DEPTH: u32 = 0x80_0000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+// The common limits of the devices.
+const MAX_DIMENSION: u32 = 16384;
+const MAX_LAYERS_COUNT: u32 = 2048;
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) enum Error {
+    Unknown,
+    Truncated,
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Error::Unknown => write!(f, "unknown texture container"),
+            Error::Truncated => write!(f, "texture container is truncated"),
+            Error::Unsupported(s) => write!(f, "unsupported texture container, {}", s),
+        };
+    }
+}
+
+fn unsupported<T>(s: String) -> Result<T, Error> {
+    return Err(Error::Unsupported(s));
+}
+
+fn read_bytes(data: &[u8], offset: usize, size: usize) -> Result<&[u8], Error> {
+    return match offset.checked_add(size) {
+        Some(end) if end <= data.len() => Ok(&data[offset..end]),
+        _ => Err(Error::Truncated),
+    };
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    let mut b = [0u8; 4];
+    b.copy_from_slice(read_bytes(data, offset, 4)?);
+    return Ok(u32::from_le_bytes(b));
+}
+
+/// Offsets and sizes that do not fit in the address space can not be in the
+/// data either.
+fn read_size(data: &[u8], offset: usize) -> Result<usize, Error> {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(read_bytes(data, offset, 8)?);
+    let v = u64::from_le_bytes(b);
+    if v > usize::MAX as u64 {
+        return Err(Error::Truncated);
+    }
+    return Ok(v as usize);
+}
+
+fn convert_vk_format(f: u32) -> Result<TextureFormat, Error> {
+    const ASTC_BLOCKS: [(u8, u8); 14] = [
+        (4, 4),
+        (5, 4),
+        (5, 5),
+        (6, 5),
+        (6, 6),
+        (8, 5),
+        (8, 6),
+        (8, 8),
+        (10, 5),
+        (10, 6),
+        (10, 8),
+        (10, 10),
+        (12, 10),
+        (12, 12),
+    ];
+    match f {
+        37 | 43 => return Ok(TextureFormat::RgbaByte),
+        131 | 132 => return Ok(TextureFormat::Bc1Rgb),
+        133 | 134 => return Ok(TextureFormat::Bc1Rgba),
+        135 | 136 => return Ok(TextureFormat::Bc2),
+        137 | 138 => return Ok(TextureFormat::Bc3),
+        139 => return Ok(TextureFormat::Bc4),
+        140 => return Ok(TextureFormat::Bc4Signed),
+        141 => return Ok(TextureFormat::Bc5),
+        142 => return Ok(TextureFormat::Bc5Signed),
+        143 => return Ok(TextureFormat::Bc6h),
+        144 => return Ok(TextureFormat::Bc6hSigned),
+        145 | 146 => return Ok(TextureFormat::Bc7),
+        147 | 148 => return Ok(TextureFormat::Etc2Rgb),
+        149 | 150 => return Ok(TextureFormat::Etc2RgbA1),
+        151 | 152 => return Ok(TextureFormat::Etc2Rgba),
+        157..=184 => {
+            let (w, h) = ASTC_BLOCKS[((f - 157) >> 1) as usize];
+            return Ok(TextureFormat::Astc(w, h));
+        }
+        _ => return unsupported(format!("KTX2 vkFormat {}", f)),
+    }
+}
+
+fn convert_dxgi_format(f: u32) -> Result<(TextureFormat, bool), Error> {
+    match f {
+        28 | 29 => return Ok((TextureFormat::RgbaByte, false)),
+        87 | 91 => return Ok((TextureFormat::RgbaByte, true)),
+        71 | 72 => return Ok((TextureFormat::Bc1Rgba, false)),
+        74 | 75 => return Ok((TextureFormat::Bc2, false)),
+        77 | 78 => return Ok((TextureFormat::Bc3, false)),
+        80 => return Ok((TextureFormat::Bc4, false)),
+        81 => return Ok((TextureFormat::Bc4Signed, false)),
+        83 => return Ok((TextureFormat::Bc5, false)),
+        84 => return Ok((TextureFormat::Bc5Signed, false)),
+        95 => return Ok((TextureFormat::Bc6h, false)),
+        96 => return Ok((TextureFormat::Bc6hSigned, false)),
+        98 | 99 => return Ok((TextureFormat::Bc7, false)),
+        _ => return unsupported(format!("DDS DXGI format {}", f)),
+    }
+}
+
+fn convert_four_cc(f: &[u8]) -> Result<TextureFormat, Error> {
+    match f {
+        b"DXT1" => return Ok(TextureFormat::Bc1Rgba),
+        b"DXT2" | b"DXT3" => return Ok(TextureFormat::Bc2),
+        b"DXT4" | b"DXT5" => return Ok(TextureFormat::Bc3),
+        b"ATI1" | b"BC4U" => return Ok(TextureFormat::Bc4),
+        b"BC4S" => return Ok(TextureFormat::Bc4Signed),
+        b"ATI2" | b"BC5U" => return Ok(TextureFormat::Bc5),
+        b"BC5S" => return Ok(TextureFormat::Bc5Signed),
+        _ => return unsupported(format!("DDS FourCC {:?}", f)),
+    }
+}
+
+pub(crate) fn is_container(data: &[u8]) -> bool {
+    return data.starts_with(&KTX2_IDENTIFIER) || data.starts_with(&DDS_MAGIC);
+}
+
+/// A texture with all of its mip levels, every level keeps the images of its
+/// layers after each other and the faces of each layer in
+/// +x, -x, +y, -y, +z, -z order.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Container {
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    layers_count: u32,
+    faces_count: u32,
+    levels: Vec<Vec<u8>>,
+}
+
+impl Container {
+    pub(crate) fn new(data: &[u8]) -> Result<Self, Error> {
+        if data.starts_with(&KTX2_IDENTIFIER) {
+            return Self::new_with_ktx2(data);
+        } else if data.starts_with(&DDS_MAGIC) {
+            return Self::new_with_dds(data);
+        }
+        return Err(Error::Unknown);
+    }
+
+    fn new_with_ktx2(data: &[u8]) -> Result<Self, Error> {
+        let format = convert_vk_format(read_u32(data, 12)?)?;
+        let width = read_u32(data, 20)?;
+        let height = read_u32(data, 24)?;
+        if height == 0 || read_u32(data, 28)? != 0 {
+            return unsupported("only 2D KTX2 textures are supported".to_string());
+        }
+        let layers_count = read_u32(data, 32)?.max(1);
+        let faces_count = read_u32(data, 36)?;
+        if faces_count != 1 && faces_count != 6 {
+            return unsupported(format!("KTX2 faces count {}", faces_count));
+        }
+        // Zero levels asks the loader to generate the mip chain.
+        let levels_count = read_u32(data, 40)?.max(1);
+        if read_u32(data, 44)? != 0 {
+            return unsupported("supercompressed KTX2 textures".to_string());
+        }
+        let mut myself = Self::new_with_header(
+            format,
+            width,
+            height,
+            layers_count,
+            faces_count,
+            levels_count,
+        )?;
+        for li in 0..levels_count {
+            let index = KTX2_HEADER_SIZE + li as usize * KTX2_LEVEL_INDEX_SIZE;
+            let offset = read_size(data, index)?;
+            let size = read_size(data, index + 8)?;
+            if size != myself.get_level_size(li) {
+                return unsupported(format!("size of KTX2 level {}", li));
+            }
+            myself.levels.push(read_bytes(data, offset, size)?.to_vec());
+        }
+        return Ok(myself);
+    }
+
+    fn new_with_dds(data: &[u8]) -> Result<Self, Error> {
+        let flags = read_u32(data, 8)?;
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let levels_count = if flags & DDSD_MIPMAPCOUNT == DDSD_MIPMAPCOUNT {
+            read_u32(data, 28)?.max(1)
+        } else {
+            1
+        };
+        let pixel_flags = read_u32(data, 80)?;
+        let caps2 = read_u32(data, 112)?;
+        if flags & DDSD_DEPTH == DDSD_DEPTH || caps2 & DDSCAPS2_VOLUME == DDSCAPS2_VOLUME {
+            return unsupported("volume DDS textures".to_string());
+        }
+        let mut faces_count = if caps2 & DDSCAPS2_CUBEMAP == DDSCAPS2_CUBEMAP {
+            6
+        } else {
+            1
+        };
+        let mut layers_count = 1;
+        let mut offset = DDS_HEADER_SIZE;
+        let (format, is_bgra) = if pixel_flags & DDPF_FOURCC == DDPF_FOURCC {
+            let four_cc = read_bytes(data, 84, 4)?;
+            if four_cc == b"DX10" {
+                let dxgi = read_u32(data, offset)?;
+                if read_u32(data, offset + 8)? & DDS_RESOURCE_MISC_TEXTURECUBE != 0 {
+                    faces_count = 6;
+                }
+                layers_count = read_u32(data, offset + 12)?.max(1);
+                offset += DDS_DX10_HEADER_SIZE;
+                convert_dxgi_format(dxgi)?
+            } else {
+                (convert_four_cc(four_cc)?, false)
+            }
+        } else if pixel_flags & DDPF_RGB == DDPF_RGB
+            && pixel_flags & DDPF_ALPHAPIXELS == DDPF_ALPHAPIXELS
+            && read_u32(data, 88)? == 32
+            && read_u32(data, 104)? == 0xFF00_0000
+        {
+            match read_u32(data, 92)? {
+                0xFF => (TextureFormat::RgbaByte, false),
+                0xFF_0000 => (TextureFormat::RgbaByte, true),
+                _ => return unsupported("DDS pixel masks".to_string()),
+            }
+        } else {
+            return unsupported("DDS pixel format".to_string());
+        };
+        let mut myself = Self::new_with_header(
+            format,
+            width,
+            height,
+            layers_count,
+            faces_count,
+            levels_count,
+        )?;
+        let size = (0..levels_count).map(|li| myself.get_level_size(li)).sum();
+        read_bytes(data, offset, size)?;
+        for li in 0..levels_count {
+            let size = myself.get_level_size(li);
+            myself.levels.push(Vec::with_capacity(size));
+        }
+        // DDS keeps the whole mip chain of an image before the next one.
+        for _ in 0..(layers_count * faces_count) {
+            for li in 0..levels_count {
+                let (w, h) = myself.get_level_dimensions(li);
+                let size = format.get_image_size(w, h);
+                let image = read_bytes(data, offset, size)?;
+                myself.levels[li as usize].extend_from_slice(image);
+                offset += size;
+            }
+        }
+        if is_bgra {
+            for level in &mut myself.levels {
+                for p in level.chunks_mut(4) {
+                    p.swap(0, 2);
+                }
+            }
+        }
+        return Ok(myself);
+    }
+
+    /// The headers are checked against the limits before anything is
+    /// allocated with them.
+    fn new_with_header(
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        layers_count: u32,
+        faces_count: u32,
+        levels_count: u32,
+    ) -> Result<Self, Error> {
+        if width == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+            return unsupported(format!("dimensions {}x{}", width, height));
+        }
+        if layers_count > MAX_LAYERS_COUNT {
+            return unsupported(format!("layers count {}", layers_count));
+        }
+        if levels_count > 32 - width.max(height).leading_zeros() {
+            return unsupported(format!("levels count {}", levels_count));
+        }
+        return Ok(Self {
+            format,
+            width,
+            height,
+            layers_count,
+            faces_count,
+            levels: Vec::with_capacity(levels_count as usize),
+        });
+    }
+
+    /// Decodes the blocks on CPU into RGBA bytes, for the devices that can
+    /// not sample the format.
+    pub(crate) fn decode(&self) -> Self {
+        if !self.format.is_compressed() {
+            vx_unexpected!();
+        }
+        let images_count = (self.layers_count * self.faces_count) as usize;
+        let mut levels = Vec::with_capacity(self.levels.len());
+        for (li, level) in self.levels.iter().enumerate() {
+            let (w, h) = self.get_level_dimensions(li as u32);
+            let size = self.format.get_image_size(w, h);
+            let mut decoded = Vec::with_capacity((w * h * 4) as usize * images_count);
+            for ii in 0..images_count {
+                let image = &level[ii * size..(ii + 1) * size];
+                decoded.extend(decoder::decode(self.format, w, h, image));
+            }
+            levels.push(decoded);
+        }
+        return Self {
+            format: TextureFormat::RgbaByte,
+            width: self.width,
+            height: self.height,
+            layers_count: self.layers_count,
+            faces_count: self.faces_count,
+            levels,
+        };
+    }
+
+    pub(crate) fn get_level_dimensions(&self, level: u32) -> (u32, u32) {
+        return ((self.width >> level).max(1), (self.height >> level).max(1));
+    }
+
+    pub(crate) fn get_level_size(&self, level: u32) -> usize {
+        let (w, h) = self.get_level_dimensions(level);
+        let images_count = (self.layers_count * self.faces_count) as usize;
+        return self.format.get_image_size(w, h) * images_count;
+    }
+
+    pub(crate) fn get_format(&self) -> TextureFormat {
+        return self.format;
+    }
+
+    pub(crate) fn get_dimensions(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    pub(crate) fn get_layers_count(&self) -> u32 {
+        return self.layers_count;
+    }
+
+    pub(crate) fn get_faces_count(&self) -> u32 {
+        return self.faces_count;
+    }
+
+    pub(crate) fn get_levels(&self) -> &[Vec<u8>] {
+        return &self.levels;
+    }
+
+    pub(crate) fn get_image_type(&self) -> ImageType {
+        match (self.faces_count, self.layers_count) {
+            (6, 1) => return ImageType::Cube,
+            (6, _) => return ImageType::CubeArray,
+            (_, 1) => return ImageType::D2,
+            _ => return ImageType::D2Array,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn push_u32(data: &mut Vec<u8>, v: u32) {
+        data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn push_u64(data: &mut Vec<u8>, v: u64) {
+        data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// 8x4 BC1 with two levels and two layers
+    fn build_ktx2() -> Vec<u8> {
+        let mut data = KTX2_IDENTIFIER.to_vec();
+        for v in &[133, 1, 8, 4, 0, 2, 1, 2, 0] {
+            push_u32(&mut data, *v);
+        }
+        for _ in 0..4 {
+            push_u32(&mut data, 0);
+        }
+        push_u64(&mut data, 0);
+        push_u64(&mut data, 0);
+        let level0 = (KTX2_HEADER_SIZE + 2 * KTX2_LEVEL_INDEX_SIZE) as u64;
+        for (offset, size) in &[(level0, 32), (level0 + 32, 16)] {
+            push_u64(&mut data, *offset);
+            push_u64(&mut data, *size);
+            push_u64(&mut data, *size);
+        }
+        for i in 0..48u8 {
+            data.push(i);
+        }
+        return data;
+    }
+
+    /// 2x2 BGRA cube with two levels
+    fn build_dds() -> Vec<u8> {
+        let mut data = DDS_MAGIC.to_vec();
+        push_u32(&mut data, 124);
+        push_u32(&mut data, DDSD_MIPMAPCOUNT);
+        push_u32(&mut data, 2);
+        push_u32(&mut data, 2);
+        push_u32(&mut data, 0);
+        push_u32(&mut data, 0);
+        push_u32(&mut data, 2);
+        for _ in 0..11 {
+            push_u32(&mut data, 0);
+        }
+        for v in &[
+            32,
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            32,
+            0xFF_0000,
+            0xFF00,
+            0xFF,
+            0xFF00_0000,
+        ] {
+            push_u32(&mut data, *v);
+        }
+        push_u32(&mut data, 0);
+        push_u32(&mut data, DDSCAPS2_CUBEMAP | 0xFC00);
+        for _ in 0..3 {
+            push_u32(&mut data, 0);
+        }
+        assert_eq!(data.len(), DDS_HEADER_SIZE);
+        for face in 0..6u8 {
+            for _ in 0..4 {
+                data.extend_from_slice(&[1, 2, 3, face]);
+            }
+            data.extend_from_slice(&[4, 5, 6, face]);
+        }
+        return data;
+    }
+
+    fn set_u32(data: &mut [u8], offset: usize, v: u32) {
+        data[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+    }
+
+    #[test]
+    fn ktx2_test() {
+        let data = build_ktx2();
+        assert!(is_container(&data));
+        let c = vx_unwrap!(Container::new(&data).ok());
+        assert!(c.get_format() == TextureFormat::Bc1Rgba);
+        assert_eq!(c.get_dimensions(), (8, 4));
+        assert_eq!(c.get_layers_count(), 2);
+        assert!(c.get_image_type() == ImageType::D2Array);
+        assert_eq!(c.get_levels().len(), 2);
+        assert_eq!(c.get_levels()[0], (0..32).collect::<Vec<u8>>());
+        assert_eq!(c.get_levels()[1], (32..48).collect::<Vec<u8>>());
+        let d = c.decode();
+        assert!(d.get_format() == TextureFormat::RgbaByte);
+        assert_eq!(d.get_levels()[0].len(), 8 * 4 * 4 * 2);
+        assert_eq!(d.get_levels()[1].len(), 4 * 2 * 4 * 2);
+    }
+
+    #[test]
+    fn dds_test() {
+        let data = build_dds();
+        let c = vx_unwrap!(Container::new(&data).ok());
+        assert!(c.get_format() == TextureFormat::RgbaByte);
+        assert!(c.get_image_type() == ImageType::Cube);
+        assert_eq!(c.get_levels()[0].len(), 2 * 2 * 4 * 6);
+        assert_eq!(c.get_levels()[1].len(), 4 * 6);
+        assert_eq!(&c.get_levels()[0][16 * 5..16 * 5 + 4], &[3, 2, 1, 5]);
+        assert_eq!(&c.get_levels()[1][4 * 3..4 * 4], &[6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn truncated_test() {
+        for data in &[build_ktx2(), build_dds()] {
+            for len in 0..data.len() {
+                match Container::new(&data[..len]) {
+                    Err(Error::Unknown) => assert!(!is_container(&data[..len])),
+                    Err(Error::Truncated) => {}
+                    _ => vx_log_f!("Truncated container is not detected at {}.", len),
+                }
+            }
+        }
+        // Offsets that do not fit in the address space.
+        let mut data = build_ktx2();
+        data[KTX2_HEADER_SIZE..KTX2_HEADER_SIZE + 8].copy_from_slice(&[0xFF; 8]);
+        match Container::new(&data) {
+            Err(Error::Truncated) => {}
+            _ => vx_unexpected!(),
+        }
+    }
+
+    #[test]
+    fn unsupported_test() {
+        let check = |data: &[u8]| match Container::new(data) {
+            Err(Error::Unsupported(_)) => {}
+            _ => vx_unexpected!(),
+        };
+        let mut data = build_ktx2();
+        // An 8x4 texture has four levels at most.
+        set_u32(&mut data, 40, 5);
+        check(&data);
+        let mut data = build_ktx2();
+        set_u32(&mut data, 12, 1);
+        check(&data);
+        let mut data = build_ktx2();
+        set_u32(&mut data, 20, MAX_DIMENSION + 1);
+        check(&data);
+        let mut data = build_dds();
+        set_u32(&mut data, 92, 0xFF00);
+        check(&data);
+        match Container::new(b"not a texture") {
+            Err(Error::Unknown) => {}
+            _ => vx_unexpected!(),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use super::super::engine::Engine;
 use super::super::gapi::GraphicApiEngine;
 use super::super::image::View as ImageView;
 use super::super::sampler::Sampler;
+use super::container::Container;
 use super::{Loadable, Texture};
 use std::sync::Arc;
 
@@ -57,6 +58,25 @@ impl Cube {
         return Self::new_with_base_pixels_name(obj_base, width, height, engine, data, None);
     }
 
+    pub(crate) fn new_with_base_container_name(
+        obj_base: ObjectBase,
+        engine: &GraphicApiEngine,
+        container: &Container,
+        name: Option<String>,
+    ) -> Self {
+        if container.get_faces_count() != 6 {
+            vx_log_f!("Cube texture container must have six faces.");
+        }
+        let image_view = engine.create_texture_with_container(container);
+        let sampler = engine.get_linear_repeat_sampler().clone();
+        Self {
+            obj_base,
+            name,
+            image_view,
+            sampler,
+        }
+    }
+
     pub(super) fn new_with_view_sampler(image_view: Arc<ImageView>, sampler: Arc<Sampler>) -> Self {
         Self {
             obj_base: ObjectBase::new(),
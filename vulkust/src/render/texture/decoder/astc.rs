@@ -0,0 +1,695 @@
+/// The HDR blocks and the illegal encodings are decoded to this color.
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// Trits, quints and bits of the quantization ranges from 2 to 256 levels.
+const RANGES: [(u32, u32, u32); 21] = [
+    (0, 0, 1),
+    (1, 0, 0),
+    (0, 0, 2),
+    (0, 1, 0),
+    (1, 0, 1),
+    (0, 0, 3),
+    (0, 1, 1),
+    (1, 0, 2),
+    (0, 0, 4),
+    (0, 1, 2),
+    (1, 0, 3),
+    (0, 0, 5),
+    (0, 1, 3),
+    (1, 0, 4),
+    (0, 0, 6),
+    (0, 1, 4),
+    (1, 0, 5),
+    (0, 0, 7),
+    (0, 1, 5),
+    (1, 0, 6),
+    (0, 0, 8),
+];
+
+#[cfg_attr(debug_mode, derive(Debug))]
+struct BlockMode {
+    width: u32,
+    height: u32,
+    dual_plane: bool,
+    weight_range: usize,
+}
+
+#[inline]
+fn read(data: u128, start: u32, count: u32) -> u32 {
+    if count == 0 || start >= 128 {
+        return 0;
+    }
+    return ((data >> start) as u32) & (((1u64 << count) - 1) as u32);
+}
+
+fn replicate(v: u32, from: u32, to: u32) -> u32 {
+    let mut result = 0;
+    let mut shift = to as i32 - from as i32;
+    while shift > -(from as i32) {
+        result |= if shift >= 0 { v << shift } else { v >> -shift };
+        shift -= from as i32;
+    }
+    return result & ((1 << to) - 1);
+}
+
+fn get_ise_bits(count: usize, range: usize) -> u32 {
+    let (trits, quints, bits) = RANGES[range];
+    let count = count as u32;
+    return count * bits + trits * ((8 * count + 4) / 5) + quints * ((7 * count + 2) / 3);
+}
+
+fn decode_trits(t: u32) -> [u32; 5] {
+    let (c, t3, t4);
+    if (t >> 2) & 7 == 7 {
+        c = (((t >> 5) & 7) << 2) | (t & 3);
+        t4 = 2;
+        t3 = 2;
+    } else {
+        c = t & 0x1F;
+        if (t >> 5) & 3 == 3 {
+            t4 = 2;
+            t3 = (t >> 7) & 1;
+        } else {
+            t4 = (t >> 7) & 1;
+            t3 = (t >> 5) & 3;
+        }
+    }
+    let cb = |i: u32| (c >> i) & 1;
+    let (t0, t1, t2);
+    if c & 3 == 3 {
+        t2 = 2;
+        t1 = cb(4);
+        t0 = (cb(3) << 1) | (cb(2) & !cb(3) & 1);
+    } else if (c >> 2) & 3 == 3 {
+        t2 = 2;
+        t1 = 2;
+        t0 = c & 3;
+    } else {
+        t2 = cb(4);
+        t1 = (c >> 2) & 3;
+        t0 = (cb(1) << 1) | (cb(0) & !cb(1) & 1);
+    }
+    return [t0, t1, t2, t3, t4];
+}
+
+fn decode_quints(q: u32) -> [u32; 3] {
+    let qb = |i: u32| (q >> i) & 1;
+    if (q >> 1) & 3 == 3 && (q >> 5) & 3 == 0 {
+        let q2 = (qb(0) << 2) | ((qb(4) & !qb(0) & 1) << 1) | (qb(3) & !qb(0) & 1);
+        return [4, 4, q2];
+    }
+    let (c, q2);
+    if (q >> 1) & 3 == 3 {
+        q2 = 4;
+        c = (((q >> 3) & 3) << 3) | ((!(q >> 5) & 3) << 1) | qb(0);
+    } else {
+        q2 = (q >> 5) & 3;
+        c = q & 0x1F;
+    }
+    let (q0, q1);
+    if c & 7 == 5 {
+        q1 = 4;
+        q0 = (c >> 3) & 3;
+    } else {
+        q1 = (c >> 3) & 3;
+        q0 = c & 7;
+    }
+    return [q0, q1, q2];
+}
+
+/// Decodes an integer sequence, the bits after the end of it are read as zeros.
+fn decode_ise(data: u128, start: u32, count: usize, range: usize) -> Vec<u32> {
+    let (trits, quints, bits) = RANGES[range];
+    let end = start + get_ise_bits(count, range);
+    let data = if end >= 128 {
+        data
+    } else {
+        data & ((1u128 << end) - 1)
+    };
+    let mut position = start;
+    let mut next = |count: u32| {
+        let v = read(data, position, count);
+        position += count;
+        return v;
+    };
+    let mut result = Vec::with_capacity(count + 4);
+    while result.len() < count {
+        if trits == 1 {
+            let mut m = [0u32; 5];
+            m[0] = next(bits);
+            let mut t = next(2);
+            m[1] = next(bits);
+            t |= next(2) << 2;
+            m[2] = next(bits);
+            t |= next(1) << 4;
+            m[3] = next(bits);
+            t |= next(2) << 5;
+            m[4] = next(bits);
+            t |= next(1) << 7;
+            let digits = decode_trits(t);
+            for i in 0..5 {
+                result.push((digits[i] << bits) | m[i]);
+            }
+        } else if quints == 1 {
+            let mut m = [0u32; 3];
+            m[0] = next(bits);
+            let mut q = next(3);
+            m[1] = next(bits);
+            q |= next(2) << 3;
+            m[2] = next(bits);
+            q |= next(2) << 5;
+            let digits = decode_quints(q);
+            for i in 0..3 {
+                result.push((digits[i] << bits) | m[i]);
+            }
+        } else {
+            result.push(next(bits));
+        }
+    }
+    result.truncate(count);
+    return result;
+}
+
+fn unquantize_color(v: u32, range: usize) -> i32 {
+    let (trits, quints, bits) = RANGES[range];
+    if trits == 0 && quints == 0 {
+        return replicate(v, bits, 8) as i32;
+    }
+    let m = v & ((1 << bits) - 1);
+    let d = v >> bits;
+    let a = if m & 1 == 1 { 0x1FF } else { 0 };
+    let x = m >> 1;
+    let (c, b) = if trits == 1 {
+        match bits {
+            1 => (204, 0),
+            2 => (93, (x << 8) | (x << 4) | (x << 2) | (x << 1)),
+            3 => (44, (x << 7) | (x << 2) | x),
+            4 => (22, (x << 6) | x),
+            5 => (11, (x << 5) | (x >> 2)),
+            _ => (5, (x << 4) | (x >> 4)),
+        }
+    } else {
+        match bits {
+            1 => (113, 0),
+            2 => (54, (x << 8) | (x << 3) | (x << 2)),
+            3 => (26, (x << 7) | (x << 1) | (x >> 1)),
+            4 => (13, (x << 6) | (x >> 1)),
+            _ => (6, (x << 5) | (x >> 3)),
+        }
+    };
+    let t = (d * c + b) ^ a;
+    return ((a & 0x80) | (t >> 2)) as i32;
+}
+
+fn unquantize_weight(v: u32, range: usize) -> u32 {
+    let (trits, quints, bits) = RANGES[range];
+    let w = if trits == 0 && quints == 0 {
+        replicate(v, bits, 6)
+    } else if bits == 0 {
+        if trits == 1 {
+            [0, 32, 63][v as usize]
+        } else {
+            [0, 16, 32, 47, 63][v as usize]
+        }
+    } else {
+        let m = v & ((1 << bits) - 1);
+        let d = v >> bits;
+        let a = if m & 1 == 1 { 0x7F } else { 0 };
+        let x = m >> 1;
+        let (c, b) = if trits == 1 {
+            match bits {
+                1 => (50, 0),
+                2 => (23, (x << 6) | (x << 2) | x),
+                _ => (11, (x << 5) | x),
+            }
+        } else {
+            match bits {
+                1 => (28, 0),
+                _ => (13, (x << 6) | (x << 1)),
+            }
+        };
+        let t = (d * c + b) ^ a;
+        (a & 0x20) | (t >> 2)
+    };
+    return if w > 32 { w + 1 } else { w };
+}
+
+fn decode_block_mode(mode: u32) -> Option<BlockMode> {
+    let bit = |i: u32| (mode >> i) & 1;
+    let a = (mode >> 5) & 3;
+    let (width, height, r);
+    let mut high = bit(9) == 1;
+    let mut dual_plane = bit(10) == 1;
+    if mode & 3 != 0 {
+        r = bit(4) | ((mode & 3) << 1);
+        let b = (mode >> 7) & 3;
+        match (mode >> 2) & 3 {
+            0 => {
+                width = b + 4;
+                height = a + 2;
+            }
+            1 => {
+                width = b + 8;
+                height = a + 2;
+            }
+            2 => {
+                width = a + 2;
+                height = b + 8;
+            }
+            _ => {
+                if bit(8) == 0 {
+                    width = a + 2;
+                    height = bit(7) + 6;
+                } else {
+                    width = bit(7) + 2;
+                    height = a + 2;
+                }
+            }
+        }
+    } else {
+        if mode & 0xF == 0 {
+            return None;
+        }
+        r = bit(4) | (((mode >> 2) & 3) << 1);
+        match (mode >> 7) & 3 {
+            0 => {
+                width = 12;
+                height = a + 2;
+            }
+            1 => {
+                width = a + 2;
+                height = 12;
+            }
+            2 => {
+                width = a + 6;
+                height = ((mode >> 9) & 3) + 6;
+                high = false;
+                dual_plane = false;
+            }
+            _ => match a {
+                0 => {
+                    width = 6;
+                    height = 10;
+                }
+                1 => {
+                    width = 10;
+                    height = 6;
+                }
+                _ => return None,
+            },
+        }
+    }
+    let weight_range = (r - 2) as usize + if high { 6 } else { 0 };
+    return Some(BlockMode {
+        width,
+        height,
+        dual_plane,
+        weight_range,
+    });
+}
+
+fn bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = (b >> 1) | (a & 0x80);
+    let mut a = (a >> 1) & 0x3F;
+    if a & 0x20 != 0 {
+        a -= 0x40;
+    }
+    return (a, b);
+}
+
+fn blue_contract(r: i32, g: i32, b: i32, a: i32) -> [i32; 4] {
+    return [(r + b) >> 1, (g + b) >> 1, b, a];
+}
+
+fn clamp(c: [i32; 4]) -> [i32; 4] {
+    return [
+        c[0].max(0).min(255),
+        c[1].max(0).min(255),
+        c[2].max(0).min(255),
+        c[3].max(0).min(255),
+    ];
+}
+
+/// Only the LDR endpoint modes are decodable, the HDR ones return none.
+fn decode_endpoints(cem: u32, v: &[i32]) -> Option<([i32; 4], [i32; 4])> {
+    let (e0, e1) = match cem {
+        0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xC0);
+            let l1 = (l0 + (v[1] & 0x3F)).min(255);
+            ([l0, l0, l0, 255], [l1, l1, l1, 255])
+        }
+        4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+        5 => {
+            let (d0, b0) = bit_transfer_signed(v[1], v[0]);
+            let (d2, b2) = bit_transfer_signed(v[3], v[2]);
+            ([b0, b0, b0, b2], [b0 + d0, b0 + d0, b0 + d0, b2 + d2])
+        }
+        6 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ),
+        8 | 12 => {
+            let (a0, a1) = if cem == 12 { (v[6], v[7]) } else { (255, 255) };
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                ([v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1])
+            } else {
+                (
+                    blue_contract(v[1], v[3], v[5], a1),
+                    blue_contract(v[0], v[2], v[4], a0),
+                )
+            }
+        }
+        9 | 13 => {
+            let (d0, b0) = bit_transfer_signed(v[1], v[0]);
+            let (d1, b1) = bit_transfer_signed(v[3], v[2]);
+            let (d2, b2) = bit_transfer_signed(v[5], v[4]);
+            let (d3, b3) = if cem == 13 {
+                bit_transfer_signed(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+            if d0 + d1 + d2 >= 0 {
+                ([b0, b1, b2, b3], [b0 + d0, b1 + d1, b2 + d2, b3 + d3])
+            } else {
+                (
+                    blue_contract(b0 + d0, b1 + d1, b2 + d2, b3 + d3),
+                    blue_contract(b0, b1, b2, b3),
+                )
+            }
+        }
+        10 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ),
+        _ => return None,
+    };
+    return Some((clamp(e0), clamp(e1)));
+}
+
+fn hash52(mut p: u32) -> u32 {
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+    return p;
+}
+
+fn select_partition(seed: u32, x: u32, y: u32, count: u32, small: bool) -> usize {
+    if count == 1 {
+        return 0;
+    }
+    let (x, y) = if small { (x << 1, y << 1) } else { (x, y) };
+    let seed = seed + (count - 1) * 1024;
+    let rnum = hash52(seed);
+    let mut s = [
+        rnum & 0xF,
+        (rnum >> 4) & 0xF,
+        (rnum >> 8) & 0xF,
+        (rnum >> 12) & 0xF,
+        (rnum >> 16) & 0xF,
+        (rnum >> 20) & 0xF,
+        (rnum >> 24) & 0xF,
+        (rnum >> 28) & 0xF,
+    ];
+    for v in s.iter_mut() {
+        *v *= *v;
+    }
+    let (sh1, sh2) = if seed & 1 == 1 {
+        (
+            if seed & 2 == 2 { 4 } else { 5 },
+            if count == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if count == 3 { 6 } else { 5 },
+            if seed & 2 == 2 { 4 } else { 5 },
+        )
+    };
+    for (i, v) in s.iter_mut().enumerate() {
+        *v >>= if i & 1 == 0 { sh1 } else { sh2 };
+    }
+    let a = (s[0] * x + s[1] * y + (rnum >> 14)) & 0x3F;
+    let b = (s[2] * x + s[3] * y + (rnum >> 10)) & 0x3F;
+    let c = if count < 3 {
+        0
+    } else {
+        (s[4] * x + s[5] * y + (rnum >> 6)) & 0x3F
+    };
+    let d = if count < 4 {
+        0
+    } else {
+        (s[6] * x + s[7] * y + (rnum >> 2)) & 0x3F
+    };
+    return if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    };
+}
+
+fn decode_void_extent(data: u128, texels: &mut [[u8; 4]]) -> Option<()> {
+    if read(data, 9, 1) == 1 {
+        return None;
+    }
+    let color = [
+        (read(data, 64, 16) >> 8) as u8,
+        (read(data, 80, 16) >> 8) as u8,
+        (read(data, 96, 16) >> 8) as u8,
+        (read(data, 112, 16) >> 8) as u8,
+    ];
+    for t in texels.iter_mut() {
+        *t = color;
+    }
+    return Some(());
+}
+
+fn decode_block(data: u128, bw: u32, bh: u32, texels: &mut [[u8; 4]]) -> Option<()> {
+    let mode = read(data, 0, 11);
+    if mode & 0x1FF == 0x1FC {
+        return decode_void_extent(data, texels);
+    }
+    let block_mode = decode_block_mode(mode)?;
+    let (gw, gh) = (block_mode.width, block_mode.height);
+    if gw > bw || gh > bh {
+        return None;
+    }
+    let planes = if block_mode.dual_plane { 2 } else { 1 };
+    let weights_count = (gw * gh) as usize * planes;
+    if weights_count > 64 {
+        return None;
+    }
+    let weight_bits = get_ise_bits(weights_count, block_mode.weight_range);
+    if weight_bits < 24 || weight_bits > 96 {
+        return None;
+    }
+    let partitions = read(data, 11, 2) + 1;
+    if block_mode.dual_plane && partitions == 4 {
+        return None;
+    }
+    let mut cems = [0u32; 4];
+    let (color_start, extra_bits) = if partitions == 1 {
+        cems[0] = read(data, 13, 4);
+        (17, 0)
+    } else {
+        let selector = read(data, 23, 6);
+        if selector & 3 == 0 {
+            for cem in cems.iter_mut() {
+                *cem = selector >> 2;
+            }
+            (29, 0)
+        } else {
+            let extra_bits = 3 * partitions - 4;
+            let extra = read(data, 128 - weight_bits - extra_bits, extra_bits);
+            let v = selector | (extra << 6);
+            let base = (v & 3) - 1;
+            for i in 0..partitions {
+                let c = (v >> (2 + i)) & 1;
+                let m = (v >> (2 + partitions + 2 * i)) & 3;
+                cems[i as usize] = ((base + c) << 2) | m;
+            }
+            (29, extra_bits)
+        }
+    };
+    let ccs_bits = if block_mode.dual_plane { 2 } else { 0 };
+    let ccs = read(data, 128 - weight_bits - extra_bits - ccs_bits, ccs_bits) as usize;
+    let cems = &cems[..partitions as usize];
+    let values_count: usize = cems.iter().map(|c| 2 * ((c >> 2) as usize + 1)).sum();
+    if values_count > 18 {
+        return None;
+    }
+    let color_bits = 128 - weight_bits - extra_bits - ccs_bits;
+    if color_bits < color_start || color_bits - color_start < (13 * values_count as u32 + 4) / 5 {
+        return None;
+    }
+    let color_bits = color_bits - color_start;
+    let mut color_range = 0;
+    for r in (0..RANGES.len()).rev() {
+        if get_ise_bits(values_count, r) <= color_bits {
+            color_range = r;
+            break;
+        }
+    }
+    let values: Vec<i32> = decode_ise(data, color_start, values_count, color_range)
+        .into_iter()
+        .map(|v| unquantize_color(v, color_range))
+        .collect();
+    let mut endpoints = Vec::with_capacity(cems.len());
+    let mut offset = 0;
+    for cem in cems {
+        endpoints.push(decode_endpoints(*cem, &values[offset..])?);
+        offset += 2 * ((cem >> 2) as usize + 1);
+    }
+    let weights: Vec<u32> = decode_ise(
+        data.reverse_bits(),
+        0,
+        weights_count,
+        block_mode.weight_range,
+    )
+    .into_iter()
+    .map(|w| unquantize_weight(w, block_mode.weight_range))
+    .collect();
+    let get_weight = |i: u32, plane: usize| -> u32 {
+        let i = i as usize * planes + plane;
+        return if i < weights.len() { weights[i] } else { 0 };
+    };
+    let ds = (1024 + bw / 2) / (bw - 1);
+    let dt = (1024 + bh / 2) / (bh - 1);
+    let seed = read(data, 13, 10);
+    let small = bw * bh < 31;
+    for t in 0..bh {
+        for s in 0..bw {
+            let gs = (ds * s * (gw - 1) + 32) >> 6;
+            let gt = (dt * t * (gh - 1) + 32) >> 6;
+            let (js, fs) = (gs >> 4, gs & 0xF);
+            let (jt, ft) = (gt >> 4, gt & 0xF);
+            let w11 = (fs * ft + 8) >> 4;
+            let w10 = ft - w11;
+            let w01 = fs - w11;
+            let w00 = 16 - fs - ft + w11;
+            let v0 = js + jt * gw;
+            let mut plane_weights = [0u32; 2];
+            for (p, w) in plane_weights.iter_mut().enumerate().take(planes) {
+                *w = (get_weight(v0, p) * w00
+                    + get_weight(v0 + 1, p) * w01
+                    + get_weight(v0 + gw, p) * w10
+                    + get_weight(v0 + gw + 1, p) * w11
+                    + 8)
+                    >> 4;
+            }
+            let partition = select_partition(seed, s, t, partitions, small);
+            let (e0, e1) = &endpoints[partition];
+            let texel = &mut texels[(t * bw + s) as usize];
+            for c in 0..4 {
+                let w = if block_mode.dual_plane && c == ccs {
+                    plane_weights[1]
+                } else {
+                    plane_weights[0]
+                };
+                let c0 = e0[c] as u32 * 257;
+                let c1 = e1[c] as u32 * 257;
+                texel[c] = (((c0 * (64 - w) + c1 * w + 32) >> 6) >> 8) as u8;
+            }
+        }
+    }
+    return Some(());
+}
+
+/// Decodes the LDR blocks, the HDR ones get the error color.
+pub(super) fn decode(block: &[u8], bw: u32, bh: u32, texels: &mut [[u8; 4]]) {
+    let mut b = [0u8; 16];
+    b.copy_from_slice(&block[..16]);
+    if decode_block(u128::from_le_bytes(b), bw, bh, texels).is_none() {
+        for t in texels.iter_mut() {
+            *t = ERROR_COLOR;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::BitWriter;
+    use super::*;
+
+    #[test]
+    fn ise_test() {
+        let mut trits = vec![false; 243];
+        for t in 0..256 {
+            let d = decode_trits(t);
+            trits[(d[0] + d[1] * 3 + d[2] * 9 + d[3] * 27 + d[4] * 81) as usize] = true;
+        }
+        assert!(trits.iter().all(|t| *t));
+        let mut quints = vec![false; 125];
+        for q in 0..128 {
+            let d = decode_quints(q);
+            quints[(d[0] + d[1] * 5 + d[2] * 25) as usize] = true;
+        }
+        assert!(quints.iter().all(|q| *q));
+        let colors: Vec<i32> = (0..6).map(|v| unquantize_color(v, 4)).collect();
+        assert_eq!(colors, vec![0, 255, 51, 204, 102, 153]);
+        let weights: Vec<u32> = (0..3).map(|v| unquantize_weight(v, 1)).collect();
+        assert_eq!(weights, vec![0, 32, 64]);
+    }
+
+    #[test]
+    fn void_extent_test() {
+        let mut w = BitWriter::new();
+        w.write(0x1FC, 9);
+        w.write(0, 55);
+        for c in &[0xFF00, 0x8000, 0x1234, 0xFFFF] {
+            w.write(*c, 16);
+        }
+        let mut texels = [[0u8; 4]; 16];
+        decode(&w.get_block(), 4, 4, &mut texels);
+        assert_eq!(texels[0], [255, 128, 18, 255]);
+        assert_eq!(texels[15], [255, 128, 18, 255]);
+        let mut w = BitWriter::new();
+        w.write(0, 11);
+        decode(&w.get_block(), 4, 4, &mut texels);
+        assert_eq!(texels[5], ERROR_COLOR);
+    }
+
+    #[test]
+    fn single_partition_test() {
+        // 4x4 grid of 2-bit weights and the RGB direct endpoint mode
+        let mut w = BitWriter::new();
+        w.write(0x42, 11);
+        w.write(0, 2);
+        w.write(8, 4);
+        for v in &[200, 0, 0, 0, 0, 255] {
+            w.write(*v, 8);
+        }
+        let mut weights = BitWriter::new();
+        for v in &[0, 3, 1] {
+            weights.write(*v, 2);
+        }
+        let data = u128::from_le_bytes(w.get_block())
+            | u128::from_le_bytes(weights.get_block()).reverse_bits();
+        let mut texels = [[0u8; 4]; 16];
+        decode(&data.to_le_bytes(), 4, 4, &mut texels);
+        assert_eq!(texels[0], [200, 0, 0, 255]);
+        assert_eq!(texels[1], [0, 0, 255, 255]);
+        assert_eq!(texels[2], [134, 0, 84, 255]);
+        assert_eq!(texels[3], [200, 0, 0, 255]);
+    }
+}
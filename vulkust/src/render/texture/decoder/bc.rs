@@ -0,0 +1,130 @@
+fn convert_565(c: u16) -> [i32; 3] {
+    let r = ((c >> 11) & 31) as i32;
+    let g = ((c >> 5) & 63) as i32;
+    let b = (c & 31) as i32;
+    return [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ];
+}
+
+/// BC2 and BC3 colors never have the three colors mode of BC1.
+pub(super) fn decode_color(block: &[u8], is_bc1: bool, has_alpha: bool, texels: &mut [[u8; 4]]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let e0 = convert_565(c0);
+    let e1 = convert_565(c1);
+    let mut palette = [[0u8; 4]; 4];
+    for i in 0..3 {
+        palette[0][i] = e0[i] as u8;
+        palette[1][i] = e1[i] as u8;
+        if c0 > c1 || !is_bc1 {
+            palette[2][i] = ((2 * e0[i] + e1[i] + 1) / 3) as u8;
+            palette[3][i] = ((e0[i] + 2 * e1[i] + 1) / 3) as u8;
+        } else {
+            palette[2][i] = ((e0[i] + e1[i] + 1) / 2) as u8;
+        }
+    }
+    palette[0][3] = 255;
+    palette[1][3] = 255;
+    palette[2][3] = 255;
+    palette[3][3] = if c0 > c1 || !is_bc1 || !has_alpha {
+        255
+    } else {
+        0
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, t) in texels.iter_mut().enumerate() {
+        *t = palette[((indices >> (i * 2)) & 3) as usize];
+    }
+}
+
+pub(super) fn decode_explicit_alpha(block: &[u8], texels: &mut [[u8; 4]]) {
+    for (i, t) in texels.iter_mut().enumerate() {
+        t[3] = ((block[i >> 1] >> ((i & 1) * 4)) & 15) * 17;
+    }
+}
+
+/// Decodes a BC4 block into the channel of the texels.
+pub(super) fn decode_channel(block: &[u8], signed: bool, channel: usize, texels: &mut [[u8; 4]]) {
+    let (e0, e1, min, max) = if signed {
+        let e0 = (block[0] as i8).max(-127) as i32;
+        let e1 = (block[1] as i8).max(-127) as i32;
+        (e0, e1, -127, 127)
+    } else {
+        (block[0] as i32, block[1] as i32, 0, 255)
+    };
+    let mut palette = [e0, e1, 0, 0, 0, 0, min, max];
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as i32) * e0 + i as i32 * e1 + 3) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as i32) * e0 + i as i32 * e1 + 2) / 5;
+        }
+    }
+    let mut bits = [0u8; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    for (i, t) in texels.iter_mut().enumerate() {
+        let v = palette[((indices >> (i * 3)) & 7) as usize];
+        t[channel] = if signed {
+            (((v + 127) * 255 + 127) / 254) as u8
+        } else {
+            v as u8
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bc1_test() {
+        let mut texels = [[0u8; 4]; 16];
+        // red and blue with the indices 0, 1, 2, 3 repeated
+        decode_color(
+            &[0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4],
+            true,
+            true,
+            &mut texels,
+        );
+        assert_eq!(texels[0], [255, 0, 0, 255]);
+        assert_eq!(texels[1], [0, 0, 255, 255]);
+        assert_eq!(texels[2], [170, 0, 85, 255]);
+        assert_eq!(texels[3], [85, 0, 170, 255]);
+        decode_color(
+            &[0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4],
+            true,
+            true,
+            &mut texels,
+        );
+        assert_eq!(texels[2], [128, 0, 128, 255]);
+        assert_eq!(texels[3], [0, 0, 0, 0]);
+        decode_color(
+            &[0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4],
+            true,
+            false,
+            &mut texels,
+        );
+        assert_eq!(texels[3], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc4_test() {
+        let mut texels = [[0u8; 4]; 16];
+        // the first texels have the indices 0, 1, 2, 7
+        decode_channel(&[200, 60, 0x88, 0x0E, 0, 0, 0, 0], false, 0, &mut texels);
+        assert_eq!(texels[0][0], 200);
+        assert_eq!(texels[1][0], 60);
+        assert_eq!(texels[2][0], 180);
+        assert_eq!(texels[3][0], 80);
+        decode_channel(&[0x81, 0x7F, 0x88, 0x0E, 0, 0, 0, 0], true, 1, &mut texels);
+        assert_eq!(texels[0][1], 0);
+        assert_eq!(texels[1][1], 255);
+        assert_eq!(texels[3][1], 255);
+    }
+}
@@ -0,0 +1,607 @@
+use super::bc7::{get_weight, ANCHORS_2, PARTITIONS_2};
+use super::BitReader;
+
+const RW: u8 = 0;
+const GW: u8 = 1;
+const BW: u8 = 2;
+const RX: u8 = 3;
+const GX: u8 = 4;
+const BX: u8 = 5;
+const RY: u8 = 6;
+const GY: u8 = 7;
+const BY: u8 = 8;
+const RZ: u8 = 9;
+const GZ: u8 = 10;
+const BZ: u8 = 11;
+const D: u8 = 12;
+/// The bits of the field are stored from the most significant one.
+const REVERSED: u8 = 0x80;
+
+struct Mode {
+    transformed: bool,
+    partitioned: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    /// Field, first bit and bits count of the fields after the mode bits.
+    layout: &'static [(u8, u8, u8)],
+}
+
+const MODE_1: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 10,
+    delta_bits: [5, 5, 5],
+    layout: &[
+        (GY, 4, 1),
+        (BY, 4, 1),
+        (BZ, 4, 1),
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 5),
+        (GZ, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 5),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 5),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 5),
+        (BZ, 2, 1),
+        (RZ, 0, 5),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_2: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 7,
+    delta_bits: [6, 6, 6],
+    layout: &[
+        (GY, 5, 1),
+        (GZ, 4, 1),
+        (GZ, 5, 1),
+        (RW, 0, 7),
+        (BZ, 0, 1),
+        (BZ, 1, 1),
+        (BY, 4, 1),
+        (GW, 0, 7),
+        (BY, 5, 1),
+        (BZ, 2, 1),
+        (GY, 4, 1),
+        (BW, 0, 7),
+        (BZ, 3, 1),
+        (BZ, 5, 1),
+        (BZ, 4, 1),
+        (RX, 0, 6),
+        (GY, 0, 4),
+        (GX, 0, 6),
+        (GZ, 0, 4),
+        (BX, 0, 6),
+        (BY, 0, 4),
+        (RY, 0, 6),
+        (RZ, 0, 6),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_3: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 11,
+    delta_bits: [5, 4, 4],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 5),
+        (RW, 10, 1),
+        (GY, 0, 4),
+        (GX, 0, 4),
+        (GW, 10, 1),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 4),
+        (BW, 10, 1),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 5),
+        (BZ, 2, 1),
+        (RZ, 0, 5),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_4: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 11,
+    delta_bits: [4, 5, 4],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 4),
+        (RW, 10, 1),
+        (GZ, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 5),
+        (GW, 10, 1),
+        (GZ, 0, 4),
+        (BX, 0, 4),
+        (BW, 10, 1),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 4),
+        (BZ, 0, 1),
+        (BZ, 2, 1),
+        (RZ, 0, 4),
+        (GY, 4, 1),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_5: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 11,
+    delta_bits: [4, 4, 5],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 4),
+        (RW, 10, 1),
+        (BY, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 4),
+        (GW, 10, 1),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 5),
+        (BW, 10, 1),
+        (BY, 0, 4),
+        (RY, 0, 4),
+        (BZ, 1, 1),
+        (BZ, 2, 1),
+        (RZ, 0, 4),
+        (BZ, 4, 1),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_6: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 9,
+    delta_bits: [5, 5, 5],
+    layout: &[
+        (RW, 0, 9),
+        (BY, 4, 1),
+        (GW, 0, 9),
+        (GY, 4, 1),
+        (BW, 0, 9),
+        (BZ, 4, 1),
+        (RX, 0, 5),
+        (GZ, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 5),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 5),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 5),
+        (BZ, 2, 1),
+        (RZ, 0, 5),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_7: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 8,
+    delta_bits: [6, 5, 5],
+    layout: &[
+        (RW, 0, 8),
+        (GZ, 4, 1),
+        (BY, 4, 1),
+        (GW, 0, 8),
+        (BZ, 2, 1),
+        (GY, 4, 1),
+        (BW, 0, 8),
+        (BZ, 3, 1),
+        (BZ, 4, 1),
+        (RX, 0, 6),
+        (GY, 0, 4),
+        (GX, 0, 5),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 5),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 6),
+        (RZ, 0, 6),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_8: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 8,
+    delta_bits: [5, 6, 5],
+    layout: &[
+        (RW, 0, 8),
+        (BZ, 0, 1),
+        (BY, 4, 1),
+        (GW, 0, 8),
+        (GY, 5, 1),
+        (GY, 4, 1),
+        (BW, 0, 8),
+        (GZ, 5, 1),
+        (BZ, 4, 1),
+        (RX, 0, 5),
+        (GZ, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 6),
+        (GZ, 0, 4),
+        (BX, 0, 5),
+        (BZ, 1, 1),
+        (BY, 0, 4),
+        (RY, 0, 5),
+        (BZ, 2, 1),
+        (RZ, 0, 5),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_9: Mode = Mode {
+    transformed: true,
+    partitioned: true,
+    endpoint_bits: 8,
+    delta_bits: [5, 5, 6],
+    layout: &[
+        (RW, 0, 8),
+        (BZ, 1, 1),
+        (BY, 4, 1),
+        (GW, 0, 8),
+        (BY, 5, 1),
+        (GY, 4, 1),
+        (BW, 0, 8),
+        (BZ, 5, 1),
+        (BZ, 4, 1),
+        (RX, 0, 5),
+        (GZ, 4, 1),
+        (GY, 0, 4),
+        (GX, 0, 5),
+        (BZ, 0, 1),
+        (GZ, 0, 4),
+        (BX, 0, 6),
+        (BY, 0, 4),
+        (RY, 0, 5),
+        (BZ, 2, 1),
+        (RZ, 0, 5),
+        (BZ, 3, 1),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_10: Mode = Mode {
+    transformed: false,
+    partitioned: true,
+    endpoint_bits: 6,
+    delta_bits: [6, 6, 6],
+    layout: &[
+        (RW, 0, 6),
+        (GZ, 4, 1),
+        (BZ, 0, 1),
+        (BZ, 1, 1),
+        (BY, 4, 1),
+        (GW, 0, 6),
+        (GY, 5, 1),
+        (BY, 5, 1),
+        (BZ, 2, 1),
+        (GY, 4, 1),
+        (BW, 0, 6),
+        (GZ, 5, 1),
+        (BZ, 3, 1),
+        (BZ, 5, 1),
+        (BZ, 4, 1),
+        (RX, 0, 6),
+        (GY, 0, 4),
+        (GX, 0, 6),
+        (GZ, 0, 4),
+        (BX, 0, 6),
+        (BY, 0, 4),
+        (RY, 0, 6),
+        (RZ, 0, 6),
+        (D, 0, 5),
+    ],
+};
+
+const MODE_11: Mode = Mode {
+    transformed: false,
+    partitioned: false,
+    endpoint_bits: 10,
+    delta_bits: [10, 10, 10],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 10),
+        (GX, 0, 10),
+        (BX, 0, 10),
+    ],
+};
+
+const MODE_12: Mode = Mode {
+    transformed: true,
+    partitioned: false,
+    endpoint_bits: 11,
+    delta_bits: [9, 9, 9],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 9),
+        (RW, 10, 1),
+        (GX, 0, 9),
+        (GW, 10, 1),
+        (BX, 0, 9),
+        (BW, 10, 1),
+    ],
+};
+
+const MODE_13: Mode = Mode {
+    transformed: true,
+    partitioned: false,
+    endpoint_bits: 12,
+    delta_bits: [8, 8, 8],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 8),
+        (RW | REVERSED, 10, 2),
+        (GX, 0, 8),
+        (GW | REVERSED, 10, 2),
+        (BX, 0, 8),
+        (BW | REVERSED, 10, 2),
+    ],
+};
+
+const MODE_14: Mode = Mode {
+    transformed: true,
+    partitioned: false,
+    endpoint_bits: 16,
+    delta_bits: [4, 4, 4],
+    layout: &[
+        (RW, 0, 10),
+        (GW, 0, 10),
+        (BW, 0, 10),
+        (RX, 0, 4),
+        (RW | REVERSED, 10, 6),
+        (GX, 0, 4),
+        (GW | REVERSED, 10, 6),
+        (BX, 0, 4),
+        (BW | REVERSED, 10, 6),
+    ],
+};
+
+fn sign_extend(v: i32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    return (v << shift) >> shift;
+}
+
+fn unquantize(v: i32, bits: u32, signed: bool) -> i32 {
+    if signed {
+        if bits >= 16 {
+            return v;
+        }
+        let (negative, v) = if v < 0 { (true, -v) } else { (false, v) };
+        let u = if v == 0 {
+            0
+        } else if v >= (1 << (bits - 1)) - 1 {
+            0x7FFF
+        } else {
+            ((v << 15) + 0x4000) >> (bits - 1)
+        };
+        return if negative { -u } else { u };
+    }
+    if bits >= 15 {
+        return v;
+    } else if v == 0 {
+        return 0;
+    } else if v == (1 << bits) - 1 {
+        return 0xFFFF;
+    }
+    return ((v << 16) + 0x8000) >> bits;
+}
+
+fn convert_half(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((h >> 10) & 0x1F) as i32;
+    let mantissa = (h & 0x3FF) as f32;
+    if exponent == 0 {
+        return sign * mantissa * (2.0f32).powi(-24);
+    } else if exponent == 31 {
+        return sign * std::f32::INFINITY;
+    }
+    return sign * (1.0 + mantissa / 1024.0) * (2.0f32).powi(exponent - 15);
+}
+
+fn finish_unquantize(v: i32, signed: bool) -> f32 {
+    if signed {
+        let h = if v < 0 {
+            0x8000 | ((-v * 31) >> 5) as u16
+        } else {
+            ((v * 31) >> 5) as u16
+        };
+        return convert_half(h);
+    }
+    return convert_half(((v * 31) >> 6) as u16);
+}
+
+/// HDR colors are clamped into the LDR range.
+pub(super) fn decode(block: &[u8], signed: bool, texels: &mut [[u8; 4]]) {
+    let mut reader = BitReader::new(block);
+    let mut mode_bits = reader.read(2);
+    if mode_bits > 1 {
+        mode_bits |= reader.read(3) << 2;
+    }
+    let mode = match mode_bits {
+        0 => &MODE_1,
+        1 => &MODE_2,
+        2 => &MODE_3,
+        6 => &MODE_4,
+        10 => &MODE_5,
+        14 => &MODE_6,
+        18 => &MODE_7,
+        22 => &MODE_8,
+        26 => &MODE_9,
+        30 => &MODE_10,
+        3 => &MODE_11,
+        7 => &MODE_12,
+        11 => &MODE_13,
+        15 => &MODE_14,
+        _ => {
+            for t in texels.iter_mut() {
+                *t = [0, 0, 0, 255];
+            }
+            return;
+        }
+    };
+    let mut fields = [0i32; 13];
+    for &(field, start, count) in mode.layout {
+        let mut v = reader.read(count as u32);
+        if field & REVERSED != 0 {
+            v = v.reverse_bits() >> (32 - count as u32);
+        }
+        fields[(field & !REVERSED) as usize] |= (v << start) as i32;
+    }
+    let partition = fields[D as usize] as usize;
+    let values_count = if mode.partitioned { 12 } else { 6 };
+    let bits = mode.endpoint_bits;
+    let mut endpoints = [0i32; 12];
+    endpoints.copy_from_slice(&fields[..12]);
+    if signed {
+        for c in 0..3 {
+            endpoints[c] = sign_extend(endpoints[c], bits);
+        }
+    }
+    if mode.transformed || signed {
+        for i in 3..values_count {
+            let b = if mode.transformed {
+                mode.delta_bits[i % 3]
+            } else {
+                bits
+            };
+            endpoints[i] = sign_extend(endpoints[i], b);
+        }
+    }
+    if mode.transformed {
+        for i in 3..values_count {
+            endpoints[i] = (endpoints[i] + endpoints[i % 3]) & ((1 << bits) - 1);
+            if signed {
+                endpoints[i] = sign_extend(endpoints[i], bits);
+            }
+        }
+    }
+    for e in endpoints[..values_count].iter_mut() {
+        *e = unquantize(*e, bits, signed);
+    }
+    let index_bits = if mode.partitioned { 3 } else { 4 };
+    for (i, t) in texels.iter_mut().enumerate() {
+        let is_anchor = i == 0 || (mode.partitioned && i == ANCHORS_2[partition]);
+        let index = reader.read(index_bits - if is_anchor { 1 } else { 0 });
+        let w = get_weight(index_bits, index);
+        let s = if mode.partitioned {
+            PARTITIONS_2[partition][i] as usize
+        } else {
+            0
+        };
+        for c in 0..3 {
+            let e0 = endpoints[s * 6 + c];
+            let e1 = endpoints[s * 6 + 3 + c];
+            let v = finish_unquantize(((64 - w) * e0 + w * e1 + 32) >> 6, signed);
+            t[c] = (v.max(0.0).min(1.0) * 255.0 + 0.5) as u8;
+        }
+        t[3] = 255;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::BitWriter;
+    use super::*;
+
+    #[test]
+    fn layouts_test() {
+        let modes = [
+            &MODE_1, &MODE_2, &MODE_3, &MODE_4, &MODE_5, &MODE_6, &MODE_7, &MODE_8, &MODE_9,
+            &MODE_10, &MODE_11, &MODE_12, &MODE_13, &MODE_14,
+        ];
+        for (mi, mode) in modes.iter().enumerate() {
+            let mut masks = [0u32; 13];
+            let mut count = if mi < 2 { 2 } else { 5 };
+            for &(field, start, bits) in mode.layout {
+                let mask = ((1u32 << bits) - 1) << start;
+                let field = (field & !REVERSED) as usize;
+                assert_eq!(masks[field] & mask, 0);
+                masks[field] |= mask;
+                count += bits as u32;
+            }
+            let indices = if mode.partitioned { 46 } else { 63 };
+            assert_eq!(count + indices, 128);
+            let values_count = if mode.partitioned { 12 } else { 6 };
+            for i in 0..values_count {
+                let bits = if i < 3 {
+                    mode.endpoint_bits
+                } else {
+                    mode.delta_bits[i % 3]
+                };
+                assert_eq!(masks[i], (1 << bits) - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn mode11_test() {
+        let mut w = BitWriter::new();
+        w.write(3, 2);
+        w.write(0, 3);
+        for v in &[1023, 0, 462, 0, 1023, 0] {
+            w.write(*v, 10);
+        }
+        w.write(0, 3);
+        w.write(15, 4);
+        let mut texels = [[0u8; 4]; 16];
+        decode(&w.get_block(), false, &mut texels);
+        assert_eq!(texels[0], [255, 0, 128, 255]);
+        assert_eq!(texels[1], [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn mode14_test() {
+        let mut w = BitWriter::new();
+        w.write(3, 2);
+        w.write(3, 3);
+        w.write(0, 30);
+        w.write(0, 4);
+        w.write(14, 6);
+        let mut texels = [[0u8; 4]; 16];
+        decode(&w.get_block(), false, &mut texels);
+        assert_eq!(texels[0], [100, 0, 0, 255]);
+        assert_eq!(texels[15], [100, 0, 0, 255]);
+    }
+}
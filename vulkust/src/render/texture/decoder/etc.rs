@@ -0,0 +1,264 @@
+const MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const ALPHA_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+#[inline]
+fn bits(v: u64, start: u32, count: u32) -> i32 {
+    return ((v >> start) & ((1 << count) - 1)) as i32;
+}
+
+#[inline]
+fn clamp(v: i32) -> u8 {
+    return v.max(0).min(255) as u8;
+}
+
+fn extend_4(v: i32) -> i32 {
+    return (v << 4) | v;
+}
+
+fn extend_5(v: i32) -> i32 {
+    return (v << 3) | (v >> 2);
+}
+
+fn offset(c: &[i32; 3], d: i32) -> [u8; 4] {
+    return [clamp(c[0] + d), clamp(c[1] + d), clamp(c[2] + d), 255];
+}
+
+/// Texels of the block are indexed in the column major order.
+fn get_index(v: u64, x: usize, y: usize) -> usize {
+    let i = (x * 4 + y) as u32;
+    return ((bits(v, i + 16, 1) << 1) | bits(v, i, 1)) as usize;
+}
+
+/// Punch-through alpha blocks use the differential bit as the opaque flag.
+pub(super) fn decode_color(block: &[u8], punch_through: bool, texels: &mut [[u8; 4]]) {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(&block[..8]);
+    let v = u64::from_be_bytes(b);
+    let differential = bits(v, 33, 1) == 1 || punch_through;
+    let opaque = !punch_through || bits(v, 33, 1) == 1;
+    if differential {
+        let r = bits(v, 59, 5);
+        let g = bits(v, 51, 5);
+        let b = bits(v, 43, 5);
+        let dr = (bits(v, 56, 3) << 29) >> 29;
+        let dg = (bits(v, 48, 3) << 29) >> 29;
+        let db = (bits(v, 40, 3) << 29) >> 29;
+        if r + dr < 0 || r + dr > 31 {
+            return decode_t(v, opaque, texels);
+        } else if g + dg < 0 || g + dg > 31 {
+            return decode_h(v, opaque, texels);
+        } else if b + db < 0 || b + db > 31 {
+            return decode_planar(v, texels);
+        }
+        let colors = [
+            [extend_5(r), extend_5(g), extend_5(b)],
+            [extend_5(r + dr), extend_5(g + dg), extend_5(b + db)],
+        ];
+        return decode_subblocks(v, &colors, opaque, texels);
+    }
+    let colors = [
+        [
+            extend_4(bits(v, 60, 4)),
+            extend_4(bits(v, 52, 4)),
+            extend_4(bits(v, 44, 4)),
+        ],
+        [
+            extend_4(bits(v, 56, 4)),
+            extend_4(bits(v, 48, 4)),
+            extend_4(bits(v, 40, 4)),
+        ],
+    ];
+    decode_subblocks(v, &colors, true, texels);
+}
+
+fn decode_subblocks(v: u64, colors: &[[i32; 3]; 2], opaque: bool, texels: &mut [[u8; 4]]) {
+    let tables = [bits(v, 37, 3) as usize, bits(v, 34, 3) as usize];
+    let flip = bits(v, 32, 1) == 1;
+    for y in 0..4 {
+        for x in 0..4 {
+            let s = if flip { y / 2 } else { x / 2 };
+            let m = MODIFIERS[tables[s]];
+            let index = get_index(v, x, y);
+            let t = &mut texels[y * 4 + x];
+            *t = match (index, opaque) {
+                (2, false) => [0, 0, 0, 0],
+                (0, false) => offset(&colors[s], 0),
+                (0, _) => offset(&colors[s], m[0]),
+                (1, _) => offset(&colors[s], m[1]),
+                (2, _) => offset(&colors[s], -m[0]),
+                _ => offset(&colors[s], -m[1]),
+            };
+        }
+    }
+}
+
+fn decode_paints(v: u64, paints: &[[u8; 4]; 4], opaque: bool, texels: &mut [[u8; 4]]) {
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = get_index(v, x, y);
+            texels[y * 4 + x] = if index == 2 && !opaque {
+                [0, 0, 0, 0]
+            } else {
+                paints[index]
+            };
+        }
+    }
+}
+
+fn decode_t(v: u64, opaque: bool, texels: &mut [[u8; 4]]) {
+    let c0 = [
+        extend_4((bits(v, 59, 2) << 2) | bits(v, 56, 2)),
+        extend_4(bits(v, 52, 4)),
+        extend_4(bits(v, 48, 4)),
+    ];
+    let c1 = [
+        extend_4(bits(v, 44, 4)),
+        extend_4(bits(v, 40, 4)),
+        extend_4(bits(v, 36, 4)),
+    ];
+    let d = DISTANCES[((bits(v, 34, 2) << 1) | bits(v, 32, 1)) as usize];
+    let paints = [
+        offset(&c0, 0),
+        offset(&c1, d),
+        offset(&c1, 0),
+        offset(&c1, -d),
+    ];
+    decode_paints(v, &paints, opaque, texels);
+}
+
+fn decode_h(v: u64, opaque: bool, texels: &mut [[u8; 4]]) {
+    let c0 = [
+        bits(v, 59, 4),
+        (bits(v, 56, 3) << 1) | bits(v, 52, 1),
+        (bits(v, 51, 1) << 3) | bits(v, 47, 3),
+    ];
+    let c1 = [bits(v, 43, 4), bits(v, 39, 4), bits(v, 35, 4)];
+    let packed0 = (c0[0] << 8) | (c0[1] << 4) | c0[2];
+    let packed1 = (c1[0] << 8) | (c1[1] << 4) | c1[2];
+    let order = if packed0 >= packed1 { 1 } else { 0 };
+    let index = (bits(v, 34, 1) << 2) | (bits(v, 32, 1) << 1) | order;
+    let d = DISTANCES[index as usize];
+    let c0 = [extend_4(c0[0]), extend_4(c0[1]), extend_4(c0[2])];
+    let c1 = [extend_4(c1[0]), extend_4(c1[1]), extend_4(c1[2])];
+    let paints = [
+        offset(&c0, d),
+        offset(&c0, -d),
+        offset(&c1, d),
+        offset(&c1, -d),
+    ];
+    decode_paints(v, &paints, opaque, texels);
+}
+
+fn decode_planar(v: u64, texels: &mut [[u8; 4]]) {
+    let extend_6 = |c: i32| (c << 2) | (c >> 4);
+    let extend_7 = |c: i32| (c << 1) | (c >> 6);
+    let o = [
+        extend_6(bits(v, 57, 6)),
+        extend_7((bits(v, 56, 1) << 6) | bits(v, 49, 6)),
+        extend_6((bits(v, 48, 1) << 5) | (bits(v, 43, 2) << 3) | bits(v, 39, 3)),
+    ];
+    let h = [
+        extend_6((bits(v, 34, 5) << 1) | bits(v, 32, 1)),
+        extend_7(bits(v, 25, 7)),
+        extend_6(bits(v, 19, 6)),
+    ];
+    let vr = [
+        extend_6(bits(v, 13, 6)),
+        extend_7(bits(v, 6, 7)),
+        extend_6(bits(v, 0, 6)),
+    ];
+    for y in 0..4 {
+        for x in 0..4 {
+            let t = &mut texels[y * 4 + x];
+            for c in 0..3 {
+                let p = x as i32 * (h[c] - o[c]) + y as i32 * (vr[c] - o[c]) + 4 * o[c] + 2;
+                t[c] = clamp(p >> 2);
+            }
+            t[3] = 255;
+        }
+    }
+}
+
+pub(super) fn decode_alpha(block: &[u8], texels: &mut [[u8; 4]]) {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(&block[..8]);
+    let v = u64::from_be_bytes(b);
+    let base = bits(v, 56, 8);
+    let multiplier = bits(v, 52, 4);
+    let modifiers = &ALPHA_MODIFIERS[bits(v, 48, 4) as usize];
+    for y in 0..4 {
+        for x in 0..4 {
+            let i = (x * 4 + y) as u32;
+            let index = bits(v, 45 - i * 3, 3) as usize;
+            texels[y * 4 + x][3] = clamp(base + modifiers[index] * multiplier);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn individual_test() {
+        // left half (9, 0, 0) with table 0, right half (0, 9, 0) with table 7
+        let block = [0x90, 0x09, 0x00, 0b0001_1100, 0x00, 0x08, 0x80, 0x00];
+        let mut texels = [[0u8; 4]; 16];
+        decode_color(&block, false, &mut texels);
+        assert_eq!(texels[0], [155, 2, 2, 255]);
+        assert_eq!(texels[12], [151, 0, 0, 255]);
+        assert_eq!(texels[2], [47, 200, 47, 255]);
+        assert_eq!(texels[15], [183, 255, 183, 255]);
+    }
+
+    #[test]
+    fn alpha_test() {
+        // base 128, multiplier 2 and the indices 0, 7 for the first column
+        let block = [128, 0x20, 0x1C, 0x00, 0, 0, 0, 0];
+        let mut texels = [[0u8; 4]; 16];
+        decode_alpha(&block, &mut texels);
+        assert_eq!(texels[0][3], 122);
+        assert_eq!(texels[4][3], 156);
+        assert_eq!(texels[1][3], 122);
+    }
+
+    #[test]
+    fn planar_test() {
+        // blue overflows and gives BO = 26 and the rest are zero
+        let block = [0, 0, 0xF9, 0x02, 0, 0, 0, 0];
+        let mut texels = [[0u8; 4]; 16];
+        decode_color(&block, false, &mut texels);
+        assert_eq!(texels[0], [0, 0, 105, 255]);
+        assert_eq!(texels[1], [0, 0, 79, 255]);
+        assert_eq!(texels[15], [0, 0, 0, 255]);
+    }
+}
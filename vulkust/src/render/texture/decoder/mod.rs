@@ -0,0 +1,140 @@
+mod astc;
+mod bc;
+mod bc6h;
+mod bc7;
+mod etc;
+
+use super::super::image::TextureFormat;
+
+/// Reads the bits of a block from the least significant one.
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn new(block: &[u8]) -> Self {
+        let mut b = [0u8; 16];
+        let size = block.len().min(16);
+        b[..size].copy_from_slice(&block[..size]);
+        return Self {
+            bits: u128::from_le_bytes(b),
+            position: 0,
+        };
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        if count == 0 || self.position >= 128 {
+            self.position += count;
+            return 0;
+        }
+        let v = ((self.bits >> self.position) as u32) & (((1u64 << count) - 1) as u32);
+        self.position += count;
+        return v;
+    }
+}
+
+/// Decodes an image of the compressed format into RGBA bytes.
+pub(crate) fn decode(format: TextureFormat, width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+    let (bw, bh) = format.get_block_dimensions();
+    let block_size = format.get_block_size();
+    let blocks_x = (width + bw - 1) / bw;
+    let blocks_y = (height + bh - 1) / bh;
+    let mut result = vec![0u8; (width * height * 4) as usize];
+    let mut texels = vec![[0u8; 4]; (bw * bh) as usize];
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let offset = ((by * blocks_x + bx) as usize) * block_size;
+            let b = &data[offset..offset + block_size];
+            for t in texels.iter_mut() {
+                *t = [0, 0, 0, 255];
+            }
+            match format {
+                TextureFormat::Bc1Rgb => bc::decode_color(b, true, false, &mut texels),
+                TextureFormat::Bc1Rgba => bc::decode_color(b, true, true, &mut texels),
+                TextureFormat::Bc2 => {
+                    bc::decode_color(&b[8..], false, false, &mut texels);
+                    bc::decode_explicit_alpha(&b[..8], &mut texels);
+                }
+                TextureFormat::Bc3 => {
+                    bc::decode_color(&b[8..], false, false, &mut texels);
+                    bc::decode_channel(&b[..8], false, 3, &mut texels);
+                }
+                TextureFormat::Bc4 => bc::decode_channel(b, false, 0, &mut texels),
+                TextureFormat::Bc4Signed => bc::decode_channel(b, true, 0, &mut texels),
+                TextureFormat::Bc5 | TextureFormat::Bc5Signed => {
+                    let signed = format == TextureFormat::Bc5Signed;
+                    bc::decode_channel(&b[..8], signed, 0, &mut texels);
+                    bc::decode_channel(&b[8..], signed, 1, &mut texels);
+                }
+                TextureFormat::Bc6h => bc6h::decode(b, false, &mut texels),
+                TextureFormat::Bc6hSigned => bc6h::decode(b, true, &mut texels),
+                TextureFormat::Bc7 => bc7::decode(b, &mut texels),
+                TextureFormat::Etc2Rgb => etc::decode_color(b, false, &mut texels),
+                TextureFormat::Etc2RgbA1 => etc::decode_color(b, true, &mut texels),
+                TextureFormat::Etc2Rgba => {
+                    etc::decode_color(&b[8..], false, &mut texels);
+                    etc::decode_alpha(&b[..8], &mut texels);
+                }
+                TextureFormat::Astc(w, h) => astc::decode(b, w as u32, h as u32, &mut texels),
+                TextureFormat::RgbaByte => vx_unexpected!(),
+            }
+            for y in 0..bh {
+                let py = by * bh + y;
+                if py >= height {
+                    break;
+                }
+                for x in 0..bw {
+                    let px = bx * bw + x;
+                    if px >= width {
+                        break;
+                    }
+                    let o = ((py * width + px) * 4) as usize;
+                    result[o..o + 4].copy_from_slice(&texels[(y * bw + x) as usize]);
+                }
+            }
+        }
+    }
+    return result;
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+
+    pub(super) struct BitWriter {
+        bits: u128,
+        position: u32,
+    }
+
+    impl BitWriter {
+        pub(super) fn new() -> Self {
+            return Self {
+                bits: 0,
+                position: 0,
+            };
+        }
+
+        pub(super) fn write(&mut self, v: u32, count: u32) {
+            if count > 0 {
+                self.bits |= ((v as u128) & ((1u128 << count) - 1)) << self.position;
+            }
+            self.position += count;
+        }
+
+        pub(super) fn get_block(&self) -> [u8; 16] {
+            return self.bits.to_le_bytes();
+        }
+    }
+
+    #[test]
+    fn clipping_test() {
+        // one BC1 block of red for a 2x3 image
+        let data = [0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0];
+        let result = decode(TextureFormat::Bc1Rgb, 2, 3, &data);
+        assert_eq!(result.len(), 24);
+        for t in result.chunks(4) {
+            assert_eq!(t, &[255, 0, 0, 255]);
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use super::super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
+// use super::super::super::core::gx3d::{Gx3DReader, Table as Gx3dTable};
 use super::super::super::core::object::Object as CoreObject;
-use super::super::super::core::types::{Id, TypeId};
+use super::super::super::core::types::Id;
+use super::super::super::gapi::engine::Engine as GraphicApiEngine;
 use super::super::engine::Engine;
-use super::super::gapi::GraphicApiEngine;
 use super::super::image::View as ImageView;
 use super::super::sampler::Sampler;
-use super::{Cube, Loadable, Texture, Texture2D, TextureType};
+use super::{Loadable, Texture, Texture2D};
 use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock, Weak};
 
@@ -17,7 +17,8 @@ pub struct Manager {
     textures: BTreeMap<Id, Weak<RwLock<dyn Texture>>>,
     name_to_id: BTreeMap<String, Id>,
     color_to_id: BTreeMap<[u8; 4], Id>,
-    gx3d_table: Option<Gx3dTable>,
+    // Gx3D reader is not ported yet.
+    // gx3d_table: Option<Gx3dTable>,
 }
 
 impl Manager {
@@ -27,13 +28,12 @@ impl Manager {
             textures: BTreeMap::new(),
             name_to_id: BTreeMap::new(),
             color_to_id: BTreeMap::new(),
-            gx3d_table: None,
         }
     }
 
-    pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
-        self.gx3d_table = Some(gx3d_table);
-    }
+    // pub(crate) fn set_gx3d_table(&mut self, gx3d_table: Gx3dTable) {
+    //     self.gx3d_table = Some(gx3d_table);
+    // }
 
     pub fn load_gltf<T>(
         &mut self,
@@ -44,12 +44,16 @@ impl Manager {
     where
         T: 'static + Loadable + Texture,
     {
-        let name = vx_unwrap!(texture.source().name()).to_string();
-        if let Some(id) = self.name_to_id.get(&name) {
-            if let Some(t) = self.textures.get(id) {
-                if let Some(t) = t.upgrade() {
-                    vx_log_i!("cached");
-                    return t;
+        vx_profile_zone!("texture::load_gltf");
+        // Images without name are not shared.
+        let name = texture.source().name().map(|n| n.to_string());
+        if let Some(name) = &name {
+            if let Some(id) = self.name_to_id.get(name) {
+                if let Some(t) = self.textures.get(id) {
+                    if let Some(t) = t.upgrade() {
+                        vx_log_i!("cached");
+                        return t;
+                    }
                 }
             }
         }
@@ -57,31 +61,35 @@ impl Manager {
             Arc::new(RwLock::new(T::new_with_gltf(texture, engine, data)));
         let id = vx_result!(texture.read()).get_id();
         let weak = Arc::downgrade(&texture);
-        self.name_to_id.insert(name, id);
+        if let Some(name) = name {
+            self.name_to_id.insert(name, id);
+        }
         self.textures.insert(id, weak);
         return texture;
     }
 
-    pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Texture>> {
-        if let Some(t) = self.textures.get(&id) {
-            if let Some(t) = t.upgrade() {
-                return t;
-            }
-        }
-        let table = vx_unwrap!(&mut self.gx3d_table);
-        table.goto(id);
-        let reader: &mut Gx3DReader = &mut table.get_mut_reader();
-        let t = reader.read_type_id();
-        let texture: Arc<RwLock<dyn Texture>> = if t == TextureType::T2D as TypeId {
-            Arc::new(RwLock::new(Texture2D::new_with_gx3d(engine, reader, id)))
-        } else if t == TextureType::Cube as TypeId {
-            Arc::new(RwLock::new(Cube::new_with_gx3d(engine, reader, id)))
-        } else {
-            vx_unexpected!();
-        };
-        self.textures.insert(id, Arc::downgrade(&texture));
-        return texture;
-    }
+    // Gx3D reader is not ported yet.
+    // pub fn load_gx3d(&mut self, engine: &Engine, id: Id) -> Arc<RwLock<dyn Texture>> {
+    //     vx_profile_zone!("texture::load_gx3d");
+    //     if let Some(t) = self.textures.get(&id) {
+    //         if let Some(t) = t.upgrade() {
+    //             return t;
+    //         }
+    //     }
+    //     let table = vx_unwrap!(&mut self.gx3d_table);
+    //     table.goto(id);
+    //     let reader: &mut Gx3DReader = &mut table.get_mut_reader();
+    //     let t = reader.read_type_id();
+    //     let texture: Arc<RwLock<dyn Texture>> = if t == TextureType::T2D as TypeId {
+    //         Arc::new(RwLock::new(Texture2D::new_with_gx3d(engine, reader, id)))
+    //     } else if t == TextureType::Cube as TypeId {
+    //         Arc::new(RwLock::new(Cube::new_with_gx3d(engine, reader, id)))
+    //     } else {
+    //         vx_unexpected!();
+    //     };
+    //     self.textures.insert(id, Arc::downgrade(&texture));
+    //     return texture;
+    // }
 
     pub fn create_2d_with_pixels(
         &mut self,
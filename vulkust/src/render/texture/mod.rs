@@ -1,17 +1,28 @@
 use super::super::core::object::Object as CoreObject;
+#[cfg(not(vulkan_api))]
 use super::image::View as ImageView;
+#[cfg(not(vulkan_api))]
 use super::sampler::Sampler;
+#[cfg(not(vulkan_api))]
 use std::sync::Arc;
 
-pub mod cube;
+pub(crate) mod container;
+pub(crate) mod decoder;
+// Cube textures wait for the skybox, that is not ported yet.
+// pub mod cube;
+#[cfg(not(vulkan_api))]
 pub mod manager;
+#[cfg(not(vulkan_api))]
 pub mod t2d;
 
-pub use self::cube::Cube;
+// pub use self::cube::Cube;
+#[cfg(not(vulkan_api))]
 pub use self::manager::Manager;
+#[cfg(not(vulkan_api))]
 pub use self::t2d::Texture2D;
+#[cfg(not(vulkan_api))]
 use super::engine::Engine;
-use crate::core::{gx3d::Gx3DReader, types::Id};
+// use crate::core::{gx3d::Gx3DReader, types::Id};
 
 #[repr(u8)]
 #[cfg_attr(debug_mode, derive(Debug))]
@@ -21,12 +32,16 @@ pub enum TextureType {
     Cube = 3,
 }
 
-pub trait Texture: CoreObject {
+// Image views and samplers of the Vulkan backend are not ported yet.
+#[cfg(not(vulkan_api))]
+pub trait Texture: CoreObject + Send + Sync {
     fn get_image_view(&self) -> &Arc<ImageView>;
     fn get_sampler(&self) -> &Arc<Sampler>;
 }
 
+#[cfg(not(vulkan_api))]
 pub trait Loadable: Sized {
     fn new_with_gltf(gltf: &gltf::Texture, engine: &Engine, data: &[u8]) -> Self;
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self;
+    // Gx3D reader is not ported yet.
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self;
 }
@@ -1,10 +1,11 @@
-use super::super::super::core::gx3d::Gx3DReader;
+// use super::super::super::core::gx3d::Gx3DReader;
 use super::super::super::core::object::{Base as ObjectBase, Object as CoreObject};
-use super::super::super::core::types::{Id, Size};
+use super::super::super::core::types::Id;
+use super::super::super::gapi::engine::Engine as GraphicApiEngine;
 use super::super::engine::Engine;
-use super::super::gapi::GraphicApiEngine;
 use super::super::image::View as ImageView;
 use super::super::sampler::Sampler;
+use super::container::{self, Container};
 use super::{Loadable, Texture};
 use std::sync::Arc;
 
@@ -57,6 +58,24 @@ impl Texture2D {
         return Self::new_with_base_pixels_name(obj_base, width, height, engine, data, None);
     }
 
+    /// Uploads every level, layer and face of the container, the compressed
+    /// formats that the device can not sample are decoded on CPU.
+    pub(crate) fn new_with_base_container_name(
+        obj_base: ObjectBase,
+        engine: &GraphicApiEngine,
+        container: &Container,
+        name: Option<String>,
+    ) -> Self {
+        let image_view = engine.create_texture_with_container(container);
+        let sampler = engine.get_linear_repeat_sampler().clone();
+        Texture2D {
+            obj_base,
+            name,
+            image_view,
+            sampler,
+        }
+    }
+
     pub(super) fn new_with_view_sampler(image_view: Arc<ImageView>, sampler: Arc<Sampler>) -> Self {
         Texture2D {
             obj_base: ObjectBase::new(),
@@ -85,13 +104,13 @@ impl Texture for Texture2D {
 
 impl Loadable for Texture2D {
     fn new_with_gltf(texture: &gltf::Texture, engine: &Engine, data: &[u8]) -> Self {
-        let name = vx_unwrap!(texture.source().name()).to_string();
+        let name = texture.source().name().map(|n| n.to_string());
         let obj_base = ObjectBase::new();
         let view = match texture.source().source() {
             gltf::image::Source::View { view, mime_type: _ } => view,
             _ => vx_log_f!("Only embeded and view texture resources is acceptable."),
         };
-        if let Some(_) = view.stride() {
+        if view.stride().is_some() {
             vx_log_f!("Stride is not acceptable in textures.");
         }
         let offset = view.offset();
@@ -100,21 +119,36 @@ impl Loadable for Texture2D {
             gltf::buffer::Source::Bin => {}
             _ => vx_log_f!("Only embeded and view texture resources is acceptable."),
         }
-        let img = vx_result!(image::load_from_memory(&data[offset..offset + length])).to_rgba();
+        let data = &data[offset..offset + length];
+        let geng = &engine.gapi_engine;
+        if container::is_container(data) {
+            let container = match Container::new(data) {
+                Ok(c) => c,
+                Err(e) => vx_log_f!("{}", e),
+            };
+            return Self::new_with_base_container_name(obj_base, geng, &container, name);
+        }
+        let img = vx_result!(image::load_from_memory(data)).to_rgba8();
         let (width, height) = img.dimensions();
         let img = img.into_raw();
-        let geng = vx_result!(engine.get_gapi_engine().read());
-        Self::new_with_base_pixels_name(obj_base, width, height, &geng, &img, Some(name))
+        Self::new_with_base_pixels_name(obj_base, width, height, geng, &img, name)
     }
 
-    fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
-        let obj_base = ObjectBase::new_with_id(id);
-        let size: Size = reader.read();
-        let data = reader.read_bytes(size);
-        let img = vx_result!(image::load_from_memory(&data)).to_rgba8();
-        let (width, height) = img.dimensions();
-        let img = img.into_raw();
-        let geng = vx_result!(engine.get_gapi_engine().read());
-        Self::new_with_base_pixels(obj_base, width, height, &geng, &img)
-    }
+    // fn new_with_gx3d(engine: &Engine, reader: &mut Gx3DReader, id: Id) -> Self {
+    //     let obj_base = ObjectBase::new_with_id(id);
+    //     let size: Size = reader.read();
+    //     let data = reader.read_bytes(size);
+    //     let geng = vx_result!(engine.get_gapi_engine().read());
+    //     if container::is_container(&data) {
+    //         let container = match Container::new(&data) {
+    //             Ok(c) => c,
+    //             Err(e) => vx_log_f!("{}", e),
+    //         };
+    //         return Self::new_with_base_container_name(obj_base, &geng, &container, None);
+    //     }
+    //     let img = vx_result!(image::load_from_memory(&data)).to_rgba8();
+    //     let (width, height) = img.dimensions();
+    //     let img = img.into_raw();
+    //     Self::new_with_base_pixels(obj_base, width, height, &geng, &img)
+    // }
 }
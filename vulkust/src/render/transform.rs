@@ -0,0 +1,362 @@
+use super::super::core::types::Real;
+use super::super::math::matrix::Mat4;
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+
+/// Local translation-rotation-scale of an object relative to its parent.
+///
+/// The world matrix is cached and only recomputed in `update` after the local
+/// transform or the parent's world matrix has been changed.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Transform {
+    location: Vec3,
+    orientation: Quat,
+    scale: Vec3,
+    local: Mat4,
+    parent_world: Mat4,
+    world: Mat4,
+    inversed_world: Mat4,
+    is_dirty: bool,
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        Self::new_with_trs(Vec3::zero(), Quat::identity(), Vec3::splat(1.0))
+    }
+
+    pub fn new_with_trs(location: Vec3, orientation: Quat, scale: Vec3) -> Self {
+        let identity = Mat4::identity();
+        let mut myself = Self {
+            location,
+            orientation,
+            scale,
+            local: identity,
+            parent_world: identity,
+            world: identity,
+            inversed_world: identity,
+            is_dirty: true,
+        };
+        myself.update();
+        return myself;
+    }
+
+    /// Decomposes an affine matrix, shear is not supported and will be lost.
+    pub fn new_with_matrix(m: &Mat4) -> Self {
+        let location = m.get_col(3).truncate();
+        let x = m.get_col(0).truncate();
+        let y = m.get_col(1).truncate();
+        let z = m.get_col(2).truncate();
+        let mut sx = x.length();
+        if x.cross(y).dot(z) < 0.0 {
+            sx = -sx;
+        }
+        let scale = Vec3::new(sx, y.length(), z.length());
+        let orientation = Quat::from_axes(x / scale.x(), y / scale.y(), z / scale.z()).normalized();
+        Self::new_with_trs(location, orientation, scale)
+    }
+
+    pub fn new_with_gltf(node: &gltf::Node) -> Self {
+        let (l, r, s) = node.transform().decomposed();
+        Self::new_with_trs(
+            Vec3::from_array(l),
+            Quat::new(r[0], r[1], r[2], r[3]),
+            Vec3::from_array(s),
+        )
+    }
+
+    /// Returns true if the world matrix has been changed.
+    pub fn update(&mut self) -> bool {
+        if !self.is_dirty {
+            return false;
+        }
+        self.local =
+            Mat4::from_scale_rotation_translation(self.scale, self.orientation, self.location);
+        self.world = self.parent_world * self.local;
+        // A zero scale makes the world matrix singular, in that case the
+        // previous inverse is kept (identity for a new transform).
+        if let Some(inversed_world) = self.world.try_inverse() {
+            self.inversed_world = inversed_world;
+        }
+        self.is_dirty = false;
+        return true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        return self.is_dirty;
+    }
+
+    pub fn set_parent_world_matrix(&mut self, m: &Mat4) {
+        self.parent_world = *m;
+        self.is_dirty = true;
+    }
+
+    pub fn get_parent_world_matrix(&self) -> &Mat4 {
+        return &self.parent_world;
+    }
+
+    pub fn get_local_matrix(&self) -> &Mat4 {
+        #[cfg(debug_mode)]
+        {
+            if self.is_dirty {
+                vx_log_f!("Transform must be updated before reading its matrices.");
+            }
+        }
+        return &self.local;
+    }
+
+    pub fn get_world_matrix(&self) -> &Mat4 {
+        #[cfg(debug_mode)]
+        {
+            if self.is_dirty {
+                vx_log_f!("Transform must be updated before reading its matrices.");
+            }
+        }
+        return &self.world;
+    }
+
+    pub fn get_inversed_world_matrix(&self) -> &Mat4 {
+        #[cfg(debug_mode)]
+        {
+            if self.is_dirty {
+                vx_log_f!("Transform must be updated before reading its matrices.");
+            }
+        }
+        return &self.inversed_world;
+    }
+
+    pub fn set_location(&mut self, l: &Vec3) {
+        self.location = *l;
+        self.is_dirty = true;
+    }
+
+    pub fn get_location(&self) -> &Vec3 {
+        return &self.location;
+    }
+
+    pub fn get_world_location(&self) -> Vec3 {
+        return self.get_world_matrix().get_col(3).truncate();
+    }
+
+    pub fn set_orientation(&mut self, q: &Quat) {
+        self.orientation = q.normalized();
+        self.is_dirty = true;
+    }
+
+    pub fn get_orientation(&self) -> &Quat {
+        return &self.orientation;
+    }
+
+    pub fn set_scale(&mut self, s: &Vec3) {
+        self.scale = *s;
+        self.is_dirty = true;
+    }
+
+    pub fn get_scale(&self) -> &Vec3 {
+        return &self.scale;
+    }
+
+    /// Biggest absolute scale factor in world space, it is useful for bounding spheres.
+    pub fn get_world_max_scale(&self) -> Real {
+        let m = self.get_world_matrix();
+        let x = m.get_col(0).truncate().length();
+        let y = m.get_col(1).truncate().length();
+        let z = m.get_col(2).truncate().length();
+        return x.max(y).max(z);
+    }
+
+    pub fn get_x_axis(&self) -> Vec3 {
+        return self.orientation.rotate(Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    pub fn get_y_axis(&self) -> Vec3 {
+        return self.orientation.rotate(Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    pub fn get_z_axis(&self) -> Vec3 {
+        return self.orientation.rotate(Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    /// Translation in parent space
+    pub fn translate(&mut self, t: &Vec3) {
+        self.location += *t;
+        self.is_dirty = true;
+    }
+
+    pub fn move_local_x(&mut self, v: Real) {
+        let t = self.get_x_axis() * v;
+        self.translate(&t);
+    }
+
+    pub fn move_local_y(&mut self, v: Real) {
+        let t = self.get_y_axis() * v;
+        self.translate(&t);
+    }
+
+    pub fn move_local_z(&mut self, v: Real) {
+        let t = self.get_z_axis() * v;
+        self.translate(&t);
+    }
+
+    pub fn rotate_local_x(&mut self, v: Real) {
+        let r = Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), v);
+        self.orientation = (self.orientation * r).normalized();
+        self.is_dirty = true;
+    }
+
+    pub fn rotate_local_y(&mut self, v: Real) {
+        let r = Quat::from_axis_angle(Vec3::new(0.0, 1.0, 0.0), v);
+        self.orientation = (self.orientation * r).normalized();
+        self.is_dirty = true;
+    }
+
+    pub fn rotate_local_z(&mut self, v: Real) {
+        let r = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), v);
+        self.orientation = (self.orientation * r).normalized();
+        self.is_dirty = true;
+    }
+
+    pub fn rotate_global_z(&mut self, v: Real) {
+        let r = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), v);
+        self.orientation = (r * self.orientation).normalized();
+        self.is_dirty = true;
+    }
+
+    pub fn scale(&mut self, s: Real) {
+        self.scale *= s;
+        self.is_dirty = true;
+    }
+
+    pub fn local_to_world_point(&self, p: &Vec3) -> Vec3 {
+        return self.get_world_matrix().transform_point(*p);
+    }
+
+    pub fn local_to_world_vector(&self, v: &Vec3) -> Vec3 {
+        return self.get_world_matrix().transform_vector(*v);
+    }
+
+    pub fn world_to_local_point(&self, p: &Vec3) -> Vec3 {
+        return self.get_inversed_world_matrix().transform_point(*p);
+    }
+
+    pub fn world_to_local_vector(&self, v: &Vec3) -> Vec3 {
+        return self.get_inversed_world_matrix().transform_vector(*v);
+    }
+
+    /// Changes the local transform so that the world transform stays the same
+    /// under a new parent.
+    pub fn reparent_keep_world(&mut self, parent_world: &Mat4) {
+        self.update();
+        let parent_inversed = match parent_world.try_inverse() {
+            Some(m) => m,
+            None => {
+                vx_log_e!("Parent world matrix is not invertible, identity is used instead.");
+                Mat4::identity()
+            }
+        };
+        let local = parent_inversed * self.world;
+        let t = Self::new_with_matrix(&local);
+        self.location = t.location;
+        self.orientation = t.orientation;
+        self.scale = t.scale;
+        self.parent_world = *parent_world;
+        self.is_dirty = true;
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: &Vec3, b: &Vec3) {
+        assert!(
+            (*a - *b).length() < 1e-4,
+            "{:?} != {:?}",
+            a.to_array(),
+            b.to_array()
+        );
+    }
+
+    #[test]
+    fn parent_child_propagation_test() {
+        let mut parent = Transform::new();
+        parent.set_location(&Vec3::new(10.0, 0.0, 0.0));
+        parent.rotate_global_z(std::f32::consts::FRAC_PI_2);
+        parent.scale(2.0);
+        parent.update();
+        let mut child = Transform::new();
+        child.set_location(&Vec3::new(1.0, 0.0, 0.0));
+        child.set_parent_world_matrix(parent.get_world_matrix());
+        child.update();
+        assert_near(&child.get_world_location(), &Vec3::new(10.0, 2.0, 0.0));
+        let p = Vec3::new(0.0, 1.0, 0.0);
+        let w = child.local_to_world_point(&p);
+        assert_near(&w, &Vec3::new(8.0, 2.0, 0.0));
+        assert_near(&child.world_to_local_point(&w), &p);
+        assert!((child.get_world_max_scale() - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dirty_flag_propagation_test() {
+        let mut parent = Transform::new();
+        let mut child = Transform::new();
+        assert!(!parent.is_dirty());
+        assert!(!child.update());
+        parent.translate(&Vec3::new(0.0, 3.0, 0.0));
+        assert!(parent.is_dirty());
+        assert!(parent.update());
+        assert!(!parent.update());
+        assert!(!child.is_dirty());
+        child.set_parent_world_matrix(parent.get_world_matrix());
+        assert!(child.is_dirty());
+        assert!(child.update());
+        assert!(!child.is_dirty());
+        assert_near(&child.get_world_location(), &Vec3::new(0.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn reparent_keep_world_test() {
+        let mut old_parent = Transform::new();
+        old_parent.set_location(&Vec3::new(1.0, 2.0, 3.0));
+        old_parent.update();
+        let mut child = Transform::new();
+        child.set_location(&Vec3::new(1.0, 0.0, 0.0));
+        child.set_parent_world_matrix(old_parent.get_world_matrix());
+        child.update();
+        let world = *child.get_world_matrix();
+        let mut new_parent = Transform::new();
+        new_parent.set_location(&Vec3::new(-5.0, 0.0, 0.0));
+        new_parent.rotate_global_z(1.0);
+        new_parent.scale(0.5);
+        new_parent.update();
+        child.reparent_keep_world(new_parent.get_world_matrix());
+        assert!(child.update());
+        assert_near(&child.get_world_location(), &world.get_col(3).truncate());
+        assert_near(
+            &child.local_to_world_vector(&Vec3::new(1.0, 0.0, 0.0)),
+            &world.get_col(0).truncate(),
+        );
+        assert!(child.get_parent_world_matrix() == new_parent.get_world_matrix());
+    }
+
+    #[test]
+    fn zero_scale_test() {
+        let mut t = Transform::new();
+        t.set_location(&Vec3::new(1.0, 0.0, 0.0));
+        t.update();
+        let inversed = *t.get_inversed_world_matrix();
+        t.set_scale(&Vec3::new(0.0, 0.0, 0.0));
+        assert!(t.update());
+        assert!(t.get_inversed_world_matrix() == &inversed);
+        let mut child = Transform::new();
+        child.reparent_keep_world(t.get_world_matrix());
+        child.update();
+        assert_near(&child.get_world_location(), &Vec3::new(1.0, 0.0, 0.0));
+    }
+}
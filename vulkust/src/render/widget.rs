@@ -1,18 +1,25 @@
 use super::super::core::object::Object as CoreObject;
 use super::super::core::types::{Id, Real};
+use super::super::math::quaternion::Quat;
+use super::super::math::vector::Vec3;
+use super::animation::Animator;
 use super::camera::Camera;
 use super::command::Buffer as CmdBuffer;
 use super::engine::Engine;
 use super::font::Font;
+use super::lod::Selector as LodSelector;
 use super::material::Material;
 use super::mesh::{Base as MeshBase, Mesh};
 use super::model::{Base as ModelBase, DefaultModel, Model, Uniform};
 use super::object::{Object, Transferable};
 use super::scene::Scene;
+use super::skin::Skin;
+use super::transform::Transform;
 use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
 
 use cgmath;
+use gltf;
 use rusttype::{point, Scale};
 
 pub trait Widget: Model {}
@@ -53,15 +60,15 @@ impl Object for Base {
 }
 
 impl Transferable for Base {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
+    fn set_orientation(&mut self, q: &Quat) {
         self.model_base.set_orientation(q);
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, l: &Vec3) {
         self.model_base.set_location(l);
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         return self.model_base.get_location();
     }
 
@@ -80,6 +87,13 @@ impl Transferable for Base {
     fn rotate_global_z(&mut self, d: Real) {
         self.model_base.rotate_global_z(d);
     }
+    fn translate(&mut self, t: &Vec3) {
+        self.model_base.translate(t);
+    }
+
+    fn scale(&mut self, s: Real) {
+        self.model_base.scale(s);
+    }
 }
 
 impl Model for Base {
@@ -103,6 +117,34 @@ impl Model for Base {
         return self.model_base.bring_all_child_models();
     }
 
+    fn add_child(&mut self, child: Arc<RwLock<dyn Model>>) {
+        self.model_base.add_child(child);
+    }
+
+    fn remove_child(&mut self, id: Id) -> Option<Arc<RwLock<dyn Model>>> {
+        return self.model_base.remove_child(id);
+    }
+
+    fn get_parent(&self) -> Option<Id> {
+        return self.model_base.get_parent();
+    }
+
+    fn set_parent(&mut self, parent: Option<Id>) {
+        self.model_base.set_parent(parent);
+    }
+
+    fn set_parent_world_matrix(&mut self, m: &cgmath::Matrix4<Real>) {
+        self.model_base.set_parent_world_matrix(m);
+    }
+
+    fn get_transform(&self) -> &Transform {
+        return self.model_base.get_transform();
+    }
+
+    fn set_transform(&mut self, t: &Transform) {
+        self.model_base.set_transform(t);
+    }
+
     fn has_shadow(&self) -> bool {
         return false;
     }
@@ -119,10 +161,50 @@ impl Model for Base {
         return self.model_base.get_distance_from_camera(c);
     }
 
+    fn get_lod(&self) -> usize {
+        return self.model_base.get_lod();
+    }
+
+    fn get_lod_selector(&self) -> &LodSelector {
+        return self.model_base.get_lod_selector();
+    }
+
+    fn get_mut_lod_selector(&mut self) -> &mut LodSelector {
+        return self.model_base.get_mut_lod_selector();
+    }
+
     fn get_uniform(&self) -> &Uniform {
         return &self.model_base.get_uniform();
     }
 
+    fn get_skin(&self) -> Option<&Skin> {
+        return self.model_base.get_skin();
+    }
+
+    fn get_mut_skin(&mut self) -> Option<&mut Skin> {
+        return self.model_base.get_mut_skin();
+    }
+
+    fn get_animator(&self) -> Option<&Animator> {
+        return self.model_base.get_animator();
+    }
+
+    fn get_mut_animator(&mut self) -> Option<&mut Animator> {
+        return self.model_base.get_mut_animator();
+    }
+
+    fn get_morph_weights(&self) -> &[Real] {
+        return self.model_base.get_morph_weights();
+    }
+
+    fn set_morph_weights(&mut self, weights: &[Real]) {
+        self.model_base.set_morph_weights(weights);
+    }
+
+    fn load_gltf_animations(&mut self, document: &gltf::Document, data: &[u8]) {
+        self.model_base.load_gltf_animations(document, data);
+    }
+
     fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         self.model_base.render_gbuffer(cmd, frame_number);
     }
@@ -134,6 +216,14 @@ impl Model for Base {
     fn render_shadow(&self, _: &mut CmdBuffer, _: usize) {
         vx_log_f!("Widget does not make shadow");
     }
+
+    fn render_skinned_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        self.model_base.render_skinned_gbuffer(cmd, frame_number);
+    }
+
+    fn render_skinned_shadow(&self, _: &mut CmdBuffer, _: usize) {
+        vx_log_f!("Widget does not make shadow");
+    }
 }
 
 impl DefaultModel for Base {
@@ -337,15 +427,15 @@ impl Object for Label {
 }
 
 impl Transferable for Label {
-    fn set_orientation(&mut self, q: &cgmath::Quaternion<Real>) {
+    fn set_orientation(&mut self, q: &Quat) {
         self.base.set_orientation(q);
     }
 
-    fn set_location(&mut self, l: &cgmath::Vector3<Real>) {
+    fn set_location(&mut self, l: &Vec3) {
         self.base.set_location(l);
     }
 
-    fn get_location(&self) -> cgmath::Vector3<Real> {
+    fn get_location(&self) -> Vec3 {
         return self.base.get_location();
     }
 
@@ -364,6 +454,13 @@ impl Transferable for Label {
     fn rotate_global_z(&mut self, d: Real) {
         self.base.rotate_global_z(d);
     }
+    fn translate(&mut self, t: &Vec3) {
+        self.base.translate(t);
+    }
+
+    fn scale(&mut self, s: Real) {
+        self.base.scale(s);
+    }
 }
 
 impl Model for Label {
@@ -387,6 +484,34 @@ impl Model for Label {
         return self.base.bring_all_child_models();
     }
 
+    fn add_child(&mut self, child: Arc<RwLock<dyn Model>>) {
+        self.base.add_child(child);
+    }
+
+    fn remove_child(&mut self, id: Id) -> Option<Arc<RwLock<dyn Model>>> {
+        return self.base.remove_child(id);
+    }
+
+    fn get_parent(&self) -> Option<Id> {
+        return self.base.get_parent();
+    }
+
+    fn set_parent(&mut self, parent: Option<Id>) {
+        self.base.set_parent(parent);
+    }
+
+    fn set_parent_world_matrix(&mut self, m: &cgmath::Matrix4<Real>) {
+        self.base.set_parent_world_matrix(m);
+    }
+
+    fn get_transform(&self) -> &Transform {
+        return self.base.get_transform();
+    }
+
+    fn set_transform(&mut self, t: &Transform) {
+        self.base.set_transform(t);
+    }
+
     fn has_shadow(&self) -> bool {
         return self.base.has_shadow();
     }
@@ -403,10 +528,50 @@ impl Model for Label {
         return self.base.get_distance_from_camera(c);
     }
 
+    fn get_lod(&self) -> usize {
+        return self.base.get_lod();
+    }
+
+    fn get_lod_selector(&self) -> &LodSelector {
+        return self.base.get_lod_selector();
+    }
+
+    fn get_mut_lod_selector(&mut self) -> &mut LodSelector {
+        return self.base.get_mut_lod_selector();
+    }
+
     fn get_uniform(&self) -> &Uniform {
         return self.base.get_uniform();
     }
 
+    fn get_skin(&self) -> Option<&Skin> {
+        return self.base.get_skin();
+    }
+
+    fn get_mut_skin(&mut self) -> Option<&mut Skin> {
+        return self.base.get_mut_skin();
+    }
+
+    fn get_animator(&self) -> Option<&Animator> {
+        return self.base.get_animator();
+    }
+
+    fn get_mut_animator(&mut self) -> Option<&mut Animator> {
+        return self.base.get_mut_animator();
+    }
+
+    fn get_morph_weights(&self) -> &[Real] {
+        return self.base.get_morph_weights();
+    }
+
+    fn set_morph_weights(&mut self, weights: &[Real]) {
+        self.base.set_morph_weights(weights);
+    }
+
+    fn load_gltf_animations(&mut self, document: &gltf::Document, data: &[u8]) {
+        self.base.load_gltf_animations(document, data);
+    }
+
     fn render_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         self.base.render_gbuffer(cmd, frame_number);
     }
@@ -418,6 +583,14 @@ impl Model for Label {
     fn render_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         self.base.render_shadow(cmd, frame_number);
     }
+
+    fn render_skinned_gbuffer(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        self.base.render_skinned_gbuffer(cmd, frame_number);
+    }
+
+    fn render_skinned_shadow(&self, cmd: &mut CmdBuffer, frame_number: usize) {
+        self.base.render_skinned_shadow(cmd, frame_number);
+    }
 }
 
 impl DefaultModel for Label {
@@ -0,0 +1,157 @@
+use super::command::Buffer as CmdBuffer;
+use std::mem::{size_of, transmute};
+use std::os::raw::c_void;
+use std::ptr::{copy_nonoverlapping, read_unaligned};
+use std::sync::{Arc, RwLock};
+
+// Buffers are plain host memory, the command buffers read them when they are
+// executed, so there is no upload or staging step.
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Buffer {
+    data: Vec<u8>,
+}
+
+impl Buffer {
+    pub(crate) fn new(size: isize) -> Self {
+        Self {
+            data: vec![0; size as usize],
+        }
+    }
+
+    pub(crate) fn new_with_ptr(data: *const c_void, size: isize) -> Self {
+        let mut myself = Self::new(size);
+        myself.write_ptr(data, size);
+        return myself;
+    }
+
+    pub(crate) fn write_ptr(&mut self, data: *const c_void, size: isize) {
+        #[cfg(debug_mode)]
+        {
+            if size as usize > self.data.len() {
+                vx_log_f!("Data is bigger than buffer.");
+            }
+        }
+        unsafe {
+            copy_nonoverlapping(data as *const u8, self.data.as_mut_ptr(), size as usize);
+        }
+    }
+
+    pub(crate) fn get_data(&self) -> &[u8] {
+        return &self.data;
+    }
+
+    pub(crate) fn get_size(&self) -> isize {
+        return self.data.len() as isize;
+    }
+
+    // It reads the T at the start of buffer, T must be a repr(C) plain data.
+    pub(crate) fn read<T: Copy>(&self) -> T {
+        #[cfg(debug_mode)]
+        {
+            if size_of::<T>() > self.data.len() {
+                vx_log_f!("Buffer is smaller than the requested type.");
+            }
+        }
+        return unsafe { read_unaligned(self.data.as_ptr() as *const T) };
+    }
+
+    pub(crate) fn read_at<T: Copy>(&self, index: usize) -> T {
+        let offset = index * size_of::<T>();
+        #[cfg(debug_mode)]
+        {
+            if offset + size_of::<T>() > self.data.len() {
+                vx_log_f!("Out of buffer range reading.");
+            }
+        }
+        return unsafe { read_unaligned(self.data.as_ptr().add(offset) as *const T) };
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Static {
+    buffer: Arc<RwLock<Buffer>>,
+}
+
+impl Static {
+    pub(crate) fn new(buffer: Arc<RwLock<Buffer>>) -> Self {
+        Self { buffer }
+    }
+
+    pub(crate) fn get_buffer(&self) -> &Arc<RwLock<Buffer>> {
+        return &self.buffer;
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Dynamic {
+    buffers: Vec<Arc<RwLock<Buffer>>>,
+    actual_size: isize,
+}
+
+impl Dynamic {
+    pub(crate) fn new(buffers: Vec<Arc<RwLock<Buffer>>>, actual_size: isize) -> Self {
+        Self {
+            buffers,
+            actual_size,
+        }
+    }
+
+    pub(crate) fn update_with_ptr(&mut self, data: *const c_void, frame_number: usize) {
+        vx_result!(self.buffers[frame_number].write()).write_ptr(data, self.actual_size);
+    }
+
+    pub(crate) fn update<T>(&mut self, data: &T, frame_number: usize)
+    where
+        T: Sized,
+    {
+        #[cfg(debug_mode)]
+        {
+            if size_of::<T>() != self.actual_size as usize {
+                vx_log_f!("Data must have same size of buffer.");
+            }
+        }
+        self.update_with_ptr(unsafe { transmute(data) }, frame_number);
+    }
+
+    pub(crate) fn get_buffer(&self, frame_number: usize) -> &Arc<RwLock<Buffer>> {
+        return &self.buffers[frame_number];
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Manager {
+    frames_count: usize,
+}
+
+impl Manager {
+    pub(crate) fn new(frames_count: usize) -> Self {
+        Self { frames_count }
+    }
+
+    pub(crate) fn create_static_buffer_with_ptr(
+        &mut self,
+        data: *const c_void,
+        size: isize,
+    ) -> Static {
+        return Static::new(Arc::new(RwLock::new(Buffer::new_with_ptr(data, size))));
+    }
+
+    pub(crate) fn create_static_buffer_with_vec<T>(&mut self, data: &[T]) -> Static {
+        let size = (size_of::<T>() * data.len()) as isize;
+        return self.create_static_buffer_with_ptr(unsafe { transmute(data.as_ptr()) }, size);
+    }
+
+    pub(crate) fn create_dynamic_buffer(&mut self, actual_size: isize) -> Dynamic {
+        let mut buffers = Vec::with_capacity(self.frames_count);
+        for _ in 0..self.frames_count {
+            buffers.push(Arc::new(RwLock::new(Buffer::new(actual_size))));
+        }
+        return Dynamic::new(buffers, actual_size);
+    }
+
+    // Memory is shared with the rasterizer, nothing needs to be copied.
+    pub(crate) fn update(&mut self, _cmd: &mut CmdBuffer, _frame_number: usize) {}
+}
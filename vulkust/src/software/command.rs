@@ -0,0 +1,456 @@
+use super::super::render::pipeline::PipelineType;
+use super::super::render::texture::Texture;
+use super::buffer::{Buffer as BufBuffer, Static as StaticBuffer};
+use super::descriptor::Set as DescriptorSet;
+use super::framebuffer::Framebuffer;
+use super::pipeline::{Pipeline, State as PipelineState};
+use super::render_pass::RenderPass;
+use super::shader;
+use std::sync::{Arc, RwLock};
+
+const GBUFF_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const GBUFF_MODEL_DESCRIPTOR_OFFSET: usize = 1;
+const GBUFF_MATERIAL_DESCRIPTOR_OFFSET: usize = 2;
+
+const GBUFF_DESCRIPTOR_SETS_COUNT: usize = 3;
+
+const SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET: usize = 3;
+
+const SKINNED_GBUFF_DESCRIPTOR_SETS_COUNT: usize = 4;
+
+const UNLIT_MODEL_DESCRIPTOR_OFFSET: usize = 0;
+const UNLIT_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
+
+const UNLIT_DESCRIPTOR_SETS_COUNT: usize = 2;
+
+const DEFERRED_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const DEFERRED_DEFERRED_DESCRIPTOR_OFFSET: usize = 1;
+
+const DEFERRED_DESCRIPTOR_SETS_COUNT: usize = 2;
+
+const SSAO_SCENE_DESCRIPTOR_OFFSET: usize = 0;
+const SSAO_SSAO_DESCRIPTOR_OFFSET: usize = 1;
+
+const SSAO_DESCRIPTOR_SETS_COUNT: usize = 2;
+
+const SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT: usize = 2;
+const SHADOW_MAPPER_LIGHT_DESCRIPTOR_OFFSET: usize = 0;
+const SHADOW_MAPPER_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
+
+const SKINNED_SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT: usize = 3;
+const SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET: usize = 2;
+
+const MAX_DESCRIPTOR_SETS_COUNT: usize = 4;
+
+const SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_SETS_COUNT: usize = 1;
+const SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_OFFSET: usize = 0;
+
+// The content of the uniform buffer is copied at the binding time, because
+// the dynamic buffers are overwritten for the next objects before submit.
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Binding {
+    pub(super) uniform: Vec<u8>,
+    pub(super) texturess: Vec<Vec<Arc<RwLock<dyn Texture>>>>,
+}
+
+impl Binding {
+    fn new(descriptor_set: &DescriptorSet, buffer: &BufBuffer) -> Self {
+        Self {
+            uniform: buffer.get_data().to_vec(),
+            texturess: descriptor_set.get_texturess().to_vec(),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Draw {
+    pub(super) pipeline: PipelineState,
+    pub(super) bindings: Vec<Binding>,
+    pub(super) vertex_buffer: Option<Arc<RwLock<BufBuffer>>>,
+    pub(super) index_buffer: Option<Arc<RwLock<BufBuffer>>>,
+    pub(super) count: u32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(debug_mode, derive(Debug))]
+enum Command {
+    BeginRenderPass(Arc<RenderPass>),
+    EndRenderPass,
+    Draw(Draw),
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Buffer {
+    has_render_record: bool,
+    commands: Vec<Command>,
+    bound_pipeline: Option<PipelineState>,
+    bound_bindings: [Option<Binding>; MAX_DESCRIPTOR_SETS_COUNT],
+    bound_vertex_buffer: Option<Arc<RwLock<BufBuffer>>>,
+    bound_index_buffer: Option<Arc<RwLock<BufBuffer>>>,
+}
+
+impl Buffer {
+    pub(crate) fn new_primary(pool: Arc<Pool>) -> Self {
+        return Self::new(pool, false);
+    }
+
+    pub(crate) fn new_secondary(pool: Arc<Pool>) -> Self {
+        return Self::new(pool, true);
+    }
+
+    fn new(_pool: Arc<Pool>, _is_secondary: bool) -> Self {
+        Self {
+            has_render_record: false,
+            commands: Vec::new(),
+            bound_pipeline: None,
+            bound_bindings: [None, None, None, None],
+            bound_vertex_buffer: None,
+            bound_index_buffer: None,
+        }
+    }
+
+    pub(crate) fn get_has_render_record(&self) -> bool {
+        return self.has_render_record;
+    }
+
+    pub(crate) fn exe_cmd(&mut self, other: &Self) {
+        if !other.has_render_record {
+            return;
+        }
+        self.has_render_record = true;
+        self.commands.extend_from_slice(&other.commands);
+    }
+
+    pub(crate) fn exe_cmds(&mut self, others: &[&Self]) {
+        for other in others {
+            self.exe_cmd(other);
+        }
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.has_render_record = false;
+        self.commands.clear();
+        self.bound_pipeline = None;
+        self.bound_bindings = [None, None, None, None];
+        self.bound_vertex_buffer = None;
+        self.bound_index_buffer = None;
+    }
+
+    // The render pass of a secondary buffer is the one that its primary
+    // buffer has begun.
+    pub(crate) fn begin_secondary(&mut self, _framebuffer: &Framebuffer) {
+        self.begin();
+    }
+
+    pub(crate) fn begin_render_pass_with_render_pass(&mut self, render_pass: &Arc<RenderPass>) {
+        self.commands
+            .push(Command::BeginRenderPass(render_pass.clone()));
+    }
+
+    pub(crate) fn end_render_pass(&mut self) {
+        self.commands.push(Command::EndRenderPass);
+    }
+
+    pub(crate) fn end(&mut self) {}
+
+    pub(crate) fn bind_pipeline(&mut self, p: &Pipeline) {
+        self.bound_pipeline = Some(*p.get_state());
+    }
+
+    pub(crate) fn bind_vertex_buffer(&mut self, buffer: &Arc<RwLock<BufBuffer>>) {
+        self.bound_vertex_buffer = Some(buffer.clone());
+    }
+
+    pub(crate) fn bind_index_buffer(&mut self, buffer: &Arc<RwLock<BufBuffer>>) {
+        self.bound_index_buffer = Some(buffer.clone());
+    }
+
+    fn record_draw(&mut self, count: u32, sets_count: usize, is_indexed: bool) {
+        let pipeline = *vx_unwrap!(&self.bound_pipeline);
+        let mut bindings = Vec::with_capacity(sets_count);
+        for b in &self.bound_bindings[..sets_count] {
+            bindings.push(vx_unwrap!(b).clone());
+        }
+        let (vertex_buffer, index_buffer) = if is_indexed {
+            (
+                self.bound_vertex_buffer.clone(),
+                self.bound_index_buffer.clone(),
+            )
+        } else {
+            (None, None)
+        };
+        self.has_render_record = true;
+        self.commands.push(Command::Draw(Draw {
+            pipeline,
+            bindings,
+            vertex_buffer,
+            index_buffer,
+            count,
+        }));
+    }
+
+    pub(crate) fn draw_index(&mut self, indices_count: u32) {
+        let sets_count = match vx_unwrap!(&self.bound_pipeline).pipeline_type {
+            PipelineType::GBuffer | PipelineType::TransparentPBR => GBUFF_DESCRIPTOR_SETS_COUNT,
+            PipelineType::Unlit => UNLIT_DESCRIPTOR_SETS_COUNT,
+            PipelineType::ShadowMapper => SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT,
+            PipelineType::SkinnedGBuffer => SKINNED_GBUFF_DESCRIPTOR_SETS_COUNT,
+            PipelineType::SkinnedShadowMapper => SKINNED_SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT,
+            _ => vx_unexpected!(),
+        };
+        self.record_draw(indices_count, sets_count, true);
+    }
+
+    // Only the full screen passes draw without vertex buffer, their vertices
+    // are generated from the vertex index like their Vulkan shaders.
+    pub(crate) fn draw(&mut self, vertices_count: u32) {
+        let sets_count = match vx_unwrap!(&self.bound_pipeline).pipeline_type {
+            PipelineType::Deferred => DEFERRED_DESCRIPTOR_SETS_COUNT,
+            PipelineType::SSAO => SSAO_DESCRIPTOR_SETS_COUNT,
+            PipelineType::ShadowAccumulatorDirectional => {
+                SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_SETS_COUNT
+            }
+            _ => vx_unexpected!(),
+        };
+        self.record_draw(vertices_count, sets_count, false);
+    }
+
+    fn bind_descriptor(
+        &mut self,
+        offset: usize,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bound_bindings[offset] = Some(Binding::new(descriptor_set, buffer));
+    }
+
+    pub(crate) fn bind_gbuff_scene_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(GBUFF_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_gbuff_model_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(GBUFF_MODEL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_gbuff_material_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(GBUFF_MATERIAL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn render_gbuff(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
+    pub(crate) fn bind_skinned_gbuff_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn render_skinned_gbuff(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
+    pub(crate) fn bind_unlit_model_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(UNLIT_MODEL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_unlit_material_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(UNLIT_MATERIAL_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn render_unlit(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
+    pub(crate) fn render_deferred(&mut self) {
+        self.draw(3);
+    }
+
+    pub(crate) fn render_ssao(&mut self) {
+        self.draw(3);
+    }
+
+    pub(crate) fn render_shadow_accumulator_directional(&mut self) {
+        self.draw(3);
+    }
+
+    pub(crate) fn bind_deferred_scene_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(DEFERRED_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_deferred_deferred_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(DEFERRED_DEFERRED_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_ssao_scene_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(SSAO_SCENE_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_ssao_ssao_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(SSAO_SSAO_DESCRIPTOR_OFFSET, descriptor_set, buffer);
+    }
+
+    pub(crate) fn bind_shadow_mapper_light_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(
+            SHADOW_MAPPER_LIGHT_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
+    }
+
+    pub(crate) fn bind_shadow_mapper_material_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(
+            SHADOW_MAPPER_MATERIAL_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
+    }
+
+    pub(crate) fn bind_skinned_shadow_mapper_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(
+            SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
+    }
+
+    pub(crate) fn bind_shadow_accumulator_directional_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bind_descriptor(
+            SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_OFFSET,
+            descriptor_set,
+            buffer,
+        );
+    }
+
+    pub(crate) fn render_shadow_mapper(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
+    pub(crate) fn render_skinned_shadow_mapper(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
+    // It is the queue of the software graphic API, commands are executed in
+    // the order of their recording.
+    pub(super) fn execute(&self) {
+        let mut render_pass: Option<&Arc<RenderPass>> = None;
+        for c in &self.commands {
+            match c {
+                Command::BeginRenderPass(rp) => {
+                    shader::begin_render_pass(rp);
+                    render_pass = Some(rp);
+                }
+                Command::EndRenderPass => render_pass = None,
+                Command::Draw(d) => {
+                    shader::draw(d, vx_unwrap!(render_pass));
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub enum Type {
+    Graphic,
+    Compute,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Pool {}
+
+impl Pool {
+    pub(super) fn new(_pool_type: Type) -> Self {
+        Self {}
+    }
+}
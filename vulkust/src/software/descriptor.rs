@@ -0,0 +1,105 @@
+use super::super::render::texture::Texture;
+use super::buffer::Dynamic as DynamicBuffer;
+use std::sync::{Arc, RwLock};
+
+const SSAO_TEX_COUNT: usize = 3;
+const DEFERRED_TEX_COUNT: usize = 6;
+const GBUFF_TEX_COUNT: usize = 7;
+
+// The uniform buffer of a set is bound with the command buffer, so a set is
+// only its textures.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Set {
+    texturess: Vec<Vec<Arc<RwLock<dyn Texture>>>>,
+}
+
+impl Set {
+    fn new(texturess: Vec<Vec<Arc<RwLock<dyn Texture>>>>) -> Self {
+        Self { texturess }
+    }
+
+    fn new_with_textures(textures: Vec<Arc<RwLock<dyn Texture>>>) -> Self {
+        let mut texturess = Vec::with_capacity(textures.len());
+        for t in textures {
+            texturess.push(vec![t]);
+        }
+        return Self::new(texturess);
+    }
+
+    pub(super) fn get_texturess(&self) -> &[Vec<Arc<RwLock<dyn Texture>>>] {
+        return &self.texturess;
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Manager {
+    buffer_only_set: Arc<Set>,
+}
+
+impl Manager {
+    pub(crate) fn new() -> Self {
+        Self {
+            buffer_only_set: Arc::new(Set::new(Vec::new())),
+        }
+    }
+
+    pub(crate) fn create_gbuff_set(
+        &mut self,
+        _uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
+    ) -> Arc<Set> {
+        #[cfg(debug_mode)]
+        {
+            if textures.len() != GBUFF_TEX_COUNT {
+                vx_log_f!(
+                    "For gbuffer filler descriptor you need {} textures.",
+                    GBUFF_TEX_COUNT
+                );
+            }
+        }
+        return Arc::new(Set::new_with_textures(textures));
+    }
+
+    pub(crate) fn create_buffer_only_set(&mut self, _uniform: &DynamicBuffer) -> Arc<Set> {
+        return self.buffer_only_set.clone();
+    }
+
+    pub(crate) fn create_deferred_set(
+        &mut self,
+        _uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
+    ) -> Arc<Set> {
+        #[cfg(debug_mode)]
+        {
+            if textures.len() != DEFERRED_TEX_COUNT {
+                vx_log_f!(
+                    "For deferred descriptor you need {} textures.",
+                    DEFERRED_TEX_COUNT
+                );
+            }
+        }
+        return Arc::new(Set::new_with_textures(textures));
+    }
+
+    pub(crate) fn create_ssao_set(
+        &mut self,
+        _uniform: &DynamicBuffer,
+        textures: Vec<Arc<RwLock<dyn Texture>>>,
+    ) -> Arc<Set> {
+        #[cfg(debug_mode)]
+        {
+            if textures.len() != SSAO_TEX_COUNT {
+                vx_log_f!("For SSAO descriptor you need {} textures.", SSAO_TEX_COUNT);
+            }
+        }
+        return Arc::new(Set::new_with_textures(textures));
+    }
+
+    pub(crate) fn create_shadow_accumulator_directional_set(
+        &mut self,
+        _uniform: &DynamicBuffer,
+        texturess: Vec<Vec<Arc<RwLock<dyn Texture>>>>,
+    ) -> Arc<Set> {
+        return Arc::new(Set::new(texturess));
+    }
+}
@@ -0,0 +1,10 @@
+// The CPU is the only device of the software graphic API.
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Device {}
+
+impl Device {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
@@ -0,0 +1,715 @@
+use super::super::core::types::Id;
+use super::super::platform::os::application::Application as OsApp;
+use super::super::render::capture::Capture;
+use super::super::render::image::{AttachmentType, Format};
+use super::super::render::sampler::Filter as SamplerFilter;
+use super::super::render::texture::container::Container;
+use super::buffer::Manager as BufferManager;
+use super::capture::Target as CaptureTarget;
+use super::command::{Buffer as CmdBuffer, Pool as CmdPool, Type as CmdPoolType};
+use super::descriptor::Manager as DescriptorManager;
+use super::device::Device;
+use super::framebuffer::Framebuffer;
+use super::image::View as ImageView;
+use super::memory::Manager as MemoryManager;
+use super::pipeline::Manager as PipelineManager;
+use super::render_pass::RenderPass;
+use super::sampler::Sampler;
+use super::surface::Surface;
+use super::sync::Semaphore;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+const FRAMES_COUNT: usize = 2;
+
+// Surface of a secondary window with its frame, it stays cleared until the
+// scenes are drawn on the secondary windows.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Window {
+    surface: Surface,
+    frame: Vec<u8>,
+}
+
+// Graphic API engine that renders on CPU, it is for the machines that do
+// not have any GPU, like CI runners and remote desktops.
+// Command buffers are executed on submit, so semaphores and fences are not
+// needed and each frame is presented at the end of its rendering.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Engine {
+    pub(crate) device: Arc<Device>,
+    pub(crate) surface: Surface,
+    pub(crate) starting_semaphore: Arc<Semaphore>,
+    pub(crate) memory_manager: Arc<RwLock<MemoryManager>>,
+    pub(crate) graphic_cmd_pool: Arc<CmdPool>,
+    pub(crate) buffer_manager: Arc<RwLock<BufferManager>>,
+    pub(crate) descriptor_manager: Arc<RwLock<DescriptorManager>>,
+    pub(crate) pipeline_manager: Arc<RwLock<PipelineManager>>,
+    pub(crate) linear_repeat_sampler: Arc<Sampler>,
+    pub(crate) nearest_repeat_sampler: Arc<Sampler>,
+    //---------------------------------------
+    pub(crate) display_view: Arc<ImageView>,
+    pub(crate) clear_render_pass: Arc<RenderPass>,
+    pub(crate) render_pass: Arc<RenderPass>,
+    pub(crate) framebuffer: Arc<Framebuffer>,
+    //---------------------------------------
+    pub(crate) current_frame_number: usize,
+    pub(crate) frame: Vec<u8>,
+    pub(crate) windows: BTreeMap<Id, Window>,
+}
+
+impl Engine {
+    pub fn new(os_app: &mut OsApp) -> Self {
+        return Self::new_with_surface(Surface::new(os_app));
+    }
+
+    // Frames are only reachable through the capture, it is for the tests and
+    // the tools that render without any window.
+    pub fn new_offscreen(width: u32, height: u32) -> Self {
+        return Self::new_with_surface(Surface::new_offscreen(width, height));
+    }
+
+    fn new_with_surface(surface: Surface) -> Self {
+        let device = Arc::new(Device::new());
+        let (width, height) = surface.get_dimensions();
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new()));
+        let graphic_cmd_pool = Arc::new(CmdPool::new(CmdPoolType::Graphic));
+        let (display_view, clear_render_pass, render_pass, framebuffer) =
+            create_display(&memory_manager, width, height);
+        let linear_repeat_sampler = Arc::new(Sampler::new());
+        let nearest_repeat_sampler = Arc::new(Sampler::new_with_filter(SamplerFilter::Nearest));
+        let buffer_manager = Arc::new(RwLock::new(BufferManager::new(FRAMES_COUNT)));
+        let descriptor_manager = Arc::new(RwLock::new(DescriptorManager::new()));
+        let pipeline_manager = Arc::new(RwLock::new(PipelineManager::new()));
+        Self {
+            device,
+            surface,
+            starting_semaphore: Arc::new(Semaphore::new()),
+            memory_manager,
+            graphic_cmd_pool,
+            buffer_manager,
+            descriptor_manager,
+            pipeline_manager,
+            linear_repeat_sampler,
+            nearest_repeat_sampler,
+            display_view,
+            clear_render_pass,
+            render_pass,
+            framebuffer,
+            current_frame_number: 0,
+            frame: vec![0; (width * height * 4) as usize],
+            windows: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn start_rendering(&mut self) {
+        self.current_frame_number = (self.current_frame_number + 1) % FRAMES_COUNT;
+        let mut cmd = self.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        let framebuffer = Framebuffer::new(
+            vec![self.display_view.clone()],
+            self.clear_render_pass.clone(),
+        );
+        framebuffer.begin(&mut cmd);
+        cmd.end_render_pass();
+        cmd.end();
+        cmd.execute();
+    }
+
+    pub(crate) fn submit(&self, _wait: &Semaphore, cmd: &CmdBuffer, _signal: &Semaphore) {
+        cmd.execute();
+    }
+
+    pub(crate) fn submit_multiple(
+        &self,
+        _waits: &[&Semaphore],
+        cmds: &[&CmdBuffer],
+        _signals: &[&Semaphore],
+    ) {
+        for cmd in cmds {
+            cmd.execute();
+        }
+    }
+
+    pub(crate) fn end(&mut self, _wait: &Semaphore) {
+        self.frame = vx_result!(self.display_view.get_image().read()).to_rgba8();
+        self.surface.present(&self.frame);
+    }
+
+    pub(crate) fn create_texture_2d_with_pixels(
+        &self,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Arc<ImageView> {
+        return Arc::new(ImageView::new_texture_2d_with_pixels(
+            width,
+            height,
+            data,
+            &self.buffer_manager,
+        ));
+    }
+
+    pub(crate) fn create_texture_cube_with_pixels(
+        &self,
+        width: u32,
+        height: u32,
+        data: &[&[u8]; 6],
+    ) -> Arc<ImageView> {
+        return Arc::new(ImageView::new_texture_cube_with_pixels(
+            width,
+            height,
+            data,
+            &self.buffer_manager,
+        ));
+    }
+
+    pub(crate) fn create_texture_with_container(&self, container: &Container) -> Arc<ImageView> {
+        return Arc::new(ImageView::new_texture_with_container(
+            container,
+            &self.buffer_manager,
+        ));
+    }
+
+    pub(crate) fn create_command_pool(&self) -> Arc<CmdPool> {
+        return Arc::new(CmdPool::new(CmdPoolType::Graphic));
+    }
+
+    pub(crate) fn create_secondary_command_buffer(&self, cmd_pool: Arc<CmdPool>) -> CmdBuffer {
+        return CmdBuffer::new_secondary(cmd_pool);
+    }
+
+    pub(crate) fn create_primary_command_buffer(&self, cmd_pool: Arc<CmdPool>) -> CmdBuffer {
+        return CmdBuffer::new_primary(cmd_pool);
+    }
+
+    pub(crate) fn create_primary_command_buffer_from_main_graphic_pool(&self) -> CmdBuffer {
+        return CmdBuffer::new_primary(self.graphic_cmd_pool.clone());
+    }
+
+    pub(crate) fn create_secondary_command_buffer_from_main_graphic_pool(&self) -> CmdBuffer {
+        return CmdBuffer::new_secondary(self.graphic_cmd_pool.clone());
+    }
+
+    pub(crate) fn create_semaphore(&self) -> Semaphore {
+        return Semaphore::new();
+    }
+
+    pub(crate) fn get_surface_dimensions(&self) -> (u32, u32) {
+        return self.surface.get_dimensions();
+    }
+
+    pub(crate) fn get_frames_count(&self) -> usize {
+        return FRAMES_COUNT;
+    }
+
+    pub(crate) fn get_frame_number(&self) -> usize {
+        return self.current_frame_number;
+    }
+
+    pub(crate) fn get_current_framebuffer(&self) -> &Arc<Framebuffer> {
+        return &self.framebuffer;
+    }
+
+    pub(crate) fn get_starting_semaphore(&self) -> &Arc<Semaphore> {
+        return &self.starting_semaphore;
+    }
+
+    pub(crate) fn get_device(&self) -> &Arc<Device> {
+        return &self.device;
+    }
+
+    pub(crate) fn get_memory_manager(&self) -> &Arc<RwLock<MemoryManager>> {
+        return &self.memory_manager;
+    }
+
+    pub(crate) fn get_buffer_manager(&self) -> &Arc<RwLock<BufferManager>> {
+        return &self.buffer_manager;
+    }
+
+    pub(crate) fn get_descriptor_manager(&self) -> &Arc<RwLock<DescriptorManager>> {
+        return &self.descriptor_manager;
+    }
+
+    pub(crate) fn get_pipeline_manager(&self) -> &Arc<RwLock<PipelineManager>> {
+        return &self.pipeline_manager;
+    }
+
+    pub(crate) fn get_linear_repeat_sampler(&self) -> &Arc<Sampler> {
+        return &self.linear_repeat_sampler;
+    }
+
+    pub(crate) fn get_nearest_repeat_sampler(&self) -> &Arc<Sampler> {
+        return &self.nearest_repeat_sampler;
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+
+    // It is the last presented frame, there is no device memory to read back.
+    pub fn capture_frame(&mut self) -> Option<Capture> {
+        let (width, height) = self.surface.get_dimensions();
+        return Some(Capture::new(width, height, self.frame.clone()));
+    }
+
+    pub fn create_offscreen_target(&self, width: u32, height: u32) -> CaptureTarget {
+        return CaptureTarget::new(width, height, &self.memory_manager);
+    }
+
+    pub fn update(&mut self) {
+        self.start_rendering();
+        let semaphore = self.starting_semaphore.clone();
+        self.end_rendering(&semaphore);
+    }
+
+    // The renderer records the scenes between start_rendering and it.
+    pub(crate) fn end_rendering(&mut self, wait: &Semaphore) {
+        self.end(wait);
+        for window in self.windows.values() {
+            window.surface.present(&window.frame);
+        }
+    }
+
+    // Display image is recreated, so the framebuffer and the render passes
+    // of the previous size must not be used after it.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || (width, height) == self.surface.get_dimensions() {
+            return;
+        }
+        self.surface.resize(width, height);
+        let (display_view, clear_render_pass, render_pass, framebuffer) =
+            create_display(&self.memory_manager, width, height);
+        self.display_view = display_view;
+        self.clear_render_pass = clear_render_pass;
+        self.render_pass = render_pass;
+        self.framebuffer = framebuffer;
+        self.frame = vec![0; (width * height * 4) as usize];
+    }
+
+    // Only the X11 windows have their own surfaces.
+    #[cfg(all(target_os = "linux", not(headless)))]
+    pub fn add_window(&mut self, os_app: &OsApp, window_id: Id) {
+        let surface = match Surface::new_for_window(os_app, window_id) {
+            Some(surface) => surface,
+            None => {
+                vx_log_e!("Window {} does not exist.", window_id);
+                return;
+            }
+        };
+        let (width, height) = surface.get_dimensions();
+        let frame = vec![0; (width * height * 4) as usize];
+        self.windows.insert(window_id, Window { surface, frame });
+    }
+
+    #[cfg(not(all(target_os = "linux", not(headless))))]
+    pub fn add_window(&mut self, _os_app: &OsApp, window_id: Id) {
+        vx_log_e!(
+            "Window {} can not have a surface on this platform.",
+            window_id
+        );
+    }
+
+    pub fn remove_window(&mut self, window_id: Id) {
+        self.windows.remove(&window_id);
+    }
+}
+
+fn create_display(
+    memory_manager: &Arc<RwLock<MemoryManager>>,
+    width: u32,
+    height: u32,
+) -> (
+    Arc<ImageView>,
+    Arc<RenderPass>,
+    Arc<RenderPass>,
+    Arc<Framebuffer>,
+) {
+    let display_view = Arc::new(ImageView::new_attachment(
+        memory_manager,
+        Format::RgbaByte,
+        AttachmentType::ColorDisplay,
+        width,
+        height,
+    ));
+    let render_pass = Arc::new(RenderPass::new(vec![display_view.clone()], false, false));
+    let clear_render_pass = Arc::new(RenderPass::new(vec![display_view.clone()], true, false));
+    let framebuffer = Arc::new(Framebuffer::new(
+        vec![display_view.clone()],
+        render_pass.clone(),
+    ));
+    return (display_view, clear_render_pass, render_pass, framebuffer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::core::object::Object as CoreObject;
+    use super::super::super::render::config::Configurations;
+    use super::super::super::render::pipeline::PipelineType;
+    use super::super::super::render::texture::Texture;
+    use super::*;
+
+    const MODEL_UNIFORM_SIZE: usize = 32;
+    const MATERIAL_UNIFORM_SIZE: usize = 36;
+    const MATERIAL_TEXTURES_COUNT: usize = 7;
+    const VERTEX_SIZE: usize = 12;
+    // Offsets of the scene uniform in floats, it has the camera, the lights,
+    // the counts of the lights and the ssao config.
+    const SCENE_UNIFORM_SIZE: usize = 700;
+    const SCENE_CAMERA_POSITION_OFFSET: usize = 12;
+    const SCENE_VIEW_PROJECTION_OFFSET: usize = 84;
+    const SCENE_DIRECTIONAL_LIGHTS_OFFSET: usize = 116;
+    const SCENE_LIGHTS_COUNT_OFFSET: usize = 692;
+    const DEFERRED_UNIFORM_SIZE: usize = 4;
+
+    #[cfg_attr(debug_mode, derive(Debug))]
+    struct TestTexture {
+        image_view: Arc<ImageView>,
+        sampler: Arc<Sampler>,
+    }
+
+    impl CoreObject for TestTexture {
+        fn get_id(&self) -> Id {
+            return 0;
+        }
+    }
+
+    impl Texture for TestTexture {
+        fn get_image_view(&self) -> &Arc<ImageView> {
+            return &self.image_view;
+        }
+
+        fn get_sampler(&self) -> &Arc<Sampler> {
+            return &self.sampler;
+        }
+    }
+
+    fn identity() -> [f32; 16] {
+        let mut result = [0.0; 16];
+        for i in 0..4 {
+            result[i * 5] = 1.0;
+        }
+        return result;
+    }
+
+    // Model and its projection are identity, so the positions are in clip
+    // space. Triangle must be counter-clockwise in framebuffer, because the
+    // pipelines cull the front faces like the Vulkan ones.
+    fn render_triangle(engine: &mut Engine, color: [u8; 4]) {
+        let config = Configurations::default();
+        let pipeline = vx_result!(engine.get_pipeline_manager().write()).create(
+            engine.get_render_pass().clone(),
+            PipelineType::Unlit,
+            &config,
+        );
+        let view = engine.create_texture_2d_with_pixels(1, 1, &color);
+        let mut textures: Vec<Arc<RwLock<dyn Texture>>> =
+            Vec::with_capacity(MATERIAL_TEXTURES_COUNT);
+        for _ in 0..MATERIAL_TEXTURES_COUNT {
+            textures.push(Arc::new(RwLock::new(TestTexture {
+                image_view: view.clone(),
+                sampler: engine.get_nearest_repeat_sampler().clone(),
+            })));
+        }
+        let mut model_data = [0.0f32; MODEL_UNIFORM_SIZE];
+        model_data[..16].copy_from_slice(&identity());
+        model_data[16..].copy_from_slice(&identity());
+        let mut material_data = [0.0f32; MATERIAL_UNIFORM_SIZE];
+        material_data[0] = 1.0; // alpha
+        material_data[12] = 1.0; // base color uv transform
+        material_data[17] = 1.0;
+        let mut vertices = [0.0f32; VERTEX_SIZE * 3];
+        for (i, (x, y)) in [(-0.5, -0.5), (-0.5, 0.5), (0.5, -0.5)].iter().enumerate() {
+            vertices[i * VERTEX_SIZE] = *x;
+            vertices[i * VERTEX_SIZE + 1] = *y;
+            vertices[i * VERTEX_SIZE + 2] = 0.5;
+        }
+        let (vertex_buffer, index_buffer, mut model_uniform, mut material_uniform) = {
+            let mut buffmgr = vx_result!(engine.get_buffer_manager().write());
+            (
+                buffmgr.create_static_buffer_with_vec(&vertices),
+                buffmgr.create_static_buffer_with_vec(&[0u32, 1, 2]),
+                buffmgr.create_dynamic_buffer((MODEL_UNIFORM_SIZE * 4) as isize),
+                buffmgr.create_dynamic_buffer((MATERIAL_UNIFORM_SIZE * 4) as isize),
+            )
+        };
+        let (model_set, material_set) = {
+            let mut descmgr = vx_result!(engine.get_descriptor_manager().write());
+            (
+                descmgr.create_buffer_only_set(&model_uniform),
+                descmgr.create_gbuff_set(&material_uniform, textures),
+            )
+        };
+        engine.start_rendering();
+        let frame_number = engine.get_frame_number();
+        model_uniform.update(&model_data, frame_number);
+        material_uniform.update(&material_data, frame_number);
+        let mut cmd = engine.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        engine.get_current_framebuffer().begin(&mut cmd);
+        cmd.bind_pipeline(&pipeline);
+        let buffer = vx_result!(model_uniform.get_buffer(frame_number).read());
+        cmd.bind_unlit_model_descriptor(&model_set, &buffer);
+        let buffer = vx_result!(material_uniform.get_buffer(frame_number).read());
+        cmd.bind_unlit_material_descriptor(&material_set, &buffer);
+        cmd.render_unlit(&vertex_buffer, &index_buffer, 3);
+        cmd.end_render_pass();
+        cmd.end();
+        let signal = engine.create_semaphore();
+        engine.submit(engine.get_starting_semaphore(), &cmd, &signal);
+        engine.end(&signal);
+    }
+
+    fn create_texture(engine: &Engine, view: Arc<ImageView>) -> Arc<RwLock<dyn Texture>> {
+        return Arc::new(RwLock::new(TestTexture {
+            image_view: view,
+            sampler: engine.get_nearest_repeat_sampler().clone(),
+        }));
+    }
+
+    fn create_pixel_texture(engine: &Engine, color: [u8; 4]) -> Arc<RwLock<dyn Texture>> {
+        return create_texture(engine, engine.create_texture_2d_with_pixels(1, 1, &color));
+    }
+
+    // Position-roughness, normal-metallic, albedo and depth.
+    fn create_gbuffer(engine: &Engine, width: u32, height: u32) -> Vec<Arc<ImageView>> {
+        let create = |format, attachment_type| {
+            Arc::new(ImageView::new_attachment(
+                engine.get_memory_manager(),
+                format,
+                attachment_type,
+                width,
+                height,
+            ))
+        };
+        return vec![
+            create(Format::RgbaFloat, AttachmentType::ColorGBuffer),
+            create(Format::RgbaFloat, AttachmentType::ColorGBuffer),
+            create(Format::RgbaFloat, AttachmentType::ColorGBuffer),
+            create(Format::DepthFloat, AttachmentType::DepthGBuffer),
+        ];
+    }
+
+    // Camera is at (0, 0, -1) and its view projection is identity, there is a
+    // white directional light that shines toward +z.
+    fn create_scene_data() -> [f32; SCENE_UNIFORM_SIZE] {
+        let mut data = [0.0f32; SCENE_UNIFORM_SIZE];
+        data[SCENE_CAMERA_POSITION_OFFSET..SCENE_CAMERA_POSITION_OFFSET + 4]
+            .copy_from_slice(&[0.0, 0.0, -1.0, 100.0]);
+        data[SCENE_VIEW_PROJECTION_OFFSET..SCENE_VIEW_PROJECTION_OFFSET + 16]
+            .copy_from_slice(&identity());
+        data[SCENE_DIRECTIONAL_LIGHTS_OFFSET..SCENE_DIRECTIONAL_LIGHTS_OFFSET + 8]
+            .copy_from_slice(&[1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+        data[SCENE_LIGHTS_COUNT_OFFSET] = f32::from_bits(1);
+        return data;
+    }
+
+    // Same triangle of render_triangle that faces the camera, its albedo is
+    // (255, 128, 0), its metallic is in blue and its roughness is in green
+    // channel of the metallic roughness texture like glTF.
+    fn fill_gbuffer(engine: &mut Engine, gbuffer: &[Arc<ImageView>]) {
+        let config = Configurations::default();
+        let render_pass = Arc::new(RenderPass::new(gbuffer.to_vec(), true, true));
+        let pipeline = vx_result!(engine.get_pipeline_manager().write()).create(
+            render_pass.clone(),
+            PipelineType::GBuffer,
+            &config,
+        );
+        let white = [255, 255, 255, 255];
+        let textures = vec![
+            create_pixel_texture(engine, [255, 128, 0, 255]),
+            create_pixel_texture(engine, white),
+            create_pixel_texture(engine, [0, 128, 64, 255]),
+            create_pixel_texture(engine, [128, 128, 255, 255]),
+            create_pixel_texture(engine, white),
+            create_pixel_texture(engine, white),
+            create_pixel_texture(engine, white),
+        ];
+        let mut model_data = [0.0f32; MODEL_UNIFORM_SIZE];
+        model_data[..16].copy_from_slice(&identity());
+        model_data[16..].copy_from_slice(&identity());
+        let mut material_data = [0.0f32; MATERIAL_UNIFORM_SIZE];
+        material_data[0] = 1.0; // alpha
+        material_data[2] = 1.0; // metallic factor
+        material_data[5] = 1.0; // roughness factor
+        material_data[9] = 1.0; // opaque
+        material_data[10] = 2.0; // metallic channel
+        material_data[11] = 1.0; // roughness channel
+        for t in 0..3 {
+            material_data[12 + t * 8] = 1.0; // uv transforms
+            material_data[17 + t * 8] = 1.0;
+        }
+        let mut vertices = [0.0f32; VERTEX_SIZE * 3];
+        for (i, (x, y)) in [(-0.5, -0.5), (-0.5, 0.5), (0.5, -0.5)].iter().enumerate() {
+            let vertex = &mut vertices[i * VERTEX_SIZE..(i + 1) * VERTEX_SIZE];
+            vertex[..3].copy_from_slice(&[*x, *y, 0.5]);
+            vertex[3..6].copy_from_slice(&[0.0, 0.0, -1.0]);
+            vertex[6..10].copy_from_slice(&[1.0, 0.0, 0.0, 1.0]);
+        }
+        let (vertex_buffer, index_buffer) = {
+            let mut buffmgr = vx_result!(engine.get_buffer_manager().write());
+            (
+                buffmgr.create_static_buffer_with_vec(&vertices),
+                buffmgr.create_static_buffer_with_vec(&[0u32, 1, 2]),
+            )
+        };
+        let (mut scene_uniform, mut model_uniform, mut material_uniform) = {
+            let mut buffmgr = vx_result!(engine.get_buffer_manager().write());
+            (
+                buffmgr.create_dynamic_buffer((SCENE_UNIFORM_SIZE * 4) as isize),
+                buffmgr.create_dynamic_buffer((MODEL_UNIFORM_SIZE * 4) as isize),
+                buffmgr.create_dynamic_buffer((MATERIAL_UNIFORM_SIZE * 4) as isize),
+            )
+        };
+        let (scene_set, model_set, material_set) = {
+            let mut descmgr = vx_result!(engine.get_descriptor_manager().write());
+            (
+                descmgr.create_buffer_only_set(&scene_uniform),
+                descmgr.create_buffer_only_set(&model_uniform),
+                descmgr.create_gbuff_set(&material_uniform, textures),
+            )
+        };
+        engine.start_rendering();
+        let frame_number = engine.get_frame_number();
+        scene_uniform.update(&create_scene_data(), frame_number);
+        model_uniform.update(&model_data, frame_number);
+        material_uniform.update(&material_data, frame_number);
+        let mut cmd = engine.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        Framebuffer::new(gbuffer.to_vec(), render_pass).begin(&mut cmd);
+        cmd.bind_pipeline(&pipeline);
+        let buffer = vx_result!(scene_uniform.get_buffer(frame_number).read());
+        cmd.bind_gbuff_scene_descriptor(&scene_set, &buffer);
+        let buffer = vx_result!(model_uniform.get_buffer(frame_number).read());
+        cmd.bind_gbuff_model_descriptor(&model_set, &buffer);
+        let buffer = vx_result!(material_uniform.get_buffer(frame_number).read());
+        cmd.bind_gbuff_material_descriptor(&material_set, &buffer);
+        cmd.render_gbuff(&vertex_buffer, &index_buffer, 3);
+        cmd.end_render_pass();
+        cmd.end();
+        let signal = engine.create_semaphore();
+        engine.submit(engine.get_starting_semaphore(), &cmd, &signal);
+    }
+
+    // The g-buffers are resolved on the display, ambient occlusion is one and
+    // nothing is in the shadow.
+    fn resolve_gbuffer(engine: &mut Engine, gbuffer: &[Arc<ImageView>]) {
+        let config = Configurations::default();
+        let pipeline = vx_result!(engine.get_pipeline_manager().write()).create(
+            engine.get_render_pass().clone(),
+            PipelineType::Deferred,
+            &config,
+        );
+        let mut textures = Vec::new();
+        for view in gbuffer {
+            textures.push(create_texture(engine, view.clone()));
+        }
+        textures.push(create_pixel_texture(engine, [255, 255, 255, 255]));
+        textures.push(create_pixel_texture(engine, [0, 0, 0, 0]));
+        let (mut scene_uniform, mut deferred_uniform) = {
+            let mut buffmgr = vx_result!(engine.get_buffer_manager().write());
+            (
+                buffmgr.create_dynamic_buffer((SCENE_UNIFORM_SIZE * 4) as isize),
+                buffmgr.create_dynamic_buffer((DEFERRED_UNIFORM_SIZE * 4) as isize),
+            )
+        };
+        let (scene_set, deferred_set) = {
+            let mut descmgr = vx_result!(engine.get_descriptor_manager().write());
+            (
+                descmgr.create_buffer_only_set(&scene_uniform),
+                descmgr.create_deferred_set(&deferred_uniform, textures),
+            )
+        };
+        let frame_number = engine.get_frame_number();
+        scene_uniform.update(&create_scene_data(), frame_number);
+        deferred_uniform.update(&[16.0f32, 16.0, 0.0, 0.0], frame_number);
+        let mut cmd = engine.create_primary_command_buffer_from_main_graphic_pool();
+        cmd.begin();
+        engine.get_current_framebuffer().begin(&mut cmd);
+        cmd.bind_pipeline(&pipeline);
+        let buffer = vx_result!(scene_uniform.get_buffer(frame_number).read());
+        cmd.bind_deferred_scene_descriptor(&scene_set, &buffer);
+        let buffer = vx_result!(deferred_uniform.get_buffer(frame_number).read());
+        cmd.bind_deferred_deferred_descriptor(&deferred_set, &buffer);
+        cmd.render_deferred();
+        cmd.end_render_pass();
+        cmd.end();
+        let signal = engine.create_semaphore();
+        engine.submit(engine.get_starting_semaphore(), &cmd, &signal);
+        engine.end(&signal);
+    }
+
+    fn get_pixel(capture: &Capture, x: u32, y: u32) -> &[u8] {
+        let i = ((y * capture.get_width() + x) * 4) as usize;
+        return &capture.get_pixels()[i..i + 4];
+    }
+
+    #[test]
+    fn test_triangle_pixels() {
+        let mut engine = Engine::new_offscreen(16, 16);
+        render_triangle(&mut engine, [255, 0, 0, 255]);
+        let capture = vx_unwrap!(engine.capture_frame());
+        // Triangle covers x >= 4, y >= 4 and x + y <= 16 in the framebuffer.
+        assert_eq!(get_pixel(&capture, 5, 5), &[255, 0, 0, 255]);
+        assert_eq!(get_pixel(&capture, 4, 10), &[255, 0, 0, 255]);
+        assert_eq!(get_pixel(&capture, 10, 4), &[255, 0, 0, 255]);
+        assert_eq!(get_pixel(&capture, 1, 1), &[0, 0, 0, 0]);
+        assert_eq!(get_pixel(&capture, 12, 12), &[0, 0, 0, 0]);
+        assert_eq!(get_pixel(&capture, 2, 14), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_update_clears() {
+        let mut engine = Engine::new_offscreen(16, 16);
+        render_triangle(&mut engine, [0, 255, 0, 255]);
+        engine.update();
+        let capture = vx_unwrap!(engine.capture_frame());
+        assert!(capture.get_pixels().iter().all(|p| *p == 0));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut engine = Engine::new_offscreen(16, 16);
+        engine.resize(32, 8);
+        render_triangle(&mut engine, [255, 0, 0, 255]);
+        let capture = vx_unwrap!(engine.capture_frame());
+        assert_eq!((capture.get_width(), capture.get_height()), (32, 8));
+        assert_eq!(capture.get_pixels().len(), 32 * 8 * 4);
+        // Triangle covers x >= 8, y >= 2 and x / 32 + y / 8 <= 0.75.
+        assert_eq!(get_pixel(&capture, 9, 3), &[255, 0, 0, 255]);
+        assert_eq!(get_pixel(&capture, 30, 6), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_gbuffer_fill() {
+        let mut engine = Engine::new_offscreen(16, 16);
+        let gbuffer = create_gbuffer(&engine, 16, 16);
+        fill_gbuffer(&mut engine, &gbuffer);
+        let texel = |i: usize, x, y| *vx_result!(gbuffer[i].get_image().read()).get(x, y);
+        let is_near =
+            |a: [f32; 4], b: [f32; 4]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-5);
+        // Center of (5, 5) in clip space.
+        let p = 5.5 / 8.0 - 1.0;
+        assert!(is_near(texel(0, 5, 5), [p, p, 0.5, 128.0 / 255.0]));
+        assert!(is_near(texel(1, 5, 5), [0.0, 0.0, -1.0, 64.0 / 255.0]));
+        assert!(is_near(texel(2, 5, 5), [1.0, 128.0 / 255.0, 0.0, 1.0]));
+        assert!(is_near(texel(3, 5, 5), [0.5, 0.0, 0.0, 0.0]));
+        for i in 0..3 {
+            assert!(texel(i, 12, 12) == [0.0; 4]);
+        }
+        assert!(texel(3, 12, 12) == [1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_deferred_resolve() {
+        let mut engine = Engine::new_offscreen(16, 16);
+        let gbuffer = create_gbuffer(&engine, 16, 16);
+        fill_gbuffer(&mut engine, &gbuffer);
+        resolve_gbuffer(&mut engine, &gbuffer);
+        let capture = vx_unwrap!(engine.capture_frame());
+        // Golden values of the ambient and the directional light terms after
+        // the tone mapping.
+        assert_eq!(get_pixel(&capture, 5, 5), &[159, 130, 21, 255]);
+        assert_eq!(get_pixel(&capture, 10, 4), &[159, 130, 21, 255]);
+        assert_eq!(get_pixel(&capture, 12, 12), &[0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,37 @@
+use super::command::Buffer as CmdBuffer;
+use super::image::View as ImageView;
+use super::render_pass::RenderPass;
+use std::sync::Arc;
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Framebuffer {
+    buffers: Vec<Arc<ImageView>>,
+    render_pass: Arc<RenderPass>,
+}
+
+impl Framebuffer {
+    pub(crate) fn new(buffers: Vec<Arc<ImageView>>, render_pass: Arc<RenderPass>) -> Self {
+        #[cfg(debug_mode)]
+        {
+            if buffers.is_empty() {
+                vx_log_f!("Framebuffer must have at least one attachment.");
+            }
+        }
+        Self {
+            buffers,
+            render_pass,
+        }
+    }
+
+    pub(crate) fn begin(&self, cmd_buffer: &mut CmdBuffer) {
+        cmd_buffer.begin_render_pass_with_render_pass(&self.render_pass);
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+
+    pub(crate) fn get_dimensions(&self) -> (u32, u32) {
+        return vx_result!(self.buffers[0].get_image().read()).get_dimensions();
+    }
+}
@@ -0,0 +1,256 @@
+use super::super::core::types::Real;
+use super::super::math::vector::Vec3;
+use super::super::render::image::{AttachmentType, Format};
+use super::super::render::sampler::Filter;
+use super::super::render::texture::container::Container;
+use super::buffer::Manager as BufferManager;
+use super::memory::Manager as MemoryManager;
+use std::sync::{Arc, RwLock};
+
+// All the texels are kept in linear float RGBA, depth images use only the
+// first channel. Cube images keep their six faces after each other in
+// +x, -x, +y, -y, +z, -z order.
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Image {
+    width: u32,
+    height: u32,
+    layers_count: u32,
+    is_depth: bool,
+    texels: Vec<[Real; 4]>,
+}
+
+impl Image {
+    pub(crate) fn new(width: u32, height: u32, layers_count: u32, is_depth: bool) -> Self {
+        let clear = if is_depth {
+            [1.0, 0.0, 0.0, 0.0]
+        } else {
+            [0.0, 0.0, 0.0, 0.0]
+        };
+        Self {
+            width,
+            height,
+            layers_count,
+            is_depth,
+            texels: vec![clear; (width * height * layers_count) as usize],
+        }
+    }
+
+    pub(crate) fn new_2d_with_pixels(width: u32, height: u32, data: &[u8]) -> Self {
+        let mut myself = Self::new(width, height, 1, false);
+        myself.write_pixels(0, data);
+        return myself;
+    }
+
+    pub(crate) fn new_cube_with_pixels(width: u32, height: u32, data: &[&[u8]; 6]) -> Self {
+        let mut myself = Self::new(width, height, 6, false);
+        for i in 0..6 {
+            myself.write_pixels(i as u32, data[i]);
+        }
+        return myself;
+    }
+
+    /// Only the first level is kept, the compressed formats are decoded.
+    pub(crate) fn new_with_container(container: &Container) -> Self {
+        let decoded;
+        let container = if container.get_format().is_compressed() {
+            decoded = container.decode();
+            &decoded
+        } else {
+            container
+        };
+        let (width, height) = container.get_dimensions();
+        let layers_count = container.get_layers_count() * container.get_faces_count();
+        let mut myself = Self::new(width, height, layers_count, false);
+        let level = &container.get_levels()[0];
+        let size = (width * height * 4) as usize;
+        for i in 0..layers_count {
+            let start = i as usize * size;
+            myself.write_pixels(i, &level[start..start + size]);
+        }
+        return myself;
+    }
+
+    fn write_pixels(&mut self, layer: u32, data: &[u8]) {
+        let size = (self.width * self.height) as usize;
+        #[cfg(debug_mode)]
+        {
+            if data.len() != size * 4 {
+                vx_log_f!("Pixels data must be RGBA and have the size of the image.");
+            }
+        }
+        let start = layer as usize * size;
+        for i in 0..size {
+            let p = &data[i * 4..i * 4 + 4];
+            self.texels[start + i] = [
+                p[0] as Real / 255.0,
+                p[1] as Real / 255.0,
+                p[2] as Real / 255.0,
+                p[3] as Real / 255.0,
+            ];
+        }
+    }
+
+    pub(crate) fn get_dimensions(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    pub(crate) fn is_depth(&self) -> bool {
+        return self.is_depth;
+    }
+
+    pub(crate) fn clear(&mut self, v: [Real; 4]) {
+        for t in &mut self.texels {
+            *t = v;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, x: u32, y: u32) -> &[Real; 4] {
+        return &self.texels[(y * self.width + x) as usize];
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, x: u32, y: u32, v: [Real; 4]) {
+        self.texels[(y * self.width + x) as usize] = v;
+    }
+
+    fn fetch(&self, layer: u32, x: i64, y: i64) -> [Real; 4] {
+        // repeat addressing
+        let x = x.rem_euclid(self.width as i64) as u32;
+        let y = y.rem_euclid(self.height as i64) as u32;
+        return self.texels[((layer * self.height + y) * self.width + x) as usize];
+    }
+
+    fn sample_layer(&self, layer: u32, u: Real, v: Real, filter: Filter) -> [Real; 4] {
+        let x = u * self.width as Real;
+        let y = v * self.height as Real;
+        match filter {
+            Filter::Nearest => return self.fetch(layer, x.floor() as i64, y.floor() as i64),
+            _ => {
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let x0 = x.floor();
+                let y0 = y.floor();
+                let fx = x - x0;
+                let fy = y - y0;
+                let x0 = x0 as i64;
+                let y0 = y0 as i64;
+                let t00 = self.fetch(layer, x0, y0);
+                let t10 = self.fetch(layer, x0 + 1, y0);
+                let t01 = self.fetch(layer, x0, y0 + 1);
+                let t11 = self.fetch(layer, x0 + 1, y0 + 1);
+                let mut result = [0.0; 4];
+                for i in 0..4 {
+                    let top = t00[i] + (t10[i] - t00[i]) * fx;
+                    let bottom = t01[i] + (t11[i] - t01[i]) * fx;
+                    result[i] = top + (bottom - top) * fy;
+                }
+                return result;
+            }
+        }
+    }
+
+    pub(crate) fn sample(&self, u: Real, v: Real, filter: Filter) -> [Real; 4] {
+        return self.sample_layer(0, u, v, filter);
+    }
+
+    pub(crate) fn sample_cube(&self, d: &Vec3, filter: Filter) -> [Real; 4] {
+        let [x, y, z] = d.to_array();
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+        let (layer, ma, sc, tc) = if ax >= ay && ax >= az {
+            if x > 0.0 {
+                (0, ax, -z, -y)
+            } else {
+                (1, ax, z, -y)
+            }
+        } else if ay >= az {
+            if y > 0.0 {
+                (2, ay, x, z)
+            } else {
+                (3, ay, x, -z)
+            }
+        } else if z > 0.0 {
+            (4, az, x, -y)
+        } else {
+            (5, az, -x, -y)
+        };
+        if ma == 0.0 {
+            return [0.0; 4];
+        }
+        let u = (sc / ma + 1.0) * 0.5;
+        let v = (tc / ma + 1.0) * 0.5;
+        return self.sample_layer(layer, u, v, filter);
+    }
+
+    pub(crate) fn to_rgba8(&self) -> Vec<u8> {
+        let size = (self.width * self.height) as usize;
+        let mut result = Vec::with_capacity(size * 4);
+        for t in &self.texels[..size] {
+            for c in t {
+                result.push((c.max(0.0).min(1.0) * 255.0 + 0.5) as u8);
+            }
+        }
+        return result;
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct View {
+    image: Arc<RwLock<Image>>,
+}
+
+impl View {
+    pub(crate) fn new_texture_2d_with_pixels(
+        width: u32,
+        height: u32,
+        data: &[u8],
+        _buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let image = Image::new_2d_with_pixels(width, height, data);
+        return Self::new_with_image(Arc::new(RwLock::new(image)));
+    }
+
+    pub(crate) fn new_texture_cube_with_pixels(
+        width: u32,
+        height: u32,
+        data: &[&[u8]; 6],
+        _buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let image = Image::new_cube_with_pixels(width, height, data);
+        return Self::new_with_image(Arc::new(RwLock::new(image)));
+    }
+
+    pub(crate) fn new_texture_with_container(
+        container: &Container,
+        _buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let image = Image::new_with_container(container);
+        return Self::new_with_image(Arc::new(RwLock::new(image)));
+    }
+
+    pub(crate) fn new_with_image(image: Arc<RwLock<Image>>) -> Self {
+        Self { image }
+    }
+
+    pub(crate) fn new_attachment(
+        _memory_mgr: &Arc<RwLock<MemoryManager>>,
+        _format: Format,
+        attachment_type: AttachmentType,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let is_depth = match attachment_type {
+            AttachmentType::DepthGBuffer
+            | AttachmentType::DepthShadowBuffer
+            | AttachmentType::DepthStencilDisplay => true,
+            _ => false,
+        };
+        let image = Image::new(width, height, 1, is_depth);
+        return Self::new_with_image(Arc::new(RwLock::new(image)));
+    }
+
+    pub(crate) fn get_image(&self) -> &Arc<RwLock<Image>> {
+        return &self.image;
+    }
+}
@@ -0,0 +1,11 @@
+// There is no device memory in the software graphic API, images and buffers
+// own their host memory. It is kept to have the same surface as other APIs.
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Manager {}
+
+impl Manager {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
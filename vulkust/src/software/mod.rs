@@ -0,0 +1,16 @@
+pub mod buffer;
+pub mod capture;
+pub mod command;
+pub mod descriptor;
+pub mod device;
+pub mod engine;
+pub mod framebuffer;
+pub mod image;
+pub mod memory;
+pub mod pipeline;
+pub mod raster;
+pub mod render_pass;
+pub mod sampler;
+pub mod shader;
+pub mod surface;
+pub mod sync;
@@ -0,0 +1,94 @@
+use super::super::render::config::Configurations;
+use super::super::render::pipeline::PipelineType;
+use super::raster::CullMode;
+use super::render_pass::RenderPass;
+use std::collections::BTreeMap;
+use std::mem::transmute;
+use std::sync::{Arc, Weak};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) enum Blending {
+    Nothing,
+    Alpha,
+    Additive,
+}
+
+// It mirrors the fixed function states of the Vulkan pipelines, the shader
+// stages are selected by the pipeline type at draw time.
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct State {
+    pub(super) pipeline_type: PipelineType,
+    pub(super) cull: CullMode,
+    pub(super) depth_test: bool,
+    pub(super) depth_write: bool,
+    pub(super) blending: Blending,
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Pipeline {
+    state: State,
+    render_pass: Arc<RenderPass>,
+}
+
+impl Pipeline {
+    fn new(render_pass: Arc<RenderPass>, pipeline_type: PipelineType) -> Self {
+        let blending = match pipeline_type {
+            PipelineType::Deferred | PipelineType::Unlit | PipelineType::TransparentPBR => {
+                Blending::Alpha
+            }
+            PipelineType::ShadowAccumulatorDirectional => Blending::Additive,
+            _ => Blending::Nothing,
+        };
+        Self {
+            state: State {
+                pipeline_type,
+                cull: CullMode::Front,
+                depth_test: true,
+                depth_write: true,
+                blending,
+            },
+            render_pass,
+        }
+    }
+
+    pub(super) fn get_state(&self) -> &State {
+        return &self.state;
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Manager {
+    pipelines: BTreeMap<(usize, u8), Weak<Pipeline>>, // (renderpass, pipeline-type) -> pipeline
+}
+
+impl Manager {
+    pub(super) fn new() -> Self {
+        Self {
+            pipelines: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn create(
+        &mut self,
+        render_pass: Arc<RenderPass>,
+        pipeline_type: PipelineType,
+        _config: &Configurations,
+    ) -> Arc<Pipeline> {
+        let rpptr: usize = unsafe { transmute(&*render_pass as *const RenderPass) };
+        let id = (rpptr, pipeline_type as u8);
+        if let Some(p) = self.pipelines.get(&id) {
+            if let Some(p) = p.upgrade() {
+                return p;
+            }
+        }
+        let p = Arc::new(Pipeline::new(render_pass, pipeline_type));
+        self.pipelines.insert(id, Arc::downgrade(&p));
+        return p;
+    }
+}
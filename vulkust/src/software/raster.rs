@@ -0,0 +1,306 @@
+use super::super::core::types::Real;
+
+// Triangle rasterizer of the software graphic API.
+// Positions are in Vulkan clip space (0 <= z <= w) and framebuffer y goes
+// down, so the same matrices and winding of the Vulkan pipelines work here.
+// Triangles are clipped against the near plane, depth is linear in screen
+// space and the other varyings are interpolated perspective correctly.
+// Pixel centers are at +0.5 and the top-left fill rule is used, so the shared
+// edges of adjacent triangles are drawn exactly once.
+
+pub(super) const MAX_VARYINGS_COUNT: usize = 16;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Vertex {
+    pub(super) position: [Real; 4],
+    pub(super) varyings: [Real; MAX_VARYINGS_COUNT],
+}
+
+impl Vertex {
+    pub(super) fn new(position: [Real; 4]) -> Self {
+        Self {
+            position,
+            varyings: [0.0; MAX_VARYINGS_COUNT],
+        }
+    }
+
+    fn lerp(&self, o: &Self, t: Real, varyings_count: usize) -> Self {
+        let mut result = *self;
+        for i in 0..4 {
+            result.position[i] += (o.position[i] - self.position[i]) * t;
+        }
+        for i in 0..varyings_count {
+            result.varyings[i] += (o.varyings[i] - self.varyings[i]) * t;
+        }
+        return result;
+    }
+}
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) struct Fragment {
+    pub(super) x: u32,
+    pub(super) y: u32,
+    pub(super) depth: Real,
+    pub(super) varyings: [Real; MAX_VARYINGS_COUNT],
+}
+
+// Front faces are the clockwise ones in the framebuffer, same as the Vulkan
+// pipelines of the engine.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(super) enum CullMode {
+    Nothing,
+    Front,
+    Back,
+}
+
+struct ScreenVertex {
+    x: Real,
+    y: Real,
+    z: Real,
+    inversed_w: Real,
+}
+
+impl ScreenVertex {
+    fn new(v: &Vertex, width: u32, height: u32) -> Self {
+        let inversed_w = 1.0 / v.position[3];
+        Self {
+            x: (v.position[0] * inversed_w + 1.0) * 0.5 * width as Real,
+            y: (v.position[1] * inversed_w + 1.0) * 0.5 * height as Real,
+            z: v.position[2] * inversed_w,
+            inversed_w,
+        }
+    }
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: Real, y: Real) -> Real {
+    return (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x);
+}
+
+fn is_top_left(a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    return (a.y == b.y && b.x > a.x) || b.y < a.y;
+}
+
+fn is_inside(w: Real, is_top_left: bool) -> bool {
+    return w > 0.0 || (w == 0.0 && is_top_left);
+}
+
+const NEAR_EPSILON: Real = 0.000_001;
+
+// Sutherland-Hodgman against z >= 0, result has at most 4 vertices.
+fn clip_near(vertices: &[Vertex; 3], varyings_count: usize) -> Vec<Vertex> {
+    let distance = |v: &Vertex| v.position[2];
+    let mut result = Vec::with_capacity(4);
+    for i in 0..3 {
+        let current = &vertices[i];
+        let next = &vertices[(i + 1) % 3];
+        let dc = distance(current);
+        let dn = distance(next);
+        if dc >= 0.0 {
+            result.push(*current);
+        }
+        if (dc >= 0.0) != (dn >= 0.0) {
+            result.push(current.lerp(next, dc / (dc - dn), varyings_count));
+        }
+    }
+    return result;
+}
+
+pub(super) fn draw_triangle<F>(
+    vertices: &[Vertex; 3],
+    width: u32,
+    height: u32,
+    cull: CullMode,
+    varyings_count: usize,
+    fragment: &mut F,
+) where
+    F: FnMut(&Fragment),
+{
+    let all_inside = vertices
+        .iter()
+        .all(|v| v.position[2] >= 0.0 && v.position[3] > NEAR_EPSILON);
+    if all_inside {
+        draw_clipped(vertices, width, height, cull, varyings_count, fragment);
+        return;
+    }
+    let polygon = clip_near(vertices, varyings_count);
+    for i in 2..polygon.len() {
+        let triangle = [polygon[0], polygon[i - 1], polygon[i]];
+        draw_clipped(&triangle, width, height, cull, varyings_count, fragment);
+    }
+}
+
+fn draw_clipped<F>(
+    vertices: &[Vertex; 3],
+    width: u32,
+    height: u32,
+    cull: CullMode,
+    varyings_count: usize,
+    fragment: &mut F,
+) where
+    F: FnMut(&Fragment),
+{
+    let mut s = [
+        ScreenVertex::new(&vertices[0], width, height),
+        ScreenVertex::new(&vertices[1], width, height),
+        ScreenVertex::new(&vertices[2], width, height),
+    ];
+    let mut order = [0usize, 1, 2];
+    let area = edge(&s[0], &s[1], s[2].x, s[2].y);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+    // positive area is clockwise in framebuffer
+    match cull {
+        CullMode::Front if area > 0.0 => return,
+        CullMode::Back if area < 0.0 => return,
+        _ => (),
+    }
+    if area < 0.0 {
+        s.swap(1, 2);
+        order.swap(1, 2);
+    }
+    let area = area.abs();
+    let min_x = s.iter().fold(Real::MAX, |m, v| m.min(v.x)).floor().max(0.0) as u32;
+    let min_y = s.iter().fold(Real::MAX, |m, v| m.min(v.y)).floor().max(0.0) as u32;
+    let max_x = s.iter().fold(Real::MIN, |m, v| m.max(v.x)).ceil();
+    let max_y = s.iter().fold(Real::MIN, |m, v| m.max(v.y)).ceil();
+    if max_x <= 0.0 || max_y <= 0.0 {
+        return;
+    }
+    let max_x = (max_x as u32).min(width);
+    let max_y = (max_y as u32).min(height);
+    let tl = [
+        is_top_left(&s[1], &s[2]),
+        is_top_left(&s[2], &s[0]),
+        is_top_left(&s[0], &s[1]),
+    ];
+    let mut f = Fragment {
+        x: 0,
+        y: 0,
+        depth: 0.0,
+        varyings: [0.0; MAX_VARYINGS_COUNT],
+    };
+    for y in min_y..max_y {
+        let py = y as Real + 0.5;
+        for x in min_x..max_x {
+            let px = x as Real + 0.5;
+            let w0 = edge(&s[1], &s[2], px, py);
+            let w1 = edge(&s[2], &s[0], px, py);
+            let w2 = edge(&s[0], &s[1], px, py);
+            if !(is_inside(w0, tl[0]) && is_inside(w1, tl[1]) && is_inside(w2, tl[2])) {
+                continue;
+            }
+            let b = [w0 / area, w1 / area, w2 / area];
+            let depth = b[0] * s[0].z + b[1] * s[1].z + b[2] * s[2].z;
+            if depth > 1.0 {
+                continue;
+            }
+            let pb = [
+                b[0] * s[0].inversed_w,
+                b[1] * s[1].inversed_w,
+                b[2] * s[2].inversed_w,
+            ];
+            let inversed_sum = 1.0 / (pb[0] + pb[1] + pb[2]);
+            for i in 0..varyings_count {
+                f.varyings[i] = (pb[0] * vertices[order[0]].varyings[i]
+                    + pb[1] * vertices[order[1]].varyings[i]
+                    + pb[2] * vertices[order[2]].varyings[i])
+                    * inversed_sum;
+            }
+            f.x = x;
+            f.y = y;
+            f.depth = depth;
+            fragment(&f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vertex(x: Real, y: Real, z: Real, w: Real, v: Real) -> Vertex {
+        let mut result = Vertex::new([x * w, y * w, z * w, w]);
+        result.varyings[0] = v;
+        return result;
+    }
+
+    #[test]
+    fn test_quad_coverage() {
+        let a = vertex(-1.0, -1.0, 0.5, 1.0, 0.0);
+        let b = vertex(1.0, -1.0, 0.5, 1.0, 0.0);
+        let c = vertex(1.0, 1.0, 0.5, 1.0, 0.0);
+        let d = vertex(-1.0, 1.0, 0.5, 1.0, 0.0);
+        let mut counts = vec![0u32; 16];
+        let mut f = |f: &Fragment| counts[(f.y * 4 + f.x) as usize] += 1;
+        draw_triangle(&[a, b, c], 4, 4, CullMode::Nothing, 0, &mut f);
+        draw_triangle(&[a, c, d], 4, 4, CullMode::Nothing, 0, &mut f);
+        assert!(counts.iter().all(|c| *c == 1));
+    }
+
+    fn count(vertices: &[Vertex; 3], cull: CullMode) -> u32 {
+        let mut result = 0;
+        let mut f = |f: &Fragment| {
+            assert!(f.depth >= 0.0 && f.depth <= 1.0);
+            result += 1;
+        };
+        draw_triangle(vertices, 8, 8, cull, 0, &mut f);
+        return result;
+    }
+
+    #[test]
+    fn test_culling() {
+        // clockwise in framebuffer, so it is front
+        let a = vertex(-1.0, -1.0, 0.5, 1.0, 0.0);
+        let b = vertex(1.0, -1.0, 0.5, 1.0, 0.0);
+        let c = vertex(-1.0, 1.0, 0.5, 1.0, 0.0);
+        assert_eq!(count(&[a, b, c], CullMode::Front), 0);
+        assert_eq!(count(&[a, c, b], CullMode::Back), 0);
+        assert!(count(&[a, c, b], CullMode::Front) > 0);
+        assert_eq!(
+            count(&[a, b, c], CullMode::Nothing),
+            count(&[a, c, b], CullMode::Nothing)
+        );
+    }
+
+    #[test]
+    fn test_near_clipping() {
+        let behind = [
+            vertex(-1.0, -1.0, -0.5, 1.0, 0.0),
+            vertex(1.0, -1.0, -0.5, 1.0, 0.0),
+            vertex(-1.0, 1.0, -0.5, 1.0, 0.0),
+        ];
+        assert_eq!(count(&behind, CullMode::Nothing), 0);
+        let whole = [
+            vertex(-1.0, -1.0, 0.5, 1.0, 0.0),
+            vertex(1.0, -1.0, 0.5, 1.0, 0.0),
+            vertex(-1.0, 1.0, 0.5, 1.0, 0.0),
+        ];
+        let crossing = [
+            vertex(-1.0, -1.0, -0.5, 1.0, 0.0),
+            vertex(1.0, -1.0, 0.5, 1.0, 0.0),
+            vertex(-1.0, 1.0, 0.5, 1.0, 0.0),
+        ];
+        let crossing_count = count(&crossing, CullMode::Nothing);
+        assert!(crossing_count > 0);
+        assert!(crossing_count < count(&whole, CullMode::Nothing));
+    }
+
+    #[test]
+    fn test_perspective_correct_varyings() {
+        let a = vertex(-1.0, -1.0, 0.5, 1.0, 0.0);
+        let b = vertex(1.0, -1.0, 0.5, 4.0, 1.0);
+        let c = vertex(-1.0, 1.0, 0.5, 1.0, 0.0);
+        let mut f = |f: &Fragment| {
+            let screen_t = (f.x as Real + 0.5) / 16.0;
+            // far vertex has less share in screen space than in linear space
+            if f.y == 0 && f.x > 0 && f.x < 15 {
+                assert!(f.varyings[0] < screen_t);
+            }
+            assert!(f.varyings[0] >= 0.0 && f.varyings[0] <= 1.0);
+        };
+        draw_triangle(&[a, b, c], 16, 16, CullMode::Nothing, 1, &mut f);
+    }
+}
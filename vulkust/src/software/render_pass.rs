@@ -0,0 +1,51 @@
+use super::super::render::image::Layout;
+use super::image::View as ImageView;
+use std::sync::Arc;
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct RenderPass {
+    colors: Vec<Arc<ImageView>>,
+    depth: Option<Arc<ImageView>>,
+    clear: bool,
+}
+
+impl RenderPass {
+    pub(crate) fn new(views: Vec<Arc<ImageView>>, clear: bool, _has_reader: bool) -> Self {
+        let mut colors = Vec::with_capacity(views.len());
+        let mut depth = None;
+        for v in views {
+            if vx_result!(v.get_image().read()).is_depth() {
+                depth = Some(v);
+            } else {
+                colors.push(v);
+            }
+        }
+        Self {
+            colors,
+            depth,
+            clear,
+        }
+    }
+
+    // Images do not have layouts in host memory.
+    pub(crate) fn new_with_layouts(
+        views: Vec<Arc<ImageView>>,
+        clear: bool,
+        _start_layouts: &[Layout],
+        _end_layouts: &[Layout],
+    ) -> Self {
+        return Self::new(views, clear, false);
+    }
+
+    pub(crate) fn get_color_attachments(&self) -> &[Arc<ImageView>] {
+        return &self.colors;
+    }
+
+    pub(crate) fn get_depth_attachment(&self) -> Option<&Arc<ImageView>> {
+        return self.depth.as_ref();
+    }
+
+    pub(crate) fn get_clear(&self) -> bool {
+        return self.clear;
+    }
+}
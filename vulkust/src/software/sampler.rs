@@ -0,0 +1,20 @@
+use super::super::render::sampler::Filter;
+
+#[cfg_attr(debug_mode, derive(Debug))]
+pub struct Sampler {
+    filter: Filter,
+}
+
+impl Sampler {
+    pub(crate) fn new() -> Self {
+        return Self::new_with_filter(Filter::Linear);
+    }
+
+    pub(crate) fn new_with_filter(filter: Filter) -> Self {
+        Self { filter }
+    }
+
+    pub(crate) fn get_filter(&self) -> Filter {
+        return self.filter;
+    }
+}
@@ -0,0 +1,739 @@
+use super::super::core::constants::{
+    MAX_DIRECTIONAL_LIGHTS_COUNT, MAX_POINT_LIGHTS_COUNT, MAX_SPOT_LIGHTS_COUNT,
+};
+use super::super::core::types::Real;
+use super::super::math::matrix::Mat4;
+use super::super::math::vector::{Vec3, Vec4};
+use super::super::render::pipeline::PipelineType;
+use super::super::render::sampler::Filter;
+use super::super::render::skeleton::MAX_JOINTS_COUNT;
+use super::command::{Binding, Draw};
+use super::image::Image;
+use super::pipeline::Blending;
+use super::raster::{draw_triangle, Fragment, Vertex, MAX_VARYINGS_COUNT};
+use super::render_pass::RenderPass;
+use std::f32::consts::PI;
+use std::ptr::read_unaligned;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// Rust ports of the shaders in vulkan/shaders, uniform structures must keep
+// the std140 layout of common.glsl.
+
+const MAX_SSAO_SAMPLES_COUNT: usize = 128;
+const NORMAL_EPSILON: Real = 0.005;
+const SMALL_EPSILON: Real = 0.00001;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Camera {
+    x: Vec4,
+    y: Vec4,
+    z: Vec4,
+    position_far: Vec4,
+    near_aspect_ratio_reserved: Vec4,
+    inversed_rotation: Mat4,
+    view: Mat4,
+    projection: Mat4,
+    uniform_projection: Mat4,
+    view_projection: Mat4,
+    uniform_view_projection: Mat4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct PointLight {
+    color_minradius: Vec4,
+    position_radius: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DirectionalLight {
+    color: Vec4,
+    direction_strength: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SpotLight {
+    color_minradius: Vec4,
+    position_radius: Vec4,
+    direction_cone_scale: Vec4,
+    cone_offset_reserved: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Scene {
+    camera: Camera,
+    directional_lights: [DirectionalLight; MAX_DIRECTIONAL_LIGHTS_COUNT],
+    point_lights: [PointLight; MAX_POINT_LIGHTS_COUNT],
+    spot_lights: [SpotLight; MAX_SPOT_LIGHTS_COUNT],
+    lights_count: [u32; 4],
+    ssao_config: Vec4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Ssao {
+    sample_vectors: [Vec4; MAX_SSAO_SAMPLES_COUNT],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Model {
+    model: Mat4,
+    model_view_projection: Mat4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Material {
+    alpha: Real,
+    alpha_cutoff: Real,
+    metallic_factor: Real,
+    normal_scale: Real,
+    occlusion_strength: Real,
+    roughness_factor: Real,
+    emissive_strength: Real,
+    rezerved: Real,
+    modes: Vec4,
+    base_color_uv_transform: [Vec4; 2],
+    metallic_roughness_uv_transform: [Vec4; 2],
+    emissive_uv_transform: [Vec4; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ModelShadow {
+    model_view_projection: Mat4,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Skin {
+    joints: [Mat4; MAX_JOINTS_COUNT],
+}
+
+// Vertex layout of the Vulkan pipelines, 48 bytes.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct VertexAttributes {
+    pos: [Real; 3],
+    nrm: [Real; 3],
+    tng: [Real; 4],
+    uv: [Real; 2],
+}
+
+// Vertex layout of the skinned pipelines, 80 bytes.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SkinnedVertexAttributes {
+    attributes: VertexAttributes,
+    joints: [Real; 4],
+    weights: [Real; 4],
+}
+
+impl SkinnedVertexAttributes {
+    // The skinned vertex shaders do it before the model transformation, so
+    // the rest of the programs stay the same.
+    fn skin(&self, skin: &Skin) -> VertexAttributes {
+        let mut cols = [Vec4::zero(); 4];
+        for (j, w) in self.joints.iter().zip(self.weights.iter()) {
+            let joint = &skin.joints[*j as usize];
+            for (i, c) in cols.iter_mut().enumerate() {
+                *c += joint.get_col(i) * *w;
+            }
+        }
+        let m = Mat4::from_cols(cols[0], cols[1], cols[2], cols[3]);
+        let a = &self.attributes;
+        let pos = m.transform_point(Vec3::from_array(a.pos));
+        let nrm = m.transform_vector(Vec3::from_array(a.nrm));
+        let tng = m.transform_vector(Vec3::new(a.tng[0], a.tng[1], a.tng[2]));
+        return VertexAttributes {
+            pos: pos.to_array(),
+            nrm: nrm.to_array(),
+            tng: tng.extend(a.tng[3]).to_array(),
+            uv: a.uv,
+        };
+    }
+}
+
+fn read<T: Copy>(data: &[u8]) -> T {
+    #[cfg(debug_mode)]
+    {
+        if std::mem::size_of::<T>() > data.len() {
+            vx_log_f!("Uniform buffer is smaller than its structure.");
+        }
+    }
+    return unsafe { read_unaligned(data.as_ptr() as *const T) };
+}
+
+// Read locked images of a descriptor set, in the order of their bindings.
+struct Samplers<'a> {
+    images: Vec<(RwLockReadGuard<'a, Image>, Filter)>,
+}
+
+impl<'a> Samplers<'a> {
+    fn new(images: &'a [(Arc<RwLock<Image>>, Filter)]) -> Self {
+        let mut result = Vec::with_capacity(images.len());
+        for (image, filter) in images {
+            result.push((vx_result!(image.read()), *filter));
+        }
+        Self { images: result }
+    }
+
+    fn sample(&self, binding: usize, u: Real, v: Real) -> Vec4 {
+        let (image, filter) = &self.images[binding];
+        return Vec4::from_array(image.sample(u, v, *filter));
+    }
+}
+
+fn collect_images(binding: &Binding) -> Vec<(Arc<RwLock<Image>>, Filter)> {
+    let mut result = Vec::with_capacity(binding.texturess.len());
+    for textures in &binding.texturess {
+        let texture = vx_result!(textures[0].read());
+        let image = texture.get_image_view().get_image().clone();
+        result.push((image, texture.get_sampler().get_filter()));
+    }
+    return result;
+}
+
+// Normal Distribution Function Trowbridge-Reitz GGX
+fn ndftrggx(normal: &Vec3, halfway: &Vec3, roughness: Real) -> Real {
+    let roughness2 = roughness * roughness;
+    let nh = normal.dot(*halfway).max(0.0);
+    let nh2 = nh * nh;
+    let tmpdenom = nh2 * (roughness2 - 1.0) + 1.0;
+    return roughness2 / (PI * tmpdenom * tmpdenom);
+}
+
+fn gfschggx_single(nd: Real, roughness: Real) -> Real {
+    let r = roughness + 1.0;
+    let k = (r * r) * (1.0 / 8.0);
+    return nd / ((nd * (1.0 - k)) + k);
+}
+
+fn gfschggx(normal: &Vec3, view: &Vec3, light: &Vec3, roughness: Real) -> Real {
+    let nv = normal.dot(*view).max(0.0);
+    let nl = normal.dot(*light).max(0.0);
+    return gfschggx_single(nl, roughness) * gfschggx_single(nv, roughness);
+}
+
+fn ffschggx(nv: Real, f0: &Vec3) -> Vec3 {
+    let inv = 1.0 - nv;
+    let inv2 = inv * inv;
+    let inv5 = inv2 * inv2 * inv;
+    return *f0 + (Vec3::splat(1.0) - *f0) * inv5;
+}
+
+fn smoothstep(e0: Real, e1: Real, x: Real) -> Real {
+    let t = ((x - e0) / (e1 - e0)).max(0.0).min(1.0);
+    return t * t * (3.0 - 2.0 * t);
+}
+
+fn radiance_term(
+    alb: &Vec3,
+    nrm: &Vec3,
+    eye: &Vec3,
+    l: &Vec3,
+    radiance: Vec3,
+    roughness: Real,
+    metallic: Real,
+) -> Option<Vec3> {
+    let slope = nrm.dot(*l);
+    if slope < 0.005 {
+        return None;
+    }
+    let base_reflectivity = Vec3::splat(0.02) * (1.0 - metallic) + *alb * metallic;
+    let halfway = (*eye + *l).normalized();
+    let distribution = ndftrggx(nrm, &halfway, roughness);
+    let geometry = gfschggx(nrm, eye, l, roughness);
+    let fresnel = ffschggx(halfway.dot(*eye).max(0.0).min(1.0), &base_reflectivity);
+    let kd = (Vec3::splat(1.0) - fresnel) * (1.0 - metallic);
+    let nom = fresnel * (distribution * geometry);
+    let denom = 4.0 * nrm.dot(*eye).max(0.0) * nrm.dot(*l).max(0.0);
+    let specular = nom / denom.max(0.001);
+    let result = (kd * (*alb / PI) + specular) * radiance;
+    return Some(result * smoothstep(0.005, 1.0, slope));
+}
+
+// Shadows are not supported yet in the software graphic API, so all the
+// lights are fully visible.
+fn calc_lights(
+    scene: &Scene,
+    alb: &Vec3,
+    nrm: &Vec3,
+    eye: &Vec3,
+    pos: &Vec3,
+    roughness: Real,
+    metallic: Real,
+) -> Vec3 {
+    let mut result = Vec3::zero();
+    for light in &scene.directional_lights[..scene.lights_count[0] as usize] {
+        let l = -light.direction_strength.truncate().normalized();
+        let radiance = light.color.truncate();
+        if let Some(r) = radiance_term(alb, nrm, eye, &l, radiance, roughness, metallic) {
+            result += r;
+        }
+    }
+    for light in &scene.point_lights[..scene.lights_count[1] as usize] {
+        let lm = light.position_radius.truncate() - *pos;
+        let mut ll = lm.length();
+        if ll > light.position_radius.w() {
+            continue;
+        } else if ll < light.color_minradius.w() {
+            ll = light.color_minradius.w();
+        }
+        let ill = 1.0 / ll;
+        let l = lm * ill;
+        let radiance = light.color_minradius.truncate() * (ill * ill);
+        if let Some(r) = radiance_term(alb, nrm, eye, &l, radiance, roughness, metallic) {
+            result += r;
+        }
+    }
+    for light in &scene.spot_lights[..scene.lights_count[2] as usize] {
+        let lm = light.position_radius.truncate() - *pos;
+        let mut ll = lm.length();
+        if ll > light.position_radius.w() {
+            continue;
+        } else if ll < light.color_minradius.w() {
+            ll = light.color_minradius.w();
+        }
+        let ill = 1.0 / ll;
+        let l = lm * ill;
+        let cone = (-l).dot(light.direction_cone_scale.truncate()) * light.direction_cone_scale.w()
+            + light.cone_offset_reserved.x();
+        let cone = cone.max(0.0).min(1.0);
+        if cone <= 0.0 {
+            continue;
+        }
+        let radiance = light.color_minradius.truncate() * (ill * ill * cone * cone);
+        if let Some(r) = radiance_term(alb, nrm, eye, &l, radiance, roughness, metallic) {
+            result += r;
+        }
+    }
+    return result;
+}
+
+fn tonemap(c: Vec3) -> Vec3 {
+    let f = |x: Real| (x / (x + 1.0)).powf(1.0 / 2.2);
+    return Vec3::new(f(c.x()), f(c.y()), f(c.z()));
+}
+
+fn gamma(c: Vec3) -> Vec3 {
+    let f = |x: Real| x.powf(1.0 / 2.2);
+    return Vec3::new(f(c.x()), f(c.y()), f(c.z()));
+}
+
+fn transform_uv(t: &[Vec4; 2], u: Real, v: Real) -> (Real, Real) {
+    let uv = Vec3::new(u, v, 1.0);
+    return (t[0].truncate().dot(uv), t[1].truncate().dot(uv));
+}
+
+fn sample_base_color(material: &Material, samplers: &Samplers, u: Real, v: Real) -> Vec4 {
+    let (u, v) = transform_uv(&material.base_color_uv_transform, u, v);
+    return samplers.sample(0, u, v) * samplers.sample(1, u, v);
+}
+
+fn sample_emission(material: &Material, samplers: &Samplers, u: Real, v: Real) -> Vec3 {
+    let (u, v) = transform_uv(&material.emissive_uv_transform, u, v);
+    return (samplers.sample(5, u, v) * samplers.sample(6, u, v)).truncate()
+        * material.emissive_strength;
+}
+
+fn get_varying3(f: &Fragment, offset: usize) -> Vec3 {
+    return Vec3::new(
+        f.varyings[offset],
+        f.varyings[offset + 1],
+        f.varyings[offset + 2],
+    );
+}
+
+// Values of g-buffers-filler.frag: position-roughness, normal-metallic and
+// albedo, it returns None on discard. Unlit fragments have negative metallic.
+fn fill_gbuffer(f: &Fragment, material: &Material, samplers: &Samplers) -> Option<[Vec4; 3]> {
+    let pos = get_varying3(f, 0);
+    let nrm = get_varying3(f, 3);
+    let tng = get_varying3(f, 6);
+    let btg = get_varying3(f, 9);
+    let (u, v) = (f.varyings[12], f.varyings[13]);
+    let alb = sample_base_color(material, samplers, u, v);
+    let (mr_u, mr_v) = transform_uv(&material.metallic_roughness_uv_transform, u, v);
+    let metal_rough = samplers.sample(2, mr_u, mr_v).to_array();
+    let mut metallic = metal_rough[material.modes.z() as usize] * material.metallic_factor;
+    let roughness = metal_rough[material.modes.w() as usize] * material.roughness_factor;
+    let mut alpha = alb.w() * material.alpha;
+    if alpha < material.alpha_cutoff {
+        return None;
+    }
+    if material.modes.y() > 0.5 {
+        alpha = 1.0;
+    }
+    if material.modes.x() > 0.5 {
+        metallic = -1.0;
+    }
+    let (u, v) = transform_uv(&material.base_color_uv_transform, u, v);
+    let n = (samplers.sample(3, u, v).truncate() - Vec3::splat(0.5)) * 2.0;
+    let n = n * Vec3::new(material.normal_scale, material.normal_scale, 1.0);
+    let nrm = (tng * n.x() + btg * n.y() + nrm * n.z()).normalized();
+    return Some([
+        pos.extend(roughness),
+        nrm.extend(metallic),
+        alb.truncate().extend(alpha),
+    ]);
+}
+
+enum Program {
+    Unlit {
+        model: Model,
+        material: Material,
+    },
+    GBuffer {
+        scene: Scene,
+        model: Model,
+        material: Material,
+    },
+    Deferred {
+        scene: Scene,
+    },
+    Ssao {
+        scene: Scene,
+        ssao: Box<Ssao>,
+    },
+    ShadowMapper {
+        model_shadow: ModelShadow,
+        material: Material,
+    },
+    Nothing,
+}
+
+impl Program {
+    fn new(draw: &Draw) -> Self {
+        let b = &draw.bindings;
+        match draw.pipeline.pipeline_type {
+            PipelineType::Unlit => Program::Unlit {
+                model: read(&b[0].uniform),
+                material: read(&b[1].uniform),
+            },
+            PipelineType::GBuffer | PipelineType::SkinnedGBuffer | PipelineType::TransparentPBR => {
+                Program::GBuffer {
+                    scene: read(&b[0].uniform),
+                    model: read(&b[1].uniform),
+                    material: read(&b[2].uniform),
+                }
+            }
+            PipelineType::Deferred => Program::Deferred {
+                scene: read(&b[0].uniform),
+            },
+            PipelineType::SSAO => Program::Ssao {
+                scene: read(&b[0].uniform),
+                ssao: Box::new(read(&b[1].uniform)),
+            },
+            PipelineType::ShadowMapper | PipelineType::SkinnedShadowMapper => {
+                Program::ShadowMapper {
+                    model_shadow: read(&b[0].uniform),
+                    material: read(&b[1].uniform),
+                }
+            }
+            PipelineType::ShadowAccumulatorDirectional => Program::Nothing,
+        }
+    }
+
+    fn get_varyings_count(&self) -> usize {
+        match self {
+            Program::Unlit { .. } | Program::ShadowMapper { .. } => return 2,
+            Program::GBuffer { .. } => return 14,
+            Program::Deferred { .. } | Program::Ssao { .. } => return 2,
+            Program::Nothing => return 0,
+        }
+    }
+
+    fn vertex(&self, a: &VertexAttributes) -> Vertex {
+        let pos = Vec3::from_array(a.pos).extend(1.0);
+        let mut v;
+        match self {
+            Program::Unlit { model, .. } | Program::GBuffer { model, .. } => {
+                let world = model.model * pos;
+                v = Vertex::new((model.model_view_projection * pos).to_array());
+                if let Program::GBuffer { scene, .. } = self {
+                    v.position = (scene.camera.view_projection * world).to_array();
+                    let nrm = model
+                        .model
+                        .transform_vector(Vec3::from_array(a.nrm))
+                        .normalized();
+                    let tng = model
+                        .model
+                        .transform_vector(Vec3::new(a.tng[0], a.tng[1], a.tng[2]))
+                        .normalized();
+                    let btg = if a.tng[3] < 0.0 {
+                        tng.cross(nrm)
+                    } else {
+                        nrm.cross(tng)
+                    };
+                    let values = [world.truncate(), nrm, tng, btg];
+                    for (i, value) in values.iter().enumerate() {
+                        v.varyings[i * 3..i * 3 + 3].copy_from_slice(&value.to_array());
+                    }
+                    v.varyings[12] = a.uv[0];
+                    v.varyings[13] = a.uv[1];
+                } else {
+                    v.varyings[0] = a.uv[0];
+                    v.varyings[1] = a.uv[1];
+                }
+            }
+            Program::ShadowMapper { model_shadow, .. } => {
+                v = Vertex::new((model_shadow.model_view_projection * pos).to_array());
+                v.varyings[0] = a.uv[0];
+                v.varyings[1] = a.uv[1];
+            }
+            _ => vx_unexpected!(),
+        }
+        return v;
+    }
+
+    // Same as deferred.vert and ssao.vert, a triangle that covers the screen.
+    fn full_screen_vertex(&self, index: u32) -> Vertex {
+        let u = (index & 2) as Real;
+        let v = ((index << 1) & 2) as Real;
+        let mut result = Vertex::new([u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0001, 1.0]);
+        result.varyings[0] = u;
+        result.varyings[1] = v;
+        return result;
+    }
+
+    fn fragment(
+        &self,
+        pipeline_type: PipelineType,
+        f: &Fragment,
+        samplers: &Samplers,
+        outputs: &mut [Vec4; 3],
+    ) -> bool {
+        match self {
+            Program::Unlit { material, .. } => {
+                let (u, v) = (f.varyings[0], f.varyings[1]);
+                outputs[0] = sample_base_color(material, samplers, u, v);
+            }
+            Program::GBuffer {
+                scene, material, ..
+            } => {
+                let gbuff = match fill_gbuffer(f, material, samplers) {
+                    Some(g) => g,
+                    None => return false,
+                };
+                if pipeline_type != PipelineType::TransparentPBR {
+                    *outputs = gbuff;
+                    return true;
+                }
+                let pos = gbuff[0].truncate();
+                let nrm = gbuff[1].truncate();
+                let alb = gbuff[2].truncate();
+                let emission = sample_emission(material, samplers, f.varyings[12], f.varyings[13]);
+                if gbuff[1].w() < 0.0 {
+                    outputs[0] = gamma(alb + emission).extend(gbuff[2].w());
+                    return true;
+                }
+                let eye = (scene.camera.position_far.truncate() - pos).normalized();
+                let mut color = alb * 0.3;
+                color += calc_lights(scene, &alb, &nrm, &eye, &pos, gbuff[0].w(), gbuff[1].w());
+                color += emission;
+                outputs[0] = tonemap(color).extend(gbuff[2].w());
+            }
+            Program::Deferred { scene } => {
+                let (u, v) = (f.varyings[0], f.varyings[1]);
+                let pos_txt = samplers.sample(0, u, v);
+                let nrm_txt = samplers.sample(1, u, v);
+                let alb = samplers.sample(2, u, v);
+                let pos = pos_txt.truncate();
+                let nrm = nrm_txt.truncate();
+                let alb3 = alb.truncate();
+                if nrm_txt.w() < 0.0 {
+                    outputs[0] = gamma(alb3).extend(alb.w());
+                    return true;
+                }
+                let eye = (scene.camera.position_far.truncate() - pos).normalized();
+                let mut color = alb3 * 0.3;
+                color += calc_lights(scene, &alb3, &nrm, &eye, &pos, pos_txt.w(), nrm_txt.w());
+                color *= samplers.sample(4, u, v).x();
+                outputs[0] = tonemap(color).extend(alb.w());
+            }
+            Program::Ssao { scene, ssao } => {
+                let (u, v) = (f.varyings[0], f.varyings[1]);
+                let pos = samplers.sample(0, u, v).truncate();
+                let nrm = samplers.sample(1, u, v).truncate();
+                let tmpf = 1.0 - nrm.x().abs();
+                let tmpv3 = if tmpf < NORMAL_EPSILON && tmpf > -NORMAL_EPSILON {
+                    Vec3::new(0.0, 1.0, 0.0)
+                } else {
+                    Vec3::new(1.0, 0.0, 0.0)
+                };
+                let btg = nrm.cross(tmpv3);
+                let tng = btg.cross(nrm);
+                let samples_count = scene.ssao_config.x() as usize;
+                let radius = scene.ssao_config.y();
+                let mut ambient_occlusion = 1.0;
+                for sample_vector in &ssao.sample_vectors[..samples_count] {
+                    let s = sample_vector.truncate() * radius;
+                    let p = tng * s.x() + btg * s.y() + nrm * s.z() + pos;
+                    let p = scene.camera.uniform_view_projection * p.extend(1.0);
+                    let w = 1.0 / p.w();
+                    let [x, y, z] = (p.truncate() * w).to_array();
+                    if x > 1.0 - SMALL_EPSILON
+                        || x < SMALL_EPSILON
+                        || y > 1.0 - SMALL_EPSILON
+                        || y < SMALL_EPSILON
+                    {
+                        continue;
+                    }
+                    let depth = samplers.sample(2, x, y).x();
+                    let tolerance = (scene.ssao_config.z() * w).abs();
+                    if depth < z && depth + tolerance > z {
+                        ambient_occlusion += sample_vector.w();
+                    }
+                }
+                outputs[0] = Vec4::new(ambient_occlusion, 0.0, 0.0, 0.0);
+            }
+            Program::ShadowMapper { material, .. } => {
+                let (u, v) = (f.varyings[0], f.varyings[1]);
+                let alb = sample_base_color(material, samplers, u, v);
+                if alb.w() * material.alpha < material.alpha_cutoff {
+                    return false;
+                }
+            }
+            Program::Nothing => return false,
+        }
+        return true;
+    }
+}
+
+fn blend(blending: Blending, src: &Vec4, dst: &[Real; 4]) -> [Real; 4] {
+    match blending {
+        Blending::Nothing => return src.to_array(),
+        Blending::Alpha => {
+            let a = src.w();
+            let dst = Vec4::from_array(*dst);
+            return (*src * a + dst * (1.0 - a)).to_array();
+        }
+        Blending::Additive => {
+            return (*src + Vec4::from_array(*dst)).to_array();
+        }
+    }
+}
+
+pub(super) fn begin_render_pass(render_pass: &RenderPass) {
+    if !render_pass.get_clear() {
+        return;
+    }
+    for c in render_pass.get_color_attachments() {
+        vx_result!(c.get_image().write()).clear([0.0, 0.0, 0.0, 0.0]);
+    }
+    if let Some(d) = render_pass.get_depth_attachment() {
+        vx_result!(d.get_image().write()).clear([1.0, 0.0, 0.0, 0.0]);
+    }
+}
+
+pub(super) fn draw(draw: &Draw, render_pass: &RenderPass) {
+    let program = Program::new(draw);
+    if let Program::Nothing = program {
+        // The directional shadow accumulator is not supported yet.
+        return;
+    }
+    let pipeline = &draw.pipeline;
+    let texture_binding = match pipeline.pipeline_type {
+        PipelineType::GBuffer | PipelineType::SkinnedGBuffer | PipelineType::TransparentPBR => 2,
+        _ => 1,
+    };
+    let skin: Option<Box<Skin>> = match pipeline.pipeline_type {
+        PipelineType::SkinnedGBuffer => Some(Box::new(read(&draw.bindings[3].uniform))),
+        PipelineType::SkinnedShadowMapper => Some(Box::new(read(&draw.bindings[2].uniform))),
+        _ => None,
+    };
+    let images = collect_images(&draw.bindings[texture_binding]);
+    let samplers = Samplers::new(&images);
+    let mut colors: Vec<RwLockWriteGuard<Image>> = render_pass
+        .get_color_attachments()
+        .iter()
+        .map(|c| vx_result!(c.get_image().write()))
+        .collect();
+    let mut depth = render_pass
+        .get_depth_attachment()
+        .map(|d| vx_result!(d.get_image().write()));
+    let (width, height) = if let Some(c) = colors.first() {
+        c.get_dimensions()
+    } else {
+        vx_unwrap!(&depth).get_dimensions()
+    };
+    let is_r_only = pipeline.pipeline_type == PipelineType::SSAO;
+    let varyings_count = program.get_varyings_count();
+    #[cfg(debug_mode)]
+    {
+        if varyings_count > MAX_VARYINGS_COUNT {
+            vx_unexpected!();
+        }
+    }
+    let mut fragment = |f: &Fragment| {
+        if let Some(d) = &depth {
+            if pipeline.depth_test && f.depth > d.get(f.x, f.y)[0] {
+                return;
+            }
+        }
+        let mut outputs = [Vec4::zero(); 3];
+        if !program.fragment(pipeline.pipeline_type, f, &samplers, &mut outputs) {
+            return;
+        }
+        if let Some(d) = &mut depth {
+            if pipeline.depth_write {
+                d.set(f.x, f.y, [f.depth, 0.0, 0.0, 0.0]);
+            }
+        }
+        for (c, o) in colors.iter_mut().zip(outputs.iter()) {
+            let dst = *c.get(f.x, f.y);
+            let mut v = blend(pipeline.blending, o, &dst);
+            if is_r_only {
+                v = [v[0], dst[1], dst[2], dst[3]];
+            }
+            c.set(f.x, f.y, v);
+        }
+    };
+    if let (Some(vb), Some(ib)) = (&draw.vertex_buffer, &draw.index_buffer) {
+        let vb = vx_result!(vb.read());
+        let ib = vx_result!(ib.read());
+        let mut i = 0;
+        while i + 3 <= draw.count as usize {
+            let mut triangle = [Vertex::new([0.0; 4]); 3];
+            for j in 0..3 {
+                let index: u32 = ib.read_at(i + j);
+                let attributes = if let Some(skin) = &skin {
+                    let attributes: SkinnedVertexAttributes = vb.read_at(index as usize);
+                    attributes.skin(skin)
+                } else {
+                    vb.read_at(index as usize)
+                };
+                triangle[j] = program.vertex(&attributes);
+            }
+            draw_triangle(
+                &triangle,
+                width,
+                height,
+                pipeline.cull,
+                varyings_count,
+                &mut fragment,
+            );
+            i += 3;
+        }
+    } else {
+        let triangle = [
+            program.full_screen_vertex(0),
+            program.full_screen_vertex(1),
+            program.full_screen_vertex(2),
+        ];
+        draw_triangle(
+            &triangle,
+            width,
+            height,
+            pipeline.cull,
+            varyings_count,
+            &mut fragment,
+        );
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(all(not(target_os = "linux"), not(headless)))]
+use super::super::core::constants::{DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH};
+#[cfg(all(target_os = "linux", not(headless)))]
+use super::super::core::types::Id;
+#[cfg(all(target_os = "linux", not(headless)))]
+use super::super::platform::linux::presenter::Presenter;
+use super::super::platform::os::application::Application as OsApp;
+
+// Where the frames of the software graphic API go, the X11 window on Linux
+// and an offscreen image everywhere else, Wayland windows included, the
+// offscreen image is only reachable through the frame capture.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Surface {
+    width: u32,
+    height: u32,
+    #[cfg(all(target_os = "linux", not(headless)))]
+    presenter: Option<Presenter>,
+}
+
+impl Surface {
+    #[cfg(all(target_os = "linux", not(headless)))]
+    pub(crate) fn new(os_app: &mut OsApp) -> Self {
+        let (width, height) = os_app.get_window_size();
+        Self {
+            width: width as u32,
+            height: height as u32,
+            presenter: os_app.create_presenter(),
+        }
+    }
+
+    // Window must be created by the platform application before it.
+    #[cfg(all(target_os = "linux", not(headless)))]
+    pub(crate) fn new_for_window(os_app: &OsApp, window_id: Id) -> Option<Self> {
+        let (width, height) = os_app.get_window_size_of(window_id)?;
+        return Some(Self {
+            width: width as u32,
+            height: height as u32,
+            presenter: os_app.create_window_presenter(window_id),
+        });
+    }
+
+    #[cfg(headless)]
+    pub(crate) fn new(os_app: &mut OsApp) -> Self {
+        let (width, height) = os_app.get_window_size();
+        Self {
+            width: width as u32,
+            height: height as u32,
+        }
+    }
+
+    #[cfg(all(not(target_os = "linux"), not(headless)))]
+    pub(crate) fn new(_os_app: &mut OsApp) -> Self {
+        Self {
+            width: DEFAULT_WINDOW_WIDTH as u32,
+            height: DEFAULT_WINDOW_HEIGHT as u32,
+        }
+    }
+
+    pub(crate) fn new_offscreen(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            #[cfg(all(target_os = "linux", not(headless)))]
+            presenter: None,
+        }
+    }
+
+    // X11 window is already resized by the window manager, only the size
+    // of the next presented frames changes.
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub(crate) fn get_dimensions(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    // Pixels are in RGBA order.
+    #[cfg(all(target_os = "linux", not(headless)))]
+    pub(crate) fn present(&self, pixels: &[u8]) {
+        if let Some(presenter) = &self.presenter {
+            let mut bgrx = pixels.to_vec();
+            for p in bgrx.chunks_mut(4) {
+                p.swap(0, 2);
+            }
+            presenter.present(self.width, self.height, &bgrx);
+        }
+    }
+
+    #[cfg(not(all(target_os = "linux", not(headless))))]
+    pub(crate) fn present(&self, _pixels: &[u8]) {}
+}
@@ -179,7 +179,7 @@ unsafe impl Sync for RootBuffer {}
 
 #[derive(Clone)]
 #[cfg_attr(debug_mode, derive(Debug))]
-pub(crate) struct Static {
+pub struct Static {
     buffer: Arc<RwLock<Buffer>>,
 }
 
@@ -384,6 +384,30 @@ impl Manager {
         self.copy_to_image_ranges.push((copy_info, image.clone()));
     }
 
+    /// Pixels must have all the layers of the level one after another.
+    pub(crate) fn create_staging_image_level(
+        &mut self,
+        image: &Arc<RwLock<Image>>,
+        pixels: &[u8],
+        mip_level: u32,
+        width: u32,
+        height: u32,
+        layers_count: u32,
+    ) {
+        let upbuff = self.create_staging_buffer_with_vec(pixels);
+        let upbuffer = vx_result!(upbuff.read());
+        let mut copy_info = vk::BufferImageCopy::default();
+        copy_info.image_subresource.aspect_mask = vk::ImageAspectFlags::COLOR;
+        copy_info.image_subresource.mip_level = mip_level;
+        copy_info.image_subresource.base_array_layer = 0;
+        copy_info.image_subresource.layer_count = layers_count;
+        copy_info.image_extent.width = width;
+        copy_info.image_extent.height = height;
+        copy_info.image_extent.depth = 1;
+        copy_info.buffer_offset = upbuffer.get_allocated_memory().get_offset() as vk::DeviceSize;
+        self.copy_to_image_ranges.push((copy_info, image.clone()));
+    }
+
     pub(crate) fn create_dynamic_buffer(&mut self, actual_size: isize) -> Dynamic {
         let mut buffers = Vec::with_capacity(self.dynamic_buffers.len());
         for dynamic_buffer in &self.dynamic_buffers {
@@ -424,7 +448,7 @@ impl Manager {
             }
             for copy_img in &self.copy_to_image_ranges {
                 let mut image = vx_result!(copy_img.1.write());
-                if image.get_mips_count() == 1 {
+                if !image.requires_mips_generation() {
                     image.set_layout(cmd, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
                 }
             }
@@ -436,7 +460,7 @@ impl Manager {
     pub(super) fn secondary_update(&self, cmd: &mut CmdBuffer, frame_number: usize) {
         for ri in &self.frame_copy_to_image_ranges[frame_number] {
             let mut img = vx_result!(ri.1.write());
-            if img.get_mips_count() < 2 {
+            if !img.requires_mips_generation() {
                 continue;
             }
             img.generate_mips(cmd);
@@ -0,0 +1,150 @@
+use super::super::render::capture::Capture;
+use super::super::render::image::{AttachmentType, Format};
+use super::command::{Buffer as CmdBuffer, Pool as CmdPool};
+use super::framebuffer::Framebuffer;
+use super::image::{Image, View as ImageView};
+use super::memory::{Location as MemoryLocation, Manager as MemoryManager};
+use super::render_pass::RenderPass;
+use super::sync::Fence;
+use ash::version::DeviceV1_0;
+use ash::vk;
+use std::mem::transmute;
+use std::ptr::copy_nonoverlapping;
+use std::sync::{Arc, RwLock};
+
+// Offscreen render target with an arbitrary size, its color attachment can be
+// sampled or copied back to host memory with read_image.
+#[cfg_attr(debug_mode, derive(Debug))]
+pub(crate) struct Target {
+    color: Arc<ImageView>,
+    depth: Arc<ImageView>,
+    render_pass: Arc<RenderPass>,
+    framebuffer: Arc<Framebuffer>,
+}
+
+impl Target {
+    pub(crate) fn new(width: u32, height: u32, memmgr: &Arc<RwLock<MemoryManager>>) -> Self {
+        let color = Arc::new(ImageView::new_attachment(
+            memmgr,
+            Format::RgbaByte,
+            AttachmentType::ColorOffscreen,
+            width,
+            height,
+        ));
+        let depth = Arc::new(ImageView::new_attachment(
+            memmgr,
+            Format::DepthFloat,
+            AttachmentType::DepthGBuffer,
+            width,
+            height,
+        ));
+        let views = vec![color.clone(), depth.clone()];
+        let render_pass = Arc::new(RenderPass::new(views.clone(), true, true));
+        let framebuffer = Arc::new(Framebuffer::new(views, render_pass.clone()));
+        Self {
+            color,
+            depth,
+            render_pass,
+            framebuffer,
+        }
+    }
+
+    pub(crate) fn get_color(&self) -> &Arc<ImageView> {
+        return &self.color;
+    }
+
+    pub(crate) fn get_depth(&self) -> &Arc<ImageView> {
+        return &self.depth;
+    }
+
+    pub(crate) fn get_render_pass(&self) -> &Arc<RenderPass> {
+        return &self.render_pass;
+    }
+
+    pub(crate) fn get_framebuffer(&self) -> &Arc<Framebuffer> {
+        return &self.framebuffer;
+    }
+
+    pub(crate) fn capture(
+        &self,
+        cmd_pool: &Arc<CmdPool>,
+        memmgr: &Arc<RwLock<MemoryManager>>,
+    ) -> Capture {
+        return read_image(self.color.get_image(), false, cmd_pool, memmgr);
+    }
+}
+
+// Copies a 4 bytes per pixel color image to host memory, the image layout is
+// restored after the copy. It waits for the graphic queue to finish the copy,
+// so it must not be used in the hot path.
+// The readback buffer has its own memory, because the shared CPU root memory is
+// persistently mapped by the buffer manager.
+pub(crate) fn read_image(
+    image: &Arc<RwLock<Image>>,
+    is_bgra: bool,
+    cmd_pool: &Arc<CmdPool>,
+    memmgr: &Arc<RwLock<MemoryManager>>,
+) -> Capture {
+    let (width, height) = vx_result!(image.read()).get_dimensions();
+    let size = (width * height * 4) as vk::DeviceSize;
+    let logical_device = vx_result!(memmgr.read()).get_device().clone();
+    let vk_dev = logical_device.get_data();
+    let buffer_info = vk::BufferCreateInfo::builder()
+        .size(size)
+        .usage(vk::BufferUsageFlags::TRANSFER_DST);
+    let vk_buffer = vx_result!(unsafe { vk_dev.create_buffer(&buffer_info, None) });
+    let mem_reqs = unsafe { vk_dev.get_buffer_memory_requirements(vk_buffer) };
+    let memory_type_index =
+        vx_result!(memmgr.read()).get_memory_type_index(&mem_reqs, MemoryLocation::CPU);
+    let alloc_info = vk::MemoryAllocateInfo::builder()
+        .allocation_size(mem_reqs.size)
+        .memory_type_index(memory_type_index);
+    let vk_memory = vx_result!(unsafe { vk_dev.allocate_memory(&alloc_info, None) });
+    vx_result!(unsafe { vk_dev.bind_buffer_memory(vk_buffer, vk_memory, 0) });
+    let mut cmd = CmdBuffer::new_primary(cmd_pool.clone());
+    cmd.begin();
+    {
+        let mut image = vx_result!(image.write());
+        let old_layout = image.get_layout();
+        image.set_layout(&mut cmd, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+        let mut region = vk::BufferImageCopy::default();
+        region.image_subresource.aspect_mask = vk::ImageAspectFlags::COLOR;
+        region.image_subresource.mip_level = 0;
+        region.image_subresource.base_array_layer = 0;
+        region.image_subresource.layer_count = 1;
+        region.image_extent.width = width;
+        region.image_extent.height = height;
+        region.image_extent.depth = 1;
+        cmd.copy_image_to_buffer(image.get_data(), vk_buffer, &region);
+        if old_layout != vk::ImageLayout::UNDEFINED {
+            image.set_layout(&mut cmd, old_layout);
+        }
+    }
+    cmd.end();
+    let fence = Fence::new_signaled(logical_device.clone());
+    fence.reset();
+    let mut submit_info = vk::SubmitInfo::default();
+    submit_info.p_command_buffers = cmd.get_data();
+    submit_info.command_buffer_count = 1;
+    vx_result!(unsafe {
+        vk_dev.queue_submit(
+            logical_device.get_vk_graphic_queue(),
+            &[submit_info],
+            *fence.get_data(),
+        )
+    });
+    fence.wait();
+    let mut pixels = vec![0u8; size as usize];
+    unsafe {
+        let ptr = vx_result!(vk_dev.map_memory(vk_memory, 0, size, vk::MemoryMapFlags::empty()));
+        copy_nonoverlapping(transmute(ptr), pixels.as_mut_ptr(), size as usize);
+        vk_dev.unmap_memory(vk_memory);
+        vk_dev.destroy_buffer(vk_buffer, None);
+        vk_dev.free_memory(vk_memory, None);
+    }
+    if is_bgra {
+        Capture::new_with_bgra(width, height, pixels)
+    } else {
+        Capture::new(width, height, pixels)
+    }
+}
@@ -15,8 +15,8 @@ pub struct Buffer {
     vk_data: vk::CommandBuffer,
     has_render_record: bool,
     bound_pipeline_layout: vk::PipelineLayout,
-    bound_descriptor_sets: [vk::DescriptorSet; 3],
-    bound_dynamic_buffer_offsets: [u32; 3],
+    bound_descriptor_sets: [vk::DescriptorSet; MAX_DESCRIPTOR_SETS_COUNT],
+    bound_dynamic_buffer_offsets: [u32; MAX_DYNAMIC_BUFFER_OFFSETS_COUNT],
     #[cfg(debug_mode)]
     is_secondary: bool,
 }
@@ -28,6 +28,11 @@ const GBUFF_MATERIAL_DESCRIPTOR_OFFSET: usize = 2;
 const GBUFF_DESCRIPTOR_SETS_COUNT: usize = 3;
 const GBUFF_DYNAMIC_BUFFER_OFFSETS_COUNT: usize = 3;
 
+const SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET: usize = 3;
+
+const SKINNED_GBUFF_DESCRIPTOR_SETS_COUNT: usize = 4;
+const SKINNED_GBUFF_DYNAMIC_BUFFER_OFFSETS_COUNT: usize = 4;
+
 const UNLIT_MODEL_DESCRIPTOR_OFFSET: usize = 0;
 const UNLIT_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
 
@@ -50,11 +55,14 @@ const SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT: usize = 2;
 const SHADOW_MAPPER_LIGHT_DESCRIPTOR_OFFSET: usize = 0;
 const SHADOW_MAPPER_MATERIAL_DESCRIPTOR_OFFSET: usize = 1;
 
+const SKINNED_SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT: usize = 3;
+const SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET: usize = 2;
+
 const SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_SETS_COUNT: usize = 1;
 const SHADOW_ACCUMULATOR_DIRECTIONAL_DESCRIPTOR_OFFSET: usize = 0;
 
-const MAX_DESCRIPTOR_SETS_COUNT: usize = 3;
-const MAX_DYNAMIC_BUFFER_OFFSETS_COUNT: usize = 3;
+const MAX_DESCRIPTOR_SETS_COUNT: usize = 4;
+const MAX_DYNAMIC_BUFFER_OFFSETS_COUNT: usize = 4;
 
 impl Buffer {
     pub(crate) fn new_primary(pool: Arc<Pool>) -> Self {
@@ -202,6 +210,23 @@ impl Buffer {
         }
     }
 
+    pub(crate) fn copy_image_to_buffer(
+        &mut self,
+        src: vk::Image,
+        dst: vk::Buffer,
+        region: &vk::BufferImageCopy,
+    ) {
+        unsafe {
+            self.vk_device.cmd_copy_image_to_buffer(
+                self.vk_data,
+                src,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst,
+                &[*region],
+            );
+        }
+    }
+
     pub(super) fn blit_image(
         &mut self,
         src_image: vk::Image,
@@ -375,6 +400,39 @@ impl Buffer {
         self.draw_index(indices_count);
     }
 
+    pub(crate) fn bind_skinned_gbuff_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bound_descriptor_sets[SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET] =
+            *descriptor_set.get_data();
+        self.bound_dynamic_buffer_offsets[SKINNED_GBUFF_SKIN_DESCRIPTOR_OFFSET] =
+            buffer.get_allocated_memory().get_offset() as u32;
+    }
+
+    pub(crate) fn render_skinned_gbuff(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.has_render_record = true;
+        unsafe {
+            self.vk_device.cmd_bind_descriptor_sets(
+                self.vk_data,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.bound_pipeline_layout,
+                0,
+                &self.bound_descriptor_sets[..SKINNED_GBUFF_DESCRIPTOR_SETS_COUNT],
+                &self.bound_dynamic_buffer_offsets[..SKINNED_GBUFF_DYNAMIC_BUFFER_OFFSETS_COUNT],
+            );
+        }
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
+
     pub(crate) fn bind_unlit_model_descriptor(
         &mut self,
         descriptor_set: &DescriptorSet,
@@ -537,6 +595,17 @@ impl Buffer {
             buffer.get_allocated_memory().get_offset() as u32;
     }
 
+    pub(crate) fn bind_skinned_shadow_mapper_skin_descriptor(
+        &mut self,
+        descriptor_set: &DescriptorSet,
+        buffer: &BufBuffer,
+    ) {
+        self.bound_descriptor_sets[SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET] =
+            *descriptor_set.get_data();
+        self.bound_dynamic_buffer_offsets[SKINNED_SHADOW_MAPPER_SKIN_DESCRIPTOR_OFFSET] =
+            buffer.get_allocated_memory().get_offset() as u32;
+    }
+
     pub(crate) fn render_shadow_mapper(
         &mut self,
         vertex_buffer: &StaticBuffer,
@@ -558,6 +627,28 @@ impl Buffer {
         self.bind_index_buffer(index_buffer.get_buffer());
         self.draw_index(indices_count);
     }
+
+    pub(crate) fn render_skinned_shadow_mapper(
+        &mut self,
+        vertex_buffer: &StaticBuffer,
+        index_buffer: &StaticBuffer,
+        indices_count: u32,
+    ) {
+        self.has_render_record = true;
+        unsafe {
+            self.vk_device.cmd_bind_descriptor_sets(
+                self.vk_data,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.bound_pipeline_layout,
+                0,
+                &self.bound_descriptor_sets[..SKINNED_SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT],
+                &self.bound_dynamic_buffer_offsets[..SKINNED_SHADOW_MAPPER_DESCRIPTOR_SETS_COUNT],
+            );
+        }
+        self.bind_vertex_buffer(vertex_buffer.get_buffer());
+        self.bind_index_buffer(index_buffer.get_buffer());
+        self.draw_index(indices_count);
+    }
 }
 
 impl Drop for Buffer {
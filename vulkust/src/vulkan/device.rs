@@ -1,6 +1,6 @@
 use super::super::core::string::{cstrings_to_ptrs, strings_to_cstrings};
 use super::super::render::config::Configurations;
-use super::super::render::image::Format;
+use super::super::render::image::{Format, TextureFormat};
 use super::surface::Surface;
 use ash::version::DeviceV1_0;
 use ash::version::InstanceV1_0;
@@ -313,6 +313,7 @@ impl Physical {
     pub(super) fn convert_format(&self, f: Format) -> vk::Format {
         match f {
             Format::RgbaFloat => return vk::Format::R32G32B32A32_SFLOAT,
+            Format::RgbaByte => return vk::Format::R8G8B8A8_UNORM,
             Format::DepthFloat => return self.get_supported_depth_format(),
             Format::Float => return vk::Format::R32_SFLOAT,
             Format::FlagBits8 => return vk::Format::R8_UNORM,
@@ -321,6 +322,66 @@ impl Physical {
         }
     }
 
+    pub(super) fn convert_texture_format(&self, f: TextureFormat) -> vk::Format {
+        match f {
+            TextureFormat::RgbaByte => return vk::Format::R8G8B8A8_UNORM,
+            TextureFormat::Bc1Rgb => return vk::Format::BC1_RGB_UNORM_BLOCK,
+            TextureFormat::Bc1Rgba => return vk::Format::BC1_RGBA_UNORM_BLOCK,
+            TextureFormat::Bc2 => return vk::Format::BC2_UNORM_BLOCK,
+            TextureFormat::Bc3 => return vk::Format::BC3_UNORM_BLOCK,
+            TextureFormat::Bc4 => return vk::Format::BC4_UNORM_BLOCK,
+            TextureFormat::Bc4Signed => return vk::Format::BC4_SNORM_BLOCK,
+            TextureFormat::Bc5 => return vk::Format::BC5_UNORM_BLOCK,
+            TextureFormat::Bc5Signed => return vk::Format::BC5_SNORM_BLOCK,
+            TextureFormat::Bc6h => return vk::Format::BC6H_UFLOAT_BLOCK,
+            TextureFormat::Bc6hSigned => return vk::Format::BC6H_SFLOAT_BLOCK,
+            TextureFormat::Bc7 => return vk::Format::BC7_UNORM_BLOCK,
+            TextureFormat::Etc2Rgb => return vk::Format::ETC2_R8G8B8_UNORM_BLOCK,
+            TextureFormat::Etc2RgbA1 => return vk::Format::ETC2_R8G8B8A1_UNORM_BLOCK,
+            TextureFormat::Etc2Rgba => return vk::Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+            TextureFormat::Astc(w, h) => match (w, h) {
+                (4, 4) => return vk::Format::ASTC_4X4_UNORM_BLOCK,
+                (5, 4) => return vk::Format::ASTC_5X4_UNORM_BLOCK,
+                (5, 5) => return vk::Format::ASTC_5X5_UNORM_BLOCK,
+                (6, 5) => return vk::Format::ASTC_6X5_UNORM_BLOCK,
+                (6, 6) => return vk::Format::ASTC_6X6_UNORM_BLOCK,
+                (8, 5) => return vk::Format::ASTC_8X5_UNORM_BLOCK,
+                (8, 6) => return vk::Format::ASTC_8X6_UNORM_BLOCK,
+                (8, 8) => return vk::Format::ASTC_8X8_UNORM_BLOCK,
+                (10, 5) => return vk::Format::ASTC_10X5_UNORM_BLOCK,
+                (10, 6) => return vk::Format::ASTC_10X6_UNORM_BLOCK,
+                (10, 8) => return vk::Format::ASTC_10X8_UNORM_BLOCK,
+                (10, 10) => return vk::Format::ASTC_10X10_UNORM_BLOCK,
+                (12, 10) => return vk::Format::ASTC_12X10_UNORM_BLOCK,
+                (12, 12) => return vk::Format::ASTC_12X12_UNORM_BLOCK,
+                _ => vx_unexpected!(),
+            },
+        }
+    }
+
+    /// Compressed formats need their feature and the sampling support of the device.
+    pub(super) fn supports_texture_format(&self, f: TextureFormat) -> bool {
+        let feature = match f {
+            TextureFormat::RgbaByte => return true,
+            TextureFormat::Etc2Rgb | TextureFormat::Etc2RgbA1 | TextureFormat::Etc2Rgba => {
+                self.features.texture_compression_etc2
+            }
+            TextureFormat::Astc(_, _) => self.features.texture_compression_astc_ldr,
+            _ => self.features.texture_compression_bc,
+        };
+        if feature == 0 {
+            return false;
+        }
+        let format_props = unsafe {
+            self.get_vk_instance()
+                .get_physical_device_format_properties(self.vk_data, self.convert_texture_format(f))
+        };
+        return vx_flag_check!(
+            format_props.optimal_tiling_features,
+            vk::FormatFeatureFlags::SAMPLED_IMAGE
+        );
+    }
+
     #[inline]
     pub(super) fn get_vk_instance(&self) -> &ash::Instance {
         return self.surface.get_instance().get_data();
@@ -380,6 +441,9 @@ impl Logical {
         if config.get_enable_anistropic_texture() {
             features.sampler_anisotropy = available_features.sampler_anisotropy;
         }
+        features.texture_compression_bc = available_features.texture_compression_bc;
+        features.texture_compression_etc2 = available_features.texture_compression_etc2;
+        features.texture_compression_astc_ldr = available_features.texture_compression_astc_ldr;
         let device_create_info = vk::DeviceCreateInfo::builder()
             .queue_create_infos(&queue_create_info_s)
             .enabled_extension_names(&device_extensions)
@@ -435,6 +499,16 @@ impl Logical {
         return self.physical_device.convert_format(f);
     }
 
+    #[inline]
+    pub(super) fn convert_texture_format(&self, f: TextureFormat) -> vk::Format {
+        return self.physical_device.convert_texture_format(f);
+    }
+
+    #[inline]
+    pub(super) fn supports_texture_format(&self, f: TextureFormat) -> bool {
+        return self.physical_device.supports_texture_format(f);
+    }
+
     #[inline]
     pub(super) fn get_vk_instance(&self) -> &ash::Instance {
         return self.physical_device.get_vk_instance();
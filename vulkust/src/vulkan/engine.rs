@@ -1,6 +1,10 @@
+use super::super::core::types::Id;
+use super::super::render::capture::Capture;
 // use super::super::render::sampler::Filter as SamplerFilter;
+// use super::super::render::texture::container::Container;
 // use super::super::system::os::application::Application as OsApp;
 // use super::buffer::Manager as BufferManager;
+// use super::capture::{read_image, Target as CaptureTarget};
 // use super::command::{Buffer as CmdBuffer, Pool as CmdPool, Type as CmdPoolType};
 // use super::descriptor::Manager as DescriptorManager;
 // use super::device::Logical as LogicalDevice;
@@ -294,6 +298,13 @@ impl Engine {
     //     ))
     // }
 
+    // pub(crate) fn create_texture_with_container(&self, container: &Container) -> Arc<ImageView> {
+    //     Arc::new(ImageView::new_texture_with_container(
+    //         container,
+    //         &self.buffer_manager,
+    //     ))
+    // }
+
     // pub(crate) fn create_command_pool(&self) -> Arc<CmdPool> {
     //     return Arc::new(CmdPool::new(
     //         self.logical_device.clone(),
@@ -374,5 +385,41 @@ impl Engine {
     //     return &self.framebuffers;
     // }
 
+    // pub(crate) fn create_offscreen_target(&self, width: u32, height: u32) -> CaptureTarget {
+    //     return CaptureTarget::new(width, height, &self.memory_manager);
+    // }
+
+    // pub fn capture_frame(&mut self) -> Option<Capture> {
+    //     self.logical_device.wait_idle();
+    //     let view = &self.swapchain.get_image_views()[self.current_frame_number as usize];
+    //     return Some(read_image(
+    //         view.get_image(),
+    //         self.swapchain.is_bgra(),
+    //         &self.graphic_cmd_pool,
+    //         &self.memory_manager,
+    //     ));
+    // }
+
+    // Swapchain is not ported to the new engine yet, the commented version above
+    // must replace this after that.
+    pub fn capture_frame(&mut self) -> Option<Capture> {
+        vx_log_e!("Frame capturing is not supported by this engine yet.");
+        return None;
+    }
+
     pub fn update(&mut self) {}
+
+    // Swapchain is recreated on resize after its porting.
+    pub fn resize(&mut self, _width: u32, _height: u32) {}
+
+    // Each window needs its own surface and swapchain, they come after the
+    // swapchain porting.
+    pub fn add_window(&mut self, _os_app: &OsApp, window_id: Id) {
+        vx_log_e!(
+            "Window {} can not be added, secondary windows are not supported by this engine yet.",
+            window_id
+        );
+    }
+
+    pub fn remove_window(&mut self, _window_id: Id) {}
 }
@@ -1,5 +1,6 @@
 use super::super::core::allocate::Object as AlcObject;
-use super::super::render::image::{AttachmentType, Format, ImageType, Layout};
+use super::super::render::image::{AttachmentType, Format, ImageType, Layout, TextureFormat};
+use super::super::render::texture::container::Container;
 use super::buffer::Manager as BufferManager;
 use super::command::Buffer as CmdBuffer;
 use super::device::Logical as LogicalDevice;
@@ -24,6 +25,8 @@ pub(crate) struct Image {
     layout: vk::ImageLayout,
     format: vk::Format,
     mips_count: u8,
+    mips_uploaded: bool,
+    layers_count: u32,
     usage: vk::ImageUsageFlags,
     memory: Option<Arc<RwLock<Memory>>>,
     width: u32,
@@ -60,6 +63,8 @@ impl Image {
             layout: info.initial_layout,
             format: info.format,
             mips_count: info.mip_levels as u8,
+            mips_uploaded: false,
+            layers_count: info.array_layers,
             usage: info.usage,
             width: info.extent.width,
             height: info.extent.height,
@@ -83,6 +88,8 @@ impl Image {
             layout,
             format,
             mips_count: 1,
+            mips_uploaded: false,
+            layers_count: 1,
             vk_data,
             usage,
             width,
@@ -204,6 +211,76 @@ impl Image {
         myself
     }
 
+    /// The levels of the container are uploaded, only a single level RGBA
+    /// container gets its mips generated.
+    pub(crate) fn new_with_container(
+        container: &Container,
+        buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Arc<RwLock<Self>> {
+        let memmgr = {
+            let buffmgr = vx_result!(buffmgr.read());
+            let memmgr = vx_result!(buffmgr.get_gpu_root_buffer().get_memory().read())
+                .get_manager()
+                .clone();
+            memmgr
+        };
+        let device = vx_result!(memmgr.read()).get_device().clone();
+        let decoded;
+        let container = if device.supports_texture_format(container.get_format()) {
+            container
+        } else {
+            decoded = container.decode();
+            &decoded
+        };
+        let (width, height) = container.get_dimensions();
+        let levels = container.get_levels();
+        let generates_mips = container.get_format() == TextureFormat::RgbaByte && levels.len() == 1;
+        let mip_levels = if generates_mips {
+            Self::calculate_mip_levels_2d(width, height)
+        } else {
+            levels.len() as u32
+        };
+        let layers_count = container.get_layers_count() * container.get_faces_count();
+        let flags = if container.get_faces_count() == 6 {
+            vk::ImageCreateFlags::CUBE_COMPATIBLE
+        } else {
+            vk::ImageCreateFlags::empty()
+        };
+        let image_info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(device.convert_texture_format(container.get_format()))
+            .extent(
+                vk::Extent3D::builder()
+                    .width(width)
+                    .height(height)
+                    .depth(1)
+                    .build(),
+            )
+            .mip_levels(mip_levels)
+            .array_layers(layers_count)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .usage(
+                vk::ImageUsageFlags::TRANSFER_DST
+                    | vk::ImageUsageFlags::TRANSFER_SRC
+                    | vk::ImageUsageFlags::SAMPLED,
+            )
+            .flags(flags)
+            .build();
+        let mut myself = Self::new_with_info(&image_info, &memmgr);
+        myself.image_type = container.get_image_type();
+        myself.mips_uploaded = !generates_mips;
+        let myself = Arc::new(RwLock::new(myself));
+        let mut buffmgr = vx_result!(buffmgr.write());
+        for (i, level) in levels.iter().enumerate() {
+            let (w, h) = container.get_level_dimensions(i as u32);
+            buffmgr.create_staging_image_level(&myself, level, i as u32, w, h, layers_count);
+        }
+        myself
+    }
+
     fn calculate_mip_levels_2d(width: u32, height: u32) -> u32 {
         let mut a = min(width, height);
         let mut result = 0;
@@ -217,7 +294,18 @@ impl Image {
     pub(crate) fn set_layout(&mut self, cmd: &mut CmdBuffer, new_layout: vk::ImageLayout) {
         let dst_stage = vk::PipelineStageFlags::ALL_COMMANDS;
         let src_stage = vk::PipelineStageFlags::ALL_COMMANDS;
-        self.set_layout_2(cmd, new_layout, 0, 1, 0, 1, src_stage, dst_stage)
+        let mips_count = self.mips_count as u32;
+        let layers_count = self.layers_count;
+        self.set_layout_2(
+            cmd,
+            new_layout,
+            0,
+            mips_count,
+            0,
+            layers_count,
+            src_stage,
+            dst_stage,
+        )
     }
 
     pub(super) fn set_layout_2(
@@ -241,6 +329,7 @@ impl Image {
             vk::ImageLayout::TRANSFER_SRC_OPTIMAL => vk::AccessFlags::TRANSFER_READ,
             vk::ImageLayout::TRANSFER_DST_OPTIMAL => vk::AccessFlags::TRANSFER_WRITE,
             vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL => vk::AccessFlags::SHADER_READ,
+            vk::ImageLayout::PRESENT_SRC_KHR => vk::AccessFlags::MEMORY_READ,
             _ => vx_unexpected!(),
         };
         let dst_access_mask = match new_layout {
@@ -256,6 +345,7 @@ impl Image {
                 }
                 vk::AccessFlags::SHADER_READ
             }
+            vk::ImageLayout::PRESENT_SRC_KHR => vk::AccessFlags::MEMORY_READ,
             _ => vx_unexpected!(),
         };
         let barrier = vk::ImageMemoryBarrier::builder()
@@ -288,10 +378,7 @@ impl Image {
     pub(super) fn generate_mips(&mut self, cmd: &mut CmdBuffer) {
         self.set_layout(cmd, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
         let mips_count = self.mips_count as u32;
-        let layer_count = match self.image_type {
-            ImageType::Cube => 6u32,
-            _ => 1u32,
-        };
+        let layer_count = self.layers_count;
         for fi in 0..layer_count {
             for mi in 1..mips_count {
                 let image_blit = vk::ImageBlit::builder()
@@ -379,6 +466,10 @@ impl Image {
     //     return convert_to_format(self.format);
     // }
 
+    pub(crate) fn get_layout(&self) -> vk::ImageLayout {
+        return self.layout;
+    }
+
     pub(crate) fn get_data(&self) -> vk::Image {
         return self.vk_data;
     }
@@ -398,6 +489,10 @@ impl Image {
     pub(super) fn get_mips_count(&self) -> u8 {
         return self.mips_count;
     }
+
+    pub(super) fn requires_mips_generation(&self) -> bool {
+        return self.mips_count > 1 && !self.mips_uploaded;
+    }
 }
 
 impl Drop for Image {
@@ -463,6 +558,14 @@ impl View {
         Self::new_with_image(image)
     }
 
+    pub(crate) fn new_texture_with_container(
+        container: &Container,
+        buffmgr: &Arc<RwLock<BufferManager>>,
+    ) -> Self {
+        let image = Image::new_with_container(container, buffmgr);
+        Self::new_with_image(image)
+    }
+
     pub(crate) fn new_with_image(image: Arc<RwLock<Image>>) -> Self {
         return Self::new_with_image_aspect(image, vk::ImageAspectFlags::COLOR);
     }
@@ -478,7 +581,9 @@ impl View {
                 .image(img.vk_data)
                 .view_type(match img.image_type {
                     ImageType::Cube => vk::ImageViewType::CUBE,
+                    ImageType::CubeArray => vk::ImageViewType::CUBE_ARRAY,
                     ImageType::D2 => vk::ImageViewType::TYPE_2D,
+                    ImageType::D2Array => vk::ImageViewType::TYPE_2D_ARRAY,
                     ImageType::D3 => vk::ImageViewType::TYPE_3D,
                 })
                 .format(img.format)
@@ -494,10 +599,7 @@ impl View {
                     vk::ImageSubresourceRange::builder()
                         .aspect_mask(aspect_mask)
                         .level_count(img.mips_count as u32)
-                        .layer_count(match img.image_type {
-                            ImageType::Cube => 6,
-                            _ => 1,
-                        })
+                        .layer_count(img.layers_count)
                         .build(),
                 )
                 .build();
@@ -533,7 +635,9 @@ impl View {
             AttachmentType::ColorGBuffer | AttachmentType::ColorDisplay => {
                 vk::ImageAspectFlags::COLOR
             }
-            AttachmentType::ShadowAccumulator => vk::ImageAspectFlags::COLOR,
+            AttachmentType::ShadowAccumulator | AttachmentType::ColorOffscreen => {
+                vk::ImageAspectFlags::COLOR
+            }
             AttachmentType::DepthGBuffer | AttachmentType::DepthShadowBuffer => {
                 vk::ImageAspectFlags::DEPTH
             }
@@ -548,6 +652,11 @@ impl View {
             AttachmentType::ShadowAccumulator => {
                 vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED
             }
+            AttachmentType::ColorOffscreen => {
+                vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::SAMPLED
+                    | vk::ImageUsageFlags::TRANSFER_SRC
+            }
             AttachmentType::ColorDisplay => vk::ImageUsageFlags::COLOR_ATTACHMENT,
             AttachmentType::DepthGBuffer | AttachmentType::DepthShadowBuffer => {
                 vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED
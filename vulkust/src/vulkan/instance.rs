@@ -170,6 +170,7 @@ fn enumerate_extensions(entry: &ash::Entry) -> Vec<String> {
             "VK_KHR_surface"
             | "VK_KHR_win32_surface"
             | "VK_KHR_xcb_surface"
+            | "VK_KHR_wayland_surface"
             | "VK_KHR_android_surface"
             | "VK_MVK_macos_surface"
             | "VK_MVK_ios_surface"
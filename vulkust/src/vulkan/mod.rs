@@ -1,4 +1,5 @@
 // pub mod buffer;
+// pub mod capture;
 // pub mod command;
 // pub mod descriptor;
 // pub mod device;
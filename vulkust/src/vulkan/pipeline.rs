@@ -41,6 +41,25 @@ impl Layout {
         Self::new(&layout, descriptor_set_layouts)
     }
 
+    // Same as the g-buffer layout with the joints palette of the skin at the end.
+    pub fn new_skinned_gbuff(descriptor_manager: &Arc<RwLock<DescriptorManager>>) -> Self {
+        let descriptor_manager = vx_result!(descriptor_manager.read());
+        let gbuff_descriptor_set_layout = descriptor_manager.get_gbuff_set_layout().clone();
+        let buffer_only_descriptor_set_layout =
+            descriptor_manager.get_buffer_only_set_layout().clone();
+        let layout = [
+            *buffer_only_descriptor_set_layout.get_data(),
+            *buffer_only_descriptor_set_layout.get_data(),
+            *gbuff_descriptor_set_layout.get_data(),
+            *buffer_only_descriptor_set_layout.get_data(),
+        ];
+        let descriptor_set_layouts = vec![
+            gbuff_descriptor_set_layout,
+            buffer_only_descriptor_set_layout,
+        ];
+        Self::new(&layout, descriptor_set_layouts)
+    }
+
     pub fn new_unlit(descriptor_manager: &Arc<RwLock<DescriptorManager>>) -> Self {
         let descriptor_manager = vx_result!(descriptor_manager.read());
         let unlit_descriptor_set_layout = descriptor_manager.get_gbuff_set_layout().clone();
@@ -73,6 +92,23 @@ impl Layout {
         Self::new(&layout, descriptor_set_layouts)
     }
 
+    pub fn new_skinned_shadow_mapper(descriptor_manager: &Arc<RwLock<DescriptorManager>>) -> Self {
+        let descriptor_manager = vx_result!(descriptor_manager.read());
+        let gbuff_descriptor_set_layout = descriptor_manager.get_gbuff_set_layout().clone();
+        let buffer_only_descriptor_set_layout =
+            descriptor_manager.get_buffer_only_set_layout().clone();
+        let layout = [
+            *buffer_only_descriptor_set_layout.get_data(),
+            *gbuff_descriptor_set_layout.get_data(),
+            *buffer_only_descriptor_set_layout.get_data(),
+        ];
+        let descriptor_set_layouts = vec![
+            buffer_only_descriptor_set_layout,
+            gbuff_descriptor_set_layout,
+        ];
+        Self::new(&layout, descriptor_set_layouts)
+    }
+
     pub fn new_shadow_accumulator_directional(
         descriptor_manager: &Arc<RwLock<DescriptorManager>>,
     ) -> Self {
@@ -209,6 +245,8 @@ impl Pipeline {
             PipelineType::GBuffer => include_shader!("g-buffers-filler.vert"),
             PipelineType::Deferred => include_shader!("deferred.vert"),
             PipelineType::ShadowMapper => include_shader!("shadow-mapper.vert"),
+            PipelineType::SkinnedGBuffer => include_shader!("skinned-g-buffers-filler.vert"),
+            PipelineType::SkinnedShadowMapper => include_shader!("skinned-shadow-mapper.vert"),
             PipelineType::ShadowAccumulatorDirectional => {
                 include_shader!("shadow-accumulator-directional.vert")
             }
@@ -220,6 +258,8 @@ impl Pipeline {
             PipelineType::GBuffer => include_shader!("g-buffers-filler.frag"),
             PipelineType::Deferred => include_shader!("deferred.frag"),
             PipelineType::ShadowMapper => include_shader!("shadow-mapper.frag"),
+            PipelineType::SkinnedGBuffer => include_shader!("g-buffers-filler.frag"),
+            PipelineType::SkinnedShadowMapper => include_shader!("shadow-mapper.frag"),
             PipelineType::ShadowAccumulatorDirectional => {
                 include_shader!("shadow-accumulator-directional.frag")
             }
@@ -237,6 +277,10 @@ impl Pipeline {
             }
             PipelineType::Deferred => Layout::new_deferred(descriptor_manager),
             PipelineType::ShadowMapper => Layout::new_shadow_mapper(descriptor_manager),
+            PipelineType::SkinnedGBuffer => Layout::new_skinned_gbuff(descriptor_manager),
+            PipelineType::SkinnedShadowMapper => {
+                Layout::new_skinned_shadow_mapper(descriptor_manager)
+            }
             PipelineType::ShadowAccumulatorDirectional => {
                 Layout::new_shadow_accumulator_directional(descriptor_manager)
             }
@@ -322,11 +366,18 @@ impl Pipeline {
         let mut multisample_state = vk::PipelineMultisampleStateCreateInfo::default();
         multisample_state.rasterization_samples = vk::SampleCountFlags::TYPE_1;
 
+        let is_skinned = match pipeline_type {
+            PipelineType::SkinnedGBuffer | PipelineType::SkinnedShadowMapper => true,
+            _ => false,
+        };
+
         let mut vertex_input_binding = vk::VertexInputBindingDescription::default();
-        vertex_input_binding.stride = 48; // bytes of vertex
+        vertex_input_binding.stride = if is_skinned { 80 } else { 48 }; // bytes of vertex
         vertex_input_binding.input_rate = vk::VertexInputRate::VERTEX;
 
-        let mut vertex_attributes = vec![vk::VertexInputAttributeDescription::default(); 4];
+        let attributes_count = if is_skinned { 6 } else { 4 };
+        let mut vertex_attributes =
+            vec![vk::VertexInputAttributeDescription::default(); attributes_count];
         vertex_attributes[0].format = vk::Format::R32G32B32_SFLOAT;
         vertex_attributes[1].location = 1;
         vertex_attributes[1].offset = 12;
@@ -337,11 +388,22 @@ impl Pipeline {
         vertex_attributes[3].location = 3;
         vertex_attributes[3].offset = 40;
         vertex_attributes[3].format = vk::Format::R32G32_SFLOAT;
+        if is_skinned {
+            // Joints are floats like the other attributes.
+            vertex_attributes[4].location = 4;
+            vertex_attributes[4].offset = 48;
+            vertex_attributes[4].format = vk::Format::R32G32B32A32_SFLOAT;
+            vertex_attributes[5].location = 5;
+            vertex_attributes[5].offset = 64;
+            vertex_attributes[5].format = vk::Format::R32G32B32A32_SFLOAT;
+        }
 
         let mut vertex_input_state = vk::PipelineVertexInputStateCreateInfo::default();
         match pipeline_type {
             PipelineType::GBuffer
             | PipelineType::ShadowMapper
+            | PipelineType::SkinnedGBuffer
+            | PipelineType::SkinnedShadowMapper
             | PipelineType::Unlit
             | PipelineType::TransparentPBR => {
                 vertex_input_state.vertex_binding_description_count = 1;
@@ -1,6 +1,8 @@
 #define MAX_DIRECTIONAL_CASCADES_COUNT 6
 #define MAX_DIRECTIONAL_LIGHTS_COUNT 8
 #define MAX_POINT_LIGHTS_COUNT 32
+#define MAX_SPOT_LIGHTS_COUNT 16
+#define MAX_JOINTS_COUNT 128
 #define MAX_SSAO_SAMPLES_COUNT 128
 #define BLUR_KERNEL_LENGTH 5
 #define SSAO_SAMPLES 32
@@ -33,11 +35,19 @@ struct DirectionalLight {
 	vec4 direction_strength;
 };
 
+struct SpotLight {
+	vec4 color_minradius;
+	vec4 position_radius;
+	vec4 direction_cone_scale;
+	vec4 cone_offset_reserved;
+};
+
 struct Scene {
 	Camera camera;
 	DirectionalLight directional_lights[MAX_DIRECTIONAL_LIGHTS_COUNT];
 	PointLight point_lights[MAX_POINT_LIGHTS_COUNT];
-	uvec4 lights_count; // directional, point
+	SpotLight spot_lights[MAX_SPOT_LIGHTS_COUNT];
+	uvec4 lights_count; // directional, point, spot
 	vec4 ssao_config; // samples-count, radius, z-tolerance, rezerved
 };
 
@@ -61,6 +71,12 @@ struct Material {
     float normal_scale;
     float occlusion_strength;
     float roughness_factor;
+    float emissive_strength;
+    float rezerved;
+    vec4 modes; // unlit, opaque, metallic channel, roughness channel
+    vec4 base_color_uv_transform[2];
+    vec4 metallic_roughness_uv_transform[2];
+    vec4 emissive_uv_transform[2];
 };
 
 struct Light {
@@ -74,6 +90,10 @@ struct ModelShadow {
 	mat4 model_view_projection;
 };
 
+struct Skin {
+	mat4 joints[MAX_JOINTS_COUNT];
+};
+
 float gausssian_blur_5x5(const sampler2D s, const vec2 uv, const vec2 pixel_step) {
 	const float ws[] = {
 		1.0 / 256.0,  4.0 / 256.0,  6.0 / 256.0,  4.0 / 256.0, 1.0 / 256.0,
@@ -93,6 +113,12 @@ float gausssian_blur_5x5(const sampler2D s, const vec2 uv, const vec2 pixel_step
 	return result;
 }
 
+// Rows of the uv matrix of KHR_texture_transform
+vec2 transform_uv(const vec4 t[2], const vec2 uv) {
+	const vec3 huv = vec3(uv, 1.0);
+	return vec2(dot(t[0].xyz, huv), dot(t[1].xyz, huv));
+}
+
 // Normal Distribution Function Trowbridge-Reitz GGX
 float NDFTRGGX(const vec3 normal, const vec3 halfway, const float roughness) {
     const float roughness2 = roughness * roughness;
@@ -89,6 +89,40 @@ vec3 calc_lights(const vec3 alb, const vec3 nrm, const vec3 eye, const vec3 pos,
         const vec3 specular = nom / max(denom, 0.001);
 		result += (kd * (alb / VX_PI) + specular) * radiance * smoothstep(0.005, 1.0, slope) * point_lights_shadowness[light_index];
 	}
+	// Spot lights
+	for(uint light_index = 0; light_index < scene_ubo.s.lights_count.z; ++light_index) {
+		const vec3 lm = scene_ubo.s.spot_lights[light_index].position_radius.xyz - pos;
+		float ll = length(lm);
+		if(ll > scene_ubo.s.spot_lights[light_index].position_radius.w) {
+			continue;
+		} else if(ll < scene_ubo.s.spot_lights[light_index].color_minradius.w) {
+			ll = scene_ubo.s.spot_lights[light_index].color_minradius.w;
+		}
+		const float ill = 1.0 / ll;
+		const vec3 l = lm * ill;
+		const float cone = clamp(
+			dot(-l, scene_ubo.s.spot_lights[light_index].direction_cone_scale.xyz) *
+			scene_ubo.s.spot_lights[light_index].direction_cone_scale.w +
+			scene_ubo.s.spot_lights[light_index].cone_offset_reserved.x, 0.0, 1.0);
+		if(cone <= 0.0) {
+			continue;
+		}
+		const float slope = dot(nrm, l);
+		if(slope < 0.005) {
+			continue;
+		}
+		const vec3 halfway = normalize(eye + l);
+		const float attenuation = ill * ill * cone * cone;
+		const vec3 radiance = scene_ubo.s.spot_lights[light_index].color_minradius.xyz * attenuation;
+		const float distribution = NDFTRGGX(nrm, halfway, roughness);
+		const float geometry = GFSCHGGX(nrm, eye, l, roughness);
+		const vec3 fresnel = FFSCHGGX(clamp(dot(halfway, eye), 0.0, 1.0), base_reflectivity);
+		const vec3 kd = (vec3(1.0) - fresnel) * (1.0 - metallic);
+		const vec3 nom = distribution * geometry * fresnel;
+		const float denom = 4 * max(dot(nrm, eye), 0.0) * max(dot(nrm, l), 0.0);
+        const vec3 specular = nom / max(denom, 0.001);
+		result += (kd * (alb / VX_PI) + specular) * radiance * smoothstep(0.005, 1.0, slope);
+	}
 	return result;
 }
 
@@ -100,6 +134,10 @@ void main() {
 	const vec4 nrm_txt = texture(normal, uv);
 	const vec3 nrm = nrm_txt.xyz;
 	const float metallic = nrm_txt.w;
+	if(metallic < 0.0) { // unlit
+		out_color = vec4(pow(alb.xyz, vec3(1.0 / 2.2)), alb.w);
+		return;
+	}
 	const vec3 eye = normalize(scene_ubo.s.camera.position_far.xyz - pos);
 	out_color.xyz = alb.xyz * 0.3; // todo it must come along scene
 	out_color.xyz += calc_lights(alb.xyz, nrm, eye, pos, roughness, metallic);
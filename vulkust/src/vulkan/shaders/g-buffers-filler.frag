@@ -30,15 +30,23 @@ layout (location = 1) out vec4 out_nrm;
 layout (location = 2) out vec4 out_alb;
 
 void main() {
-    vec4 alb = texture(base_color, uv) * texture(base_color_factor, uv);
-    vec2 metal_rough = texture(metallic_roughness, uv).xy * vec2(material_ubo.s.metallic_factor, material_ubo.s.roughness_factor);
+    const vec2 base_uv = transform_uv(material_ubo.s.base_color_uv_transform, uv);
+    vec4 alb = texture(base_color, base_uv) * texture(base_color_factor, base_uv);
+    const vec4 mr = texture(metallic_roughness, transform_uv(material_ubo.s.metallic_roughness_uv_transform, uv));
+    vec2 metal_rough = vec2(mr[int(material_ubo.s.modes.z)], mr[int(material_ubo.s.modes.w)]) * vec2(material_ubo.s.metallic_factor, material_ubo.s.roughness_factor);
     alb.w *= material_ubo.s.alpha;
     if(alb.w < material_ubo.s.alpha_cutoff) {
         discard;
     }
+    if(material_ubo.s.modes.y > 0.5) {
+        alb.w = 1.0;
+    }
     out_alb = alb;
     out_pos.xyz = pos;
     out_pos.w = metal_rough.y;
-    out_nrm.xyz = normalize(mat3(tng, btg, nrm) * ((texture(normal, uv).xyz - 0.5) * 2.0));
-    out_nrm.w = metal_rough.x;
+    const vec3 mapped_nrm = (texture(normal, base_uv).xyz - 0.5) * 2.0 * vec3(material_ubo.s.normal_scale, material_ubo.s.normal_scale, 1.0);
+    out_nrm.xyz = normalize(mat3(tng, btg, nrm) * mapped_nrm);
+    // Unlit fragments have negative metallic.
+    // todo emissive needs its own g-buffer target
+    out_nrm.w = material_ubo.s.modes.x > 0.5 ? -1.0 : metal_rough.x;
 }
\ No newline at end of file
@@ -20,7 +20,8 @@ layout (set = 1, binding = 6) uniform sampler2D emissive;
 layout (set = 1, binding = 7) uniform sampler2D emissive_factor;
 
 void main() {
-    vec4 alb = texture(base_color, uv) * texture(base_color_factor, uv);
+    const vec2 base_uv = transform_uv(material_ubo.s.base_color_uv_transform, uv);
+    vec4 alb = texture(base_color, base_uv) * texture(base_color_factor, base_uv);
     alb.w *= material_ubo.s.alpha;
     if(alb.w < material_ubo.s.alpha_cutoff) {
         discard;
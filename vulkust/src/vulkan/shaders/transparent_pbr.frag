@@ -75,23 +75,69 @@ vec3 calc_lights(const vec3 alb, const vec3 nrm, const vec3 eye, const vec3 pos,
         const vec3 specular = nom / max(denom, 0.001);
 		result += (kd * (alb / VX_PI) + specular) * radiance * smoothstep(0.005, 1.0, slope);
 	}
+	// Spot lights
+	for(uint light_index = 0; light_index < scene_ubo.s.lights_count.z; ++light_index) {
+		const vec3 lm = scene_ubo.s.spot_lights[light_index].position_radius.xyz - pos;
+		float ll = length(lm);
+		if(ll > scene_ubo.s.spot_lights[light_index].position_radius.w) {
+			continue;
+		} else if(ll < scene_ubo.s.spot_lights[light_index].color_minradius.w) {
+			ll = scene_ubo.s.spot_lights[light_index].color_minradius.w;
+		}
+		const float ill = 1.0 / ll;
+		const vec3 l = lm * ill;
+		const float cone = clamp(
+			dot(-l, scene_ubo.s.spot_lights[light_index].direction_cone_scale.xyz) *
+			scene_ubo.s.spot_lights[light_index].direction_cone_scale.w +
+			scene_ubo.s.spot_lights[light_index].cone_offset_reserved.x, 0.0, 1.0);
+		if(cone <= 0.0) {
+			continue;
+		}
+		const float slope = dot(nrm, l);
+		if(slope < 0.005) {
+			continue;
+		}
+		const vec3 halfway = normalize(eye + l);
+		const float attenuation = ill * ill * cone * cone;
+		const vec3 radiance = scene_ubo.s.spot_lights[light_index].color_minradius.xyz * attenuation;
+		const float distribution = NDFTRGGX(nrm, halfway, roughness);
+		const float geometry = GFSCHGGX(nrm, eye, l, roughness);
+		const vec3 fresnel = FFSCHGGX(clamp(dot(halfway, eye), 0.0, 1.0), base_reflectivity);
+		const vec3 kd = (vec3(1.0) - fresnel) * (1.0 - metallic);
+		const vec3 nom = distribution * geometry * fresnel;
+		const float denom = 4 * max(dot(nrm, eye), 0.0) * max(dot(nrm, l), 0.0);
+        const vec3 specular = nom / max(denom, 0.001);
+		result += (kd * (alb / VX_PI) + specular) * radiance * smoothstep(0.005, 1.0, slope);
+	}
 	return result;
 }
 
 void main() {
-    vec4 tmpv4 = texture(base_color, uv) * texture(base_color_factor, uv);
+    const vec2 base_uv = transform_uv(material_ubo.s.base_color_uv_transform, uv);
+    vec4 tmpv4 = texture(base_color, base_uv) * texture(base_color_factor, base_uv);
     tmpv4.w *= material_ubo.s.alpha;
     if(tmpv4.w < material_ubo.s.alpha_cutoff) {
         discard;
     }
+    if(material_ubo.s.modes.y > 0.5) {
+        tmpv4.w = 1.0;
+    }
     const vec4 alb = tmpv4;
-    tmpv4.xy = texture(metallic_roughness, uv).xy * vec2(material_ubo.s.metallic_factor, material_ubo.s.roughness_factor);
+    const vec2 emissive_uv = transform_uv(material_ubo.s.emissive_uv_transform, uv);
+    const vec3 emission = texture(emissive, emissive_uv).xyz * texture(emissive_factor, emissive_uv).xyz * material_ubo.s.emissive_strength;
+    if(material_ubo.s.modes.x > 0.5) {
+        out_color = vec4(pow(alb.xyz + emission, vec3(1.0 / 2.2)), alb.w);
+        return;
+    }
+    tmpv4 = texture(metallic_roughness, transform_uv(material_ubo.s.metallic_roughness_uv_transform, uv));
+    tmpv4.xy = vec2(tmpv4[int(material_ubo.s.modes.z)], tmpv4[int(material_ubo.s.modes.w)]) * vec2(material_ubo.s.metallic_factor, material_ubo.s.roughness_factor);
 	const float metallic = tmpv4.x;
 	const float roughness = tmpv4.y;
-    const vec3 mapped_nrm = normalize(mat3(tng, btg, nrm) * ((texture(normal, uv).xyz - 0.5) * 2.0));
+    const vec3 mapped_nrm = normalize(mat3(tng, btg, nrm) * ((texture(normal, base_uv).xyz - 0.5) * 2.0 * vec3(material_ubo.s.normal_scale, material_ubo.s.normal_scale, 1.0)));
 	const vec3 eye = normalize(scene_ubo.s.camera.position_far.xyz - pos);
 	out_color.xyz = alb.xyz * 0.3; // todo it must come along scene
 	out_color.xyz += calc_lights(alb.xyz, mapped_nrm, eye, pos, roughness, metallic);
+	out_color.xyz += emission;
 	out_color.xyz = pow(out_color.xyz / (out_color.xyz + vec3(1.0)), vec3(1.0 / 2.2)); // todo gamma must come along scene
 	out_color.w = alb.w;
 }
\ No newline at end of file
@@ -22,5 +22,6 @@ layout (set = 1, binding = 7) uniform sampler2D emissive_factor;
 layout (location = 0) out vec4 out_color;
 
 void main() {
-    out_color = texture(base_color, uv) * texture(base_color_factor, uv);
+    const vec2 base_uv = transform_uv(material_ubo.s.base_color_uv_transform, uv);
+    out_color = texture(base_color, base_uv) * texture(base_color_factor, base_uv);
 }
\ No newline at end of file
@@ -76,6 +76,24 @@ impl Surface {
     pub(super) fn new(instance: &Arc<Instance>, os_app: &Arc<RwLock<OsApp>>) -> Self {
         use ash::extensions::khr::XcbSurface;
         let os_app = vx_result!(os_app.read());
+        #[cfg(wayland)]
+        {
+            if let Some((display, surface)) = os_app.get_wayland_surface() {
+                use ash::extensions::khr::WaylandSurface;
+                let loader = WaylandSurface::new(instance.get_entry(), instance.get_data());
+                let create_info = vk::WaylandSurfaceCreateInfoKHR::builder()
+                    .display(display as *mut vk::wl_display)
+                    .surface(surface);
+                let vk_data =
+                    vx_result!(unsafe { loader.create_wayland_surface(&create_info, None) });
+                let loader = SurfaceLoader::new(instance.get_entry(), instance.get_data());
+                return Self {
+                    instance: instance.clone(),
+                    vk_data,
+                    loader,
+                };
+            }
+        }
         let loader = XcbSurface::new(instance.get_entry(), instance.get_data());
         let create_info = vk::XcbSurfaceCreateInfoKHR::builder()
             .window(os_app.get_window())
@@ -14,7 +14,7 @@ pub(crate) enum NextImageResult {
 
 pub(crate) struct Swapchain {
     logical_device: Arc<LogicalDevice>,
-    _surface_format: vk::SurfaceFormatKHR,
+    surface_format: vk::SurfaceFormatKHR,
     image_views: Vec<Arc<ImageView>>,
     vk_data: vk::SwapchainKHR,
     loader: SwapchainLoader,
@@ -136,7 +136,7 @@ impl Swapchain {
         vx_log_i!("Swapchain created.");
         Self {
             logical_device: logical_device.clone(),
-            _surface_format: best_surface_format,
+            surface_format: best_surface_format,
             image_views,
             vk_data,
             loader,
@@ -162,6 +162,10 @@ impl Swapchain {
         return &self.image_views;
     }
 
+    pub(crate) fn is_bgra(&self) -> bool {
+        return self.surface_format.format == vk::Format::B8G8R8A8_UNORM;
+    }
+
     pub(crate) fn get_logical_device(&self) -> &Arc<LogicalDevice> {
         return &self.logical_device;
     }